@@ -0,0 +1,37 @@
+-- Migration: 011_follows_release_calendar
+-- 关注（系列/厂商/演员）和即将发售日历
+
+-- 关注表
+CREATE TABLE IF NOT EXISTS follows (
+    id TEXT PRIMARY KEY NOT NULL,
+    target_type TEXT NOT NULL CHECK(target_type IN ('series', 'studio', 'actor')),
+    target_name TEXT NOT NULL CHECK(length(target_name) > 0),
+    plugin_id TEXT,  -- 为空时使用默认刮削插件
+    last_checked_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(target_type, target_name)
+);
+
+-- 发售日历表（由定时任务从插件拉取）
+CREATE TABLE IF NOT EXISTS release_calendar (
+    id TEXT PRIMARY KEY NOT NULL,
+    follow_id TEXT NOT NULL,
+    code TEXT,
+    title TEXT NOT NULL,
+    release_date TEXT NOT NULL,  -- YYYY-MM-DD
+    poster_url TEXT,
+    studio TEXT,
+    series TEXT,
+    actors TEXT,  -- JSON array
+    plugin_id TEXT NOT NULL,
+    media_id TEXT,  -- 已入库时关联的媒体
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (follow_id) REFERENCES follows(id) ON DELETE CASCADE,
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE SET NULL,
+    UNIQUE(follow_id, title, release_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_release_calendar_date ON release_calendar(release_date);
+CREATE INDEX IF NOT EXISTS idx_release_calendar_follow ON release_calendar(follow_id);
+CREATE INDEX IF NOT EXISTS idx_release_calendar_code ON release_calendar(code);
@@ -0,0 +1,21 @@
+-- Migration: 012_wanted_media
+-- 想要列表：对没有本地文件的媒体定时搜索磁力链接
+
+CREATE TABLE IF NOT EXISTS wanted_media (
+    media_id TEXT PRIMARY KEY NOT NULL,
+    status TEXT NOT NULL DEFAULT 'monitoring' CHECK(status IN ('monitoring', 'found', 'pushed', 'paused')),
+    action TEXT NOT NULL DEFAULT 'notify' CHECK(action IN ('notify', 'auto_push')),
+    quality_filter TEXT,  -- JSON: MagnetQualityFilter
+    plugin_id TEXT,  -- 为空时使用默认磁力插件
+    search_query TEXT,  -- 为空时使用番号或标题
+    found_title TEXT,
+    found_magnet TEXT,
+    found_size TEXT,
+    found_at TEXT,
+    last_searched_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_wanted_media_status ON wanted_media(status);
@@ -0,0 +1,21 @@
+-- Migration: 013_quality_profiles_upgrades
+-- 质量配置（存储于 user_settings）和可升级媒体记录
+
+-- 默认质量配置：最低 1080p，期望 2160p
+INSERT OR IGNORE INTO user_settings (key, value, description) VALUES
+    ('quality_profiles', '[{"name":"Default","is_default":true,"min_resolution":1080,"preferred_resolution":2160,"preferred_codecs":[]}]', 'Quality profiles used by magnet monitoring and upgrade detection (JSON array)');
+
+-- 可升级媒体表（磁力监控或扫描发现了比本地文件更高质量的资源）
+CREATE TABLE IF NOT EXISTS media_upgrades (
+    media_id TEXT PRIMARY KEY NOT NULL,
+    current_quality TEXT,
+    available_quality TEXT NOT NULL,
+    source TEXT NOT NULL CHECK(source IN ('magnet', 'scan')),
+    source_title TEXT NOT NULL,
+    magnet_link TEXT,
+    file_path TEXT,
+    detected_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_upgrades_detected_at ON media_upgrades(detected_at DESC);
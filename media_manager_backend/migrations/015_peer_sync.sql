@@ -0,0 +1,624 @@
+-- Migration: 015_peer_sync
+-- 多实例双向同步：变更记录来源，远端变更应用时保留原始时间戳，避免回环
+
+-- 变更来源实例ID（本地变更为空）
+ALTER TABLE changes ADD COLUMN origin TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_changes_origin ON changes(origin);
+
+-- 应用远端变更时的上下文（仅在同步事务内存在一行），触发器据此记录来源和原始时间
+CREATE TABLE IF NOT EXISTS sync_apply_context (
+    origin TEXT NOT NULL,
+    changed_at TEXT NOT NULL
+);
+
+-- 同步对端
+CREATE TABLE IF NOT EXISTS sync_peers (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL CHECK(length(name) > 0),
+    base_url TEXT NOT NULL UNIQUE,
+    token TEXT,  -- 对端要求的同步令牌（X-Sync-Token）
+    remote_instance_id TEXT,
+    last_pulled_seq INTEGER NOT NULL DEFAULT 0,  -- 已拉取到的对端序列号
+    last_pushed_seq INTEGER NOT NULL DEFAULT 0,  -- 已推送到的本地序列号
+    last_synced_at TEXT,
+    last_error TEXT,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- 重建变更流触发器：写入来源和原始时间
+
+-- media_items
+DROP TRIGGER IF EXISTS changes_media_items_insert;
+DROP TRIGGER IF EXISTS changes_media_items_update;
+DROP TRIGGER IF EXISTS changes_media_items_delete;
+
+CREATE TRIGGER changes_media_items_insert
+    AFTER INSERT ON media_items
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('media', NEW.id, 'insert', json_object(
+        'id', NEW.id,
+        'external_ids', NEW.external_ids,
+        'title', NEW.title,
+        'original_title', NEW.original_title,
+        'code', NEW.code,
+        'year', NEW.year,
+        'media_type', NEW.media_type,
+        'genres', NEW.genres,
+        'rating', NEW.rating,
+        'vote_count', NEW.vote_count,
+        'poster_url', NEW.poster_url,
+        'overview', NEW.overview,
+        'runtime', NEW.runtime,
+        'release_date', NEW.release_date,
+        'cast', NEW.cast,
+        'crew', NEW.crew,
+        'language', NEW.language,
+        'country', NEW.country,
+        'budget', NEW.budget,
+        'revenue', NEW.revenue,
+        'status', NEW.status,
+        'play_links', NEW.play_links,
+        'download_links', NEW.download_links,
+        'preview_urls', NEW.preview_urls,
+        'preview_video_urls', NEW.preview_video_urls,
+        'studio', NEW.studio,
+        'series', NEW.series,
+        'created_at', NEW.created_at,
+        'updated_at', NEW.updated_at,
+        'local_file_path', NEW.local_file_path,
+        'file_size', NEW.file_size,
+        'last_scanned_at', NEW.last_scanned_at,
+        'is_local_only', NEW.is_local_only,
+        'cover_video_url', NEW.cover_video_url,
+        'backdrop_url', NEW.backdrop_url,
+        'scraper_name', NEW.scraper_name
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_media_items_update
+    AFTER UPDATE ON media_items
+    FOR EACH ROW
+    WHEN OLD.id IS NOT NEW.id OR OLD.external_ids IS NOT NEW.external_ids OR OLD.title IS NOT NEW.title OR OLD.original_title IS NOT NEW.original_title OR OLD.code IS NOT NEW.code OR OLD.year IS NOT NEW.year OR OLD.media_type IS NOT NEW.media_type OR OLD.genres IS NOT NEW.genres OR OLD.rating IS NOT NEW.rating OR OLD.vote_count IS NOT NEW.vote_count OR OLD.poster_url IS NOT NEW.poster_url OR OLD.overview IS NOT NEW.overview OR OLD.runtime IS NOT NEW.runtime OR OLD.release_date IS NOT NEW.release_date OR OLD.cast IS NOT NEW.cast OR OLD.crew IS NOT NEW.crew OR OLD.language IS NOT NEW.language OR OLD.country IS NOT NEW.country OR OLD.budget IS NOT NEW.budget OR OLD.revenue IS NOT NEW.revenue OR OLD.status IS NOT NEW.status OR OLD.play_links IS NOT NEW.play_links OR OLD.download_links IS NOT NEW.download_links OR OLD.preview_urls IS NOT NEW.preview_urls OR OLD.preview_video_urls IS NOT NEW.preview_video_urls OR OLD.studio IS NOT NEW.studio OR OLD.series IS NOT NEW.series OR OLD.created_at IS NOT NEW.created_at OR OLD.local_file_path IS NOT NEW.local_file_path OR OLD.file_size IS NOT NEW.file_size OR OLD.last_scanned_at IS NOT NEW.last_scanned_at OR OLD.is_local_only IS NOT NEW.is_local_only OR OLD.cover_video_url IS NOT NEW.cover_video_url OR OLD.backdrop_url IS NOT NEW.backdrop_url OR OLD.scraper_name IS NOT NEW.scraper_name
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('media', NEW.id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'id' AS field WHERE OLD.id IS NOT NEW.id
+            UNION ALL SELECT 'external_ids' WHERE OLD.external_ids IS NOT NEW.external_ids
+            UNION ALL SELECT 'title' WHERE OLD.title IS NOT NEW.title
+            UNION ALL SELECT 'original_title' WHERE OLD.original_title IS NOT NEW.original_title
+            UNION ALL SELECT 'code' WHERE OLD.code IS NOT NEW.code
+            UNION ALL SELECT 'year' WHERE OLD.year IS NOT NEW.year
+            UNION ALL SELECT 'media_type' WHERE OLD.media_type IS NOT NEW.media_type
+            UNION ALL SELECT 'genres' WHERE OLD.genres IS NOT NEW.genres
+            UNION ALL SELECT 'rating' WHERE OLD.rating IS NOT NEW.rating
+            UNION ALL SELECT 'vote_count' WHERE OLD.vote_count IS NOT NEW.vote_count
+            UNION ALL SELECT 'poster_url' WHERE OLD.poster_url IS NOT NEW.poster_url
+            UNION ALL SELECT 'overview' WHERE OLD.overview IS NOT NEW.overview
+            UNION ALL SELECT 'runtime' WHERE OLD.runtime IS NOT NEW.runtime
+            UNION ALL SELECT 'release_date' WHERE OLD.release_date IS NOT NEW.release_date
+            UNION ALL SELECT 'cast' WHERE OLD.cast IS NOT NEW.cast
+            UNION ALL SELECT 'crew' WHERE OLD.crew IS NOT NEW.crew
+            UNION ALL SELECT 'language' WHERE OLD.language IS NOT NEW.language
+            UNION ALL SELECT 'country' WHERE OLD.country IS NOT NEW.country
+            UNION ALL SELECT 'budget' WHERE OLD.budget IS NOT NEW.budget
+            UNION ALL SELECT 'revenue' WHERE OLD.revenue IS NOT NEW.revenue
+            UNION ALL SELECT 'status' WHERE OLD.status IS NOT NEW.status
+            UNION ALL SELECT 'play_links' WHERE OLD.play_links IS NOT NEW.play_links
+            UNION ALL SELECT 'download_links' WHERE OLD.download_links IS NOT NEW.download_links
+            UNION ALL SELECT 'preview_urls' WHERE OLD.preview_urls IS NOT NEW.preview_urls
+            UNION ALL SELECT 'preview_video_urls' WHERE OLD.preview_video_urls IS NOT NEW.preview_video_urls
+            UNION ALL SELECT 'studio' WHERE OLD.studio IS NOT NEW.studio
+            UNION ALL SELECT 'series' WHERE OLD.series IS NOT NEW.series
+            UNION ALL SELECT 'created_at' WHERE OLD.created_at IS NOT NEW.created_at
+            UNION ALL SELECT 'local_file_path' WHERE OLD.local_file_path IS NOT NEW.local_file_path
+            UNION ALL SELECT 'file_size' WHERE OLD.file_size IS NOT NEW.file_size
+            UNION ALL SELECT 'last_scanned_at' WHERE OLD.last_scanned_at IS NOT NEW.last_scanned_at
+            UNION ALL SELECT 'is_local_only' WHERE OLD.is_local_only IS NOT NEW.is_local_only
+            UNION ALL SELECT 'cover_video_url' WHERE OLD.cover_video_url IS NOT NEW.cover_video_url
+            UNION ALL SELECT 'backdrop_url' WHERE OLD.backdrop_url IS NOT NEW.backdrop_url
+            UNION ALL SELECT 'scraper_name' WHERE OLD.scraper_name IS NOT NEW.scraper_name
+        )),
+        json_object(
+        'id', NEW.id,
+        'external_ids', NEW.external_ids,
+        'title', NEW.title,
+        'original_title', NEW.original_title,
+        'code', NEW.code,
+        'year', NEW.year,
+        'media_type', NEW.media_type,
+        'genres', NEW.genres,
+        'rating', NEW.rating,
+        'vote_count', NEW.vote_count,
+        'poster_url', NEW.poster_url,
+        'overview', NEW.overview,
+        'runtime', NEW.runtime,
+        'release_date', NEW.release_date,
+        'cast', NEW.cast,
+        'crew', NEW.crew,
+        'language', NEW.language,
+        'country', NEW.country,
+        'budget', NEW.budget,
+        'revenue', NEW.revenue,
+        'status', NEW.status,
+        'play_links', NEW.play_links,
+        'download_links', NEW.download_links,
+        'preview_urls', NEW.preview_urls,
+        'preview_video_urls', NEW.preview_video_urls,
+        'studio', NEW.studio,
+        'series', NEW.series,
+        'created_at', NEW.created_at,
+        'updated_at', NEW.updated_at,
+        'local_file_path', NEW.local_file_path,
+        'file_size', NEW.file_size,
+        'last_scanned_at', NEW.last_scanned_at,
+        'is_local_only', NEW.is_local_only,
+        'cover_video_url', NEW.cover_video_url,
+        'backdrop_url', NEW.backdrop_url,
+        'scraper_name', NEW.scraper_name
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_media_items_delete
+    AFTER DELETE ON media_items
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('media', OLD.id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+-- collections
+DROP TRIGGER IF EXISTS changes_collections_insert;
+DROP TRIGGER IF EXISTS changes_collections_update;
+DROP TRIGGER IF EXISTS changes_collections_delete;
+
+CREATE TRIGGER changes_collections_insert
+    AFTER INSERT ON collections
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('collection', NEW.id, 'insert', json_object(
+        'id', NEW.id,
+        'media_id', NEW.media_id,
+        'user_tags', NEW.user_tags,
+        'personal_rating', NEW.personal_rating,
+        'watch_status', NEW.watch_status,
+        'watch_progress', NEW.watch_progress,
+        'notes', NEW.notes,
+        'is_favorite', NEW.is_favorite,
+        'added_at', NEW.added_at,
+        'last_watched', NEW.last_watched,
+        'completed_at', NEW.completed_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_collections_update
+    AFTER UPDATE ON collections
+    FOR EACH ROW
+    WHEN OLD.id IS NOT NEW.id OR OLD.media_id IS NOT NEW.media_id OR OLD.user_tags IS NOT NEW.user_tags OR OLD.personal_rating IS NOT NEW.personal_rating OR OLD.watch_status IS NOT NEW.watch_status OR OLD.watch_progress IS NOT NEW.watch_progress OR OLD.notes IS NOT NEW.notes OR OLD.is_favorite IS NOT NEW.is_favorite OR OLD.added_at IS NOT NEW.added_at OR OLD.last_watched IS NOT NEW.last_watched OR OLD.completed_at IS NOT NEW.completed_at
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('collection', NEW.id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'id' AS field WHERE OLD.id IS NOT NEW.id
+            UNION ALL SELECT 'media_id' WHERE OLD.media_id IS NOT NEW.media_id
+            UNION ALL SELECT 'user_tags' WHERE OLD.user_tags IS NOT NEW.user_tags
+            UNION ALL SELECT 'personal_rating' WHERE OLD.personal_rating IS NOT NEW.personal_rating
+            UNION ALL SELECT 'watch_status' WHERE OLD.watch_status IS NOT NEW.watch_status
+            UNION ALL SELECT 'watch_progress' WHERE OLD.watch_progress IS NOT NEW.watch_progress
+            UNION ALL SELECT 'notes' WHERE OLD.notes IS NOT NEW.notes
+            UNION ALL SELECT 'is_favorite' WHERE OLD.is_favorite IS NOT NEW.is_favorite
+            UNION ALL SELECT 'added_at' WHERE OLD.added_at IS NOT NEW.added_at
+            UNION ALL SELECT 'last_watched' WHERE OLD.last_watched IS NOT NEW.last_watched
+            UNION ALL SELECT 'completed_at' WHERE OLD.completed_at IS NOT NEW.completed_at
+        )),
+        json_object(
+        'id', NEW.id,
+        'media_id', NEW.media_id,
+        'user_tags', NEW.user_tags,
+        'personal_rating', NEW.personal_rating,
+        'watch_status', NEW.watch_status,
+        'watch_progress', NEW.watch_progress,
+        'notes', NEW.notes,
+        'is_favorite', NEW.is_favorite,
+        'added_at', NEW.added_at,
+        'last_watched', NEW.last_watched,
+        'completed_at', NEW.completed_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_collections_delete
+    AFTER DELETE ON collections
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('collection', OLD.id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+-- actors
+DROP TRIGGER IF EXISTS changes_actors_insert;
+DROP TRIGGER IF EXISTS changes_actors_update;
+DROP TRIGGER IF EXISTS changes_actors_delete;
+
+CREATE TRIGGER changes_actors_insert
+    AFTER INSERT ON actors
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('actor', NEW.id, 'insert', json_object(
+        'id', NEW.id,
+        'name', NEW.name,
+        'photo_url', NEW.photo_url,
+        'biography', NEW.biography,
+        'birth_date', NEW.birth_date,
+        'nationality', NEW.nationality,
+        'created_at', NEW.created_at,
+        'updated_at', NEW.updated_at,
+        'backdrop_url', NEW.backdrop_url,
+        'avatar_url', NEW.avatar_url,
+        'poster_url', NEW.poster_url
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_actors_update
+    AFTER UPDATE ON actors
+    FOR EACH ROW
+    WHEN OLD.id IS NOT NEW.id OR OLD.name IS NOT NEW.name OR OLD.photo_url IS NOT NEW.photo_url OR OLD.biography IS NOT NEW.biography OR OLD.birth_date IS NOT NEW.birth_date OR OLD.nationality IS NOT NEW.nationality OR OLD.created_at IS NOT NEW.created_at OR OLD.backdrop_url IS NOT NEW.backdrop_url OR OLD.avatar_url IS NOT NEW.avatar_url OR OLD.poster_url IS NOT NEW.poster_url
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('actor', NEW.id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'id' AS field WHERE OLD.id IS NOT NEW.id
+            UNION ALL SELECT 'name' WHERE OLD.name IS NOT NEW.name
+            UNION ALL SELECT 'photo_url' WHERE OLD.photo_url IS NOT NEW.photo_url
+            UNION ALL SELECT 'biography' WHERE OLD.biography IS NOT NEW.biography
+            UNION ALL SELECT 'birth_date' WHERE OLD.birth_date IS NOT NEW.birth_date
+            UNION ALL SELECT 'nationality' WHERE OLD.nationality IS NOT NEW.nationality
+            UNION ALL SELECT 'created_at' WHERE OLD.created_at IS NOT NEW.created_at
+            UNION ALL SELECT 'backdrop_url' WHERE OLD.backdrop_url IS NOT NEW.backdrop_url
+            UNION ALL SELECT 'avatar_url' WHERE OLD.avatar_url IS NOT NEW.avatar_url
+            UNION ALL SELECT 'poster_url' WHERE OLD.poster_url IS NOT NEW.poster_url
+        )),
+        json_object(
+        'id', NEW.id,
+        'name', NEW.name,
+        'photo_url', NEW.photo_url,
+        'biography', NEW.biography,
+        'birth_date', NEW.birth_date,
+        'nationality', NEW.nationality,
+        'created_at', NEW.created_at,
+        'updated_at', NEW.updated_at,
+        'backdrop_url', NEW.backdrop_url,
+        'avatar_url', NEW.avatar_url,
+        'poster_url', NEW.poster_url
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_actors_delete
+    AFTER DELETE ON actors
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('actor', OLD.id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+-- actor_media
+DROP TRIGGER IF EXISTS changes_actor_media_insert;
+DROP TRIGGER IF EXISTS changes_actor_media_update;
+DROP TRIGGER IF EXISTS changes_actor_media_delete;
+
+CREATE TRIGGER changes_actor_media_insert
+    AFTER INSERT ON actor_media
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('actor_media', NEW.id, 'insert', json_object(
+        'id', NEW.id,
+        'actor_id', NEW.actor_id,
+        'media_id', NEW.media_id,
+        'character_name', NEW.character_name,
+        'role', NEW.role,
+        'created_at', NEW.created_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_actor_media_update
+    AFTER UPDATE ON actor_media
+    FOR EACH ROW
+    WHEN OLD.id IS NOT NEW.id OR OLD.actor_id IS NOT NEW.actor_id OR OLD.media_id IS NOT NEW.media_id OR OLD.character_name IS NOT NEW.character_name OR OLD.role IS NOT NEW.role OR OLD.created_at IS NOT NEW.created_at
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('actor_media', NEW.id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'id' AS field WHERE OLD.id IS NOT NEW.id
+            UNION ALL SELECT 'actor_id' WHERE OLD.actor_id IS NOT NEW.actor_id
+            UNION ALL SELECT 'media_id' WHERE OLD.media_id IS NOT NEW.media_id
+            UNION ALL SELECT 'character_name' WHERE OLD.character_name IS NOT NEW.character_name
+            UNION ALL SELECT 'role' WHERE OLD.role IS NOT NEW.role
+            UNION ALL SELECT 'created_at' WHERE OLD.created_at IS NOT NEW.created_at
+        )),
+        json_object(
+        'id', NEW.id,
+        'actor_id', NEW.actor_id,
+        'media_id', NEW.media_id,
+        'character_name', NEW.character_name,
+        'role', NEW.role,
+        'created_at', NEW.created_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_actor_media_delete
+    AFTER DELETE ON actor_media
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('actor_media', OLD.id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+-- studios
+DROP TRIGGER IF EXISTS changes_studios_insert;
+DROP TRIGGER IF EXISTS changes_studios_update;
+DROP TRIGGER IF EXISTS changes_studios_delete;
+
+CREATE TRIGGER changes_studios_insert
+    AFTER INSERT ON studios
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('studio', NEW.id, 'insert', json_object(
+        'id', NEW.id,
+        'name', NEW.name,
+        'logo_url', NEW.logo_url,
+        'description', NEW.description,
+        'media_count', NEW.media_count,
+        'created_at', NEW.created_at,
+        'updated_at', NEW.updated_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_studios_update
+    AFTER UPDATE ON studios
+    FOR EACH ROW
+    WHEN OLD.id IS NOT NEW.id OR OLD.name IS NOT NEW.name OR OLD.logo_url IS NOT NEW.logo_url OR OLD.description IS NOT NEW.description OR OLD.created_at IS NOT NEW.created_at
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('studio', NEW.id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'id' AS field WHERE OLD.id IS NOT NEW.id
+            UNION ALL SELECT 'name' WHERE OLD.name IS NOT NEW.name
+            UNION ALL SELECT 'logo_url' WHERE OLD.logo_url IS NOT NEW.logo_url
+            UNION ALL SELECT 'description' WHERE OLD.description IS NOT NEW.description
+            UNION ALL SELECT 'created_at' WHERE OLD.created_at IS NOT NEW.created_at
+        )),
+        json_object(
+        'id', NEW.id,
+        'name', NEW.name,
+        'logo_url', NEW.logo_url,
+        'description', NEW.description,
+        'media_count', NEW.media_count,
+        'created_at', NEW.created_at,
+        'updated_at', NEW.updated_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_studios_delete
+    AFTER DELETE ON studios
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('studio', OLD.id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+-- series
+DROP TRIGGER IF EXISTS changes_series_insert;
+DROP TRIGGER IF EXISTS changes_series_update;
+DROP TRIGGER IF EXISTS changes_series_delete;
+
+CREATE TRIGGER changes_series_insert
+    AFTER INSERT ON series
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('series', NEW.id, 'insert', json_object(
+        'id', NEW.id,
+        'name', NEW.name,
+        'studio_id', NEW.studio_id,
+        'description', NEW.description,
+        'cover_url', NEW.cover_url,
+        'media_count', NEW.media_count,
+        'created_at', NEW.created_at,
+        'updated_at', NEW.updated_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_series_update
+    AFTER UPDATE ON series
+    FOR EACH ROW
+    WHEN OLD.id IS NOT NEW.id OR OLD.name IS NOT NEW.name OR OLD.studio_id IS NOT NEW.studio_id OR OLD.description IS NOT NEW.description OR OLD.cover_url IS NOT NEW.cover_url OR OLD.created_at IS NOT NEW.created_at
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('series', NEW.id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'id' AS field WHERE OLD.id IS NOT NEW.id
+            UNION ALL SELECT 'name' WHERE OLD.name IS NOT NEW.name
+            UNION ALL SELECT 'studio_id' WHERE OLD.studio_id IS NOT NEW.studio_id
+            UNION ALL SELECT 'description' WHERE OLD.description IS NOT NEW.description
+            UNION ALL SELECT 'cover_url' WHERE OLD.cover_url IS NOT NEW.cover_url
+            UNION ALL SELECT 'created_at' WHERE OLD.created_at IS NOT NEW.created_at
+        )),
+        json_object(
+        'id', NEW.id,
+        'name', NEW.name,
+        'studio_id', NEW.studio_id,
+        'description', NEW.description,
+        'cover_url', NEW.cover_url,
+        'media_count', NEW.media_count,
+        'created_at', NEW.created_at,
+        'updated_at', NEW.updated_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_series_delete
+    AFTER DELETE ON series
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('series', OLD.id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+-- media_files
+DROP TRIGGER IF EXISTS changes_media_files_insert;
+DROP TRIGGER IF EXISTS changes_media_files_update;
+DROP TRIGGER IF EXISTS changes_media_files_delete;
+
+CREATE TRIGGER changes_media_files_insert
+    AFTER INSERT ON media_files
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('media_file', NEW.id, 'insert', json_object(
+        'id', NEW.id,
+        'media_id', NEW.media_id,
+        'file_path', NEW.file_path,
+        'file_size', NEW.file_size,
+        'part_number', NEW.part_number,
+        'part_label', NEW.part_label,
+        'created_at', NEW.created_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_media_files_update
+    AFTER UPDATE ON media_files
+    FOR EACH ROW
+    WHEN OLD.id IS NOT NEW.id OR OLD.media_id IS NOT NEW.media_id OR OLD.file_path IS NOT NEW.file_path OR OLD.file_size IS NOT NEW.file_size OR OLD.part_number IS NOT NEW.part_number OR OLD.part_label IS NOT NEW.part_label OR OLD.created_at IS NOT NEW.created_at
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('media_file', NEW.id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'id' AS field WHERE OLD.id IS NOT NEW.id
+            UNION ALL SELECT 'media_id' WHERE OLD.media_id IS NOT NEW.media_id
+            UNION ALL SELECT 'file_path' WHERE OLD.file_path IS NOT NEW.file_path
+            UNION ALL SELECT 'file_size' WHERE OLD.file_size IS NOT NEW.file_size
+            UNION ALL SELECT 'part_number' WHERE OLD.part_number IS NOT NEW.part_number
+            UNION ALL SELECT 'part_label' WHERE OLD.part_label IS NOT NEW.part_label
+            UNION ALL SELECT 'created_at' WHERE OLD.created_at IS NOT NEW.created_at
+        )),
+        json_object(
+        'id', NEW.id,
+        'media_id', NEW.media_id,
+        'file_path', NEW.file_path,
+        'file_size', NEW.file_size,
+        'part_number', NEW.part_number,
+        'part_label', NEW.part_label,
+        'created_at', NEW.created_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_media_files_delete
+    AFTER DELETE ON media_files
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('media_file', OLD.id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+-- tags
+DROP TRIGGER IF EXISTS changes_tags_insert;
+DROP TRIGGER IF EXISTS changes_tags_update;
+DROP TRIGGER IF EXISTS changes_tags_delete;
+
+CREATE TRIGGER changes_tags_insert
+    AFTER INSERT ON tags
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('tag', NEW.id, 'insert', json_object(
+        'id', NEW.id,
+        'name', NEW.name,
+        'color', NEW.color,
+        'description', NEW.description,
+        'usage_count', NEW.usage_count,
+        'created_at', NEW.created_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_tags_update
+    AFTER UPDATE ON tags
+    FOR EACH ROW
+    WHEN OLD.id IS NOT NEW.id OR OLD.name IS NOT NEW.name OR OLD.color IS NOT NEW.color OR OLD.description IS NOT NEW.description OR OLD.created_at IS NOT NEW.created_at
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('tag', NEW.id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'id' AS field WHERE OLD.id IS NOT NEW.id
+            UNION ALL SELECT 'name' WHERE OLD.name IS NOT NEW.name
+            UNION ALL SELECT 'color' WHERE OLD.color IS NOT NEW.color
+            UNION ALL SELECT 'description' WHERE OLD.description IS NOT NEW.description
+            UNION ALL SELECT 'created_at' WHERE OLD.created_at IS NOT NEW.created_at
+        )),
+        json_object(
+        'id', NEW.id,
+        'name', NEW.name,
+        'color', NEW.color,
+        'description', NEW.description,
+        'usage_count', NEW.usage_count,
+        'created_at', NEW.created_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_tags_delete
+    AFTER DELETE ON tags
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('tag', OLD.id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+-- media_tags
+DROP TRIGGER IF EXISTS changes_media_tags_insert;
+DROP TRIGGER IF EXISTS changes_media_tags_update;
+DROP TRIGGER IF EXISTS changes_media_tags_delete;
+
+CREATE TRIGGER changes_media_tags_insert
+    AFTER INSERT ON media_tags
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('media_tag', NEW.media_id || ':' || NEW.tag_id, 'insert', json_object(
+        'media_id', NEW.media_id,
+        'tag_id', NEW.tag_id,
+        'created_at', NEW.created_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_media_tags_update
+    AFTER UPDATE ON media_tags
+    FOR EACH ROW
+    WHEN OLD.media_id IS NOT NEW.media_id OR OLD.tag_id IS NOT NEW.tag_id OR OLD.created_at IS NOT NEW.created_at
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('media_tag', NEW.media_id || ':' || NEW.tag_id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'media_id' AS field WHERE OLD.media_id IS NOT NEW.media_id
+            UNION ALL SELECT 'tag_id' WHERE OLD.tag_id IS NOT NEW.tag_id
+            UNION ALL SELECT 'created_at' WHERE OLD.created_at IS NOT NEW.created_at
+        )),
+        json_object(
+        'media_id', NEW.media_id,
+        'tag_id', NEW.tag_id,
+        'created_at', NEW.created_at
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_media_tags_delete
+    AFTER DELETE ON media_tags
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('media_tag', OLD.media_id || ':' || OLD.tag_id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
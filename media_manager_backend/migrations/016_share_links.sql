@@ -0,0 +1,15 @@
+-- Migration: 016_share_links
+-- 只读公开分享链接：签名令牌 + 过期时间，可撤销，记录访问次数
+
+CREATE TABLE IF NOT EXISTS share_links (
+    id TEXT PRIMARY KEY NOT NULL,
+    media_id TEXT NOT NULL,
+    expires_at DATETIME NOT NULL,
+    revoked_at DATETIME,
+    access_count INTEGER NOT NULL DEFAULT 0,
+    last_accessed_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_share_links_media ON share_links(media_id);
@@ -0,0 +1,32 @@
+-- Migration: 017_scan_sessions
+-- 持久化扫描结果：扫描会话 + 每个扫描到的文件及其匹配状态，支持跨会话继续匹配
+
+CREATE TABLE IF NOT EXISTS scan_sessions (
+    id TEXT PRIMARY KEY NOT NULL,
+    paths TEXT NOT NULL DEFAULT '[]',            -- 扫描路径（JSON 数组）
+    recursive INTEGER NOT NULL DEFAULT 1,
+    total_files INTEGER NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS scan_files (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    file_name TEXT NOT NULL,
+    file_size INTEGER NOT NULL DEFAULT 0,
+    parsed_code TEXT,
+    parsed_title TEXT,
+    parsed_year INTEGER,
+    parsed_series TEXT,
+    parsed_date TEXT,
+    status TEXT NOT NULL DEFAULT 'unmatched',   -- unmatched / matched / ignored
+    media_id TEXT,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (session_id, file_path),
+    FOREIGN KEY (session_id) REFERENCES scan_sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_scan_files_session_status ON scan_files(session_id, status);
+CREATE INDEX IF NOT EXISTS idx_scan_files_path ON scan_files(file_path);
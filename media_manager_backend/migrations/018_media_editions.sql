@@ -0,0 +1,65 @@
+-- Migration: 018_media_editions
+-- 版本支持：同一媒体可拥有多组文件（导演剪辑版、4K 修复版等），edition 为空表示默认版本
+
+ALTER TABLE media_files ADD COLUMN edition TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_media_files_edition ON media_files(media_id, edition);
+
+-- 重新生成 media_files 变更触发器（包含 edition 字段）
+DROP TRIGGER IF EXISTS changes_media_files_insert;
+DROP TRIGGER IF EXISTS changes_media_files_update;
+DROP TRIGGER IF EXISTS changes_media_files_delete;
+
+CREATE TRIGGER changes_media_files_insert
+    AFTER INSERT ON media_files
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('media_file', NEW.id, 'insert', json_object(
+        'id', NEW.id,
+        'media_id', NEW.media_id,
+        'file_path', NEW.file_path,
+        'file_size', NEW.file_size,
+        'part_number', NEW.part_number,
+        'part_label', NEW.part_label,
+        'created_at', NEW.created_at,
+        'edition', NEW.edition
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_media_files_update
+    AFTER UPDATE ON media_files
+    FOR EACH ROW
+    WHEN OLD.id IS NOT NEW.id OR OLD.media_id IS NOT NEW.media_id OR OLD.file_path IS NOT NEW.file_path OR OLD.file_size IS NOT NEW.file_size OR OLD.part_number IS NOT NEW.part_number OR OLD.part_label IS NOT NEW.part_label OR OLD.created_at IS NOT NEW.created_at OR OLD.edition IS NOT NEW.edition
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('media_file', NEW.id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'id' AS field WHERE OLD.id IS NOT NEW.id
+            UNION ALL SELECT 'media_id' WHERE OLD.media_id IS NOT NEW.media_id
+            UNION ALL SELECT 'file_path' WHERE OLD.file_path IS NOT NEW.file_path
+            UNION ALL SELECT 'file_size' WHERE OLD.file_size IS NOT NEW.file_size
+            UNION ALL SELECT 'part_number' WHERE OLD.part_number IS NOT NEW.part_number
+            UNION ALL SELECT 'part_label' WHERE OLD.part_label IS NOT NEW.part_label
+            UNION ALL SELECT 'created_at' WHERE OLD.created_at IS NOT NEW.created_at
+            UNION ALL SELECT 'edition' WHERE OLD.edition IS NOT NEW.edition
+        )),
+        json_object(
+        'id', NEW.id,
+        'media_id', NEW.media_id,
+        'file_path', NEW.file_path,
+        'file_size', NEW.file_size,
+        'part_number', NEW.part_number,
+        'part_label', NEW.part_label,
+        'created_at', NEW.created_at,
+        'edition', NEW.edition
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_media_files_delete
+    AFTER DELETE ON media_files
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('media_file', OLD.id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
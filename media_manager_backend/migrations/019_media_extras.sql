@@ -0,0 +1,15 @@
+-- Migration: 019_media_extras
+-- 附加内容：预告片、幕后花絮、图集等，来自媒体文件所在目录下的 Extras/ 子文件夹
+
+CREATE TABLE IF NOT EXISTS media_extras (
+    id TEXT PRIMARY KEY NOT NULL,
+    media_id TEXT NOT NULL,
+    file_path TEXT NOT NULL UNIQUE,
+    file_name TEXT NOT NULL,
+    file_size INTEGER NOT NULL DEFAULT 0,
+    extra_type TEXT NOT NULL DEFAULT 'other',
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_extras_media ON media_extras(media_id, extra_type);
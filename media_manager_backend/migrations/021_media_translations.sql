@@ -0,0 +1,13 @@
+-- Migration: 021_media_translations
+-- 多语言元数据：每种语言的标题和简介，来自 TMDB 翻译接口或刮削插件输出
+
+CREATE TABLE IF NOT EXISTS media_translations (
+    media_id TEXT NOT NULL,
+    language TEXT NOT NULL, -- BCP 47 语言标签，如 zh-CN、en、ja
+    title TEXT,
+    overview TEXT,
+    source TEXT, -- tmdb / 刮削器名称 / manual
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (media_id, language),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
@@ -0,0 +1,193 @@
+-- Migration: 022_title_transliteration
+-- 标题转写：排序键（拼音/罗马字）和可搜索的罗马字形式，用于 sort_by=title 和全文搜索
+
+ALTER TABLE media_items ADD COLUMN title_sort TEXT;
+ALTER TABLE media_items ADD COLUMN title_romanized TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_media_items_title_sort ON media_items(title_sort);
+
+-- 重建全文搜索索引（增加 romanized 列）
+DROP TRIGGER IF EXISTS media_search_fts_insert;
+DROP TRIGGER IF EXISTS media_search_fts_delete;
+DROP TRIGGER IF EXISTS media_search_fts_update;
+DROP TABLE IF EXISTS media_search_fts;
+
+CREATE VIRTUAL TABLE media_search_fts USING fts5(
+    media_id,
+    title,
+    original_title,
+    overview,
+    romanized
+);
+
+INSERT INTO media_search_fts(media_id, title, original_title, overview, romanized)
+SELECT id, title, original_title, overview, title_romanized FROM media_items;
+
+CREATE TRIGGER media_search_fts_insert AFTER INSERT ON media_items BEGIN
+    INSERT INTO media_search_fts(media_id, title, original_title, overview, romanized)
+    VALUES (new.id, new.title, new.original_title, new.overview, new.title_romanized);
+END;
+
+CREATE TRIGGER media_search_fts_delete AFTER DELETE ON media_items BEGIN
+    DELETE FROM media_search_fts WHERE media_id = old.id;
+END;
+
+CREATE TRIGGER media_search_fts_update AFTER UPDATE ON media_items BEGIN
+    UPDATE media_search_fts SET
+        title = new.title,
+        original_title = new.original_title,
+        overview = new.overview,
+        romanized = new.title_romanized
+    WHERE media_id = new.id;
+END;
+
+-- 重新生成 media_items 变更触发器（包含转写字段，转写字段单独变化时不记录变更）
+-- media_items
+DROP TRIGGER IF EXISTS changes_media_items_insert;
+DROP TRIGGER IF EXISTS changes_media_items_update;
+DROP TRIGGER IF EXISTS changes_media_items_delete;
+
+CREATE TRIGGER changes_media_items_insert
+    AFTER INSERT ON media_items
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, data, origin, changed_at)
+    VALUES ('media', NEW.id, 'insert', json_object(
+        'id', NEW.id,
+        'external_ids', NEW.external_ids,
+        'title', NEW.title,
+        'original_title', NEW.original_title,
+        'code', NEW.code,
+        'year', NEW.year,
+        'media_type', NEW.media_type,
+        'genres', NEW.genres,
+        'rating', NEW.rating,
+        'vote_count', NEW.vote_count,
+        'poster_url', NEW.poster_url,
+        'overview', NEW.overview,
+        'runtime', NEW.runtime,
+        'release_date', NEW.release_date,
+        'cast', NEW.cast,
+        'crew', NEW.crew,
+        'language', NEW.language,
+        'country', NEW.country,
+        'budget', NEW.budget,
+        'revenue', NEW.revenue,
+        'status', NEW.status,
+        'play_links', NEW.play_links,
+        'download_links', NEW.download_links,
+        'preview_urls', NEW.preview_urls,
+        'preview_video_urls', NEW.preview_video_urls,
+        'studio', NEW.studio,
+        'series', NEW.series,
+        'created_at', NEW.created_at,
+        'updated_at', NEW.updated_at,
+        'local_file_path', NEW.local_file_path,
+        'file_size', NEW.file_size,
+        'last_scanned_at', NEW.last_scanned_at,
+        'is_local_only', NEW.is_local_only,
+        'cover_video_url', NEW.cover_video_url,
+        'backdrop_url', NEW.backdrop_url,
+        'scraper_name', NEW.scraper_name,
+        'trailer_url', NEW.trailer_url,
+        'title_sort', NEW.title_sort,
+        'title_romanized', NEW.title_romanized
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_media_items_update
+    AFTER UPDATE ON media_items
+    FOR EACH ROW
+    WHEN OLD.id IS NOT NEW.id OR OLD.external_ids IS NOT NEW.external_ids OR OLD.title IS NOT NEW.title OR OLD.original_title IS NOT NEW.original_title OR OLD.code IS NOT NEW.code OR OLD.year IS NOT NEW.year OR OLD.media_type IS NOT NEW.media_type OR OLD.genres IS NOT NEW.genres OR OLD.rating IS NOT NEW.rating OR OLD.vote_count IS NOT NEW.vote_count OR OLD.poster_url IS NOT NEW.poster_url OR OLD.overview IS NOT NEW.overview OR OLD.runtime IS NOT NEW.runtime OR OLD.release_date IS NOT NEW.release_date OR OLD.cast IS NOT NEW.cast OR OLD.crew IS NOT NEW.crew OR OLD.language IS NOT NEW.language OR OLD.country IS NOT NEW.country OR OLD.budget IS NOT NEW.budget OR OLD.revenue IS NOT NEW.revenue OR OLD.status IS NOT NEW.status OR OLD.play_links IS NOT NEW.play_links OR OLD.download_links IS NOT NEW.download_links OR OLD.preview_urls IS NOT NEW.preview_urls OR OLD.preview_video_urls IS NOT NEW.preview_video_urls OR OLD.studio IS NOT NEW.studio OR OLD.series IS NOT NEW.series OR OLD.created_at IS NOT NEW.created_at OR OLD.local_file_path IS NOT NEW.local_file_path OR OLD.file_size IS NOT NEW.file_size OR OLD.last_scanned_at IS NOT NEW.last_scanned_at OR OLD.is_local_only IS NOT NEW.is_local_only OR OLD.cover_video_url IS NOT NEW.cover_video_url OR OLD.backdrop_url IS NOT NEW.backdrop_url OR OLD.scraper_name IS NOT NEW.scraper_name OR OLD.trailer_url IS NOT NEW.trailer_url
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, changed_fields, data, origin, changed_at)
+    VALUES ('media', NEW.id, 'update',
+        (SELECT json_group_array(field) FROM (
+            SELECT 'id' AS field WHERE OLD.id IS NOT NEW.id
+            UNION ALL SELECT 'external_ids' WHERE OLD.external_ids IS NOT NEW.external_ids
+            UNION ALL SELECT 'title' WHERE OLD.title IS NOT NEW.title
+            UNION ALL SELECT 'original_title' WHERE OLD.original_title IS NOT NEW.original_title
+            UNION ALL SELECT 'code' WHERE OLD.code IS NOT NEW.code
+            UNION ALL SELECT 'year' WHERE OLD.year IS NOT NEW.year
+            UNION ALL SELECT 'media_type' WHERE OLD.media_type IS NOT NEW.media_type
+            UNION ALL SELECT 'genres' WHERE OLD.genres IS NOT NEW.genres
+            UNION ALL SELECT 'rating' WHERE OLD.rating IS NOT NEW.rating
+            UNION ALL SELECT 'vote_count' WHERE OLD.vote_count IS NOT NEW.vote_count
+            UNION ALL SELECT 'poster_url' WHERE OLD.poster_url IS NOT NEW.poster_url
+            UNION ALL SELECT 'overview' WHERE OLD.overview IS NOT NEW.overview
+            UNION ALL SELECT 'runtime' WHERE OLD.runtime IS NOT NEW.runtime
+            UNION ALL SELECT 'release_date' WHERE OLD.release_date IS NOT NEW.release_date
+            UNION ALL SELECT 'cast' WHERE OLD.cast IS NOT NEW.cast
+            UNION ALL SELECT 'crew' WHERE OLD.crew IS NOT NEW.crew
+            UNION ALL SELECT 'language' WHERE OLD.language IS NOT NEW.language
+            UNION ALL SELECT 'country' WHERE OLD.country IS NOT NEW.country
+            UNION ALL SELECT 'budget' WHERE OLD.budget IS NOT NEW.budget
+            UNION ALL SELECT 'revenue' WHERE OLD.revenue IS NOT NEW.revenue
+            UNION ALL SELECT 'status' WHERE OLD.status IS NOT NEW.status
+            UNION ALL SELECT 'play_links' WHERE OLD.play_links IS NOT NEW.play_links
+            UNION ALL SELECT 'download_links' WHERE OLD.download_links IS NOT NEW.download_links
+            UNION ALL SELECT 'preview_urls' WHERE OLD.preview_urls IS NOT NEW.preview_urls
+            UNION ALL SELECT 'preview_video_urls' WHERE OLD.preview_video_urls IS NOT NEW.preview_video_urls
+            UNION ALL SELECT 'studio' WHERE OLD.studio IS NOT NEW.studio
+            UNION ALL SELECT 'series' WHERE OLD.series IS NOT NEW.series
+            UNION ALL SELECT 'created_at' WHERE OLD.created_at IS NOT NEW.created_at
+            UNION ALL SELECT 'local_file_path' WHERE OLD.local_file_path IS NOT NEW.local_file_path
+            UNION ALL SELECT 'file_size' WHERE OLD.file_size IS NOT NEW.file_size
+            UNION ALL SELECT 'last_scanned_at' WHERE OLD.last_scanned_at IS NOT NEW.last_scanned_at
+            UNION ALL SELECT 'is_local_only' WHERE OLD.is_local_only IS NOT NEW.is_local_only
+            UNION ALL SELECT 'cover_video_url' WHERE OLD.cover_video_url IS NOT NEW.cover_video_url
+            UNION ALL SELECT 'backdrop_url' WHERE OLD.backdrop_url IS NOT NEW.backdrop_url
+            UNION ALL SELECT 'scraper_name' WHERE OLD.scraper_name IS NOT NEW.scraper_name
+            UNION ALL SELECT 'trailer_url' WHERE OLD.trailer_url IS NOT NEW.trailer_url
+        )),
+        json_object(
+        'id', NEW.id,
+        'external_ids', NEW.external_ids,
+        'title', NEW.title,
+        'original_title', NEW.original_title,
+        'code', NEW.code,
+        'year', NEW.year,
+        'media_type', NEW.media_type,
+        'genres', NEW.genres,
+        'rating', NEW.rating,
+        'vote_count', NEW.vote_count,
+        'poster_url', NEW.poster_url,
+        'overview', NEW.overview,
+        'runtime', NEW.runtime,
+        'release_date', NEW.release_date,
+        'cast', NEW.cast,
+        'crew', NEW.crew,
+        'language', NEW.language,
+        'country', NEW.country,
+        'budget', NEW.budget,
+        'revenue', NEW.revenue,
+        'status', NEW.status,
+        'play_links', NEW.play_links,
+        'download_links', NEW.download_links,
+        'preview_urls', NEW.preview_urls,
+        'preview_video_urls', NEW.preview_video_urls,
+        'studio', NEW.studio,
+        'series', NEW.series,
+        'created_at', NEW.created_at,
+        'updated_at', NEW.updated_at,
+        'local_file_path', NEW.local_file_path,
+        'file_size', NEW.file_size,
+        'last_scanned_at', NEW.last_scanned_at,
+        'is_local_only', NEW.is_local_only,
+        'cover_video_url', NEW.cover_video_url,
+        'backdrop_url', NEW.backdrop_url,
+        'scraper_name', NEW.scraper_name,
+        'trailer_url', NEW.trailer_url,
+        'title_sort', NEW.title_sort,
+        'title_romanized', NEW.title_romanized
+    ), (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
+CREATE TRIGGER changes_media_items_delete
+    AFTER DELETE ON media_items
+    FOR EACH ROW
+BEGIN
+    INSERT INTO changes (entity_type, entity_id, operation, origin, changed_at)
+    VALUES ('media', OLD.id, 'delete', (SELECT origin FROM sync_apply_context LIMIT 1), COALESCE((SELECT changed_at FROM sync_apply_context LIMIT 1), strftime('%Y-%m-%dT%H:%M:%fZ', 'now')));
+END;
+
@@ -0,0 +1,15 @@
+-- Migration: 023_media_broken_links
+-- 失效链接检查：记录媒体的海报、预览、下载等远程地址中无法访问的链接
+
+CREATE TABLE IF NOT EXISTS media_broken_links (
+    media_id TEXT NOT NULL,
+    field TEXT NOT NULL, -- poster_url / backdrop_url / preview_urls / preview_video_urls / cover_video_url / play_links / download_links
+    url TEXT NOT NULL,
+    status_code INTEGER, -- HTTP 状态码，连接失败时为空
+    error TEXT,
+    checked_at TEXT NOT NULL,
+    PRIMARY KEY (media_id, url),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_broken_links_field ON media_broken_links(field);
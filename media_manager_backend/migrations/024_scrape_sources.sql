@@ -0,0 +1,19 @@
+-- Migration: 024_scrape_sources
+-- 刮削来源存档：压缩保存刮削插件返回的原始数据，便于源站消失后重新提取元数据或排查解析错误
+
+CREATE TABLE IF NOT EXISTS scrape_sources (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_id TEXT NOT NULL,
+    source TEXT, -- 刮削器名称
+    query TEXT, -- 刮削时使用的番号/标题
+    source_url TEXT,
+    content_type TEXT NOT NULL DEFAULT 'application/json',
+    payload BLOB NOT NULL, -- gzip 压缩后的原始数据
+    original_size INTEGER NOT NULL,
+    compressed_size INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_scrape_sources_media_id ON scrape_sources(media_id);
+CREATE INDEX IF NOT EXISTS idx_scrape_sources_created_at ON scrape_sources(created_at);
@@ -0,0 +1,11 @@
+-- Migration: 025_favorite_entities
+-- 收藏演员、厂商和系列，用于聚合收藏动态
+
+CREATE TABLE IF NOT EXISTS favorite_entities (
+    entity_type TEXT NOT NULL CHECK(entity_type IN ('actor', 'studio', 'series')),
+    entity_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (entity_type, entity_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_favorite_entities_created_at ON favorite_entities(created_at DESC);
@@ -0,0 +1,13 @@
+-- Migration: 026_browsing_history
+-- 最近浏览记录：记录打开过的媒体详情页（按用户区分，未指定用户时为 default）
+
+CREATE TABLE IF NOT EXISTS browsing_history (
+    user_key TEXT NOT NULL DEFAULT 'default',
+    media_id TEXT NOT NULL,
+    view_count INTEGER NOT NULL DEFAULT 1,
+    viewed_at TEXT NOT NULL,
+    PRIMARY KEY (user_key, media_id),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_browsing_history_viewed_at ON browsing_history(user_key, viewed_at DESC);
@@ -0,0 +1,36 @@
+-- Migration: 028_filter_options_version
+-- 筛选选项版本号：媒体的类型/厂商/系列/年份/类型标签变化时递增，
+-- 筛选选项缓存据此判断是否需要重新统计
+
+CREATE TABLE IF NOT EXISTS filter_options_version (
+    id INTEGER PRIMARY KEY CHECK(id = 1),
+    version INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT OR IGNORE INTO filter_options_version (id, version) VALUES (1, 0);
+
+CREATE TRIGGER IF NOT EXISTS filter_options_media_insert
+    AFTER INSERT ON media_items
+    FOR EACH ROW
+BEGIN
+    UPDATE filter_options_version SET version = version + 1 WHERE id = 1;
+END;
+
+CREATE TRIGGER IF NOT EXISTS filter_options_media_update
+    AFTER UPDATE OF media_type, studio, series, year, genres ON media_items
+    FOR EACH ROW
+    WHEN OLD.media_type IS NOT NEW.media_type
+      OR OLD.studio IS NOT NEW.studio
+      OR OLD.series IS NOT NEW.series
+      OR OLD.year IS NOT NEW.year
+      OR OLD.genres IS NOT NEW.genres
+BEGIN
+    UPDATE filter_options_version SET version = version + 1 WHERE id = 1;
+END;
+
+CREATE TRIGGER IF NOT EXISTS filter_options_media_delete
+    AFTER DELETE ON media_items
+    FOR EACH ROW
+BEGIN
+    UPDATE filter_options_version SET version = version + 1 WHERE id = 1;
+END;
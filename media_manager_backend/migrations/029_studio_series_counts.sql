@@ -0,0 +1,101 @@
+-- Migration: 029_studio_series_counts
+-- 厂商和系列的 media_count 由触发器增量维护，不再需要手动同步
+-- POST /api/studios-series/sync-counts 仅作为计数出错时的重建手段
+
+-- 新增媒体
+CREATE TRIGGER IF NOT EXISTS media_count_media_insert
+    AFTER INSERT ON media_items
+    FOR EACH ROW
+BEGIN
+    UPDATE studios SET media_count = media_count + 1 WHERE name = NEW.studio COLLATE NOCASE;
+    UPDATE series SET media_count = media_count + 1 WHERE name = NEW.series COLLATE NOCASE;
+END;
+
+-- 修改媒体的厂商或系列
+CREATE TRIGGER IF NOT EXISTS media_count_media_update
+    AFTER UPDATE OF studio, series ON media_items
+    FOR EACH ROW
+    WHEN OLD.studio IS NOT NEW.studio OR OLD.series IS NOT NEW.series
+BEGIN
+    UPDATE studios SET media_count = MAX(media_count - 1, 0)
+        WHERE OLD.studio IS NOT NEW.studio AND name = OLD.studio COLLATE NOCASE;
+    UPDATE studios SET media_count = media_count + 1
+        WHERE OLD.studio IS NOT NEW.studio AND name = NEW.studio COLLATE NOCASE;
+    UPDATE series SET media_count = MAX(media_count - 1, 0)
+        WHERE OLD.series IS NOT NEW.series AND name = OLD.series COLLATE NOCASE;
+    UPDATE series SET media_count = media_count + 1
+        WHERE OLD.series IS NOT NEW.series AND name = NEW.series COLLATE NOCASE;
+END;
+
+-- 删除媒体
+CREATE TRIGGER IF NOT EXISTS media_count_media_delete
+    AFTER DELETE ON media_items
+    FOR EACH ROW
+BEGIN
+    UPDATE studios SET media_count = MAX(media_count - 1, 0) WHERE name = OLD.studio COLLATE NOCASE;
+    UPDATE series SET media_count = MAX(media_count - 1, 0) WHERE name = OLD.series COLLATE NOCASE;
+END;
+
+-- 新建或改名的厂商/系列：按名称重新统计已有媒体
+CREATE TRIGGER IF NOT EXISTS media_count_studio_insert
+    AFTER INSERT ON studios
+    FOR EACH ROW
+BEGIN
+    UPDATE studios SET media_count = (
+        SELECT COUNT(*) FROM media_items WHERE studio = NEW.name COLLATE NOCASE
+    ) WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS media_count_studio_rename
+    AFTER UPDATE OF name ON studios
+    FOR EACH ROW
+    WHEN OLD.name IS NOT NEW.name
+BEGIN
+    UPDATE studios SET media_count = (
+        SELECT COUNT(*) FROM media_items WHERE studio = NEW.name COLLATE NOCASE
+    ) WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS media_count_series_insert
+    AFTER INSERT ON series
+    FOR EACH ROW
+BEGIN
+    UPDATE series SET media_count = (
+        SELECT COUNT(*) FROM media_items WHERE series = NEW.name COLLATE NOCASE
+    ) WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS media_count_series_rename
+    AFTER UPDATE OF name ON series
+    FOR EACH ROW
+    WHEN OLD.name IS NOT NEW.name
+BEGIN
+    UPDATE series SET media_count = (
+        SELECT COUNT(*) FROM media_items WHERE series = NEW.name COLLATE NOCASE
+    ) WHERE id = NEW.id;
+END;
+
+-- 计数变化不算作编辑，不再刷新 updated_at
+DROP TRIGGER IF EXISTS update_studios_timestamp;
+CREATE TRIGGER IF NOT EXISTS update_studios_timestamp
+    AFTER UPDATE OF name, logo_url, description ON studios
+    FOR EACH ROW
+BEGIN
+    UPDATE studios SET updated_at = datetime('now') WHERE id = NEW.id;
+END;
+
+DROP TRIGGER IF EXISTS update_series_timestamp;
+CREATE TRIGGER IF NOT EXISTS update_series_timestamp
+    AFTER UPDATE OF name, studio_id, description, cover_url ON series
+    FOR EACH ROW
+BEGIN
+    UPDATE series SET updated_at = datetime('now') WHERE id = NEW.id;
+END;
+
+-- 按当前数据重建一次计数
+UPDATE studios SET media_count = (
+    SELECT COUNT(*) FROM media_items WHERE studio = studios.name COLLATE NOCASE
+);
+UPDATE series SET media_count = (
+    SELECT COUNT(*) FROM media_items WHERE series = series.name COLLATE NOCASE
+);
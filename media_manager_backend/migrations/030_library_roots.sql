@@ -0,0 +1,13 @@
+-- Migration: 030_library_roots
+-- 媒体库根目录：扫描、后台定期扫描和导入整理使用的目录及其设置
+
+CREATE TABLE IF NOT EXISTS library_roots (
+    id TEXT PRIMARY KEY NOT NULL,
+    path TEXT NOT NULL UNIQUE CHECK(length(path) > 0),
+    label TEXT,
+    content_type TEXT,  -- 该目录下新建媒体的默认内容类型
+    auto_scan INTEGER NOT NULL DEFAULT 0,
+    rename_template TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Migration: 031_library_root_status
+-- 根目录可用性：网络共享（SMB/NFS）掉线时扫描跳过该目录
+
+ALTER TABLE library_roots ADD COLUMN path_exists INTEGER;
+ALTER TABLE library_roots ADD COLUMN reachable INTEGER;
+ALTER TABLE library_roots ADD COLUMN last_error TEXT;
+ALTER TABLE library_roots ADD COLUMN last_checked_at TEXT;
+-- 最近一次可访问的时间
+ALTER TABLE library_roots ADD COLUMN last_seen_at TEXT;
@@ -0,0 +1,6 @@
+-- Migration: 032_library_root_storage
+-- 根目录的存储后端：local（本地/挂载目录）、s3、webdav，连接参数以 JSON 保存在 storage_config
+
+ALTER TABLE library_roots ADD COLUMN storage_type TEXT NOT NULL DEFAULT 'local'
+    CHECK(storage_type IN ('local', 's3', 'webdav'));
+ALTER TABLE library_roots ADD COLUMN storage_config TEXT;
@@ -0,0 +1,21 @@
+-- Migration: 033_torrent_downloads
+-- 推送到下载客户端的磁力任务，下载完成后自动整理到媒体库并关联到媒体
+
+CREATE TABLE IF NOT EXISTS torrent_downloads (
+    info_hash TEXT PRIMARY KEY NOT NULL,  -- 小写十六进制 BTIH
+    media_id TEXT NOT NULL,
+    title TEXT,
+    magnet_link TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'downloading' CHECK(status IN ('downloading', 'importing', 'imported', 'failed')),
+    progress REAL NOT NULL DEFAULT 0,
+    imported_files TEXT,  -- JSON: 整理后的文件路径
+    error TEXT,
+    completed_at TEXT,
+    imported_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_torrent_downloads_status ON torrent_downloads(status);
+CREATE INDEX IF NOT EXISTS idx_torrent_downloads_media ON torrent_downloads(media_id);
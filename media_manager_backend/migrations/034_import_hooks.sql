@@ -0,0 +1,31 @@
+-- Migration: 034_import_hooks
+-- 导入后处理钩子：文件导入/匹配到媒体后执行的命令或 Webhook，以及每次执行的结果
+
+CREATE TABLE IF NOT EXISTS import_hooks (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT NOT NULL CHECK(length(name) > 0),
+    hook_type TEXT NOT NULL CHECK(hook_type IN ('command', 'webhook')),
+    target TEXT NOT NULL,           -- 命令路径或 Webhook 地址（支持模板）
+    args TEXT NOT NULL DEFAULT '[]', -- 命令参数（JSON 数组，支持模板）
+    method TEXT NOT NULL DEFAULT 'POST', -- Webhook 请求方法
+    enabled INTEGER NOT NULL DEFAULT 1,
+    timeout_secs INTEGER NOT NULL DEFAULT 30,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS hook_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    hook_id TEXT NOT NULL,
+    media_id TEXT,
+    file_path TEXT,
+    success INTEGER NOT NULL,
+    exit_code INTEGER,              -- 命令退出码或 Webhook 响应状态码
+    output TEXT,                    -- 命令输出或响应内容（截断）
+    error TEXT,
+    duration_ms INTEGER NOT NULL,
+    fired_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (hook_id) REFERENCES import_hooks(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_hook_events_hook_id ON hook_events(hook_id, id DESC);
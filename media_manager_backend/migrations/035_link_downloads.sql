@@ -0,0 +1,21 @@
+-- Migration: 035_link_downloads
+-- 推送到 aria2 的 HTTP/FTP 下载任务，与磁力任务一样在完成后自动整理到媒体库
+
+CREATE TABLE IF NOT EXISTS link_downloads (
+    gid TEXT PRIMARY KEY NOT NULL,  -- aria2 任务ID
+    media_id TEXT NOT NULL,
+    name TEXT,
+    url TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'downloading' CHECK(status IN ('downloading', 'importing', 'imported', 'failed')),
+    progress REAL NOT NULL DEFAULT 0,
+    imported_files TEXT,  -- JSON: 整理后的文件路径
+    error TEXT,
+    completed_at TEXT,
+    imported_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_link_downloads_status ON link_downloads(status);
+CREATE INDEX IF NOT EXISTS idx_link_downloads_media ON link_downloads(media_id);
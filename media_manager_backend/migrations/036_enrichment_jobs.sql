@@ -0,0 +1,33 @@
+-- Migration: 036_enrichment_jobs
+-- 新建媒体后在后台依次执行的补全任务（补充刮削、图片缓存、演员资料、文件技术信息），以及 ffprobe 探测结果
+
+CREATE TABLE IF NOT EXISTS enrichment_jobs (
+    media_id TEXT PRIMARY KEY NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK(status IN ('pending', 'running', 'completed', 'failed')),
+    reason TEXT NOT NULL DEFAULT 'manual',  -- 加入队列的原因：create、scan、manual
+    steps TEXT NOT NULL DEFAULT '[]',  -- JSON: 各步骤的执行结果
+    error TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    started_at TEXT,
+    finished_at TEXT,
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_enrichment_jobs_status ON enrichment_jobs(status, created_at);
+
+CREATE TABLE IF NOT EXISTS media_probes (
+    file_path TEXT PRIMARY KEY NOT NULL,
+    media_id TEXT NOT NULL,
+    container TEXT,
+    duration_secs REAL,
+    bit_rate INTEGER,
+    width INTEGER,
+    height INTEGER,
+    video_codec TEXT,
+    audio_codec TEXT,
+    probed_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_probes_media ON media_probes(media_id);
@@ -0,0 +1,14 @@
+-- Migration: 037_preview_actor_tags
+-- 手动标注预览图中出现的演员，演员页可展示其出镜画面
+
+CREATE TABLE IF NOT EXISTS preview_actor_tags (
+    media_id TEXT NOT NULL,
+    preview_index INTEGER NOT NULL,  -- preview_urls 中的下标
+    actor_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    PRIMARY KEY (media_id, preview_index, actor_id),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE,
+    FOREIGN KEY (actor_id) REFERENCES actors(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_preview_actor_tags_actor ON preview_actor_tags(actor_id);
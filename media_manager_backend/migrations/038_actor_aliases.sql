@@ -0,0 +1,11 @@
+-- Migration: 038_actor_aliases
+-- 演员别名：合并重复演员后保留被合并的名字，刮削时按别名找到同一演员
+
+CREATE TABLE IF NOT EXISTS actor_aliases (
+    alias TEXT PRIMARY KEY NOT NULL COLLATE NOCASE,
+    actor_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (actor_id) REFERENCES actors(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_actor_aliases_actor ON actor_aliases(actor_id);
@@ -0,0 +1,11 @@
+-- Migration: 039_tmdb_list_cache
+-- TMDB 热门/趋势列表的本地缓存：每晚预取，TMDB 无法访问时继续提供最近一次的结果
+
+CREATE TABLE IF NOT EXISTS tmdb_list_cache (
+    list TEXT NOT NULL,        -- popular / trending
+    media_type TEXT NOT NULL,  -- movie / tv
+    page INTEGER NOT NULL,
+    results TEXT NOT NULL,     -- JSON: 该页的媒体列表
+    fetched_at TEXT NOT NULL,
+    PRIMARY KEY (list, media_type, page)
+);
@@ -0,0 +1,7 @@
+-- Migration: 040_media_acquisition
+-- 从 TMDB 保存的无本地文件媒体的获取状态：wanted → downloading → imported，由下载任务推进，为空表示不跟踪
+
+ALTER TABLE media_items ADD COLUMN acquisition_status TEXT;
+ALTER TABLE media_items ADD COLUMN acquisition_updated_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_media_items_acquisition ON media_items(acquisition_status);
@@ -0,0 +1,17 @@
+-- Migration: 041_actor_scrape_runs
+-- 批量演员刮削结果：每次批量刮削结束后保存汇总和每个演员的结果，供事后查看
+
+CREATE TABLE IF NOT EXISTS actor_scrape_runs (
+    id TEXT PRIMARY KEY NOT NULL,               -- 与进度会话ID相同
+    mode TEXT NOT NULL,                         -- replace / supplement
+    status TEXT NOT NULL,                       -- completed / failed
+    message TEXT,
+    total INTEGER NOT NULL DEFAULT 0,
+    success_count INTEGER NOT NULL DEFAULT 0,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    results TEXT NOT NULL DEFAULT '[]',         -- 每个演员的结果（JSON 数组）
+    started_at DATETIME NOT NULL,
+    finished_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_actor_scrape_runs_finished ON actor_scrape_runs(finished_at);
@@ -0,0 +1,17 @@
+-- Migration: 042_magnet_swarm_stats
+-- 已保存磁力链接（媒体 download_links 中的 magnet）的做种/下载人数，由后台任务定期向 tracker 查询
+
+CREATE TABLE IF NOT EXISTS magnet_swarm_stats (
+    media_id TEXT NOT NULL,
+    info_hash TEXT NOT NULL,       -- 小写十六进制
+    seeders INTEGER,               -- 所有 tracker 中的最大值，查询失败时为空
+    leechers INTEGER,
+    completed INTEGER,             -- 完成下载次数
+    trackers_ok INTEGER NOT NULL DEFAULT 0,   -- 成功返回统计的 tracker 数
+    error TEXT,
+    checked_at TEXT NOT NULL,
+    PRIMARY KEY (media_id, info_hash),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_magnet_swarm_stats_checked ON magnet_swarm_stats(checked_at);
@@ -0,0 +1,6 @@
+-- Migration: 043_search_history_user
+-- 搜索历史按用户区分（X-User-Id 请求头，与浏览记录相同），用于个性化的搜索建议
+
+ALTER TABLE search_history ADD COLUMN user_key TEXT NOT NULL DEFAULT 'default';
+
+CREATE INDEX IF NOT EXISTS idx_search_history_user ON search_history(user_key, searched_at DESC);
@@ -0,0 +1,41 @@
+-- Migration: 044_media_studio_series_fk
+-- 媒体通过外键关联厂商/系列表；studio、series 文本字段在过渡期继续保留，
+-- 未关联（外键为空）的媒体按文本字段过滤。已有数据由 POST /api/studios-series/link-media 关联
+
+ALTER TABLE media_items ADD COLUMN studio_id TEXT REFERENCES studios(id) ON DELETE SET NULL;
+ALTER TABLE media_items ADD COLUMN series_id TEXT REFERENCES series(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_media_studio_id ON media_items(studio_id);
+CREATE INDEX IF NOT EXISTS idx_media_series_id ON media_items(series_id);
+
+-- 新增媒体或修改厂商/系列名时按名称重新关联（同名系列优先选择同一厂商的）
+CREATE TRIGGER IF NOT EXISTS media_link_studio_series_insert
+    AFTER INSERT ON media_items
+    FOR EACH ROW
+BEGIN
+    UPDATE media_items SET
+        studio_id = (SELECT id FROM studios WHERE name = NEW.studio COLLATE NOCASE),
+        series_id = (
+            SELECT s.id FROM series s LEFT JOIN studios st ON st.id = s.studio_id
+            WHERE s.name = NEW.series COLLATE NOCASE
+            ORDER BY st.name = NEW.studio COLLATE NOCASE DESC
+            LIMIT 1
+        )
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS media_link_studio_series_update
+    AFTER UPDATE OF studio, series ON media_items
+    FOR EACH ROW
+    WHEN OLD.studio IS NOT NEW.studio OR OLD.series IS NOT NEW.series
+BEGIN
+    UPDATE media_items SET
+        studio_id = (SELECT id FROM studios WHERE name = NEW.studio COLLATE NOCASE),
+        series_id = (
+            SELECT s.id FROM series s LEFT JOIN studios st ON st.id = s.studio_id
+            WHERE s.name = NEW.series COLLATE NOCASE
+            ORDER BY st.name = NEW.studio COLLATE NOCASE DESC
+            LIMIT 1
+        )
+    WHERE id = NEW.id;
+END;
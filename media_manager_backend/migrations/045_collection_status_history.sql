@@ -0,0 +1,53 @@
+-- Migration: 045_collection_status_history
+-- 收藏观看状态变化记录（加入收藏时记录初始状态，之后每次状态变化记录一条），
+-- 用于观看时间线和"本月看完"之类的统计
+
+CREATE TABLE IF NOT EXISTS collection_status_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_id TEXT NOT NULL,
+    -- 加入收藏时为 NULL
+    from_status TEXT,
+    to_status TEXT NOT NULL,
+    changed_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_status_history_media ON collection_status_history(media_id, changed_at);
+CREATE INDEX IF NOT EXISTS idx_collection_status_history_status ON collection_status_history(to_status, changed_at);
+
+CREATE TRIGGER IF NOT EXISTS collection_status_history_insert
+    AFTER INSERT ON collections
+    FOR EACH ROW
+BEGIN
+    INSERT INTO collection_status_history (media_id, from_status, to_status)
+    VALUES (NEW.media_id, NULL, NEW.watch_status);
+END;
+
+CREATE TRIGGER IF NOT EXISTS collection_status_history_update
+    AFTER UPDATE OF watch_status ON collections
+    FOR EACH ROW
+    WHEN OLD.watch_status IS NOT NEW.watch_status
+BEGIN
+    INSERT INTO collection_status_history (media_id, from_status, to_status)
+    VALUES (NEW.media_id, OLD.watch_status, NEW.watch_status);
+END;
+
+-- 移出收藏时一并删除记录
+CREATE TRIGGER IF NOT EXISTS collection_status_history_delete
+    AFTER DELETE ON collections
+    FOR EACH ROW
+BEGIN
+    DELETE FROM collection_status_history WHERE media_id = OLD.media_id;
+END;
+
+-- 已有收藏：加入时间记为初始状态，已看完的再按完成时间补一条
+INSERT INTO collection_status_history (media_id, from_status, to_status, changed_at)
+SELECT media_id, NULL,
+       CASE WHEN watch_status = 'Completed' AND completed_at IS NOT NULL THEN 'WantToWatch' ELSE watch_status END,
+       COALESCE(datetime(added_at), datetime('now'))
+FROM collections;
+
+INSERT INTO collection_status_history (media_id, from_status, to_status, changed_at)
+SELECT media_id, 'WantToWatch', 'Completed', COALESCE(datetime(completed_at), datetime('now'))
+FROM collections
+WHERE watch_status = 'Completed' AND completed_at IS NOT NULL;
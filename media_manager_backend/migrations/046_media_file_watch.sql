@@ -0,0 +1,5 @@
+-- Migration: 046_media_file_watch
+-- 多分段媒体按分段记录观看进度（0-1），看完时记录时间；收藏的观看状态由同一版本所有分段的进度推算
+
+ALTER TABLE media_files ADD COLUMN watch_progress REAL CHECK(watch_progress IS NULL OR (watch_progress >= 0.0 AND watch_progress <= 1.0));
+ALTER TABLE media_files ADD COLUMN watched_at TEXT;
@@ -0,0 +1,26 @@
+-- Migration: 047_offline_packages
+-- 离线下载包：后台把媒体转码为限制大小的 MP4，完成后提供一次性下载链接
+
+CREATE TABLE IF NOT EXISTS offline_packages (
+    id TEXT PRIMARY KEY NOT NULL,
+    media_id TEXT NOT NULL,
+    edition TEXT,
+    part INTEGER NOT NULL DEFAULT 0,
+    max_size_mb INTEGER NOT NULL,
+    max_height INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK(status IN ('pending', 'running', 'ready', 'failed', 'downloaded', 'expired')),
+    file_path TEXT,
+    file_size INTEGER,
+    error TEXT,
+    download_token TEXT UNIQUE,  -- 一次性下载令牌，下载完成后清空
+    downloading_at TEXT,         -- 正在下载（同一时间只允许一个下载）
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    started_at TEXT,
+    finished_at TEXT,
+    expires_at TEXT,
+    downloaded_at TEXT,
+    FOREIGN KEY (media_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_offline_packages_status ON offline_packages(status, created_at);
+CREATE INDEX IF NOT EXISTS idx_offline_packages_media ON offline_packages(media_id);
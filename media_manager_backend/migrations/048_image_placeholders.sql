@@ -0,0 +1,5 @@
+-- Migration: 048_image_placeholders
+-- 封面/背景图的 BlurHash 占位图，缓存图片转换为 WebP 时生成
+-- JSON 对象：图片地址（原始 URL 和缓存路径）-> BlurHash
+
+ALTER TABLE media_items ADD COLUMN image_placeholders TEXT;
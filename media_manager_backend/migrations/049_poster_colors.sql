@@ -0,0 +1,5 @@
+-- Migration: 049_poster_colors
+-- 封面的主色和强调色，缓存封面时提取，用于详情页主题色
+-- JSON：{"urls": [原始 URL, 缓存路径], "dominant": "#rrggbb", "accent": "#rrggbb"}
+
+ALTER TABLE media_items ADD COLUMN poster_colors TEXT;
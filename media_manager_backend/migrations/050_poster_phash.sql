@@ -0,0 +1,5 @@
+-- Migration: 050_poster_phash
+-- 封面的感知哈希，缓存封面时计算，用于检测不同刮削器产生的重复条目
+-- JSON：{"urls": [原始 URL, 缓存路径], "hash": "16 位十六进制"}
+
+ALTER TABLE media_items ADD COLUMN poster_phash TEXT;
@@ -0,0 +1,5 @@
+-- Migration: 051_image_sources
+-- 缓存图片的来源地址，数据库中的图片地址被替换为缓存路径后，刷新图片时从这里找回原始 URL
+-- JSON：{"缓存路径": "原始 URL"}
+
+ALTER TABLE media_items ADD COLUMN image_sources TEXT;
@@ -1,4 +1,4 @@
-//! 磁力链接刮削插件 - 支持多个网站（优先级：DemoSearch -> SkrBT）
+//! 磁力链接刮削插件 - 同时搜索多个网站（Kiteyuan、Knaben、SkrBT），按完成顺序流式输出结果
 
 use std::io::{self, BufRead, Write};
 use anyhow::{Result, anyhow};
@@ -6,6 +6,7 @@ use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use headless_chrome::{Browser, LaunchOptions, Tab};
+use std::sync::Mutex;
 use std::time::Duration;
 use reqwest::blocking::Client;
 use uuid::Uuid;
@@ -16,6 +17,9 @@ const KITEYUAN_SITE: &str = "https://demosearch.kiteyuan.info";
 const KNABEN_API: &str = "https://api.knaben.org/v1";
 const SKRBT_SITE: &str = "https://skrbtux.top";
 
+/// 同时搜索的网站
+const SOURCES: [&str; 3] = ["Kiteyuan", "Knaben", "SkrBT"];
+
 #[derive(Debug, Deserialize)]
 #[serde(tag = "action", rename_all = "snake_case")]
 enum PluginRequest {
@@ -49,6 +53,9 @@ struct SearchProgress {
     result_count: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    /// 该网站找到的结果（completed 时输出，去重由主程序完成）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    results: Vec<MagnetResult>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -65,26 +72,6 @@ struct MagnetResult {
     files: Vec<FileInfo>,
 }
 
-impl MagnetResult {
-    /// 提取磁力链接的 info_hash（用于去重）
-    fn extract_hash(&self) -> Option<String> {
-        // 磁力链接格式: magnet:?xt=urn:btih:HASH&...
-        if let Some(start) = self.magnet_link.find("xt=urn:btih:") {
-            let hash_start = start + "xt=urn:btih:".len();
-            let remaining = &self.magnet_link[hash_start..];
-            
-            // Hash 可能以 & 或字符串结尾
-            let hash_end = remaining.find('&').unwrap_or(remaining.len());
-            let hash = &remaining[..hash_end];
-            
-            // 转换为小写以便比较
-            Some(hash.to_lowercase())
-        } else {
-            None
-        }
-    }
-}
-
 #[derive(Debug, Clone, Serialize)]
 struct FileInfo {
     name: String,
@@ -108,22 +95,23 @@ impl PluginResponse {
 }
 
 struct MagnetScraper {
-    browser: Option<Browser>,
+    browser: Mutex<Option<Browser>>,
 }
 
 impl MagnetScraper {
     fn new() -> Result<Self> {
         // 不立即启动浏览器，延迟到需要时再启动
-        Ok(Self { browser: None })
+        Ok(Self { browser: Mutex::new(None) })
     }
     
     /// 输出进度状态到 stderr（stderr 是无缓冲的，可以实时输出）
-    fn emit_progress(site_name: &str, status: &str, result_count: Option<usize>, error: Option<String>) {
+    fn emit_progress(site_name: &str, status: &str, result_count: Option<usize>, error: Option<String>, results: Vec<MagnetResult>) {
         let progress = SearchProgress {
             site_name: site_name.to_string(),
             status: status.to_string(),
             result_count,
             error,
+            results,
         };
         
         let response = PluginResponse {
@@ -139,38 +127,11 @@ impl MagnetScraper {
         }
     }
     
-    /// 对磁力链接结果进行 hash 去重
-    fn deduplicate_by_hash(results: Vec<MagnetResult>) -> Vec<MagnetResult> {
-        use std::collections::HashSet;
-        
-        let mut seen_hashes = HashSet::new();
-        let mut deduplicated = Vec::new();
-        let original_count = results.len();
-        
-        for result in results {
-            if let Some(hash) = result.extract_hash() {
-                // 如果这个 hash 没见过，就保留这个结果
-                if seen_hashes.insert(hash.clone()) {
-                    eprintln!("✓ Keeping result: {} (hash: {})", result.title, hash);
-                    deduplicated.push(result);
-                } else {
-                    eprintln!("⊗ Duplicate removed: {} (hash: {})", result.title, hash);
-                }
-            } else {
-                // 如果无法提取 hash，保留结果（可能是格式异常）
-                eprintln!("⚠ No hash found for: {}, keeping anyway", result.title);
-                deduplicated.push(result);
-            }
-        }
-        
-        eprintln!("Deduplication: {} -> {} results", original_count, deduplicated.len());
-        deduplicated
-    }
-    
-    /// 延迟初始化浏览器（仅在需要 SkrBT 时调用）
-    fn ensure_browser(&mut self) -> Result<&Browser> {
-        if self.browser.is_none() {
-            eprintln!("Initializing browser for SkrBT fallback...");
+    /// 延迟初始化浏览器（首次查询 SkrBT 时才启动，其他网站不等待浏览器）
+    fn ensure_browser(&self) -> Result<Browser> {
+        let mut slot = self.browser.lock().map_err(|_| anyhow!("Browser lock poisoned"))?;
+        if slot.is_none() {
+            eprintln!("Initializing browser for SkrBT...");
             let launch_options = LaunchOptions::default_builder()
                 .headless(true)
                 .window_size(Some((1920, 1080)))
@@ -187,87 +148,60 @@ impl MagnetScraper {
             // 给浏览器一点预热时间（首次启动可能需要加载资源）
             std::thread::sleep(std::time::Duration::from_millis(500));
             
-            self.browser = Some(browser);
+            *slot = Some(browser);
         }
         
-        Ok(self.browser.as_ref().unwrap())
+        Ok(slot.as_ref().unwrap().clone())
     }
     
-    /// 搜索磁力链接 - 优先级：Kiteyuan -> Knaben -> SkrBT（带流式进度输出）
-    fn search_magnets(&mut self, query: &str) -> Result<Vec<MagnetResult>> {
+    /// 搜索磁力链接 - 同时搜索所有网站，每个网站完成时输出进度和结果
+    ///
+    /// 所有网站都失败时返回错误，否则返回全部网站的结果（去重由主程序完成）
+    fn search_magnets(&self, query: &str) -> Result<Vec<MagnetResult>> {
         eprintln!("=== Starting magnet search for: {} ===", query);
         
-        // 1. 优先尝试 Kiteyuan（HTTP API，无需浏览器，中文资源丰富）
-        eprintln!("Trying Kiteyuan first...");
-        Self::emit_progress("Kiteyuan", "searching", None, None);
-        
-        match self.search_kiteyuan(query) {
-            Ok(results) if !results.is_empty() => {
-                eprintln!("✓ Kiteyuan succeeded with {} results", results.len());
-                let deduplicated = Self::deduplicate_by_hash(results);
-                let count = deduplicated.len();
-                Self::emit_progress("Kiteyuan", "completed", Some(count), None);
-                
-                // 标记其他网站为跳过
-                Self::emit_progress("Knaben", "skipped", None, None);
-                Self::emit_progress("SkrBT", "skipped", None, None);
-                
-                return Ok(deduplicated);
-            }
-            Ok(_) => {
-                eprintln!("⚠ Kiteyuan returned 0 results, trying Knaben...");
-                Self::emit_progress("Kiteyuan", "completed", Some(0), None);
-            }
-            Err(e) => {
-                eprintln!("✗ Kiteyuan failed: {}, trying Knaben...", e);
-                Self::emit_progress("Kiteyuan", "failed", None, Some(e.to_string()));
-            }
-        }
-        
-        // 2. 尝试 Knaben（HTTP API，无需浏览器，聚合多个站点）
-        eprintln!("Trying Knaben...");
-        Self::emit_progress("Knaben", "searching", None, None);
-        
-        match self.search_knaben(query) {
-            Ok(results) if !results.is_empty() => {
-                eprintln!("✓ Knaben succeeded with {} results", results.len());
-                let deduplicated = Self::deduplicate_by_hash(results);
-                let count = deduplicated.len();
-                Self::emit_progress("Knaben", "completed", Some(count), None);
-                
-                // 标记 SkrBT 为跳过
-                Self::emit_progress("SkrBT", "skipped", None, None);
-                
-                return Ok(deduplicated);
-            }
-            Ok(_) => {
-                eprintln!("⚠ Knaben returned 0 results, falling back to SkrBT");
-                Self::emit_progress("Knaben", "completed", Some(0), None);
-            }
-            Err(e) => {
-                eprintln!("✗ Knaben failed: {}, falling back to SkrBT", e);
-                Self::emit_progress("Knaben", "failed", None, Some(e.to_string()));
+        let scraper = self;
+        let (sender, receiver) = std::sync::mpsc::channel();
+        
+        std::thread::scope(|scope| {
+            for site in SOURCES {
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    Self::emit_progress(site, "searching", None, None, vec![]);
+                    let result = match site {
+                        "Kiteyuan" => scraper.search_kiteyuan(query),
+                        "Knaben" => scraper.search_knaben(query),
+                        _ => scraper.search_skrbt(query),
+                    };
+                    let _ = sender.send((site, result));
+                });
             }
-        }
-        
-        // 3. 回退到 SkrBT（需要浏览器）
-        eprintln!("Trying SkrBT as final fallback...");
-        Self::emit_progress("SkrBT", "searching", None, None);
-        
-        match self.search_skrbt(query) {
-            Ok(results) => {
-                eprintln!("✓ SkrBT succeeded with {} results", results.len());
-                let deduplicated = Self::deduplicate_by_hash(results);
-                let count = deduplicated.len();
-                Self::emit_progress("SkrBT", "completed", Some(count), None);
-                Ok(deduplicated)
+            drop(sender);
+            
+            // 按完成顺序收集结果
+            let mut merged = Vec::new();
+            let mut failed = Vec::new();
+            for (site, result) in receiver {
+                match result {
+                    Ok(results) => {
+                        eprintln!("✓ {} succeeded with {} results", site, results.len());
+                        merged.extend(results.iter().cloned());
+                        Self::emit_progress(site, "completed", Some(results.len()), None, results);
+                    }
+                    Err(e) => {
+                        eprintln!("✗ {} failed: {}", site, e);
+                        Self::emit_progress(site, "failed", None, Some(e.to_string()), vec![]);
+                        failed.push(site);
+                    }
+                }
             }
-            Err(e) => {
-                eprintln!("✗ All sources failed (Kiteyuan, Knaben, SkrBT)");
-                Self::emit_progress("SkrBT", "failed", None, Some(e.to_string()));
-                Err(anyhow!("All search sources failed"))
+            
+            eprintln!("Collected {} results", merged.len());
+            if failed.len() == SOURCES.len() {
+                return Err(anyhow!("All search sources failed ({})", failed.join(", ")));
             }
-        }
+            Ok(merged)
+        })
     }
     
     /// Kiteyuan 网站搜索（使用 HTTP API，无需浏览器）
@@ -857,10 +791,9 @@ impl MagnetScraper {
         Ok(results)
     }
     
-    /// SkrBT 网站搜索（需要浏览器自动化）
-    fn search_skrbt(&mut self, query: &str) -> Result<Vec<MagnetResult>> {
-        // 延迟初始化浏览器
-        let browser = self.ensure_browser()?;
+    /// SkrBT 网站搜索（需要浏览器自动化，浏览器启动失败只影响 SkrBT）
+    fn search_skrbt(&self, query: &str) -> Result<Vec<MagnetResult>> {
+        let browser = self.ensure_browser().map_err(|e| anyhow!("Browser unavailable: {}", e))?;
         
         // 创建新标签页
         let tab = browser.new_tab()?;
@@ -1224,7 +1157,7 @@ struct SearchResult {
 }
 
 fn main() -> Result<()> {
-    let mut scraper = MagnetScraper::new()?;
+    let scraper = MagnetScraper::new()?;
     
     let stdin = io::stdin();
     let mut stdout = io::stdout();
@@ -1259,4 +1192,4 @@ fn main() -> Result<()> {
     }
     
     Ok(())
-}
\ No newline at end of file
+}
\ No newline at end of file
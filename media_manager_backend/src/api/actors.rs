@@ -3,12 +3,12 @@ use axum::{
     response::IntoResponse,
     Json,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error};
 
-use super::error::{ApiError, ApiResult};
+use super::error::{ApiError, ApiResult, ErrorCode};
 use super::response::{success, success_message};
-use super::scrape::{MEDIA_SCRAPE_PROGRESS, MediaScrapeProgress, MediaScrapeResponse};
+use super::scrape::{ActorScrapeProgress, MediaScrapeResponse, ACTOR_SCRAPE_PROGRESS};
 
 /// 自定义反序列化：支持字符串和布尔值
 fn deserialize_bool_from_anything<'de, D>(deserializer: D) -> Result<bool, D::Error>
@@ -42,16 +42,43 @@ use crate::{
         create_actor, get_actor, update_actor, delete_actor, list_actors,
         get_actor_with_filmography, add_actor_to_media, remove_actor_from_media,
         get_actors_for_media, find_actor_by_name, DatabaseRepository,
+        get_actor_photo_cache_settings, save_actor_photo_cache_settings,
+        add_preview_actors, remove_preview_actor, get_preview_actors_for_media, get_actor_scenes,
+        link_actor_to_media_by_name, save_actor_scrape_run, list_actor_scrape_runs, get_actor_scrape_run,
     },
+    plugins::{manager::PluginSelectError, protocol::ActorProfile},
     models::{
-        CreateActorRequest, UpdateActorRequest, AddActorToMediaRequest,
-        ActorSearchFilters,
+        Actor, CreateActorRequest, UpdateActorRequest, AddActorToMediaRequest,
+        ActorSearchFilters, ListSortOption, clamp_list_page, parse_list_sort, ActorPhotoCacheSettings, PreviewActor, TagPreviewActorsRequest,
+        MergeDuplicateActorsRequest, ActorScrapeItemResult, ActorScrapeRun,
     },
+    services::actor_dedup,
 };
 
+/// 保存演员后按设置缓存远程图片（缓存失败不影响主流程）
+async fn handle_actor_cache(state: &AppState, actor: &Actor) {
+    if let Err(e) = state.cache_service.handle_actor_save(actor).await {
+        error!("演员图片缓存处理失败: actor_id={}, error={:?}", actor.id, e);
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListActorsQuery {
-    pub query: Option<String>,
+    /// 按名称过滤（也可用 query）
+    #[serde(alias = "query")]
+    pub q: Option<String>,
+    /// 最少作品数
+    pub min_media_count: Option<i64>,
+    /// 是否有头像/写真/封面
+    pub has_photo: Option<bool>,
+    pub nationality: Option<String>,
+    /// 出演过该厂商的作品
+    pub studio: Option<String>,
+    /// 与该演员合作过的演员
+    pub co_star_id: Option<String>,
+    /// name、media_count 或 created_at，默认 name
+    pub sort_by: Option<String>,
+    pub sort_order: Option<String>,  // asc, desc
     pub limit: Option<i32>,
     pub offset: Option<i32>,
 }
@@ -61,10 +88,20 @@ pub async fn list_actors_handler(
     State(state): State<AppState>,
     Query(params): Query<ListActorsQuery>,
 ) -> ApiResult<impl IntoResponse> {
+    let (sort_by, sort_order) = parse_list_sort(params.sort_by.as_deref(), params.sort_order.as_deref(), ListSortOption::Name)
+        .map_err(ApiError::BadRequest)?;
+    let (limit, offset) = clamp_list_page(params.limit, params.offset, 20);
     let filters = ActorSearchFilters {
-        query: params.query,
-        limit: params.limit,
-        offset: params.offset,
+        query: params.q,
+        min_media_count: params.min_media_count,
+        has_photo: params.has_photo,
+        nationality: params.nationality,
+        studio: params.studio,
+        co_star_id: params.co_star_id,
+        sort_by,
+        sort_order,
+        limit: Some(limit),
+        offset: Some(offset),
     };
     
     let response = list_actors(state.database.pool(), &filters).await
@@ -86,7 +123,7 @@ pub async fn get_actor_handler(
             tracing::error!("Failed to get actor: {}", e);
             ApiError::Internal("Failed to retrieve actor".to_string())
         })?
-        .ok_or_else(|| ApiError::NotFound("Actor not found".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::ActorNotFound, "Actor not found"))?;
     
     Ok(success(actor))
 }
@@ -125,10 +162,11 @@ pub async fn create_actor_handler(
                     tracing::error!("Failed to update actor: {}", e);
                     ApiError::Internal("Failed to update actor".to_string())
                 })?
-                .ok_or_else(|| ApiError::NotFound("Actor not found".to_string()))?;
+                .ok_or_else(|| ApiError::coded(ErrorCode::ActorNotFound, "Actor not found"))?;
             
             tracing::info!("Updated existing actor: {} - photo_url={:?}, backdrop_url={:?}", 
                 actor.name, actor.photo_url, actor.backdrop_url);
+            handle_actor_cache(&state, &actor).await;
             Ok(success(actor))
         },
         Ok(None) => {
@@ -148,6 +186,7 @@ pub async fn create_actor_handler(
             
             tracing::info!("Created new actor: {} - photo_url={:?}, backdrop_url={:?}", 
                 actor.name, actor.photo_url, actor.backdrop_url);
+            handle_actor_cache(&state, &actor).await;
             Ok(success(actor))
         },
         Err(e) => {
@@ -168,8 +207,9 @@ pub async fn update_actor_handler(
             tracing::error!("Failed to update actor: {}", e);
             ApiError::Internal("Failed to update actor".to_string())
         })?
-        .ok_or_else(|| ApiError::NotFound("Actor not found".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::ActorNotFound, "Actor not found"))?;
     
+    handle_actor_cache(&state, &actor).await;
     Ok(success(actor))
 }
 
@@ -187,7 +227,7 @@ pub async fn delete_actor_handler(
     if deleted {
         Ok(success_message("Actor deleted successfully"))
     } else {
-        Err(ApiError::NotFound("Actor not found".to_string()))
+        Err(ApiError::coded(ErrorCode::ActorNotFound, "Actor not found"))
     }
 }
 
@@ -203,7 +243,7 @@ pub async fn add_actor_to_media_handler(
             tracing::error!("Failed to get media: {}", e);
             ApiError::Internal("Failed to verify media".to_string())
         })?
-        .ok_or_else(|| ApiError::NotFound("Media not found".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
     
     // 验证演员存在
     get_actor(state.database.pool(), &payload.actor_id).await
@@ -211,7 +251,7 @@ pub async fn add_actor_to_media_handler(
             tracing::error!("Failed to get actor: {}", e);
             ApiError::Internal("Failed to verify actor".to_string())
         })?
-        .ok_or_else(|| ApiError::Validation("Actor not found".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::ActorNotFound, "Actor not found"))?;
     
     add_actor_to_media(
         state.database.pool(),
@@ -260,6 +300,74 @@ pub async fn get_media_actors_handler(
     Ok(success(actors))
 }
 
+/// GET /api/media/:id/previews/actors - 获取媒体各预览图中标注的演员
+pub async fn get_preview_actors_handler(
+    State(state): State<AppState>,
+    Path(media_id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    let tags = get_preview_actors_for_media(state.database.pool(), &media_id).await?;
+    Ok(success(tags))
+}
+
+/// POST /api/media/:id/previews/:index/actors - 标注预览图中出现的演员
+pub async fn tag_preview_actors_handler(
+    State(state): State<AppState>,
+    Path((media_id, index)): Path<(String, i64)>,
+    Json(payload): Json<TagPreviewActorsRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if payload.actor_ids.is_empty() {
+        return Err(ApiError::invalid_field("actor_ids", "actor_ids cannot be empty"));
+    }
+
+    // 验证媒体存在且预览图下标有效
+    let media = state.database.repository().get_media_by_id(&media_id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    let preview_count = media.get_preview_urls().unwrap_or_default().len() as i64;
+    if !(0..preview_count).contains(&index) {
+        return Err(ApiError::invalid_field("index", format!(
+            "Preview index {} out of range, media has {} preview images", index, preview_count
+        )));
+    }
+
+    // 验证演员存在
+    for actor_id in &payload.actor_ids {
+        get_actor(state.database.pool(), actor_id).await?
+            .ok_or_else(|| ApiError::coded(ErrorCode::ActorNotFound, format!("Actor not found: {}", actor_id)))?;
+    }
+
+    let added = add_preview_actors(state.database.pool(), &media_id, index, &payload.actor_ids).await?;
+    info!("Tagged {} actors in preview {} of media {}", added, index, media_id);
+
+    let tags: Vec<PreviewActor> = get_preview_actors_for_media(state.database.pool(), &media_id).await?
+        .into_iter()
+        .filter(|tag| tag.preview_index == index)
+        .collect();
+    Ok(success(tags))
+}
+
+/// DELETE /api/media/:id/previews/:index/actors/:actor_id - 移除预览图中的演员标注
+pub async fn untag_preview_actor_handler(
+    State(state): State<AppState>,
+    Path((media_id, index, actor_id)): Path<(String, i64, String)>,
+) -> ApiResult<impl IntoResponse> {
+    if remove_preview_actor(state.database.pool(), &media_id, index, &actor_id).await? {
+        Ok(success_message("Actor untagged from preview successfully"))
+    } else {
+        Err(ApiError::NotFound("Preview actor tag not found".to_string()))
+    }
+}
+
+/// GET /api/actors/:id/scenes - 获取演员出镜的预览图（演员详情页画廊）
+pub async fn get_actor_scenes_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    get_actor(state.database.pool(), &id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::ActorNotFound, "Actor not found"))?;
+    let scenes = get_actor_scenes(state.database.pool(), &id).await?;
+    Ok(success(scenes))
+}
+
 /// 模式验证函数
 fn validate_mode(mode: &str) -> Result<(), String> {
     match mode.to_lowercase().as_str() {
@@ -284,7 +392,7 @@ pub async fn scrape_actor(
     // 2. 获取演员信息
     let actor = get_actor(state.database.pool(), &id)
         .await?
-        .ok_or_else(|| ApiError::NotFound(format!("Actor not found: {}", id)))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::ActorNotFound, format!("Actor not found: {}", id)))?;
     
     // 3. 确定刮削关键词（优先使用请求中的name，否则使用演员的名字）
     let actor_name = request.name.unwrap_or_else(|| actor.name.clone());
@@ -306,7 +414,7 @@ pub async fn scrape_actor(
     let plugins = plugin_manager_guard.list_plugins();
     let media_scraper = plugins.iter()
         .find(|p| p.config.id == "media_scraper")
-        .ok_or_else(|| ApiError::ExternalService("media_scraper plugin not found".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::PluginNotFound, "media_scraper plugin not found"))?;
     
     // 执行插件调用
     use tokio::process::Command;
@@ -335,7 +443,7 @@ pub async fn scrape_actor(
         std::time::Duration::from_secs(30),
         child.wait_with_output()
     ).await
-        .map_err(|_| ApiError::ExternalService("Plugin timeout after 30 seconds".to_string()))?
+        .map_err(|_| ApiError::coded(ErrorCode::PluginTimeout, "Plugin timeout after 30 seconds"))?
         .map_err(|e| ApiError::ExternalService(format!("Failed to get plugin output: {}", e)))?;
     
     if !output.status.success() {
@@ -424,6 +532,7 @@ pub async fn scrape_actor(
             .map_err(|e| ApiError::Internal(format!("Failed to update actor: {}", e)))?;
         
         tracing::info!("Successfully replaced actor: {} (id={}, mode=replace)", updated_actor.name, id);
+        handle_actor_cache(&state, &updated_actor).await;
         
         Ok(success(updated_actor))
     } else {
@@ -473,6 +582,7 @@ pub async fn scrape_actor(
             .ok_or_else(|| ApiError::NotFound(format!("Actor not found after update: {}", id)))?;
         
         tracing::info!("Successfully supplemented actor: {} (id={}, mode=supplement)", updated_actor.name, id);
+        handle_actor_cache(&state, &updated_actor).await;
         
         Ok(success(updated_actor))
     }
@@ -501,54 +611,164 @@ pub struct BatchScrapeActorRequest {
 
 /// 统一的批量演员刮削端点（异步模式，返回 session_id）
 /// POST /api/scrape/actor/batch
+///
+/// 进度通过 GET /api/scrape/progress/:session_id 查询，结束后结果汇总保存到
+/// GET /api/scrape/actor/runs/:session_id
 pub async fn batch_scrape_actor_unified(
     State(state): State<AppState>,
     Json(request): Json<BatchScrapeActorRequest>,
-) -> Json<MediaScrapeResponse> {
-    // 生成会话ID
-    let session_id = uuid::Uuid::new_v4().to_string();
+) -> ApiResult<Json<MediaScrapeResponse>> {
+    validate_mode(&request.mode).map_err(|e| ApiError::invalid_field("mode", e))?;
+
+    let started_at = chrono::Utc::now();
+    let session_id = ACTOR_SCRAPE_PROGRESS.create(ActorScrapeProgress {
+        status: "scraping".to_string(),
+        message: Some("正在初始化演员刮削...".to_string()),
+        mode: request.mode.to_lowercase(),
+        current: 0,
+        total: request.actor_ids.len() as i32,
+        current_actor: None,
+        item_status: "pending".to_string(),
+        success_count: 0,
+        failed_count: 0,
+        completed: false,
+        concurrent: request.concurrent,
+        processing_actors: vec![],
+        results: vec![],
+    });
     info!("开始批量演员刮削，会话ID: {}, 数量: {}, 并发: {}", session_id, request.actor_ids.len(), request.concurrent);
     
-    // 初始化进度跟踪（复用 MEDIA_SCRAPE_PROGRESS）
-    {
-        let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-        progress_map.insert(session_id.clone(), MediaScrapeProgress {
-            status: "scraping".to_string(),
-            message: Some("正在初始化演员刮削...".to_string()),
-            current: 0,
-            total: request.actor_ids.len() as i32,
-            current_item: None,
-            item_status: "pending".to_string(),
-            success_count: 0,
-            failed_count: 0,
-            completed: false,
-            concurrent: request.concurrent,
-            processing_items: vec![],
-        });
-    }
-    
     // 克隆需要的数据用于后台任务
     let session_id_clone = session_id.clone();
     let state_clone = state.clone();
-    let request_clone = request;
     
-    // 在后台任务中执行刮削
+    // 在后台任务中执行刮削，结束后保存结果汇总
     tokio::spawn(async move {
-        let result = process_batch_actor_scrape(state_clone, request_clone, session_id_clone).await;
-        if let Err(e) = result {
+        if let Err(e) = process_batch_actor_scrape(state_clone.clone(), request, session_id_clone.clone()).await {
             error!("后台批量演员刮削任务失败: {}", e);
+            ACTOR_SCRAPE_PROGRESS.update(&session_id_clone, |progress| {
+                if progress.status == "scraping" {
+                    progress.status = "failed".to_string();
+                    progress.message = Some(format!("刮削失败: {}", e));
+                }
+            });
         }
+        // 先保存汇总再标记完成，保证前端看到完成时汇总已可查询
+        save_actor_scrape_summary(&state_clone, &session_id_clone, started_at).await;
+        ACTOR_SCRAPE_PROGRESS.update(&session_id_clone, |progress| progress.completed = true);
     });
     
     // 立即返回session_id，让前端开始轮询
-    Json(MediaScrapeResponse {
+    Ok(Json(MediaScrapeResponse {
         success: true,
         session_id,
         message: "批量演员刮削任务已启动".to_string(),
-    })
+    }))
+}
+
+/// 把批量演员刮削的最终进度保存为结果汇总
+async fn save_actor_scrape_summary(state: &AppState, session_id: &str, started_at: chrono::DateTime<chrono::Utc>) {
+    let Some(progress) = ACTOR_SCRAPE_PROGRESS.get(session_id) else {
+        return;
+    };
+    let run = ActorScrapeRun {
+        id: session_id.to_string(),
+        mode: progress.mode,
+        status: progress.status,
+        message: progress.message,
+        total: progress.total as i64,
+        success_count: progress.success_count as i64,
+        failed_count: progress.failed_count as i64,
+        results: progress.results,
+        started_at,
+        finished_at: chrono::Utc::now(),
+    };
+    if let Err(e) = save_actor_scrape_run(state.database.pool(), &run).await {
+        error!("保存演员刮削结果失败: session_id={}, error={:?}", session_id, e);
+    }
 }
 
-/// 处理批量演员刮削（后台任务）
+/// 刮削前后发生变化的演员字段
+fn changed_actor_fields(before: &Actor, after: &Actor) -> Vec<String> {
+    [
+        ("avatar_url", &before.avatar_url, &after.avatar_url),
+        ("photo_url", &before.photo_url, &after.photo_url),
+        ("poster_url", &before.poster_url, &after.poster_url),
+        ("backdrop_url", &before.backdrop_url, &after.backdrop_url),
+        ("biography", &before.biography, &after.biography),
+        ("birth_date", &before.birth_date, &after.birth_date),
+        ("nationality", &before.nationality, &after.nationality),
+    ]
+    .into_iter()
+    .filter(|(_, old, new)| old != new)
+    .map(|(field, _, _)| field.to_string())
+    .collect()
+}
+
+/// 把插件返回的演员资料写入数据库，返回更新后的演员
+///
+/// replace 模式：刮削数据有值时覆盖，无值时保留原数据；supplement 模式：只更新空字段
+async fn apply_scraped_actor(
+    state: &AppState,
+    existing_actor: &Actor,
+    result: &serde_json::Value,
+    replace: bool,
+) -> Result<Actor, String> {
+    // 辅助函数：获取字符串字段
+    let get_str = |key: &str| -> Option<String> {
+        result.get(key)
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    let photo_urls = result.get("photo_urls")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(","))
+        .filter(|s| !s.is_empty());
+
+    if replace {
+        let mut updated_actor = existing_actor.clone();
+        let fields = [
+            (&mut updated_actor.avatar_url, get_str("avatar_url")),
+            (&mut updated_actor.photo_url, photo_urls),
+            (&mut updated_actor.poster_url, get_str("poster_url")),
+            (&mut updated_actor.backdrop_url, get_str("backdrop_url")),
+            (&mut updated_actor.biography, get_str("biography")),
+            (&mut updated_actor.birth_date, get_str("birth_date")),
+            (&mut updated_actor.nationality, get_str("nationality")),
+        ];
+        for (field, value) in fields {
+            if value.is_some() {
+                *field = value;
+            }
+        }
+        updated_actor.updated_at = chrono::Utc::now();
+
+        crate::database::update_actor_direct(state.database.pool(), &updated_actor).await
+            .map_err(|e| e.to_string())?;
+        Ok(updated_actor)
+    } else {
+        let fill = |current: &Option<String>, value: Option<String>| {
+            if current.as_deref().unwrap_or_default().is_empty() { value } else { None }
+        };
+        let update_request = UpdateActorRequest {
+            name: None,
+            avatar_url: fill(&existing_actor.avatar_url, get_str("avatar_url")),
+            photo_url: fill(&existing_actor.photo_url, photo_urls),
+            poster_url: fill(&existing_actor.poster_url, get_str("poster_url")),
+            backdrop_url: fill(&existing_actor.backdrop_url, get_str("backdrop_url")),
+            biography: fill(&existing_actor.biography, get_str("biography")),
+            birth_date: fill(&existing_actor.birth_date, get_str("birth_date")),
+            nationality: fill(&existing_actor.nationality, get_str("nationality")),
+        };
+
+        update_actor(state.database.pool(), &existing_actor.id, update_request).await
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "Actor not found".to_string())
+    }
+}
+
+/// 处理批量演员刮削（后台任务，结束时只设置状态，由调用方保存结果汇总后标记完成）
 async fn process_batch_actor_scrape(
     state: AppState,
     request: BatchScrapeActorRequest,
@@ -562,29 +782,11 @@ async fn process_batch_actor_scrape(
     
     info!("开始执行批量演员刮削: {} 个演员", request.actor_ids.len());
     
-    // 验证 mode 参数
-    if let Err(e) = validate_mode(&request.mode) {
-        let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
-            progress.status = "failed".to_string();
-            progress.message = Some(format!("参数错误: {}", e));
-            progress.completed = true;
-        }
-        return Err(e);
-    }
-    
-    // 收集演员名称和信息
-    let mut actor_names = Vec::new();
-    let mut actor_map = std::collections::HashMap::new();
-    let mut actor_info_map = std::collections::HashMap::new();
-    
+    // 收集演员信息
+    let mut actors = Vec::new();
     for actor_id in &request.actor_ids {
         match get_actor(state.database.pool(), actor_id).await {
-            Ok(Some(actor)) => {
-                actor_names.push(actor.name.clone());
-                actor_map.insert(actor.name.clone(), actor_id.clone());
-                actor_info_map.insert(actor_id.clone(), actor);
-            }
+            Ok(Some(actor)) => actors.push(actor),
             Ok(None) => {
                 warn!("Actor not found: {}", actor_id);
             }
@@ -594,23 +796,19 @@ async fn process_batch_actor_scrape(
         }
     }
     
-    if actor_names.is_empty() {
-        let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
+    if actors.is_empty() {
+        ACTOR_SCRAPE_PROGRESS.update(&session_id, |progress| {
             progress.status = "completed".to_string();
             progress.message = Some("没有找到有效的演员".to_string());
-            progress.completed = true;
-        }
+            progress.total = 0;
+        });
         return Ok(());
     }
     
     // 更新总数
-    {
-        let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
-            progress.total = actor_names.len() as i32;
-        }
-    }
+    ACTOR_SCRAPE_PROGRESS.update(&session_id, |progress| {
+        progress.total = actors.len() as i32;
+    });
     
     // 获取插件
     let plugin_manager = state.plugin_manager.read().await;
@@ -618,17 +816,16 @@ async fn process_batch_actor_scrape(
     let media_scraper = match plugins.iter().find(|p| p.config.id == "media_scraper") {
         Some(p) => p,
         None => {
-            let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-            if let Some(progress) = progress_map.get_mut(&session_id) {
+            ACTOR_SCRAPE_PROGRESS.update(&session_id, |progress| {
                 progress.status = "failed".to_string();
                 progress.message = Some("media_scraper 插件未找到".to_string());
-                progress.completed = true;
-            }
+            });
             return Err("Plugin not found".to_string());
         }
     };
     
     // 构建请求
+    let actor_names: Vec<&str> = actors.iter().map(|actor| actor.name.as_str()).collect();
     let request_json = json!({
         "action": "batch_scrape_actors",
         "actor_names": actor_names,
@@ -666,45 +863,35 @@ async fn process_batch_actor_scrape(
                 if let Ok(progress_data) = serde_json::from_str::<serde_json::Value>(json_str) {
                     let current = progress_data.get("current").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
                     let total = progress_data.get("total").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
-                    let item_name = progress_data.get("item_name").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                    let actor_name = progress_data.get("item_name").and_then(|v| v.as_str()).unwrap_or("").to_string();
                     let status = progress_data.get("status").and_then(|v| v.as_str()).unwrap_or("scraping").to_string();
                     let error_msg = progress_data.get("error").and_then(|v| v.as_str()).map(String::from);
-                    // 解析正在处理的项目列表（并发模式）
-                    let processing_items: Vec<String> = progress_data.get("processing_items")
+                    // 解析正在处理的演员列表（并发模式）
+                    let processing_actors: Vec<String> = progress_data.get("processing_items")
                         .and_then(|v| v.as_array())
                         .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
                         .unwrap_or_default();
                     
-                    info!("Actor scrape progress: {}/{} - {} ({})", current, total, item_name, status);
+                    info!("Actor scrape progress: {}/{} - {} ({})", current, total, actor_name, status);
                     
-                    // 更新进度
-                    let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-                    if let Some(progress) = progress_map.get_mut(&session_id_for_stderr) {
+                    // 更新进度（成功/失败计数在保存阶段统计）
+                    ACTOR_SCRAPE_PROGRESS.update(&session_id_for_stderr, |progress| {
                         progress.current = current;
                         progress.total = total;
-                        progress.current_item = Some(item_name.clone());
+                        progress.current_actor = Some(actor_name.clone());
                         progress.item_status = status.clone();
                         
-                        // 更新正在处理的项目列表（并发模式）
-                        if progress.concurrent && !processing_items.is_empty() {
-                            progress.processing_items = processing_items;
-                        } else if progress.concurrent && status == "scraping" && !item_name.is_empty() {
-                            // 并发模式下，如果没有 processing_items，将当前项目添加到列表
-                            if !progress.processing_items.contains(&item_name) {
-                                progress.processing_items.push(item_name.clone());
-                            }
+                        // 更新正在处理的演员列表（并发模式）
+                        if progress.concurrent && !processing_actors.is_empty() {
+                            progress.processing_actors = processing_actors;
+                        } else if progress.concurrent && status == "scraping" && !actor_name.is_empty()
+                            && !progress.processing_actors.contains(&actor_name) {
+                            progress.processing_actors.push(actor_name.clone());
                         }
                         
                         // 并发模式下，完成或失败时从列表中移除
                         if progress.concurrent && (status == "completed" || status == "failed") {
-                            progress.processing_items.retain(|x| x != &item_name);
-                        }
-                        
-                        // 更新成功/失败计数
-                        if status == "completed" {
-                            progress.success_count += 1;
-                        } else if status == "failed" {
-                            progress.failed_count += 1;
+                            progress.processing_actors.retain(|x| x != &actor_name);
                         }
                         
                         // 更新消息
@@ -712,7 +899,7 @@ async fn process_batch_actor_scrape(
                             progress.message = Some(format!("刮削失败: {}", err));
                         } else if status == "scraping" {
                             if progress.concurrent {
-                                let active_count = progress.processing_items.len();
+                                let active_count = progress.processing_actors.len();
                                 progress.message = Some(format!("正在并发刮削 {} 个演员 ({}/{})", active_count, current, total));
                             } else {
                                 progress.message = Some(format!("正在刮削 {}/{}", current, total));
@@ -720,7 +907,7 @@ async fn process_batch_actor_scrape(
                         } else if status == "completed" {
                             progress.message = Some(format!("已完成 {}/{}", current, total));
                         }
-                    }
+                    });
                 }
             }
         }
@@ -760,158 +947,343 @@ async fn process_batch_actor_scrape(
     let status = child.wait().await.map_err(|e| e.to_string())?;
     
     if !status.success() {
-        let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
+        ACTOR_SCRAPE_PROGRESS.update(&session_id, |progress| {
             progress.status = "failed".to_string();
             progress.message = Some("插件执行失败".to_string());
-            progress.completed = true;
-        }
+        });
         return Err("Plugin execution failed".to_string());
     }
     
+    let Some(response) = final_response else {
+        return Err("Plugin returned no result".to_string());
+    };
+    if !response.get("success").and_then(|v| v.as_bool()).unwrap_or(false) {
+        let error_msg = response.get("error").and_then(|v| v.as_str()).unwrap_or("Unknown error");
+        ACTOR_SCRAPE_PROGRESS.update(&session_id, |progress| {
+            progress.status = "failed".to_string();
+            progress.message = Some(format!("刮削失败: {}", error_msg));
+        });
+        return Ok(());
+    }
+    
     // 处理结果并更新数据库
-    if let Some(response) = final_response {
-        let success = response.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
+    let results_data = response.get("data").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let is_replace_mode = request.mode.to_lowercase() == "replace";
+    let total = actors.len();
+    
+    for (index, existing_actor) in actors.iter().enumerate() {
+        ACTOR_SCRAPE_PROGRESS.update(&session_id, |progress| {
+            progress.current = index as i32 + 1;
+            progress.current_actor = Some(existing_actor.name.clone());
+            progress.item_status = "saving".to_string();
+            progress.message = Some(format!("正在保存 {} ({}/{})", existing_actor.name, index + 1, total));
+        });
         
-        if success {
-            let results_data = response.get("data").and_then(|v| v.as_array()).cloned().unwrap_or_default();
-            let is_replace_mode = request.mode.to_lowercase() == "replace";
-            
-            let mut success_count = 0;
-            let mut failed_count = 0;
-            
-            for result in results_data {
-                let name = match result.get("name").and_then(|v| v.as_str()) {
-                    Some(n) => n,
-                    None => continue,
-                };
-                
-                let actor_id = match actor_map.get(name) {
-                    Some(id) => id,
-                    None => continue,
-                };
-                
-                let existing_actor = match actor_info_map.get(actor_id) {
-                    Some(actor) => actor,
-                    None => {
-                        failed_count += 1;
-                        continue;
+        let scraped = results_data.iter()
+            .find(|result| result.get("name").and_then(|v| v.as_str()) == Some(existing_actor.name.as_str()));
+        let outcome = match scraped {
+            None => Err("No profile returned by plugin".to_string()),
+            Some(result) if result.get("success").and_then(|v| v.as_bool()) == Some(false) => {
+                Err(result.get("error").and_then(|v| v.as_str()).unwrap_or("Unknown error").to_string())
+            }
+            Some(result) => apply_scraped_actor(&state, existing_actor, result, is_replace_mode).await,
+        };
+        
+        let item = match outcome {
+            Ok(updated_actor) => {
+                info!("Successfully scraped actor: {} (mode: {})", existing_actor.name, request.mode);
+                let photo_downloaded = match state.cache_service.cache_actor_photos_now(&updated_actor).await {
+                    Ok(cached) => cached.is_some_and(|result| result.refreshed > 0),
+                    Err(e) => {
+                        error!("演员图片缓存处理失败: actor_id={}, error={:?}", updated_actor.id, e);
+                        false
                     }
                 };
-                
-                // 辅助函数：获取字符串字段
-                let get_str = |key: &str| -> Option<String> {
-                    result.get(key)
-                        .and_then(|v| v.as_str())
-                        .filter(|s| !s.is_empty())
-                        .map(String::from)
-                };
-                
-                let get_photo_urls = || -> Option<String> {
-                    result.get("photo_urls")
-                        .and_then(|v| v.as_array())
-                        .map(|arr| arr.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(","))
-                        .filter(|s| !s.is_empty())
-                };
-                
-                if is_replace_mode {
-                    // Replace 模式：刮削数据有值时覆盖，无值时保留原数据
-                    let mut updated_actor = existing_actor.clone();
-                    
-                    // 有值则覆盖，无值则保留原数据
-                    if let Some(val) = get_str("avatar_url") {
-                        updated_actor.avatar_url = Some(val);
-                    }
-                    if let Some(val) = get_photo_urls() {
-                        updated_actor.photo_url = Some(val);
-                    }
-                    if let Some(val) = get_str("poster_url") {
-                        updated_actor.poster_url = Some(val);
-                    }
-                    if let Some(val) = get_str("backdrop_url") {
-                        updated_actor.backdrop_url = Some(val);
-                    }
-                    if let Some(val) = get_str("biography") {
-                        updated_actor.biography = Some(val);
-                    }
-                    if let Some(val) = get_str("birth_date") {
-                        updated_actor.birth_date = Some(val);
-                    }
-                    if let Some(val) = get_str("nationality") {
-                        updated_actor.nationality = Some(val);
-                    }
-                    updated_actor.updated_at = chrono::Utc::now();
-                    
-                    match crate::database::update_actor_direct(state.database.pool(), &updated_actor).await {
-                        Ok(_) => {
-                            success_count += 1;
-                            info!("Successfully replaced actor: {} (mode: replace)", name);
-                        }
-                        Err(e) => {
-                            error!("Failed to replace actor {}: {}", name, e);
-                            failed_count += 1;
-                        }
-                    }
-                } else {
-                    // Supplement 模式：只更新空字段
-                    let update_request = UpdateActorRequest {
-                        name: None,
-                        avatar_url: if existing_actor.avatar_url.is_none() || existing_actor.avatar_url.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
-                            get_str("avatar_url")
-                        } else { None },
-                        photo_url: if existing_actor.photo_url.is_none() || existing_actor.photo_url.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
-                            get_photo_urls()
-                        } else { None },
-                        poster_url: if existing_actor.poster_url.is_none() || existing_actor.poster_url.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
-                            get_str("poster_url")
-                        } else { None },
-                        backdrop_url: if existing_actor.backdrop_url.is_none() || existing_actor.backdrop_url.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
-                            get_str("backdrop_url")
-                        } else { None },
-                        biography: if existing_actor.biography.is_none() || existing_actor.biography.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
-                            get_str("biography")
-                        } else { None },
-                        birth_date: if existing_actor.birth_date.is_none() || existing_actor.birth_date.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
-                            get_str("birth_date")
-                        } else { None },
-                        nationality: if existing_actor.nationality.is_none() || existing_actor.nationality.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
-                            get_str("nationality")
-                        } else { None },
-                    };
-                    
-                    match update_actor(state.database.pool(), actor_id, update_request).await {
-                        Ok(Some(_)) => {
-                            success_count += 1;
-                            info!("Successfully supplemented actor: {} (mode: supplement)", name);
-                        }
-                        _ => {
-                            failed_count += 1;
-                        }
-                    }
+                ActorScrapeItemResult {
+                    actor_id: existing_actor.id.clone(),
+                    name: existing_actor.name.clone(),
+                    status: "completed".to_string(),
+                    fields_updated: changed_actor_fields(existing_actor, &updated_actor),
+                    photo_downloaded,
+                    error: None,
                 }
             }
-            
-            // 更新最终进度
-            let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-            if let Some(progress) = progress_map.get_mut(&session_id) {
-                progress.status = "completed".to_string();
-                progress.message = Some(format!("刮削完成: {} 成功, {} 失败", success_count, failed_count));
-                progress.success_count = success_count;
-                progress.failed_count = failed_count;
-                progress.completed = true;
-            }
-        } else {
-            let error_msg = response.get("error").and_then(|v| v.as_str()).unwrap_or("Unknown error");
-            let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-            if let Some(progress) = progress_map.get_mut(&session_id) {
-                progress.status = "failed".to_string();
-                progress.message = Some(format!("刮削失败: {}", error_msg));
-                progress.completed = true;
+            Err(e) => {
+                error!("Failed to scrape actor {}: {}", existing_actor.name, e);
+                ActorScrapeItemResult {
+                    actor_id: existing_actor.id.clone(),
+                    name: existing_actor.name.clone(),
+                    status: "failed".to_string(),
+                    fields_updated: vec![],
+                    photo_downloaded: false,
+                    error: Some(e),
+                }
             }
-        }
+        };
+        
+        ACTOR_SCRAPE_PROGRESS.update(&session_id, |progress| {
+            progress.item_status = item.status.clone();
+            progress.results.push(item);
+            progress.success_count = progress.results.iter().filter(|r| r.status == "completed").count() as i32;
+            progress.failed_count = progress.results.len() as i32 - progress.success_count;
+        });
     }
     
+    // 更新最终进度
+    ACTOR_SCRAPE_PROGRESS.update(&session_id, |progress| {
+        progress.status = "completed".to_string();
+        progress.message = Some(format!("刮削完成: {} 成功, {} 失败", progress.success_count, progress.failed_count));
+        progress.current_actor = None;
+        progress.processing_actors.clear();
+    });
+    
     Ok(())
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ListActorScrapeRunsQuery {
+    pub limit: Option<i64>,
+}
+
+/// GET /api/scrape/actor/runs - 最近的批量演员刮削结果
+pub async fn list_actor_scrape_runs_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ListActorScrapeRunsQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let runs = list_actor_scrape_runs(state.database.pool(), limit).await?;
+    Ok(success(runs))
+}
+
+/// GET /api/scrape/actor/runs/:id - 单次批量演员刮削结果
+pub async fn get_actor_scrape_run_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    let run = get_actor_scrape_run(state.database.pool(), &id).await?
+        .ok_or_else(|| ApiError::NotFound(format!("Actor scrape run not found: {}", id)))?;
+    Ok(success(run))
+}
+
+// ============ Import Actors From Plugin ============
+
+/// 从插件导入厂商演员名单请求
+#[derive(Debug, Deserialize)]
+pub struct ImportActorsFromPluginRequest {
+    /// 厂商名
+    pub studio: String,
+    /// 刮削插件ID，默认使用发售日历的插件
+    pub plugin_id: Option<String>,
+    /// 已有演员的更新模式：supplement（补全，默认）或 replace（替换）
+    #[serde(default = "default_import_mode")]
+    pub mode: String,
+}
+
+fn default_import_mode() -> String {
+    "supplement".to_string()
+}
+
+/// 从插件导入厂商演员名单结果
+#[derive(Debug, Default, Serialize)]
+pub struct ImportActorsFromPluginResponse {
+    pub studio: String,
+    pub plugin_id: String,
+    /// 插件返回的演员数
+    pub total: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub failed: usize,
+    /// 按名字新关联到已有媒体的数量
+    pub linked_media: u64,
+    pub errors: Vec<String>,
+}
+
+/// 按更新模式生成已有演员的更新请求（没有需要更新的字段时返回 None）
+fn profile_update_request(actor: &Actor, profile: &ActorProfile, replace: bool) -> Option<UpdateActorRequest> {
+    let pick = |current: &Option<String>, scraped: Option<String>| {
+        let scraped = scraped.filter(|v| !v.trim().is_empty())?;
+        let fill = replace || current.as_deref().is_none_or(str::is_empty);
+        (fill && current.as_deref() != Some(scraped.as_str())).then_some(scraped)
+    };
+    let photo_urls = Some(profile.photo_urls.join(","));
+    let request = UpdateActorRequest {
+        name: None,
+        avatar_url: pick(&actor.avatar_url, profile.avatar_url.clone()),
+        photo_url: pick(&actor.photo_url, photo_urls),
+        poster_url: pick(&actor.poster_url, profile.poster_url.clone()),
+        backdrop_url: pick(&actor.backdrop_url, profile.backdrop_url.clone()),
+        biography: pick(&actor.biography, profile.biography.clone()),
+        birth_date: pick(&actor.birth_date, profile.birth_date.clone()),
+        nationality: pick(&actor.nationality, profile.nationality.clone()),
+    };
+    let changed = request.avatar_url.is_some() || request.photo_url.is_some() || request.poster_url.is_some()
+        || request.backdrop_url.is_some() || request.biography.is_some() || request.birth_date.is_some()
+        || request.nationality.is_some();
+    changed.then_some(request)
+}
+
+/// 新增或更新一个演员，返回保存后的演员和是否为新建（None 表示没有变化）
+async fn upsert_actor_profile(
+    state: &AppState,
+    profile: &ActorProfile,
+    replace: bool,
+) -> Result<(Actor, Option<bool>), sqlx::Error> {
+    let pool = state.database.pool();
+    match find_actor_by_name(pool, &profile.name).await? {
+        Some(existing) => match profile_update_request(&existing, profile, replace) {
+            Some(request) => {
+                let actor = update_actor(pool, &existing.id, request).await?.unwrap_or(existing);
+                Ok((actor, Some(false)))
+            }
+            None => Ok((existing, None)),
+        },
+        None => {
+            let actor = create_actor(pool, CreateActorRequest {
+                id: None,
+                name: profile.name.clone(),
+                avatar_url: profile.avatar_url.clone(),
+                photo_url: Some(profile.photo_urls.join(",")).filter(|v| !v.is_empty()),
+                poster_url: profile.poster_url.clone(),
+                backdrop_url: profile.backdrop_url.clone(),
+                biography: profile.biography.clone(),
+                birth_date: profile.birth_date.clone(),
+                nationality: profile.nationality.clone(),
+            }).await?;
+            Ok((actor, Some(true)))
+        }
+    }
+}
+
+/// POST /api/actors/import-from-plugin - 从插件导入厂商的全部演员并按名字关联到已有媒体
+pub async fn import_actors_from_plugin_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ImportActorsFromPluginRequest>,
+) -> ApiResult<impl IntoResponse> {
+    let studio = request.studio.trim().to_string();
+    if studio.is_empty() {
+        return Err(ApiError::invalid_field("studio", "studio cannot be empty"));
+    }
+    validate_mode(&request.mode).map_err(|e| ApiError::invalid_field("mode", e))?;
+    let replace = request.mode.eq_ignore_ascii_case("replace");
+    // 未指定插件时使用声明了 list_actors 能力的插件
+    let plugin_id = match request.plugin_id {
+        Some(plugin_id) => plugin_id,
+        None => state.plugin_manager.read().await
+            .plugin_with_capability(crate::plugins::protocol::CAPABILITY_LIST_ACTORS)
+            .ok_or_else(|| ApiError::BadRequest("No plugin supports actor lists".to_string()))?,
+    };
+
+    let profiles = {
+        let manager = state.plugin_manager.read().await;
+        manager.list_actors(&plugin_id, &studio).await
+    }
+    .map_err(|e| match e.downcast_ref::<PluginSelectError>() {
+        Some(PluginSelectError::Unsupported(message)) => ApiError::BadRequest(message.clone()),
+        _ => ApiError::ExternalService(format!("Failed to list actors from plugin: {}", e)),
+    })?;
+
+    let mut result = ImportActorsFromPluginResponse {
+        studio: studio.clone(),
+        plugin_id,
+        total: profiles.len(),
+        ..Default::default()
+    };
+    let mut seen = std::collections::HashSet::new();
+    for mut profile in profiles {
+        profile.name = profile.name.trim().to_string();
+        if profile.name.is_empty() || !seen.insert(profile.name.clone()) {
+            result.unchanged += 1;
+            continue;
+        }
+
+        let actor = match upsert_actor_profile(&state, &profile, replace).await {
+            Ok((actor, Some(true))) => {
+                result.created += 1;
+                actor
+            }
+            Ok((actor, Some(false))) => {
+                result.updated += 1;
+                actor
+            }
+            Ok((actor, None)) => {
+                result.unchanged += 1;
+                actor
+            }
+            Err(e) => {
+                error!("Failed to import actor {}: {}", profile.name, e);
+                result.failed += 1;
+                result.errors.push(format!("Actor '{}': {}", profile.name, e));
+                continue;
+            }
+        };
+        handle_actor_cache(&state, &actor).await;
 
+        match link_actor_to_media_by_name(state.database.pool(), &actor.id, &actor.name).await {
+            Ok(linked) => result.linked_media += linked,
+            Err(e) => {
+                warn!("Failed to link actor {} to media: {}", actor.name, e);
+                result.errors.push(format!("Actor '{}': failed to link media: {}", actor.name, e));
+            }
+        }
+    }
+
+    info!(
+        "Imported actors of studio {}: created={}, updated={}, unchanged={}, failed={}, linked_media={}",
+        studio, result.created, result.updated, result.unchanged, result.failed, result.linked_media
+    );
+    Ok(success(result))
+}
+
+// ============ Actor Photo Cache Settings ============
+
+/// 获取演员图片缓存设置
+/// GET /api/settings/actor-photo-cache
+pub async fn get_actor_photo_cache_settings_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let settings = get_actor_photo_cache_settings(state.database.pool()).await?;
+    Ok(success(settings))
+}
+
+/// 保存演员图片缓存设置
+/// PUT /api/settings/actor-photo-cache
+pub async fn update_actor_photo_cache_settings_handler(
+    State(state): State<AppState>,
+    Json(settings): Json<ActorPhotoCacheSettings>,
+) -> ApiResult<impl IntoResponse> {
+    settings.validate().map_err(ApiError::Validation)?;
+    save_actor_photo_cache_settings(state.database.pool(), &settings).await?;
+    Ok(success(settings))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateSuggestionsQuery {
+    /// 为 true 时重新扫描，否则返回最近一次的报告
+    #[serde(default, deserialize_with = "deserialize_bool_from_anything")]
+    pub refresh: bool,
+}
+
+/// GET /api/actors/duplicate-suggestions - 获取疑似重复演员的合并建议
+pub async fn get_duplicate_actor_suggestions_handler(
+    State(state): State<AppState>,
+    Query(params): Query<DuplicateSuggestionsQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let report = actor_dedup::latest_report(state.database.pool(), params.refresh).await?;
+    Ok(success(report))
+}
+
+/// POST /api/actors/duplicate-suggestions/merge - 批量合并重复演员（不传 groups 时合并全部建议）
+pub async fn merge_duplicate_actors_handler(
+    State(state): State<AppState>,
+    Json(request): Json<MergeDuplicateActorsRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(groups) = &request.groups {
+        if groups.iter().any(|g| g.source_ids.is_empty()) {
+            return Err(ApiError::invalid_field("groups", "Each group needs at least one source_id"));
+        }
+    }
+    let result = actor_dedup::merge_groups(state.database.pool(), request.groups).await?;
+    info!("合并重复演员: {} 组, {} 个演员", result.merged_groups, result.merged_actors);
+    Ok(success(result))
+}
@@ -4,16 +4,20 @@
 // - 获取缓存配置
 // - 更新缓存配置
 // - 更新单个刮削器配置
+// - 为已有演员补缓存图片
+// - 读取缓存文件
 
 use axum::{
+    body::Body,
     extract::{Path, State},
-    response::IntoResponse,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use crate::services::cache::{CacheConfig, CacheService, ConfigManager, ScraperCacheConfig};
+use crate::services::cache::{ArtworkRefreshResult, CacheConfig, CacheService, ConfigManager, ScraperCacheConfig};
 
 use super::error::{ApiError, ApiResult};
 use super::response::success;
@@ -299,6 +303,63 @@ pub async fn clear_orphaned_cache(
     })))
 }
 
+/// 为已有演员补缓存图片
+///
+/// 把所有仍指向远程 URL 的演员图片下载并转换为 WebP，不受自动缓存开关影响，
+/// 缓存的字段遵循演员图片缓存设置。
+///
+/// # 端点
+/// POST /api/cache/actors/backfill
+pub async fn backfill_actor_photos(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let pool = state.database.pool();
+    let settings = crate::database::get_actor_photo_cache_settings(pool).await?;
+    let actors = crate::database::list_actors_with_remote_images(pool).await?;
+    tracing::info!("开始补缓存演员图片: {} 个演员", actors.len());
+
+    let mut total = ArtworkRefreshResult::default();
+    for actor in &actors {
+        let result = state.cache_service.cache_actor_photos(actor, &settings).await;
+        total.refreshed += result.refreshed;
+        total.failed += result.failed;
+        total.skipped += result.skipped;
+    }
+
+    tracing::info!(
+        "演员图片补缓存完成: 演员={}, 成功={}, 失败={}, 跳过={}",
+        actors.len(), total.refreshed, total.failed, total.skipped
+    );
+
+    Ok(success(serde_json::json!({
+        "actors": actors.len(),
+        "cached": total.refreshed,
+        "failed": total.failed,
+        "skipped": total.skipped,
+    })))
+}
+
+/// 读取缓存文件（本地没有时从远程缓存存储读取）
+///
+/// # 端点
+/// GET /cache/*path
+pub async fn serve_cached_file(
+    State(state): State<AppState>,
+    Path(path): Path<String>,
+) -> Result<Response, StatusCode> {
+    if path.split(['/', '\\']).any(|segment| segment == ".." || segment.is_empty()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let key = format!("cache/{}", path);
+    let stream = state.cache_service.read_cached_file(&key).await.ok_or(StatusCode::NOT_FOUND)?;
+    let content_type = match std::path::Path::new(&path).extension().and_then(|e| e.to_str()) {
+        Some("webp") => "image/webp",
+        Some("mp4") => "video/mp4",
+        _ => "application/octet-stream",
+    };
+    Ok(([(header::CONTENT_TYPE, content_type)], Body::from_stream(stream)).into_response())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
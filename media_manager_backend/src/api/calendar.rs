@@ -0,0 +1,140 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+
+use crate::database;
+use crate::models::{CreateFollowRequest, FOLLOW_TARGET_TYPES};
+use crate::services::release_calendar::{refresh_release_calendar, to_ical};
+use super::AppState;
+use super::error::{ApiError, ApiResult};
+use super::response::{success, success_message};
+
+/// 默认查询未来多少天的发售日历
+const DEFAULT_CALENDAR_DAYS: i64 = 90;
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarParams {
+    /// 起始日期 YYYY-MM-DD（默认今天）
+    pub from: Option<String>,
+    /// 结束日期 YYYY-MM-DD（默认 90 天后）
+    pub to: Option<String>,
+    /// 输出格式：json（默认）或 ical
+    pub format: Option<String>,
+}
+
+// ============ Follow Handlers ============
+
+/// 获取关注列表
+pub async fn list_follows_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let follows = database::list_follows(state.database.pool()).await
+        .map_err(|e| {
+            tracing::error!("Failed to list follows: {}", e);
+            ApiError::Internal("Failed to retrieve follows".to_string())
+        })?;
+
+    Ok(success(follows))
+}
+
+/// 关注系列/厂商/演员
+pub async fn create_follow_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateFollowRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if !FOLLOW_TARGET_TYPES.contains(&payload.target_type.as_str()) {
+        return Err(ApiError::Validation(format!(
+            "Invalid target_type '{}'. Must be one of: {}",
+            payload.target_type,
+            FOLLOW_TARGET_TYPES.join(", ")
+        )));
+    }
+    if payload.target_name.trim().is_empty() {
+        return Err(ApiError::Validation("Target name cannot be empty".to_string()));
+    }
+
+    let follow = database::create_follow(state.database.pool(), payload).await
+        .map_err(|e| {
+            tracing::error!("Failed to create follow: {}", e);
+            if e.to_string().contains("UNIQUE constraint") {
+                ApiError::Conflict("Already following".to_string())
+            } else {
+                ApiError::Internal("Failed to create follow".to_string())
+            }
+        })?;
+
+    Ok(success(follow))
+}
+
+/// 取消关注
+pub async fn delete_follow_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    database::delete_follow(state.database.pool(), &id).await
+        .map_err(|e| {
+            tracing::error!("Failed to delete follow: {}", e);
+            if e.to_string().contains("not found") {
+                ApiError::NotFound("Follow not found".to_string())
+            } else {
+                ApiError::Internal("Failed to delete follow".to_string())
+            }
+        })?;
+
+    Ok(success_message("Follow deleted successfully"))
+}
+
+// ============ Calendar Handlers ============
+
+/// 获取发售日历（支持 format=ical 导出）
+pub async fn get_calendar_handler(
+    Query(params): Query<CalendarParams>,
+    State(state): State<AppState>,
+) -> ApiResult<Response> {
+    let today = Utc::now().date_naive();
+    let from = params.from
+        .unwrap_or_else(|| today.format("%Y-%m-%d").to_string());
+    let to = params.to
+        .unwrap_or_else(|| (today + Duration::days(DEFAULT_CALENDAR_DAYS)).format("%Y-%m-%d").to_string());
+
+    for date in [&from, &to] {
+        if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+            return Err(ApiError::Validation(format!("Invalid date '{}', expected YYYY-MM-DD", date)));
+        }
+    }
+
+    let entries = database::list_release_calendar(state.database.pool(), &from, &to).await
+        .map_err(|e| {
+            tracing::error!("Failed to list release calendar: {}", e);
+            ApiError::Internal("Failed to retrieve release calendar".to_string())
+        })?;
+
+    match params.format.as_deref() {
+        Some("ical") | Some("ics") => Ok((
+            [
+                (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"release_calendar.ics\""),
+            ],
+            to_ical(&entries),
+        ).into_response()),
+        _ => Ok(success(entries).into_response()),
+    }
+}
+
+/// 立即刷新发售日历
+pub async fn refresh_calendar_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let result = refresh_release_calendar(state.database.pool(), &state.plugin_manager).await
+        .map_err(|e| {
+            tracing::error!("Failed to refresh release calendar: {}", e);
+            ApiError::Internal("Failed to refresh release calendar".to_string())
+        })?;
+
+    Ok(success(result))
+}
@@ -1,14 +1,16 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
     Json,
 };
 
 use crate::models::{
-    AddToCollectionRequest, WatchStatus, CollectionResponse
+    AddToCollectionRequest, WatchStatus, CollectionResponse, StatusHistoryFilters,
+    DEFAULT_STATUS_HISTORY_LIMIT, MAX_STATUS_HISTORY_LIMIT,
 };
+use crate::database::DatabaseRepository;
 use super::AppState;
-use super::error::{ApiError, ApiResult};
+use super::error::{ApiError, ApiResult, ErrorCode};
 use super::response::{success, success_message};
 
 pub async fn get_collections(
@@ -28,6 +30,88 @@ pub async fn get_collections(
     Ok(success(responses))
 }
 
+/// 收藏统计：各观看状态数量、平均评分、已观看时长和每个标签的完成度
+pub async fn get_collection_stats(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let stats = crate::database::get_collection_stats(state.database.pool()).await
+        .map_err(|e| {
+            tracing::error!("Failed to get collection stats: {}", e);
+            ApiError::Internal("Failed to retrieve collection stats".to_string())
+        })?;
+    
+    Ok(success(stats))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct StatusHistoryParams {
+    /// 只返回变为该状态的记录
+    pub status: Option<WatchStatus>,
+    /// 日期范围（YYYY-MM-DD，包含两端）
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl StatusHistoryParams {
+    fn filters(self, media_id: Option<String>) -> ApiResult<StatusHistoryFilters> {
+        for (field, date) in [("from", &self.from), ("to", &self.to)] {
+            if let Some(date) = date {
+                if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+                    return Err(ApiError::invalid_field(field, format!("Invalid date '{}', expected YYYY-MM-DD", date)));
+                }
+            }
+        }
+        Ok(StatusHistoryFilters {
+            media_id,
+            to_status: self.status,
+            from: self.from,
+            to: self.to,
+            limit: self.limit.unwrap_or(DEFAULT_STATUS_HISTORY_LIMIT).clamp(1, MAX_STATUS_HISTORY_LIMIT),
+        })
+    }
+}
+
+/// 所有收藏的观看状态变化（例如 status=Completed&from=2024-05-01 查询本月看完的）
+pub async fn get_status_history(
+    Query(params): Query<StatusHistoryParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let filters = params.filters(None)?;
+    let changes = crate::database::list_status_history(state.database.pool(), &filters).await
+        .map_err(|e| {
+            tracing::error!("Failed to list status history: {}", e);
+            ApiError::Internal("Failed to retrieve status history".to_string())
+        })?;
+    
+    Ok(success(changes))
+}
+
+/// 单个收藏的观看状态时间线
+pub async fn get_collection_status_history(
+    Path(media_id): Path<String>,
+    Query(params): Query<StatusHistoryParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let in_collection = state.database.repository().is_in_collection(&media_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to check collection: {}", e);
+            ApiError::Internal("Failed to retrieve status history".to_string())
+        })?;
+    if !in_collection {
+        return Err(ApiError::NotFound("Media not in collection".to_string()));
+    }
+    
+    let filters = params.filters(Some(media_id))?;
+    let changes = crate::database::list_status_history(state.database.pool(), &filters).await
+        .map_err(|e| {
+            tracing::error!("Failed to list status history: {}", e);
+            ApiError::Internal("Failed to retrieve status history".to_string())
+        })?;
+    
+    Ok(success(changes))
+}
+
 pub async fn add_to_collection(
     State(state): State<AppState>,
     Json(payload): Json<AddToCollectionRequest>,
@@ -44,7 +128,7 @@ pub async fn add_to_collection(
             tracing::error!("Failed to add to collection: {}", e);
             let error_msg = e.to_string();
             if error_msg.contains("not found") {
-                ApiError::NotFound("Media not found".to_string())
+                ApiError::coded(ErrorCode::MediaNotFound, "Media not found")
             } else if error_msg.contains("already in collection") {
                 ApiError::Conflict("Media already in collection".to_string())
             } else {
@@ -0,0 +1,105 @@
+//! 响应压缩
+//!
+//! 通过环境变量配置：
+//! - COMPRESSION_ALGORITHMS：逗号分隔的算法（gzip、br、zstd），默认全部启用，设为 none 时不压缩
+//! - COMPRESSION_MIN_SIZE：小于该字节数的响应不压缩，默认 1024
+//!
+//! 视频、音频、图片、SSE 以及支持范围请求的响应（带 Accept-Ranges / Content-Range）不压缩，
+//! 否则压缩后的长度与 Range 中的字节偏移对不上
+
+use axum::http::{header, Extensions, HeaderMap, StatusCode, Version};
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+
+/// 支持的压缩算法
+pub const COMPRESSION_ALGORITHMS: &[&str] = &["gzip", "br", "zstd"];
+
+/// 默认的最小压缩字节数
+pub const DEFAULT_COMPRESSION_MIN_SIZE: u16 = 1024;
+
+/// 压缩配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionConfig {
+    pub gzip: bool,
+    pub br: bool,
+    pub zstd: bool,
+    pub min_size: u16,
+}
+
+impl CompressionConfig {
+    /// 解析配置（algorithms 为空时启用全部算法，无效的算法名忽略）
+    pub fn parse(algorithms: Option<&str>, min_size: Option<&str>) -> Self {
+        let algorithms: Vec<String> = match algorithms.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(value) => value.split(',').map(|a| a.trim().to_lowercase()).collect(),
+            None => COMPRESSION_ALGORITHMS.iter().map(|a| a.to_string()).collect(),
+        };
+        for algorithm in &algorithms {
+            if algorithm != "none" && !COMPRESSION_ALGORITHMS.contains(&algorithm.as_str()) {
+                tracing::warn!("Unknown compression algorithm '{}', ignored", algorithm);
+            }
+        }
+        let enabled = |name: &str| algorithms.iter().any(|a| a == name);
+        Self {
+            gzip: enabled("gzip"),
+            br: enabled("br") || enabled("brotli"),
+            zstd: enabled("zstd"),
+            min_size: min_size
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_COMPRESSION_MIN_SIZE),
+        }
+    }
+
+    /// 从环境变量读取配置
+    pub fn from_env() -> Self {
+        Self::parse(
+            std::env::var("COMPRESSION_ALGORITHMS").ok().as_deref(),
+            std::env::var("COMPRESSION_MIN_SIZE").ok().as_deref(),
+        )
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.gzip || self.br || self.zstd
+    }
+}
+
+/// 支持范围请求的响应（视频流、文件代理等）
+fn is_range_response(status: StatusCode, _: Version, headers: &HeaderMap, _: &Extensions) -> bool {
+    status == StatusCode::PARTIAL_CONTENT
+        || headers.contains_key(header::ACCEPT_RANGES)
+        || headers.contains_key(header::CONTENT_RANGE)
+}
+
+/// 创建压缩层（未启用任何算法时响应原样返回）
+pub fn compression_layer(config: &CompressionConfig) -> CompressionLayer<impl Predicate> {
+    let predicate = SizeAbove::new(config.min_size)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE)
+        .and(NotForContentType::const_new("video/"))
+        .and(NotForContentType::const_new("audio/"))
+        .and(|status, version, headers: &HeaderMap, extensions: &Extensions| {
+            !is_range_response(status, version, headers, extensions)
+        });
+    CompressionLayer::new()
+        .gzip(config.gzip)
+        .br(config.br)
+        .zstd(config.zstd)
+        .no_deflate()
+        .compress_when(predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let all = CompressionConfig::parse(None, None);
+        assert_eq!(all, CompressionConfig { gzip: true, br: true, zstd: true, min_size: DEFAULT_COMPRESSION_MIN_SIZE });
+
+        let gzip = CompressionConfig::parse(Some(" GZIP, lz4 "), Some("256"));
+        assert_eq!(gzip, CompressionConfig { gzip: true, br: false, zstd: false, min_size: 256 });
+
+        assert!(!CompressionConfig::parse(Some("none"), None).enabled());
+    }
+}
@@ -0,0 +1,94 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::database;
+use crate::models::{EnrichmentSettings, ENRICHMENT_REASON_MANUAL, ENRICHMENT_STATUSES};
+use crate::services::enrichment;
+use super::AppState;
+use super::error::{ApiError, ApiResult, ErrorCode};
+use super::response::{success, success_message};
+
+const DEFAULT_JOB_LIMIT: i64 = 100;
+const MAX_JOB_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct EnrichmentJobsParams {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+// ============ Enrichment Jobs ============
+
+/// 获取后台补全任务（任务管理器中显示）
+/// GET /api/enrichment/jobs
+pub async fn list_enrichment_jobs_handler(
+    Query(params): Query<EnrichmentJobsParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(status) = params.status.as_deref() {
+        if !ENRICHMENT_STATUSES.contains(&status) {
+            return Err(ApiError::invalid_field("status", format!(
+                "Invalid status '{}', expected one of: {}",
+                status,
+                ENRICHMENT_STATUSES.join(", ")
+            )));
+        }
+    }
+    let limit = params.limit.unwrap_or(DEFAULT_JOB_LIMIT).clamp(1, MAX_JOB_LIMIT);
+    let jobs = database::list_enrichment_jobs(state.database.pool(), params.status.as_deref(), limit).await?;
+    Ok(success(jobs))
+}
+
+/// 获取媒体的补全任务和文件技术信息
+/// GET /api/media/:id/enrichment
+pub async fn get_media_enrichment_handler(
+    Path(media_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let pool = state.database.pool();
+    let job = database::get_enrichment_job(pool, &media_id).await?;
+    let probes = database::list_media_probes(pool, &media_id).await?;
+    Ok(success(serde_json::json!({ "job": job, "probes": probes })))
+}
+
+/// 手动把媒体加入补全队列（不受自动补全开关影响）
+/// POST /api/media/:id/enrich
+pub async fn enqueue_enrichment_handler(
+    Path(media_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let pool = state.database.pool();
+    if database::get_media_with(pool, &media_id).await?.is_none() {
+        return Err(ApiError::coded(ErrorCode::MediaNotFound, "Media not found"));
+    }
+    if !enrichment::enqueue(pool, &media_id, ENRICHMENT_REASON_MANUAL).await? {
+        return Err(ApiError::Conflict("Enrichment is already running for this media".to_string()));
+    }
+    Ok(success_message("Enrichment queued"))
+}
+
+// ============ Enrichment Settings ============
+
+/// 获取自动补全设置
+/// GET /api/settings/enrichment
+pub async fn get_enrichment_settings_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let settings = database::get_enrichment_settings(state.database.pool()).await?;
+    Ok(success(settings))
+}
+
+/// 保存自动补全设置
+/// PUT /api/settings/enrichment
+pub async fn update_enrichment_settings_handler(
+    State(state): State<AppState>,
+    Json(settings): Json<EnrichmentSettings>,
+) -> ApiResult<impl IntoResponse> {
+    settings.validate().map_err(ApiError::Validation)?;
+    database::save_enrichment_settings(state.database.pool(), &settings).await?;
+    Ok(success(settings))
+}
@@ -3,9 +3,175 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use serde::Serialize;
 use serde_json::json;
 use std::fmt;
 
+use crate::plugins::manager::PluginSelectError;
+use crate::plugins::protocol::{LocalizedMessage, PluginError};
+
+/// 定义错误码：同一份列表生成枚举（含序列化名称）、`ALL` 和 `as_str`，避免各处的名称不一致
+macro_rules! error_codes {
+    ($($variant:ident => $name:literal,)+) => {
+        /// 稳定的机器可读错误码
+        ///
+        /// 前端按错误码判断错误类型，不要匹配 message 文本（文本可能随时调整）。
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+        pub enum ErrorCode {
+            $(#[serde(rename = $name)] $variant,)+
+        }
+
+        impl ErrorCode {
+            /// 错误码目录
+            pub const ALL: &'static [ErrorCode] = &[$(ErrorCode::$variant,)+];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => $name,)+
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    // 通用错误码（未细分的错误按变体归入这些类别）
+    NotFound => "NOT_FOUND",
+    ValidationError => "VALIDATION_ERROR",
+    BadRequest => "BAD_REQUEST",
+    Unauthorized => "UNAUTHORIZED",
+    Forbidden => "FORBIDDEN",
+    Conflict => "CONFLICT",
+    InternalError => "INTERNAL_ERROR",
+    DatabaseError => "DATABASE_ERROR",
+    ExternalServiceError => "EXTERNAL_SERVICE_ERROR",
+    // 资源不存在
+    MediaNotFound => "MEDIA_NOT_FOUND",
+    ActorNotFound => "ACTOR_NOT_FOUND",
+    StudioNotFound => "STUDIO_NOT_FOUND",
+    SeriesNotFound => "SERIES_NOT_FOUND",
+    PluginNotFound => "PLUGIN_NOT_FOUND",
+    // 参数验证
+    ValidationField => "VALIDATION_FIELD",
+    // 插件
+    PluginTimeout => "PLUGIN_TIMEOUT",
+    PluginFailed => "PLUGIN_FAILED",
+    PluginInvalidResponse => "PLUGIN_INVALID_RESPONSE",
+    // 幂等键
+    IdempotencyKeyReused => "IDEMPOTENCY_KEY_REUSED",
+    IdempotencyInProgress => "IDEMPOTENCY_IN_PROGRESS",
+}
+
+impl ErrorCode {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ErrorCode::NotFound
+            | ErrorCode::MediaNotFound
+            | ErrorCode::ActorNotFound
+            | ErrorCode::StudioNotFound
+            | ErrorCode::SeriesNotFound
+            | ErrorCode::PluginNotFound => StatusCode::NOT_FOUND,
+            ErrorCode::ValidationError
+            | ErrorCode::ValidationField
+            | ErrorCode::IdempotencyKeyReused => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::BadRequest => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+            ErrorCode::Conflict | ErrorCode::IdempotencyInProgress => StatusCode::CONFLICT,
+            ErrorCode::InternalError | ErrorCode::DatabaseError => StatusCode::INTERNAL_SERVER_ERROR,
+            ErrorCode::ExternalServiceError
+            | ErrorCode::PluginFailed
+            | ErrorCode::PluginInvalidResponse => StatusCode::BAD_GATEWAY,
+            ErrorCode::PluginTimeout => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+
+    /// 兼容旧版响应的 error.type 字段
+    pub fn error_type(&self) -> &'static str {
+        match self.status() {
+            StatusCode::NOT_FOUND => "not_found",
+            StatusCode::UNPROCESSABLE_ENTITY => "validation_error",
+            StatusCode::BAD_REQUEST => "bad_request",
+            StatusCode::UNAUTHORIZED => "unauthorized",
+            StatusCode::FORBIDDEN => "forbidden",
+            StatusCode::CONFLICT => "conflict",
+            StatusCode::BAD_GATEWAY | StatusCode::GATEWAY_TIMEOUT => "external_service_error",
+            _ if *self == ErrorCode::DatabaseError => "database_error",
+            _ => "internal_error",
+        }
+    }
+
+    /// 错误码说明（中文）
+    pub fn description(&self) -> &'static str {
+        match self {
+            ErrorCode::NotFound => "资源不存在",
+            ErrorCode::ValidationError => "请求数据验证失败",
+            ErrorCode::BadRequest => "请求参数错误",
+            ErrorCode::Unauthorized => "未认证",
+            ErrorCode::Forbidden => "无权访问",
+            ErrorCode::Conflict => "资源冲突",
+            ErrorCode::InternalError => "服务器内部错误",
+            ErrorCode::DatabaseError => "数据库错误",
+            ErrorCode::ExternalServiceError => "外部服务错误",
+            ErrorCode::MediaNotFound => "媒体不存在",
+            ErrorCode::ActorNotFound => "演员不存在",
+            ErrorCode::StudioNotFound => "厂商不存在",
+            ErrorCode::SeriesNotFound => "系列不存在",
+            ErrorCode::PluginNotFound => "插件不存在或未加载",
+            ErrorCode::ValidationField => "字段验证失败",
+            ErrorCode::PluginTimeout => "插件执行超时",
+            ErrorCode::PluginFailed => "插件执行失败或返回错误",
+            ErrorCode::PluginInvalidResponse => "插件返回的数据格式不符合协议",
+            ErrorCode::IdempotencyKeyReused => "幂等键已用于不同的请求",
+            ErrorCode::IdempotencyInProgress => "相同幂等键的请求正在处理中",
+        }
+    }
+
+    /// 错误码说明（英文）
+    pub fn description_en(&self) -> &'static str {
+        match self {
+            ErrorCode::NotFound => "Resource not found",
+            ErrorCode::ValidationError => "Request validation failed",
+            ErrorCode::BadRequest => "Bad request",
+            ErrorCode::Unauthorized => "Unauthorized",
+            ErrorCode::Forbidden => "Forbidden",
+            ErrorCode::Conflict => "Resource conflict",
+            ErrorCode::InternalError => "An internal server error occurred",
+            ErrorCode::DatabaseError => "An internal database error occurred",
+            ErrorCode::ExternalServiceError => "External service error",
+            ErrorCode::MediaNotFound => "Media not found",
+            ErrorCode::ActorNotFound => "Actor not found",
+            ErrorCode::StudioNotFound => "Studio not found",
+            ErrorCode::SeriesNotFound => "Series not found",
+            ErrorCode::PluginNotFound => "Plugin not found or not loaded",
+            ErrorCode::ValidationField => "Invalid field, see details",
+            ErrorCode::PluginTimeout => "Plugin timed out",
+            ErrorCode::PluginFailed => "Plugin failed or returned an error",
+            ErrorCode::PluginInvalidResponse => "Plugin returned data that does not match the protocol",
+            ErrorCode::IdempotencyKeyReused => "Idempotency key was already used for a different request",
+            ErrorCode::IdempotencyInProgress => "A request with this idempotency key is still in progress",
+        }
+    }
+
+    /// 错误码的多语言消息
+    pub fn localized_message(&self) -> LocalizedMessage {
+        LocalizedMessage::new(self.description(), self.description_en())
+    }
+}
+
+/// 字段级错误详情
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorDetail {
+    pub field: String,
+    pub message: String,
+}
+
+impl ErrorDetail {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { field: field.into(), message: message.into() }
+    }
+}
+
 /// 统一的API错误类型
 #[derive(Debug)]
 pub enum ApiError {
@@ -27,6 +193,110 @@ pub enum ApiError {
     ExternalService(String),
     /// 请求参数错误
     BadRequest(String),
+    /// 带明确错误码的错误
+    Coded {
+        code: ErrorCode,
+        message: String,
+        details: Vec<ErrorDetail>,
+        /// 多语言消息（按 Accept-Language 选择）
+        localized: Option<LocalizedMessage>,
+        /// 插件返回的错误码
+        plugin_code: Option<String>,
+    },
+}
+
+impl ApiError {
+    /// 创建带错误码的错误
+    pub fn coded(code: ErrorCode, message: impl Into<String>) -> Self {
+        ApiError::Coded { code, message: message.into(), details: Vec::new(), localized: None, plugin_code: None }
+    }
+
+    /// 创建带中英文消息的错误（默认消息为英文）
+    pub fn localized(code: ErrorCode, zh: impl Into<String>, en: impl Into<String>) -> Self {
+        let localized = LocalizedMessage::new(zh, en);
+        ApiError::Coded {
+            code,
+            message: localized.get("en").unwrap_or_default().to_string(),
+            details: Vec::new(),
+            localized: Some(localized),
+            plugin_code: None,
+        }
+    }
+
+    /// 给消息加上前缀（多语言消息同时加上对应语言的前缀）
+    ///
+    /// message 只加与其语言相同的前缀：有多语言消息时看 message 取自哪种语言，否则看是否包含中文
+    pub fn prefixed(self, zh: &str, en: &str) -> Self {
+        match self {
+            ApiError::Coded { code, message, details, localized, plugin_code } => {
+                let is_zh = match &localized {
+                    Some(localized) => localized.zh.as_deref() == Some(message.as_str()),
+                    None => message.chars().any(|c| matches!(c, '\u{4E00}'..='\u{9FFF}')),
+                };
+                ApiError::Coded {
+                    code,
+                    message: format!("{}{}", if is_zh { zh } else { en }, message),
+                    details,
+                    localized: localized.map(|m| m.prefixed(zh, en)),
+                    plugin_code,
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// 单个字段验证失败
+    pub fn invalid_field(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::invalid_fields(vec![ErrorDetail::new(field, message)])
+    }
+
+    /// 多个字段验证失败
+    pub fn invalid_fields(details: Vec<ErrorDetail>) -> Self {
+        let fields: Vec<&str> = details.iter().map(|d| d.field.as_str()).collect();
+        ApiError::Coded {
+            code: ErrorCode::ValidationField,
+            message: format!("Invalid field: {}", fields.join(", ")),
+            details,
+            localized: None,
+            plugin_code: None,
+        }
+    }
+
+    /// 插件调用错误（超时单独归类，保留插件返回的错误码和多语言消息）
+    pub fn plugin(err: anyhow::Error) -> Self {
+        if let Some(select_error) = err.chain().find_map(|cause| cause.downcast_ref::<PluginSelectError>()) {
+            return select_error.clone().into();
+        }
+        if let Some(plugin_error) = err.chain().find_map(|cause| cause.downcast_ref::<PluginError>()) {
+            let plugin_error = plugin_error.clone();
+            return ApiError::Coded {
+                code: ErrorCode::PluginFailed,
+                message: err.to_string(),
+                details: Vec::new(),
+                localized: plugin_error.localized,
+                plugin_code: plugin_error.code,
+            };
+        }
+        let timed_out = err.chain().any(|cause| cause.is::<tokio::time::error::Elapsed>());
+        let code = if timed_out { ErrorCode::PluginTimeout } else { ErrorCode::PluginFailed };
+        ApiError::coded(code, err.to_string())
+    }
+
+    /// 错误码（未指定错误码的变体返回对应的通用错误码）
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::Database(_) => ErrorCode::DatabaseError,
+            ApiError::NotFound(_) => ErrorCode::NotFound,
+            ApiError::Validation(_) => ErrorCode::ValidationError,
+            ApiError::Unauthorized(_) => ErrorCode::Unauthorized,
+            ApiError::Forbidden(_) => ErrorCode::Forbidden,
+            ApiError::Conflict(_) => ErrorCode::Conflict,
+            ApiError::Internal(_) => ErrorCode::InternalError,
+            ApiError::ExternalService(_) => ErrorCode::ExternalServiceError,
+            ApiError::BadRequest(_) => ErrorCode::BadRequest,
+            ApiError::Coded { code, .. } => *code,
+        }
+    }
 }
 
 impl fmt::Display for ApiError {
@@ -41,6 +311,7 @@ impl fmt::Display for ApiError {
             ApiError::Internal(msg) => write!(f, "Internal error: {}", msg),
             ApiError::ExternalService(msg) => write!(f, "External service error: {}", msg),
             ApiError::BadRequest(msg) => write!(f, "Bad request: {}", msg),
+            ApiError::Coded { code, message, .. } => write!(f, "{}: {}", code.as_str(), message),
         }
     }
 }
@@ -57,6 +328,37 @@ impl From<sqlx::Error> for ApiError {
     }
 }
 
+/// 从模型验证错误转换（带出错字段）
+impl From<crate::models::ValidationError> for ApiError {
+    fn from(err: crate::models::ValidationError) -> Self {
+        ApiError::invalid_field(err.field(), err.to_string())
+    }
+}
+
+/// 从插件错误转换
+impl From<PluginError> for ApiError {
+    fn from(err: PluginError) -> Self {
+        ApiError::Coded {
+            code: ErrorCode::PluginFailed,
+            message: err.message,
+            details: Vec::new(),
+            localized: err.localized,
+            plugin_code: err.code,
+        }
+    }
+}
+
+/// 从插件选择错误转换
+impl From<PluginSelectError> for ApiError {
+    fn from(err: PluginSelectError) -> Self {
+        match err {
+            PluginSelectError::NotFound(_) => ApiError::coded(ErrorCode::PluginNotFound, err.to_string()),
+            PluginSelectError::NoneSupports(message) => ApiError::NotFound(message),
+            PluginSelectError::Unsupported(message) => ApiError::BadRequest(message),
+        }
+    }
+}
+
 /// 从anyhow::Error转换
 impl From<anyhow::Error> for ApiError {
     fn from(err: anyhow::Error) -> Self {
@@ -67,30 +369,35 @@ impl From<anyhow::Error> for ApiError {
 /// 实现IntoResponse，将错误转换为HTTP响应
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, error_type, message) = match self {
+        let code = self.code();
+        let mut localized = None;
+        let mut plugin_code = None;
+        let (status, error_type, message, details) = match self {
             ApiError::Database(ref e) => {
                 tracing::error!("Database error: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "database_error",
                     "An internal database error occurred".to_string(),
+                    Vec::new(),
                 )
             }
-            ApiError::NotFound(ref msg) => (StatusCode::NOT_FOUND, "not_found", msg.clone()),
+            ApiError::NotFound(ref msg) => (StatusCode::NOT_FOUND, "not_found", msg.clone(), Vec::new()),
             ApiError::Validation(ref msg) => {
-                (StatusCode::UNPROCESSABLE_ENTITY, "validation_error", msg.clone())
+                (StatusCode::UNPROCESSABLE_ENTITY, "validation_error", msg.clone(), Vec::new())
             }
             ApiError::Unauthorized(ref msg) => {
-                (StatusCode::UNAUTHORIZED, "unauthorized", msg.clone())
+                (StatusCode::UNAUTHORIZED, "unauthorized", msg.clone(), Vec::new())
             }
-            ApiError::Forbidden(ref msg) => (StatusCode::FORBIDDEN, "forbidden", msg.clone()),
-            ApiError::Conflict(ref msg) => (StatusCode::CONFLICT, "conflict", msg.clone()),
+            ApiError::Forbidden(ref msg) => (StatusCode::FORBIDDEN, "forbidden", msg.clone(), Vec::new()),
+            ApiError::Conflict(ref msg) => (StatusCode::CONFLICT, "conflict", msg.clone(), Vec::new()),
             ApiError::Internal(ref msg) => {
                 tracing::error!("Internal error: {}", msg);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "internal_error",
                     "An internal server error occurred".to_string(),
+                    Vec::new(),
                 )
             }
             ApiError::ExternalService(ref msg) => {
@@ -99,25 +406,88 @@ impl IntoResponse for ApiError {
                     StatusCode::BAD_GATEWAY,
                     "external_service_error",
                     msg.clone(),
+                    Vec::new(),
                 )
             }
             ApiError::BadRequest(ref msg) => {
-                (StatusCode::BAD_REQUEST, "bad_request", msg.clone())
+                (StatusCode::BAD_REQUEST, "bad_request", msg.clone(), Vec::new())
+            }
+            ApiError::Coded { code, message, details, localized: coded_localized, plugin_code: coded_plugin_code } => {
+                localized = coded_localized;
+                plugin_code = coded_plugin_code;
+                let status = code.status();
+                if status.is_server_error() {
+                    tracing::error!("{}: {}", code.as_str(), message);
+                }
+                // 内部错误不向客户端暴露细节
+                let message = if status == StatusCode::INTERNAL_SERVER_ERROR {
+                    "An internal server error occurred".to_string()
+                } else {
+                    message
+                };
+                (status, code.error_type(), message, details)
             }
         };
 
+        let mut error = json!({
+            "type": error_type,
+            "code": code,
+            "message": message,
+        });
+        if !details.is_empty() {
+            error["details"] = json!(details);
+        }
+        if let Some(plugin_code) = &plugin_code {
+            error["plugin_code"] = json!(plugin_code);
+        }
         let body = Json(json!({
             "success": false,
-            "error": {
-                "type": error_type,
-                "message": message,
-            }
+            "error": error,
         }));
 
-        (status, body).into_response()
+        // 附带结构化错误，供 i18n 中间件按语言重写消息
+        let mut response = (status, body).into_response();
+        response.extensions_mut().insert(ErrorPayload { code, message, details, localized, plugin_code });
+        response
     }
 }
 
+/// 错误响应的结构化内容（存放在响应扩展中）
+#[derive(Debug, Clone)]
+pub struct ErrorPayload {
+    pub code: ErrorCode,
+    pub message: String,
+    pub details: Vec<ErrorDetail>,
+    pub localized: Option<LocalizedMessage>,
+    pub plugin_code: Option<String>,
+}
+
+/// 错误码目录条目
+#[derive(Debug, Serialize)]
+pub struct ErrorCatalogEntry {
+    pub code: ErrorCode,
+    pub status: u16,
+    #[serde(rename = "type")]
+    pub error_type: &'static str,
+    pub description: &'static str,
+    pub description_en: &'static str,
+}
+
+/// 获取错误码目录
+/// GET /api/errors
+pub async fn get_error_catalog() -> impl IntoResponse {
+    let entries: Vec<ErrorCatalogEntry> = ErrorCode::ALL.iter()
+        .map(|code| ErrorCatalogEntry {
+            code: *code,
+            status: code.status().as_u16(),
+            error_type: code.error_type(),
+            description: code.description(),
+            description_en: code.description_en(),
+        })
+        .collect();
+    super::response::success(entries)
+}
+
 /// Result类型别名
 pub type ApiResult<T> = Result<T, ApiError>;
 
@@ -154,4 +524,81 @@ mod tests {
         let api_error: ApiError = sqlx_error.into();
         assert!(matches!(api_error, ApiError::NotFound(_)));
     }
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(ApiError::NotFound("x".to_string()).code(), ErrorCode::NotFound);
+        let error = ApiError::coded(ErrorCode::MediaNotFound, "Media not found");
+        assert_eq!(error.code().as_str(), "MEDIA_NOT_FOUND");
+        assert_eq!(error.code().status(), StatusCode::NOT_FOUND);
+        assert_eq!(ErrorCode::PluginTimeout.error_type(), "external_service_error");
+        assert_eq!(serde_json::to_value(ErrorCode::ValidationField).unwrap(), "VALIDATION_FIELD");
+    }
+
+    #[test]
+    fn test_plugin_timeout_code() {
+        let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+        let elapsed = rt.block_on(async {
+            tokio::time::timeout(std::time::Duration::from_millis(1), std::future::pending::<()>()).await
+        });
+        let err = anyhow::Context::context(elapsed, "Plugin timeout").unwrap_err();
+        assert_eq!(ApiError::plugin(err).code(), ErrorCode::PluginTimeout);
+        assert_eq!(ApiError::plugin(anyhow::anyhow!("boom")).code(), ErrorCode::PluginFailed);
+    }
+
+    #[test]
+    fn test_error_code_names_match_serde() {
+        for code in ErrorCode::ALL {
+            assert_eq!(serde_json::to_value(code).unwrap(), code.as_str());
+        }
+    }
+
+    #[test]
+    fn test_prefixed_keeps_message_language() {
+        let prefix = |message: &str| match ApiError::coded(ErrorCode::PluginFailed, message).prefixed("刮削失败: ", "Scrape failed: ") {
+            ApiError::Coded { message, .. } => message,
+            other => panic!("unexpected error: {:?}", other),
+        };
+        assert_eq!(prefix("Timed out"), "Scrape failed: Timed out");
+        assert_eq!(prefix("未找到"), "刮削失败: 未找到");
+    }
+
+    #[test]
+    fn test_plugin_select_error_code() {
+        let code = |err: PluginSelectError| ApiError::plugin(anyhow::Error::new(err).context("Scrape by URL")).code();
+        assert_eq!(code(PluginSelectError::NotFound("x".to_string())), ErrorCode::PluginNotFound);
+        assert_eq!(code(PluginSelectError::NoneSupports("any text".to_string())), ErrorCode::NotFound);
+        assert_eq!(code(PluginSelectError::Unsupported("any text".to_string())), ErrorCode::BadRequest);
+    }
+
+    #[test]
+    fn test_plugin_localized_message() {
+        let plugin_error = PluginError {
+            code: Some("not_found".to_string()),
+            message: "未找到".to_string(),
+            localized: Some(LocalizedMessage::new("未找到", "Not found")),
+        };
+        match ApiError::plugin(plugin_error.into()).prefixed("刮削失败: ", "Scrape failed: ") {
+            ApiError::Coded { code, message, localized, plugin_code, .. } => {
+                assert_eq!(code, ErrorCode::PluginFailed);
+                assert_eq!(message, "刮削失败: 未找到");
+                assert_eq!(localized.unwrap().get("en"), Some("Scrape failed: Not found"));
+                assert_eq!(plugin_code.as_deref(), Some("not_found"));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_field_details() {
+        let error: ApiError = crate::models::ValidationError::InvalidYear(3000).into();
+        match error {
+            ApiError::Coded { code, details, .. } => {
+                assert_eq!(code, ErrorCode::ValidationField);
+                assert_eq!(details.len(), 1);
+                assert_eq!(details[0].field, "year");
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,100 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+
+use crate::database;
+use crate::models::{MediaItemResponse, PaginatedResponse, FAVORITE_ENTITY_TYPES};
+use super::AppState;
+use super::error::{ApiError, ApiResult};
+use super::media::localize_responses;
+use super::response::{success, success_message};
+
+#[derive(Debug, Deserialize)]
+pub struct FavoriteListParams {
+    pub entity_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FavoriteFeedParams {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+    /// 只返回收藏之后新增的媒体
+    #[serde(default)]
+    pub new_only: bool,
+}
+
+fn validate_entity_type(entity_type: &str) -> Result<(), ApiError> {
+    if !FAVORITE_ENTITY_TYPES.contains(&entity_type) {
+        return Err(ApiError::Validation(format!(
+            "Invalid entity type '{}'. Must be one of: {}", entity_type, FAVORITE_ENTITY_TYPES.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// 获取收藏的演员/厂商/系列
+/// GET /api/favorites
+pub async fn list_favorites_handler(
+    Query(params): Query<FavoriteListParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(ref entity_type) = params.entity_type {
+        validate_entity_type(entity_type)?;
+    }
+
+    let favorites = database::list_favorites(state.database.pool(), params.entity_type.as_deref()).await?;
+    Ok(success(favorites))
+}
+
+/// 收藏演员/厂商/系列
+/// PUT /api/favorites/:entity_type/:id
+pub async fn add_favorite_handler(
+    Path((entity_type, id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    validate_entity_type(&entity_type)?;
+
+    let favorite = database::add_favorite(state.database.pool(), &entity_type, &id).await?
+        .ok_or_else(|| ApiError::NotFound(format!("{} not found", entity_type)))?;
+    Ok(success(favorite))
+}
+
+/// 取消收藏
+/// DELETE /api/favorites/:entity_type/:id
+pub async fn remove_favorite_handler(
+    Path((entity_type, id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    validate_entity_type(&entity_type)?;
+
+    if !database::remove_favorite(state.database.pool(), &entity_type, &id).await? {
+        return Err(ApiError::NotFound("Favorite not found".to_string()));
+    }
+    Ok(success_message("Favorite removed successfully"))
+}
+
+/// 收藏动态：收藏的演员/厂商/系列下未看完的媒体，最新添加的在前
+/// GET /api/feed/favorites
+pub async fn favorite_feed_handler(
+    Query(params): Query<FavoriteFeedParams>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<impl IntoResponse> {
+    let page = params.page.unwrap_or(1).max(1) as i32;
+    let page_size = params.limit.unwrap_or(20).clamp(1, 100) as i32;
+
+    let (items, total) = database::get_favorite_feed(
+        state.database.pool(),
+        params.new_only,
+        page_size,
+        (page - 1) * page_size,
+    ).await?;
+
+    let mut responses: Vec<MediaItemResponse> = items.into_iter().map(MediaItemResponse::from).collect();
+    localize_responses(&state, &headers, &mut responses).await;
+
+    Ok(success(PaginatedResponse::new(responses, total, page, page_size)))
+}
@@ -1,16 +1,16 @@
 use axum::{
-    extract::{State, Path},
+    extract::{State, Path, Query},
     http::StatusCode,
     response::Json,
 };
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error};
-use std::sync::Arc;
-use tokio::sync::RwLock;
 use std::collections::HashMap;
 
+use crate::services::progress::{ProgressRegistry, SessionProgress};
+
 lazy_static::lazy_static! {
-    static ref SCRAPE_PROGRESS: Arc<RwLock<HashMap<String, AutoScrapeProgress>>> = Arc::new(RwLock::new(HashMap::new()));
+    static ref SCRAPE_PROGRESS: ProgressRegistry<AutoScrapeProgress> = ProgressRegistry::new();
 }
 
 fn deserialize_bool_from_anything<'de, D>(deserializer: D) -> Result<bool, D::Error>
@@ -39,22 +39,48 @@ where
 }
 
 use crate::api::AppState;
-use crate::services::{FileScanner, FileMatcher, FileGrouper, MatchResult, GroupMatchResult, ScannedFile, FileGroup};
+use crate::services::{FileScanner, FileMatcher, FileGrouper, MatchCandidate, MatchResult, GroupMatchResult, ScannedExtra, ScannedFile, FileGroup};
+use crate::plugins::protocol::ScrapeResult;
 use crate::database::repository::{DatabaseRepository, IgnoredFile};
-use crate::models::MediaFile;
+use crate::models::{derive_watch_state, group_by_edition, normalize_edition, AutoConfirmSettings, Collection, WatchStatus, FilenameRule, LibraryRoot, MediaEdition, MediaExtra, MediaFile, EXTRA_TYPES, MediaItem, MediaType, ScrapeFieldMask, MediaUpgrade, ScanSession, ENRICHMENT_REASON_SCAN, SCAN_FILE_IGNORED, SCAN_FILE_MATCHED, SCAN_FILE_UNMATCHED};
+use crate::services::file_scanner::ParsedFilename;
+use crate::services::quality;
+use crate::services::library_watcher::refresh_root_status;
+use crate::services::import_hooks::spawn_import_hooks;
+use crate::services::enrichment;
+use crate::services::search_query;
 
 #[derive(Debug, Deserialize)]
 pub struct ScanRequest {
+    #[serde(default)]
     pub paths: Vec<String>,
+    /// 要扫描的媒体库根目录（paths 和 root_ids 都为空时扫描全部根目录）
+    #[serde(default)]
+    pub root_ids: Vec<String>,
+    #[serde(default = "default_recursive")]
     pub recursive: bool,
 }
 
+fn default_recursive() -> bool {
+    true
+}
+
 #[derive(Debug, Serialize)]
 pub struct ScanResponse {
     pub success: bool,
+    /// 扫描会话ID（结果已保存，可稍后继续匹配）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
     pub total_files: usize,
     pub scanned_files: Vec<ScannedFile>,
     pub file_groups: Vec<FileGroup>,
+    /// 因无法访问而跳过的根目录
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skipped_roots: Vec<String>,
+    /// Extras/ 文件夹中发现的附加内容数
+    pub extras_found: usize,
+    /// 已关联到媒体的附加内容数
+    pub extras_linked: u64,
     pub message: String,
 }
 
@@ -62,6 +88,10 @@ pub struct ScanResponse {
 pub struct MatchRequest {
     pub scanned_files: Vec<ScannedFile>,
     pub file_groups: Vec<FileGroup>,
+    /// 每个文件返回的候选数（默认 5）
+    pub top_n: Option<usize>,
+    /// 是否自动确认高置信度匹配（默认使用保存的设置）
+    pub auto_confirm: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -72,6 +102,9 @@ pub struct MatchResponse {
     pub exact_matches: usize,
     pub fuzzy_matches: usize,
     pub no_matches: usize,
+    pub upgrades_detected: usize,
+    /// 按阈值自动确认的匹配数
+    pub auto_confirmed: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -91,6 +124,9 @@ pub struct FileInfo {
     pub file_size: i64,
     pub part_number: Option<i32>,
     pub part_label: Option<String>,
+    /// 所属版本（为空表示默认版本）
+    #[serde(default)]
+    pub edition: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -131,6 +167,12 @@ pub struct AutoScrapeProgress {
     pub failed_count: usize,
 }
 
+impl SessionProgress for AutoScrapeProgress {
+    fn is_finished(&self) -> bool {
+        self.status == "completed"
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct ScrapeFileResult {
     pub file_path: String,
@@ -140,21 +182,93 @@ pub struct ScrapeFileResult {
     pub error: Option<String>,
 }
 
+/// 确定要扫描的目录：请求中的路径加上指定的根目录，都未指定时使用全部根目录
+///
+/// 无法访问的根目录会被跳过（返回在第二项中），避免把掉线的网络共享当成空目录扫描
+async fn resolve_scan_paths(state: &AppState, request: &ScanRequest) -> Result<(Vec<String>, Vec<String>), (StatusCode, String)> {
+    let mut paths = request.paths.clone();
+    if !request.paths.is_empty() && request.root_ids.is_empty() {
+        return Ok((paths, Vec::new()));
+    }
+
+    let pool = state.database.pool();
+    let roots = crate::database::list_library_roots(pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load library roots: {}", e)))?;
+    // 对象存储上的根目录不支持扫描
+    let selected: Vec<LibraryRoot> = if request.root_ids.is_empty() {
+        roots.into_iter().filter(|root| root.is_local()).collect()
+    } else {
+        let mut selected = Vec::new();
+        for id in &request.root_ids {
+            let root = roots.iter()
+                .find(|root| &root.id == id)
+                .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Library root {} not found", id)))?;
+            if !root.is_local() {
+                return Err((StatusCode::BAD_REQUEST, format!("Library root {} is on {} storage and cannot be scanned", root.path, root.storage_type)));
+            }
+            selected.push(root.clone());
+        }
+        selected
+    };
+    if paths.is_empty() && selected.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "No scan paths given and no library roots configured".to_string()));
+    }
+
+    let mut skipped = Vec::new();
+    for mut root in selected {
+        let reachable = refresh_root_status(pool, &mut root)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to check library root: {}", e)))?;
+        if reachable {
+            paths.push(root.path);
+        } else {
+            warn!("Skipping unreachable library root {}: {}", root.path, root.last_error.as_deref().unwrap_or(""));
+            skipped.push(root.path);
+        }
+    }
+
+    if paths.is_empty() {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, format!("Library roots are unreachable: {}", skipped.join(", "))));
+    }
+    Ok((paths, skipped))
+}
+
+/// 文件所属根目录的默认内容类型（所有文件的根目录设置一致时才使用）
+async fn root_content_type(state: &AppState, file_paths: &[&str]) -> Option<String> {
+    let roots = crate::database::list_library_roots(state.database.pool()).await.ok()?;
+    let mut content_types = file_paths.iter()
+        .map(|path| crate::models::find_library_root(&roots, path).and_then(|root| root.content_type.clone()));
+    let first = content_types.next()??;
+    content_types.all(|ct| ct.as_deref() == Some(first.as_str())).then_some(first)
+}
+
+/// 加载自定义解析规则并创建扫描器
+async fn load_scanner(state: &AppState) -> Result<FileScanner, (StatusCode, String)> {
+    let rules = crate::database::get_filename_rules(state.database.pool())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load filename rules: {}", e)))?;
+    Ok(FileScanner::with_rules(&rules))
+}
+
 pub async fn start_scan(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(request): Json<ScanRequest>,
 ) -> Result<Json<ScanResponse>, (StatusCode, String)> {
-    let scanner = FileScanner::new();
+    let scanner = load_scanner(&state).await?;
     let grouper = FileGrouper::new();
+    let (paths, skipped_roots) = resolve_scan_paths(&state, &request).await?;
     
     let mut all_scanned_files = Vec::new();
+    let mut all_extras = Vec::new();
     let mut total_files = 0;
     
-    for path in &request.paths {
+    for path in &paths {
         match scanner.scan_directory(path, request.recursive) {
             Ok(result) => {
                 total_files += result.total_files;
                 all_scanned_files.extend(result.scanned_files);
+                all_extras.extend(result.extras);
             }
             Err(e) => {
                 return Err((StatusCode::BAD_REQUEST, format!("Scan path {} failed: {}", path, e)));
@@ -170,13 +284,41 @@ pub async fn start_scan(
     
     let file_groups_len = file_groups.len();
     
+    // 保存扫描结果，关闭页面后仍可继续匹配
+    let records: Vec<_> = all_scanned_files.iter().map(ScannedFile::to_record).collect();
+    let session_id = match crate::database::create_scan_session(state.database.pool(), &paths, request.recursive, &records).await {
+        Ok(id) => Some(id),
+        Err(e) => {
+            warn!("Failed to persist scan session: {}", e);
+            None
+        }
+    };
+    
+    // 正片已入库的目录，直接关联其附加内容
+    let mut extras_by_dir: HashMap<String, Vec<ScannedExtra>> = HashMap::new();
+    for extra in &all_extras {
+        extras_by_dir.entry(extra.owner_dir.clone()).or_default().push(extra.clone());
+    }
+    let mut extras_linked = 0;
+    for (dir, extras) in &extras_by_dir {
+        match crate::database::find_media_id_by_file_dir(state.database.pool(), dir).await {
+            Ok(Some(media_id)) => extras_linked += attach_extras(&state, &media_id, extras).await,
+            Ok(None) => {}
+            Err(e) => warn!("Failed to look up media for {}: {}", dir, e),
+        }
+    }
+    
     Ok(Json(ScanResponse {
         success: true,
+        session_id,
         total_files,
         scanned_files: all_scanned_files,
         file_groups,
+        skipped_roots,
+        extras_found: all_extras.len(),
+        extras_linked,
         message: format!("Successfully scanned {} directories, found {} video files, grouped into {} groups", 
-            request.paths.len(), total_files, file_groups_len),
+            paths.len(), total_files, file_groups_len),
     }))
 }
 
@@ -189,8 +331,9 @@ pub async fn match_files(
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get media list: {}", e)))?;
     
-    let match_results = FileMatcher::match_files(request.scanned_files, all_media.clone());
-    let group_match_results = FileMatcher::match_file_groups(request.file_groups, all_media);
+    let top_n = request.top_n.unwrap_or(crate::services::file_matcher::DEFAULT_TOP_CANDIDATES);
+    let mut match_results = FileMatcher::match_files_ranked(request.scanned_files, &all_media, top_n);
+    let mut group_match_results = FileMatcher::match_file_groups_ranked(request.file_groups, &all_media, top_n);
     
     let exact_matches = match_results.iter()
         .filter(|r| r.match_type == crate::services::MatchType::Exact)
@@ -213,6 +356,20 @@ pub async fn match_files(
         .filter(|r| r.match_type == crate::services::MatchType::None)
         .count();
     
+    let upgrades_detected = detect_scan_upgrades(&state, &match_results, &group_match_results).await;
+    
+    let settings = crate::database::get_auto_confirm_settings(state.database.pool())
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Failed to load auto-confirm settings: {}", e);
+            AutoConfirmSettings::default()
+        });
+    let auto_confirmed = if request.auto_confirm.unwrap_or(settings.enabled) {
+        auto_confirm_matches(&state, settings.threshold, &mut match_results, &mut group_match_results).await
+    } else {
+        0
+    };
+    
     Ok(Json(MatchResponse {
         success: true,
         match_results,
@@ -220,9 +377,152 @@ pub async fn match_files(
         exact_matches,
         fuzzy_matches,
         no_matches,
+        upgrades_detected,
+        auto_confirmed,
     }))
 }
 
+/// 自动关联置信度达到阈值的匹配（媒体已有本地文件时留待人工确认）
+async fn auto_confirm_matches(
+    state: &AppState,
+    threshold: f32,
+    match_results: &mut [MatchResult],
+    group_match_results: &mut [GroupMatchResult],
+) -> usize {
+    let pool = state.database.pool();
+    let mut confirmed = 0;
+    
+    for result in match_results.iter_mut().filter(|r| r.confidence >= threshold) {
+        let Some(media_id) = result.matched_media.as_ref().map(|m| m.id.clone()) else { continue };
+        if !crate::database::get_local_file_paths(pool, &media_id).await.map(|p| p.is_empty()).unwrap_or(false) {
+            continue;
+        }
+        let files = vec![FileInfo {
+            file_path: result.scanned_file.file_path.clone(),
+            file_size: result.scanned_file.file_size as i64,
+            part_number: None,
+            part_label: None,
+            edition: None,
+        }];
+        if link_files_to_media(state, &media_id, &files).await {
+            result.auto_confirmed = true;
+            confirmed += 1;
+        }
+    }
+    
+    for result in group_match_results.iter_mut().filter(|r| r.confidence >= threshold) {
+        let Some(media_id) = result.matched_media.as_ref().map(|m| m.id.clone()) else { continue };
+        if !crate::database::get_local_file_paths(pool, &media_id).await.map(|p| p.is_empty()).unwrap_or(false) {
+            continue;
+        }
+        let files: Vec<FileInfo> = result.file_group.sorted_files().iter().map(|f| FileInfo {
+            file_path: f.scanned_file.file_path.clone(),
+            file_size: f.scanned_file.file_size as i64,
+            part_number: f.part_info.as_ref().map(|p| p.part_number),
+            part_label: f.part_info.as_ref().map(|p| p.part_label.clone()),
+            edition: None,
+        }).collect();
+        if link_files_to_media(state, &media_id, &files).await {
+            result.auto_confirmed = true;
+            confirmed += 1;
+        }
+    }
+    
+    if confirmed > 0 {
+        info!("Auto-confirmed {} matches (threshold {})", confirmed, threshold);
+    }
+    confirmed
+}
+
+/// 获取匹配自动确认设置
+pub async fn get_auto_confirm_settings(
+    State(state): State<AppState>,
+) -> Result<Json<AutoConfirmSettings>, (StatusCode, String)> {
+    let settings = crate::database::get_auto_confirm_settings(state.database.pool())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load auto-confirm settings: {}", e)))?;
+    Ok(Json(settings))
+}
+
+/// 保存匹配自动确认设置
+pub async fn update_auto_confirm_settings(
+    State(state): State<AppState>,
+    Json(settings): Json<AutoConfirmSettings>,
+) -> Result<Json<AutoConfirmSettings>, (StatusCode, String)> {
+    if !(0.0..=1.0).contains(&settings.threshold) {
+        return Err((StatusCode::BAD_REQUEST, "threshold must be between 0 and 1".to_string()));
+    }
+    crate::database::save_auto_confirm_settings(state.database.pool(), &settings)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to save auto-confirm settings: {}", e)))?;
+    Ok(Json(settings))
+}
+
+/// 检测扫描到的文件是否是已有本地文件媒体的质量升级（仅精确匹配）
+async fn detect_scan_upgrades(
+    state: &AppState,
+    match_results: &[MatchResult],
+    group_match_results: &[GroupMatchResult],
+) -> usize {
+    let pool = state.database.pool();
+    let profile = match crate::database::get_quality_profiles(pool).await {
+        Ok(profiles) => quality::default_profile(&profiles),
+        Err(e) => {
+            warn!("Failed to load quality profiles: {}", e);
+            return 0;
+        }
+    };
+    
+    // (媒体ID, 扫描到的文件路径)
+    let mut candidates: Vec<(&str, Vec<&str>)> = Vec::new();
+    for result in match_results.iter().filter(|r| r.match_type == crate::services::MatchType::Exact) {
+        if let Some(ref media) = result.matched_media {
+            candidates.push((&media.id, vec![result.scanned_file.file_path.as_str()]));
+        }
+    }
+    for result in group_match_results.iter().filter(|r| r.match_type == crate::services::MatchType::Exact) {
+        if let Some(ref media) = result.matched_media {
+            let paths = result.file_group.files.iter().map(|f| f.scanned_file.file_path.as_str()).collect();
+            candidates.push((&media.id, paths));
+        }
+    }
+    
+    let mut detected = 0;
+    for (media_id, scanned_paths) in candidates {
+        let local_paths = match crate::database::get_local_file_paths(pool, media_id).await {
+            Ok(paths) => paths,
+            Err(_) => continue,
+        };
+        // 没有本地文件，或扫描到的就是当前文件，跳过
+        if local_paths.is_empty() || scanned_paths.iter().any(|p| local_paths.iter().any(|l| l == p)) {
+            continue;
+        }
+        
+        let current = quality::best_quality_of(local_paths.iter().map(|p| p.as_str()), &profile);
+        let candidate = quality::best_quality_of(scanned_paths.iter().copied(), &profile);
+        if !quality::is_upgrade(&current, &candidate, &profile) {
+            continue;
+        }
+        
+        let upgrade = MediaUpgrade {
+            media_id: media_id.to_string(),
+            current_quality: Some(current.label()),
+            available_quality: candidate.label(),
+            source: "scan".to_string(),
+            source_title: scanned_paths.first().map(|p| p.to_string()).unwrap_or_default(),
+            magnet_link: None,
+            file_path: scanned_paths.first().map(|p| p.to_string()),
+            detected_at: chrono::Utc::now(),
+        };
+        match crate::database::record_upgrade(pool, &upgrade).await {
+            Ok(_) => detected += 1,
+            Err(e) => warn!("Failed to record upgrade for {}: {}", media_id, e),
+        }
+    }
+    
+    detected
+}
+
 pub async fn confirm_matches(
     State(state): State<AppState>,
     Json(request): Json<ConfirmMatchRequest>,
@@ -230,38 +530,8 @@ pub async fn confirm_matches(
     let mut updated_count = 0;
     
     for confirm_match in request.matches {
-        let media_files: Vec<MediaFile> = confirm_match.files.iter().map(|file_info| {
-            MediaFile::new(
-                confirm_match.media_id.clone(),
-                file_info.file_path.clone(),
-                file_info.file_size,
-                file_info.part_number,
-                file_info.part_label.clone(),
-            )
-        }).collect();
-        
-        let save_result = state.database.repository()
-            .save_media_files(&media_files)
-            .await;
-        
-        if save_result.is_err() {
-            continue;
-        }
-        
-        if let Some(first_file) = confirm_match.files.first() {
-            let total_size: i64 = confirm_match.files.iter().map(|f| f.file_size).sum();
-            
-            let update_result = state.database.repository()
-                .update_media_file_info(
-                    &confirm_match.media_id,
-                    &first_file.file_path,
-                    total_size
-                )
-                .await;
-            
-            if update_result.is_ok() {
-                updated_count += 1;
-            }
+        if link_files_to_media(&state, &confirm_match.media_id, &confirm_match.files).await {
+            updated_count += 1;
         }
     }
     
@@ -272,6 +542,278 @@ pub async fn confirm_matches(
     }))
 }
 
+/// 在一个事务中将文件和附加内容关联到媒体，成功返回 true
+async fn link_files_to_media(state: &AppState, media_id: &str, files: &[FileInfo]) -> bool {
+    let media_files = to_media_files(media_id, files);
+    let result = async {
+        let rules = crate::database::get_filename_rules(state.database.pool()).await?;
+        let extras = extras_for_files(&FileScanner::with_rules(&rules), media_id, files);
+        let repository = state.database.repository();
+        let mut tx = repository.begin().await?;
+        crate::database::link_media_files_with(&mut tx, media_id, &media_files).await?;
+        let saved = crate::database::save_media_extras_with(&mut tx, &extras).await?;
+        repository.commit(tx).await?;
+        anyhow::Ok(saved)
+    }.await;
+    match result {
+        Ok(saved) => {
+            if saved > 0 {
+                info!("Linked {} extras to media {}", saved, media_id);
+            }
+            spawn_import_hooks(
+                state.database.pool().clone(),
+                media_id.to_string(),
+                media_files.into_iter().map(|f| f.file_path).collect(),
+            );
+            true
+        }
+        Err(e) => {
+            warn!("Failed to link files to media {}: {}", media_id, e);
+            false
+        }
+    }
+}
+
+fn to_media_extras(media_id: &str, extras: &[ScannedExtra]) -> Vec<MediaExtra> {
+    extras.iter().map(|e| {
+        MediaExtra::new(media_id.to_string(), e.file_path.clone(), e.file_name.clone(), e.file_size as i64, e.extra_type.clone())
+    }).collect()
+}
+
+/// 保存附加内容到媒体，返回保存数量
+async fn attach_extras(state: &AppState, media_id: &str, extras: &[ScannedExtra]) -> u64 {
+    crate::database::save_media_extras(state.database.pool(), &to_media_extras(media_id, extras))
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Failed to save extras for media {}: {}", media_id, e);
+            0
+        })
+}
+
+/// 查找文件所在目录下 Extras/ 中的附加内容（使用带自定义规则的扫描器）
+fn extras_for_files(scanner: &FileScanner, media_id: &str, files: &[FileInfo]) -> Vec<MediaExtra> {
+    let mut dirs: Vec<&std::path::Path> = files.iter()
+        .filter_map(|f| std::path::Path::new(&f.file_path).parent())
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    
+    let extras: Vec<ScannedExtra> = dirs.into_iter().flat_map(|dir| scanner.find_extras(dir)).collect();
+    to_media_extras(media_id, &extras)
+}
+
+fn to_media_files(media_id: &str, files: &[FileInfo]) -> Vec<MediaFile> {
+    files.iter().map(|file_info| {
+        MediaFile::new(
+            media_id.to_string(),
+            file_info.file_path.clone(),
+            file_info.file_size,
+            file_info.part_number,
+            file_info.part_label.clone(),
+        ).with_edition(file_info.edition.clone())
+    }).collect()
+}
+
+// ============ Identify ============
+
+#[derive(Debug, Deserialize)]
+pub struct CandidatesRequest {
+    pub scanned_file: ScannedFile,
+    /// 搜索关键词（默认使用解析出的识别号或标题）
+    pub query: Option<String>,
+    /// 参与搜索的插件（默认所有支持搜索的插件）
+    pub plugin_ids: Option<Vec<String>>,
+    /// 媒体库候选数（默认 5）
+    pub top_n: Option<usize>,
+}
+
+/// 单个插件的搜索结果
+#[derive(Debug, Serialize)]
+pub struct ScraperCandidates {
+    pub plugin_id: String,
+    pub results: Vec<ScrapeResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CandidatesResponse {
+    pub success: bool,
+    pub query: String,
+    pub library_candidates: Vec<MatchCandidate>,
+    pub scraper_results: Vec<ScraperCandidates>,
+}
+
+/// 获取单个未匹配文件的候选：媒体库排序候选与刮削器搜索结果
+pub async fn get_match_candidates(
+    State(state): State<AppState>,
+    Json(request): Json<CandidatesRequest>,
+) -> Result<Json<CandidatesResponse>, (StatusCode, String)> {
+    let file = &request.scanned_file;
+    let query = request.query.as_deref()
+        .or(file.parsed_code.as_deref())
+        .or(file.parsed_title.as_deref())
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(String::from)
+        .unwrap_or_else(|| {
+            std::path::Path::new(&file.file_name)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| file.file_name.clone())
+        });
+    
+    let all_media = state.database.repository()
+        .get_all_media()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get media: {}", e)))?;
+    let top_n = request.top_n.unwrap_or(crate::services::file_matcher::DEFAULT_TOP_CANDIDATES);
+    let title_override = request.query.as_deref().filter(|_| file.parsed_code.is_none());
+    let library_candidates = FileMatcher::rank_candidates(file, title_override, &all_media, top_n);
+    
+    let manager = state.plugin_manager.read().await;
+    let plugin_ids: Vec<String> = match &request.plugin_ids {
+        Some(ids) => ids.clone(),
+        None => manager.list_plugins().iter()
+            .filter(|p| p.config.supports_search)
+            .map(|p| p.config.id.clone())
+            .collect(),
+    };
+    
+    let mut scraper_results = Vec::new();
+    for plugin_id in plugin_ids {
+        let (results, error) = match search_query::search_plugin(&manager, &plugin_id, &query, None).await {
+            Ok(response) => (response.results, None),
+            Err(e) => {
+                warn!("Candidate search failed with plugin {}: {}", plugin_id, e);
+                (Vec::new(), Some(e.to_string()))
+            }
+        };
+        scraper_results.push(ScraperCandidates { plugin_id, results, error });
+    }
+    
+    Ok(Json(CandidatesResponse {
+        success: true,
+        query,
+        library_candidates,
+        scraper_results,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdentifyRequest {
+    pub files: Vec<FileInfo>,
+    /// 关联到已有媒体（为空时用 scrape_data 创建新媒体）
+    pub media_id: Option<String>,
+    /// 选择的刮削数据
+    pub scrape_data: Option<serde_json::Value>,
+    #[serde(flatten)]
+    pub fields: ScrapeFieldMask,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IdentifyResponse {
+    pub success: bool,
+    pub media_id: String,
+    pub created: bool,
+    pub linked_files: usize,
+    pub message: String,
+}
+
+/// 确认识别结果：关联文件并应用选择的刮削数据，任一步失败都会回滚
+pub async fn confirm_identify(
+    State(state): State<AppState>,
+    Json(request): Json<IdentifyRequest>,
+) -> Result<Json<IdentifyResponse>, (StatusCode, String)> {
+    if request.files.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "files cannot be empty".to_string()));
+    }
+    if request.media_id.is_none() && request.scrape_data.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "media_id or scrape_data is required".to_string()));
+    }
+    request.fields.validate()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    
+    let pool = state.database.pool();
+    let paths: Vec<String> = request.files.iter().map(|f| f.file_path.clone()).collect();
+    let linked = crate::database::find_linked_file_paths(pool, &paths)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to check files: {}", e)))?;
+    if !linked.is_empty() {
+        return Err((StatusCode::CONFLICT, format!("Files already linked to media: {}", linked.join(", "))));
+    }
+    
+    let scrape_data = request.scrape_data.as_ref().map(|data| request.fields.apply(data));
+    let repository = state.database.repository();
+    let internal = |action: &str, e: anyhow::Error| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to {}: {}", action, e));
+    
+    // 1. 准备媒体（新建，或把刮削数据应用到已有媒体）
+    let (media, created) = match &request.media_id {
+        Some(media_id) => {
+            let mut media = repository.get_media_by_id(media_id)
+                .await
+                .map_err(|e| internal("get media", e))?
+                .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Media not found: {}", media_id)))?;
+            if let Some(data) = &scrape_data {
+                apply_scrape_result_to_media(&mut media, data);
+                media.updated_at = chrono::Utc::now();
+            }
+            (media, false)
+        }
+        None => {
+            let data = scrape_data.as_ref().unwrap_or(&serde_json::Value::Null);
+            let title = data.get("title")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown")
+                .to_string();
+            let mut media = MediaItem::new(title, MediaType::Movie)
+                .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid scrape data: {:?}", e)))?;
+            apply_scrape_result_to_media(&mut media, data);
+            (media, true)
+        }
+    };
+    let media_files = to_media_files(&media.id, &request.files);
+    let extras = extras_for_files(&load_scanner(&state).await?, &media.id, &request.files);
+    
+    // 2. 媒体、演员、翻译、文件和附加内容在同一个事务中写入，提交前出错自动回滚
+    let mut tx = repository.begin().await.map_err(|e| internal("begin transaction", e))?;
+    if let Some(data) = &scrape_data {
+        super::scrape::write_scraped_media(&mut tx, &media, data, created)
+            .await
+            .map_err(|e| internal(if created { "create media" } else { "update media" }, e))?;
+    }
+    crate::database::link_media_files_with(&mut tx, &media.id, &media_files)
+        .await
+        .map_err(|e| internal("link files", e))?;
+    crate::database::save_media_extras_with(&mut tx, &extras)
+        .await
+        .map_err(|e| internal("save extras", e))?;
+    repository.commit(tx).await.map_err(|e| internal("commit", e))?;
+    spawn_import_hooks(pool.clone(), media.id.clone(), media_files.into_iter().map(|f| f.file_path).collect());
+    if created {
+        enrichment::queue_new_media(pool, &media.id, ENRICHMENT_REASON_SCAN).await;
+    }
+    
+    // 3. 图片缓存（不影响结果）
+    if let Some(data) = &scrape_data {
+        let scraper_name = data.get("source").and_then(|v| v.as_str()).unwrap_or("unknown");
+        let media_data = crate::services::cache::MediaData::from_media_item(&media);
+        if let Err(e) = state.cache_service.handle_media_save(&media.id, &media_data, scraper_name).await {
+            error!("缓存处理失败: media_id={}, scraper={}, error={:?}", media.id, scraper_name, e);
+        }
+    }
+    
+    info!("Identified {} files as media {} (created: {})", request.files.len(), media.id, created);
+    
+    Ok(Json(IdentifyResponse {
+        success: true,
+        media_id: media.id,
+        created,
+        linked_files: request.files.len(),
+        message: format!("Linked {} files to {}", request.files.len(), media.title),
+    }))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct IgnoreFileRequest {
     pub file_path: String,
@@ -296,6 +838,12 @@ pub async fn ignore_file(
         .add_ignored_file(&id, &request.file_path, &request.file_name, &ignored_at, request.reason.as_deref())
         .await;
     
+    if result.is_ok() {
+        if let Err(e) = crate::database::mark_scan_files(state.database.pool(), std::slice::from_ref(&request.file_path), SCAN_FILE_IGNORED, None).await {
+            warn!("Failed to update scan file status: {}", e);
+        }
+    }
+    
     match result {
         Ok(_) => Ok(Json(IgnoreFileResponse {
             success: true,
@@ -347,11 +895,290 @@ pub async fn remove_ignored_file(
     }
 }
 
+#[derive(Debug, Serialize)]
+pub struct FilenameRulesResponse {
+    pub success: bool,
+    pub rules: Vec<FilenameRule>,
+}
+
+/// 获取自定义文件名解析规则
+pub async fn get_filename_rules(
+    State(state): State<AppState>,
+) -> Result<Json<FilenameRulesResponse>, (StatusCode, String)> {
+    let rules = crate::database::get_filename_rules(state.database.pool())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load filename rules: {}", e)))?;
+    
+    Ok(Json(FilenameRulesResponse { success: true, rules }))
+}
+
+/// 保存自定义文件名解析规则（整体替换，按顺序匹配）
+pub async fn update_filename_rules(
+    State(state): State<AppState>,
+    Json(rules): Json<Vec<FilenameRule>>,
+) -> Result<Json<FilenameRulesResponse>, (StatusCode, String)> {
+    for rule in &rules {
+        rule.validate().map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    }
+    
+    crate::database::save_filename_rules(state.database.pool(), &rules)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to save filename rules: {}", e)))?;
+    
+    Ok(Json(FilenameRulesResponse { success: true, rules }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TestFilenameRuleRequest {
+    pub filename: String,
+    /// 可选：使用未保存的规则测试（默认使用已保存的规则）
+    pub rules: Option<Vec<FilenameRule>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestFilenameRuleResponse {
+    pub success: bool,
+    pub filename: String,
+    pub parsed: ParsedFilename,
+}
+
+/// 测试文件名会如何被解析
+pub async fn test_filename_rules(
+    State(state): State<AppState>,
+    Json(request): Json<TestFilenameRuleRequest>,
+) -> Result<Json<TestFilenameRuleResponse>, (StatusCode, String)> {
+    let scanner = match &request.rules {
+        Some(rules) => {
+            for rule in rules {
+                rule.validate().map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+            }
+            FileScanner::with_rules(rules)
+        }
+        None => load_scanner(&state).await?,
+    };
+    
+    let parsed = scanner.parse(&request.filename);
+    Ok(Json(TestFilenameRuleResponse {
+        success: true,
+        filename: request.filename,
+        parsed,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ParsePreviewRequest {
+    pub filenames: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParsePreviewItem {
+    pub filename: String,
+    pub parsed: ParsedFilename,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParsePreviewResponse {
+    pub success: bool,
+    pub results: Vec<ParsePreviewItem>,
+}
+
+/// 批量预览文件名解析结果（使用当前保存的规则）
+pub async fn parse_preview(
+    State(state): State<AppState>,
+    Json(request): Json<ParsePreviewRequest>,
+) -> Result<Json<ParsePreviewResponse>, (StatusCode, String)> {
+    let scanner = load_scanner(&state).await?;
+    
+    let results = request.filenames
+        .into_iter()
+        .map(|filename| ParsePreviewItem {
+            parsed: scanner.parse(&filename),
+            filename,
+        })
+        .collect();
+    
+    Ok(Json(ParsePreviewResponse { success: true, results }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReparseRequest {
+    /// 之前扫描但未匹配的文件
+    #[serde(default)]
+    pub unmatched_files: Vec<ScannedFile>,
+    /// 或：已保存的扫描会话（重新解析其中未匹配的文件并更新保存的解析结果）
+    pub session_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReparseResponse {
+    pub success: bool,
+    /// 解析结果发生变化的文件数
+    pub changed_count: usize,
+    /// 重新解析后匹配到媒体的文件数
+    pub matched_count: usize,
+    pub match_results: Vec<MatchResult>,
+}
+
+/// 规则修改后，对未匹配的文件重新解析并重新匹配
+pub async fn reparse_unmatched(
+    State(state): State<AppState>,
+    Json(request): Json<ReparseRequest>,
+) -> Result<Json<ReparseResponse>, (StatusCode, String)> {
+    let scanner = load_scanner(&state).await?;
+    
+    let records = match &request.session_id {
+        Some(session_id) => crate::database::get_scan_files(state.database.pool(), session_id, Some(SCAN_FILE_UNMATCHED))
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load scan session: {}", e)))?,
+        None => Vec::new(),
+    };
+    let unmatched_files: Vec<ScannedFile> = if request.session_id.is_some() {
+        records.iter().cloned().map(ScannedFile::from).collect()
+    } else {
+        request.unmatched_files
+    };
+    
+    let mut changed_count = 0;
+    let mut reparsed: Vec<ScannedFile> = Vec::with_capacity(unmatched_files.len());
+    for (index, file) in unmatched_files.iter().enumerate() {
+        let updated = scanner.reparse(file);
+        if updated.parsed_code != file.parsed_code
+            || updated.parsed_title != file.parsed_title
+            || updated.parsed_series != file.parsed_series
+            || updated.parsed_date != file.parsed_date
+        {
+            changed_count += 1;
+            // 会话中的文件：保存新的解析结果
+            if let Some(record) = records.get(index) {
+                let record = crate::models::ScanFileRecord { id: record.id, ..updated.to_record() };
+                if let Err(e) = crate::database::update_scan_file_parse(state.database.pool(), &record).await {
+                    warn!("Failed to save reparsed file {}: {}", file.file_path, e);
+                }
+            }
+        }
+        reparsed.push(updated);
+    }
+    
+    let all_media = state.database.repository()
+        .get_all_media()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get media list: {}", e)))?;
+    
+    let match_results = FileMatcher::match_files(reparsed, all_media);
+    let matched_count = match_results.iter()
+        .filter(|r| r.match_type != crate::services::MatchType::None)
+        .count();
+    
+    info!("Reparsed {} unmatched files: {} changed, {} matched", unmatched_files.len(), changed_count, matched_count);
+    
+    Ok(Json(ReparseResponse {
+        success: true,
+        changed_count,
+        matched_count,
+        match_results,
+    }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanSessionsResponse {
+    pub success: bool,
+    pub sessions: Vec<ScanSession>,
+}
+
+/// 获取已保存的扫描会话
+pub async fn list_scan_sessions(
+    State(state): State<AppState>,
+) -> Result<Json<ScanSessionsResponse>, (StatusCode, String)> {
+    let sessions = crate::database::list_scan_sessions(state.database.pool())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to list scan sessions: {}", e)))?;
+    
+    Ok(Json(ScanSessionsResponse { success: true, sessions }))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanSessionDetailResponse {
+    pub success: bool,
+    pub session: ScanSession,
+    /// 尚未匹配的文件
+    pub scanned_files: Vec<ScannedFile>,
+    pub file_groups: Vec<FileGroup>,
+}
+
+/// 继续扫描会话：返回其中尚未匹配的文件（格式与扫描结果相同）
+pub async fn get_scan_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<ScanSessionDetailResponse>, (StatusCode, String)> {
+    let pool = state.database.pool();
+    let session = crate::database::get_scan_session(pool, &session_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load scan session: {}", e)))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Scan session not found".to_string()))?;
+    
+    let scanned_files: Vec<ScannedFile> = crate::database::get_scan_files(pool, &session_id, Some(SCAN_FILE_UNMATCHED))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to load scan files: {}", e)))?
+        .into_iter()
+        .map(ScannedFile::from)
+        .collect();
+    
+    let file_groups: Vec<FileGroup> = FileGrouper::new()
+        .group_files(scanned_files.clone())
+        .into_iter()
+        .filter(|group| group.files.len() > 1)
+        .collect();
+    
+    Ok(Json(ScanSessionDetailResponse {
+        success: true,
+        session,
+        scanned_files,
+        file_groups,
+    }))
+}
+
+/// 删除扫描会话
+pub async fn delete_scan_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Result<Json<IgnoreFileResponse>, (StatusCode, String)> {
+    crate::database::delete_scan_session(state.database.pool(), &session_id)
+        .await
+        .map_err(|e| {
+            if e.to_string().contains("not found") {
+                (StatusCode::NOT_FOUND, "Scan session not found".to_string())
+            } else {
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to delete scan session: {}", e))
+            }
+        })?;
+    
+    Ok(Json(IgnoreFileResponse {
+        success: true,
+        message: "Scan session deleted".to_string(),
+    }))
+}
+
+/// 清空所有扫描会话
+pub async fn clear_scan_sessions(
+    State(state): State<AppState>,
+) -> Result<Json<IgnoreFileResponse>, (StatusCode, String)> {
+    let deleted = crate::database::clear_scan_sessions(state.database.pool())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to clear scan sessions: {}", e)))?;
+    
+    Ok(Json(IgnoreFileResponse {
+        success: true,
+        message: format!("Cleared {} scan sessions", deleted),
+    }))
+}
+
 #[derive(Debug, Serialize)]
 pub struct GetMediaFilesResponse {
     pub success: bool,
     pub files: Vec<MediaFile>,
     pub total_size: i64,
+    /// 按版本分组的文件
+    pub editions: Vec<MediaEdition>,
 }
 
 pub async fn get_media_files(
@@ -364,11 +1191,188 @@ pub async fn get_media_files(
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get file list: {}", e)))?;
     
     let total_size: i64 = files.iter().map(|f| f.file_size).sum();
+    let editions = group_by_edition(files.clone());
     
     Ok(Json(GetMediaFilesResponse {
         success: true,
         files,
         total_size,
+        editions,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MediaExtrasQuery {
+    /// 按类型筛选（trailer / behind_the_scenes / gallery 等）
+    #[serde(rename = "type")]
+    pub extra_type: Option<String>,
+}
+
+/// 附加内容及其播放地址
+#[derive(Debug, Serialize)]
+pub struct MediaExtraItem {
+    #[serde(flatten)]
+    pub extra: MediaExtra,
+    pub stream_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MediaExtrasResponse {
+    pub success: bool,
+    pub extras: Vec<MediaExtraItem>,
+    pub types: Vec<&'static str>,
+}
+
+/// 获取媒体的附加内容列表
+pub async fn get_media_extras(
+    State(state): State<AppState>,
+    Path(media_id): Path<String>,
+    Query(query): Query<MediaExtrasQuery>,
+) -> Result<Json<MediaExtrasResponse>, (StatusCode, String)> {
+    if let Some(extra_type) = query.extra_type.as_deref() {
+        if !EXTRA_TYPES.contains(&extra_type) {
+            return Err((StatusCode::BAD_REQUEST, format!("Unknown extra type: {}", extra_type)));
+        }
+    }
+    
+    let extras = crate::database::get_media_extras(state.database.pool(), &media_id, query.extra_type.as_deref())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get extras: {}", e)))?;
+    
+    Ok(Json(MediaExtrasResponse {
+        success: true,
+        extras: extras.into_iter().map(|extra| MediaExtraItem {
+            stream_url: format!("/api/media/{}/extras/{}/stream", media_id, extra.id),
+            extra,
+        }).collect(),
+        types: EXTRA_TYPES.to_vec(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetEditionRequest {
+    pub file_ids: Vec<String>,
+    /// 版本名称（为空表示移回默认版本）
+    pub edition: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetEditionResponse {
+    pub success: bool,
+    pub updated_count: u64,
+    pub editions: Vec<MediaEdition>,
+}
+
+/// 设置媒体文件的所属版本
+pub async fn set_media_files_edition(
+    State(state): State<AppState>,
+    Path(media_id): Path<String>,
+    Json(request): Json<SetEditionRequest>,
+) -> Result<Json<SetEditionResponse>, (StatusCode, String)> {
+    if request.file_ids.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "file_ids cannot be empty".to_string()));
+    }
+    
+    let updated_count = crate::database::set_media_files_edition(
+        state.database.pool(),
+        &media_id,
+        &request.file_ids,
+        request.edition.as_deref(),
+    )
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to set edition: {}", e)))?;
+    
+    let files = state.database.repository()
+        .get_media_files(&media_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get file list: {}", e)))?;
+    
+    Ok(Json(SetEditionResponse {
+        success: true,
+        updated_count,
+        editions: group_by_edition(files),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetFileWatchRequest {
+    /// true 标记为看完，false 清除进度
+    pub watched: Option<bool>,
+    /// 观看进度（0-1），同时提供时优先于 watched
+    pub progress: Option<f32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetFileWatchResponse {
+    pub success: bool,
+    pub editions: Vec<MediaEdition>,
+    /// 推算出的收藏观看状态和进度（同一版本的所有分段）
+    pub watch_status: WatchStatus,
+    pub watch_progress: f32,
+}
+
+/// 标记单个分段的观看进度，并按同一版本所有分段的进度更新收藏状态
+/// （看过任一分段时未收藏的媒体会自动加入收藏）
+pub async fn set_media_file_watch(
+    State(state): State<AppState>,
+    Path((media_id, file_id)): Path<(String, String)>,
+    Json(request): Json<SetFileWatchRequest>,
+) -> Result<Json<SetFileWatchResponse>, (StatusCode, String)> {
+    let progress = match (request.progress, request.watched) {
+        (Some(progress), _) if !(0.0..=1.0).contains(&progress) => {
+            return Err((StatusCode::BAD_REQUEST, "progress must be between 0 and 1".to_string()));
+        }
+        (Some(progress), _) => Some(progress).filter(|p| *p > 0.0),
+        (None, Some(true)) => Some(1.0),
+        (None, Some(false)) => None,
+        (None, None) => return Err((StatusCode::BAD_REQUEST, "watched or progress is required".to_string())),
+    };
+    
+    let found = crate::database::set_media_file_progress(state.database.pool(), &media_id, &file_id, progress)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to update watch progress: {}", e)))?;
+    if !found {
+        return Err((StatusCode::NOT_FOUND, "Media file not found".to_string()));
+    }
+    
+    let repository = state.database.repository();
+    let files = repository.get_media_files(&media_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to get file list: {}", e)))?;
+    let edition = files.iter().find(|f| f.id == file_id).and_then(|f| normalize_edition(f.edition.as_deref()));
+    let parts: Vec<MediaFile> = files.iter()
+        .filter(|f| normalize_edition(f.edition.as_deref()).map(|e| e.to_lowercase()) == edition.as_ref().map(|e| e.to_lowercase()))
+        .cloned()
+        .collect();
+    let (watch_status, watch_progress) = derive_watch_state(&parts);
+    
+    let internal = |e: anyhow::Error| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to update collection: {}", e));
+    match repository.get_collection_by_media_id(&media_id).await.map_err(internal)? {
+        Some(mut collection) => {
+            // 一个分段都没看时，只有原本在看/已看完的收藏才退回想看
+            let keep_status = watch_status == WatchStatus::WantToWatch
+                && !matches!(collection.get_watch_status(), Ok(WatchStatus::Watching | WatchStatus::Completed));
+            if !keep_status {
+                collection.set_watch_status(watch_status.clone());
+            }
+            collection.watch_progress = Some(watch_progress);
+            collection.last_watched = Some(chrono::Utc::now());
+            repository.update_collection(&collection).await.map_err(internal)?;
+        }
+        None if watch_status != WatchStatus::WantToWatch => {
+            let mut collection = Collection::new(media_id.clone(), watch_status.clone());
+            collection.watch_progress = Some(watch_progress);
+            collection.last_watched = Some(chrono::Utc::now());
+            repository.add_to_collection(&collection).await.map_err(internal)?;
+        }
+        None => {}
+    }
+    
+    Ok(Json(SetFileWatchResponse {
+        success: true,
+        editions: group_by_edition(files),
+        watch_status,
+        watch_progress,
     }))
 }
 
@@ -376,25 +1380,19 @@ pub async fn auto_scrape_unmatched(
     State(state): State<AppState>,
     Json(request): Json<AutoScrapeRequest>,
 ) -> Result<Json<AutoScrapeResponse>, (StatusCode, String)> {
-    // 生成会话ID
-    let session_id = uuid::Uuid::new_v4().to_string();
-    info!("开始自动刮削，会话ID: {}", session_id);
-    
     let total_count = request.unmatched_files.len() + request.unmatched_groups.as_ref().map(|g| g.len()).unwrap_or(0);
     
     // 初始化进度跟踪
-    {
-        let mut progress_map = SCRAPE_PROGRESS.write().await;
-        progress_map.insert(session_id.clone(), AutoScrapeProgress {
-            current: 0,
-            total: total_count,
-            file_name: String::new(),
-            status: "准备开始...".to_string(),
-            message: Some("正在初始化刮削任务".to_string()),
-            scraped_count: 0,
-            failed_count: 0,
-        });
-    }
+    let session_id = SCRAPE_PROGRESS.create(AutoScrapeProgress {
+        current: 0,
+        total: total_count,
+        file_name: String::new(),
+        status: "准备开始...".to_string(),
+        message: Some("正在初始化刮削任务".to_string()),
+        scraped_count: 0,
+        failed_count: 0,
+    });
+    info!("开始自动刮削，会话ID: {}", session_id);
     
     // 克隆需要的数据用于后台任务
     let session_id_clone = session_id.clone();
@@ -563,33 +1561,38 @@ async fn process_auto_scrape(
     if media_list.is_empty() {
         info!("没有可刮削的文件");
         // 更新进度为完成
-        {
-            let mut progress_map = SCRAPE_PROGRESS.write().await;
-            if let Some(progress) = progress_map.get_mut(&session_id) {
-                progress.status = "completed".to_string();
-                progress.message = Some("没有可刮削的文件".to_string());
-            }
-        }
+        SCRAPE_PROGRESS.update(&session_id, |progress| {
+            progress.status = "completed".to_string();
+            progress.message = Some("没有可刮削的文件".to_string());
+        });
         return Ok(());
     }
     
     // 更新进度：开始刮削
-    {
-        let mut progress_map = SCRAPE_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
-            progress.status = format!("正在刮削 (0/{})", media_list.len());
-            progress.message = Some(format!("开始{}刮削 {} 个项目", 
-                if request.concurrent { "并发" } else { "串行" }, 
-                media_list.len()));
-        }
-    }
+    SCRAPE_PROGRESS.update(&session_id, |progress| {
+        progress.status = format!("正在刮削 (0/{})", media_list.len());
+        progress.message = Some(format!("开始{}刮削 {} 个项目", 
+            if request.concurrent { "并发" } else { "串行" }, 
+            media_list.len()));
+    });
     
     // 调用插件批量刮削
     let manager = state.plugin_manager.read().await;
     info!("准备调用插件管理器，并发模式: {}", request.concurrent);
     
-    // 使用用户选择的 content_type，默认为 "Scene"
-    let content_type = request.content_type.as_deref().unwrap_or("Scene");
+    // 使用用户选择的 content_type，未选择时使用文件所在根目录的默认类型，否则为 "Scene"
+    let content_type = match &request.content_type {
+        Some(content_type) => content_type.clone(),
+        None => {
+            let file_paths: Vec<&str> = request.unmatched_files.iter()
+                .map(|f| f.file_path.as_str())
+                .chain(request.unmatched_groups.iter().flatten()
+                    .flat_map(|g| g.files.iter().map(|f| f.scanned_file.file_path.as_str())))
+                .collect();
+            root_content_type(&state, &file_paths).await.unwrap_or_else(|| "Scene".to_string())
+        }
+    };
+    let content_type = content_type.as_str();
     info!("使用内容类型: {}", content_type);
     
     let scrape_results = if request.concurrent {
@@ -610,14 +1613,11 @@ async fn process_auto_scrape(
                 let current = index + 1;
                 
                 // 更新进度
-                {
-                    let mut progress_map = SCRAPE_PROGRESS.write().await;
-                    if let Some(progress) = progress_map.get_mut(&session_id) {
-                        progress.current = current;
-                        progress.total = total_count;
-                        progress.status = format!("正在保存 ({}/{})", current, total_count);
-                    }
-                }
+                SCRAPE_PROGRESS.update(&session_id, |progress| {
+                    progress.current = current;
+                    progress.total = total_count;
+                    progress.status = format!("正在保存 ({}/{})", current, total_count);
+                });
                 
                 let file_info = match file_info_map.get(&result.media_id) {
                     Some(info) => info,
@@ -641,6 +1641,13 @@ async fn process_auto_scrape(
                         // 调试：输出刮削数据
                         info!("刮削数据: {}", serde_json::to_string_pretty(scrape_data).unwrap_or_else(|_| "无法序列化".to_string()));
                         
+                        let report = crate::api::scrape::check_scrape_data("media_scraper", scrape_data).await;
+                        if !report.is_valid() {
+                            warn!("刮削数据格式错误，跳过 {}: {}", display_name, report.error_message());
+                            failed_count += 1;
+                            continue;
+                        }
+                        
                         // 获取标题
                         let title = scrape_data.get("title")
                             .and_then(|v| v.as_str())
@@ -658,107 +1665,57 @@ async fn process_auto_scrape(
                                 // 应用刮削结果
                                 apply_scrape_result_to_media(&mut media, scrape_data);
                                 
-                                // 保存到数据库
-                                match state.database.repository().insert_media(&media).await {
-                                    Ok(_) => {
-                                        // 关联文件
-                                        let save_result = if is_group {
-                                            // 文件组
-                                            if let Some(files) = file_info["files"].as_array() {
-                                                let media_files: Vec<MediaFile> = files.iter().enumerate().map(|(i, f)| {
-                                                    let file_path = f["file_path"].as_str().unwrap_or("").to_string();
-                                                    let file_size = f["file_size"].as_i64().unwrap_or(0);
-                                                    let part_label = f["part_label"].as_str().map(|s| s.to_string());
-                                                    
-                                                    MediaFile::new(
-                                                        media_id.clone(),
-                                                        file_path,
-                                                        file_size,
-                                                        Some((i + 1) as i32),
-                                                        part_label,
-                                                    )
-                                                }).collect();
-                                                
-                                                state.database.repository().save_media_files(&media_files).await
-                                            } else {
-                                                Err(anyhow::anyhow!("文件组没有文件列表"))
-                                            }
-                                        } else {
-                                            // 单文件
-                                            let file_path = file_info["file_path"].as_str().unwrap_or("").to_string();
-                                            let file_size = file_info["file_size"].as_i64().unwrap_or(0);
-                                            
-                                            let media_file = MediaFile::new(
+                                // 关联的文件
+                                let media_files: Vec<MediaFile> = if is_group {
+                                    // 文件组：按顺序编号分段
+                                    file_info["files"].as_array().map(|files| {
+                                        files.iter().enumerate().map(|(i, f)| {
+                                            MediaFile::new(
                                                 media_id.clone(),
-                                                file_path,
-                                                file_size,
-                                                None,
-                                                None,
-                                            );
-                                            
-                                            state.database.repository().save_media_files(&[media_file]).await
-                                        };
+                                                f["file_path"].as_str().unwrap_or("").to_string(),
+                                                f["file_size"].as_i64().unwrap_or(0),
+                                                Some((i + 1) as i32),
+                                                f["part_label"].as_str().map(|s| s.to_string()),
+                                            )
+                                        }).collect()
+                                    }).unwrap_or_default()
+                                } else {
+                                    // 单文件
+                                    vec![MediaFile::new(
+                                        media_id.clone(),
+                                        file_info["file_path"].as_str().unwrap_or("").to_string(),
+                                        file_info["file_size"].as_i64().unwrap_or(0),
+                                        None,
+                                        None,
+                                    )]
+                                };
+                                
+                                // 在一个事务中保存媒体、演员、翻译和文件关联
+                                let save_result = async {
+                                    let repository = state.database.repository();
+                                    let mut tx = repository.begin().await?;
+                                    super::scrape::write_scraped_media(&mut tx, &media, scrape_data, true).await?;
+                                    crate::database::link_media_files_with(&mut tx, &media_id, &media_files).await?;
+                                    repository.commit(tx).await
+                                }.await;
+                                
+                                match save_result {
+                                    Ok(()) => {
+                                        spawn_import_hooks(
+                                            state.database.pool().clone(),
+                                            media_id.clone(),
+                                            media_files.iter().map(|f| f.file_path.clone()).collect(),
+                                        );
+                                        enrichment::queue_new_media(state.database.pool(), &media_id, ENRICHMENT_REASON_SCAN).await;
+                                        info!("{} {} 刮削成功: {}", 
+                                            if is_group { "文件组" } else { "单文件" },
+                                            display_name, title);
+                                        scraped_count += 1;
                                         
-                                        match save_result {
-                                            Ok(_) => {
-                                                // 更新媒体的文件信息
-                                                let (first_file_path, total_size) = if is_group {
-                                                    // 文件组：使用第一个文件的路径，总大小为所有文件之和
-                                                    if let Some(files) = file_info["files"].as_array() {
-                                                        let first_path = files.first()
-                                                            .and_then(|f| f["file_path"].as_str())
-                                                            .unwrap_or("")
-                                                            .to_string();
-                                                        let total: i64 = files.iter()
-                                                            .filter_map(|f| f["file_size"].as_i64())
-                                                            .sum();
-                                                        (first_path, total)
-                                                    } else {
-                                                        (String::new(), 0)
-                                                    }
-                                                } else {
-                                                    // 单文件
-                                                    let path = file_info["file_path"].as_str().unwrap_or("").to_string();
-                                                    let size = file_info["file_size"].as_i64().unwrap_or(0);
-                                                    (path, size)
-                                                };
-                                                
-                                                // 更新媒体表中的文件路径和大小
-                                                if let Err(e) = state.database.repository()
-                                                    .update_media_file_info(&media_id, &first_file_path, total_size)
-                                                    .await {
-                                                    warn!("更新媒体文件信息失败: {}", e);
-                                                }
-                                                
-                                                info!("{} {} 刮削成功: {}", 
-                                                    if is_group { "文件组" } else { "单文件" },
-                                                    display_name, title);
-                                                scraped_count += 1;
-                                                
-                                                // 同步演员到数据库
-                                                if let Some(actors) = scrape_data.get("actors").and_then(|v| v.as_array()) {
-                                                    let actor_names: Vec<String> = actors.iter()
-                                                        .filter_map(|v| v.as_str())
-                                                        .map(String::from)
-                                                        .collect();
-                                                    sync_actors_to_db(&state, &actor_names, &media_id).await;
-                                                }
-                                                
-                                                // 更新成功计数
-                                                {
-                                                    let mut progress_map = SCRAPE_PROGRESS.write().await;
-                                                    if let Some(progress) = progress_map.get_mut(&session_id) {
-                                                        progress.scraped_count = scraped_count;
-                                                    }
-                                                }
-                                            }
-                                            Err(e) => {
-                                                error!("{} {} 保存文件关联失败: {}", 
-                                                    if is_group { "文件组" } else { "单文件" },
-                                                    display_name, e);
-                                                failed_count += 1;
-                                            }
-                                        }
+                                        // 更新成功计数
+                                        SCRAPE_PROGRESS.update(&session_id, |progress| {
+                                            progress.scraped_count = scraped_count;
+                                        });
                                     }
                                     Err(e) => {
                                         error!("{} {} 保存到数据库失败: {}", 
@@ -791,12 +1748,9 @@ async fn process_auto_scrape(
                 }
                 
                 // 更新失败计数
-                {
-                    let mut progress_map = SCRAPE_PROGRESS.write().await;
-                    if let Some(progress) = progress_map.get_mut(&session_id) {
-                        progress.failed_count = failed_count;
-                    }
-                }
+                SCRAPE_PROGRESS.update(&session_id, |progress| {
+                    progress.failed_count = failed_count;
+                });
             }
         }
         Err(e) => {
@@ -808,13 +1762,10 @@ async fn process_auto_scrape(
     info!("自动刮削完成：成功 {} 个，失败 {} 个", scraped_count, failed_count);
     
     // 标记完成
-    {
-        let mut progress_map = SCRAPE_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
-            progress.status = "completed".to_string();
-            progress.message = Some(format!("刮削完成：成功 {} 个，失败 {} 个", scraped_count, failed_count));
-        }
-    }
+    SCRAPE_PROGRESS.update(&session_id, |progress| {
+        progress.status = "completed".to_string();
+        progress.message = Some(format!("刮削完成：成功 {} 个，失败 {} 个", scraped_count, failed_count));
+    });
     
     Ok(())
 }
@@ -984,64 +1935,14 @@ fn apply_scrape_result_to_media(media: &mut crate::models::MediaItem, scrape_dat
     }
 }
 
-/// 同步演员到数据库
-async fn sync_actors_to_db(state: &AppState, actor_names: &[String], media_id: &str) {
-    use crate::database::actor_repository::{find_or_create_actor_by_name, add_actor_to_media};
-    
-    info!("开始同步演员到数据库: media_id={}, 演员数量={}", media_id, actor_names.len());
-    
-    if actor_names.is_empty() {
-        warn!("演员列表为空，跳过同步");
-        return;
-    }
-    
-    for actor_name in actor_names {
-        info!("处理演员: {}", actor_name);
-        
-        // 查找或创建演员
-        match find_or_create_actor_by_name(state.database.pool(), actor_name).await {
-            Ok(actor) => {
-                info!("演员已找到或创建: {} (id={})", actor.name, actor.id);
-                
-                // 建立演员与媒体的关联
-                match add_actor_to_media(
-                    state.database.pool(),
-                    &actor.id,
-                    media_id,
-                    None,  // character_name
-                    Some("cast".to_string()), // role
-                ).await {
-                    Ok(_) => {
-                        info!("✓ 演员 {} 已关联到媒体 {}", actor.name, media_id);
-                    }
-                    Err(e) => {
-                        error!("✗ 关联演员 {} 到媒体 {} 失败: {}", actor.name, media_id, e);
-                    }
-                }
-            }
-            Err(e) => {
-                error!("✗ 查找或创建演员 {} 失败: {}", actor_name, e);
-            }
-        }
-    }
-    
-    info!("演员同步完成: media_id={}", media_id);
-}
-
 pub async fn get_auto_scrape_progress(
     State(_state): State<AppState>,
     Path(session_id): Path<String>,
 ) -> Result<Json<AutoScrapeProgress>, (StatusCode, String)> {
     info!("查询进度，会话ID: {}", session_id);
-    let progress_map = SCRAPE_PROGRESS.read().await;
-    
-    // 调试：显示所有会话ID
-    let all_sessions: Vec<String> = progress_map.keys().cloned().collect();
-    info!("当前所有会话: {:?}", all_sessions);
-    
-    if let Some(progress) = progress_map.get(&session_id) {
+    if let Some(progress) = SCRAPE_PROGRESS.get(&session_id) {
         info!("找到进度：{:?}", progress);
-        Ok(Json(progress.clone()))
+        Ok(Json(progress))
     } else {
         warn!("会话未找到: {}", session_id);
         Err((StatusCode::NOT_FOUND, format!("Session not found: {}", session_id)))
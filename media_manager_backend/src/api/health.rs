@@ -4,6 +4,7 @@ use axum::{
 };
 use serde_json::json;
 
+use crate::services::hwaccel;
 use super::AppState;
 use super::error::{ApiError, ApiResult};
 use super::response::success;
@@ -30,7 +31,9 @@ pub async fn health_check(
         "timestamp": chrono::Utc::now().to_rfc3339(),
         "version": "1.0.0",
         "database": "connected",
-        "tmdb_api": tmdb_status
+        "tmdb_api": tmdb_status,
+        // 启动时的检测完成前为 null
+        "hardware_acceleration": hwaccel::capabilities()
     })))
 }
 
@@ -58,7 +61,8 @@ pub async fn get_stats(
         "tmdb_cache": {
             "search_cache_size": cache_stats.search_cache_size,
             "details_cache_size": cache_stats.details_cache_size,
-            "popular_cache_size": cache_stats.popular_cache_size
+            "popular_cache_size": cache_stats.popular_cache_size,
+            "providers_cache_size": cache_stats.providers_cache_size
         },
         "popular_tags": stats.popular_tags.iter().take(5).map(|tag| json!({
             "name": tag.name,
@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+
+use crate::database;
+use crate::models::{BrowsingHistoryItem, MediaItemResponse, BROWSING_HISTORY_LIMIT, DEFAULT_HISTORY_USER};
+use super::AppState;
+use super::error::ApiResult;
+use super::media::localize_responses;
+use super::response::success;
+
+/// 区分浏览记录所属用户的请求头
+const HISTORY_USER_HEADER: &str = "x-user-id";
+
+#[derive(Debug, Deserialize)]
+pub struct BrowsingHistoryParams {
+    pub limit: Option<i64>,
+}
+
+/// 请求所属的用户（X-User-Id 请求头，未提供时为 default）
+pub(crate) fn history_user(headers: &HeaderMap) -> String {
+    headers.get(HISTORY_USER_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .unwrap_or(DEFAULT_HISTORY_USER)
+        .to_string()
+}
+
+/// 记录媒体详情浏览（失败不影响详情页）
+pub(crate) async fn record_media_view(state: &AppState, headers: &HeaderMap, media_id: &str) {
+    if let Err(e) = database::record_browsing(state.database.pool(), &history_user(headers), media_id).await {
+        tracing::warn!("Failed to record browsing history for {}: {}", media_id, e);
+    }
+}
+
+/// 获取最近浏览的媒体
+/// GET /api/history/browsing
+pub async fn list_browsing_history_handler(
+    Query(params): Query<BrowsingHistoryParams>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<impl IntoResponse> {
+    let limit = params.limit.unwrap_or(20).clamp(1, BROWSING_HISTORY_LIMIT);
+    let entries = database::list_browsing_history(state.database.pool(), &history_user(&headers), limit).await?;
+
+    let (entries, media): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+    let mut responses: Vec<MediaItemResponse> = media.into_iter().map(MediaItemResponse::from).collect();
+    localize_responses(&state, &headers, &mut responses).await;
+
+    let items: Vec<BrowsingHistoryItem> = entries.into_iter()
+        .zip(responses)
+        .map(|(entry, media)| BrowsingHistoryItem {
+            media,
+            view_count: entry.view_count,
+            viewed_at: entry.viewed_at,
+        })
+        .collect();
+    Ok(success(items))
+}
+
+/// 清空浏览记录
+/// DELETE /api/history/browsing
+pub async fn clear_browsing_history_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<impl IntoResponse> {
+    let removed = database::clear_browsing_history(state.database.pool(), &history_user(&headers), None).await?;
+    Ok(success(serde_json::json!({ "removed": removed })))
+}
+
+/// 从浏览记录中移除单个媒体
+/// DELETE /api/history/browsing/:media_id
+pub async fn remove_browsing_history_handler(
+    Path(media_id): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<impl IntoResponse> {
+    let removed = database::clear_browsing_history(state.database.pool(), &history_user(&headers), Some(&media_id)).await?;
+    Ok(success(serde_json::json!({ "removed": removed })))
+}
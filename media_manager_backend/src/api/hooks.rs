@@ -0,0 +1,135 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::database;
+use crate::models::{command_hooks_enabled, CreateImportHookRequest, HookContext, ImportHook, UpdateImportHookRequest, HOOK_COMMANDS_ENV};
+use crate::services::import_hooks::{fire_hook, hook_context};
+use super::AppState;
+use super::error::{ApiError, ApiResult};
+use super::response::{success, success_message};
+
+const DEFAULT_EVENT_LIMIT: i64 = 100;
+const MAX_EVENT_LIMIT: i64 = 1000;
+
+// ============ Import Hooks ============
+
+/// 命令钩子需要服务器显式开启
+fn ensure_hook_allowed(hook: &ImportHook) -> ApiResult<()> {
+    if hook.hook_type == "command" && !command_hooks_enabled() {
+        return Err(ApiError::Forbidden(format!(
+            "Command hooks are disabled on this server (set {}=true to enable)",
+            HOOK_COMMANDS_ENV
+        )));
+    }
+    Ok(())
+}
+
+/// 获取导入钩子
+/// GET /api/hooks
+pub async fn list_hooks_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let hooks = database::list_import_hooks(state.database.pool()).await?;
+    Ok(success(hooks))
+}
+
+/// 添加导入钩子
+/// POST /api/hooks
+pub async fn create_hook_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateImportHookRequest>,
+) -> ApiResult<impl IntoResponse> {
+    let hook = ImportHook::new(payload);
+    hook.validate().map_err(ApiError::Validation)?;
+    ensure_hook_allowed(&hook)?;
+    database::save_import_hook(state.database.pool(), &hook).await?;
+    Ok(success(hook))
+}
+
+/// 修改导入钩子
+/// PUT /api/hooks/:id
+pub async fn update_hook_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<UpdateImportHookRequest>,
+) -> ApiResult<impl IntoResponse> {
+    let mut hook = database::get_import_hook(state.database.pool(), &id).await?
+        .ok_or_else(|| ApiError::NotFound("Hook not found".to_string()))?;
+    hook.apply_update(payload);
+    hook.validate().map_err(ApiError::Validation)?;
+    ensure_hook_allowed(&hook)?;
+    database::save_import_hook(state.database.pool(), &hook).await?;
+    Ok(success(hook))
+}
+
+/// 删除导入钩子
+/// DELETE /api/hooks/:id
+pub async fn delete_hook_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if !database::delete_import_hook(state.database.pool(), &id).await? {
+        return Err(ApiError::NotFound("Hook not found".to_string()));
+    }
+    Ok(success_message("Hook deleted successfully"))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TestHookRequest {
+    /// 使用该媒体的信息和文件执行（为空时使用示例数据）
+    pub media_id: Option<String>,
+}
+
+/// 立即执行一次钩子（结果同样记录到执行记录中）
+/// POST /api/hooks/:id/test
+pub async fn test_hook_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    body: Option<Json<TestHookRequest>>,
+) -> ApiResult<impl IntoResponse> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    let pool = state.database.pool();
+    let hook = database::get_import_hook(pool, &id).await?
+        .ok_or_else(|| ApiError::NotFound("Hook not found".to_string()))?;
+
+    let context = match request.media_id {
+        Some(media_id) => {
+            let media = database::get_media_with(pool, &media_id).await?
+                .ok_or_else(|| ApiError::NotFound("Media not found".to_string()))?;
+            let files = database::get_local_file_paths(pool, &media_id).await?;
+            hook_context(&media, &files)
+        }
+        None => HookContext {
+            event: "test".to_string(),
+            title: "Test".to_string(),
+            ..Default::default()
+        },
+    };
+
+    let result = fire_hook(pool, &hook, &context).await?;
+    Ok(success(result))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HookEventsParams {
+    pub hook_id: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// 获取钩子执行记录（最新的在前）
+/// GET /api/hooks/events
+pub async fn list_hook_events_handler(
+    Query(params): Query<HookEventsParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let limit = params.limit.unwrap_or(DEFAULT_EVENT_LIMIT);
+    if !(1..=MAX_EVENT_LIMIT).contains(&limit) {
+        return Err(ApiError::Validation(format!("limit must be between 1 and {}", MAX_EVENT_LIMIT)));
+    }
+    let events = database::list_hook_events(state.database.pool(), params.hook_id.as_deref(), limit).await?;
+    Ok(success(events))
+}
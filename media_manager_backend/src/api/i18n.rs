@@ -0,0 +1,127 @@
+//! 错误消息国际化
+//!
+//! 按请求的 `Accept-Language` 选择错误消息语言（zh/en）。
+//! 未携带该请求头时保持原始消息不变，兼容旧客户端。
+
+use axum::{
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+use super::error::ErrorPayload;
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Zh,
+    En,
+}
+
+impl Language {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Zh => "zh",
+            Language::En => "en",
+        }
+    }
+
+    /// 解析 Accept-Language，按 q 值选出第一个支持的语言
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut candidates: Vec<(f32, &str)> = header.split(',')
+            .filter_map(|part| {
+                let mut pieces = part.trim().split(';');
+                let tag = pieces.next()?.trim();
+                let quality = pieces
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (!tag.is_empty()).then_some((quality, tag))
+            })
+            .collect();
+        // 稳定排序，q 值相同时保持原顺序
+        candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        candidates.into_iter()
+            .filter(|(quality, _)| *quality > 0.0)
+            .find_map(|(_, tag)| {
+                let primary = tag.split(['-', '_']).next().unwrap_or(tag).to_ascii_lowercase();
+                match primary.as_str() {
+                    "zh" => Some(Language::Zh),
+                    "en" => Some(Language::En),
+                    _ => None,
+                }
+            })
+    }
+}
+
+/// 按 Accept-Language 重写错误响应的 message
+///
+/// 优先使用错误自带的多语言消息（如插件返回的 zh/en），否则使用错误码目录中的消息，
+/// 原始消息放入 `detail` 字段。
+pub async fn localize_errors(request: Request, next: Next) -> Response {
+    let language = request.headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(Language::from_accept_language);
+
+    let mut response = next.run(request).await;
+    let Some(language) = language else {
+        return response;
+    };
+    let Some(payload) = response.extensions_mut().remove::<ErrorPayload>() else {
+        return response;
+    };
+
+    let lang = language.as_str();
+    let message = payload.localized.as_ref()
+        .and_then(|m| m.get(lang))
+        .map(str::to_string)
+        .unwrap_or_else(|| payload.code.localized_message().get(lang).unwrap_or_default().to_string());
+
+    let mut error = json!({
+        "type": payload.code.error_type(),
+        "code": payload.code,
+        "message": message,
+        "lang": lang,
+    });
+    if payload.message != message {
+        error["detail"] = json!(payload.message);
+    }
+    if !payload.details.is_empty() {
+        error["details"] = json!(payload.details);
+    }
+    if let Some(plugin_code) = &payload.plugin_code {
+        error["plugin_code"] = json!(plugin_code);
+    }
+
+    let status = response.status();
+    let mut localized = (status, Json(json!({ "success": false, "error": error }))).into_response();
+    localized.headers_mut().insert(header::CONTENT_LANGUAGE, header::HeaderValue::from_static(lang));
+    // 保留原响应的其他头（如 CORS）
+    for (name, value) in response.headers() {
+        if name != header::CONTENT_LENGTH && name != header::CONTENT_TYPE {
+            localized.headers_mut().entry(name.clone()).or_insert_with(|| value.clone());
+        }
+    }
+    localized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_language() {
+        assert_eq!(Language::from_accept_language("zh-CN,zh;q=0.9,en;q=0.8"), Some(Language::Zh));
+        assert_eq!(Language::from_accept_language("en-US"), Some(Language::En));
+        assert_eq!(Language::from_accept_language("fr;q=0.9, en;q=0.5"), Some(Language::En));
+        assert_eq!(Language::from_accept_language("zh;q=0.3, en;q=0.7"), Some(Language::En));
+        assert_eq!(Language::from_accept_language("zh;q=0, en;q=0.1"), Some(Language::En));
+        assert_eq!(Language::from_accept_language("fr, de"), None);
+        assert_eq!(Language::from_accept_language(""), None);
+    }
+}
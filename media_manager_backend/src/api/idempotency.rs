@@ -0,0 +1,213 @@
+//! 幂等键中间件
+//!
+//! 客户端在创建/批量接口上携带 `Idempotency-Key` 请求头时，保存请求哈希和响应，
+//! 在保存期内用相同的键重试会直接重放之前的响应，不会重复创建数据。
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+
+use super::error::{ApiError, ErrorCode};
+use crate::database::{
+    claim_idempotency_key, complete_idempotency_key, get_idempotency_record,
+    release_idempotency_key, Database,
+};
+use crate::models::{IdempotencyRecord, IDEMPOTENCY_WINDOW_HOURS, MAX_IDEMPOTENCY_KEY_LEN};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// 重放的响应带上此响应头
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// 幂等请求体的最大字节数
+const MAX_IDEMPOTENT_BODY_BYTES: usize = 64 * 1024 * 1024;
+/// 保存的响应体最大字节数，超过时不保存响应并释放幂等键（响应照常返回）
+const MAX_STORED_RESPONSE_BYTES: usize = 8 * 1024 * 1024;
+/// 处理中的记录超过该时间视为服务中断遗留，允许重新处理
+const PENDING_TIMEOUT_MINUTES: i64 = 10;
+
+fn request_hash(method: &str, uri: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(uri.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+fn replay(record: IdempotencyRecord) -> Response {
+    let status = record.status_code
+        .and_then(|code| StatusCode::from_u16(code as u16).ok())
+        .unwrap_or(StatusCode::OK);
+    let mut response = (status, record.response_body.unwrap_or_default()).into_response();
+    let headers = response.headers_mut();
+    if let Some(content_type) = record.content_type.and_then(|v| HeaderValue::from_str(&v).ok()) {
+        headers.insert(header::CONTENT_TYPE, content_type);
+    }
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+/// 读取到的响应体
+enum ResponseBody {
+    /// 未超过上限，已全部读入内存
+    Buffered(Bytes),
+    /// 超过上限，已读取的部分和剩余的流拼回完整响应体
+    TooLarge(Body),
+}
+
+/// 最多读取 limit 字节的响应体，超过时不再继续缓冲
+async fn buffer_response_body(body: Body, limit: usize) -> Result<ResponseBody, axum::Error> {
+    let mut stream = body.into_data_stream();
+    let mut chunks = Vec::new();
+    let mut len = 0;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        len += chunk.len();
+        chunks.push(chunk);
+        if len > limit {
+            let head = futures_util::stream::iter(chunks.into_iter().map(Ok::<_, axum::Error>));
+            return Ok(ResponseBody::TooLarge(Body::from_stream(head.chain(stream))));
+        }
+    }
+    Ok(ResponseBody::Buffered(Bytes::from(chunks.concat())))
+}
+
+/// 幂等键中间件（挂在需要幂等的路由上）
+pub async fn idempotent(State(database): State<Database>, request: Request, next: Next) -> Response {
+    let key = match request.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        None => return next.run(request).await,
+        Some(value) => match value.to_str().map(str::trim) {
+            Ok(key) if !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN => key.to_string(),
+            _ => {
+                return ApiError::invalid_field(
+                    "Idempotency-Key",
+                    format!("Idempotency-Key must be 1-{} visible ASCII characters", MAX_IDEMPOTENCY_KEY_LEN),
+                ).into_response();
+            }
+        },
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, MAX_IDEMPOTENT_BODY_BYTES).await {
+        Ok(body) => body,
+        Err(e) => return ApiError::BadRequest(format!("Failed to read request body: {}", e)).into_response(),
+    };
+    let method = parts.method.to_string();
+    let path = parts.uri.path().to_string();
+    let hash = request_hash(&method, &parts.uri.to_string(), &body);
+    let pool = database.pool();
+
+    let claimed = match claim_idempotency_key(pool, &key, &method, &path, &hash).await {
+        Ok(claimed) => claimed,
+        Err(e) => return ApiError::Internal(format!("Failed to claim idempotency key: {}", e)).into_response(),
+    };
+    if !claimed {
+        let record = match get_idempotency_record(pool, &key, &method, &path).await {
+            Ok(Some(record)) => record,
+            // 记录刚被其他请求释放，让客户端稍后重试
+            Ok(None) => {
+                return ApiError::coded(ErrorCode::IdempotencyInProgress, "Idempotency key is being released, retry later")
+                    .into_response();
+            }
+            Err(e) => return ApiError::Internal(format!("Failed to load idempotency key: {}", e)).into_response(),
+        };
+        // 过期的键由定期清理任务删除，删除前按新键处理
+        let expired = record.created_at < chrono::Utc::now() - chrono::Duration::hours(IDEMPOTENCY_WINDOW_HOURS);
+        if !expired {
+            if record.request_hash != hash {
+                return ApiError::coded(
+                    ErrorCode::IdempotencyKeyReused,
+                    "Idempotency-Key was already used with a different request",
+                ).into_response();
+            }
+            if !record.is_pending() {
+                tracing::info!("Replaying idempotent response for {} {} (key: {})", method, path, key);
+                return replay(record);
+            }
+            let stale = record.created_at < chrono::Utc::now() - chrono::Duration::minutes(PENDING_TIMEOUT_MINUTES);
+            if !stale {
+                return ApiError::coded(
+                    ErrorCode::IdempotencyInProgress,
+                    "A request with this Idempotency-Key is still in progress",
+                ).into_response();
+            }
+        }
+        // 过期的记录或遗留的处理中记录：重新占用后处理
+        let reclaimed = async {
+            release_idempotency_key(pool, &key, &method, &path).await?;
+            claim_idempotency_key(pool, &key, &method, &path, &hash).await
+        }.await;
+        if !matches!(reclaimed, Ok(true)) {
+            return ApiError::coded(
+                ErrorCode::IdempotencyInProgress,
+                "A request with this Idempotency-Key is still in progress",
+            ).into_response();
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    // 服务端错误不保存，允许客户端用相同的键重试
+    if response.status().is_server_error() {
+        if let Err(e) = release_idempotency_key(pool, &key, &method, &path).await {
+            tracing::warn!("Failed to release idempotency key {}: {}", key, e);
+        }
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = match buffer_response_body(body, MAX_STORED_RESPONSE_BYTES).await {
+        Ok(ResponseBody::Buffered(body)) => body,
+        Ok(ResponseBody::TooLarge(body)) => {
+            tracing::warn!("Response for {} {} is too large to store, releasing idempotency key {}", method, path, key);
+            if let Err(e) = release_idempotency_key(pool, &key, &method, &path).await {
+                tracing::warn!("Failed to release idempotency key {}: {}", key, e);
+            }
+            return Response::from_parts(parts, body);
+        }
+        Err(e) => {
+            let _ = release_idempotency_key(pool, &key, &method, &path).await;
+            return ApiError::Internal(format!("Failed to read response body: {}", e)).into_response();
+        }
+    };
+    let content_type = parts.headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
+    if let Err(e) = complete_idempotency_key(pool, &key, &method, &path, parts.status.as_u16(), content_type, &body).await {
+        tracing::warn!("Failed to save idempotent response for key {}: {}", key, e);
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_hash() {
+        let hash = request_hash("POST", "/api/media", br#"{"title":"a"}"#);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, request_hash("POST", "/api/media", br#"{"title":"a"}"#));
+        assert_ne!(hash, request_hash("POST", "/api/media", br#"{"title":"b"}"#));
+        assert_ne!(hash, request_hash("POST", "/api/collections", br#"{"title":"a"}"#));
+    }
+
+    #[tokio::test]
+    async fn test_buffer_response_body_limit() {
+        match buffer_response_body(Body::from("small"), 8).await.unwrap() {
+            ResponseBody::Buffered(body) => assert_eq!(&body[..], b"small"),
+            ResponseBody::TooLarge(_) => panic!("body under the limit should be buffered"),
+        }
+        // 超过上限时不缓冲，但返回的响应体仍然完整
+        match buffer_response_body(Body::from("larger than limit"), 8).await.unwrap() {
+            ResponseBody::Buffered(_) => panic!("body over the limit should not be buffered"),
+            ResponseBody::TooLarge(body) => assert_eq!(&to_bytes(body, usize::MAX).await.unwrap()[..], b"larger than limit"),
+        }
+    }
+}
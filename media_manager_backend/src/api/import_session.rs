@@ -0,0 +1,460 @@
+//! 分块导入 API 端点
+//!
+//! 大体积的导入文件先分块上传到临时文件，提交后在后台流式解析并导入，
+//! 通过会话进度接口查询导入状态。媒体条目逐条解析、逐条导入，不会整体读入内存。
+//! 会话进度登记在 `ProgressRegistry` 中，过期移除后遗留的临时文件在创建新会话时清理。
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path as FsPath, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, Mutex};
+
+use crate::services::progress::{ProgressRegistry, SessionProgress, STALE_SESSION_TTL};
+use super::AppState;
+use super::error::{ApiError, ApiResult};
+use super::media::{
+    import_payload, ImportActorItem, ImportActorMediaRelation, ImportCollectionItem, ImportConflictStrategy,
+    ImportDataRequest, ImportDataResponse, ImportMediaItem, ImportSeriesItem,
+};
+use super::response::{success, success_message};
+
+/// 单个分块的最大字节数
+pub const MAX_IMPORT_CHUNK_BYTES: usize = 16 * 1024 * 1024;
+
+lazy_static::lazy_static! {
+    static ref IMPORT_SESSIONS: ProgressRegistry<ImportSessionProgress> = ProgressRegistry::new();
+}
+
+/// 分块按顺序追加到临时文件，同一时间只写入一个分块
+static UPLOAD_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// 逐条导入时解析器最多领先导入的媒体条数
+const MEDIA_CHANNEL_CAPACITY: usize = 64;
+
+/// 分块导入进度
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSessionProgress {
+    pub session_id: String,
+    pub status: String,  // "uploading", "parsing", "importing", "completed", "failed"
+    pub message: Option<String>,
+    pub chunks_received: usize,
+    pub bytes_received: u64,
+    /// 已处理的媒体数
+    pub current: usize,
+    pub total: usize,
+    pub current_item: Option<String>,
+    pub completed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<ImportDataResponse>,
+    /// 覆盖导入文件中的 on_conflict
+    #[serde(skip)]
+    on_conflict: Option<ImportConflictStrategy>,
+}
+
+impl SessionProgress for ImportSessionProgress {
+    fn is_finished(&self) -> bool {
+        self.completed
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CreateImportSessionRequest {
+    /// 覆盖导入文件中的 on_conflict
+    pub on_conflict: Option<ImportConflictStrategy>,
+}
+
+fn session_dir() -> PathBuf {
+    std::env::temp_dir().join("media_manager_import")
+}
+
+fn session_file(session_id: &str) -> PathBuf {
+    session_dir().join(format!("{}.json", session_id))
+}
+
+async fn create_session_file(session_id: &str) -> std::io::Result<()> {
+    tokio::fs::create_dir_all(session_dir()).await?;
+    tokio::fs::File::create(session_file(session_id)).await?;
+    Ok(())
+}
+
+fn session_not_found(session_id: &str) -> ApiError {
+    ApiError::NotFound(format!("Import session not found: {}", session_id))
+}
+
+/// 删除不属于任何会话且超过 STALE_SESSION_TTL 未修改的临时文件
+/// （过期被移除的会话、上次运行遗留的）
+async fn remove_orphaned_files() {
+    let Ok(mut entries) = tokio::fs::read_dir(session_dir()).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let owned = path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|session_id| IMPORT_SESSIONS.get(session_id).is_some());
+        // 临时目录可能被其他实例共用，只删除很久没有写入的文件
+        let stale = entry.metadata().await
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age >= STALE_SESSION_TTL));
+        if !owned && stale {
+            let _ = tokio::fs::remove_file(&path).await;
+        }
+    }
+}
+
+/// 更新导入会话中媒体的处理进度
+pub(crate) fn report_import_progress(session_id: &str, current: usize, total: usize, item: &str) {
+    IMPORT_SESSIONS.update(session_id, |progress| {
+        progress.current = current;
+        progress.total = total;
+        progress.current_item = Some(item.to_string());
+    });
+}
+
+/// 创建分块导入会话
+/// POST /api/data/import/session
+pub async fn create_import_session(
+    body: Option<Json<CreateImportSessionRequest>>,
+) -> ApiResult<impl IntoResponse> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    let session_id = IMPORT_SESSIONS.create(ImportSessionProgress {
+        session_id: String::new(),
+        status: "uploading".to_string(),
+        message: None,
+        chunks_received: 0,
+        bytes_received: 0,
+        current: 0,
+        total: 0,
+        current_item: None,
+        completed: false,
+        result: None,
+        on_conflict: request.on_conflict,
+    });
+    IMPORT_SESSIONS.update(&session_id, |progress| progress.session_id = session_id.clone());
+    remove_orphaned_files().await;
+
+    if let Err(e) = create_session_file(&session_id).await {
+        IMPORT_SESSIONS.remove(&session_id);
+        return Err(ApiError::Internal(format!("Failed to create import file: {}", e)));
+    }
+
+    let progress = IMPORT_SESSIONS.get(&session_id).ok_or_else(|| session_not_found(&session_id))?;
+    Ok(success(progress))
+}
+
+/// 上传分块（分块必须按顺序上传，index 从 0 开始）
+/// PUT /api/data/import/session/:id/chunks/:index
+pub async fn upload_import_chunk(
+    Path((session_id, index)): Path<(String, usize)>,
+    body: Bytes,
+) -> ApiResult<impl IntoResponse> {
+    if body.is_empty() {
+        return Err(ApiError::Validation("Chunk is empty".to_string()));
+    }
+
+    let _guard = UPLOAD_LOCK.lock().await;
+    let progress = IMPORT_SESSIONS.get(&session_id).ok_or_else(|| session_not_found(&session_id))?;
+    if progress.status != "uploading" {
+        return Err(ApiError::BadRequest(format!("Import session is already {}", progress.status)));
+    }
+    if index != progress.chunks_received {
+        return Err(ApiError::Validation(format!(
+            "Expected chunk {}, got {}", progress.chunks_received, index
+        )));
+    }
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .append(true)
+        .open(session_file(&session_id))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to open import file: {}", e)))?;
+    file.write_all(&body).await
+        .map_err(|e| ApiError::Internal(format!("Failed to write import chunk: {}", e)))?;
+
+    IMPORT_SESSIONS.update(&session_id, |progress| {
+        progress.chunks_received += 1;
+        progress.bytes_received += body.len() as u64;
+    });
+    let progress = IMPORT_SESSIONS.get(&session_id).ok_or_else(|| session_not_found(&session_id))?;
+    Ok(success(progress))
+}
+
+/// 提交会话，在后台解析并导入已上传的数据
+/// POST /api/data/import/session/:id/commit
+pub async fn commit_import_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    // 在同一次更新中检查并切换状态，重复提交只有一次生效
+    let _guard = UPLOAD_LOCK.lock().await;
+    let mut checked: Result<Option<ImportConflictStrategy>, ApiError> = Err(session_not_found(&session_id));
+    IMPORT_SESSIONS.update(&session_id, |progress| {
+        checked = if progress.status != "uploading" {
+            Err(ApiError::BadRequest(format!("Import session is already {}", progress.status)))
+        } else if progress.chunks_received == 0 {
+            Err(ApiError::Validation("No chunks uploaded".to_string()))
+        } else {
+            progress.status = "parsing".to_string();
+            progress.message = Some("正在解析导入数据...".to_string());
+            Ok(progress.on_conflict)
+        };
+    });
+    let on_conflict = checked?;
+    let progress = IMPORT_SESSIONS.get(&session_id).ok_or_else(|| session_not_found(&session_id))?;
+
+    let path = session_file(&session_id);
+    tokio::spawn(run_import_session(state, session_id, path, on_conflict));
+
+    Ok(success(progress))
+}
+
+/// 导入文件中除媒体以外的部分，媒体只校验并计数，导入时再逐条读取
+#[derive(Deserialize)]
+struct ImportFileHeader {
+    version: String,
+    #[serde(default)]
+    on_conflict: ImportConflictStrategy,
+    #[serde(deserialize_with = "count_media")]
+    media: usize,
+    collections: Option<Vec<ImportCollectionItem>>,
+    actors: Option<Vec<ImportActorItem>>,
+    actor_media_relations: Option<Vec<ImportActorMediaRelation>>,
+    studios: Option<Vec<String>>,
+    series: Option<Vec<ImportSeriesItem>>,
+}
+
+impl ImportFileHeader {
+    fn into_request(self) -> (ImportDataRequest, usize) {
+        let request = ImportDataRequest {
+            version: self.version,
+            on_conflict: self.on_conflict,
+            media: Vec::new(),
+            collections: self.collections,
+            actors: self.actors,
+            actor_media_relations: self.actor_media_relations,
+            studios: self.studios,
+            series: self.series,
+        };
+        (request, self.media)
+    }
+}
+
+fn count_media<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    MediaEntries(|_| true).deserialize(deserializer)
+}
+
+/// 逐条解析 media 数组，每条交给回调，回调返回 false 时中止；返回条目数
+struct MediaEntries<F>(F);
+
+impl<'de, F: FnMut(ImportMediaItem) -> bool> DeserializeSeed<'de> for MediaEntries<F> {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<usize, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(ImportMediaItem) -> bool> Visitor<'de> for MediaEntries<F> {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of media")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<usize, A::Error> {
+        let mut count = 0;
+        while let Some(item) = seq.next_element::<ImportMediaItem>()? {
+            count += 1;
+            if !(self.0)(item) {
+                return Err(de::Error::custom("import cancelled"));
+            }
+        }
+        Ok(count)
+    }
+}
+
+/// 在导入文件的顶层对象中找到 media 数组并逐条解析，跳过其他字段
+struct ImportFileMedia<F>(F);
+
+impl<'de, F: FnMut(ImportMediaItem) -> bool> Visitor<'de> for ImportFileMedia<F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an import object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "media" {
+                map.next_value_seed(MediaEntries(&mut self.0))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 第一遍解析：读取媒体以外的部分并校验媒体条目
+fn read_import_header(path: &FsPath) -> anyhow::Result<(ImportDataRequest, usize)> {
+    let file = std::fs::File::open(path)?;
+    let header: ImportFileHeader = serde_json::from_reader(std::io::BufReader::new(file))?;
+    Ok(header.into_request())
+}
+
+/// 第二遍解析：逐条把媒体发送到通道（通道容量有限，解析不会领先导入太多），接收端关闭时停止
+fn send_import_media(path: &FsPath, sender: mpsc::Sender<ImportMediaItem>) -> anyhow::Result<()> {
+    let file = std::fs::File::open(path)?;
+    let mut deserializer = serde_json::Deserializer::from_reader(std::io::BufReader::new(file));
+    deserializer.deserialize_map(ImportFileMedia(|item| sender.blocking_send(item).is_ok()))?;
+    Ok(())
+}
+
+async fn run_import_session(
+    state: AppState,
+    session_id: String,
+    path: PathBuf,
+    on_conflict: Option<ImportConflictStrategy>,
+) {
+    // 从临时文件流式解析，避免把整个文件读入内存
+    let parse_path = path.clone();
+    let parsed = tokio::task::spawn_blocking(move || read_import_header(&parse_path)).await;
+
+    let (mut payload, total) = match parsed {
+        Ok(Ok(parsed)) => parsed,
+        Ok(Err(e)) => return fail_session(&session_id, &path, format!("Invalid import data: {}", e)).await,
+        Err(e) => return fail_session(&session_id, &path, format!("Parse task failed: {}", e)).await,
+    };
+    if let Some(on_conflict) = on_conflict {
+        payload.on_conflict = on_conflict;
+    }
+
+    IMPORT_SESSIONS.update(&session_id, |progress| {
+        progress.status = "importing".to_string();
+        progress.message = Some(format!("正在导入 {} 个媒体...", total));
+        progress.total = total;
+    });
+
+    let (sender, receiver) = mpsc::channel(MEDIA_CHANNEL_CAPACITY);
+    let media_path = path.clone();
+    let reader = tokio::task::spawn_blocking(move || send_import_media(&media_path, sender));
+    let media = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|item| (item, receiver))
+    });
+    let mut result = import_payload(&state, &payload, media, total, Some(&session_id)).await;
+    match reader.await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => result.errors.push(format!("Failed to read media: {}", e)),
+        Err(e) => result.errors.push(format!("Failed to read media: {}", e)),
+    }
+    let _ = tokio::fs::remove_file(&path).await;
+
+    IMPORT_SESSIONS.update(&session_id, |progress| {
+        progress.status = "completed".to_string();
+        progress.message = Some(format!(
+            "导入完成：新增 {} 个，更新 {} 个，跳过 {} 个，失败 {} 个",
+            result.media_imported, result.media_updated, result.media_skipped, result.media_failed
+        ));
+        progress.current = total;
+        progress.current_item = None;
+        progress.completed = true;
+        progress.result = Some(result);
+    });
+}
+
+async fn fail_session(session_id: &str, path: &PathBuf, message: String) {
+    tracing::warn!("Import session {} failed: {}", session_id, message);
+    let _ = tokio::fs::remove_file(path).await;
+    IMPORT_SESSIONS.update(session_id, |progress| {
+        progress.status = "failed".to_string();
+        progress.message = Some(message);
+        progress.completed = true;
+    });
+}
+
+/// 查询分块导入进度
+/// GET /api/data/import/session/:id
+pub async fn get_import_session(
+    Path(session_id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    let progress = IMPORT_SESSIONS.get(&session_id).ok_or_else(|| session_not_found(&session_id))?;
+    Ok(success(progress))
+}
+
+/// 取消或清理导入会话
+/// DELETE /api/data/import/session/:id
+pub async fn delete_import_session(
+    Path(session_id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    let _guard = UPLOAD_LOCK.lock().await;
+    let progress = IMPORT_SESSIONS.get(&session_id).ok_or_else(|| session_not_found(&session_id))?;
+    if matches!(progress.status.as_str(), "parsing" | "importing") {
+        return Err(ApiError::BadRequest("Import session is still running".to_string()));
+    }
+    IMPORT_SESSIONS.remove(&session_id);
+    let _ = tokio::fs::remove_file(session_file(&session_id)).await;
+    Ok(success_message("Import session deleted"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_import(dir: &tempfile::TempDir, value: serde_json::Value) -> PathBuf {
+        let path = dir.path().join("import.json");
+        std::fs::write(&path, value.to_string()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_read_media_incrementally() {
+        let dir = tempfile::tempdir().unwrap();
+        // media 不在最前面，其他字段照常读取
+        let path = write_import(&dir, serde_json::json!({
+            "version": "1.2",
+            "exported_at": "2024-01-01T00:00:00Z",
+            "media": [
+                { "title": "First", "media_type": "Movie" },
+                { "title": "Second", "media_type": "Scene", "code": "ABC-001" },
+            ],
+            "studios": ["Studio"],
+        }));
+
+        let (request, total) = read_import_header(&path).unwrap();
+        assert_eq!(total, 2);
+        assert!(request.media.is_empty());
+        assert_eq!(request.studios, Some(vec!["Studio".to_string()]));
+
+        let (sender, mut receiver) = mpsc::channel(MEDIA_CHANNEL_CAPACITY);
+        send_import_media(&path, sender).unwrap();
+        let mut titles = Vec::new();
+        while let Some(item) = receiver.blocking_recv() {
+            titles.push(item.title);
+        }
+        assert_eq!(titles, ["First", "Second"]);
+
+        // 接收端关闭后停止解析
+        let (sender, receiver) = mpsc::channel(1);
+        drop(receiver);
+        assert!(send_import_media(&path, sender).is_err());
+    }
+
+    #[test]
+    fn test_invalid_media_fails_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_import(&dir, serde_json::json!({
+            "version": "1.2",
+            "media": [{ "title": "Missing Type" }],
+        }));
+        assert!(read_import_header(&path).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::database;
+use crate::models::{
+    find_library_root, normalize_root_path, rescrape_fields, CreateLibraryRootRequest, DirectoryEntry,
+    DirectoryListing, LibraryRoot, ScrapeFieldMask, UpdateLibraryRootRequest,
+};
+use crate::services::library_watcher::refresh_root_status;
+use crate::services::storage::root_storage;
+use crate::services::link_checker::{check_media_links, DEFAULT_LINK_CHECK_CONCURRENCY, MAX_LINK_CHECK_CONCURRENCY};
+use super::AppState;
+use super::error::{ApiError, ApiResult};
+use super::response::{success, success_message};
+use super::scrape::{scrape_media, ScrapeMediaRequest};
+
+// ============ Link Check ============
+
+/// 链接检查请求
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CheckLinksRequest {
+    /// 要检查的媒体（为空时检查全部媒体）
+    pub ids: Vec<String>,
+    /// 同时检查的链接数
+    pub concurrency: Option<usize>,
+    /// 对失效的图片、预览和下载链接重新刮削对应字段
+    pub rescrape: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BrokenLinksParams {
+    pub media_id: Option<String>,
+}
+
+/// 立即检查远程链接
+/// POST /api/library/check-links
+pub async fn check_links_handler(
+    State(state): State<AppState>,
+    body: Option<Json<CheckLinksRequest>>,
+) -> ApiResult<impl IntoResponse> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    let concurrency = request.concurrency.unwrap_or(DEFAULT_LINK_CHECK_CONCURRENCY);
+    if concurrency == 0 || concurrency > MAX_LINK_CHECK_CONCURRENCY {
+        return Err(ApiError::Validation(format!(
+            "concurrency must be between 1 and {}",
+            MAX_LINK_CHECK_CONCURRENCY
+        )));
+    }
+
+    let pool = state.database.pool();
+    let ids = (!request.ids.is_empty()).then_some(request.ids.as_slice());
+    let mut result = check_media_links(pool, ids, concurrency).await
+        .map_err(|e| {
+            tracing::error!("Failed to check links: {}", e);
+            ApiError::Internal("Failed to check links".to_string())
+        })?;
+
+    if request.rescrape {
+        for media_id in result.broken_media.clone() {
+            let links = database::list_broken_links(pool, Some(&media_id)).await?;
+            let fields = rescrape_fields(&links);
+            if fields.is_empty() {
+                continue;
+            }
+            let scrape_request = ScrapeMediaRequest {
+                mode: "replace".to_string(),
+                code: None,
+                content_type: None,
+                series: None,
+                studio: None,
+                data: None,
+                result_token: None,
+                result_index: None,
+                create_new: false,
+                fields: ScrapeFieldMask { include_fields: fields, exclude_fields: Vec::new() },
+            };
+            match scrape_media(State(state.clone()), Path(media_id.clone()), Json(scrape_request)).await {
+                Ok(_) => result.rescraped += 1,
+                Err(e) => result.errors.push(format!("{}: rescrape failed: {}", media_id, e)),
+            }
+        }
+    }
+
+    Ok(success(result))
+}
+
+/// 获取失效链接列表
+/// GET /api/library/broken-links
+pub async fn list_broken_links_handler(
+    Query(params): Query<BrokenLinksParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let links = database::list_broken_links(state.database.pool(), params.media_id.as_deref()).await?;
+    Ok(success(links))
+}
+
+// ============ Library Roots ============
+
+/// 检查根目录设置，路径不能与其他根目录重复
+async fn validate_library_root(state: &AppState, root: &LibraryRoot) -> ApiResult<()> {
+    root.validate().map_err(ApiError::Validation)?;
+    if let Some(existing) = database::get_library_root_by_path(state.database.pool(), &root.path).await? {
+        if existing.id != root.id {
+            return Err(ApiError::Conflict(format!("Library root {} already exists", root.path)));
+        }
+    }
+    Ok(())
+}
+
+/// 获取媒体库根目录（含最近一次可用性检查结果）
+/// GET /api/library/roots
+pub async fn list_library_roots_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let roots = database::list_library_roots(state.database.pool()).await?;
+    Ok(success(roots))
+}
+
+/// 添加媒体库根目录
+/// POST /api/library/roots
+pub async fn create_library_root_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateLibraryRootRequest>,
+) -> ApiResult<impl IntoResponse> {
+    let mut root = LibraryRoot::new(payload);
+    validate_library_root(&state, &root).await?;
+    database::insert_library_root(state.database.pool(), &root).await?;
+    refresh_root_status(state.database.pool(), &mut root).await?;
+    Ok(success(root))
+}
+
+/// 修改媒体库根目录设置
+/// PUT /api/library/roots/:id
+pub async fn update_library_root_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<UpdateLibraryRootRequest>,
+) -> ApiResult<impl IntoResponse> {
+    let mut root = database::get_library_root(state.database.pool(), &id).await?
+        .ok_or_else(|| ApiError::NotFound("Library root not found".to_string()))?;
+    root.apply_update(payload);
+    validate_library_root(&state, &root).await?;
+    database::update_library_root(state.database.pool(), &root).await?;
+    if root.last_checked_at.is_none() {
+        refresh_root_status(state.database.pool(), &mut root).await?;
+    }
+    Ok(success(root))
+}
+
+/// 删除媒体库根目录（不删除目录中的文件）
+/// DELETE /api/library/roots/:id
+pub async fn delete_library_root_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if !database::delete_library_root(state.database.pool(), &id).await? {
+        return Err(ApiError::NotFound("Library root not found".to_string()));
+    }
+    Ok(success_message("Library root deleted successfully"))
+}
+
+// ============ Directory Browse ============
+
+#[derive(Debug, Deserialize)]
+pub struct BrowseQuery {
+    pub path: Option<String>,
+}
+
+/// 浏览媒体库根目录下的子目录（供扫描/导入时选择文件夹）
+/// GET /api/fs/browse?path=
+///
+/// 只允许浏览已配置的根目录内部，未指定路径时返回根目录列表
+pub async fn browse_directories_handler(
+    State(state): State<AppState>,
+    Query(query): Query<BrowseQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let roots = database::list_library_roots(state.database.pool()).await?;
+    let Some(path) = query.path.as_deref().map(str::trim).filter(|path| !path.is_empty()) else {
+        let entries = roots.into_iter()
+            .map(|root| DirectoryEntry { name: root.label.unwrap_or_else(|| root.path.clone()), path: root.path })
+            .collect();
+        return Ok(success(DirectoryListing { path: None, parent: None, root_id: None, entries }));
+    };
+
+    let path = normalize_root_path(path);
+    if path.split(['/', '\\']).any(|segment| segment == "..") {
+        return Err(ApiError::Validation("path cannot contain '..'".to_string()));
+    }
+    let root = find_library_root(&roots, &path)
+        .ok_or_else(|| ApiError::Forbidden(format!("{} is not inside a library root", path)))?;
+    let relative = root.relative_path(&path).unwrap_or_default();
+
+    if root.is_local() {
+        // 解析符号链接后仍需位于根目录内
+        let resolved = tokio::fs::canonicalize(&path).await
+            .map_err(|_| ApiError::NotFound(format!("Directory {} not found", path)))?;
+        let resolved_root = tokio::fs::canonicalize(&root.path).await
+            .map_err(|_| ApiError::NotFound(format!("Library root {} is not accessible", root.path)))?;
+        if !resolved.starts_with(&resolved_root) {
+            return Err(ApiError::Forbidden(format!("{} is not inside a library root", path)));
+        }
+        if !resolved.is_dir() {
+            return Err(ApiError::Validation(format!("{} is not a directory", path)));
+        }
+    }
+
+    let storage = root_storage(root).map_err(|e| ApiError::Internal(e.to_string()))?;
+    let mut names = storage.list_dirs(relative).await
+        .map_err(|e| ApiError::ExternalService(format!("Failed to list {}: {}", path, e)))?;
+    names.retain(|name| !name.starts_with('.'));
+    names.sort_by_key(|name| name.to_lowercase());
+
+    let entries = names.into_iter()
+        .map(|name| DirectoryEntry { path: root.child_path(&path, &name), name })
+        .collect();
+    Ok(success(DirectoryListing {
+        parent: root.parent_path(&path),
+        root_id: Some(root.id.clone()),
+        path: Some(path),
+        entries,
+    }))
+}
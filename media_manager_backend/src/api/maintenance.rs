@@ -0,0 +1,105 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use serde::Deserialize;
+
+use crate::services::maintenance::{cleanup_orphans, optimize_database};
+use crate::database;
+use crate::models::FfmpegSettings;
+use crate::services::{ffmpeg, thumbnail};
+use super::AppState;
+use super::error::{ApiError, ApiResult};
+use super::response::success;
+
+// ============ Maintenance ============
+
+/// 立即优化数据库（清理孤立关联、重建全文索引、PRAGMA optimize、VACUUM）
+/// POST /api/maintenance/optimize
+pub async fn optimize_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let result = optimize_database(state.database.pool()).await
+        .map_err(|e| {
+            tracing::error!("Failed to optimize database: {}", e);
+            ApiError::Internal("Failed to optimize database".to_string())
+        })?
+        .ok_or_else(|| ApiError::Conflict("Database optimization is already running".to_string()))?;
+
+    Ok(success(result))
+}
+
+/// 孤立数据清理请求
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CleanupOrphansRequest {
+    /// 只统计不删除
+    pub dry_run: bool,
+}
+
+/// 清理孤立的关联行和缓存文件
+/// POST /api/maintenance/cleanup-orphans
+pub async fn cleanup_orphans_handler(
+    State(state): State<AppState>,
+    body: Option<Json<CleanupOrphansRequest>>,
+) -> ApiResult<impl IntoResponse> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    let report = cleanup_orphans(state.database.pool(), &state.cache_service, request.dry_run).await
+        .map_err(|e| {
+            tracing::error!("Failed to clean up orphans: {}", e);
+            ApiError::Internal("Failed to clean up orphans".to_string())
+        })?;
+
+    Ok(success(report))
+}
+
+/// 数据库迁移状态（已执行、待执行和未知的迁移）
+/// GET /api/maintenance/migrations
+pub async fn migration_status_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let status = state.database.migration_status().await
+        .map_err(|e| {
+            tracing::error!("Failed to load migration status: {}", e);
+            ApiError::Internal("Failed to load migration status".to_string())
+        })?;
+
+    Ok(success(status))
+}
+
+// ============ Thumbnails ============
+
+/// 为缺少缩略图的媒体补全缩略图（后台低优先级生成）
+/// POST /api/maintenance/thumbnails/backfill
+pub async fn backfill_thumbnails_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let result = thumbnail::backfill(state.database.pool()).await
+        .map_err(|e| {
+            tracing::error!("Failed to backfill thumbnails: {}", e);
+            ApiError::Internal("Failed to backfill thumbnails".to_string())
+        })?;
+
+    Ok(success(result))
+}
+
+/// 缩略图生成队列状态
+/// GET /api/maintenance/thumbnails
+pub async fn thumbnail_queue_handler() -> ApiResult<impl IntoResponse> {
+    Ok(success(thumbnail::queue_stats()))
+}
+
+/// FFmpeg 任务调度设置和正在运行的进程数
+/// GET /api/settings/ffmpeg
+pub async fn get_ffmpeg_settings_handler() -> ApiResult<impl IntoResponse> {
+    Ok(success(ffmpeg::status()))
+}
+
+/// 保存 FFmpeg 任务调度设置（立即生效）
+/// PUT /api/settings/ffmpeg
+pub async fn update_ffmpeg_settings_handler(
+    State(state): State<AppState>,
+    Json(settings): Json<FfmpegSettings>,
+) -> ApiResult<impl IntoResponse> {
+    settings.validate().map_err(ApiError::Validation)?;
+    database::save_ffmpeg_settings(state.database.pool(), &settings).await?;
+    ffmpeg::apply_settings(settings);
+    Ok(success(ffmpeg::status()))
+}
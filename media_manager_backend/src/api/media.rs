@@ -1,18 +1,24 @@
 use axum::{
     extract::{Path, Query, State},
+    http::{header, HeaderMap},
     response::{Json, IntoResponse},
 };
+use futures_util::{stream, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::collections::HashMap;
 
 use crate::models::{
-    CreateMediaRequest, MediaItem, MediaType, WatchStatus,
-    MediaItemResponse, PaginatedResponse
+    CreateMediaRequest, DryRunItem, ExternalIds, MediaItem, MediaType, WatchStatus,
+    MediaItemResponse, PaginatedResponse, SortOption, ENRICHMENT_REASON_CREATE,
+    DUPLICATE_ARTWORK_DEFAULT_DISTANCE, DUPLICATE_ARTWORK_MAX_DISTANCE,
 };
 use crate::database::repository::DatabaseRepository;
-use crate::api::error::{ApiError, ApiResult};
+use crate::api::error::{ApiError, ApiResult, ErrorCode, ErrorDetail};
 use crate::api::response::{success, success_message};
+use crate::api::scrape::{MediaScrapeProgress, MediaScrapeResponse, MEDIA_SCRAPE_PROGRESS};
+use crate::services::cache::{ArtworkRefreshResult, MediaData};
+use crate::services::{artwork_dedup, enrichment};
 use super::AppState;
 
 /// 从各种日期格式中解析年份
@@ -127,8 +133,12 @@ pub struct MediaListParams {
     pub keyword: Option<String>,
     pub year: Option<i32>,
     pub genre: Option<String>,
-    pub sort_by: Option<String>,  // created_at, year, rating, title
+    pub missing_poster: Option<bool>,
+    pub release_date_from: Option<String>,  // YYYY-MM-DD
+    pub release_date_to: Option<String>,    // YYYY-MM-DD
+    pub sort_by: Option<String>,  // 见 SortOption::PARAMS
     pub sort_order: Option<String>,  // asc, desc
+    pub seed: Option<u32>,  // 随机排序的种子，翻页时保持不变
 }
 
 #[derive(Debug, Deserialize)]
@@ -137,12 +147,33 @@ pub struct TmdbDetailsParams {
     pub media_type: String, // "movie" or "tv"
 }
 
+#[derive(Debug, Deserialize)]
+pub struct TmdbDiscoverQuery {
+    /// movie 或 tv，默认 movie
+    pub media_type: Option<String>,
+    /// 逗号分隔的 TMDB 类型ID
+    pub genres: Option<String>,
+    pub year_from: Option<i32>,
+    pub year_to: Option<i32>,
+    pub vote_average_gte: Option<f32>,
+    pub vote_count_gte: Option<u32>,
+    /// popularity / vote_average / vote_count / release_date 加 .asc 或 .desc
+    pub sort_by: Option<String>,
+    pub page: Option<u32>,
+    /// 是否排除本地已有的媒体
+    #[serde(default)]
+    pub exclude_owned: bool,
+}
+
 pub async fn get_media_list(
     Query(params): Query<MediaListParams>,
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> ApiResult<impl IntoResponse> {
     let page = params.page.unwrap_or(1) as i32;
     let page_size = params.limit.unwrap_or(20) as i32;
+    let sort_by = params.sort_by.unwrap_or_else(|| "created_at".to_string());
+    sort_by.parse::<SortOption>().map_err(ApiError::BadRequest)?;
     
     // 构建筛选条件
     let filters = MediaFilters {
@@ -152,23 +183,29 @@ pub async fn get_media_list(
         keyword: params.keyword,
         year: params.year,
         genre: params.genre,
-        sort_by: params.sort_by.unwrap_or_else(|| "created_at".to_string()),
+        missing_poster: params.missing_poster,
+        release_date_from: params.release_date_from,
+        release_date_to: params.release_date_to,
+        sort_by,
         sort_order: params.sort_order.unwrap_or_else(|| "desc".to_string()),
+        seed: params.seed,
     };
     
     let (media_list, total) = state.db_service.get_media_list_filtered(page, page_size, &filters).await?;
     
-    let media_responses: Vec<MediaItemResponse> = media_list
+    let mut media_responses: Vec<MediaItemResponse> = media_list
         .into_iter()
         .map(MediaItemResponse::from)
         .collect();
+    localize_responses(&state, &headers, &mut media_responses).await;
     
     let response = PaginatedResponse::new(media_responses, total, page, page_size);
     Ok(success(response))
 }
 
-/// 媒体筛选条件
-#[derive(Debug, Clone, Default)]
+/// 媒体筛选条件（也作为按筛选条件批量操作的请求体）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
 pub struct MediaFilters {
     pub media_type: Option<String>,
     pub studio: Option<String>,
@@ -176,97 +213,200 @@ pub struct MediaFilters {
     pub keyword: Option<String>,
     pub year: Option<i32>,
     pub genre: Option<String>,
+    pub missing_poster: Option<bool>,
+    pub release_date_from: Option<String>,
+    pub release_date_to: Option<String>,
     pub sort_by: String,
     pub sort_order: String,
-}
-
-/// 筛选选项响应
-#[derive(Debug, Serialize)]
-pub struct FilterOptionsResponse {
-    pub media_types: Vec<String>,
-    pub studios: Vec<String>,
-    pub series: Vec<String>,
-    pub years: Vec<i32>,
-    pub genres: Vec<String>,
+    pub seed: Option<u32>,
 }
 
 /// 获取筛选选项
 pub async fn get_filter_options(
     State(state): State<AppState>,
 ) -> ApiResult<impl IntoResponse> {
-    let pool = state.database.pool();
-    
-    // 获取所有媒体类型
-    let media_types: Vec<String> = sqlx::query_scalar(
-        "SELECT DISTINCT media_type FROM media_items WHERE media_type IS NOT NULL ORDER BY media_type"
-    )
-    .fetch_all(pool)
-    .await
-    .unwrap_or_default();
-    
-    // 获取所有厂商
-    let studios: Vec<String> = sqlx::query_scalar(
-        "SELECT DISTINCT studio FROM media_items WHERE studio IS NOT NULL AND studio != '' ORDER BY studio"
-    )
-    .fetch_all(pool)
-    .await
-    .unwrap_or_default();
-    
-    // 获取所有系列
-    let series: Vec<String> = sqlx::query_scalar(
-        "SELECT DISTINCT series FROM media_items WHERE series IS NOT NULL AND series != '' ORDER BY series"
-    )
-    .fetch_all(pool)
-    .await
-    .unwrap_or_default();
-    
-    // 获取所有年份
-    let years: Vec<i32> = sqlx::query_scalar(
-        "SELECT DISTINCT year FROM media_items WHERE year IS NOT NULL ORDER BY year DESC"
-    )
-    .fetch_all(pool)
-    .await
-    .unwrap_or_default();
-    
-    // 获取所有genres（从JSON数组中提取）
-    let genres_raw: Vec<String> = sqlx::query_scalar(
-        "SELECT DISTINCT genres FROM media_items WHERE genres IS NOT NULL AND genres != '[]'"
-    )
-    .fetch_all(pool)
-    .await
-    .unwrap_or_default();
-    
-    // 解析JSON数组并去重
-    let mut genres_set = std::collections::HashSet::new();
-    for genres_json in genres_raw {
-        if let Ok(genres_array) = serde_json::from_str::<Vec<String>>(&genres_json) {
-            for genre in genres_array {
-                if !genre.is_empty() {
-                    genres_set.insert(genre);
-                }
-            }
-        }
+    let options = state.database.filter_options().await
+        .map_err(|e| ApiError::Internal(format!("Failed to load filter options: {}", e)))?;
+    Ok(success(options.as_ref().clone()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateArtworkQuery {
+    /// 汉明距离阈值（默认 6，最大 16）
+    pub max_distance: Option<u32>,
+}
+
+/// GET /api/media/duplicate-artwork - 列出封面几乎相同的媒体（通常是不同刮削器产生的重复条目）
+pub async fn get_duplicate_artwork(
+    State(state): State<AppState>,
+    Query(params): Query<DuplicateArtworkQuery>,
+) -> ApiResult<impl IntoResponse> {
+    let max_distance = params.max_distance.unwrap_or(DUPLICATE_ARTWORK_DEFAULT_DISTANCE);
+    if max_distance > DUPLICATE_ARTWORK_MAX_DISTANCE {
+        return Err(ApiError::invalid_field(
+            "max_distance",
+            format!("max_distance must be at most {}", DUPLICATE_ARTWORK_MAX_DISTANCE),
+        ));
     }
-    let mut genres: Vec<String> = genres_set.into_iter().collect();
-    genres.sort();
-    
-    Ok(success(FilterOptionsResponse {
-        media_types,
-        studios,
-        series,
-        years,
-        genres,
-    }))
+    let report = artwork_dedup::generate_report(state.database.pool(), max_distance).await?;
+    Ok(success(report))
 }
 
 pub async fn get_media_detail(
     Path(id): Path<String>,
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> ApiResult<impl IntoResponse> {
     let media = state.db_service.get_media_detail(&id).await?
-        .ok_or_else(|| ApiError::NotFound("Media not found".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
     
-    Ok(success(MediaItemResponse::from(media)))
+    super::history::record_media_view(&state, &headers, &id).await;
+    
+    let mut responses = vec![MediaItemResponse::from(media)];
+    localize_responses(&state, &headers, &mut responses).await;
+    Ok(success(responses.remove(0)))
+}
+
+/// 媒体详情聚合接口可选的关联数据
+pub const MEDIA_FULL_SECTIONS: &[&str] = &["files", "actors", "collection", "magnets", "extras", "translations"];
+
+#[derive(Debug, Deserialize)]
+pub struct MediaFullParams {
+    /// 逗号分隔的关联数据（见 MEDIA_FULL_SECTIONS），为空时返回全部
+    pub include: Option<String>,
+}
+
+/// 媒体详情及关联数据，未请求的部分不返回
+#[derive(Debug, Serialize)]
+pub struct MediaFullResponse {
+    pub media: MediaItemResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<crate::models::MediaFile>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub editions: Option<Vec<crate::models::MediaEdition>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actors: Option<Vec<crate::models::MediaActor>>,
+    /// 不在收藏中时为 null
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collection: Option<Option<crate::models::CollectionResponse>>,
+    /// 磁力链接的做种人数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magnets: Option<Vec<crate::models::MagnetSwarmStats>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extras: Option<Vec<crate::models::MediaExtra>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub translations: Option<Vec<crate::models::MediaTranslation>>,
+}
+
+/// 一次获取详情页需要的全部数据（媒体、文件、演员、收藏状态、磁力链接、附加内容、翻译）
+pub async fn get_media_full(
+    Path(id): Path<String>,
+    Query(params): Query<MediaFullParams>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<impl IntoResponse> {
+    let sections: Vec<&str> = match params.include.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(include) => include.split(',').map(str::trim).filter(|s| !s.is_empty()).collect(),
+        None => MEDIA_FULL_SECTIONS.to_vec(),
+    };
+    if let Some(unknown) = sections.iter().find(|s| !MEDIA_FULL_SECTIONS.contains(s)) {
+        return Err(ApiError::invalid_field(
+            "include",
+            format!("Unknown section '{}', expected one of: {}", unknown, MEDIA_FULL_SECTIONS.join(", ")),
+        ));
+    }
+    let included = |section: &str| sections.contains(&section);
+    
+    let media = state.db_service.get_media_detail(&id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    super::history::record_media_view(&state, &headers, &id).await;
+    let mut responses = vec![MediaItemResponse::from(media)];
+    localize_responses(&state, &headers, &mut responses).await;
+    
+    let pool = state.database.pool();
+    let repository = state.database.repository();
+    let files = if included("files") {
+        Some(repository.get_media_files(&id).await?)
+    } else {
+        None
+    };
+    let collection = if included("collection") {
+        Some(repository.get_collection_by_media_id(&id).await?.map(crate::models::CollectionResponse::from))
+    } else {
+        None
+    };
+    let actors = if included("actors") {
+        Some(crate::database::get_actors_for_media(pool, &id).await?)
+    } else {
+        None
+    };
+    let magnets = if included("magnets") {
+        Some(crate::database::list_swarm_stats(pool, Some(&id), false).await?)
+    } else {
+        None
+    };
+    let extras = if included("extras") {
+        Some(crate::database::get_media_extras(pool, &id, None).await?)
+    } else {
+        None
+    };
+    let translations = if included("translations") {
+        Some(crate::database::get_media_translations(pool, &id).await?)
+    } else {
+        None
+    };
+    
+    Ok(success(MediaFullResponse {
+        media: responses.remove(0),
+        editions: files.clone().map(crate::models::group_by_edition),
+        files,
+        actors,
+        collection,
+        magnets,
+        extras,
+        translations,
+    }))
+}
+
+/// 把人员列表关联到媒体（演员不存在时创建）
+async fn link_cast_to_media(pool: &sqlx::Pool<sqlx::Sqlite>, media_id: &str, cast: Vec<crate::models::Person>) {
+    for person in cast {
+        // 查找或创建演员
+        let actor_id = match crate::database::find_actor_by_name(pool, &person.name).await {
+            Ok(Some(actor)) => actor.id,
+            Ok(None) => {
+                // 创建新演员
+                let new_actor = crate::models::Actor::new(person.name.clone());
+                if let Err(e) = crate::database::insert_actor(pool, &new_actor).await {
+                    tracing::warn!("Failed to create actor {}: {}", person.name, e);
+                    continue;
+                }
+                new_actor.id
+            }
+            Err(e) => {
+                tracing::warn!("Failed to find actor {}: {}", person.name, e);
+                continue;
+            }
+        };
+        
+        // 创建演员-媒体关联
+        // 将 role 转换为数据库约束的值 ('cast' 或 'crew')
+        let db_role = match person.role.to_lowercase().as_str() {
+            "actor" | "actress" => "cast",
+            "director" | "producer" | "writer" => "crew",
+            _ => "cast", // 默认为 cast
+        };
+        
+        if let Err(e) = crate::database::add_actor_to_media(
+            pool, 
+            &actor_id, 
+            media_id, 
+            person.character.clone(),
+            Some(db_role.to_string()),
+        ).await {
+            tracing::warn!("Failed to link actor {} to media: {}", person.name, e);
+        }
+    }
 }
 
 pub async fn create_media(
@@ -278,7 +418,7 @@ pub async fn create_media(
     
     // 验证输入
     if payload.title.trim().is_empty() {
-        return Err(ApiError::Validation("Title cannot be empty".to_string()));
+        return Err(ApiError::invalid_field("title", "Title cannot be empty"));
     }
     
     // 提取演员列表用于后续关联
@@ -287,14 +427,8 @@ pub async fn create_media(
     // 从请求构建完整的媒体对象
     let media = MediaItem::from_create_request(payload)
         .map_err(|e| {
-            // 特别处理 InvalidId 错误
-            if matches!(e, crate::models::ValidationError::InvalidId) {
-                tracing::error!("Invalid UUID format in create media request: {:?}", e);
-                ApiError::Validation("Invalid UUID format".to_string())
-            } else {
-                tracing::error!("Failed to build media from request: {:?}", e);
-                ApiError::Validation(format!("Failed to build media: {:?}", e))
-            }
+            tracing::error!("Failed to build media from request: {:?}", e);
+            ApiError::from(e)
         })?;
     
     let media_id = media.id.clone();
@@ -311,45 +445,9 @@ pub async fn create_media(
     
     // 处理演员关联
     if let Some(cast) = cast_list {
-        let pool = state.database.pool();
-        for person in cast {
-            // 查找或创建演员
-            let actor_id = match crate::database::find_actor_by_name(pool, &person.name).await {
-                Ok(Some(actor)) => actor.id,
-                Ok(None) => {
-                    // 创建新演员
-                    let new_actor = crate::models::Actor::new(person.name.clone());
-                    if let Err(e) = crate::database::insert_actor(pool, &new_actor).await {
-                        tracing::warn!("Failed to create actor {}: {}", person.name, e);
-                        continue;
-                    }
-                    new_actor.id
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to find actor {}: {}", person.name, e);
-                    continue;
-                }
-            };
-            
-            // 创建演员-媒体关联
-            // 将 role 转换为数据库约束的值 ('cast' 或 'crew')
-            let db_role = match person.role.to_lowercase().as_str() {
-                "actor" | "actress" => "cast",
-                "director" | "producer" | "writer" => "crew",
-                _ => "cast", // 默认为 cast
-            };
-            
-            if let Err(e) = crate::database::add_actor_to_media(
-                pool, 
-                &actor_id, 
-                &media_id, 
-                person.character.clone(),
-                Some(db_role.to_string()),
-            ).await {
-                tracing::warn!("Failed to link actor {} to media: {}", person.name, e);
-            }
-        }
+        link_cast_to_media(state.database.pool(), &media_id, cast).await;
     }
+    enrichment::queue_new_media(state.database.pool(), &media_id, ENRICHMENT_REASON_CREATE).await;
     
     Ok(success(MediaItemResponse::from(media)))
 }
@@ -361,11 +459,10 @@ pub async fn update_media(
 ) -> ApiResult<impl IntoResponse> {
     // 首先获取现有媒体
     let mut media = state.db_service.get_media_detail(&id).await?
-        .ok_or_else(|| ApiError::NotFound("Media not found".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
     
     // 应用更新
-    media.apply_update(payload)
-        .map_err(|e| ApiError::Validation(format!("Failed to apply update: {:?}", e)))?;
+    media.apply_update(payload)?;
     
     // 保存更新
     state.db_service.update_media(media.clone()).await?;
@@ -378,9 +475,425 @@ pub async fn delete_media(
     State(state): State<AppState>,
 ) -> ApiResult<impl IntoResponse> {
     state.db_service.delete_media(&id).await?;
+    if let Err(e) = state.cache_service.clear_media_cache(&id).await {
+        tracing::warn!("Failed to clear cache for deleted media {}: {}", id, e);
+    }
     Ok(success_message("Media deleted successfully"))
 }
 
+// ============ Translations ============
+
+/// 按 Accept-Language 用翻译替换响应中的标题和简介（没有请求头或没有匹配的翻译时保持原样）
+pub(crate) async fn localize_responses(state: &AppState, headers: &HeaderMap, responses: &mut [MediaItemResponse]) {
+    let preferred = headers.get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .map(crate::models::parse_accept_language)
+        .unwrap_or_default();
+    if preferred.is_empty() || responses.is_empty() {
+        return;
+    }
+    
+    let ids: Vec<String> = responses.iter().map(|r| r.id.clone()).collect();
+    let translations = match crate::database::get_translations_for_media(state.database.pool(), &ids).await {
+        Ok(t) => t,
+        Err(e) => {
+            tracing::warn!("Failed to load translations: {}", e);
+            return;
+        }
+    };
+    for response in responses.iter_mut() {
+        let picked = translations.get(&response.id)
+            .and_then(|t| crate::models::pick_translation(t, &preferred));
+        if let Some(translation) = picked {
+            response.apply_translation(translation);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveTranslationsRequest {
+    pub translations: Vec<crate::models::TranslationInput>,
+}
+
+/// 获取媒体的所有翻译
+pub async fn get_media_translations(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let translations = crate::database::get_media_translations(state.database.pool(), &id).await?;
+    Ok(success(translations))
+}
+
+/// 手动保存翻译（按语言覆盖）
+pub async fn save_media_translations(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<SaveTranslationsRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if !state.database.repository().media_exists(&id).await? {
+        return Err(ApiError::coded(ErrorCode::MediaNotFound, "Media not found"));
+    }
+    
+    let mut translations = Vec::new();
+    for input in &payload.translations {
+        let translation = crate::models::MediaTranslation::from_input(&id, input, "manual")
+            .ok_or_else(|| ApiError::Validation(format!(
+                "Invalid translation '{}': needs a valid language tag and a title or overview",
+                input.language
+            )))?;
+        translations.push(translation);
+    }
+    crate::database::save_media_translations(state.database.pool(), &translations).await?;
+    
+    let translations = crate::database::get_media_translations(state.database.pool(), &id).await?;
+    Ok(success(translations))
+}
+
+/// 删除某个语言的翻译
+pub async fn delete_media_translation(
+    Path((id, language)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let language = crate::models::normalize_language(&language)
+        .ok_or_else(|| ApiError::BadRequest("Invalid language tag".to_string()))?;
+    if !crate::database::delete_media_translation(state.database.pool(), &id, &language).await? {
+        return Err(ApiError::NotFound("Translation not found".to_string()));
+    }
+    Ok(success_message("Translation deleted"))
+}
+
+/// 从 TMDB 获取全部翻译并保存（需要媒体已有 TMDB ID）
+pub async fn fetch_media_translations(
+    Path(id): Path<String>,
+    Query(query): Query<TmdbTypeQuery>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if !state.external_client.is_tmdb_available() {
+        return Err(ApiError::ExternalService("TMDB service unavailable".to_string()));
+    }
+    
+    let media = state.database.repository().get_media_by_id(&id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    let tmdb_id = media.get_external_ids().ok()
+        .and_then(|ids| ids.tmdb_id)
+        .ok_or_else(|| ApiError::Validation("Media has no TMDB ID".to_string()))?;
+    let media_type = tmdb_media_type(query.media_type.as_deref(), &media)?;
+    
+    let tmdb_translations = state.external_client.get_translations(media_type, tmdb_id as u32).await
+        .map_err(|e| ApiError::ExternalService(format!("Failed to get translations: {}", e)))?;
+    let translations: Vec<crate::models::MediaTranslation> = tmdb_translations.iter()
+        .filter_map(|t| {
+            let input = crate::models::TranslationInput {
+                language: t.language(),
+                title: t.title().map(String::from),
+                overview: t.data.overview.clone(),
+            };
+            crate::models::MediaTranslation::from_input(&id, &input, "tmdb")
+        })
+        .collect();
+    let saved = crate::database::save_media_translations(state.database.pool(), &translations).await?;
+    tracing::info!("Saved {} TMDB translations for media {}", saved, id);
+    
+    let translations = crate::database::get_media_translations(state.database.pool(), &id).await?;
+    Ok(success(translations))
+}
+
+// ============ Trailer ============
+
+#[derive(Debug, Deserialize)]
+pub struct TmdbTypeQuery {
+    /// TMDB 类型：movie 或 tv（默认按媒体类型推断）
+    pub media_type: Option<String>,
+}
+
+/// 确定媒体对应的 TMDB 类型：优先使用请求指定的类型，否则 Scene 为 tv，其余为 movie
+fn tmdb_media_type(requested: Option<&str>, media: &MediaItem) -> Result<&'static str, ApiError> {
+    match requested {
+        Some("movie") => Ok("movie"),
+        Some("tv") => Ok("tv"),
+        Some(_) => Err(ApiError::BadRequest("Invalid media type".to_string())),
+        None if media.media_type == MediaType::Scene.to_string() => Ok("tv"),
+        None => Ok("movie"),
+    }
+}
+
+/// 预告片信息
+#[derive(Debug, Serialize)]
+pub struct TrailerInfo {
+    pub trailer_url: Option<String>,
+    /// YouTube/Vimeo 嵌入播放地址
+    pub embed_url: Option<String>,
+    /// 后端代理播放地址（直链或启用 yt-dlp 时可用）
+    pub playback_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl TrailerInfo {
+    fn new(media_id: &str, trailer_url: Option<String>) -> Self {
+        use crate::services::trailer;
+        let embed_url = trailer_url.as_deref().and_then(trailer::embed_url);
+        let proxy_playable = trailer_url.as_deref()
+            .is_some_and(|url| trailer::is_direct_video(url) || (trailer::ytdlp_enabled() && embed_url.is_some()));
+        Self {
+            playback_url: proxy_playable.then(|| format!("/api/media/{}/trailer/play", media_id)),
+            trailer_url,
+            embed_url,
+            name: None,
+        }
+    }
+}
+
+/// 获取媒体的预告片信息
+pub async fn get_trailer(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let media = state.database.repository().get_media_by_id(&id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    
+    Ok(success(TrailerInfo::new(&id, media.trailer_url)))
+}
+
+/// 从 TMDB 获取预告片并保存到媒体（需要媒体已有 TMDB ID）
+pub async fn fetch_trailer(
+    Path(id): Path<String>,
+    Query(query): Query<TmdbTypeQuery>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if !state.external_client.is_tmdb_available() {
+        return Err(ApiError::ExternalService("TMDB service unavailable".to_string()));
+    }
+    
+    let mut media = state.database.repository().get_media_by_id(&id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    let media_type = tmdb_media_type(query.media_type.as_deref(), &media)?;
+    let tmdb_id = media.get_external_ids().ok()
+        .and_then(|ids| ids.tmdb_id)
+        .ok_or_else(|| ApiError::Validation("Media has no TMDB ID".to_string()))?;
+    
+    let video = state.external_client.get_trailer(media_type, tmdb_id as u32).await
+        .map_err(|e| ApiError::ExternalService(format!("Failed to get trailer: {}", e)))?
+        .ok_or_else(|| ApiError::NotFound("No trailer found on TMDB".to_string()))?;
+    
+    media.set_trailer_url(video.url())
+        .map_err(|e| ApiError::Validation(format!("Invalid trailer URL: {:?}", e)))?;
+    state.database.repository().update_media(&media).await?;
+    tracing::info!("Saved trailer for media {}: {:?}", id, media.trailer_url);
+    
+    let mut info = TrailerInfo::new(&id, media.trailer_url);
+    info.name = Some(video.name);
+    Ok(success(info))
+}
+
+// ============ Availability ============
+
+/// 未指定地区时使用的默认地区
+const DEFAULT_WATCH_REGION: &str = "CN";
+
+#[derive(Debug, Deserialize)]
+pub struct AvailabilityQuery {
+    /// ISO 3166-1 地区代码，如 CN、US、JP
+    pub region: Option<String>,
+    /// TMDB 类型：movie 或 tv（默认按媒体类型推断）
+    pub media_type: Option<String>,
+}
+
+/// 流媒体观看渠道
+#[derive(Debug, Serialize)]
+pub struct AvailabilityResponse {
+    pub media_id: String,
+    pub region: String,
+    /// 是否能在流媒体上直接观看（订阅、免费或含广告）
+    pub streamable: bool,
+    /// 是否已有本地文件
+    pub has_local_file: bool,
+    pub link: Option<String>,
+    pub flatrate: Vec<crate::external::TmdbWatchProvider>,
+    pub free: Vec<crate::external::TmdbWatchProvider>,
+    pub ads: Vec<crate::external::TmdbWatchProvider>,
+    pub rent: Vec<crate::external::TmdbWatchProvider>,
+    pub buy: Vec<crate::external::TmdbWatchProvider>,
+}
+
+/// 查询媒体在指定地区的流媒体观看渠道（TMDB watch providers，按地区缓存）
+pub async fn get_media_availability(
+    Path(id): Path<String>,
+    Query(query): Query<AvailabilityQuery>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if !state.external_client.is_tmdb_available() {
+        return Err(ApiError::ExternalService("TMDB service unavailable".to_string()));
+    }
+    
+    let region = query.region.as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .unwrap_or(DEFAULT_WATCH_REGION)
+        .to_uppercase();
+    if region.len() != 2 || !region.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(ApiError::BadRequest("Region must be a two-letter country code".to_string()));
+    }
+    
+    let media = state.database.repository().get_media_by_id(&id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    let tmdb_id = media.get_external_ids().ok()
+        .and_then(|ids| ids.tmdb_id)
+        .ok_or_else(|| ApiError::Validation("Media has no TMDB ID".to_string()))?;
+    
+    let media_type = tmdb_media_type(query.media_type.as_deref(), &media)?;
+    
+    let providers = state.external_client.get_watch_providers(media_type, tmdb_id as u32, &region).await
+        .map_err(|e| ApiError::ExternalService(format!("Failed to get watch providers: {}", e)))?
+        .unwrap_or_default();
+    let has_local_file = crate::database::get_local_file_paths(state.database.pool(), &id).await
+        .map(|paths| !paths.is_empty())
+        .unwrap_or(false);
+    
+    Ok(success(AvailabilityResponse {
+        media_id: id,
+        region,
+        streamable: providers.is_streamable(),
+        has_local_file,
+        link: providers.link,
+        flatrate: providers.flatrate,
+        free: providers.free,
+        ads: providers.ads,
+        rent: providers.rent,
+        buy: providers.buy,
+    }))
+}
+
+// ============ External IDs ============
+
+/// 外部ID更新结果
+#[derive(Debug, Serialize)]
+pub struct UpdateExternalIdsResponse {
+    pub external_ids: ExternalIds,
+    /// TMDB ID 是否变化（详情、观看渠道、翻译和预告片改用新条目）
+    pub tmdb_relinked: bool,
+    /// 清除的旧 TMDB 条目翻译数
+    pub cleared_translations: u64,
+}
+
+/// 替换媒体的外部ID（tmdb/imdb/tvdb/自定义来源），同一ID不能被其他媒体使用。
+/// TMDB ID 变化时清除旧条目的详情和观看渠道缓存以及旧条目的翻译
+pub async fn update_external_ids(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    Json(ids): Json<ExternalIds>,
+) -> ApiResult<impl IntoResponse> {
+    ids.validate().map_err(|e| ApiError::invalid_field("external_ids", e))?;
+    
+    let mut media = state.database.repository().get_media_by_id(&id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    let tmdb_type = tmdb_media_type(None, &media)?;
+    
+    let conflicts = crate::database::find_external_id_conflicts(state.database.pool(), &id, &ids, tmdb_type == "tv").await?;
+    if !conflicts.is_empty() {
+        let message = conflicts.iter()
+            .map(|(field, media_id)| format!("{} is already used by media {}", field, media_id))
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(ApiError::Conflict(message));
+    }
+    
+    let previous_tmdb = media.get_external_ids().ok().and_then(|ids| ids.tmdb_id);
+    media.set_external_ids(&ids)
+        .map_err(|e| ApiError::Internal(format!("Failed to serialize external ids: {}", e)))?;
+    state.database.repository().update_media(&media).await?;
+    
+    let tmdb_relinked = previous_tmdb != ids.tmdb_id;
+    let mut cleared_translations = 0;
+    if tmdb_relinked {
+        if let Some(old_id) = previous_tmdb {
+            state.external_client.cache.invalidate_item(tmdb_type, old_id as u32);
+        }
+        cleared_translations = crate::database::delete_media_translations_by_source(state.database.pool(), &id, "tmdb").await?;
+        tracing::info!("Media {} relinked from TMDB {:?} to {:?}", id, previous_tmdb, ids.tmdb_id);
+    }
+    
+    Ok(success(UpdateExternalIdsResponse {
+        external_ids: ids,
+        tmdb_relinked,
+        cleared_translations,
+    }))
+}
+
+// ============ Download Link Resolution ============
+
+/// 网盘链接解析结果
+#[derive(Debug, Serialize)]
+pub struct ResolvedLinkResponse {
+    pub media_id: String,
+    pub index: usize,
+    pub name: String,
+    /// 原始分享链接
+    pub source_url: String,
+    pub plugin_id: String,
+    /// 解析得到的直链
+    pub url: String,
+    pub file_name: Option<String>,
+    pub size: Option<u64>,
+    pub expires_at: Option<String>,
+    /// 通过视频代理播放的地址（会附带插件要求的请求头）
+    pub play_url: String,
+}
+
+/// 取出媒体的第 index 个下载链接并交给解析插件
+pub(crate) async fn resolve_media_link(
+    state: &AppState,
+    id: &str,
+    index: usize,
+) -> ApiResult<(crate::models::DownloadLink, String, crate::plugins::protocol::ResolvedLink)> {
+    let media = state.database.repository().get_media_by_id(id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    let link = media.get_download_links()
+        .map_err(|e| ApiError::Internal(format!("Invalid download links: {}", e)))?
+        .into_iter()
+        .nth(index)
+        .ok_or_else(|| ApiError::NotFound(format!("Download link {} not found", index)))?;
+    
+    let (plugin_id, resolved) = {
+        let manager = state.plugin_manager.read().await;
+        manager.resolve_link(&link.url, link.password.as_deref()).await
+            .map_err(|e| {
+                if e.to_string().contains("No plugin supports") {
+                    ApiError::NotFound(e.to_string())
+                } else {
+                    ApiError::plugin(e)
+                }
+            })?
+    };
+    match url::Url::parse(&resolved.url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+        _ => return Err(ApiError::ExternalService(format!("Plugin returned an invalid URL: {}", resolved.url))),
+    }
+    tracing::info!("解析网盘链接成功: {} (插件: {})", link.url, plugin_id);
+    Ok((link, plugin_id, resolved))
+}
+
+/// 把媒体的网盘下载链接解析为直链
+/// GET /api/media/:id/links/:index/resolve
+pub async fn resolve_download_link(
+    Path((id, index)): Path<(String, usize)>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let (link, plugin_id, resolved) = resolve_media_link(&state, &id, index).await?;
+    Ok(success(ResolvedLinkResponse {
+        play_url: format!("/api/media/{}/links/{}/play", id, index),
+        media_id: id,
+        index,
+        name: link.name,
+        source_url: link.url,
+        plugin_id,
+        url: resolved.url,
+        file_name: resolved.file_name,
+        size: resolved.size,
+        expires_at: resolved.expires_at,
+    }))
+}
+
 /// 从TMDB获取详细信息并可选择性地保存到本地数据库
 pub async fn get_tmdb_details(
     Query(params): Query<TmdbDetailsParams>,
@@ -407,42 +920,134 @@ pub async fn get_tmdb_details(
     Ok(success(media_item))
 }
 
-/// 获取TMDB热门内容
-pub async fn get_popular_content(
-    Query(params): Query<HashMap<String, String>>,
+/// 按类型、年份范围、评分等条件从 TMDB 发现内容，每个结果附带本地媒体ID（未收录时为 null）
+pub async fn discover_tmdb_content(
+    Query(query): Query<TmdbDiscoverQuery>,
     State(state): State<AppState>,
 ) -> ApiResult<impl IntoResponse> {
+    let media_type = query.media_type.as_deref().unwrap_or("movie");
+    if !matches!(media_type, "movie" | "tv") {
+        return Err(ApiError::BadRequest("Invalid media type".to_string()));
+    }
+    let genre_ids = query.genres.as_deref()
+        .map(|genres| {
+            genres.split(',')
+                .map(str::trim)
+                .filter(|g| !g.is_empty())
+                .map(|g| g.parse::<u32>().map_err(|_| ApiError::invalid_field("genres", format!("Invalid genre id '{}'", g))))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let params = crate::external::TmdbDiscoverParams {
+        genre_ids,
+        year_from: query.year_from,
+        year_to: query.year_to,
+        min_vote_average: query.vote_average_gte,
+        min_vote_count: query.vote_count_gte,
+        sort_by: query.sort_by,
+        page: query.page.unwrap_or(1).max(1),
+    };
+    params.validate().map_err(ApiError::Validation)?;
+    
     if !state.external_client.is_tmdb_available() {
         return Err(ApiError::ExternalService("TMDB service unavailable".to_string()));
     }
+    let items = state.external_client.discover(media_type, &params).await
+        .map_err(|e| ApiError::ExternalService(format!("Failed to discover {}: {}", media_type, e)))?;
     
-    let media_type = params.get("media_type").map(|s| s.as_str()).unwrap_or("movie");
-    let page = params.get("page")
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(1);
-    
-    let results = match media_type {
-        "movie" => {
-            state.external_client.get_popular_movies(Some(page)).await
-                .map_err(|e| ApiError::ExternalService(format!("Failed to get popular movies: {}", e)))?
-        }
-        "tv" => {
-            state.external_client.get_popular_tv_shows(Some(page)).await
-                .map_err(|e| ApiError::ExternalService(format!("Failed to get popular TV shows: {}", e)))?
-        }
-        _ => {
-            return Err(ApiError::BadRequest("Invalid media type".to_string()));
+    let mut results = Vec::with_capacity(items.len());
+    for item in items {
+        let tmdb_id = item.get_external_ids().ok().and_then(|ids| ids.tmdb_id);
+        let local_media_id = match tmdb_id {
+            Some(tmdb_id) => crate::database::find_media_id_by_external_ids(state.database.pool(), Some(tmdb_id), media_type == "tv", None).await?,
+            None => None,
+        };
+        if query.exclude_owned && local_media_id.is_some() {
+            continue;
         }
-    };
+        let mut value = serde_json::to_value(&item)
+            .map_err(|e| ApiError::Internal(format!("Failed to serialize media: {}", e)))?;
+        value["local_media_id"] = json!(local_media_id);
+        results.push(value);
+    }
     
     Ok(success(json!({
         "results": results,
         "total": results.len(),
-        "page": page,
+        "page": params.page,
         "media_type": media_type
     })))
 }
 
+/// 获取 TMDB 列表（popular/trending）的一页：优先使用未过期的本地缓存，
+/// TMDB 无法访问时返回最近一次缓存的结果（fetched_at 为获取时间，stale 表示已过期）
+async fn get_tmdb_list_content(
+    state: &AppState,
+    list: &str,
+    params: &HashMap<String, String>,
+) -> ApiResult<crate::models::TmdbListContent> {
+    let media_type = params.get("media_type").map(|s| s.as_str()).unwrap_or("movie");
+    if !crate::models::TMDB_LIST_MEDIA_TYPES.contains(&media_type) {
+        return Err(ApiError::BadRequest("Invalid media type".to_string()));
+    }
+    let page = params.get("page")
+        .and_then(|p| p.parse().ok())
+        .unwrap_or(1)
+        .max(1);
+    
+    crate::services::tmdb_list_cache::get_list_page(state.database.pool(), &state.external_client, list, media_type, page)
+        .await?
+        .ok_or_else(|| ApiError::ExternalService(format!("Failed to get {} {} list and no cached copy is available", list, media_type)))
+}
+
+/// 获取TMDB热门内容
+pub async fn get_popular_content(
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    Ok(success(get_tmdb_list_content(&state, "popular", &params).await?))
+}
+
+/// 获取TMDB本周趋势内容
+pub async fn get_trending_content(
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    Ok(success(get_tmdb_list_content(&state, "trending", &params).await?))
+}
+
+/// 按缓存设置立即预取全部 TMDB 列表页
+pub async fn prefetch_tmdb_lists(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if !state.external_client.is_tmdb_available() {
+        return Err(ApiError::ExternalService("TMDB service unavailable".to_string()));
+    }
+    let pool = state.database.pool();
+    let settings = crate::database::get_tmdb_list_cache_settings(pool).await?;
+    let result = crate::services::tmdb_list_cache::prefetch(pool, &state.external_client, &settings).await?;
+    Ok(success(result))
+}
+
+/// 获取 TMDB 列表缓存设置
+pub async fn get_tmdb_list_cache_settings(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let settings = crate::database::get_tmdb_list_cache_settings(state.database.pool()).await?;
+    Ok(success(settings))
+}
+
+/// 保存 TMDB 列表缓存设置
+pub async fn update_tmdb_list_cache_settings(
+    State(state): State<AppState>,
+    Json(settings): Json<crate::models::TmdbListCacheSettings>,
+) -> ApiResult<impl IntoResponse> {
+    settings.validate().map_err(ApiError::Validation)?;
+    crate::database::save_tmdb_list_cache_settings(state.database.pool(), &settings).await?;
+    Ok(success(settings))
+}
+
 /// 将TMDB媒体保存到本地数据库
 pub async fn save_tmdb_media(
     State(state): State<AppState>,
@@ -455,7 +1060,13 @@ pub async fn save_tmdb_media(
         }
     }
     
-    state.db_service.update_media(media_item.clone()).await?;
+    if state.database.repository().media_exists(&media_item.id).await? {
+        state.db_service.update_media(media_item.clone()).await?;
+    } else {
+        state.database.repository().insert_media(&media_item).await?;
+    }
+    // 没有本地文件时进入获取流程（wanted），之后由下载任务推进
+    crate::database::start_acquisition(state.database.pool(), &media_item.id).await?;
     
     Ok(success(MediaItemResponse::from(media_item)))
 }
@@ -487,7 +1098,12 @@ pub async fn batch_delete_media(
 
     for id in &payload.ids {
         match state.db_service.delete_media(id).await {
-            Ok(_) => success_count += 1,
+            Ok(_) => {
+                success_count += 1;
+                if let Err(e) = state.cache_service.clear_media_cache(id).await {
+                    tracing::warn!("Failed to clear cache for deleted media {}: {}", id, e);
+                }
+            }
             Err(e) => {
                 failed_count += 1;
                 errors.push(format!("{}: {}", id, e));
@@ -507,6 +1123,9 @@ pub async fn batch_delete_media(
 pub struct BatchEditRequest {
     pub ids: Vec<String>,
     pub updates: BatchEditUpdates,
+    /// 试运行：只返回将会发生的变化，不写入数据库
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -524,6 +1143,9 @@ pub struct BatchEditResponse {
     pub success_count: usize,
     pub failed_count: usize,
     pub errors: Vec<String>,
+    /// 试运行结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<Vec<DryRunItem>>,
 }
 
 /// 批量编辑媒体
@@ -534,6 +1156,7 @@ pub async fn batch_edit_media(
     let mut success_count = 0;
     let mut failed_count = 0;
     let mut errors = Vec::new();
+    let mut preview = Vec::new();
 
     for id in &payload.ids {
         // 获取现有媒体
@@ -551,6 +1174,7 @@ pub async fn batch_edit_media(
             }
         };
 
+        let original = media.clone();
         let mut media = media;
         let mut changed = false;
 
@@ -584,30 +1208,209 @@ pub async fn batch_edit_media(
             changed = true;
         }
 
-        // 保存更新
-        if changed {
-            match state.db_service.update_media(media).await {
-                Ok(_) => success_count += 1,
-                Err(e) => {
-                    failed_count += 1;
-                    errors.push(format!("{}: {}", id, e));
-                }
+        // 试运行：只记录变化
+        if payload.dry_run {
+            preview.push(DryRunItem::update(&original, &media));
+            success_count += 1;
+            continue;
+        }
+
+        // 保存更新
+        if changed {
+            match state.db_service.update_media(media).await {
+                Ok(_) => success_count += 1,
+                Err(e) => {
+                    failed_count += 1;
+                    errors.push(format!("{}: {}", id, e));
+                }
+            }
+        } else {
+            success_count += 1; // 没有变化也算成功
+        }
+    }
+
+    success(BatchEditResponse {
+        success_count,
+        failed_count,
+        errors,
+        preview: payload.dry_run.then_some(preview),
+    })
+}
+
+/// 按筛选条件批量设置标签请求
+#[derive(Debug, Deserialize)]
+pub struct BatchTagByFilterRequest {
+    /// 高级搜索的筛选条件（忽略分页和排序）
+    pub filter: crate::api::search::AdvancedSearchRequest,
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+    /// 试运行：只返回匹配数量和将会变化的数量
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 去掉空白和重复（不区分大小写）的标签名
+fn normalize_tag_names(tags: &[String]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if !names.iter().any(|n| n.eq_ignore_ascii_case(tag)) {
+            names.push(tag.to_string());
+        }
+    }
+    names
+}
+
+/// 按筛选条件批量添加/移除标签
+/// POST /api/batch/tag-by-filter
+pub async fn batch_tag_by_filter(
+    State(state): State<AppState>,
+    Json(payload): Json<BatchTagByFilterRequest>,
+) -> ApiResult<impl IntoResponse> {
+    let add_tags = normalize_tag_names(&payload.add_tags);
+    let remove_tags = normalize_tag_names(&payload.remove_tags);
+    if add_tags.is_empty() && remove_tags.is_empty() {
+        return Err(ApiError::Validation("add_tags or remove_tags is required".to_string()));
+    }
+    if let Some(tag) = add_tags.iter().find(|t| remove_tags.iter().any(|r| r.eq_ignore_ascii_case(t))) {
+        return Err(ApiError::Validation(format!("Tag '{}' cannot be both added and removed", tag)));
+    }
+
+    let filters = payload.filter.search_filters(SortOption::AddedDate);
+    let ids = state.db_service.search_ids_with_filters(&filters).await?;
+    let result = crate::database::batch_tag_media(
+        state.database.pool(),
+        &ids,
+        &add_tags,
+        &remove_tags,
+        payload.dry_run,
+    ).await?;
+
+    tracing::info!(
+        "Batch tag by filter: matched={}, dry_run={}, added={:?}, removed={:?}",
+        result.matched, result.dry_run, result.added, result.removed
+    );
+    Ok(success(result))
+}
+
+/// 批量刷新图片请求
+#[derive(Debug, Deserialize)]
+pub struct BatchRefreshArtworkRequest {
+    /// 媒体ID列表（与 filter 二选一）
+    #[serde(default)]
+    pub ids: Vec<String>,
+    /// 按媒体列表的筛选条件选择媒体
+    #[serde(default)]
+    pub filter: Option<MediaFilters>,
+}
+
+/// 批量重新下载并转换封面/背景图
+/// POST /api/batch/refresh-artwork
+/// 返回 session_id，通过 /api/scrape/progress/:session_id 查询进度
+pub async fn batch_refresh_artwork(
+    State(state): State<AppState>,
+    Json(payload): Json<BatchRefreshArtworkRequest>,
+) -> ApiResult<Json<MediaScrapeResponse>> {
+    let ids = match payload.filter {
+        Some(_) if !payload.ids.is_empty() => {
+            return Err(ApiError::BadRequest("ids and filter are mutually exclusive".to_string()));
+        }
+        Some(ref filter) => state.db_service.get_media_ids_filtered(filter).await?,
+        None => payload.ids,
+    };
+    if ids.is_empty() {
+        return Err(ApiError::BadRequest("No media selected".to_string()));
+    }
+
+    // 进度复用 MEDIA_SCRAPE_PROGRESS
+    let session_id = MEDIA_SCRAPE_PROGRESS.create(MediaScrapeProgress {
+        status: "refreshing".to_string(),
+        message: Some("正在刷新图片...".to_string()),
+        current: 0,
+        total: ids.len() as i32,
+        current_item: None,
+        item_status: "pending".to_string(),
+        success_count: 0,
+        failed_count: 0,
+        completed: false,
+        concurrent: false,
+        processing_items: vec![],
+        dry_run_results: vec![],
+    });
+    tracing::info!("开始批量刷新图片，会话ID: {}, 数量: {}", session_id, ids.len());
+
+    let task_session_id = session_id.clone();
+    tokio::spawn(async move {
+        process_artwork_refresh(state, ids, task_session_id).await;
+    });
+
+    Ok(Json(MediaScrapeResponse {
+        success: true,
+        session_id,
+        message: "图片刷新任务已启动".to_string(),
+    }))
+}
+
+/// 逐个刷新媒体图片并更新进度
+async fn process_artwork_refresh(state: AppState, ids: Vec<String>, session_id: String) {
+    let mut totals = ArtworkRefreshResult::default();
+
+    for (index, id) in ids.iter().enumerate() {
+        let media = state.db_service.get_media_detail(id).await;
+        let title = media.as_ref().ok().and_then(|m| m.as_ref()).map(|m| m.title.clone()).unwrap_or_else(|| id.clone());
+
+        MEDIA_SCRAPE_PROGRESS.update(&session_id, |progress| {
+            progress.current = index as i32 + 1;
+            progress.current_item = Some(title.clone());
+            progress.item_status = "refreshing".to_string();
+        });
+
+        let item_ok = match media {
+            Ok(Some(media)) => {
+                let result = state.cache_service
+                    .refresh_media_artwork(id, &MediaData::from_media_item(&media))
+                    .await;
+                totals.refreshed += result.refreshed;
+                totals.failed += result.failed;
+                totals.skipped += result.skipped;
+                result.failed == 0
             }
-        } else {
-            success_count += 1; // 没有变化也算成功
-        }
+            Ok(None) => false,
+            Err(e) => {
+                tracing::error!("刷新图片时读取媒体失败: {}: {}", id, e);
+                false
+            }
+        };
+
+        MEDIA_SCRAPE_PROGRESS.update(&session_id, |progress| {
+            if item_ok {
+                progress.success_count += 1;
+                progress.item_status = "completed".to_string();
+            } else {
+                progress.failed_count += 1;
+                progress.item_status = "failed".to_string();
+            }
+        });
     }
 
-    success(BatchEditResponse {
-        success_count,
-        failed_count,
-        errors,
-    })
+    MEDIA_SCRAPE_PROGRESS.update(&session_id, |progress| {
+        progress.status = "completed".to_string();
+        progress.completed = true;
+        progress.current_item = None;
+        progress.message = Some(format!(
+            "图片刷新完成：成功 {} 张，失败 {} 张，跳过 {} 张",
+            totals.refreshed, totals.failed, totals.skipped
+        ));
+    });
 }
 
 #[derive(Debug, Deserialize)]
 pub struct BatchImportRequest {
     pub items: Vec<BatchImportItem>,
+    /// 试运行：只返回将会创建或修改的内容，不写入数据库
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -622,6 +1425,9 @@ pub struct BatchImportResponse {
     pub success_count: usize,
     pub failed_count: usize,
     pub results: Vec<BatchImportResult>,
+    /// 试运行结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<Vec<DryRunItem>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -637,6 +1443,20 @@ pub async fn batch_import_media(
     State(state): State<AppState>,
     Json(payload): Json<BatchImportRequest>,
 ) -> impl IntoResponse {
+    if payload.dry_run {
+        let mut preview = Vec::new();
+        for item in &payload.items {
+            preview.push(preview_import_item(&state, item).await);
+        }
+        let failed_count = preview.iter().filter(|p| p.action == "error").count();
+        return success(BatchImportResponse {
+            success_count: preview.len() - failed_count,
+            failed_count,
+            results: Vec::new(),
+            preview: Some(preview),
+        });
+    }
+
     let mut results = Vec::new();
     let mut success_count = 0;
     let mut failed_count = 0;
@@ -747,9 +1567,40 @@ pub async fn batch_import_media(
         success_count,
         failed_count,
         results,
+        preview: None,
     })
 }
 
+/// 试运行单个导入项：TMDB 条目与本地已有媒体比较，其余视为新建
+async fn preview_import_item(state: &AppState, item: &BatchImportItem) -> DryRunItem {
+    if let Some(tmdb_id) = item.tmdb_id {
+        let label = format!("tmdb:{}", tmdb_id);
+        let fetched = match item.media_type.as_str() {
+            "movie" => state.external_client.get_movie_details(tmdb_id).await,
+            "tv" => state.external_client.get_tv_details(tmdb_id).await,
+            _ => return DryRunItem::error(None, label, "Invalid media type"),
+        };
+        match fetched {
+            Ok(media) => match state.db_service.get_media_detail(&media.id).await {
+                Ok(Some(existing)) => DryRunItem::update(&existing, &media),
+                _ => DryRunItem::create(&media),
+            },
+            Err(e) => DryRunItem::error(None, label, format!("TMDB fetch failed: {}", e)),
+        }
+    } else if let Some(title) = &item.title {
+        let media_type = match item.media_type.as_str() {
+            "tv" => MediaType::Scene,
+            _ => MediaType::Movie,
+        };
+        match MediaItem::new(title.clone(), media_type) {
+            Ok(media) => DryRunItem::create(&media),
+            Err(e) => DryRunItem::error(None, title.clone(), format!("Failed to create: {:?}", e)),
+        }
+    } else {
+        DryRunItem::error(None, String::new(), "Either tmdb_id or title is required")
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BatchCollectionRequest {
     pub media_ids: Vec<String>,
@@ -851,6 +1702,225 @@ pub async fn batch_collection_operation(
 }
 
 
+// ============ Bulk Upsert ============
+
+/// 单次批量写入的最大条数
+const MAX_UPSERT_ITEMS: usize = 1000;
+
+/// 批量写入时匹配已有媒体的方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpsertMatchOn {
+    #[default]
+    Code,
+    TmdbId,
+    #[serde(alias = "title+year")]
+    TitleYear,
+}
+
+impl UpsertMatchOn {
+    fn field(&self) -> &'static str {
+        match self {
+            UpsertMatchOn::Code => "code",
+            UpsertMatchOn::TmdbId => "tmdb_id",
+            UpsertMatchOn::TitleYear => "title",
+        }
+    }
+}
+
+/// 批量写入的单条数据（字段与更新媒体接口相同）
+#[derive(Debug, Deserialize)]
+pub struct UpsertMediaItem {
+    /// 覆盖请求级的 match_on
+    #[serde(default)]
+    pub match_on: Option<UpsertMatchOn>,
+    #[serde(default)]
+    pub tmdb_id: Option<i32>,
+    #[serde(flatten)]
+    pub fields: crate::models::UpdateMediaRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertMediaRequest {
+    #[serde(default)]
+    pub match_on: UpsertMatchOn,
+    pub items: Vec<UpsertMediaItem>,
+}
+
+/// 单条写入结果
+#[derive(Debug, Serialize)]
+pub struct UpsertMediaResult {
+    pub index: usize,
+    pub action: String,  // "created", "updated", "unchanged"
+    pub media_id: String,
+    pub title: String,
+    pub match_on: UpsertMatchOn,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpsertMediaResponse {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub results: Vec<UpsertMediaResult>,
+}
+
+/// 按匹配方式查找已有媒体
+async fn find_upsert_match(
+    conn: &mut sqlx::SqliteConnection,
+    match_on: UpsertMatchOn,
+    item: &UpsertMediaItem,
+) -> Result<Option<String>, String> {
+    let fields = &item.fields;
+    let found = match match_on {
+        UpsertMatchOn::Code => {
+            let code = fields.code.as_deref().map(str::trim).filter(|c| !c.is_empty())
+                .ok_or("code is required when match_on is code")?;
+            crate::database::find_media_id_by_code(&mut *conn, code).await
+        }
+        UpsertMatchOn::TmdbId => {
+            let tmdb_id = item.tmdb_id.ok_or("tmdb_id is required when match_on is tmdb_id")?;
+            let is_tv = fields.media_type.as_deref() == Some("Scene");
+            crate::database::find_media_id_by_external_ids(&mut *conn, Some(tmdb_id), is_tv, None).await
+        }
+        UpsertMatchOn::TitleYear => {
+            let title = fields.title.as_deref().map(str::trim).filter(|t| !t.is_empty())
+                .ok_or("title is required when match_on is title_year")?;
+            let year = fields.year.or_else(|| fields.release_date.as_deref().and_then(parse_year_from_date));
+            crate::database::find_media_id_by_title_year(&mut *conn, title, year).await
+        }
+    };
+    found.map_err(|e| format!("Failed to look up media: {}", e))
+}
+
+/// 写入内容是否与原媒体相同（忽略更新时间）
+fn media_unchanged(before: &MediaItem, after: &MediaItem) -> bool {
+    MediaItem { updated_at: after.updated_at, ..before.clone() } == *after
+}
+
+/// 批量创建或更新媒体（在同一事务中执行，任意一条失败则全部回滚）
+/// POST /api/media/upsert
+pub async fn upsert_media(
+    State(state): State<AppState>,
+    Json(request): Json<UpsertMediaRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if request.items.is_empty() {
+        return Err(ApiError::invalid_field("items", "items cannot be empty"));
+    }
+    if request.items.len() > MAX_UPSERT_ITEMS {
+        return Err(ApiError::invalid_field("items", format!("Too many items (max {})", MAX_UPSERT_ITEMS)));
+    }
+
+    let pool = state.database.pool();
+    let mut tx = pool.begin().await
+        .map_err(|e| ApiError::Internal(format!("Failed to start transaction: {}", e)))?;
+
+    let mut results = Vec::with_capacity(request.items.len());
+    let mut errors = Vec::new();
+    let mut cast_links = Vec::new();
+
+    for (index, item) in request.items.into_iter().enumerate() {
+        let match_on = item.match_on.unwrap_or(request.match_on);
+        let existing_id = match find_upsert_match(&mut tx, match_on, &item).await {
+            Ok(id) => id,
+            Err(message) => {
+                errors.push(ErrorDetail::new(format!("items[{}].{}", index, match_on.field()), message));
+                continue;
+            }
+        };
+        let existing = match existing_id {
+            Some(id) => crate::database::get_media_with(&mut *tx, &id).await?,
+            None => None,
+        };
+
+        let tmdb_id = item.tmdb_id;
+        let cast = item.fields.cast.clone();
+        let (mut media, before) = match existing {
+            Some(media) => {
+                let before = media.clone();
+                (media, Some(before))
+            }
+            None => {
+                let title = item.fields.title.clone().unwrap_or_default();
+                let media_type = match item.fields.media_type.as_deref().map(str::parse::<MediaType>) {
+                    None => MediaType::Movie,
+                    Some(Ok(media_type)) => media_type,
+                    Some(Err(e)) => {
+                        errors.push(ErrorDetail::new(format!("items[{}].media_type", index), e));
+                        continue;
+                    }
+                };
+                match MediaItem::new(title, media_type) {
+                    Ok(media) => (media, None),
+                    Err(e) => {
+                        errors.push(ErrorDetail::new(format!("items[{}].{}", index, e.field()), e.to_string()));
+                        continue;
+                    }
+                }
+            }
+        };
+
+        if let Err(e) = media.apply_update(item.fields) {
+            errors.push(ErrorDetail::new(format!("items[{}].{}", index, e.field()), e.to_string()));
+            continue;
+        }
+        if let Some(tmdb_id) = tmdb_id {
+            let mut ids = media.get_external_ids().unwrap_or_default();
+            if ids.tmdb_id != Some(tmdb_id) {
+                ids.tmdb_id = Some(tmdb_id);
+                media.set_external_ids(&ids)
+                    .map_err(|e| ApiError::Internal(format!("Failed to set external ids: {}", e)))?;
+            }
+        }
+
+        let action = match &before {
+            None => {
+                crate::database::insert_media_with(&mut *tx, &media).await?;
+                "created"
+            }
+            Some(before) if media_unchanged(before, &media) => "unchanged",
+            Some(_) => {
+                crate::database::update_media_with(&mut *tx, &media).await?;
+                "updated"
+            }
+        };
+        if action != "unchanged" {
+            if let Some(cast) = cast {
+                cast_links.push((media.id.clone(), cast));
+            }
+        }
+        results.push(UpsertMediaResult {
+            index,
+            action: action.to_string(),
+            media_id: media.id.clone(),
+            title: media.title.clone(),
+            match_on,
+        });
+    }
+
+    if !errors.is_empty() {
+        // tx 未提交，丢弃时自动回滚
+        return Err(ApiError::invalid_fields(errors));
+    }
+    tx.commit().await
+        .map_err(|e| ApiError::Internal(format!("Failed to commit upsert: {}", e)))?;
+
+    for (media_id, cast) in cast_links {
+        link_cast_to_media(pool, &media_id, cast).await;
+    }
+
+    let count = |action: &str| results.iter().filter(|r| r.action == action).count();
+    let response = UpsertMediaResponse {
+        created: count("created"),
+        updated: count("updated"),
+        unchanged: count("unchanged"),
+        results,
+    };
+    tracing::info!("批量写入媒体完成：新增 {} 个，更新 {} 个，未变化 {} 个",
+        response.created, response.updated, response.unchanged);
+    Ok(success(response))
+}
+
 // ============ Export/Import Data ============
 
 #[derive(Debug, Serialize)]
@@ -892,21 +1962,89 @@ pub struct ExportSeriesItem {
     pub studio: Option<String>,
 }
 
-/// 导出所有数据
+/// 导出筛选条件（与媒体列表相同，另可按收藏状态筛选；都不提供时导出全部数据）
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportParams {
+    pub media_type: Option<String>,
+    pub studio: Option<String>,
+    pub series: Option<String>,
+    pub keyword: Option<String>,
+    pub year: Option<i32>,
+    pub genre: Option<String>,
+    pub missing_poster: Option<bool>,
+    pub release_date_from: Option<String>,
+    pub release_date_to: Option<String>,
+    /// 只导出该观看状态的收藏
+    pub watch_status: Option<String>,
+    /// 只导出标记为喜爱的收藏
+    pub favorite: Option<bool>,
+}
+
+impl ExportParams {
+    fn is_filtered(&self) -> bool {
+        self.media_type.is_some() || self.studio.is_some() || self.series.is_some()
+            || self.keyword.is_some() || self.year.is_some() || self.genre.is_some()
+            || self.missing_poster.is_some() || self.release_date_from.is_some()
+            || self.release_date_to.is_some() || self.has_collection_filter()
+    }
+
+    fn has_collection_filter(&self) -> bool {
+        self.watch_status.is_some() || self.favorite == Some(true)
+    }
+
+    /// 收藏是否满足收藏状态筛选
+    fn matches_collection(&self, collection: &crate::models::Collection) -> bool {
+        self.watch_status.as_ref().is_none_or(|s| collection.watch_status == *s)
+            && (self.favorite != Some(true) || collection.is_favorite)
+    }
+}
+
+/// 导出数据（可按筛选条件只导出部分媒体及其关联的收藏、演员、厂商和系列）
 pub async fn export_all_data(
+    Query(params): Query<ExportParams>,
     State(state): State<AppState>,
 ) -> ApiResult<impl IntoResponse> {
-    // 获取所有媒体
-    let (media_list, _) = state.db_service.get_media_list(1, 10000).await?;
+    if let Some(ref status) = params.watch_status {
+        status.parse::<WatchStatus>().map_err(ApiError::BadRequest)?;
+    }
+    let subset = params.is_filtered();
+    
+    // 获取媒体
+    let filters = MediaFilters {
+        media_type: params.media_type.clone(),
+        studio: params.studio.clone(),
+        series: params.series.clone(),
+        keyword: params.keyword.clone(),
+        year: params.year,
+        genre: params.genre.clone(),
+        missing_poster: params.missing_poster,
+        release_date_from: params.release_date_from.clone(),
+        release_date_to: params.release_date_to.clone(),
+        sort_by: "created_at".to_string(),
+        sort_order: "desc".to_string(),
+        seed: None,
+    };
+    let (mut media_list, _) = state.db_service.get_media_list_filtered(1, 10000, &filters).await?;
+    
+    // 获取收藏
+    let mut collections = state.db_service.get_collections().await?;
+    if params.has_collection_filter() {
+        collections.retain(|c| params.matches_collection(c));
+        let collected: std::collections::HashSet<&str> = collections.iter().map(|c| c.media_id.as_str()).collect();
+        media_list.retain(|m| collected.contains(m.id.as_str()));
+    }
+    let media_ids: std::collections::HashSet<String> = media_list.iter().map(|m| m.id.clone()).collect();
+    if subset {
+        collections.retain(|c| media_ids.contains(&c.media_id));
+    }
+    let used_studios: std::collections::HashSet<String> = media_list.iter().filter_map(|m| m.studio.clone()).collect();
+    let used_series: std::collections::HashSet<String> = media_list.iter().filter_map(|m| m.series.clone()).collect();
     
     let media_responses: Vec<MediaItemResponse> = media_list
         .into_iter()
         .map(MediaItemResponse::from)
         .collect();
     
-    // 获取所有收藏
-    let collections = state.db_service.get_collections().await?;
-    
     let collection_responses: Vec<crate::models::CollectionResponse> = collections
         .into_iter()
         .map(crate::models::CollectionResponse::from)
@@ -915,10 +2053,10 @@ pub async fn export_all_data(
     // 获取所有演员
     let actors_result = crate::database::list_actors(
         state.database.pool(),
-        &crate::models::ActorSearchFilters { query: None, limit: Some(10000), offset: None }
+        &crate::models::ActorSearchFilters { limit: Some(i32::MAX), ..Default::default() }
     ).await;
     
-    let actors: Vec<ExportActorItem> = match actors_result {
+    let mut actors: Vec<ExportActorItem> = match actors_result {
         Ok(response) => response.actors.into_iter().map(|a| ExportActorItem {
             id: a.id,
             name: a.name,
@@ -937,7 +2075,7 @@ pub async fn export_all_data(
     };
     
     // 获取所有演员-媒体关系
-    let relations: Vec<ExportActorMediaRelation> = {
+    let mut relations: Vec<ExportActorMediaRelation> = {
         let pool = state.database.pool();
         let rows: Result<Vec<(String, String, Option<String>, String)>, _> = sqlx::query_as(
             "SELECT actor_id, media_id, character_name, role FROM actor_media"
@@ -957,7 +2095,7 @@ pub async fn export_all_data(
     };
     
     // 获取所有厂商
-    let studios: Vec<String> = {
+    let mut studios: Vec<String> = {
         let pool = state.database.pool();
         let rows: Result<Vec<(String,)>, _> = sqlx::query_as(
             "SELECT DISTINCT name FROM studios ORDER BY name"
@@ -975,10 +2113,10 @@ pub async fn export_all_data(
     };
     
     // 获取所有系列
-    let series: Vec<ExportSeriesItem> = {
+    let mut series: Vec<ExportSeriesItem> = {
         let pool = state.database.pool();
         let rows: Result<Vec<(String, Option<String>)>, _> = sqlx::query_as(
-            "SELECT name, studio FROM series ORDER BY name"
+            "SELECT se.name, st.name FROM series se LEFT JOIN studios st ON st.id = se.studio_id ORDER BY se.name"
         )
         .fetch_all(pool)
         .await;
@@ -994,6 +2132,15 @@ pub async fn export_all_data(
         }
     };
     
+    // 部分导出时只保留与导出媒体相关的演员、厂商和系列
+    if subset {
+        relations.retain(|r| media_ids.contains(&r.media_id));
+        let actor_ids: std::collections::HashSet<&str> = relations.iter().map(|r| r.actor_id.as_str()).collect();
+        actors.retain(|a| actor_ids.contains(a.id.as_str()));
+        studios.retain(|s| used_studios.contains(s));
+        series.retain(|s| used_series.contains(&s.name));
+    }
+    
     Ok(success(ExportDataResponse {
         version: "1.2".to_string(),  // 版本升级到 1.2
         exported_at: chrono::Utc::now().to_rfc3339(),
@@ -1032,6 +2179,9 @@ pub struct ImportActorMediaRelation {
 #[derive(Debug, Deserialize)]
 pub struct ImportDataRequest {
     pub version: String,
+    /// 媒体已存在时的处理方式，默认总是新建
+    #[serde(default)]
+    pub on_conflict: ImportConflictStrategy,
     pub media: Vec<ImportMediaItem>,
     pub collections: Option<Vec<ImportCollectionItem>>,
     pub actors: Option<Vec<ImportActorItem>>,
@@ -1040,62 +2190,373 @@ pub struct ImportDataRequest {
     pub series: Option<Vec<ImportSeriesItem>>,  // 新增：系列列表
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ImportSeriesItem {
-    pub name: String,
-    pub studio: Option<String>,
+#[derive(Debug, Deserialize)]
+pub struct ImportSeriesItem {
+    pub name: String,
+    pub studio: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportMediaItem {
+    pub code: Option<String>,
+    pub external_ids: Option<ExternalIds>,
+    pub title: String,
+    pub original_title: Option<String>,
+    pub year: Option<i32>,
+    pub release_date: Option<String>,  // 发售日期，支持多种格式：YYYY-MM-DD, YY.MM.DD, YYYY/MM/DD 等
+    pub media_type: String,
+    pub genres: Option<Vec<String>>,
+    pub rating: Option<f64>,
+    pub overview: Option<String>,
+    pub poster_url: Option<String>,
+    pub backdrop_url: Option<String>,
+    pub play_links: Option<Vec<crate::models::PlayLink>>,
+    pub download_links: Option<Vec<crate::models::DownloadLink>>,
+    pub preview_urls: Option<Vec<String>>,
+    pub preview_video_urls: Option<Vec<String>>,
+    pub studio: Option<String>,
+    pub series: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportCollectionItem {
+    pub media_title: String,
+    pub watch_status: String,
+    pub personal_rating: Option<f64>,
+    pub notes: Option<String>,
+    pub is_favorite: Option<bool>,
+    pub user_tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportDataResponse {
+    pub media_imported: usize,
+    pub media_failed: usize,
+    pub collections_imported: usize,
+    pub collections_failed: usize,
+    pub actors_imported: usize,
+    pub actors_failed: usize,
+    pub relations_imported: usize,
+    pub relations_failed: usize,
+    pub studios_imported: usize,  // 新增
+    pub series_imported: usize,   // 新增
+    pub media_skipped: usize,
+    /// 覆盖或补充的已有媒体数量
+    pub media_updated: usize,
+    pub decisions: Vec<ImportMediaDecision>,
+    pub errors: Vec<String>,
+}
+
+/// 导入时的媒体冲突处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportConflictStrategy {
+    /// 总是新建媒体（不检查冲突）
+    #[default]
+    Create,
+    /// 已存在时跳过
+    Skip,
+    /// 已存在时用导入的字段覆盖
+    Overwrite,
+    /// 已存在时只补充为空的字段
+    Merge,
+}
+
+/// 单个媒体的导入结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportMediaDecision {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// created / skipped / overwritten / merged / failed
+    pub action: &'static str,
+    /// 与已有媒体冲突的依据：code / external_id / title_year
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_on: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_id: Option<String>,
+}
+
+impl ImportMediaDecision {
+    fn new(item: &ImportMediaItem, action: &'static str, matched_on: Option<&'static str>, media_id: Option<String>) -> Self {
+        Self {
+            title: item.title.clone(),
+            code: item.code.clone(),
+            action,
+            matched_on,
+            media_id,
+        }
+    }
+}
+
+fn import_year(item: &ImportMediaItem) -> Option<i32> {
+    item.year.or_else(|| item.release_date.as_ref().and_then(|date| parse_year_from_date(date)))
+}
+
+/// 查找与导入项相同的已有媒体：依次按番号、外部ID（TMDB/IMDb）、标题+年份精确匹配
+async fn find_import_conflict(
+    state: &AppState,
+    item: &ImportMediaItem,
+    media_type: &MediaType,
+) -> anyhow::Result<Option<(String, &'static str)>> {
+    let pool = state.database.pool();
+    if let Some(code) = item.code.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+        if let Some(id) = crate::database::find_media_id_by_code(pool, code).await? {
+            return Ok(Some((id, "code")));
+        }
+    }
+    if let Some(ids) = &item.external_ids {
+        if ids.tmdb_id.is_some() || ids.imdb_id.is_some() {
+            let is_tv = *media_type == MediaType::Scene;
+            if let Some(id) = crate::database::find_media_id_by_external_ids(pool, ids.tmdb_id, is_tv, ids.imdb_id.as_deref()).await? {
+                return Ok(Some((id, "external_id")));
+            }
+        }
+    }
+    let id = crate::database::find_media_id_by_title_year(pool, &item.title, import_year(item)).await?;
+    Ok(id.map(|id| (id, "title_year")))
+}
+
+fn json_list_is_empty(value: Option<&str>) -> bool {
+    value.is_none_or(|v| matches!(v.trim(), "" | "[]" | "null"))
+}
+
+/// 把导入项的字段写入媒体（supplement 时只填充为空的字段）
+async fn apply_import_fields(state: &AppState, media: &mut MediaItem, item: &ImportMediaItem, supplement: bool) {
+    let fill = |is_empty: bool| !supplement || is_empty;
+    
+    if let Some(code) = item.code.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+        if fill(media.code.is_none()) {
+            media.code = Some(code.to_string());
+        }
+    }
+    if let Some(ref imported) = item.external_ids {
+        let current = media.get_external_ids().unwrap_or_default();
+        let merged = if supplement {
+            ExternalIds {
+                tmdb_id: current.tmdb_id.or(imported.tmdb_id),
+                imdb_id: current.imdb_id.or_else(|| imported.imdb_id.clone()),
+                omdb_id: current.omdb_id.or_else(|| imported.omdb_id.clone()),
+                tvdb_id: current.tvdb_id.or(imported.tvdb_id),
+                custom: imported.custom.iter().chain(&current.custom)
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            }
+        } else {
+            ExternalIds {
+                tmdb_id: imported.tmdb_id.or(current.tmdb_id),
+                imdb_id: imported.imdb_id.clone().or(current.imdb_id),
+                omdb_id: imported.omdb_id.clone().or(current.omdb_id),
+                tvdb_id: imported.tvdb_id.or(current.tvdb_id),
+                custom: current.custom.into_iter().chain(imported.custom.clone()).collect(),
+            }
+        };
+        let _ = media.set_external_ids(&merged);
+    }
+    
+    // 处理年份 - 优先使用 year，如果没有则从 release_date 提取
+    if let Some(y) = import_year(item) {
+        if fill(media.year.is_none()) {
+            let _ = media.set_year(Some(y));
+        }
+    }
+    
+    // 处理发售日期 - 标准化格式
+    if let Some(ref release_date) = item.release_date {
+        if let Some(normalized) = normalize_release_date(release_date) {
+            if fill(media.release_date.is_none()) {
+                media.release_date = Some(normalized);
+            }
+        }
+    }
+    
+    if let Some(ref overview) = item.overview {
+        if fill(media.overview.is_none()) {
+            let _ = media.set_overview(Some(overview.clone()));
+        }
+    }
+    if let Some(ref genres) = item.genres {
+        if fill(json_list_is_empty(Some(&media.genres))) {
+            let _ = media.set_genres(genres);
+        }
+    }
+    if let Some(rating) = item.rating {
+        if fill(media.rating.is_none()) {
+            let _ = media.set_rating(Some(rating as f32));
+        }
+    }
+    if let Some(ref poster_url) = item.poster_url {
+        if fill(media.poster_url.is_none()) {
+            let _ = media.set_poster_url(Some(poster_url.clone()));
+        }
+    }
+    if let Some(ref backdrop_url) = item.backdrop_url {
+        if fill(json_list_is_empty(media.backdrop_url.as_deref())) {
+            let _ = media.set_backdrop_url(Some(backdrop_url.clone()));
+        }
+    }
+    if let Some(ref original_title) = item.original_title {
+        if fill(media.original_title.is_none()) {
+            media.original_title = Some(original_title.clone());
+        }
+    }
+    if let Some(ref play_links) = item.play_links {
+        if fill(json_list_is_empty(media.play_links.as_deref())) {
+            let _ = media.set_play_links(play_links);
+        }
+    }
+    if let Some(ref download_links) = item.download_links {
+        if fill(json_list_is_empty(media.download_links.as_deref())) {
+            let _ = media.set_download_links(download_links);
+        }
+    }
+    if let Some(ref preview_urls) = item.preview_urls {
+        if fill(json_list_is_empty(media.preview_urls.as_deref())) {
+            let _ = media.set_preview_urls(preview_urls);
+        }
+    }
+    if let Some(ref preview_video_urls) = item.preview_video_urls {
+        if fill(json_list_is_empty(media.preview_video_urls.as_deref())) {
+            let _ = media.set_preview_video_urls(preview_video_urls);
+        }
+    }
+    // 新增 studio 和 series - 使用智能匹配
+    if item.studio.is_some() || item.series.is_some() {
+        // 处理厂商
+        if let Some(ref studio_name) = item.studio {
+            if !studio_name.is_empty() && fill(media.studio.is_none()) {
+                // 确保厂商存在
+                if let Err(e) = crate::database::find_or_create_studio(state.database.pool(), studio_name).await {
+                    tracing::warn!("Failed to create studio '{}': {}", studio_name, e);
+                }
+                media.studio = Some(studio_name.clone());
+            }
+        }
+        
+        // 处理系列 - 智能匹配
+        if let Some(ref series_name) = item.series {
+            if !series_name.is_empty() && fill(media.series.is_none()) {
+                match crate::database::smart_match_or_create_series(
+                    state.database.pool(),
+                    series_name,
+                    item.studio.as_deref(),
+                ).await {
+                    Ok(match_result) => {
+                        media.series = Some(match_result.series_name.clone());
+                        
+                        // 如果只提供了系列名且匹配到唯一厂商，自动填充厂商
+                        if item.studio.is_none() && match_result.studio_name.is_some() && fill(media.studio.is_none()) {
+                            media.studio = match_result.studio_name.clone();
+                            if let Some(ref studio_name) = match_result.studio_name {
+                                tracing::info!(
+                                    "Auto-matched series '{}' to studio '{}'",
+                                    series_name,
+                                    studio_name
+                                );
+                            }
+                        }
+                        
+                        // 记录匹配类型
+                        match match_result.match_type {
+                            crate::models::SeriesMatchType::Ambiguous => {
+                                tracing::warn!(
+                                    "Series '{}' exists in multiple studios, using first match",
+                                    series_name
+                                );
+                            }
+                            _ => {}
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to match series '{}': {}", series_name, e);
+                        media.series = Some(series_name.clone());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 导入数据
+pub async fn import_data(
+    State(state): State<AppState>,
+    Json(mut payload): Json<ImportDataRequest>,
+) -> impl IntoResponse {
+    let media = std::mem::take(&mut payload.media);
+    let media_total = media.len();
+    success(import_payload(&state, &payload, stream::iter(media), media_total, None).await)
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ImportMediaItem {
-    pub title: String,
-    pub original_title: Option<String>,
-    pub year: Option<i32>,
-    pub release_date: Option<String>,  // 发售日期，支持多种格式：YYYY-MM-DD, YY.MM.DD, YYYY/MM/DD 等
-    pub media_type: String,
-    pub genres: Option<Vec<String>>,
-    pub rating: Option<f64>,
-    pub overview: Option<String>,
-    pub poster_url: Option<String>,
-    pub backdrop_url: Option<String>,
-    pub play_links: Option<Vec<crate::models::PlayLink>>,
-    pub download_links: Option<Vec<crate::models::DownloadLink>>,
-    pub preview_urls: Option<Vec<String>>,
-    pub preview_video_urls: Option<Vec<String>>,
-    pub studio: Option<String>,
-    pub series: Option<String>,
-}
+/// 一批最多攒多少个新建媒体再写入
+const IMPORT_INSERT_BATCH: usize = 500;
 
-#[derive(Debug, Deserialize)]
-pub struct ImportCollectionItem {
-    pub media_title: String,
-    pub watch_status: String,
-    pub personal_rating: Option<f64>,
-    pub notes: Option<String>,
-    pub is_favorite: Option<bool>,
-    pub user_tags: Option<Vec<String>>,
+/// 等待批量插入的新建媒体
+#[derive(Default)]
+struct PendingImports {
+    media: Vec<MediaItem>,
+    /// 对应的 decisions 下标
+    decisions: Vec<usize>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct ImportDataResponse {
-    pub media_imported: usize,
-    pub media_failed: usize,
-    pub collections_imported: usize,
-    pub collections_failed: usize,
-    pub actors_imported: usize,
-    pub actors_failed: usize,
-    pub relations_imported: usize,
-    pub relations_failed: usize,
-    pub studios_imported: usize,  // 新增
-    pub series_imported: usize,   // 新增
-    pub errors: Vec<String>,
+impl PendingImports {
+    fn push(&mut self, media: MediaItem, decision: usize) {
+        self.media.push(media);
+        self.decisions.push(decision);
+    }
+    
+    fn is_full(&self) -> bool {
+        self.media.len() >= IMPORT_INSERT_BATCH
+    }
+    
+    /// 导入项是否可能与未写入的媒体重复（番号、外部ID或标题相同）
+    fn may_conflict(&self, item: &ImportMediaItem) -> bool {
+        let code = item.code.as_deref().map(str::trim).filter(|c| !c.is_empty());
+        self.media.iter().any(|media| {
+            if media.title.eq_ignore_ascii_case(&item.title) {
+                return true;
+            }
+            if code.is_some_and(|code| media.code.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(code))) {
+                return true;
+            }
+            match (&item.external_ids, media.get_external_ids()) {
+                (Some(imported), Ok(ids)) => {
+                    (imported.tmdb_id.is_some() && imported.tmdb_id == ids.tmdb_id)
+                        || (imported.imdb_id.is_some() && imported.imdb_id == ids.imdb_id)
+                }
+                _ => false,
+            }
+        })
+    }
+    
+    /// 写入攒下的媒体，整批失败时逐条重试，返回写入失败的 (decisions 下标, 错误)
+    async fn flush(&mut self, state: &AppState) -> Vec<(usize, anyhow::Error)> {
+        let media = std::mem::take(&mut self.media);
+        let decisions = std::mem::take(&mut self.decisions);
+        let repository = state.database.repository();
+        let Err(e) = repository.insert_media_batch(&media).await else {
+            return Vec::new();
+        };
+        tracing::warn!("Batch insert of {} imported media failed, retrying one by one: {}", media.len(), e);
+        let mut failed = Vec::new();
+        for (media, index) in media.iter().zip(decisions) {
+            if let Err(e) = repository.insert_media(media).await {
+                failed.push((index, e));
+            }
+        }
+        failed
+    }
 }
 
-/// 导入数据
-pub async fn import_data(
-    State(state): State<AppState>,
-    Json(payload): Json<ImportDataRequest>,
-) -> impl IntoResponse {
+/// 执行导入（session_id 不为空时更新分块导入会话的进度）
+///
+/// 媒体逐条从 media 读取（payload.media 不使用），分块导入时可以边解析边导入
+pub(crate) async fn import_payload(
+    state: &AppState,
+    payload: &ImportDataRequest,
+    media: impl Stream<Item = ImportMediaItem>,
+    media_total: usize,
+    session_id: Option<&str>,
+) -> ImportDataResponse {
     let mut media_imported = 0;
     let mut media_failed = 0;
     let mut collections_imported = 0;
@@ -1106,6 +2567,9 @@ pub async fn import_data(
     let mut relations_failed = 0;
     let mut studios_imported = 0;
     let mut series_imported = 0;
+    let mut media_skipped = 0;
+    let mut media_updated = 0;
+    let mut decisions = Vec::new();
     let mut errors = Vec::new();
     let mut media_id_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
     let mut actor_id_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
@@ -1150,12 +2614,23 @@ pub async fn import_data(
         }
     }
     
-    // 导入演员
+    // 导入演员（一次查出已有演员并批量创建缺少的）
     if let Some(actors) = &payload.actors {
-        for item in actors {
-            // 先尝试查找已存在的演员
-            match crate::database::find_or_create_actor_by_name(state.database.pool(), &item.name).await {
-                Ok(mut actor) => {
+        let names: Vec<&str> = actors.iter()
+            .map(|item| item.name.as_str())
+            .filter(|name| !name.trim().is_empty())
+            .collect();
+        match crate::database::find_or_create_actors_by_names(state.database.pool(), &names).await {
+            Ok(found) => {
+                let mut by_name: std::collections::HashMap<String, crate::models::Actor> = found.into_iter()
+                    .map(|actor| (actor.name.clone(), actor))
+                    .collect();
+                for item in actors {
+                    let Some(actor) = by_name.get_mut(&item.name) else {
+                        actors_failed += 1;
+                        errors.push(format!("Actor '{}': name cannot be empty", item.name));
+                        continue;
+                    };
                     // 更新演员详细信息
                     if item.avatar_url.is_some() || item.photo_url.is_some() || item.poster_url.is_some() || item.backdrop_url.is_some() || item.biography.is_some() || item.birth_date.is_some() || item.nationality.is_some() {
                         if let Some(ref avatar_url) = item.avatar_url {
@@ -1180,27 +2655,41 @@ pub async fn import_data(
                             actor.nationality = Some(nationality.clone());
                         }
                         // 更新演员信息
-                        if let Err(e) = crate::database::update_actor_direct(state.database.pool(), &actor).await {
+                        if let Err(e) = crate::database::update_actor_direct(state.database.pool(), actor).await {
                             tracing::warn!("Failed to update actor details: {}", e);
                         }
                     }
-                    
+                
                     if let Some(ref original_id) = item.id {
                         actor_id_map.insert(original_id.clone(), actor.id.clone());
                     }
                     actor_id_map.insert(item.name.clone(), actor.id.clone());
                     actors_imported += 1;
                 }
-                Err(e) => {
-                    actors_failed += 1;
-                    errors.push(format!("Actor '{}': {}", item.name, e));
-                }
+            }
+            Err(e) => {
+                actors_failed += actors.len();
+                errors.push(format!("Actors: {}", e));
             }
         }
     }
     
-    // 导入媒体
-    for item in &payload.media {
+    // 导入媒体（新建的媒体攒成一批用多行 INSERT 写入）
+    let on_conflict = payload.on_conflict;
+    let mut pending = PendingImports::default();
+    let mut insert_failures = Vec::new();
+    let mut media = std::pin::pin!(media.enumerate());
+    while let Some((index, item)) = media.next().await {
+        let item = &item;
+        if let Some(session_id) = session_id {
+            super::import_session::report_import_progress(session_id, index, media_total, &item.title);
+        }
+        
+        // 与未写入的媒体可能重复时先写入，查重才能查到
+        if pending.may_conflict(item) || pending.is_full() {
+            insert_failures.extend(pending.flush(state).await);
+        }
+        
         let media_type = match item.media_type.as_str() {
             "Movie" => MediaType::Movie,
             "Scene" => MediaType::Scene,
@@ -1209,124 +2698,89 @@ pub async fn import_data(
             _ => MediaType::Movie,
         };
         
-        match state.db_service.create_media(item.title.clone(), media_type).await {
+        // 查找已有的同一媒体（不冲突处理时总是新建）
+        let conflict = if on_conflict == ImportConflictStrategy::Create {
+            None
+        } else {
+            match find_import_conflict(state, item, &media_type).await {
+                Ok(conflict) => conflict,
+                Err(e) => {
+                    media_failed += 1;
+                    errors.push(format!("Media '{}': {}", item.title, e));
+                    decisions.push(ImportMediaDecision::new(item, "failed", None, None));
+                    continue;
+                }
+            }
+        };
+        
+        let (media, action, matched_on) = match conflict {
+            Some((media_id, matched_on)) if on_conflict == ImportConflictStrategy::Skip => {
+                media_id_map.insert(item.title.clone(), media_id.clone());
+                media_skipped += 1;
+                decisions.push(ImportMediaDecision::new(item, "skipped", Some(matched_on), Some(media_id)));
+                continue;
+            }
+            Some((media_id, matched_on)) => match state.db_service.get_media_detail(&media_id).await {
+                Ok(Some(media)) => {
+                    let action = if on_conflict == ImportConflictStrategy::Merge { "merged" } else { "overwritten" };
+                    (Ok(media), action, Some(matched_on))
+                }
+                Ok(None) => (Err(anyhow::anyhow!("Media not found")), "failed", Some(matched_on)),
+                Err(e) => (Err(e), "failed", Some(matched_on)),
+            },
+            None => (state.db_service.new_media(item.title.clone(), media_type).await, "created", None),
+        };
+        
+        match media {
             Ok(mut media) => {
-                // 处理年份 - 优先使用 year，如果没有则从 release_date 提取
-                let year = item.year.or_else(|| {
-                    item.release_date.as_ref().and_then(|date| parse_year_from_date(date))
-                });
-                if let Some(y) = year {
-                    let _ = media.set_year(Some(y));
+                let supplement = on_conflict == ImportConflictStrategy::Merge && action != "created";
+                if action == "overwritten" {
+                    let _ = media.set_title(item.title.clone());
                 }
+                apply_import_fields(state, &mut media, item, supplement).await;
                 
-                // 处理发售日期 - 标准化格式
-                if let Some(ref release_date) = item.release_date {
-                    if let Some(normalized) = normalize_release_date(release_date) {
-                        media.release_date = Some(normalized);
+                // 填好全部字段后再写入，新建的媒体不会只留下标题
+                if action != "created" {
+                    if let Err(e) = state.db_service.update_media(media.clone()).await {
+                        media_failed += 1;
+                        errors.push(format!("Media '{}': {}", item.title, e));
+                        decisions.push(ImportMediaDecision::new(item, "failed", matched_on, None));
+                        continue;
                     }
                 }
                 
-                if let Some(ref overview) = item.overview {
-                    let _ = media.set_overview(Some(overview.clone()));
-                }
-                if let Some(ref genres) = item.genres {
-                    let _ = media.set_genres(genres);
-                }
-                if let Some(rating) = item.rating {
-                    let _ = media.set_rating(Some(rating as f32));
-                }
-                if let Some(ref poster_url) = item.poster_url {
-                    let _ = media.set_poster_url(Some(poster_url.clone()));
-                }
-                if let Some(ref backdrop_url) = item.backdrop_url {
-                    let _ = media.set_backdrop_url(Some(backdrop_url.clone()));
-                }
-                if let Some(ref original_title) = item.original_title {
-                    media.original_title = Some(original_title.clone());
-                }
-                if let Some(ref play_links) = item.play_links {
-                    let _ = media.set_play_links(play_links);
-                }
-                if let Some(ref download_links) = item.download_links {
-                    let _ = media.set_download_links(download_links);
-                }
-                if let Some(ref preview_urls) = item.preview_urls {
-                    let _ = media.set_preview_urls(preview_urls);
-                }
-                if let Some(ref preview_video_urls) = item.preview_video_urls {
-                    let _ = media.set_preview_video_urls(preview_video_urls);
-                }
-                // 新增 studio 和 series - 使用智能匹配
-                if item.studio.is_some() || item.series.is_some() {
-                    // 处理厂商
-                    if let Some(ref studio_name) = item.studio {
-                        if !studio_name.is_empty() {
-                            // 确保厂商存在
-                            if let Err(e) = crate::database::find_or_create_studio(state.database.pool(), studio_name).await {
-                                tracing::warn!("Failed to create studio '{}': {}", studio_name, e);
-                            }
-                            media.studio = Some(studio_name.clone());
-                        }
-                    }
-                    
-                    // 处理系列 - 智能匹配
-                    if let Some(ref series_name) = item.series {
-                        if !series_name.is_empty() {
-                            match crate::database::smart_match_or_create_series(
-                                state.database.pool(),
-                                series_name,
-                                item.studio.as_deref(),
-                            ).await {
-                                Ok(match_result) => {
-                                    media.series = Some(match_result.series_name.clone());
-                                    
-                                    // 如果只提供了系列名且匹配到唯一厂商，自动填充厂商
-                                    if item.studio.is_none() && match_result.studio_name.is_some() {
-                                        media.studio = match_result.studio_name.clone();
-                                        if let Some(ref studio_name) = match_result.studio_name {
-                                            tracing::info!(
-                                                "Auto-matched series '{}' to studio '{}'",
-                                                series_name,
-                                                studio_name
-                                            );
-                                        }
-                                    }
-                                    
-                                    // 记录匹配类型
-                                    match match_result.match_type {
-                                        crate::models::SeriesMatchType::Ambiguous => {
-                                            tracing::warn!(
-                                                "Series '{}' exists in multiple studios, using first match",
-                                                series_name
-                                            );
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                                Err(e) => {
-                                    tracing::warn!("Failed to match series '{}': {}", series_name, e);
-                                    media.series = Some(series_name.clone());
-                                }
-                            }
-                        }
-                    }
+                media_id_map.insert(item.title.clone(), media.id.clone());
+                if action == "created" {
+                    media_imported += 1;
+                } else {
+                    media_updated += 1;
                 }
-                
-                // 保存更新
-                if let Err(e) = state.db_service.update_media(media.clone()).await {
-                    tracing::warn!("Failed to update imported media fields: {}", e);
+                decisions.push(ImportMediaDecision::new(item, action, matched_on, Some(media.id.clone())));
+                if action == "created" {
+                    pending.push(media, decisions.len() - 1);
                 }
-                
-                media_id_map.insert(item.title.clone(), media.id.clone());
-                media_imported += 1;
             }
             Err(e) => {
                 media_failed += 1;
                 errors.push(format!("Media '{}': {}", item.title, e));
+                decisions.push(ImportMediaDecision::new(item, "failed", matched_on, None));
             }
         }
     }
     
+    insert_failures.extend(pending.flush(state).await);
+    for (index, e) in insert_failures {
+        let decision = &mut decisions[index];
+        if decision.media_id.is_some() && media_id_map.get(&decision.title) == decision.media_id.as_ref() {
+            media_id_map.remove(&decision.title);
+        }
+        errors.push(format!("Media '{}': {}", decision.title, e));
+        decision.action = "failed";
+        decision.media_id = None;
+        media_imported -= 1;
+        media_failed += 1;
+    }
+    
     // 导入演员-媒体关系
     if let Some(relations) = &payload.actor_media_relations {
         for rel in relations {
@@ -1396,7 +2850,7 @@ pub async fn import_data(
         }
     }
     
-    success(ImportDataResponse {
+    ImportDataResponse {
         media_imported,
         media_failed,
         collections_imported,
@@ -1407,6 +2861,450 @@ pub async fn import_data(
         relations_failed,
         studios_imported,
         series_imported,
+        media_skipped,
+        media_updated,
+        decisions,
         errors,
-    })
+    }
+}
+
+// ============ Import Validation ============
+
+/// 可以导入的数据版本
+const SUPPORTED_IMPORT_VERSIONS: &[&str] = &["1.0", "1.1", "1.2"];
+
+const IMPORT_FIELDS: &[&str] = &[
+    "version", "exported_at", "on_conflict", "media", "collections", "actors",
+    "actor_media_relations", "studios", "series",
+];
+const IMPORT_MEDIA_FIELDS: &[&str] = &[
+    "code", "external_ids", "title", "original_title", "year", "release_date", "media_type",
+    "genres", "rating", "overview", "poster_url", "backdrop_url", "play_links", "download_links",
+    "preview_urls", "preview_video_urls", "studio", "series",
+];
+const IMPORT_COLLECTION_FIELDS: &[&str] = &[
+    "media_title", "watch_status", "personal_rating", "notes", "is_favorite", "user_tags",
+];
+const IMPORT_ACTOR_FIELDS: &[&str] = &[
+    "id", "name", "avatar_url", "photo_url", "poster_url", "backdrop_url", "biography",
+    "birth_date", "nationality",
+];
+const IMPORT_RELATION_FIELDS: &[&str] = &[
+    "actor_id", "actor_name", "media_id", "media_title", "character_name", "role",
+];
+const IMPORT_SERIES_FIELDS: &[&str] = &["name", "studio"];
+
+/// 导入文件中的一个问题
+#[derive(Debug, Serialize)]
+pub struct ImportValidationIssue {
+    /// error：导入会失败；warning：导入时会被忽略或使用默认值
+    pub severity: &'static str,
+    /// 出问题的位置，如 media[3].release_date
+    pub path: String,
+    /// schema / unsupported_version / unknown_field / invalid_date / invalid_value / duplicate / unresolved_relation
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// 导入文件校验报告
+#[derive(Debug, Default, Serialize)]
+pub struct ImportValidationReport {
+    /// 没有 error 级别的问题
+    pub valid: bool,
+    pub version: Option<String>,
+    pub media_count: usize,
+    pub collection_count: usize,
+    pub actor_count: usize,
+    pub relation_count: usize,
+    pub studio_count: usize,
+    pub series_count: usize,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub issues: Vec<ImportValidationIssue>,
+}
+
+impl ImportValidationReport {
+    fn error(&mut self, path: impl Into<String>, code: &'static str, message: impl Into<String>) {
+        self.issues.push(ImportValidationIssue { severity: "error", path: path.into(), code, message: message.into() });
+    }
+
+    fn warning(&mut self, path: impl Into<String>, code: &'static str, message: impl Into<String>) {
+        self.issues.push(ImportValidationIssue { severity: "warning", path: path.into(), code, message: message.into() });
+    }
+
+    fn check_unknown_fields(&mut self, path: &str, value: &serde_json::Value, known: &[&str]) {
+        if let Some(object) = value.as_object() {
+            for key in object.keys().filter(|key| !known.contains(&key.as_str())) {
+                let field_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                self.warning(field_path, "unknown_field", "Unknown field will be ignored");
+            }
+        }
+    }
+
+    fn check_date(&mut self, path: String, date: Option<&str>) {
+        if let Some(date) = date.filter(|d| !d.trim().is_empty()) {
+            if normalize_release_date(date).is_none() {
+                self.warning(path, "invalid_date", format!("Unrecognized date format '{}'", date));
+            }
+        }
+    }
+
+    /// 逐项解析数组，返回能解析的项（解析失败的项记为 error）
+    fn parse_section<T: serde::de::DeserializeOwned>(
+        &mut self,
+        root: &serde_json::Value,
+        section: &str,
+        required: bool,
+        known: &[&str],
+    ) -> Vec<(usize, T)> {
+        let value = match root.get(section) {
+            None | Some(serde_json::Value::Null) => {
+                if required {
+                    self.error(section, "schema", "Missing required field");
+                }
+                return Vec::new();
+            }
+            Some(value) => value,
+        };
+        let Some(items) = value.as_array() else {
+            self.error(section, "schema", "Expected an array");
+            return Vec::new();
+        };
+
+        let mut parsed = Vec::with_capacity(items.len());
+        for (index, item) in items.iter().enumerate() {
+            let path = format!("{}[{}]", section, index);
+            self.check_unknown_fields(&path, item, known);
+            match serde_json::from_value::<T>(item.clone()) {
+                Ok(item) => parsed.push((index, item)),
+                Err(e) => self.error(path, "schema", e.to_string()),
+            }
+        }
+        parsed
+    }
+}
+
+/// 校验导入文件（只读取，不写入任何数据）
+/// POST /api/data/import/validate
+pub async fn validate_import_data(
+    State(state): State<AppState>,
+    body: axum::body::Bytes,
+) -> ApiResult<impl IntoResponse> {
+    let mut report = ImportValidationReport::default();
+    let root: serde_json::Value = match serde_json::from_slice(&body) {
+        Ok(root) => root,
+        Err(e) => {
+            report.error("", "schema", format!("Invalid JSON: {}", e));
+            return Ok(success(finish_validation_report(report)));
+        }
+    };
+    if !root.is_object() {
+        report.error("", "schema", "Expected a JSON object");
+        return Ok(success(finish_validation_report(report)));
+    }
+    report.check_unknown_fields("", &root, IMPORT_FIELDS);
+
+    // 版本
+    match root.get("version") {
+        Some(serde_json::Value::String(version)) => {
+            if !SUPPORTED_IMPORT_VERSIONS.contains(&version.as_str()) {
+                report.warning("version", "unsupported_version", format!(
+                    "Version '{}' is not one of {}", version, SUPPORTED_IMPORT_VERSIONS.join(", ")
+                ));
+            }
+            report.version = Some(version.clone());
+        }
+        Some(_) => report.error("version", "schema", "Expected a string"),
+        None => report.error("version", "schema", "Missing required field"),
+    }
+    if let Some(on_conflict) = root.get("on_conflict") {
+        if let Err(e) = serde_json::from_value::<ImportConflictStrategy>(on_conflict.clone()) {
+            report.error("on_conflict", "schema", e.to_string());
+        }
+    }
+
+    let media: Vec<(usize, ImportMediaItem)> = report.parse_section(&root, "media", true, IMPORT_MEDIA_FIELDS);
+    let collections: Vec<(usize, ImportCollectionItem)> = report.parse_section(&root, "collections", false, IMPORT_COLLECTION_FIELDS);
+    let actors: Vec<(usize, ImportActorItem)> = report.parse_section(&root, "actors", false, IMPORT_ACTOR_FIELDS);
+    let relations: Vec<(usize, ImportActorMediaRelation)> = report.parse_section(&root, "actor_media_relations", false, IMPORT_RELATION_FIELDS);
+    report.parse_section::<ImportSeriesItem>(&root, "series", false, IMPORT_SERIES_FIELDS);
+    match root.get("studios") {
+        None | Some(serde_json::Value::Null) => {}
+        Some(studios) => match serde_json::from_value::<Vec<String>>(studios.clone()) {
+            Ok(studios) => report.studio_count = studios.len(),
+            Err(e) => report.error("studios", "schema", e.to_string()),
+        },
+    }
+    let section_len = |section: &str| root.get(section).and_then(|v| v.as_array()).map_or(0, Vec::len);
+    report.media_count = section_len("media");
+    report.collection_count = section_len("collections");
+    report.actor_count = section_len("actors");
+    report.relation_count = section_len("actor_media_relations");
+    report.series_count = section_len("series");
+
+    // 媒体
+    let mut media_titles = std::collections::HashSet::new();
+    for (index, item) in &media {
+        if !media_titles.insert(item.title.as_str()) {
+            report.warning(format!("media[{}].title", index), "duplicate",
+                format!("Duplicate title '{}', collections and relations will use the last one", item.title));
+        }
+        if !matches!(item.media_type.as_str(), "Movie" | "Scene" | "Documentary" | "Anime") {
+            report.warning(format!("media[{}].media_type", index), "invalid_value",
+                format!("Unknown media type '{}', Movie will be used", item.media_type));
+        }
+        report.check_date(format!("media[{}].release_date", index), item.release_date.as_deref());
+    }
+
+    // 演员
+    let mut actor_keys = std::collections::HashSet::new();
+    for (index, item) in &actors {
+        actor_keys.insert(item.name.as_str());
+        if let Some(ref id) = item.id {
+            actor_keys.insert(id.as_str());
+        }
+        report.check_date(format!("actors[{}].birth_date", index), item.birth_date.as_deref());
+    }
+
+    // 收藏
+    for (index, item) in &collections {
+        if !media_titles.contains(item.media_title.as_str()) {
+            report.error(format!("collections[{}].media_title", index), "unresolved_relation",
+                format!("Media '{}' is not in the import file", item.media_title));
+        }
+        if !matches!(item.watch_status.as_str(), "WantToWatch" | "Watching" | "Completed" | "OnHold" | "Dropped") {
+            report.warning(format!("collections[{}].watch_status", index), "invalid_value",
+                format!("Unknown watch status '{}', WantToWatch will be used", item.watch_status));
+        }
+    }
+
+    // 演员-媒体关系：ID 不在导入文件中时必须已存在于数据库
+    let pool = state.database.pool();
+    let mut existing_actors = HashMap::new();
+    let mut existing_media = HashMap::new();
+    for (index, rel) in &relations {
+        let path = format!("actor_media_relations[{}]", index);
+        if let Some(ref id) = rel.actor_id {
+            if !actor_keys.contains(id.as_str()) {
+                if !existing_actors.contains_key(id) {
+                    let exists = crate::database::get_actor(pool, id).await.map_err(anyhow::Error::from)?.is_some();
+                    existing_actors.insert(id.clone(), exists);
+                }
+                if !existing_actors[id] {
+                    report.error(format!("{}.actor_id", path), "unresolved_relation", format!("Actor '{}' not found", id));
+                }
+            }
+        } else if let Some(ref name) = rel.actor_name {
+            if !actor_keys.contains(name.as_str()) {
+                report.error(format!("{}.actor_name", path), "unresolved_relation",
+                    format!("Actor '{}' is not in the import file", name));
+            }
+        } else {
+            report.error(path.clone(), "unresolved_relation", "Missing actor_id or actor_name");
+        }
+
+        if let Some(ref id) = rel.media_id {
+            if !media_titles.contains(id.as_str()) {
+                if !existing_media.contains_key(id) {
+                    let exists = state.db_service.get_media_detail(id).await?.is_some();
+                    existing_media.insert(id.clone(), exists);
+                }
+                if !existing_media[id] {
+                    report.error(format!("{}.media_id", path), "unresolved_relation", format!("Media '{}' not found", id));
+                }
+            }
+        } else if let Some(ref title) = rel.media_title {
+            if !media_titles.contains(title.as_str()) {
+                report.error(format!("{}.media_title", path), "unresolved_relation",
+                    format!("Media '{}' is not in the import file", title));
+            }
+        } else {
+            report.error(path, "unresolved_relation", "Missing media_id or media_title");
+        }
+    }
+
+    Ok(success(finish_validation_report(report)))
+}
+
+fn finish_validation_report(mut report: ImportValidationReport) -> ImportValidationReport {
+    report.error_count = report.issues.iter().filter(|issue| issue.severity == "error").count();
+    report.warning_count = report.issues.len() - report.error_count;
+    report.valid = report.error_count == 0;
+    report
+}
+
+// ============ List Import ============
+
+#[derive(Debug, Deserialize)]
+pub struct ListImportRequest {
+    /// IMDb 或 Letterboxd 导出的 CSV 内容
+    pub content: String,
+    /// imdb / letterboxd，为空时根据表头自动识别
+    pub format: Option<String>,
+    /// 收藏分组名称（写入收藏的用户标签）
+    pub collection: String,
+    /// 新加入收藏的观看状态，为空时已看过/已评分的为 Completed，其余为 WantToWatch
+    pub watch_status: Option<WatchStatus>,
+}
+
+/// 单行的解析结果
+#[derive(Debug, Serialize)]
+pub struct ListImportRowResult {
+    pub line: usize,
+    pub title: String,
+    pub year: Option<i32>,
+    /// created / existing / not_found / skipped / failed
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tmdb_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListImportResponse {
+    pub collection: String,
+    pub format: crate::services::list_import::ListFormat,
+    pub total: usize,
+    pub created: usize,
+    pub existing: usize,
+    pub not_found: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub results: Vec<ListImportRowResult>,
+}
+
+/// 导入 IMDb / Letterboxd 列表：逐行通过 TMDB 搜索解析，创建缺失的媒体并加入指定收藏分组
+pub async fn import_list(
+    State(state): State<AppState>,
+    Json(payload): Json<ListImportRequest>,
+) -> ApiResult<impl IntoResponse> {
+    use crate::services::list_import::{parse_list, ListFormat};
+    
+    let collection = payload.collection.trim().to_string();
+    if collection.is_empty() {
+        return Err(ApiError::Validation("Collection name cannot be empty".to_string()));
+    }
+    let format = payload.format.as_deref()
+        .map(str::parse::<ListFormat>)
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
+    let (format, rows) = parse_list(&payload.content, format).map_err(ApiError::BadRequest)?;
+    
+    if !state.external_client.is_tmdb_available() {
+        return Err(ApiError::ExternalService("TMDB service unavailable".to_string()));
+    }
+    
+    let mut results = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let mut result = ListImportRowResult {
+            line: row.line,
+            title: row.title.clone(),
+            year: row.year,
+            status: "skipped",
+            media_id: None,
+            tmdb_id: None,
+            matched_title: None,
+            error: row.skip_reason.clone(),
+        };
+        if row.skip_reason.is_none() {
+            let watch_status = payload.watch_status.clone().unwrap_or(if row.watched {
+                WatchStatus::Completed
+            } else {
+                WatchStatus::WantToWatch
+            });
+            if let Err(e) = import_list_row(&state, row, &collection, watch_status, &mut result).await {
+                tracing::warn!("List import line {} '{}' failed: {}", row.line, row.title, e);
+                result.status = "failed";
+                result.error = Some(e);
+            }
+        }
+        results.push(result);
+    }
+    
+    let count = |status: &str| results.iter().filter(|r| r.status == status).count();
+    Ok(success(ListImportResponse {
+        collection,
+        format,
+        total: results.len(),
+        created: count("created"),
+        existing: count("existing"),
+        not_found: count("not_found"),
+        skipped: count("skipped"),
+        failed: count("failed"),
+        results,
+    }))
+}
+
+/// 解析单行并加入收藏分组，结果写入 result
+async fn import_list_row(
+    state: &AppState,
+    row: &crate::services::list_import::ListRow,
+    collection: &str,
+    watch_status: WatchStatus,
+    result: &mut ListImportRowResult,
+) -> Result<(), String> {
+    let is_tv = row.media_type == "tv";
+    let search_results = if is_tv {
+        state.external_client.search_tv_shows(&row.title, None).await
+    } else {
+        state.external_client.search_movies(&row.title, None).await
+    }
+    .map_err(|e| format!("TMDB search failed: {}", e))?;
+    
+    let Some(mut media) = crate::services::list_import::pick_by_year(search_results, row.year) else {
+        result.status = "not_found";
+        return Ok(());
+    };
+    let mut external_ids = media.get_external_ids().map_err(|e| e.to_string())?;
+    if external_ids.imdb_id.is_none() {
+        external_ids.imdb_id = row.imdb_id.clone();
+    }
+    result.tmdb_id = external_ids.tmdb_id;
+    result.matched_title = Some(media.title.clone());
+    
+    let pool = state.database.pool();
+    let existing = crate::database::find_media_id_by_external_ids(
+        pool,
+        external_ids.tmdb_id,
+        is_tv,
+        row.imdb_id.as_deref(),
+    ).await.map_err(|e| e.to_string())?;
+    
+    let media_id = match existing {
+        Some(id) => {
+            result.status = "existing";
+            id
+        }
+        None => {
+            media.set_external_ids(&external_ids).map_err(|e| e.to_string())?;
+            state.database.repository().insert_media(&media).await.map_err(|e| e.to_string())?;
+            result.status = "created";
+            media.id.clone()
+        }
+    };
+    result.media_id = Some(media_id.clone());
+    
+    let repository = state.database.repository();
+    let validation = |e: crate::models::ValidationError| format!("Validation error: {:?}", e);
+    match repository.get_collection_by_media_id(&media_id).await.map_err(|e| e.to_string())? {
+        Some(mut item) => {
+            item.add_user_tag(collection.to_string()).map_err(validation)?;
+            if item.personal_rating.is_none() {
+                item.set_personal_rating(row.rating).map_err(validation)?;
+            }
+            repository.update_collection(&item).await.map_err(|e| e.to_string())?;
+        }
+        None => {
+            let mut item = crate::models::Collection::new(media_id, watch_status);
+            item.add_user_tag(collection.to_string()).map_err(validation)?;
+            item.set_personal_rating(row.rating).map_err(validation)?;
+            repository.add_to_collection(&item).await.map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
 }
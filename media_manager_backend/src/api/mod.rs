@@ -1,4 +1,5 @@
 pub mod media;
+pub mod import_session;
 pub mod collections;
 pub mod search;
 pub mod health;
@@ -10,7 +11,22 @@ pub mod sync;
 pub mod file_scan;
 pub mod streaming;
 pub mod cache;
+pub mod calendar;
+pub mod wanted;
+pub mod quality;
+pub mod share;
+pub mod library;
+pub mod hooks;
+pub mod enrichment;
+pub mod favorites;
+pub mod history;
+pub mod maintenance;
 pub mod error;
+pub mod i18n;
+pub mod idempotency;
+pub mod compression;
+pub mod sessions;
+pub mod offline;
 pub mod response;
 
 use std::sync::Arc;
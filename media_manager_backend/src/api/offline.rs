@@ -0,0 +1,148 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures_util::StreamExt;
+use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
+use tokio_util::io::ReaderStream;
+
+use crate::database::{self, DatabaseRepository};
+use crate::models::{select_edition_file, CreateOfflinePackageRequest, OfflinePackage, OfflinePackageResponse};
+use crate::services::offline_package;
+use super::AppState;
+use super::error::{ApiError, ApiResult, ErrorCode};
+use super::response::{success, success_message};
+
+// ============ Offline Packages ============
+
+/// 创建离线下载包（后台转码为限制大小的 MP4，完成后通过一次性链接下载）
+/// POST /api/media/:id/offline-package
+pub async fn create_offline_package_handler(
+    Path(media_id): Path<String>,
+    State(state): State<AppState>,
+    payload: Option<Json<CreateOfflinePackageRequest>>,
+) -> ApiResult<impl IntoResponse> {
+    let request = payload.map(|Json(p)| p).unwrap_or_default();
+    request.validate().map_err(ApiError::Validation)?;
+
+    state.database.repository().get_media_by_id(&media_id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    let files = state.database.repository().get_media_files(&media_id).await?;
+    select_edition_file(files, request.edition.as_deref(), request.part)
+        .ok_or_else(|| ApiError::NotFound("Media file not found".to_string()))?;
+
+    let package = offline_package::enqueue(state.database.pool(), &media_id, &request).await?;
+    Ok(success(OfflinePackageResponse::from(package)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListOfflinePackagesParams {
+    /// 只返回该媒体的离线包
+    pub media_id: Option<String>,
+}
+
+/// 获取离线包（就绪的包含下载地址）
+/// GET /api/offline-packages
+pub async fn list_offline_packages_handler(
+    State(state): State<AppState>,
+    Query(params): Query<ListOfflinePackagesParams>,
+) -> ApiResult<impl IntoResponse> {
+    let packages = database::list_offline_packages(state.database.pool(), params.media_id.as_deref()).await?;
+    let packages: Vec<OfflinePackageResponse> = packages.into_iter().map(OfflinePackageResponse::from).collect();
+    Ok(success(packages))
+}
+
+/// 获取离线包状态
+/// GET /api/offline-packages/:id
+pub async fn get_offline_package_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let package = database::get_offline_package(state.database.pool(), &id).await?
+        .ok_or_else(|| ApiError::NotFound("Offline package not found".to_string()))?;
+    Ok(success(OfflinePackageResponse::from(package)))
+}
+
+/// 删除离线包及其文件（正在转码的包在转码结束后删除文件）
+/// DELETE /api/offline-packages/:id
+pub async fn delete_offline_package_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let package = database::delete_offline_package(state.database.pool(), &id).await?
+        .ok_or_else(|| ApiError::NotFound("Offline package not found".to_string()))?;
+    offline_package::remove_file(&package).await;
+    Ok(success_message("Offline package deleted"))
+}
+
+// ============ Public Download ============
+// 仅凭令牌访问，完整下载一次后失效
+
+/// 下载结束时（响应体 drop）按是否传完更新离线包
+struct DownloadGuard {
+    pool: Pool<Sqlite>,
+    package: OfflinePackage,
+    sent: Arc<AtomicU64>,
+}
+
+impl Drop for DownloadGuard {
+    fn drop(&mut self) {
+        let pool = self.pool.clone();
+        let package = self.package.clone();
+        let complete = package.file_size.is_some_and(|size| self.sent.load(Ordering::Relaxed) >= size as u64);
+        tokio::spawn(async move {
+            offline_package::finish_download(&pool, &package, complete).await;
+        });
+    }
+}
+
+/// 一次性下载离线包（同一时间只允许一个下载，中断后可重试）
+/// GET /api/public/offline/:token
+pub async fn public_offline_download(
+    Path(token): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Response, StatusCode> {
+    let pool = state.database.pool();
+    let package = database::claim_offline_download(pool, &token).await
+        .map_err(|e| {
+            tracing::error!("Failed to claim offline download: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let file = match package.file_path.as_deref() {
+        Some(path) => tokio::fs::File::open(path).await.ok(),
+        None => None,
+    };
+    let Some(file) = file else {
+        offline_package::finish_download(pool, &package, false).await;
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let filename = format!("{}.mp4", package.media_id);
+    let file_size = package.file_size.unwrap_or_default();
+    let guard = DownloadGuard { pool: pool.clone(), package, sent: Arc::new(AtomicU64::new(0)) };
+    let stream = ReaderStream::new(file).map(move |chunk| {
+        if let Ok(bytes) = &chunk {
+            guard.sent.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        }
+        chunk
+    });
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "video/mp4".to_string()),
+            (header::CONTENT_LENGTH, file_size.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(stream),
+    ).into_response())
+}
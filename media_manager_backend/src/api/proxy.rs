@@ -1,9 +1,14 @@
 use axum::{
-    extract::Query,
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     response::Response,
 };
 use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::database::repository::DatabaseRepository;
+use crate::services::cache::VideoSelector;
+use super::AppState;
 
 #[derive(Debug, Deserialize)]
 pub struct ImageProxyParams {
@@ -18,6 +23,17 @@ pub struct VideoProxyParams {
 #[derive(Debug, Deserialize)]
 pub struct HlsProxyParams {
     pub url: String,
+    /// 额外请求头（JSON 对象），由网盘直链的 HLS 播放列表改写时带上，播放列表和分片请求都会附带
+    pub headers: Option<String>,
+}
+
+impl HlsProxyParams {
+    fn extra_headers(&self) -> Result<HashMap<String, String>, StatusCode> {
+        match &self.headers {
+            Some(headers) => serde_json::from_str(headers).map_err(|_| StatusCode::BAD_REQUEST),
+            None => Ok(HashMap::new()),
+        }
+    }
 }
 
 /// 图片代理 - 解决 CORS 和防盗链问题
@@ -85,28 +101,59 @@ pub async fn proxy_image(
 /// 视频代理 - 解决 CORS 和防盗链问题
 pub async fn proxy_video(
     Query(params): Query<VideoProxyParams>,
+    headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
-    let url = params.url;
+    fetch_video(&params.url, &HashMap::new(), &headers).await
+}
+
+/// 网盘链接代理播放 - 先通过插件解析为直链，再附带插件给出的请求头代理视频
+pub async fn proxy_download_link(
+    State(state): State<AppState>,
+    Path((id, index)): Path<(String, usize)>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let (_, _, resolved) = super::media::resolve_media_link(&state, &id, index)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Failed to resolve download link {}#{}: {}", id, index, e);
+            e.code().status()
+        })?;
     
+    if resolved.url.contains(".m3u8") {
+        return fetch_hls_playlist(&resolved.url, &resolved.headers).await;
+    }
+    fetch_video(&resolved.url, &resolved.headers, &headers).await
+}
+
+/// 请求视频并以流的形式转发，extra_headers 会覆盖默认的浏览器请求头
+///
+/// 客户端的 Range 请求头原样转发给上游，上游返回 206 时响应也是 206 并带上 Content-Range，
+/// 播放器拖动进度时不需要从头下载
+async fn fetch_video(url: &str, extra_headers: &HashMap<String, String>, request_headers: &HeaderMap) -> Result<Response, StatusCode> {
     // 验证 URL 是否合法
     if !url.starts_with("http://") && !url.starts_with("https://") {
         return Err(StatusCode::BAD_REQUEST);
     }
     
-    // 构建请求客户端
+    // 响应体是流，只限制连接时间
     let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(60)) // 视频需要更长的超时时间
+        .connect_timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     // 发送请求，添加常见的浏览器 headers 来绕过防盗链
-    let response = client
-        .get(&url)
+    let mut request = client
+        .get(url)
         .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
         .header("Accept", "video/webm,video/ogg,video/*;q=0.9,application/ogg;q=0.7,audio/*;q=0.6,*/*;q=0.5")
         .header("Accept-Language", "zh-CN,zh;q=0.9,en;q=0.8")
-        .header("Referer", extract_origin(&url))
-        .header("Range", "bytes=0-") // 支持视频流式传输
+        .header("Referer", extract_origin(url));
+    let range = request_headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    if let Some(range) = range {
+        request = request.header("Range", range);
+    }
+    let response = request
+        .headers(to_header_map(extra_headers))
         .send()
         .await
         .map_err(|e| {
@@ -114,37 +161,160 @@ pub async fn proxy_video(
             StatusCode::BAD_GATEWAY
         })?;
     
-    // 使用 u16 比较状态码
-    if !response.status().is_success() && response.status().as_u16() != 206 {
+    let status = response.status().as_u16();
+    if status == 416 {
+        return Err(StatusCode::RANGE_NOT_SATISFIABLE);
+    }
+    if !response.status().is_success() {
         tracing::error!("Video fetch failed with status: {}", response.status());
         return Err(StatusCode::BAD_GATEWAY);
     }
     
-    // 获取 Content-Type
-    let content_type = response
-        .headers()
-        .get("content-type")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("video/mp4")
-        .to_string();
+    // 客户端没有请求范围时按完整响应返回
+    let partial = status == 206 && range.is_some();
+    let mut builder = Response::builder()
+        .status(if partial { StatusCode::PARTIAL_CONTENT } else { StatusCode::OK })
+        .header("Content-Type", response.headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("video/mp4"))
+        .header("Accept-Ranges", "bytes") // 支持范围请求
+        .header("Cache-Control", "public, max-age=3600") // 缓存 1 小时
+        .header("Access-Control-Allow-Origin", "*");
+    let forwarded = if partial { &[header::CONTENT_LENGTH, header::CONTENT_RANGE][..] } else { &[header::CONTENT_LENGTH][..] };
+    for name in forwarded {
+        if let Some(value) = response.headers().get(name.as_str()).and_then(|v| v.to_str().ok()) {
+            builder = builder.header(name, value);
+        }
+    }
     
-    // 获取视频数据
-    let bytes = response
-        .bytes()
+    builder
+        .body(axum::body::Body::from_stream(response.bytes_stream()))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// 预告片代理播放 - YouTube/Vimeo 页面地址需要启用 yt-dlp 特性
+pub async fn proxy_trailer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let trailer_url = state.database.repository()
+        .get_media_by_id(&id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?
+        .trailer_url
+        .ok_or(StatusCode::NOT_FOUND)?;
     
-    // 构建响应
-    let response = Response::builder()
-        .status(StatusCode::OK)
-        .header("Content-Type", content_type)
-        .header("Accept-Ranges", "bytes") // 支持范围请求
-        .header("Cache-Control", "public, max-age=3600") // 缓存 1 小时
-        .header("Access-Control-Allow-Origin", "*")
-        .body(axum::body::Body::from(bytes))
+    let url = crate::services::trailer::resolve_stream_url(&trailer_url)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Failed to resolve trailer {}: {}", trailer_url, e);
+            if crate::services::trailer::ytdlp_enabled() { StatusCode::BAD_GATEWAY } else { StatusCode::NOT_IMPLEMENTED }
+        })?;
+    
+    if url.contains(".m3u8") {
+        return fetch_hls_playlist(&url, &HashMap::new()).await;
+    }
+    proxy_video(Query(VideoProxyParams { url }), headers).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PreviewResolveParams {
+    /// redirect（默认，302 跳转到可用地址）或 proxy（通过视频代理播放）
+    pub mode: Option<String>,
+}
+
+/// 预览视频健康检查 - 按清晰度从高到低检查 preview_video_urls，使用第一个可访问的地址
+pub async fn resolve_preview_video(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<PreviewResolveParams>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let proxy = match params.mode.as_deref() {
+        None | Some("redirect") => false,
+        Some("proxy") => true,
+        Some(_) => return Err(StatusCode::BAD_REQUEST),
+    };
+    let stored = state.database.repository()
+        .get_media_by_id(&id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?
+        .preview_video_urls
+        .unwrap_or_default();
+    
+    let candidates = VideoSelector::rank_by_quality(&VideoSelector::parse_stored(&stored));
+    if candidates.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(PREVIEW_CHECK_TIMEOUT_SECS))
+        .build()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
-    Ok(response)
+    for video in candidates {
+        if !is_video_reachable(&client, &video.url).await {
+            tracing::debug!("Preview video unavailable for {}: {} ({})", id, video.url, video.quality);
+            continue;
+        }
+        if proxy {
+            if video.url.contains(".m3u8") {
+                return fetch_hls_playlist(&video.url, &HashMap::new()).await;
+            }
+            return fetch_video(&video.url, &HashMap::new(), &headers).await;
+        }
+        return Response::builder()
+            .status(StatusCode::FOUND)
+            .header("Location", video.url.as_str())
+            .header("X-Preview-Quality", video.quality.as_str())
+            .header("Cache-Control", "no-cache")
+            .body(axum::body::Body::empty())
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR);
+    }
+    
+    tracing::warn!("No reachable preview video for media {}", id);
+    Err(StatusCode::NOT_FOUND)
+}
+
+/// 单个预览视频地址的检查超时秒数
+const PREVIEW_CHECK_TIMEOUT_SECS: u64 = 10;
+
+/// 检查视频地址是否可访问（不支持 HEAD 的服务器改用只请求 1 字节的 GET）
+async fn is_video_reachable(client: &reqwest::Client, url: &str) -> bool {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return false;
+    }
+    let user_agent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    let head = client.head(url)
+        .header("User-Agent", user_agent)
+        .header("Referer", extract_origin(url))
+        .send()
+        .await;
+    match head {
+        Ok(response) if response.status().is_success() => return true,
+        Ok(response) if matches!(response.status().as_u16(), 403 | 405 | 501) => {}
+        _ => return false,
+    }
+    client.get(url)
+        .header("User-Agent", user_agent)
+        .header("Referer", extract_origin(url))
+        .header("Range", "bytes=0-0")
+        .send()
+        .await
+        .is_ok_and(|response| response.status().is_success())
+}
+
+/// 把插件给出的请求头转换为 reqwest 请求头，忽略不合法的名称或值
+fn to_header_map(headers: &HashMap<String, String>) -> reqwest::header::HeaderMap {
+    headers.iter()
+        .filter_map(|(name, value)| Some((
+            reqwest::header::HeaderName::from_bytes(name.as_bytes()).ok()?,
+            reqwest::header::HeaderValue::from_str(value).ok()?,
+        )))
+        .collect()
 }
 
 /// 从 URL 提取 origin 作为 Referer
@@ -160,8 +330,11 @@ fn extract_origin(url: &str) -> String {
 pub async fn proxy_hls(
     Query(params): Query<HlsProxyParams>,
 ) -> Result<Response, StatusCode> {
-    let url = params.url;
-    
+    fetch_hls_playlist(&params.url, &params.extra_headers()?).await
+}
+
+/// 请求 M3U8 播放列表并改写其中的地址，extra_headers 会附带到播放列表和改写后的分片请求上
+async fn fetch_hls_playlist(url: &str, extra_headers: &HashMap<String, String>) -> Result<Response, StatusCode> {
     // 验证 URL 是否合法
     if !url.starts_with("http://") && !url.starts_with("https://") {
         return Err(StatusCode::BAD_REQUEST);
@@ -175,12 +348,13 @@ pub async fn proxy_hls(
     
     // 发送请求
     let response = client
-        .get(&url)
+        .get(url)
         .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
         .header("Accept", "*/*")
         .header("Accept-Language", "zh-CN,zh;q=0.9,en;q=0.8")
-        .header("Referer", extract_origin(&url))
-        .header("Origin", extract_origin(&url).trim_end_matches('/'))
+        .header("Referer", extract_origin(url))
+        .header("Origin", extract_origin(url).trim_end_matches('/'))
+        .headers(to_header_map(extra_headers))
         .send()
         .await
         .map_err(|e| {
@@ -200,8 +374,8 @@ pub async fn proxy_hls(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     // 解析并重写 M3U8 内容中的 URL
-    let base_url = extract_base_url(&url);
-    let rewritten_content = rewrite_m3u8_urls(&content, &base_url, &url);
+    let base_url = extract_base_url(url);
+    let rewritten_content = rewrite_m3u8_urls(&content, &base_url, url, extra_headers);
     
     // 构建响应
     let response = Response::builder()
@@ -219,6 +393,7 @@ pub async fn proxy_hls(
 pub async fn proxy_hls_segment(
     Query(params): Query<HlsProxyParams>,
 ) -> Result<Response, StatusCode> {
+    let extra_headers = params.extra_headers()?;
     let url = params.url;
     
     // 验证 URL 是否合法
@@ -240,6 +415,7 @@ pub async fn proxy_hls_segment(
         .header("Accept-Language", "zh-CN,zh;q=0.9,en;q=0.8")
         .header("Referer", extract_origin(&url))
         .header("Origin", extract_origin(&url).trim_end_matches('/'))
+        .headers(to_header_map(&extra_headers))
         .send()
         .await
         .map_err(|e| {
@@ -278,9 +454,14 @@ pub async fn proxy_hls_segment(
     Ok(response)
 }
 
-/// 重写 M3U8 文件中的 URL，使其通过代理访问
-fn rewrite_m3u8_urls(content: &str, base_url: &str, original_url: &str) -> String {
+/// 重写 M3U8 文件中的 URL，使其通过代理访问（有额外请求头时随 headers 参数一起传给代理）
+fn rewrite_m3u8_urls(content: &str, base_url: &str, original_url: &str, extra_headers: &HashMap<String, String>) -> String {
     let mut result = String::new();
+    let headers_param = if extra_headers.is_empty() {
+        String::new()
+    } else {
+        format!("&headers={}", urlencoding::encode(&serde_json::to_string(extra_headers).unwrap_or_default()))
+    };
     
     for line in content.lines() {
         let trimmed = line.trim();
@@ -318,11 +499,11 @@ fn rewrite_m3u8_urls(content: &str, base_url: &str, original_url: &str) -> Strin
         
         if is_m3u8 {
             // 子播放列表，通过 HLS 代理
-            let proxy_url = format!("http://localhost:3000/api/proxy/hls?url={}", urlencoding::encode(&absolute_url));
+            let proxy_url = format!("http://localhost:3000/api/proxy/hls?url={}{}", urlencoding::encode(&absolute_url), headers_param);
             result.push_str(&proxy_url);
         } else {
             // TS 分片或其他文件，通过分片代理
-            let proxy_url = format!("http://localhost:3000/api/proxy/hls/segment?url={}", urlencoding::encode(&absolute_url));
+            let proxy_url = format!("http://localhost:3000/api/proxy/hls/segment?url={}{}", urlencoding::encode(&absolute_url), headers_param);
             result.push_str(&proxy_url);
         }
         
@@ -0,0 +1,89 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::database;
+use crate::models::QualityProfile;
+use super::AppState;
+use super::error::{ApiError, ApiResult};
+use super::response::{success, success_message};
+
+// ============ Quality Profile Handlers ============
+
+/// 获取质量配置列表
+pub async fn get_quality_profiles_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let profiles = database::get_quality_profiles(state.database.pool()).await
+        .map_err(|e| {
+            tracing::error!("Failed to load quality profiles: {}", e);
+            ApiError::Internal("Failed to load quality profiles".to_string())
+        })?;
+
+    Ok(success(profiles))
+}
+
+/// 保存质量配置列表（整体替换）
+pub async fn update_quality_profiles_handler(
+    State(state): State<AppState>,
+    Json(profiles): Json<Vec<QualityProfile>>,
+) -> ApiResult<impl IntoResponse> {
+    if profiles.iter().any(|p| p.name.trim().is_empty()) {
+        return Err(ApiError::Validation("Profile name cannot be empty".to_string()));
+    }
+    if profiles.iter().filter(|p| p.is_default).count() > 1 {
+        return Err(ApiError::Validation("Only one profile can be the default".to_string()));
+    }
+    for profile in &profiles {
+        if let (Some(min), Some(preferred)) = (profile.min_resolution, profile.preferred_resolution) {
+            if min > preferred {
+                return Err(ApiError::Validation(format!(
+                    "Profile '{}': min_resolution cannot exceed preferred_resolution", profile.name
+                )));
+            }
+        }
+    }
+
+    database::save_quality_profiles(state.database.pool(), &profiles).await
+        .map_err(|e| {
+            tracing::error!("Failed to save quality profiles: {}", e);
+            ApiError::Internal("Failed to save quality profiles".to_string())
+        })?;
+
+    Ok(success(profiles))
+}
+
+// ============ Upgrade Handlers ============
+
+/// 获取可升级媒体列表
+pub async fn list_upgrades_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let upgrades = database::list_upgrades(state.database.pool()).await
+        .map_err(|e| {
+            tracing::error!("Failed to list upgrades: {}", e);
+            ApiError::Internal("Failed to retrieve upgrades".to_string())
+        })?;
+
+    Ok(success(upgrades))
+}
+
+/// 忽略/清除某个媒体的升级记录
+pub async fn dismiss_upgrade_handler(
+    Path(media_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let removed = database::remove_upgrade(state.database.pool(), &media_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to remove upgrade: {}", e);
+            ApiError::Internal("Failed to remove upgrade".to_string())
+        })?;
+
+    if !removed {
+        return Err(ApiError::NotFound("Upgrade not found".to_string()));
+    }
+
+    Ok(success_message("Upgrade dismissed"))
+}
@@ -2,25 +2,54 @@
 
 use axum::{
     extract::{Path, Query, State},
-    response::IntoResponse,
+    response::{sse::{Event, KeepAlive, Sse}, IntoResponse},
     Json,
 };
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use std::collections::HashMap;
+use std::time::Duration;
 use tracing::{info, warn, error};
 
 use crate::api::AppState;
-use crate::api::error::{ApiError, ApiResult};
+use crate::api::error::{ApiError, ApiResult, ErrorCode};
+use crate::services::progress::{progress_stream, ProgressRegistry, SessionProgress};
+use crate::services::{magnet, search_query};
 use crate::api::response::{success, success_message};
-use crate::plugins::protocol::MagnetResult;
-use crate::models::{MediaItemResponse, MediaItem};
-use crate::database::{find_or_create_actor_by_name, add_actor_to_media, DatabaseRepository};
+use crate::external::cache::MemoryCache;
+use crate::plugins::manager::PluginManager;
+use crate::plugins::protocol::{MagnetResult, PluginError, ScrapeData, ScrapeSchemaError, ScrapeSchemaReport};
+use crate::plugins::testkit::{run_conformance, SelftestOptions, MAX_SELFTEST_TIMEOUT_SECS};
+use crate::models::{ActorScrapeItemResult, DryRunItem, MagnetTrackerSettings, MediaItemResponse, MediaItem, ScrapeFieldsResponse, SCRAPE_FIELDS};
+use crate::database::{find_or_create_actors_by_names_with, add_actors_to_media_with, DatabaseRepository};
+use sqlx::SqliteConnection;
 
 lazy_static::lazy_static! {
-    static ref MAGNET_SEARCH_PROGRESS: Arc<RwLock<HashMap<String, MagnetSearchProgress>>> = Arc::new(RwLock::new(HashMap::new()));
-    pub static ref MEDIA_SCRAPE_PROGRESS: Arc<RwLock<HashMap<String, MediaScrapeProgress>>> = Arc::new(RwLock::new(HashMap::new()));
+    static ref MAGNET_SEARCH_PROGRESS: ProgressRegistry<MagnetSearchProgress> = ProgressRegistry::new();
+    pub static ref MEDIA_SCRAPE_PROGRESS: ProgressRegistry<MediaScrapeProgress> = ProgressRegistry::new();
+    pub static ref ACTOR_SCRAPE_PROGRESS: ProgressRegistry<ActorScrapeProgress> = ProgressRegistry::new();
+    static ref SCRAPE_SCHEMA_STATS: Arc<RwLock<HashMap<String, PluginSchemaStats>>> = Arc::new(RwLock::new(HashMap::new()));
+    /// 多结果刮削的结果集，按 result_token 保存 30 分钟，选择结果时只需回传 token 和序号
+    static ref SCRAPE_RESULT_SETS: MemoryCache<Vec<serde_json::Value>> = MemoryCache::new(Duration::from_secs(30 * 60));
+}
+
+/// 最多保留的最近校验错误数
+const MAX_RECENT_SCHEMA_ERRORS: usize = 20;
+
+/// 单个插件返回数据的校验统计
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PluginSchemaStats {
+    pub plugin_id: String,
+    /// 校验过的刮削结果数
+    pub checked: u64,
+    /// 存在格式错误（未入库）的结果数
+    pub invalid: u64,
+    /// 各未知字段出现的次数
+    pub unknown_fields: HashMap<String, u64>,
+    /// 最近的格式错误
+    pub recent_errors: Vec<ScrapeSchemaError>,
 }
 
 /// 搜索查询参数
@@ -30,6 +59,20 @@ pub struct SearchQuery {
     pub page: Option<u32>,
 }
 
+/// 磁力搜索查询参数
+#[derive(Debug, Deserialize)]
+pub struct MagnetSearchQuery {
+    pub q: String,
+    /// 全部插件搜索的总超时（秒）
+    pub timeout: Option<u64>,
+}
+
+/// 使用所有支持磁力搜索的插件
+const ALL_MAGNET_PLUGINS: &str = "all";
+/// 全部插件磁力搜索的默认/最大总超时（秒）
+const DEFAULT_MAGNET_SEARCH_TIMEOUT_SECS: u64 = 60;
+const MAX_MAGNET_SEARCH_TIMEOUT_SECS: u64 = 300;
+
 /// 磁力搜索进度
 #[derive(Debug, Serialize, Clone)]
 pub struct MagnetSearchProgress {
@@ -37,19 +80,40 @@ pub struct MagnetSearchProgress {
     pub message: Option<String>,
     pub current_site: Option<String>,  // 当前正在搜索的网站
     pub sites_status: Vec<SiteSearchStatus>,  // 各个网站的搜索状态
+    /// 各个插件的搜索状态（全部插件搜索时）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub plugins_status: Vec<PluginSearchStatus>,
     pub results: Vec<MagnetResult>,
     pub completed: bool,
 }
 
+impl SessionProgress for MagnetSearchProgress {
+    fn is_finished(&self) -> bool {
+        self.completed
+    }
+}
+
 /// 单个网站的搜索状态
 #[derive(Debug, Serialize, Clone)]
 pub struct SiteSearchStatus {
     pub site_name: String,
+    /// 所属插件（全部插件搜索时区分不同插件的同名网站）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin_id: Option<String>,
     pub status: String,  // "pending", "searching", "completed", "failed"
     pub result_count: usize,
     pub error: Option<String>,
 }
 
+/// 单个插件的搜索状态
+#[derive(Debug, Serialize, Clone)]
+pub struct PluginSearchStatus {
+    pub plugin_id: String,
+    pub status: String,  // "searching", "completed", "failed", "timeout"
+    pub result_count: usize,
+    pub error: Option<String>,
+}
+
 /// 媒体刮削进度
 #[derive(Debug, Serialize, Clone)]
 pub struct MediaScrapeProgress {
@@ -64,6 +128,40 @@ pub struct MediaScrapeProgress {
     pub completed: bool,
     pub concurrent: bool,  // 是否并发模式
     pub processing_items: Vec<String>,  // 正在处理的项目列表（并发模式）
+    /// 试运行模式下每个媒体将会发生的变化
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dry_run_results: Vec<DryRunItem>,
+}
+
+impl SessionProgress for MediaScrapeProgress {
+    fn is_finished(&self) -> bool {
+        self.completed
+    }
+}
+
+/// 批量演员刮削进度
+#[derive(Debug, Serialize, Clone)]
+pub struct ActorScrapeProgress {
+    pub status: String,  // "scraping", "completed", "failed"
+    pub message: Option<String>,
+    pub mode: String,
+    pub current: i32,
+    pub total: i32,
+    pub current_actor: Option<String>,  // 当前正在刮削或保存的演员名（串行模式）
+    pub item_status: String,  // "scraping", "saving", "completed", "failed"
+    pub success_count: i32,
+    pub failed_count: i32,
+    pub completed: bool,
+    pub concurrent: bool,  // 是否并发模式
+    pub processing_actors: Vec<String>,  // 正在刮削的演员列表（并发模式）
+    /// 已保存的演员结果（更新的字段、是否下载了图片）
+    pub results: Vec<ActorScrapeItemResult>,
+}
+
+impl SessionProgress for ActorScrapeProgress {
+    fn is_finished(&self) -> bool {
+        self.completed
+    }
 }
 
 /// 媒体刮削响应
@@ -103,6 +201,33 @@ pub async fn reload_plugins(
     Ok(success_message("Plugins reloaded"))
 }
 
+/// 对插件运行协议一致性检查
+/// POST /api/scrape/plugins/:id/selftest
+pub async fn selftest_plugin(
+    State(state): State<AppState>,
+    Path(plugin_id): Path<String>,
+    body: Option<Json<SelftestOptions>>,
+) -> ApiResult<impl IntoResponse> {
+    let options = body.map(|Json(o)| o).unwrap_or_default();
+    if options.timeout_secs == 0 || options.timeout_secs > MAX_SELFTEST_TIMEOUT_SECS {
+        return Err(ApiError::Validation(format!(
+            "timeout_secs must be between 1 and {}", MAX_SELFTEST_TIMEOUT_SECS
+        )));
+    }
+    
+    // 复制插件信息后释放锁，避免自测期间阻塞其他刮削请求
+    let plugin = {
+        let manager = state.plugin_manager.read().await;
+        manager.get_plugin(&plugin_id).cloned()
+            .ok_or_else(|| ApiError::coded(ErrorCode::PluginNotFound, format!("Plugin not found: {}", plugin_id)))?
+    };
+    
+    let report = run_conformance(&plugin, &options).await;
+    info!("插件 {} 自测完成: {} 通过, {} 警告, {} 失败",
+        plugin_id, report.passed_count, report.warning_count, report.failed_count);
+    Ok(success(report))
+}
+
 /// 自动识别ID并刮削
 pub async fn scrape_auto(
     State(state): State<AppState>,
@@ -110,7 +235,7 @@ pub async fn scrape_auto(
 ) -> ApiResult<impl IntoResponse> {
     let manager = state.plugin_manager.read().await;
     let result = manager.scrape_auto(&id).await
-        .map_err(|e| ApiError::ExternalService(e.to_string()))?;
+        .map_err(ApiError::plugin)?;
     Ok(success(result))
 }
 
@@ -121,7 +246,7 @@ pub async fn scrape_with_plugin(
 ) -> ApiResult<impl IntoResponse> {
     let manager = state.plugin_manager.read().await;
     let result = manager.scrape_with_plugin(&plugin_id, &id).await
-        .map_err(|e| ApiError::ExternalService(e.to_string()))?;
+        .map_err(ApiError::plugin)?;
     Ok(success(result))
 }
 
@@ -132,11 +257,74 @@ pub async fn search_with_plugin(
     Query(query): Query<SearchQuery>,
 ) -> ApiResult<impl IntoResponse> {
     let manager = state.plugin_manager.read().await;
-    let result = manager.search_with_plugin(&plugin_id, &query.q, query.page).await
-        .map_err(|e| ApiError::ExternalService(e.to_string()))?;
+    let result = search_query::search_plugin(&manager, &plugin_id, &query.q, query.page).await
+        .map_err(ApiError::plugin)?;
     Ok(success(result))
 }
 
+/// 按页面URL刮削请求
+#[derive(Debug, Deserialize)]
+pub struct ScrapeUrlRequest {
+    /// 来源页面完整URL
+    pub url: String,
+    /// 可选：指定插件（否则按插件声明的 url_domains 自动选择）
+    pub plugin_id: Option<String>,
+    /// 试运行：只返回刮削结果，不创建媒体
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 按页面URL刮削响应
+#[derive(Debug, Serialize)]
+pub struct ScrapeUrlResponse {
+    pub plugin_id: String,
+    pub result: crate::plugins::protocol::ScrapeResult,
+    /// 导入后创建的媒体（试运行时为空）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media: Option<MediaItemResponse>,
+}
+
+/// 按页面URL刮削并导入为新媒体（内置的 Media_Scraper 不支持，需要安装声明了 url_domains 的第三方插件）
+/// POST /api/scrape/url
+pub async fn scrape_url(
+    State(state): State<AppState>,
+    Json(request): Json<ScrapeUrlRequest>,
+) -> ApiResult<impl IntoResponse> {
+    let url = request.url.trim();
+    match url::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+        _ => return Err(ApiError::Validation(format!("Invalid URL: {}", url))),
+    }
+    
+    let (plugin_id, result) = {
+        let manager = state.plugin_manager.read().await;
+        manager.scrape_url(url, request.plugin_id.as_deref()).await
+            .map_err(ApiError::plugin)?
+    };
+    info!("按URL刮削成功: {} (插件: {})", url, plugin_id);
+    
+    if request.dry_run {
+        return Ok(success(ScrapeUrlResponse { plugin_id, result, media: None }));
+    }
+    
+    let mut scrape_data = serde_json::to_value(&result)
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if let Some(obj) = scrape_data.as_object_mut() {
+        obj.insert("source".to_string(), serde_json::json!(plugin_id));
+    }
+    let media_id = create_media_from_scrape_result(&scrape_data, &state).await?;
+    let media = state.db_service.get_media_detail(&media_id).await?
+        .map(MediaItemResponse::from);
+    
+    Ok(success(ScrapeUrlResponse { plugin_id, result, media }))
+}
+
+/// 获取刮削结果中可单独选择的字段
+/// GET /api/scrape/fields
+pub async fn list_scrape_fields() -> ApiResult<impl IntoResponse> {
+    Ok(success(ScrapeFieldsResponse { fields: SCRAPE_FIELDS.to_vec() }))
+}
+
 /// 模式验证函数
 fn validate_mode(mode: &str) -> Result<(), String> {
     match mode.to_lowercase().as_str() {
@@ -150,7 +338,7 @@ fn validate_mode(mode: &str) -> Result<(), String> {
 /// 
 /// 行为：
 /// - 如果刮削返回1个结果：直接更新数据库并返回更新后的媒体信息
-/// - 如果刮削返回多个结果：返回结果列表供前端选择（不入库）
+/// - 如果刮削返回多个结果：返回结果列表供前端选择（不入库），选择时回传 result_token 和 result_index
 pub async fn scrape_media(
     State(state): State<AppState>,
     Path(media_id): Path<String>,
@@ -159,36 +347,43 @@ pub async fn scrape_media(
     // 1. 验证 mode 参数
     validate_mode(&request.mode)
         .map_err(|e| ApiError::Validation(e))?;
+    request.fields.validate()
+        .map_err(ApiError::Validation)?;
     
     // 2. 获取媒体项目
     let mut media = state.db_service.get_media_detail(&media_id).await?
-        .ok_or_else(|| ApiError::NotFound("Media not found".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
     
-    // 3. 如果提供了 data 字段，直接使用这个数据入库（用户从多个结果中选择的情况）
-    if let Some(data) = &request.data {
+    // 3. 如果提供了 data 字段或 result_token，直接使用这个数据入库（用户从多个结果中选择的情况）
+    let selected = request.selected_data()?;
+    if let Some(data) = &selected {
         // 3.1 检查是否是批量创建新媒体（data 是数组）
         if let Some(data_array) = data.as_array() {
             if request.create_new {
                 // 批量创建新媒体
                 info!("批量创建 {} 个新媒体", data_array.len());
                 
-                let mut created_media = Vec::new();
-                
+                let mut items = Vec::new();
                 for (index, item_data) in data_array.iter().enumerate() {
-                    match create_media_from_scrape_result(item_data, &state).await {
-                        Ok(media_id) => {
-                            info!("✓ 创建媒体成功 ({}/{}): {}", index + 1, data_array.len(), media_id);
-                            // 获取创建的媒体详情
-                            if let Ok(Some(media)) = state.db_service.get_media_detail(&media_id).await {
-                                created_media.push(MediaItemResponse::from(media));
-                            }
-                        }
-                        Err(e) => {
-                            error!("✗ 创建媒体失败 ({}/{}): {}", index + 1, data_array.len(), e);
-                        }
+                    let report = ScrapeData::check(item_data);
+                    if !report.is_valid() {
+                        error!("✗ 刮削数据格式错误 ({}/{}): {}", index + 1, data_array.len(), report.error_message());
+                        continue;
+                    }
+                    match new_media_from_scrape_result(item_data) {
+                        Ok(media) => items.push((media, item_data)),
+                        Err(e) => error!("✗ 创建媒体失败 ({}/{}): {}", index + 1, data_array.len(), e),
                     }
                 }
                 
+                // 一次写入全部媒体
+                save_new_scraped_media_batch(&state, &items).await
+                    .map_err(|e| ApiError::Internal(format!("创建媒体失败: {}", e)))?;
+                info!("✓ 批量创建媒体成功: {} 个", items.len());
+                let created_media: Vec<MediaItemResponse> = items.into_iter()
+                    .map(|(media, _)| MediaItemResponse::from(media))
+                    .collect();
+                
                 return Ok(Json(serde_json::json!({
                     "success": true,
                     "message": format!("成功创建 {} 个媒体", created_media.len()),
@@ -203,6 +398,10 @@ pub async fn scrape_media(
         }
         
         // 3.2 单个数据
+        let report = ScrapeData::check(data);
+        if !report.is_valid() {
+            return Err(ApiError::Validation(format!("刮削数据格式错误: {}", report.error_message())));
+        }
         if request.create_new {
             // 创建新媒体
             info!("从刮削数据创建新媒体");
@@ -227,6 +426,9 @@ pub async fn scrape_media(
             // 更新现有媒体
             info!("使用用户选择的刮削数据更新媒体，媒体ID: {}", media_id);
             
+            // 只保留选择的字段
+            let data = &request.fields.apply(data);
+            
             // 根据 mode 参数应用刮削结果
             match request.mode.to_lowercase().as_str() {
                 "replace" => apply_scrape_result_to_media(&mut media, data),
@@ -234,17 +436,8 @@ pub async fn scrape_media(
                 _ => unreachable!(),
             }
             
-            // 保存更新后的媒体
-            state.db_service.update_media(media.clone()).await?;
-            
-            // 同步演员到 actors 表并建立关联
-            if let Some(actors) = data.get("actors").and_then(|v| v.as_array()) {
-                let actor_names: Vec<String> = actors.iter()
-                    .filter_map(|v| v.as_str())
-                    .map(String::from)
-                    .collect();
-                sync_actors_to_db(&state, &actor_names, &media_id).await;
-            }
+            // 在一个事务中保存媒体、演员和翻译
+            save_scraped_media(&state, &media, data, false).await?;
             
             // 调用缓存服务处理图片缓存
             let scraper_name = data.get("source")
@@ -281,7 +474,7 @@ pub async fn scrape_media(
         let plugins = manager.list_plugins();
         let media_scraper = plugins.iter()
             .find(|p| p.config.id == "media_scraper")
-            .ok_or_else(|| ApiError::NotFound("media_scraper 插件未找到".to_string()))?;
+            .ok_or_else(|| ApiError::coded(ErrorCode::PluginNotFound, "media_scraper 插件未找到"))?;
         
         (media_scraper.executable_path.clone(), media_scraper.path.clone())
     };
@@ -353,55 +546,21 @@ pub async fn scrape_media(
         .map_err(|e| ApiError::Internal(format!("等待插件结束失败: {}", e)))?;
     
     if !status.success() {
-        return Err(ApiError::ExternalService("插件执行失败".to_string()));
+        return Err(ApiError::coded(ErrorCode::PluginFailed, "插件执行失败"));
     }
     
     // 解析响应
     let response = response_json
-        .ok_or_else(|| ApiError::ExternalService("未收到插件响应".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::PluginInvalidResponse, "未收到插件响应"))?;
     
     let is_success = response.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
     
     if !is_success {
-        // 处理错误信息（可能是字符串或对象）
+        // 处理错误信息（可能是字符串或对象，保留多语言消息）
         // 先打印完整的响应用于调试
         error!("插件返回错误，完整响应: {:?}", response);
-        
-        let error_msg = if let Some(error_obj) = response.get("error") {
-            error!("error 字段内容: {:?}", error_obj);
-            
-            if let Some(error_str) = error_obj.as_str() {
-                error_str.to_string()
-            } else if let Some(error_dict) = error_obj.as_object() {
-                if let Some(message) = error_dict.get("message") {
-                    error!("message 字段内容: {:?}", message);
-                    
-                    // message 可能是对象（包含 zh/en）或字符串
-                    if let Some(message_obj) = message.as_object() {
-                        // message 是对象，尝试获取 zh 或 en
-                        if let Some(zh_msg) = message_obj.get("zh").and_then(|v| v.as_str()) {
-                            zh_msg.to_string()
-                        } else if let Some(en_msg) = message_obj.get("en").and_then(|v| v.as_str()) {
-                            en_msg.to_string()
-                        } else {
-                            "未知错误".to_string()
-                        }
-                    } else if let Some(msg_str) = message.as_str() {
-                        // message 是字符串
-                        msg_str.to_string()
-                    } else {
-                        "未知错误".to_string()
-                    }
-                } else {
-                    "未知错误".to_string()
-                }
-            } else {
-                "未知错误".to_string()
-            }
-        } else {
-            "未知错误".to_string()
-        };
-        return Err(ApiError::ExternalService(format!("刮削失败: {}", error_msg)));
+        let plugin_error = PluginError::from_value(response.get("error"));
+        return Err(ApiError::from(plugin_error).prefixed("刮削失败: ", "Scrape failed: "));
     }
     
     // 5. 检查是否是多结果格式
@@ -418,11 +577,15 @@ pub async fn scrape_media(
                 .unwrap_or(results.len() as u64);
             
             info!("刮削返回 {} 个结果，返回给前端选择", total_count);
+            for result in &results {
+                check_scrape_data("media_scraper", result).await;
+            }
             
             let response = ScrapeMultipleResponse {
                 success: true,
                 mode: mode.to_string(),
-                results: results.clone(),
+                result_token: Some(cache_scrape_results(&results)),
+                results,
                 message: Some(format!("找到 {} 个结果", total_count)),
             };
             
@@ -435,28 +598,25 @@ pub async fn scrape_media(
     
     // 6. 单个结果：直接入库
     let data = response.get("data")
-        .ok_or_else(|| ApiError::ExternalService("响应中缺少 data 字段".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::PluginInvalidResponse, "响应中缺少 data 字段"))?;
     
     info!("刮削返回 1 个结果，直接入库");
+    let report = check_scrape_data("media_scraper", data).await;
+    if !report.is_valid() {
+        return Err(ApiError::coded(ErrorCode::PluginInvalidResponse, format!("插件返回的数据格式错误: {}", report.error_message())));
+    }
+    let data = &request.fields.apply(data);
     
-    // 7. 根据 mode 参数应用刮削结果
+    // 7. 根据 mode 参数应用刮削结果（只应用选择的字段）
     match request.mode.to_lowercase().as_str() {
         "replace" => apply_scrape_result_to_media(&mut media, data),
         "supplement" => apply_scrape_result_to_media_supplement(&mut media, data),
         _ => unreachable!(),
     }
     
-    // 8. 保存更新后的媒体
-    state.db_service.update_media(media.clone()).await?;
-    
-    // 9. 同步演员到 actors 表并建立关联
-    if let Some(actors) = data.get("actors").and_then(|v| v.as_array()) {
-        let actor_names: Vec<String> = actors.iter()
-            .filter_map(|v| v.as_str())
-            .map(String::from)
-            .collect();
-        sync_actors_to_db(&state, &actor_names, &media_id).await;
-    }
+    // 8. 在一个事务中保存媒体、演员和翻译
+    save_scraped_media(&state, &media, data, false).await?;
+    archive_scrape_source(&state, &media_id, Some(&code), &response).await;
     
     // 10. 调用缓存服务处理图片缓存
     // 从刮削数据中提取刮削器名称（source 字段）
@@ -473,80 +633,243 @@ pub async fn scrape_media(
         tracing::error!("缓存处理失败: media_id={}, scraper={}, error={:?}", media_id, scraper_name, e);
     }
     
-    Ok(Json(serde_json::json!({
+    let mut body = serde_json::json!({
         "success": true,
         "data": MediaItemResponse::from(media)
-    })))
+    });
+    if !report.unknown_fields.is_empty() {
+        body["unknown_fields"] = serde_json::json!(report.unknown_fields);
+    }
+    Ok(Json(body))
+}
+
+/// 保存刮削结果中的多语言标题/简介（translations 字段，格式同 TranslationInput 数组）
+async fn save_scrape_translations(
+    conn: &mut SqliteConnection,
+    scrape_data: &serde_json::Value,
+    media_id: &str,
+) -> anyhow::Result<()> {
+    let Some(items) = scrape_data.get("translations")
+        .and_then(|v| serde_json::from_value::<Vec<crate::models::TranslationInput>>(v.clone()).ok())
+    else {
+        return Ok(());
+    };
+    let source = scrape_data.get("source").and_then(|v| v.as_str()).unwrap_or("scraper");
+    let translations: Vec<crate::models::MediaTranslation> = items.iter()
+        .filter_map(|t| crate::models::MediaTranslation::from_input(media_id, t, source))
+        .collect();
+    if !translations.is_empty() {
+        crate::database::save_media_translations_with(conn, &translations).await?;
+    }
+    Ok(())
 }
 
-/// 同步演员到数据库
-async fn sync_actors_to_db(state: &AppState, actor_names: &[String], media_id: &str) {
-    for actor_name in actor_names {
-        // 查找或创建演员
-        if let Ok(actor) = find_or_create_actor_by_name(state.database.pool(), actor_name).await {
-            // 建立演员与媒体的关联
-            let _ = add_actor_to_media(
-                state.database.pool(),
-                &actor.id,
-                media_id,
-                None,  // character_name
-                Some("cast".to_string()), // role
-            ).await;
+/// 在给定事务中写入刮削结果：媒体（新建或更新）、演员关联和翻译
+pub(crate) async fn write_scraped_media(
+    conn: &mut SqliteConnection,
+    media: &MediaItem,
+    scrape_data: &serde_json::Value,
+    is_new: bool,
+) -> anyhow::Result<()> {
+    if is_new {
+        crate::database::insert_media_with(&mut *conn, media).await?;
+    } else {
+        crate::database::update_media_with(&mut *conn, media).await?;
+    }
+    sync_actors_to_db(conn, scrape_data, &media.id).await?;
+    save_scrape_translations(conn, scrape_data, &media.id).await
+}
+
+/// 在一个事务中保存刮削结果，任一步失败都不会留下部分数据
+pub(crate) async fn save_scraped_media(
+    state: &AppState,
+    media: &MediaItem,
+    scrape_data: &serde_json::Value,
+    is_new: bool,
+) -> anyhow::Result<()> {
+    let repository = state.database.repository();
+    let mut tx = repository.begin().await?;
+    write_scraped_media(&mut tx, media, scrape_data, is_new).await?;
+    repository.commit(tx).await
+}
+
+/// 在一个事务中批量创建刮削得到的媒体：多行 INSERT 写入媒体，再同步各自的演员和翻译
+async fn save_new_scraped_media_batch(
+    state: &AppState,
+    items: &[(MediaItem, &serde_json::Value)],
+) -> anyhow::Result<()> {
+    if items.is_empty() {
+        return Ok(());
+    }
+    let media: Vec<MediaItem> = items.iter().map(|(media, _)| media.clone()).collect();
+    let repository = state.database.repository();
+    let mut tx = repository.begin().await?;
+    crate::database::insert_media_batch_with(&mut tx, &media).await?;
+    for (media, scrape_data) in items {
+        sync_actors_to_db(&mut tx, scrape_data, &media.id).await?;
+        save_scrape_translations(&mut tx, scrape_data, &media.id).await?;
+    }
+    repository.commit(tx).await
+}
+
+/// 校验插件返回的刮削数据，记录未知字段和格式错误
+pub(crate) async fn check_scrape_data(plugin_id: &str, data: &serde_json::Value) -> ScrapeSchemaReport {
+    let report = ScrapeData::check(data);
+    if !report.unknown_fields.is_empty() {
+        warn!("插件 {} 返回了未知字段: {}", plugin_id, report.unknown_fields.join(", "));
+    }
+    if !report.is_valid() {
+        warn!("插件 {} 返回的数据格式错误: {}", plugin_id, report.error_message());
+    }
+    
+    let mut stats_map = SCRAPE_SCHEMA_STATS.write().await;
+    let stats = stats_map.entry(plugin_id.to_string()).or_insert_with(|| PluginSchemaStats {
+        plugin_id: plugin_id.to_string(),
+        ..Default::default()
+    });
+    stats.checked += 1;
+    for field in &report.unknown_fields {
+        *stats.unknown_fields.entry(field.clone()).or_default() += 1;
+    }
+    if !report.is_valid() {
+        stats.invalid += 1;
+        stats.recent_errors.extend(report.errors.iter().cloned());
+        let overflow = stats.recent_errors.len().saturating_sub(MAX_RECENT_SCHEMA_ERRORS);
+        stats.recent_errors.drain(..overflow);
+    }
+    report
+}
+
+/// 获取各插件返回数据的校验统计
+/// GET /api/scrape/plugins/schema-report
+pub async fn get_scrape_schema_report() -> ApiResult<impl IntoResponse> {
+    let stats_map = SCRAPE_SCHEMA_STATS.read().await;
+    let mut stats: Vec<PluginSchemaStats> = stats_map.values().cloned().collect();
+    stats.sort_by(|a, b| a.plugin_id.cmp(&b.plugin_id));
+    Ok(success(stats))
+}
+
+/// 存档刮削原始数据（未启用存档时跳过，失败不影响刮削）
+async fn archive_scrape_source(state: &AppState, media_id: &str, query: Option<&str>, payload: &serde_json::Value) {
+    if let Err(e) = crate::services::scrape_archive::archive_scrape_payload(state.database.pool(), media_id, query, payload).await {
+        warn!("存档刮削原始数据失败: media_id={}, error={}", media_id, e);
+    }
+}
+
+/// 同步刮削结果中的演员到数据库并关联到媒体（批量查找/创建演员，批量建立关联）
+async fn sync_actors_to_db(
+    conn: &mut SqliteConnection,
+    scrape_data: &serde_json::Value,
+    media_id: &str,
+) -> Result<(), sqlx::Error> {
+    let Some(actors) = scrape_data.get("actors").and_then(|v| v.as_array()) else {
+        return Ok(());
+    };
+    let names: Vec<&str> = actors.iter()
+        .filter_map(|v| v.as_str())
+        .filter(|name| !name.trim().is_empty())
+        .collect();
+    if names.is_empty() {
+        return Ok(());
+    }
+    let actor_ids: Vec<String> = find_or_create_actors_by_names_with(conn, &names).await?
+        .into_iter()
+        .map(|actor| actor.id)
+        .collect();
+    add_actors_to_media_with(conn, media_id, &actor_ids, "cast").await?;
+    Ok(())
+}
+
+/// 试运行：计算刮削结果应用到现有媒体后的变化（不写入数据库）
+async fn preview_scrape_update(
+    state: &AppState,
+    media: MediaItem,
+    scrape_data: &serde_json::Value,
+    replace: bool,
+) -> DryRunItem {
+    let mut updated = media.clone();
+    if replace {
+        apply_scrape_result_to_media(&mut updated, scrape_data);
+    } else {
+        apply_scrape_result_to_media_supplement(&mut updated, scrape_data);
+    }
+    let item = DryRunItem::update(&media, &updated);
+    with_actor_preview(state, item, Some(&media.id), scrape_data).await
+}
+
+/// 演员只追加关联：列出当前演员和追加后的演员
+async fn with_actor_preview(
+    state: &AppState,
+    item: DryRunItem,
+    media_id: Option<&str>,
+    scrape_data: &serde_json::Value,
+) -> DryRunItem {
+    let Some(actors) = scrape_data.get("actors").and_then(|v| v.as_array()) else {
+        return item;
+    };
+    let current: Vec<String> = match media_id {
+        Some(id) => crate::database::get_actors_for_media(state.database.pool(), id).await
+            .map(|list| list.into_iter().map(|a| a.name).collect())
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    let mut merged = current.clone();
+    for name in actors.iter().filter_map(|v| v.as_str()) {
+        if !merged.iter().any(|n| n == name) {
+            merged.push(name.to_string());
         }
     }
+    item.with_change("actors", serde_json::json!(current), serde_json::json!(merged))
 }
 
-/// 搜索磁力链接
-/// GET /api/scrape/magnets/:plugin_id?q=query
+/// 默认的磁力搜索网站（单个插件搜索时预先列出）
+fn default_magnet_sites() -> Vec<SiteSearchStatus> {
+    ["Kiteyuan", "Knaben", "SkrBT"].iter()
+        .map(|site| SiteSearchStatus {
+            site_name: site.to_string(),
+            plugin_id: None,
+            status: "pending".to_string(),
+            result_count: 0,
+            error: None,
+        })
+        .collect()
+}
+
+/// 搜索磁力链接（plugin_id 为 all 时同时使用所有声明 search_magnets 能力的插件）
+/// GET /api/scrape/magnets/:plugin_id?q=query&timeout=60
 pub async fn search_magnets(
     State(state): State<AppState>,
     Path(plugin_id): Path<String>,
-    Query(query): Query<SearchQuery>,
+    Query(query): Query<MagnetSearchQuery>,
 ) -> Json<MagnetSearchResponse> {
-    // 生成会话ID
-    let session_id = uuid::Uuid::new_v4().to_string();
-    info!("开始磁力搜索，会话ID: {}, 查询: {}", session_id, query.q);
+    let all_plugins = plugin_id == ALL_MAGNET_PLUGINS;
     
     // 初始化进度跟踪
-    {
-        let mut progress_map = MAGNET_SEARCH_PROGRESS.write().await;
-        progress_map.insert(session_id.clone(), MagnetSearchProgress {
-            status: "searching".to_string(),
-            message: Some("正在初始化搜索...".to_string()),
-            current_site: None,
-            sites_status: vec![
-                SiteSearchStatus {
-                    site_name: "Kiteyuan".to_string(),
-                    status: "pending".to_string(),
-                    result_count: 0,
-                    error: None,
-                },
-                SiteSearchStatus {
-                    site_name: "Knaben".to_string(),
-                    status: "pending".to_string(),
-                    result_count: 0,
-                    error: None,
-                },
-                SiteSearchStatus {
-                    site_name: "SkrBT".to_string(),
-                    status: "pending".to_string(),
-                    result_count: 0,
-                    error: None,
-                },
-            ],
-            results: vec![],
-            completed: false,
-        });
-    }
+    let session_id = MAGNET_SEARCH_PROGRESS.create(MagnetSearchProgress {
+        status: "searching".to_string(),
+        message: Some("正在初始化搜索...".to_string()),
+        current_site: None,
+        sites_status: if all_plugins { vec![] } else { default_magnet_sites() },
+        plugins_status: vec![],
+        results: vec![],
+        completed: false,
+    });
+    info!("开始磁力搜索，会话ID: {}, 插件: {}, 查询: {}", session_id, plugin_id, query.q);
     
     // 克隆需要的数据用于后台任务
     let session_id_clone = session_id.clone();
     let state_clone = state.clone();
-    let query_clone = query.q.clone();
+    let timeout = Duration::from_secs(
+        query.timeout.unwrap_or(DEFAULT_MAGNET_SEARCH_TIMEOUT_SECS).clamp(1, MAX_MAGNET_SEARCH_TIMEOUT_SECS)
+    );
     
     // 在后台任务中执行搜索
     tokio::spawn(async move {
-        let result = process_magnet_search(state_clone, plugin_id, query_clone, session_id_clone).await;
+        let result = if all_plugins {
+            process_all_magnet_search(state_clone, query.q, session_id_clone, timeout).await
+        } else {
+            process_magnet_search(state_clone, plugin_id, query.q, session_id_clone).await
+        };
         if let Err(e) = result {
             error!("后台磁力搜索任务失败: {}", e);
         }
@@ -560,6 +883,104 @@ pub async fn search_magnets(
     })
 }
 
+/// 用一个插件依次搜索所有候选关键词（全角、番号格式、繁简体），返回找到的结果数（合并去重前），
+/// 所有关键词都失败时返回第一个错误
+async fn search_plugin_magnets(
+    manager: &PluginManager,
+    session_id: &str,
+    plugin_id: &str,
+    query: &str,
+    site_plugin: Option<String>,
+    trackers: &MagnetTrackerSettings,
+) -> anyhow::Result<usize> {
+    let variants = search_query::query_variants(query);
+    let mut found = None;
+    let mut first_error = None;
+    for (index, variant) in variants.iter().enumerate() {
+        if variants.len() > 1 {
+            MAGNET_SEARCH_PROGRESS.update(session_id, |progress| {
+                progress.message = Some(format!("正在搜索 '{}' ({}/{})...", variant, index + 1, variants.len()));
+            });
+        }
+        match search_plugin_magnet_query(manager, session_id, plugin_id, variant, site_plugin.clone(), trackers).await {
+            Ok(count) => *found.get_or_insert(0) += count,
+            Err(e) => {
+                warn!("插件 {} 磁力搜索 '{}' 失败: {}", plugin_id, variant, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    match (found, first_error) {
+        (Some(count), _) => Ok(count),
+        (None, Some(e)) => Err(e),
+        (None, None) => Ok(0),
+    }
+}
+
+/// 用一个插件搜索一个关键词：网站进度和结果实时并入会话，结果标记来源插件并按 tracker 设置改写，返回找到的结果数
+///
+/// `site_plugin` 为 Some 时网站状态按插件区分（多个插件可能搜索同名网站）
+async fn search_plugin_magnet_query(
+    manager: &PluginManager,
+    session_id: &str,
+    plugin_id: &str,
+    query: &str,
+    site_plugin: Option<String>,
+    trackers: &MagnetTrackerSettings,
+) -> anyhow::Result<usize> {
+    let session_id_for_callback = session_id.to_string();
+    let source = plugin_id.to_string();
+    let trackers_for_callback = trackers.clone();
+    
+    // 创建进度回调函数
+    let progress_callback = move |site_progress: crate::plugins::protocol::SiteSearchProgress| {
+        info!("Progress callback invoked: {} - {}", site_progress.site_name, site_progress.status);
+        let found = MAGNET_SEARCH_PROGRESS.update(&session_id_for_callback, |progress| {
+            // 更新当前正在搜索的网站
+            if site_progress.status == "searching" {
+                progress.current_site = Some(site_progress.site_name.clone());
+            }
+            
+            // 网站完成时立即并入结果，前端不必等待所有网站
+            let results = prepare_magnet_results(site_progress.results, &source, &trackers_for_callback);
+            let added = magnet::merge_results(&mut progress.results, results);
+            if added > 0 {
+                progress.message = Some(format!("{} 新增 {} 个结果，共 {} 个", site_progress.site_name, added, progress.results.len()));
+            }
+            
+            // 更新或添加网站状态
+            if let Some(existing) = progress.sites_status.iter_mut()
+                .find(|s| s.site_name == site_progress.site_name && s.plugin_id == site_plugin)
+            {
+                existing.status = site_progress.status.clone();
+                existing.result_count = site_progress.result_count.unwrap_or(0);
+                existing.error = site_progress.error.clone();
+            } else {
+                // 如果网站不在列表中，添加它
+                progress.sites_status.push(SiteSearchStatus {
+                    site_name: site_progress.site_name.clone(),
+                    plugin_id: site_plugin.clone(),
+                    status: site_progress.status.clone(),
+                    result_count: site_progress.result_count.unwrap_or(0),
+                    error: site_progress.error.clone(),
+                });
+            }
+        });
+        if !found {
+            warn!("Session not found: {}", session_id_for_callback);
+        }
+    };
+    
+    let results = manager.search_magnets_with_progress(plugin_id, query, progress_callback).await?;
+    let count = results.len();
+    
+    // 不流式输出结果的插件在这里一次性并入
+    MAGNET_SEARCH_PROGRESS.update(session_id, |progress| {
+        magnet::merge_results(&mut progress.results, prepare_magnet_results(results, plugin_id, trackers));
+    });
+    Ok(count)
+}
+
 /// 处理磁力搜索（后台任务）
 async fn process_magnet_search(
     state: AppState,
@@ -570,97 +991,140 @@ async fn process_magnet_search(
     info!("开始执行磁力搜索: {}", query);
     
     // 更新状态：开始搜索
-    {
-        let mut progress_map = MAGNET_SEARCH_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
-            progress.message = Some("正在搜索磁力资源...".to_string());
-            progress.current_site = None;
-        }
-    }
+    MAGNET_SEARCH_PROGRESS.update(&session_id, |progress| {
+        progress.message = Some("正在搜索磁力资源...".to_string());
+        progress.current_site = None;
+    });
     
-    // 在锁外执行耗时的插件调用，使用带进度回调的版本
+    let trackers = load_magnet_tracker_settings(&state).await;
     let manager = state.plugin_manager.read().await;
-    
-    // 克隆 session_id 用于闭包
-    let session_id_for_callback = session_id.clone();
-    
-    // 创建进度回调函数
-    let progress_callback = move |site_progress: crate::plugins::protocol::SiteSearchProgress| {
-        // 使用 tokio::spawn 在异步上下文中更新进度
-        let session_id = session_id_for_callback.clone();
-        info!("Progress callback invoked: {} - {}", site_progress.site_name, site_progress.status);
-        tokio::spawn(async move {
-            info!("Updating progress map for session: {}", session_id);
-            let mut progress_map = MAGNET_SEARCH_PROGRESS.write().await;
-            if let Some(progress) = progress_map.get_mut(&session_id) {
-                info!("Found session, updating status for: {}", site_progress.site_name);
-                // 更新当前正在搜索的网站
-                if site_progress.status == "searching" {
-                    progress.current_site = Some(site_progress.site_name.clone());
-                }
-                
-                // 更新或添加网站状态
-                if let Some(existing) = progress.sites_status.iter_mut()
-                    .find(|s| s.site_name == site_progress.site_name) 
-                {
-                    existing.status = site_progress.status.clone();
-                    existing.result_count = site_progress.result_count.unwrap_or(0);
-                    existing.error = site_progress.error.clone();
-                    info!("Updated existing site status: {} -> {}", site_progress.site_name, site_progress.status);
-                } else {
-                    // 如果网站不在列表中，添加它
-                    progress.sites_status.push(SiteSearchStatus {
-                        site_name: site_progress.site_name.clone(),
-                        status: site_progress.status.clone(),
-                        result_count: site_progress.result_count.unwrap_or(0),
-                        error: site_progress.error.clone(),
-                    });
-                    info!("Added new site status: {} -> {}", site_progress.site_name, site_progress.status);
-                }
-            } else {
-                warn!("Session not found: {}", session_id);
-            }
-        });
-    };
-    
-    let search_result = manager.search_magnets_with_progress(&plugin_id, &query, progress_callback).await;
+    let search_result = search_plugin_magnets(&manager, &session_id, &plugin_id, &query, None, &trackers).await;
     drop(manager);
     
     match search_result {
-        Ok(results) => {
-            info!("磁力搜索成功，找到 {} 个结果", results.len());
+        Ok(count) => {
+            info!("磁力搜索成功，找到 {} 个结果", count);
             
             // 更新进度为完成
-            {
-                let mut progress_map = MAGNET_SEARCH_PROGRESS.write().await;
-                if let Some(progress) = progress_map.get_mut(&session_id) {
-                    progress.status = "completed".to_string();
-                    progress.message = Some(format!("搜索完成，找到 {} 个结果", results.len()));
-                    progress.current_site = None;
-                    progress.results = results;
-                    progress.completed = true;
-                }
-            }
+            MAGNET_SEARCH_PROGRESS.update(&session_id, |progress| {
+                progress.status = "completed".to_string();
+                progress.message = Some(format!("搜索完成，找到 {} 个结果", progress.results.len()));
+                progress.current_site = None;
+                progress.completed = true;
+            });
             Ok(())
         }
         Err(e) => {
             error!("磁力搜索失败: {}", e);
             
             // 更新进度为失败
-            {
-                let mut progress_map = MAGNET_SEARCH_PROGRESS.write().await;
-                if let Some(progress) = progress_map.get_mut(&session_id) {
-                    progress.status = "failed".to_string();
-                    progress.message = Some(format!("搜索失败: {}", e));
-                    progress.current_site = None;
-                    progress.completed = true;
-                }
-            }
+            MAGNET_SEARCH_PROGRESS.update(&session_id, |progress| {
+                progress.status = "failed".to_string();
+                progress.message = Some(format!("搜索失败: {}", e));
+                progress.current_site = None;
+                progress.completed = true;
+            });
             Err(e.to_string())
         }
     }
 }
 
+/// 同时使用所有声明 search_magnets 能力的插件搜索（后台任务）
+///
+/// 所有插件共用一个截止时间，超时的插件记为 timeout，已流式并入的结果保留
+async fn process_all_magnet_search(
+    state: AppState,
+    query: String,
+    session_id: String,
+    timeout: Duration,
+) -> Result<(), String> {
+    // 复制一份插件快照后立即释放读锁，避免长时间搜索阻塞插件重载
+    let manager = state.plugin_manager.read().await.clone();
+    let plugin_ids = manager.magnet_plugin_ids();
+    info!("开始全部插件磁力搜索: {}, 插件: {:?}", query, plugin_ids);
+    
+    if plugin_ids.is_empty() {
+        MAGNET_SEARCH_PROGRESS.update(&session_id, |progress| {
+            progress.status = "failed".to_string();
+            progress.message = Some("没有支持磁力搜索的插件".to_string());
+            progress.completed = true;
+        });
+        return Err("No plugin declares the search_magnets capability".to_string());
+    }
+    
+    MAGNET_SEARCH_PROGRESS.update(&session_id, |progress| {
+        progress.message = Some(format!("正在 {} 个插件中搜索磁力资源...", plugin_ids.len()));
+        progress.plugins_status = plugin_ids.iter()
+            .map(|plugin_id| PluginSearchStatus {
+                plugin_id: plugin_id.clone(),
+                status: "searching".to_string(),
+                result_count: 0,
+                error: None,
+            })
+            .collect();
+    });
+    
+    let trackers = load_magnet_tracker_settings(&state).await;
+    let deadline = tokio::time::Instant::now() + timeout;
+    let searches = plugin_ids.iter().map(|plugin_id| {
+        let manager = &manager;
+        let trackers = &trackers;
+        let session_id = session_id.as_str();
+        let query = query.as_str();
+        async move {
+            let search = search_plugin_magnets(manager, session_id, plugin_id, query, Some(plugin_id.clone()), trackers);
+            let (status, result_count, error) = match tokio::time::timeout_at(deadline, search).await {
+                Ok(Ok(count)) => ("completed", count, None),
+                Ok(Err(e)) => ("failed", 0, Some(e.to_string())),
+                Err(_) => ("timeout", 0, Some(format!("Timed out after {}s", timeout.as_secs()))),
+            };
+            info!("插件 {} 磁力搜索结束: {} ({} 个结果)", plugin_id, status, result_count);
+            MAGNET_SEARCH_PROGRESS.update(session_id, |progress| {
+                if let Some(plugin_status) = progress.plugins_status.iter_mut().find(|s| &s.plugin_id == plugin_id) {
+                    plugin_status.status = status.to_string();
+                    plugin_status.result_count = result_count;
+                    plugin_status.error = error;
+                }
+            });
+            status == "completed"
+        }
+    });
+    let succeeded = futures_util::future::join_all(searches).await.into_iter().filter(|ok| *ok).count();
+    
+    MAGNET_SEARCH_PROGRESS.update(&session_id, |progress| {
+        if succeeded == 0 && progress.results.is_empty() {
+            progress.status = "failed".to_string();
+            progress.message = Some("所有插件搜索失败".to_string());
+        } else {
+            progress.status = "completed".to_string();
+            progress.message = Some(format!(
+                "搜索完成，{}/{} 个插件成功，找到 {} 个结果", succeeded, progress.plugins_status.len(), progress.results.len()
+            ));
+        }
+        progress.current_site = None;
+        progress.completed = true;
+    });
+    Ok(())
+}
+
+/// 给结果标记来源插件，并按 tracker 设置改写磁力链接
+fn prepare_magnet_results(results: Vec<MagnetResult>, plugin_id: &str, trackers: &MagnetTrackerSettings) -> Vec<MagnetResult> {
+    let mut results: Vec<MagnetResult> = results.into_iter()
+        .map(|result| MagnetResult { source: Some(plugin_id.to_string()), ..result })
+        .collect();
+    magnet::apply_trackers(&mut results, trackers);
+    results
+}
+
+/// 读取 tracker 设置，读取失败时不改写磁力链接
+async fn load_magnet_tracker_settings(state: &AppState) -> MagnetTrackerSettings {
+    crate::database::get_magnet_tracker_settings(state.database.pool()).await
+        .unwrap_or_else(|e| {
+            warn!("读取 tracker 设置失败: {}", e);
+            MagnetTrackerSettings::default()
+        })
+}
+
 /// 查询磁力搜索进度
 /// GET /api/scrape/magnets/progress/:session_id
 pub async fn get_magnet_search_progress(
@@ -668,16 +1132,14 @@ pub async fn get_magnet_search_progress(
     Path(session_id): Path<String>,
 ) -> ApiResult<impl IntoResponse> {
     info!("查询磁力搜索进度，会话ID: {}", session_id);
-    let progress_map = MAGNET_SEARCH_PROGRESS.read().await;
-    
-    let progress = progress_map.get(&session_id)
+    let progress = MAGNET_SEARCH_PROGRESS.get(&session_id)
         .ok_or_else(|| {
             warn!("会话未找到: {}", session_id);
             ApiError::NotFound(format!("Session not found: {}", session_id))
         })?;
     
     info!("找到进度：{:?}", progress.status);
-    Ok(success(progress.clone()))
+    Ok(success(progress))
 }
 
 /// 自定义反序列化：支持字符串和布尔值
@@ -723,16 +1185,54 @@ pub struct ScrapeMediaRequest {
     /// - 单个对象：更新或创建单个媒体
     /// - 数组：批量创建新媒体
     pub data: Option<serde_json::Value>,
+    /// 可选：多结果刮削返回的 result_token，与 result_index 一起代替 data
+    pub result_token: Option<String>,
+    /// 可选：选择的结果在结果集中的序号（从 0 开始）
+    pub result_index: Option<usize>,
     /// 可选：是否创建新媒体（默认 false，更新现有媒体）
     #[serde(default)]
     pub create_new: bool,
+    /// 可选：只应用部分字段（include_fields / exclude_fields），仅用于更新现有媒体
+    #[serde(flatten)]
+    pub fields: crate::models::ScrapeFieldMask,
+}
+
+impl ScrapeMediaRequest {
+    /// 用户选择的刮削数据：直接提供的 data，或从缓存的结果集中按序号取出
+    fn selected_data(&self) -> Result<Option<serde_json::Value>, ApiError> {
+        let Some(token) = &self.result_token else {
+            return Ok(self.data.clone());
+        };
+        if self.data.is_some() {
+            return Err(ApiError::Validation("data and result_token cannot be used together".to_string()));
+        }
+        let index = self.result_index
+            .ok_or_else(|| ApiError::invalid_field("result_index", "result_index is required with result_token"))?;
+        let results = SCRAPE_RESULT_SETS.get(token)
+            .ok_or_else(|| ApiError::NotFound("Scrape results expired or not found, please scrape again".to_string()))?;
+        let data = results.get(index).cloned()
+            .ok_or_else(|| ApiError::invalid_field("result_index", format!("Must be less than {}", results.len())))?;
+        Ok(Some(data))
+    }
+}
+
+/// 缓存多结果刮削的结果集，返回 result_token
+fn cache_scrape_results(results: &[serde_json::Value]) -> String {
+    SCRAPE_RESULT_SETS.cleanup_expired();
+    let token = uuid::Uuid::new_v4().to_string();
+    SCRAPE_RESULT_SETS.set(token.clone(), results.to_vec());
+    token
 }
 
 /// 批量刮削请求
 #[derive(Debug, Deserialize)]
 pub struct BatchScrapeMediaRequest {
-    /// 媒体ID列表
+    /// 媒体ID列表（与 filter 二选一）
+    #[serde(default)]
     pub media_ids: Vec<String>,
+    /// 按媒体列表的筛选条件选择媒体，由后端解析出匹配的ID
+    #[serde(default)]
+    pub filter: Option<crate::api::media::MediaFilters>,
     /// 更新模式：replace（替换）或 supplement（补全）
     pub mode: String,
     /// 是否并发处理，默认false（串行）
@@ -744,6 +1244,9 @@ pub struct BatchScrapeMediaRequest {
     /// 内容类型：Scene/Movie
     #[serde(default)]
     pub content_type: Option<String>,
+    /// 试运行：只计算每个媒体将会发生的变化，不写入数据库
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 /// 批量刮削响应
@@ -767,32 +1270,56 @@ pub struct BatchScrapeResult {
 /// 返回 session_id，前端通过轮询 /api/scrape/media/progress/:session_id 获取进度
 pub async fn batch_scrape_media_unified(
     State(state): State<AppState>,
-    Json(request): Json<BatchScrapeMediaRequest>,
+    Json(mut request): Json<BatchScrapeMediaRequest>,
 ) -> Json<MediaScrapeResponse> {
     use serde_json::json;
     
-    // 生成会话ID
-    let session_id = uuid::Uuid::new_v4().to_string();
-    info!("开始批量媒体刮削，会话ID: {}, 数量: {}, 并发: {}", session_id, request.media_ids.len(), request.concurrent);
-    
-    // 初始化进度跟踪
-    {
-        let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-        progress_map.insert(session_id.clone(), MediaScrapeProgress {
-            status: "scraping".to_string(),
-            message: Some("正在初始化刮削...".to_string()),
-            current: 0,
-            total: request.media_ids.len() as i32,
-            current_item: None,
-            item_status: "pending".to_string(),
-            success_count: 0,
-            failed_count: 0,
-            completed: false,
-            concurrent: request.concurrent,
-            processing_items: vec![],
+    // 按筛选条件选择时，由后端解析出媒体ID
+    if let Some(filter) = request.filter.take() {
+        if !request.media_ids.is_empty() {
+            return Json(MediaScrapeResponse {
+                success: false,
+                session_id: String::new(),
+                message: "media_ids 和 filter 只能提供一个".to_string(),
+            });
+        }
+        match state.db_service.get_media_ids_filtered(&filter).await {
+            Ok(ids) => request.media_ids = ids,
+            Err(e) => {
+                error!("按筛选条件解析媒体失败: {}", e);
+                return Json(MediaScrapeResponse {
+                    success: false,
+                    session_id: String::new(),
+                    message: format!("按筛选条件解析媒体失败: {}", e),
+                });
+            }
+        }
+    }
+    if request.media_ids.is_empty() {
+        return Json(MediaScrapeResponse {
+            success: false,
+            session_id: String::new(),
+            message: "没有需要刮削的媒体".to_string(),
         });
     }
     
+    // 初始化进度跟踪
+    let session_id = MEDIA_SCRAPE_PROGRESS.create(MediaScrapeProgress {
+        status: "scraping".to_string(),
+        message: Some("正在初始化刮削...".to_string()),
+        current: 0,
+        total: request.media_ids.len() as i32,
+        current_item: None,
+        item_status: "pending".to_string(),
+        success_count: 0,
+        failed_count: 0,
+        completed: false,
+        concurrent: request.concurrent,
+        processing_items: vec![],
+        dry_run_results: vec![],
+    });
+    info!("开始批量媒体刮削，会话ID: {}, 数量: {}, 并发: {}", session_id, request.media_ids.len(), request.concurrent);
+    
     // 克隆需要的数据用于后台任务
     let session_id_clone = session_id.clone();
     let state_clone = state.clone();
@@ -816,6 +1343,133 @@ pub async fn batch_scrape_media_unified(
 
 
 
+/// 批量刮削预览响应
+#[derive(Debug, Serialize)]
+pub struct BatchScrapePreviewResponse {
+    /// 匹配筛选条件的媒体总数
+    pub count: i64,
+    /// 前若干条匹配的媒体
+    pub sample: Vec<MediaItemResponse>,
+}
+
+/// 批量刮削预览：返回筛选条件匹配的媒体数量和示例
+/// POST /api/scrape/media/batch/preview
+pub async fn preview_batch_scrape_media(
+    State(state): State<AppState>,
+    Json(filter): Json<crate::api::media::MediaFilters>,
+) -> ApiResult<impl IntoResponse> {
+    const PREVIEW_SAMPLE_SIZE: i32 = 20;
+    
+    let (sample, count) = state.db_service.get_media_list_filtered(1, PREVIEW_SAMPLE_SIZE, &filter).await
+        .map_err(|e| {
+            error!("Failed to preview batch scrape: {}", e);
+            ApiError::Internal("Failed to resolve filter".to_string())
+        })?;
+    
+    Ok(success(BatchScrapePreviewResponse {
+        count,
+        sample: sample.into_iter().map(MediaItemResponse::from).collect(),
+    }))
+}
+
+/// 从提供方刷新媒体请求
+#[derive(Debug, Deserialize)]
+pub struct RefreshMediaRequest {
+    /// 更新模式：replace（替换）或 supplement（补全），默认为 supplement
+    #[serde(default = "default_refresh_mode")]
+    pub mode: String,
+    /// 可选：只应用部分字段（include_fields / exclude_fields）
+    #[serde(flatten)]
+    pub fields: crate::models::ScrapeFieldMask,
+}
+
+fn default_refresh_mode() -> String {
+    "supplement".to_string()
+}
+
+impl Default for RefreshMediaRequest {
+    fn default() -> Self {
+        Self { mode: default_refresh_mode(), fields: Default::default() }
+    }
+}
+
+/// 把提供方返回的媒体详情转换为刮削数据格式，与插件刮削共用字段映射
+fn provider_media_to_scrape_data(item: &MediaItem, source: &str) -> serde_json::Value {
+    let backdrop = item.backdrop_url.as_deref().map(|b| {
+        serde_json::from_str::<Vec<String>>(b)
+            .map(serde_json::Value::from)
+            .unwrap_or_else(|_| serde_json::Value::from(b))
+    });
+    let actors: Vec<String> = item.get_cast().unwrap_or_default().into_iter().map(|p| p.name).collect();
+    let director = item.get_crew().unwrap_or_default().into_iter()
+        .find(|p| p.role.eq_ignore_ascii_case("director"))
+        .map(|p| p.name);
+    serde_json::json!({
+        "source": source,
+        "title": item.title,
+        "original_title": item.original_title,
+        "year": item.year,
+        "rating": item.rating,
+        "runtime": item.runtime,
+        "overview": item.overview,
+        "poster_url": item.poster_url,
+        "backdrop_url": backdrop,
+        "release_date": item.release_date,
+        "studio": item.studio,
+        "director": director,
+        "language": item.language,
+        "country": item.country,
+        "genres": item.get_genres().unwrap_or_default(),
+        "actors": actors,
+    })
+}
+
+/// 按外部ID从对应的提供方（TMDB 电影/剧集）重新获取详情，按 replace/supplement 模式应用
+/// POST /api/media/:id/refresh
+pub async fn refresh_media_from_provider(
+    State(state): State<AppState>,
+    Path(media_id): Path<String>,
+    body: Option<Json<RefreshMediaRequest>>,
+) -> ApiResult<impl IntoResponse> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    validate_mode(&request.mode)
+        .map_err(ApiError::Validation)?;
+    request.fields.validate()
+        .map_err(ApiError::Validation)?;
+    
+    let mut media = state.db_service.get_media_detail(&media_id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    let tmdb_id = media.get_external_ids().ok()
+        .and_then(|ids| ids.tmdb_id)
+        .ok_or_else(|| ApiError::Validation("Media has no provider ID to refresh from".to_string()))?;
+    if !state.external_client.is_tmdb_available() {
+        return Err(ApiError::ExternalService("TMDB service unavailable".to_string()));
+    }
+    
+    // Scene 对应 TMDB 剧集，其余为电影
+    let details = if media.media_type == crate::models::MediaType::Scene.to_string() {
+        state.external_client.get_tv_details(tmdb_id as u32).await
+    } else {
+        state.external_client.get_movie_details(tmdb_id as u32).await
+    }
+    .map_err(|e| ApiError::ExternalService(format!("Failed to get TMDB details: {}", e)))?;
+    
+    let data = request.fields.apply(&provider_media_to_scrape_data(&details, "tmdb"));
+    match request.mode.to_lowercase().as_str() {
+        "replace" => apply_scrape_result_to_media(&mut media, &data),
+        _ => apply_scrape_result_to_media_supplement(&mut media, &data),
+    }
+    save_scraped_media(&state, &media, &data, false).await?;
+    info!("从 TMDB 刷新媒体: media_id={}, tmdb_id={}, mode={}", media_id, tmdb_id, request.mode);
+    
+    let media_data = crate::services::cache::MediaData::from_media_item(&media);
+    if let Err(e) = state.cache_service.handle_media_save(&media_id, &media_data, "tmdb").await {
+        error!("缓存处理失败: media_id={}, scraper=tmdb, error={:?}", media_id, e);
+    }
+    
+    Ok(success(MediaItemResponse::from(media)))
+}
+
 /// 应用刮削结果到媒体（替换式更新 - 刮削数据有值时覆盖原数据）
 fn apply_scrape_result_to_media(media: &mut crate::models::MediaItem, scrape_data: &serde_json::Value) {
     // 刮削器名称：有值则覆盖
@@ -1340,12 +1994,11 @@ async fn process_batch_media_scrape(
     
     // 验证 mode 参数
     if let Err(e) = validate_mode(&request.mode) {
-        let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
+        MEDIA_SCRAPE_PROGRESS.update(&session_id, |progress| {
             progress.status = "failed".to_string();
             progress.message = Some(format!("参数错误: {}", e));
             progress.completed = true;
-        }
+        });
         return Err(e);
     }
     
@@ -1378,22 +2031,18 @@ async fn process_batch_media_scrape(
     }
     
     if media_info_list.is_empty() {
-        let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
+        MEDIA_SCRAPE_PROGRESS.update(&session_id, |progress| {
             progress.status = "completed".to_string();
             progress.message = Some("没有找到有效的媒体项目".to_string());
             progress.completed = true;
-        }
+        });
         return Ok(());
     }
     
     // 更新总数
-    {
-        let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
-            progress.total = media_info_list.len() as i32;
-        }
-    }
+    MEDIA_SCRAPE_PROGRESS.update(&session_id, |progress| {
+        progress.total = media_info_list.len() as i32;
+    });
     
     // 获取插件
     let plugin_manager = state.plugin_manager.read().await;
@@ -1401,12 +2050,11 @@ async fn process_batch_media_scrape(
     let media_scraper = match plugins.iter().find(|p| p.config.id == "media_scraper") {
         Some(p) => p,
         None => {
-            let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-            if let Some(progress) = progress_map.get_mut(&session_id) {
+            MEDIA_SCRAPE_PROGRESS.update(&session_id, |progress| {
                 progress.status = "failed".to_string();
                 progress.message = Some("media_scraper 插件未找到".to_string());
                 progress.completed = true;
-            }
+            });
             return Err("Plugin not found".to_string());
         }
     };
@@ -1469,8 +2117,7 @@ async fn process_batch_media_scrape(
                     info!("Media scrape progress: {}/{} - {} ({})", current, total, item_name, status);
                     
                     // 更新进度
-                    let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-                    if let Some(progress) = progress_map.get_mut(&session_id_for_stderr) {
+                    MEDIA_SCRAPE_PROGRESS.update(&session_id_for_stderr, |progress| {
                         progress.current = current;
                         progress.total = total;
                         progress.current_item = Some(item_name.clone());
@@ -1511,7 +2158,7 @@ async fn process_batch_media_scrape(
                         } else if status == "completed" {
                             progress.message = Some(format!("已完成 {}/{}", current, total));
                         }
-                    }
+                    });
                 }
             }
         }
@@ -1551,12 +2198,11 @@ async fn process_batch_media_scrape(
     let status = child.wait().await.map_err(|e| e.to_string())?;
     
     if !status.success() {
-        let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-        if let Some(progress) = progress_map.get_mut(&session_id) {
+        MEDIA_SCRAPE_PROGRESS.update(&session_id, |progress| {
             progress.status = "failed".to_string();
             progress.message = Some("插件执行失败".to_string());
             progress.completed = true;
-        }
+        });
         return Err("Plugin execution failed".to_string());
     }
     
@@ -1570,6 +2216,7 @@ async fn process_batch_media_scrape(
             
             let mut success_count = 0;
             let mut failed_count = 0;
+            let mut dry_run_results = Vec::new();
             
             for scrape_result in scrape_results {
                 let media_id = match scrape_result.get("media_id").and_then(|v| v.as_str()) {
@@ -1591,26 +2238,36 @@ async fn process_batch_media_scrape(
                         continue;
                     }
                 };
+                if !check_scrape_data("media_scraper", scrape_data).await.is_valid() {
+                    failed_count += 1;
+                    continue;
+                }
+                
+                // 试运行：只记录变化
+                if request.dry_run {
+                    match state.db_service.get_media_detail(media_id).await {
+                        Ok(Some(media)) => {
+                            dry_run_results.push(preview_scrape_update(&state, media, scrape_data, is_replace_mode).await);
+                            success_count += 1;
+                        }
+                        _ => failed_count += 1,
+                    }
+                    continue;
+                }
                 
                 // 获取媒体并更新
                 match state.db_service.get_media_detail(media_id).await {
                     Ok(Some(mut media)) => {
+                        let query = media.code.clone().unwrap_or_else(|| media.title.clone());
                         if is_replace_mode {
                             apply_scrape_result_to_media(&mut media, scrape_data);
                         } else {
                             apply_scrape_result_to_media_supplement(&mut media, scrape_data);
                         }
                         
-                        match state.db_service.update_media(media).await {
+                        match save_scraped_media(&state, &media, scrape_data, false).await {
                             Ok(_) => {
-                                // 同步演员
-                                if let Some(actors) = scrape_data.get("actors").and_then(|v| v.as_array()) {
-                                    let actor_names: Vec<String> = actors.iter()
-                                        .filter_map(|v| v.as_str())
-                                        .map(String::from)
-                                        .collect();
-                                    sync_actors_to_db(&state, &actor_names, media_id).await;
-                                }
+                                archive_scrape_source(&state, media_id, Some(&query), &scrape_result).await;
                                 success_count += 1;
                             }
                             Err(_) => {
@@ -1625,22 +2282,25 @@ async fn process_batch_media_scrape(
             }
             
             // 更新最终进度
-            let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-            if let Some(progress) = progress_map.get_mut(&session_id) {
+            MEDIA_SCRAPE_PROGRESS.update(&session_id, |progress| {
                 progress.status = "completed".to_string();
-                progress.message = Some(format!("刮削完成: {} 成功, {} 失败", success_count, failed_count));
+                progress.message = Some(if request.dry_run {
+                    format!("试运行完成（未写入）: {} 成功, {} 失败", success_count, failed_count)
+                } else {
+                    format!("刮削完成: {} 成功, {} 失败", success_count, failed_count)
+                });
+                progress.dry_run_results = dry_run_results;
                 progress.success_count = success_count;
                 progress.failed_count = failed_count;
                 progress.completed = true;
-            }
+            });
         } else {
             let error_msg = response.get("error").and_then(|v| v.as_str()).unwrap_or("Unknown error");
-            let mut progress_map = MEDIA_SCRAPE_PROGRESS.write().await;
-            if let Some(progress) = progress_map.get_mut(&session_id) {
+            MEDIA_SCRAPE_PROGRESS.update(&session_id, |progress| {
                 progress.status = "failed".to_string();
                 progress.message = Some(format!("刮削失败: {}", error_msg));
                 progress.completed = true;
-            }
+            });
         }
     }
     
@@ -1654,16 +2314,55 @@ pub async fn get_scrape_progress(
     Path(session_id): Path<String>,
 ) -> ApiResult<impl IntoResponse> {
     info!("查询刮削进度，会话ID: {}", session_id);
-    let progress_map = MEDIA_SCRAPE_PROGRESS.read().await;
-    
-    let progress = progress_map.get(&session_id)
+    if let Some(progress) = MEDIA_SCRAPE_PROGRESS.get(&session_id) {
+        info!("找到进度：{:?}", progress.status);
+        return Ok(success(serde_json::to_value(progress).unwrap_or_default()));
+    }
+    let progress = ACTOR_SCRAPE_PROGRESS.get(&session_id)
         .ok_or_else(|| {
             warn!("会话未找到: {}", session_id);
             ApiError::NotFound(format!("Session not found: {}", session_id))
         })?;
-    
-    info!("找到进度：{:?}", progress.status);
-    Ok(success(progress.clone()))
+
+    info!("找到演员刮削进度：{:?}", progress.status);
+    Ok(success(serde_json::to_value(progress).unwrap_or_default()))
+}
+
+/// 把进度订阅转换为 SSE 响应：每次进度变化推送一次，任务结束后关闭连接
+fn progress_events<T>(registry: &ProgressRegistry<T>, session_id: &str) -> ApiResult<impl IntoResponse>
+where
+    T: SessionProgress + Clone + Serialize + Send + Sync + 'static,
+{
+    let receiver = registry.subscribe(session_id)
+        .ok_or_else(|| ApiError::NotFound(format!("Session not found: {}", session_id)))?;
+    let events = progress_stream(receiver).map(|progress| Event::default().json_data(progress));
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// 订阅刮削进度（SSE，媒体和演员刮削共用）
+/// GET /api/scrape/progress/:session_id/events
+pub async fn scrape_progress_events(Path(session_id): Path<String>) -> ApiResult<axum::response::Response> {
+    if MEDIA_SCRAPE_PROGRESS.get(&session_id).is_some() {
+        return progress_events(&MEDIA_SCRAPE_PROGRESS, &session_id).map(IntoResponse::into_response);
+    }
+    progress_events(&ACTOR_SCRAPE_PROGRESS, &session_id).map(IntoResponse::into_response)
+}
+
+/// 订阅磁力搜索进度（SSE）
+/// GET /api/scrape/magnets/progress/:session_id/events
+pub async fn magnet_search_progress_events(Path(session_id): Path<String>) -> ApiResult<impl IntoResponse> {
+    progress_events(&MAGNET_SEARCH_PROGRESS, &session_id)
+}
+
+/// 删除刮削进度（前端不再需要时主动释放，否则结束后 30 分钟自动清理）
+/// DELETE /api/scrape/progress/:session_id
+pub async fn delete_scrape_progress(
+    Path(session_id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    if !MEDIA_SCRAPE_PROGRESS.remove(&session_id) && !ACTOR_SCRAPE_PROGRESS.remove(&session_id) {
+        return Err(ApiError::NotFound(format!("Session not found: {}", session_id)));
+    }
+    Ok(success_message("Session deleted"))
 }
 
 /// 多结果刮削请求
@@ -1682,6 +2381,9 @@ pub struct ScrapeMultipleRequest {
 pub struct ScrapeMultipleResponse {
     pub success: bool,
     pub mode: String,  // "single" 或 "multiple"
+    /// 多结果时的结果集 token（30 分钟内有效），选择结果时回传 token 和序号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_token: Option<String>,
     pub results: Vec<serde_json::Value>,
     pub message: Option<String>,
 }
@@ -1695,6 +2397,9 @@ pub struct BatchImportRequest {
     pub media_id: Option<String>,
     /// 更新模式：replace（替换）或 supplement（补全），默认为 replace
     pub mode: Option<String>,
+    /// 试运行：只返回将会发生的变化，不写入数据库
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 /// 批量导入响应
@@ -1705,6 +2410,9 @@ pub struct BatchImportResponse {
     pub failed_count: usize,
     pub results: Vec<ImportResult>,
     pub message: String,
+    /// 试运行结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<Vec<DryRunItem>>,
 }
 
 /// 单个导入结果
@@ -1731,7 +2439,7 @@ pub async fn scrape_media_multiple(
         let plugins = manager.list_plugins();
         let media_scraper = plugins.iter()
             .find(|p| p.config.id == "media_scraper")
-            .ok_or_else(|| ApiError::NotFound("media_scraper 插件未找到".to_string()))?;
+            .ok_or_else(|| ApiError::coded(ErrorCode::PluginNotFound, "media_scraper 插件未找到"))?;
         
         (media_scraper.executable_path.clone(), media_scraper.path.clone())
     };
@@ -1801,41 +2509,19 @@ pub async fn scrape_media_multiple(
         .map_err(|e| ApiError::Internal(format!("等待插件结束失败: {}", e)))?;
     
     if !status.success() {
-        return Err(ApiError::ExternalService("插件执行失败".to_string()));
+        return Err(ApiError::coded(ErrorCode::PluginFailed, "插件执行失败"));
     }
     
     // 解析响应
     let response = response_json
-        .ok_or_else(|| ApiError::ExternalService("未收到插件响应".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::PluginInvalidResponse, "未收到插件响应"))?;
     
     let success = response.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
     
     if !success {
-        // 处理错误信息（可能是字符串或对象）
-        let error_msg = if let Some(error_obj) = response.get("error") {
-            if let Some(error_str) = error_obj.as_str() {
-                // 简单字符串错误
-                error_str.to_string()
-            } else if let Some(error_dict) = error_obj.as_object() {
-                // 结构化错误对象
-                if let Some(message) = error_dict.get("message") {
-                    if let Some(zh_msg) = message.get("zh").and_then(|v| v.as_str()) {
-                        zh_msg.to_string()
-                    } else if let Some(msg_str) = message.as_str() {
-                        msg_str.to_string()
-                    } else {
-                        "未知错误".to_string()
-                    }
-                } else {
-                    "未知错误".to_string()
-                }
-            } else {
-                "未知错误".to_string()
-            }
-        } else {
-            "未知错误".to_string()
-        };
-        return Err(ApiError::ExternalService(format!("刮削失败: {}", error_msg)));
+        // 处理错误信息（可能是字符串或对象，保留多语言消息）
+        let plugin_error = PluginError::from_value(response.get("error"));
+        return Err(ApiError::from(plugin_error).prefixed("刮削失败: ", "Scrape failed: "));
     }
     
     // 检查是否是多结果格式（mode 字段在顶层）
@@ -1854,7 +2540,8 @@ pub async fn scrape_media_multiple(
             return Ok(crate::api::response::success(ScrapeMultipleResponse {
                 success: true,
                 mode: mode.to_string(),
-                results: results.clone(),
+                result_token: Some(cache_scrape_results(&results)),
+                results,
                 message: Some(format!("找到 {} 个结果", total_count)),
             }));
         }
@@ -1862,11 +2549,12 @@ pub async fn scrape_media_multiple(
     
     // 单结果格式（兼容旧格式）
     let data = response.get("data")
-        .ok_or_else(|| ApiError::ExternalService("响应中缺少 data 字段".to_string()))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::PluginInvalidResponse, "响应中缺少 data 字段"))?;
     
     Ok(crate::api::response::success(ScrapeMultipleResponse {
         success: true,
         mode: "single".to_string(),
+        result_token: None,
         results: vec![data.clone()],
         message: Some("找到 1 个结果".to_string()),
     }))
@@ -1880,6 +2568,10 @@ pub async fn batch_import_media(
 ) -> ApiResult<impl IntoResponse> {
     info!("开始批量导入，数量: {}", request.selected_results.len());
     
+    if request.dry_run {
+        return Ok(success(preview_batch_import(&request, &state).await?));
+    }
+    
     let mut imported_count = 0;
     let mut failed_count = 0;
     let mut results = Vec::new();
@@ -1925,33 +2617,53 @@ pub async fn batch_import_media(
             }
         }
     } else {
-        // 批量创建新媒体模式
+        // 批量创建新媒体模式：先逐个构造，再在一个事务中批量写入
+        let mut items = Vec::new();
         for scrape_result in &request.selected_results {
             let title = scrape_result.get("title")
                 .and_then(|v| v.as_str())
                 .unwrap_or("未知标题")
                 .to_string();
             
-            match create_media_from_scrape_result(scrape_result, &state).await {
-                Ok(media_id) => {
-                    imported_count += 1;
+            match new_media_from_scrape_result(scrape_result) {
+                Ok(media) => items.push((media, scrape_result)),
+                Err(e) => {
+                    failed_count += 1;
                     results.push(ImportResult {
                         title: title.clone(),
+                        success: false,
+                        media_id: None,
+                        error: Some(e.to_string()),
+                    });
+                    error!("导入失败: {} - {}", title, e);
+                }
+            }
+        }
+        
+        let saved = save_new_scraped_media_batch(&state, &items).await;
+        if let Err(e) = &saved {
+            error!("批量导入写入失败: {}", e);
+        }
+        for (media, _) in &items {
+            match &saved {
+                Ok(()) => {
+                    imported_count += 1;
+                    results.push(ImportResult {
+                        title: media.title.clone(),
                         success: true,
-                        media_id: Some(media_id.clone()),
+                        media_id: Some(media.id.clone()),
                         error: None,
                     });
-                    info!("成功导入: {} (ID: {})", title, media_id);
+                    info!("成功导入: {} (ID: {})", media.title, media.id);
                 }
                 Err(e) => {
                     failed_count += 1;
                     results.push(ImportResult {
-                        title: title.clone(),
+                        title: media.title.clone(),
                         success: false,
                         media_id: None,
                         error: Some(e.to_string()),
                     });
-                    error!("导入失败: {} - {}", title, e);
                 }
             }
         }
@@ -1969,14 +2681,66 @@ pub async fn batch_import_media(
         failed_count,
         results,
         message,
+        preview: None,
     }))
 }
 
-/// 从刮削结果创建媒体记录
-async fn create_media_from_scrape_result(
-    scrape_result: &serde_json::Value,
+/// 试运行批量导入：计算每个结果将会创建或修改的内容
+async fn preview_batch_import(
+    request: &BatchImportRequest,
     state: &AppState,
-) -> Result<String, ApiError> {
+) -> Result<BatchImportResponse, ApiError> {
+    use crate::models::MediaType;
+    
+    let mut preview = Vec::new();
+    
+    if let Some(media_id) = &request.media_id {
+        if request.selected_results.len() != 1 {
+            return Err(ApiError::Validation(
+                "当提供 media_id 时，只能选择一个结果".to_string()
+            ));
+        }
+        let mode = request.mode.as_deref().unwrap_or("replace");
+        let media = state.db_service.get_media_detail(media_id).await
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, format!("媒体不存在: {}", media_id)))?;
+        let replace = mode.to_lowercase() != "supplement";
+        preview.push(preview_scrape_update(state, media, &request.selected_results[0], replace).await);
+    } else {
+        for scrape_result in &request.selected_results {
+            let title = scrape_result.get("title")
+                .and_then(|v| v.as_str())
+                .unwrap_or("未知标题")
+                .to_string();
+            let media_type = scrape_result.get("media_type")
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.parse::<MediaType>().ok())
+                .unwrap_or(MediaType::Movie);
+            
+            let item = match MediaItem::new(title.clone(), media_type) {
+                Ok(mut media) => {
+                    apply_scrape_result_to_media(&mut media, scrape_result);
+                    with_actor_preview(state, DryRunItem::create(&media), None, scrape_result).await
+                }
+                Err(e) => DryRunItem::error(None, title, format!("Validation error: {:?}", e)),
+            };
+            preview.push(item);
+        }
+    }
+    
+    let failed_count = preview.iter().filter(|p| p.action == "error").count();
+    Ok(BatchImportResponse {
+        success: failed_count == 0,
+        imported_count: 0,
+        failed_count,
+        results: Vec::new(),
+        message: format!("试运行：{} 个结果将被导入（未写入）", preview.len() - failed_count),
+        preview: Some(preview),
+    })
+}
+
+/// 从刮削结果构造新的媒体（未写入数据库）
+fn new_media_from_scrape_result(scrape_result: &serde_json::Value) -> Result<MediaItem, ApiError> {
     use crate::models::MediaType;
     
     // 提取标题和媒体类型
@@ -1993,37 +2757,26 @@ async fn create_media_from_scrape_result(
         .unwrap_or(MediaType::Movie);
     
     // 直接创建媒体记录（不检查重复，因为用户可能想创建多个相同标题的媒体）
-    let media = MediaItem::new(title, media_type)
+    let mut media = MediaItem::new(title, media_type)
         .map_err(|e| ApiError::Validation(format!("Validation error: {:?}", e)))?;
     
-    // 插入到数据库
-    state.database.repository().insert_media(&media).await
-        .map_err(|e| ApiError::Internal(e.to_string()))?;
-    
-    let media_id = media.id.clone();
-    
-    // 获取刚创建的媒体
-    let mut media = state.db_service.get_media_detail(&media_id).await
-        .map_err(|e| ApiError::Internal(e.to_string()))?
-        .ok_or_else(|| ApiError::Internal("Failed to retrieve created media".to_string()))?;
-    
     // 应用刮削结果到媒体（使用替换模式）
     apply_scrape_result_to_media(&mut media, scrape_result);
+    Ok(media)
+}
+
+/// 从刮削结果创建媒体记录
+async fn create_media_from_scrape_result(
+    scrape_result: &serde_json::Value,
+    state: &AppState,
+) -> Result<String, ApiError> {
+    let media = new_media_from_scrape_result(scrape_result)?;
     
-    // 更新媒体记录
-    state.db_service.update_media(media).await
+    // 在一个事务中插入媒体、同步演员和翻译
+    save_scraped_media(state, &media, scrape_result, true).await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
     
-    // 同步演员到数据库
-    if let Some(actors) = scrape_result.get("actors").and_then(|v| v.as_array()) {
-        let actor_names: Vec<String> = actors.iter()
-            .filter_map(|v| v.as_str())
-            .map(String::from)
-            .collect();
-        sync_actors_to_db(state, &actor_names, &media_id).await;
-    }
-    
-    Ok(media_id)
+    Ok(media.id)
 }
 
 /// 从刮削结果更新现有媒体记录
@@ -2036,7 +2789,7 @@ async fn update_media_from_scrape_result(
     // 获取现有媒体
     let mut media = state.db_service.get_media_detail(media_id).await
         .map_err(|e| ApiError::Internal(e.to_string()))?
-        .ok_or_else(|| ApiError::NotFound(format!("媒体不存在: {}", media_id)))?;
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, format!("媒体不存在: {}", media_id)))?;
     
     // 根据模式应用刮削结果
     match mode.to_lowercase().as_str() {
@@ -2045,18 +2798,53 @@ async fn update_media_from_scrape_result(
         _ => apply_scrape_result_to_media(&mut media, scrape_result), // 默认使用替换模式
     }
     
-    // 更新媒体记录
-    state.db_service.update_media(media).await
+    // 在一个事务中更新媒体、同步演员和翻译
+    save_scraped_media(state, &media, scrape_result, false).await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
     
-    // 同步演员到数据库
-    if let Some(actors) = scrape_result.get("actors").and_then(|v| v.as_array()) {
-        let actor_names: Vec<String> = actors.iter()
-            .filter_map(|v| v.as_str())
-            .map(String::from)
-            .collect();
-        sync_actors_to_db(state, &actor_names, media_id).await;
-    }
-    
     Ok(())
 }
+
+/// 获取媒体的刮削来源存档列表
+/// GET /api/media/:id/scrape-sources
+pub async fn list_scrape_sources(
+    State(state): State<AppState>,
+    Path(media_id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    let sources = crate::database::list_scrape_sources(state.database.pool(), &media_id).await?;
+    Ok(success(sources))
+}
+
+/// 获取刮削来源存档的原始内容（解压后按原始类型返回）
+/// GET /api/scrape-sources/:id
+pub async fn get_scrape_source(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+) -> ApiResult<impl IntoResponse> {
+    let (source, payload) = crate::database::get_scrape_source_payload(state.database.pool(), id).await?
+        .ok_or_else(|| ApiError::NotFound("Scrape source not found".to_string()))?;
+    let content = crate::services::scrape_archive::decompress(&payload)
+        .map_err(|e| ApiError::Internal(format!("Failed to decompress scrape source: {}", e)))?;
+    let content_type = format!("{}; charset=utf-8", source.content_type);
+    Ok(([(axum::http::header::CONTENT_TYPE, content_type)], content))
+}
+
+/// 获取刮削来源存档设置
+/// GET /api/settings/scrape-archive
+pub async fn get_scrape_archive_settings(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let settings = crate::database::get_scrape_archive_settings(state.database.pool()).await?;
+    Ok(success(settings))
+}
+
+/// 保存刮削来源存档设置
+/// PUT /api/settings/scrape-archive
+pub async fn update_scrape_archive_settings(
+    State(state): State<AppState>,
+    Json(settings): Json<crate::models::ScrapeArchiveSettings>,
+) -> ApiResult<impl IntoResponse> {
+    settings.validate().map_err(ApiError::Validation)?;
+    crate::database::save_scrape_archive_settings(state.database.pool(), &settings).await?;
+    Ok(success(settings))
+}
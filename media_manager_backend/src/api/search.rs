@@ -1,14 +1,20 @@
 use axum::{
     extract::{Query, State},
+    http::HeaderMap,
     response::{Json, IntoResponse},
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::time::Instant;
 
 use super::AppState;
+use super::history::history_user;
+use crate::api::error::{ApiError, ApiResult};
 use crate::api::response::success;
-use crate::models::{MediaItem, MediaType, MediaItemResponse};
+use crate::database;
+use crate::models::{
+    merge_suggestions, rank_trending, MediaItem, MediaType, MediaItemResponse, SearchSuggestion, SortOption, SortOrder,
+    DEFAULT_SUGGESTION_LIMIT, DEFAULT_TRENDING_WINDOW_DAYS, MAX_SUGGESTION_LIMIT, MAX_TRENDING_WINDOW_DAYS,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct SearchParams {
@@ -47,13 +53,68 @@ pub struct AdvancedSearchRequest {
     pub actor_id: Option<String>,
     pub studio: Option<String>,
     pub series: Option<String>,
+    /// 本地结果的排序方式（见 SortOption::PARAMS），默认按评分
+    pub sort_by: Option<String>,
+    pub sort_order: Option<String>,  // asc, desc
+    /// 随机排序的种子，翻页时保持不变
+    pub seed: Option<u32>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct SearchSuggestion {
-    pub text: String,
-    pub type_: String, // "title", "genre", "person", "year"
-    pub count: i32,
+impl AdvancedSearchRequest {
+    /// 转换为本地搜索过滤器（不含分页）
+    pub fn search_filters(&self, sort_by: SortOption) -> crate::models::SearchFilters {
+        let year_range = match (self.year_from, self.year_to) {
+            (Some(from), Some(to)) => Some((from, to)),
+            (Some(from), None) => Some((from, 2100)),
+            (None, Some(to)) => Some((1800, to)),
+            (None, None) => None,
+        };
+        
+        let rating_range = match (self.rating_min, self.rating_max) {
+            (Some(min), Some(max)) => Some((min, max)),
+            (Some(min), None) => Some((min, 10.0)),
+            (None, Some(max)) => Some((0.0, max)),
+            (None, None) => None,
+        };
+        
+        crate::models::SearchFilters {
+            query: self.query.clone(),
+            media_type: self.media_type.clone(),
+            genres: self.genre.clone().map(|g| vec![g]).unwrap_or_default(),
+            year_range,
+            rating_range,
+            watch_status: None,
+            actor_id: self.actor_id.clone(),
+            studio: self.studio.clone(),
+            series: self.series.clone(),
+            sort_by,
+            sort_order: match self.sort_order.as_deref() {
+                Some(order) if order.eq_ignore_ascii_case("asc") => SortOrder::Ascending,
+                _ => SortOrder::Descending,
+            },
+            limit: None,
+            offset: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchSuggestionsParams {
+    pub q: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TrendingSearchesParams {
+    /// 统计最近多少天的搜索
+    pub days: Option<u32>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClearSearchHistoryParams {
+    /// 只删除该关键词，未提供时清空全部
+    pub q: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -65,6 +126,7 @@ pub struct SearchSuggestionsResponse {
 pub async fn search_media(
     Query(params): Query<SearchParams>,
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let start_time = Instant::now();
     let query = params.q.unwrap_or_default();
@@ -83,7 +145,7 @@ pub async fn search_media(
     }
     
     // 记录搜索历史
-    record_search_history(state.database.pool(), &query).await;
+    record_search_history(state.database.pool(), &history_user(&headers), &query).await;
     
     let mut all_results = Vec::new();
     
@@ -136,8 +198,13 @@ pub async fn search_media(
 
 pub async fn advanced_search(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(request): Json<AdvancedSearchRequest>,
-) -> impl IntoResponse {
+) -> ApiResult<impl IntoResponse> {
+    let sort_by = match request.sort_by.as_deref() {
+        Some(sort_by) => sort_by.parse::<SortOption>().map_err(ApiError::BadRequest)?.with_seed(request.seed),
+        None => SortOption::Rating,
+    };
     let start_time = Instant::now();
     let page = request.page.unwrap_or(1);
     let source = request.source.clone().unwrap_or_else(|| "all".to_string());
@@ -145,14 +212,14 @@ pub async fn advanced_search(
     
     // 记录搜索历史
     if !query.is_empty() {
-        record_search_history(state.database.pool(), &query).await;
+        record_search_history(state.database.pool(), &history_user(&headers), &query).await;
     }
     
     let mut all_results = Vec::new();
     
     // 本地高级搜索
     if source == "local" || source == "all" {
-        match advanced_search_local(&state, &request).await {
+        match advanced_search_local(&state, &request, sort_by).await {
             Ok(local_results) => {
                 all_results.extend(local_results);
             }
@@ -184,7 +251,10 @@ pub async fn advanced_search(
         }
     }
     
-    all_results = deduplicate_results(all_results);
+    // 只搜本地时结果没有重复，保留 sort_by 指定的顺序
+    if source != "local" {
+        all_results = deduplicate_results(all_results);
+    }
     
     // 应用分页
     let limit = request.limit.unwrap_or(20) as usize;
@@ -196,103 +266,76 @@ pub async fn advanced_search(
         .map(MediaItemResponse::from)
         .collect();
     
-    success(SearchResponse {
+    Ok(success(SearchResponse {
         results: paginated_results,
         total,
         page,
         source,
         took_ms: start_time.elapsed().as_millis() as u64,
         query,
-    })
+    }))
 }
 
-/// 获取搜索建议
+/// 获取搜索建议：当前用户搜索过的关键词（按次数和时间排序）在前，
+/// 其后是媒体库中以关键词开头的番号、标题和演员。关键词为空时只返回搜索历史
 pub async fn get_search_suggestions(
-    Query(params): Query<HashMap<String, String>>,
-    State(_state): State<AppState>,
-) -> impl IntoResponse {
-    let query = params.get("q").cloned().unwrap_or_default();
-    
-    if query.len() < 2 {
-        return success(SearchSuggestionsResponse {
-            suggestions: Vec::new(),
-            query,
-        });
+    Query(params): Query<SearchSuggestionsParams>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<impl IntoResponse> {
+    let pool = state.database.pool();
+    let query = params.q.unwrap_or_default().trim().to_string();
+    let limit = params.limit.unwrap_or(DEFAULT_SUGGESTION_LIMIT).clamp(1, MAX_SUGGESTION_LIMIT);
+
+    let history = database::list_search_history_stats(pool, &history_user(&headers), &query).await?;
+    let mut library = Vec::new();
+    if !query.is_empty() {
+        library.extend(database::suggest_codes(pool, &query, limit as i64).await?);
+        library.extend(database::suggest_titles(pool, &query, limit as i64).await?);
+        library.extend(database::suggest_actors(pool, &query, limit as i64).await?);
     }
-    
-    // TODO: 实现搜索建议逻辑
-    // 这里可以从数据库中获取匹配的标题、类型、演员等
-    let suggestions = vec![
-        SearchSuggestion {
-            text: format!("{} (Movie)", query),
-            type_: "title".to_string(),
-            count: 5,
-        },
-        SearchSuggestion {
-            text: format!("{} (TV Show)", query),
-            type_: "title".to_string(),
-            count: 3,
-        },
-    ];
-    
-    success(SearchSuggestionsResponse {
-        suggestions,
+
+    Ok(success(SearchSuggestionsResponse {
+        suggestions: merge_suggestions(history, library, limit),
         query,
-    })
+    }))
+}
+
+/// 清空当前用户的搜索历史（?q= 时只删除该关键词）
+pub async fn clear_search_history(
+    Query(params): Query<ClearSearchHistoryParams>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<impl IntoResponse> {
+    let query = params.q.as_deref().map(str::trim).filter(|q| !q.is_empty());
+    let removed = database::clear_user_search_history(state.database.pool(), &history_user(&headers), query).await?;
+    Ok(success(serde_json::json!({ "removed": removed })))
 }
 
-/// 获取热门搜索词
+/// 获取热门搜索词：统计最近 days 天（默认 30）所有用户的搜索历史，越近的搜索权重越高
 pub async fn get_trending_searches(
+    Query(params): Query<TrendingSearchesParams>,
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    let pool = state.database.pool();
-    
-    // 从搜索历史中获取最近30天内最热门的搜索词
-    let trending: Vec<String> = sqlx::query_scalar(
-        r#"
-        SELECT query
-        FROM search_history
-        WHERE searched_at >= datetime('now', '-30 days')
-        GROUP BY query COLLATE NOCASE
-        ORDER BY COUNT(*) DESC, MAX(searched_at) DESC
-        LIMIT 10
-        "#
-    )
-    .fetch_all(pool)
-    .await
-    .unwrap_or_default();
-    
-    // 如果没有搜索历史，返回默认热门词
-    if trending.is_empty() {
-        return success(vec![
-            "Marvel".to_string(),
-            "Star Wars".to_string(),
-            "Netflix".to_string(),
-            "Action".to_string(),
-            "Comedy".to_string(),
-        ]);
+) -> ApiResult<impl IntoResponse> {
+    let days = params.days.unwrap_or(DEFAULT_TRENDING_WINDOW_DAYS);
+    if days == 0 || days > MAX_TRENDING_WINDOW_DAYS {
+        return Err(ApiError::Validation(format!("days must be between 1 and {}", MAX_TRENDING_WINDOW_DAYS)));
     }
-    
-    success(trending)
+    let limit = params.limit.unwrap_or(DEFAULT_SUGGESTION_LIMIT).clamp(1, MAX_SUGGESTION_LIMIT);
+
+    let counts = database::list_search_day_counts(state.database.pool(), days).await?;
+    Ok(success(rank_trending(counts, days, limit)))
 }
 
 /// 记录搜索历史
-async fn record_search_history(pool: &sqlx::SqlitePool, query: &str) {
+async fn record_search_history(pool: &sqlx::SqlitePool, user_key: &str, query: &str) {
     if query.trim().is_empty() {
         return;
     }
     
-    let id = uuid::Uuid::new_v4().to_string();
-    let _ = sqlx::query(
-        r#"
-        INSERT INTO search_history (id, query, result_count, searched_at)
-        VALUES (?, ?, 0, datetime('now'))
-        "#
-    )
-    .bind(&id)
-    .bind(query.trim())
-    .execute(pool)
-    .await;
+    if let Err(e) = database::record_search_query(pool, user_key, query.trim()).await {
+        tracing::warn!("Failed to record search history: {}", e);
+    }
 }
 
 async fn search_local_media(
@@ -352,39 +395,11 @@ async fn search_tmdb_media(
 async fn advanced_search_local(
     state: &AppState,
     request: &AdvancedSearchRequest,
+    sort_by: SortOption,
 ) -> Result<Vec<MediaItem>, anyhow::Error> {
-    // 构建搜索过滤器
-    let year_range = match (request.year_from, request.year_to) {
-        (Some(from), Some(to)) => Some((from, to)),
-        (Some(from), None) => Some((from, 2100)),
-        (None, Some(to)) => Some((1800, to)),
-        (None, None) => None,
-    };
-    
-    let rating_range = match (request.rating_min, request.rating_max) {
-        (Some(min), Some(max)) => Some((min, max)),
-        (Some(min), None) => Some((min, 10.0)),
-        (None, Some(max)) => Some((0.0, max)),
-        (None, None) => None,
-    };
-    
-    let genres = request.genre.clone().map(|g| vec![g]).unwrap_or_default();
-    
-    let filters = crate::models::SearchFilters {
-        query: request.query.clone(),
-        media_type: request.media_type.clone(),
-        genres,
-        year_range,
-        rating_range,
-        watch_status: None,
-        actor_id: request.actor_id.clone(),
-        studio: request.studio.clone(),
-        series: request.series.clone(),
-        sort_by: crate::models::SortOption::Rating,
-        sort_order: crate::models::SortOrder::Descending,
-        limit: Some(50),
-        offset: Some(((request.page.unwrap_or(1) - 1) * 20) as i32),
-    };
+    let mut filters = request.search_filters(sort_by);
+    filters.limit = Some(50);
+    filters.offset = Some(((request.page.unwrap_or(1) - 1) * 20) as i32);
     
     // 使用数据库服务进行高级搜索
     state.db_service.search_with_filters(&filters).await
@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::database;
+use crate::models::StreamingSettings;
+use crate::services::stream_sessions;
+use super::AppState;
+use super::error::{ApiError, ApiResult};
+use super::response::success;
+
+// ============ Stream Sessions ============
+
+#[derive(Debug, Deserialize)]
+pub struct ListSessionsParams {
+    /// 只返回该用户的会话
+    pub user: Option<String>,
+}
+
+/// 正在进行的播放会话
+/// GET /api/sessions
+pub async fn list_sessions_handler(
+    Query(params): Query<ListSessionsParams>,
+) -> ApiResult<impl IntoResponse> {
+    let user = params.user.as_deref().map(str::trim).filter(|u| !u.is_empty());
+    Ok(success(stream_sessions::list(user)))
+}
+
+/// 终止播放会话（断开所有连接，转码时结束 FFmpeg 进程）
+/// DELETE /api/sessions/:id
+pub async fn terminate_session_handler(
+    Path(id): Path<String>,
+) -> ApiResult<impl IntoResponse> {
+    if !stream_sessions::terminate(&id) {
+        return Err(ApiError::NotFound(format!("Session {} not found", id)));
+    }
+    Ok(success(serde_json::json!({ "terminated": id })))
+}
+
+/// 获取播放会话设置
+/// GET /api/settings/streaming
+pub async fn get_streaming_settings_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let settings = database::get_streaming_settings(state.database.pool()).await?;
+    Ok(success(settings))
+}
+
+/// 保存播放会话设置（立即生效，已有的会话不受影响）
+/// PUT /api/settings/streaming
+pub async fn update_streaming_settings_handler(
+    State(state): State<AppState>,
+    Json(settings): Json<StreamingSettings>,
+) -> ApiResult<impl IntoResponse> {
+    settings.validate().map_err(ApiError::Validation)?;
+    database::save_streaming_settings(state.database.pool(), &settings).await?;
+    stream_sessions::apply_settings(&settings);
+    Ok(success(settings))
+}
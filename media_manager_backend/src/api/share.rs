@@ -0,0 +1,177 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{Duration, Utc};
+
+use crate::database::{self, DatabaseRepository};
+use crate::models::{
+    CreateShareRequest, CreateShareResponse, PublicMediaDetail, ShareLink,
+    DEFAULT_SHARE_HOURS, MAX_SHARE_HOURS,
+};
+use crate::services::share_link::{get_share_secret, sign_token, verify_token};
+use super::AppState;
+use super::error::{ApiError, ApiResult, ErrorCode};
+use super::response::{success, success_message};
+use super::streaming::{stream_media_video, StreamQuery};
+
+fn public_url(token: &str) -> String {
+    format!("/api/public/share/{}", token)
+}
+
+// ============ Share Link Management ============
+
+/// 创建只读分享链接
+pub async fn create_share_handler(
+    Path(media_id): Path<String>,
+    State(state): State<AppState>,
+    payload: Option<Json<CreateShareRequest>>,
+) -> ApiResult<impl IntoResponse> {
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let hours = payload.expires_in_hours.unwrap_or(DEFAULT_SHARE_HOURS);
+    if !(1..=MAX_SHARE_HOURS).contains(&hours) {
+        return Err(ApiError::Validation(format!(
+            "expires_in_hours must be between 1 and {}", MAX_SHARE_HOURS
+        )));
+    }
+
+    let exists = state.database.repository().media_exists(&media_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to check media: {}", e);
+            ApiError::Internal("Failed to check media".to_string())
+        })?;
+    if !exists {
+        return Err(ApiError::coded(ErrorCode::MediaNotFound, "Media not found"));
+    }
+
+    let pool = state.database.pool();
+    let secret = get_share_secret(pool).await
+        .map_err(|e| {
+            tracing::error!("Failed to load share secret: {}", e);
+            ApiError::Internal("Failed to create share link".to_string())
+        })?;
+
+    // 过期时间取整到秒，与令牌中的时间戳一致
+    let expires_ts = (Utc::now() + Duration::hours(hours)).timestamp();
+    let expires_at = chrono::DateTime::from_timestamp(expires_ts, 0)
+        .ok_or_else(|| ApiError::Internal("Invalid expiry".to_string()))?;
+    let link = database::create_share_link(pool, &ShareLink::new(media_id, expires_at)).await
+        .map_err(|e| {
+            tracing::error!("Failed to create share link: {}", e);
+            ApiError::Internal("Failed to create share link".to_string())
+        })?;
+
+    let token = sign_token(&secret, &link.id, expires_ts);
+    Ok(success(CreateShareResponse {
+        url: public_url(&token),
+        video_url: format!("{}/video", public_url(&token)),
+        token,
+        link,
+    }))
+}
+
+/// 获取媒体的分享链接（含访问次数）
+pub async fn list_shares_handler(
+    Path(media_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let links = database::list_share_links(state.database.pool(), &media_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to list share links: {}", e);
+            ApiError::Internal("Failed to retrieve share links".to_string())
+        })?;
+
+    Ok(success(links))
+}
+
+/// 撤销分享链接
+pub async fn revoke_share_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    database::revoke_share_link(state.database.pool(), &id).await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke share link: {}", e);
+            if e.to_string().contains("not found") {
+                ApiError::NotFound("Share link not found".to_string())
+            } else {
+                ApiError::Internal("Failed to revoke share link".to_string())
+            }
+        })?;
+
+    Ok(success_message("Share link revoked"))
+}
+
+// ============ Public Endpoints ============
+// 仅凭令牌访问，只暴露单个媒体的最小信息和播放
+
+/// 校验令牌并返回仍有效的分享链接
+async fn resolve_share(state: &AppState, token: &str) -> Result<ShareLink, ApiError> {
+    let pool = state.database.pool();
+    let secret = get_share_secret(pool).await
+        .map_err(|e| {
+            tracing::error!("Failed to load share secret: {}", e);
+            ApiError::Internal("Failed to verify share link".to_string())
+        })?;
+
+    let share_id = verify_token(&secret, token, Utc::now().timestamp())
+        .map_err(|_| ApiError::NotFound("Share link not found or expired".to_string()))?;
+    let link = database::get_share_link(pool, &share_id).await
+        .map_err(|_| ApiError::NotFound("Share link not found or expired".to_string()))?;
+
+    if !link.is_active(Utc::now()) {
+        return Err(ApiError::NotFound("Share link not found or expired".to_string()));
+    }
+    Ok(link)
+}
+
+/// 公开详情
+pub async fn public_share_detail(
+    Path(token): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let link = resolve_share(&state, &token).await?;
+
+    let media = state.database.repository().get_media_by_id(&link.media_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to load shared media: {}", e);
+            ApiError::Internal("Failed to load shared media".to_string())
+        })?
+        .ok_or_else(|| ApiError::NotFound("Share link not found or expired".to_string()))?;
+    let has_files = !state.database.repository().get_media_files(&link.media_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to load shared media files: {}", e);
+            ApiError::Internal("Failed to load shared media".to_string())
+        })?
+        .is_empty();
+
+    if let Err(e) = database::record_share_access(state.database.pool(), &link.id).await {
+        tracing::warn!("Failed to record share access: {}", e);
+    }
+
+    Ok(success(PublicMediaDetail {
+        title: media.title,
+        original_title: media.original_title,
+        year: media.year,
+        overview: media.overview,
+        poster_url: media.poster_url,
+        runtime: media.runtime,
+        release_date: media.release_date,
+        genres: serde_json::from_str(&media.genres).unwrap_or_default(),
+        expires_at: link.expires_at,
+        video_url: has_files.then(|| format!("{}/video", public_url(&token))),
+    }))
+}
+
+/// 公开播放（支持 Range）
+pub async fn public_share_video(
+    Path(token): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let link = resolve_share(&state, &token).await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    stream_media_video(&state, &link.media_id, &StreamQuery::default(), &headers).await
+}
@@ -1,5 +1,6 @@
 use axum::{
     extract::{Path, State, Query},
+    Json,
     http::{header, StatusCode, HeaderMap},
     response::{IntoResponse, Response},
     body::Body,
@@ -7,11 +8,24 @@ use axum::{
 use tokio::fs::File;
 use tokio::io::AsyncReadExt;
 use tokio_util::io::ReaderStream;
-use std::path::PathBuf;
-use sha2::{Sha256, Digest};
+use std::path::{Path as FsPath, PathBuf};
+use futures_util::StreamExt;
+
+use serde::Deserialize;
 
 use crate::database::repository::DatabaseRepository;
+use crate::models::{
+    decide_playback, find_library_root, is_extra_image, select_edition_file, HwAccel, LibraryRoot, PlayDecision,
+    PlayDecisionRequest, PLAY_DIRECT, STREAM_DIRECT, STREAM_TRANSCODE, TRANSCODE_AUDIO_CODECS,
+    TRANSCODE_CONTAINERS, TRANSCODE_VIDEO_CODECS,
+};
+use crate::services::storage::root_storage;
+use crate::services::stream_sessions::{self, SessionGuard};
+use crate::services::{ffmpeg, hwaccel};
 use super::AppState;
+use super::history::history_user;
+use super::error::{ApiError, ApiResult, ErrorCode};
+use super::response::success;
 
 /// 获取媒体缩略图
 pub async fn get_media_thumbnail(
@@ -53,8 +67,8 @@ pub async fn get_media_thumbnail(
         return Err(StatusCode::NOT_FOUND);
     }
 
-    // 生成缩略图（使用缓存）
-    match generate_thumbnail_cached(&video_path, &id, file_index).await {
+    // 生成缩略图（使用缓存，未缓存时由生成队列优先处理）
+    match crate::services::thumbnail::get_thumbnail(&video_path, &id, file_index).await {
         Ok(thumbnail_data) => {
             Ok((
                 [(header::CONTENT_TYPE, "image/jpeg")],
@@ -65,31 +79,70 @@ pub async fn get_media_thumbnail(
     }
 }
 
+/// 视频流参数
+#[derive(Debug, Default, Deserialize)]
+pub struct StreamQuery {
+    /// 版本名称（默认播放默认版本）
+    pub edition: Option<String>,
+    /// 版本内的分段索引（从 0 开始）
+    #[serde(default)]
+    pub part: usize,
+}
+
 /// 流式传输视频
 pub async fn stream_video(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    Query(query): Query<StreamQuery>,
     headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let session = open_session(&headers, &id, query.edition.as_deref(), query.part, STREAM_DIRECT)?;
+    let response = stream_media_video(&state, &id, &query, &headers).await?;
+    Ok(stream_sessions::track_response(session, response))
+}
+
+/// 登记播放会话，超过用户的同时播放数上限时返回 429
+fn open_session(
+    headers: &HeaderMap,
+    media_id: &str,
+    edition: Option<&str>,
+    part: usize,
+    kind: &str,
+) -> Result<SessionGuard<'static>, StatusCode> {
+    let user = history_user(headers);
+    let user_agent = headers.get(header::USER_AGENT).and_then(|v| v.to_str().ok());
+    stream_sessions::open(&user, media_id, edition, part, kind, user_agent).map_err(|e| {
+        tracing::info!("User {} reached the limit of {} concurrent streams", user, e.limit);
+        StatusCode::TOO_MANY_REQUESTS
+    })
+}
+
+/// 流式传输媒体的指定版本/分段（支持 Range，供分享链接复用）
+pub(crate) async fn stream_media_video(
+    state: &AppState,
+    id: &str,
+    query: &StreamQuery,
+    headers: &HeaderMap,
 ) -> Result<Response, StatusCode> {
     // 从数据库获取媒体信息
     let _media = state.database.repository()
-        .get_media_by_id(&id)
+        .get_media_by_id(id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
 
     // 获取关联的文件
     let files = state.database.repository()
-        .get_media_files(&id)
+        .get_media_files(id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    if files.is_empty() {
-        return Err(StatusCode::NOT_FOUND);
+    let file = select_edition_file(files, query.edition.as_deref(), query.part)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if let Some(root) = remote_root(state, &file.file_path).await? {
+        return stream_from_storage(&root, &file.file_path, "video/mp4", headers).await;
     }
-
-    // 使用第一个文件
-    let video_path = PathBuf::from(&files[0].file_path);
+    let video_path = PathBuf::from(&file.file_path);
     
     if !video_path.exists() {
         return Err(StatusCode::NOT_FOUND);
@@ -117,175 +170,288 @@ pub async fn stream_video(
     stream_full_file(&video_path, file_size).await
 }
 
-/// 获取缩略图缓存目录
-fn get_thumbnail_cache_dir() -> Result<PathBuf, std::io::Error> {
-    // 优先使用环境变量 CACHE_DIR，如果没有设置则使用当前目录
-    let base_dir = std::env::var("CACHE_DIR")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| {
-            // 在开发模式下，使用 Cargo.toml 所在目录（项目根目录）
-            // 在生产模式下，使用当前工作目录
-            if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
-                PathBuf::from(manifest_dir)
-            } else {
-                std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+/// 根据客户端上报的播放能力（容器、编码、码率和分辨率上限）和文件的技术信息，
+/// 决定直接播放、只换容器或转码，返回对应的播放地址
+/// POST /api/media/:id/play-decision
+pub async fn play_decision(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(request): Json<PlayDecisionRequest>,
+) -> ApiResult<impl IntoResponse> {
+    state.database.repository().get_media_by_id(&id).await?
+        .ok_or_else(|| ApiError::coded(ErrorCode::MediaNotFound, "Media not found"))?;
+    let files = state.database.repository().get_media_files(&id).await?;
+    let file = select_edition_file(files, request.edition.as_deref(), request.part)
+        .ok_or_else(|| ApiError::NotFound("Media file not found".to_string()))?;
+    let probe = crate::database::list_media_probes(state.database.pool(), &id).await?
+        .into_iter()
+        .find(|probe| probe.file_path == file.file_path);
+
+    let plan = decide_playback(probe.as_ref(), &request);
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    if let Some(edition) = request.edition.as_deref() {
+        query.append_pair("edition", edition);
+    }
+    query.append_pair("part", &request.part.to_string());
+    let stream_url = if plan.method == PLAY_DIRECT {
+        format!("/api/media/{}/video?{}", id, query.finish())
+    } else {
+        query.append_pair("container", plan.container.as_deref().unwrap_or("mp4"));
+        query.append_pair("video", plan.video_codec.as_deref().unwrap_or("copy"));
+        query.append_pair("audio", plan.audio_codec.as_deref().unwrap_or("copy"));
+        if plan.video_codec.as_deref() != Some("copy") {
+            if let Some(max_bitrate) = request.max_bitrate {
+                query.append_pair("max_bitrate", &max_bitrate.to_string());
             }
-        });
-    
-    let cache_dir = base_dir
-        .join("cache")
-        .join("thumbnails");
+            if let Some(max_height) = request.max_height {
+                query.append_pair("max_height", &max_height.to_string());
+            }
+        }
+        format!("/api/media/{}/video/transcode?{}", id, query.finish())
+    };
 
-    // 确保缓存目录存在
-    if !cache_dir.exists() {
-        std::fs::create_dir_all(&cache_dir)?;
-    }
+    Ok(success(PlayDecision { plan, stream_url, file_id: file.id, probed: probe.is_some() }))
+}
 
-    tracing::info!("缩略图缓存目录: {}", cache_dir.display());
+/// 换容器/转码参数
+#[derive(Debug, Deserialize)]
+pub struct TranscodeQuery {
+    pub edition: Option<String>,
+    #[serde(default)]
+    pub part: usize,
+    /// mp4（分片 MP4）或 matroska
+    #[serde(default = "default_transcode_container")]
+    pub container: String,
+    /// copy 或 h264
+    #[serde(default = "default_transcode_copy")]
+    pub video: String,
+    /// copy 或 aac
+    #[serde(default = "default_transcode_copy")]
+    pub audio: String,
+    /// 视频码率上限（bit/s，仅重新编码视频时有效）
+    pub max_bitrate: Option<i64>,
+    /// 视频高度上限（仅重新编码视频时有效）
+    pub max_height: Option<i64>,
+}
 
-    Ok(cache_dir)
+fn default_transcode_container() -> String {
+    "mp4".to_string()
 }
 
-/// 生成缓存文件名（基于视频路径和文件索引的哈希）
-fn generate_cache_filename(video_path: &PathBuf, media_id: &str, file_index: usize) -> String {
-    // 使用视频路径 + media_id + file_index 生成唯一的哈希
-    let mut hasher = Sha256::new();
-    hasher.update(video_path.to_string_lossy().as_bytes());
-    hasher.update(media_id.as_bytes());
-    hasher.update(file_index.to_string().as_bytes());
-    let hash = hasher.finalize();
-    
-    // 使用哈希的前32个字符作为文件名，然后添加 .jpg 扩展名
-    let hash_str: String = format!("{:x}", hash).chars().take(32).collect();
-    format!("{}.jpg", hash_str)
+fn default_transcode_copy() -> String {
+    "copy".to_string()
 }
 
-/// 生成视频缩略图（带缓存）
-async fn generate_thumbnail_cached(
-    video_path: &PathBuf,
-    media_id: &str,
-    file_index: usize,
-) -> Result<Vec<u8>, std::io::Error> {
-    // 获取缓存目录
-    let cache_dir = get_thumbnail_cache_dir()?;
-    tracing::info!("使用缓存目录: {}", cache_dir.display());
-    
-    // 生成缓存文件名
-    let cache_filename = generate_cache_filename(video_path, media_id, file_index);
-    let cache_path = cache_dir.join(&cache_filename);
-    
-    tracing::info!("缓存文件路径: {}", cache_path.display());
-    
-    // 检查缓存是否存在
-    if cache_path.exists() {
-        tracing::info!("使用缓存的缩略图: {}", cache_path.display());
-        // 读取并返回缓存的缩略图
-        return tokio::fs::read(&cache_path).await;
-    }
-    
-    tracing::info!("缓存不存在，开始生成新缩略图");
-    
-    // 缓存不存在，生成新的缩略图
-    use tokio::process::Command;
-
-    // 使用 FFmpeg 生成缩略图
-    let video_path_str = video_path.to_str()
-        .ok_or_else(|| std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            "Invalid video path encoding"
-        ))?;
-    
-    let cache_path_str = cache_path.to_str()
-        .ok_or_else(|| std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            "Invalid cache path encoding"
-        ))?;
-    
-    tracing::info!("FFmpeg 命令: ffmpeg -ss 00:00:02 -i {} -vframes 1 -update 1 -q:v 2 -y {}", 
-        video_path_str, cache_path_str);
-    
-    let output = Command::new("ffmpeg")
-        .args(&[
-            "-ss", "00:00:02",           // 从第2秒开始
-            "-i", video_path_str,
-            "-vframes", "1",              // 只提取一帧
-            "-update", "1",               // 允许输出单张图片
-            // 不缩放，保持原始分辨率
-            "-q:v", "2",                  // JPEG 质量（高质量）
-            "-y",                         // 覆盖输出文件
-            cache_path_str,
-        ])
-        .output()
-        .await?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        tracing::error!("FFmpeg 失败: {}", stderr);
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("FFmpeg failed to generate thumbnail: {}", stderr),
-        ));
+impl TranscodeQuery {
+    /// FFmpeg 参数（输出到标准输出；重新编码视频时使用选中的硬件加速）
+    fn ffmpeg_args(&self, input: &str, accel: Option<HwAccel>) -> Vec<String> {
+        let transcode_video = self.video != "copy";
+        let accel = accel.filter(|_| transcode_video);
+        let mut args: Vec<String> = ["-hide_banner", "-loglevel", "error"].map(String::from).to_vec();
+        if let Some(accel) = accel {
+            args.extend(hwaccel::decode_args(accel));
+        }
+        args.extend(["-i", input, "-map", "0:v:0", "-map", "0:a:0?"].map(String::from));
+
+        if transcode_video {
+            let mut filters = Vec::new();
+            if let Some(max_height) = self.max_height {
+                filters.push(format!("scale=-2:'min({},ih)'", max_height));
+            }
+            if accel == Some(HwAccel::Vaapi) {
+                filters.push("format=nv12,hwupload".to_string());
+            }
+            if !filters.is_empty() {
+                args.extend(["-vf".to_string(), filters.join(",")]);
+            }
+            match accel {
+                Some(accel) => args.extend(["-c:v".to_string(), accel.h264_encoder().to_string()]),
+                None => args.extend(["-c:v", "libx264", "-preset", "veryfast"].map(String::from)),
+            }
+            if let Some(max_bitrate) = self.max_bitrate {
+                args.extend([
+                    "-b:v".to_string(), max_bitrate.to_string(),
+                    "-maxrate".to_string(), max_bitrate.to_string(),
+                    "-bufsize".to_string(), (max_bitrate * 2).to_string(),
+                ]);
+            }
+        } else {
+            args.extend(["-c:v", "copy"].map(String::from));
+        }
+
+        match self.audio.as_str() {
+            "aac" => args.extend(["-c:a", "aac", "-b:a", "192k"].map(String::from)),
+            _ => args.extend(["-c:a", "copy"].map(String::from)),
+        }
+        match self.container.as_str() {
+            "matroska" => args.extend(["-f", "matroska", "pipe:1"].map(String::from)),
+            // 分片 MP4 不需要回写文件头，可以边转边播
+            _ => args.extend(["-movflags", "frag_keyframe+empty_moov+default_base_moof", "-f", "mp4", "pipe:1"].map(String::from)),
+        }
+        args
     }
+}
 
-    tracing::info!("FFmpeg 成功生成缩略图");
+/// 换容器或转码后流式输出（不支持 Range，客户端断开或会话被终止时结束 FFmpeg 进程）
+/// GET /api/media/:id/video/transcode
+pub async fn stream_transcoded(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<TranscodeQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    if !TRANSCODE_CONTAINERS.contains(&query.container.as_str())
+        || !TRANSCODE_VIDEO_CODECS.contains(&query.video.as_str())
+        || !TRANSCODE_AUDIO_CODECS.contains(&query.audio.as_str())
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
-    // 读取缩略图数据
-    let thumbnail_data = tokio::fs::read(&cache_path).await?;
-    
-    tracing::info!("成功读取缩略图数据，大小: {} bytes", thumbnail_data.len());
+    let _media = state.database.repository()
+        .get_media_by_id(&id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let files = state.database.repository()
+        .get_media_files(&id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let file = select_edition_file(files, query.edition.as_deref(), query.part)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    // 对象存储中的文件只支持直接播放
+    if remote_root(&state, &file.file_path).await?.is_some() || !FsPath::new(&file.file_path).exists() {
+        return Err(StatusCode::NOT_FOUND);
+    }
 
-    Ok(thumbnail_data)
+    let session = open_session(&headers, &id, query.edition.as_deref(), query.part, STREAM_TRANSCODE)?;
+    let permit = ffmpeg::acquire().await;
+    let mut child = ffmpeg::ffmpeg_command(query.ffmpeg_args(&file.file_path, hwaccel::selected()))
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            tracing::error!("Failed to start ffmpeg: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let stdout = child.stdout.take().ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // 进程和调度许可随响应体一起释放
+    let guard = (child, permit);
+    let stream = ReaderStream::new(stdout).map(move |chunk| {
+        let _ = &guard;
+        chunk
+    });
+    let content_type = match query.container.as_str() {
+        "matroska" => "video/x-matroska",
+        _ => "video/mp4",
+    };
+    let response = ([(header::CONTENT_TYPE, content_type)], Body::from_stream(stream)).into_response();
+    Ok(stream_sessions::track_response(session, response))
 }
 
-/// 生成视频缩略图（旧版本，不使用缓存）
-#[allow(dead_code)]
-async fn generate_thumbnail(video_path: &PathBuf) -> Result<Vec<u8>, std::io::Error> {
-    use tokio::process::Command;
-
-    // 创建临时文件
-    let temp_dir = std::env::temp_dir();
-    let thumbnail_path = temp_dir.join(format!("thumb_{}.jpg", uuid::Uuid::new_v4()));
-
-    // 使用 FFmpeg 生成缩略图
-    let video_path_str = video_path.to_str()
-        .ok_or_else(|| std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            "Invalid video path encoding"
-        ))?;
-    
-    let thumbnail_path_str = thumbnail_path.to_str()
-        .ok_or_else(|| std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            "Invalid thumbnail path encoding"
-        ))?;
-    
-    let output = Command::new("ffmpeg")
-        .args(&[
-            "-ss", "00:00:02",           // 从第2秒开始
-            "-i", video_path_str,
-            "-vframes", "1",              // 只提取一帧
-            // 不缩放，保持原始分辨率
-            "-q:v", "2",                  // JPEG 质量（高质量）
-            "-y",                         // 覆盖输出文件
-            thumbnail_path_str,
-        ])
-        .output()
-        .await?;
-
-    if !output.status.success() {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "FFmpeg failed to generate thumbnail",
-        ));
+/// 流式传输媒体的附加内容（视频支持 Range，图集图片直接返回）
+pub async fn stream_media_extra(
+    State(state): State<AppState>,
+    Path((id, extra_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let extra = crate::database::get_media_extra(state.database.pool(), &id, &extra_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let path = PathBuf::from(&extra.file_path);
+    let image_type = is_extra_image(&path).then(|| {
+        match path.extension().map(|e| e.to_string_lossy().to_lowercase()).as_deref() {
+            Some("png") => "image/png",
+            Some("webp") => "image/webp",
+            Some("gif") => "image/gif",
+            Some("bmp") => "image/bmp",
+            _ => "image/jpeg",
+        }
+    });
+    if let Some(root) = remote_root(&state, &extra.file_path).await? {
+        return stream_from_storage(&root, &extra.file_path, image_type.unwrap_or("video/mp4"), &headers).await;
+    }
+    if !path.exists() {
+        return Err(StatusCode::NOT_FOUND);
     }
 
-    // 读取缩略图数据
-    let thumbnail_data = tokio::fs::read(&thumbnail_path).await?;
+    if let Some(content_type) = image_type {
+        let data = tokio::fs::read(&path).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        return Ok(([(header::CONTENT_TYPE, content_type)], data).into_response());
+    }
+
+    let file_size = tokio::fs::metadata(&path)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .len();
 
-    // 删除临时文件
-    let _ = tokio::fs::remove_file(&thumbnail_path).await;
+    if let Some(range_spec) = headers.get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|r| parse_range(r, file_size))
+    {
+        return stream_range(&path, range_spec, file_size).await;
+    }
 
-    Ok(thumbnail_data)
+    stream_full_file(&path, file_size).await
+}
+
+/// 文件所在的根目录使用对象存储时返回该根目录
+async fn remote_root(state: &AppState, file_path: &str) -> Result<Option<LibraryRoot>, StatusCode> {
+    let roots = crate::database::list_library_roots(state.database.pool())
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(find_library_root(&roots, file_path).filter(|root| !root.is_local()).cloned())
+}
+
+/// 从对象存储代理读取文件（支持 Range）
+async fn stream_from_storage(
+    root: &LibraryRoot,
+    file_path: &str,
+    content_type: &str,
+    headers: &HeaderMap,
+) -> Result<Response, StatusCode> {
+    let key = root.relative_path(file_path).ok_or(StatusCode::NOT_FOUND)?;
+    let storage = root_storage(root).map_err(|e| {
+        tracing::error!("Invalid storage config for library root {}: {}", root.path, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let remote_error = |e: anyhow::Error| {
+        tracing::warn!("Failed to read {} from {} storage: {}", file_path, root.storage_type, e);
+        StatusCode::BAD_GATEWAY
+    };
+
+    let file_size = storage.size(key).await.map_err(remote_error)?.ok_or(StatusCode::NOT_FOUND)?;
+    let range = headers.get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|r| parse_range(r, file_size));
+    let stream = storage.read(key, range).await.map_err(remote_error)?;
+    let body = Body::from_stream(stream);
+
+    let response = match range {
+        Some((start, end)) => (
+            StatusCode::PARTIAL_CONTENT,
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (header::CONTENT_LENGTH, (end - start + 1).to_string()),
+                (header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, file_size)),
+                (header::ACCEPT_RANGES, "bytes".to_string()),
+            ],
+            body,
+        ).into_response(),
+        None => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, content_type.to_string()),
+                (header::CONTENT_LENGTH, file_size.to_string()),
+                (header::ACCEPT_RANGES, "bytes".to_string()),
+            ],
+            body,
+        ).into_response(),
+    };
+    Ok(response)
 }
 
 /// 解析 Range 请求头
@@ -3,7 +3,7 @@ use axum::{
     response::IntoResponse,
     Json,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx;
 
 use crate::database;
@@ -11,18 +11,40 @@ use crate::models::{
     Studio, Series, StudioWithSeries, SeriesWithStudio,
     CreateStudioRequest, UpdateStudioRequest,
     CreateSeriesRequest, UpdateSeriesRequest,
+    StudioListFilters, ListSortOption, clamp_list_page, parse_list_sort,
+    ApplySeriesSuggestionsRequest, ReassignStudioRequest,
 };
+use crate::plugins::manager::PluginSelectError;
+use crate::services::series_detect;
 use super::AppState;
-use super::error::{ApiError, ApiResult};
+use super::error::{ApiError, ApiResult, ErrorCode};
 use super::response::{success, success_message};
 
 #[derive(Debug, Deserialize)]
 pub struct ListParams {
+    /// 按名称过滤
+    pub q: Option<String>,
+    /// name、media_count 或 created_at，默认 media_count
+    pub sort_by: Option<String>,
+    pub sort_order: Option<String>,  // asc, desc
     pub limit: Option<i32>,
     pub offset: Option<i32>,
     pub studio_id: Option<String>,
 }
 
+impl ListParams {
+    /// 转换为列表过滤器（校验排序参数，限制每页条数）
+    fn filters(self) -> ApiResult<StudioListFilters> {
+        let (sort_by, sort_order) = parse_list_sort(
+            self.sort_by.as_deref(),
+            self.sort_order.as_deref(),
+            ListSortOption::MediaCount,
+        ).map_err(ApiError::BadRequest)?;
+        let (limit, offset) = clamp_list_page(self.limit, self.offset, 100);
+        Ok(StudioListFilters { query: self.q, studio_id: self.studio_id, sort_by, sort_order, limit, offset })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SearchParams {
     pub q: String,
@@ -37,7 +59,7 @@ pub async fn list_studios_handler(
     Query(params): Query<ListParams>,
     State(state): State<AppState>,
 ) -> ApiResult<impl IntoResponse> {
-    let response = database::list_studios(state.database.pool(), params.limit, params.offset).await
+    let response = database::list_studios(state.database.pool(), &params.filters()?).await
         .map_err(|e| {
             tracing::error!("Failed to list studios: {}", e);
             ApiError::Internal("Failed to retrieve studios".to_string())
@@ -52,7 +74,7 @@ pub async fn get_studio_handler(
     State(state): State<AppState>,
 ) -> ApiResult<impl IntoResponse> {
     let studio = database::get_studio_by_id(state.database.pool(), &id).await
-        .map_err(|_| ApiError::NotFound("Studio not found".to_string()))?;
+        .map_err(|_| ApiError::coded(ErrorCode::StudioNotFound, "Studio not found"))?;
     
     let series_list: Vec<Series> = sqlx::query_as(
         "SELECT * FROM series WHERE studio_id = ? ORDER BY media_count DESC, name COLLATE NOCASE"
@@ -65,6 +87,31 @@ pub async fn get_studio_handler(
     Ok(success(StudioWithSeries { studio, series_list }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct StudioOverviewParams {
+    /// 系列、演员和最近作品各返回的条数，默认 10
+    pub limit: Option<i64>,
+}
+
+/// 获取厂商详情页汇总
+pub async fn get_studio_overview_handler(
+    Path(id): Path<String>,
+    Query(params): Query<StudioOverviewParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let studio = database::get_studio_by_id(state.database.pool(), &id).await
+        .map_err(|_| ApiError::coded(ErrorCode::StudioNotFound, "Studio not found"))?;
+    let limit = params.limit.unwrap_or(10).clamp(1, 100);
+    
+    let overview = database::get_studio_overview(state.database.pool(), studio, limit).await
+        .map_err(|e| {
+            tracing::error!("Failed to get studio overview: {}", e);
+            ApiError::Internal("Failed to retrieve studio overview".to_string())
+        })?;
+    
+    Ok(success(overview))
+}
+
 /// 创建厂商
 pub async fn create_studio_handler(
     State(state): State<AppState>,
@@ -102,6 +149,31 @@ pub async fn update_studio_handler(
     Ok(success(studio))
 }
 
+/// 把厂商的全部媒体和系列移到另一个厂商
+pub async fn reassign_studio_handler(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<ReassignStudioRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if payload.target_studio_id == id {
+        return Err(ApiError::invalid_field("target_studio_id", "Target studio must differ from the source"));
+    }
+    let pool = state.database.pool();
+    let source = database::get_studio_by_id(pool, &id).await
+        .map_err(|_| ApiError::coded(ErrorCode::StudioNotFound, "Studio not found"))?;
+    let target = database::get_studio_by_id(pool, &payload.target_studio_id).await
+        .map_err(|_| ApiError::coded(ErrorCode::StudioNotFound, "Target studio not found"))?;
+    
+    let result = database::reassign_studio(pool, &source, &target, payload.delete_source).await
+        .map_err(|e| {
+            tracing::error!("Failed to reassign studio: {}", e);
+            ApiError::Internal("Failed to reassign studio".to_string())
+        })?;
+    tracing::info!("厂商 {} 的 {} 个媒体移到 {}", source.name, result.moved_media, target.name);
+    
+    Ok(success(result))
+}
+
 /// 删除厂商
 pub async fn delete_studio_handler(
     Path(id): Path<String>,
@@ -123,12 +195,7 @@ pub async fn list_series_handler(
     Query(params): Query<ListParams>,
     State(state): State<AppState>,
 ) -> ApiResult<impl IntoResponse> {
-    let response = database::list_series(
-        state.database.pool(),
-        params.studio_id.as_deref(),
-        params.limit,
-        params.offset,
-    ).await
+    let response = database::list_series(state.database.pool(), &params.filters()?).await
         .map_err(|e| {
             tracing::error!("Failed to list series: {}", e);
             ApiError::Internal("Failed to retrieve series".to_string())
@@ -137,13 +204,44 @@ pub async fn list_series_handler(
     Ok(success(response))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SeriesSuggestionsParams {
+    /// 为 true 时重新检测，否则返回最近一次的报告
+    #[serde(default)]
+    pub refresh: bool,
+}
+
+/// 获取未设置系列的媒体的系列建议
+pub async fn get_series_suggestions_handler(
+    Query(params): Query<SeriesSuggestionsParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let report = series_detect::latest_report(state.database.pool(), params.refresh).await?;
+    Ok(success(report))
+}
+
+/// 批量应用系列建议（不传 assignments 时应用全部建议）
+pub async fn apply_series_suggestions_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ApplySeriesSuggestionsRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(assignments) = &request.assignments {
+        if assignments.iter().any(|a| a.series.trim().is_empty()) {
+            return Err(ApiError::invalid_field("assignments", "Series name cannot be empty"));
+        }
+    }
+    let result = series_detect::apply_suggestions(state.database.pool(), request.assignments).await?;
+    tracing::info!("应用系列建议: {} 个媒体, 跳过 {} 个", result.applied, result.skipped);
+    Ok(success(result))
+}
+
 /// 获取单个系列
 pub async fn get_series_handler(
     Path(id): Path<String>,
     State(state): State<AppState>,
 ) -> ApiResult<impl IntoResponse> {
     let series = database::get_series_by_id(state.database.pool(), &id).await
-        .map_err(|_| ApiError::NotFound("Series not found".to_string()))?;
+        .map_err(|_| ApiError::coded(ErrorCode::SeriesNotFound, "Series not found"))?;
     
     let studio_name = if let Some(ref studio_id) = series.studio_id {
         database::get_studio_by_id(state.database.pool(), studio_id)
@@ -157,6 +255,64 @@ pub async fn get_series_handler(
     Ok(success(SeriesWithStudio { series, studio_name }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SeriesGapsParams {
+    /// 刮削插件ID，默认使用发售日历的插件
+    pub plugin_id: Option<String>,
+}
+
+/// 系列缺失作品分析结果
+#[derive(Debug, Serialize)]
+pub struct SeriesGapsResponse {
+    pub series_id: String,
+    pub series_name: String,
+    pub plugin_id: String,
+    /// 插件返回的已知作品数
+    pub known_count: usize,
+    /// 本地拥有的作品数
+    pub owned_count: usize,
+    pub missing: Vec<crate::services::series_gaps::SeriesGap>,
+}
+
+/// 获取系列缺失的作品（向插件查询全部已知作品并与本地媒体库对比）
+pub async fn get_series_gaps_handler(
+    Path(id): Path<String>,
+    Query(params): Query<SeriesGapsParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let series = database::get_series_by_id(state.database.pool(), &id).await
+        .map_err(|_| ApiError::coded(ErrorCode::SeriesNotFound, "Series not found"))?;
+    // 未指定插件时使用声明了 releases 能力的插件
+    let plugin_id = match params.plugin_id {
+        Some(plugin_id) => plugin_id,
+        None => state.plugin_manager.read().await
+            .plugin_with_capability(crate::plugins::protocol::CAPABILITY_RELEASES)
+            .ok_or_else(|| ApiError::BadRequest("No plugin supports release lists".to_string()))?,
+    };
+    
+    let releases = {
+        let manager = state.plugin_manager.read().await;
+        manager.fetch_all_releases(&plugin_id, "series", &series.name).await
+    }
+    .map_err(|e| match e.downcast_ref::<PluginSelectError>() {
+        Some(PluginSelectError::Unsupported(message)) => ApiError::BadRequest(message.clone()),
+        _ => ApiError::ExternalService(format!("Failed to get releases from plugin: {}", e)),
+    })?;
+    
+    let owned = database::get_series_media_keys(state.database.pool(), &series.name).await?;
+    let known_count = releases.len();
+    let missing = crate::services::series_gaps::find_missing_releases(releases, &owned);
+    
+    Ok(success(SeriesGapsResponse {
+        series_id: series.id,
+        series_name: series.name,
+        plugin_id,
+        known_count,
+        owned_count: owned.len(),
+        missing,
+    }))
+}
+
 /// 创建系列
 pub async fn create_series_handler(
     State(state): State<AppState>,
@@ -208,7 +364,7 @@ pub async fn delete_series_handler(
     Ok(success_message("Series deleted successfully"))
 }
 
-/// 同步所有计数
+/// 重建所有计数（计数由触发器自动维护，仅在计数出错时使用）
 pub async fn sync_counts_handler(
     State(state): State<AppState>,
 ) -> ApiResult<impl IntoResponse> {
@@ -221,6 +377,19 @@ pub async fn sync_counts_handler(
     Ok(success_message("Counts synced successfully"))
 }
 
+/// 按名称为已有媒体关联厂商/系列表，返回无法关联的名称
+pub async fn link_media_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let result = database::link_media_studio_series(state.database.pool()).await
+        .map_err(|e| {
+            tracing::error!("Failed to link media to studios/series: {}", e);
+            ApiError::Internal("Failed to link media to studios/series".to_string())
+        })?;
+    
+    Ok(success(result))
+}
+
 // ============ Search Handlers ============
 
 /// 搜索厂商（模糊匹配）
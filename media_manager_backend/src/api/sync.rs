@@ -1,5 +1,6 @@
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
+    http::HeaderMap,
     response::IntoResponse,
     Json,
 };
@@ -8,8 +9,21 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use chrono::{DateTime, Utc};
 
-use super::error::ApiResult;
-use super::response::success;
+use crate::database;
+use crate::models::{ChangeEntry, ChangeFeedResponse, CreatePeerRequest, PushChangesRequest};
+use crate::services::peer_sync::{self, SYNC_TOKEN_HEADER};
+use super::AppState;
+use super::error::{ApiError, ApiResult};
+use super::response::{success, success_message};
+
+/// 变更流单次默认/最大返回条数
+const DEFAULT_CHANGES_LIMIT: i64 = 500;
+const MAX_CHANGES_LIMIT: i64 = 5000;
+
+// ============ Legacy Trigger Flow ============
+// 简单的同步标记（PC 端触发、移动端轮询），新客户端应使用下方的变更流。
+// media_manager_app 仍在调用 /api/sync/trigger、check、complete、status，
+// 客户端迁移到 /api/sync/changes 之前保留这组接口
 
 /// 同步触发状态
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,3 +97,178 @@ pub async fn get_sync_status(
     let trigger = state.trigger.read().await;
     Ok(success(trigger.clone()))
 }
+
+// ============ Change Feed ============
+
+#[derive(Debug, Deserialize)]
+pub struct ChangesParams {
+    /// 只返回序列号大于该值的变更（默认 0，即全部）
+    pub since_seq: Option<i64>,
+    pub limit: Option<i64>,
+    /// 按实体类型筛选（media / collection / actor / studio / series ...）
+    pub entity_type: Option<String>,
+    /// 排除来自该实例的变更（对端同步时传入自身实例ID）
+    pub exclude_origin: Option<String>,
+}
+
+/// 增量拉取变更流（离线优先客户端使用）
+pub async fn get_changes(
+    Query(params): Query<ChangesParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let since_seq = params.since_seq.unwrap_or(0);
+    if since_seq < 0 {
+        return Err(ApiError::Validation("since_seq cannot be negative".to_string()));
+    }
+    let limit = params.limit.unwrap_or(DEFAULT_CHANGES_LIMIT).clamp(1, MAX_CHANGES_LIMIT);
+    let pool = state.database.pool();
+
+    // 多取一条用于判断是否还有更多
+    let mut records = database::list_changes(pool, since_seq, limit + 1, params.entity_type.as_deref(), params.exclude_origin.as_deref()).await
+        .map_err(|e| {
+            tracing::error!("Failed to list changes: {}", e);
+            ApiError::Internal("Failed to retrieve changes".to_string())
+        })?;
+    let has_more = records.len() as i64 > limit;
+    records.truncate(limit as usize);
+
+    let latest_seq = database::latest_change_seq(pool).await
+        .map_err(|e| {
+            tracing::error!("Failed to get latest change seq: {}", e);
+            ApiError::Internal("Failed to retrieve changes".to_string())
+        })?;
+
+    let oldest_seq = database::oldest_change_seq(pool).await
+        .map_err(|e| {
+            tracing::error!("Failed to get oldest change seq: {}", e);
+            ApiError::Internal("Failed to retrieve changes".to_string())
+        })?;
+    // 序列号连续递增，最早保留的序列号之前有缺口说明 since_seq 之后的变更已被清理
+    let reset_required = oldest_seq.is_some_and(|oldest| since_seq < oldest - 1);
+
+    let instance_id = database::get_instance_id(pool).await
+        .map_err(|e| {
+            tracing::error!("Failed to get instance id: {}", e);
+            ApiError::Internal("Failed to retrieve changes".to_string())
+        })?;
+
+    let next_seq = records.last().map(|r| r.seq).unwrap_or(since_seq);
+    let changes: Vec<ChangeEntry> = records.into_iter().map(ChangeEntry::from).collect();
+
+    Ok(success(ChangeFeedResponse {
+        changes,
+        next_seq,
+        latest_seq,
+        has_more,
+        reset_required,
+        instance_id,
+    }))
+}
+
+// ============ Peer Sync ============
+// 多实例双向同步：对端之间交换变更流，按字段最后写入优先解决冲突
+
+/// 获取同步对端列表
+pub async fn list_peers(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let peers = database::list_peers(state.database.pool(), false).await
+        .map_err(|e| {
+            tracing::error!("Failed to list sync peers: {}", e);
+            ApiError::Internal("Failed to retrieve sync peers".to_string())
+        })?;
+
+    Ok(success(peers))
+}
+
+/// 添加同步对端
+pub async fn create_peer(
+    State(state): State<AppState>,
+    Json(payload): Json<CreatePeerRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if payload.name.trim().is_empty() {
+        return Err(ApiError::Validation("Peer name cannot be empty".to_string()));
+    }
+    let base_url = payload.base_url.trim();
+    if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+        return Err(ApiError::Validation("base_url must start with http:// or https://".to_string()));
+    }
+
+    let peer = database::create_peer(state.database.pool(), payload).await
+        .map_err(|e| {
+            tracing::error!("Failed to create sync peer: {}", e);
+            if e.to_string().contains("UNIQUE constraint") {
+                ApiError::Conflict("Peer with this base_url already exists".to_string())
+            } else {
+                ApiError::Internal("Failed to create sync peer".to_string())
+            }
+        })?;
+
+    Ok(success(peer))
+}
+
+/// 删除同步对端
+pub async fn delete_peer(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    database::delete_peer(state.database.pool(), &id).await
+        .map_err(|e| {
+            tracing::error!("Failed to delete sync peer: {}", e);
+            if e.to_string().contains("not found") {
+                ApiError::NotFound("Peer not found".to_string())
+            } else {
+                ApiError::Internal("Failed to delete sync peer".to_string())
+            }
+        })?;
+
+    Ok(success_message("Peer deleted"))
+}
+
+/// 立即与对端同步一次
+pub async fn sync_peer(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let pool = state.database.pool();
+    let peer = database::get_peer(pool, &id).await
+        .map_err(|_| ApiError::NotFound("Peer not found".to_string()))?;
+
+    let result = peer_sync::run_peer_sync(pool, &peer).await
+        .map_err(|e| {
+            tracing::error!("Peer sync with {} failed: {}", peer.base_url, e);
+            ApiError::ExternalService(format!("Peer sync failed: {}", e))
+        })?;
+
+    Ok(success(result))
+}
+
+/// 接收对端推送的变更（必须设置 SYNC_PEER_TOKEN，请求需携带相同的 X-Sync-Token；未设置时拒绝推送）
+pub async fn push_changes(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<PushChangesRequest>,
+) -> ApiResult<impl IntoResponse> {
+    let expected = std::env::var("SYNC_PEER_TOKEN").unwrap_or_default();
+    if expected.is_empty() {
+        return Err(ApiError::Forbidden("Sync push is disabled (SYNC_PEER_TOKEN is not set)".to_string()));
+    }
+    let provided = headers.get(SYNC_TOKEN_HEADER).and_then(|v| v.to_str().ok());
+    if !provided.is_some_and(|token| peer_sync::verify_sync_token(&expected, token)) {
+        return Err(ApiError::Unauthorized("Invalid sync token".to_string()));
+    }
+    if payload.origin.trim().is_empty() {
+        return Err(ApiError::Validation("origin cannot be empty".to_string()));
+    }
+    if payload.changes.len() as i64 > MAX_CHANGES_LIMIT {
+        return Err(ApiError::Validation(format!("Too many changes (max {})", MAX_CHANGES_LIMIT)));
+    }
+
+    let result = peer_sync::apply_remote_changes(state.database.pool(), &payload.origin, &payload.changes).await
+        .map_err(|e| {
+            tracing::error!("Failed to apply pushed changes: {}", e);
+            ApiError::Internal("Failed to apply changes".to_string())
+        })?;
+
+    Ok(success(result))
+}
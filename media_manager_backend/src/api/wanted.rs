@@ -0,0 +1,494 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::database::{self, DatabaseRepository};
+use crate::models::{AddWantedRequest, MagnetTrackerSettings, SwarmStatsSettings, UpdateWantedRequest, MAX_SWARM_STATS_CONCURRENCY, ACQUISITION_STATES, DOWNLOAD_STATUSES, WANTED_ACTIONS, WANTED_STATUSES};
+use crate::external::aria2::{is_aria2_url, Aria2Client, ARIA2_SCHEMES};
+use crate::services::magnet::{self, MagnetUri};
+use crate::services::swarm_stats::refresh_swarm_stats;
+use crate::services::{TorrentClient, quality, download_importer::import_completed_downloads, wanted_monitor::{check_wanted_media, track_download}};
+use super::AppState;
+use super::error::{ApiError, ApiResult, ErrorCode};
+use super::response::{success, success_message};
+
+#[derive(Debug, Deserialize)]
+pub struct WantedListParams {
+    pub status: Option<String>,
+}
+
+fn validate_status(status: &str) -> Result<(), ApiError> {
+    if !WANTED_STATUSES.contains(&status) {
+        return Err(ApiError::Validation(format!(
+            "Invalid status '{}'. Must be one of: {}", status, WANTED_STATUSES.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn validate_download_status(status: &str) -> Result<(), ApiError> {
+    if !DOWNLOAD_STATUSES.contains(&status) {
+        return Err(ApiError::Validation(format!(
+            "Invalid status '{}'. Must be one of: {}", status, DOWNLOAD_STATUSES.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn validate_action(action: &str) -> Result<(), ApiError> {
+    if !WANTED_ACTIONS.contains(&action) {
+        return Err(ApiError::Validation(format!(
+            "Invalid action '{}'. Must be one of: {}", action, WANTED_ACTIONS.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// 获取想要列表
+pub async fn list_wanted_handler(
+    Query(params): Query<WantedListParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(ref status) = params.status {
+        validate_status(status)?;
+    }
+
+    let items = database::list_wanted(state.database.pool(), params.status.as_deref()).await
+        .map_err(|e| {
+            tracing::error!("Failed to list wanted media: {}", e);
+            ApiError::Internal("Failed to retrieve wanted list".to_string())
+        })?;
+
+    Ok(success(items))
+}
+
+/// 添加到想要列表（没有本地文件，或本地文件未达到期望质量的媒体）
+pub async fn add_wanted_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<AddWantedRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(ref action) = payload.action {
+        validate_action(action)?;
+    }
+
+    let exists = state.database.repository().media_exists(&payload.media_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to check media: {}", e);
+            ApiError::Internal("Failed to check media".to_string())
+        })?;
+    if !exists {
+        return Err(ApiError::coded(ErrorCode::MediaNotFound, "Media not found"));
+    }
+
+    // 已有本地文件时，只有未达到期望质量才允许加入（用于监控升级）
+    let local_paths = database::get_local_file_paths(state.database.pool(), &payload.media_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to check media files: {}", e);
+            ApiError::Internal("Failed to check media files".to_string())
+        })?;
+    if !local_paths.is_empty() {
+        let profiles = database::get_quality_profiles(state.database.pool()).await
+            .map_err(|e| {
+                tracing::error!("Failed to load quality profiles: {}", e);
+                ApiError::Internal("Failed to load quality profiles".to_string())
+            })?;
+        let profile = quality::default_profile(&profiles);
+        let current = quality::best_quality_of(local_paths.iter().map(|p| p.as_str()), &profile);
+        if quality::meets_cutoff(&current, &profile) {
+            return Err(ApiError::Conflict("Media already has local files meeting the quality profile".to_string()));
+        }
+    }
+
+    let wanted = database::add_wanted(state.database.pool(), payload).await
+        .map_err(|e| {
+            tracing::error!("Failed to add wanted media: {}", e);
+            if e.to_string().contains("UNIQUE constraint") {
+                ApiError::Conflict("Media already in wanted list".to_string())
+            } else {
+                ApiError::Internal("Failed to add to wanted list".to_string())
+            }
+        })?;
+
+    Ok(success(wanted))
+}
+
+/// 更新想要列表条目
+pub async fn update_wanted_handler(
+    Path(media_id): Path<String>,
+    State(state): State<AppState>,
+    Json(payload): Json<UpdateWantedRequest>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(ref status) = payload.status {
+        validate_status(status)?;
+    }
+    if let Some(ref action) = payload.action {
+        validate_action(action)?;
+    }
+
+    let wanted = database::update_wanted(state.database.pool(), &media_id, payload).await
+        .map_err(|e| {
+            tracing::error!("Failed to update wanted media: {}", e);
+            ApiError::Internal("Failed to update wanted item".to_string())
+        })?
+        .ok_or_else(|| ApiError::NotFound("Wanted item not found".to_string()))?;
+
+    Ok(success(wanted))
+}
+
+/// 从想要列表移除
+pub async fn remove_wanted_handler(
+    Path(media_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    database::remove_wanted(state.database.pool(), &media_id).await
+        .map_err(|e| {
+            tracing::error!("Failed to remove wanted media: {}", e);
+            if e.to_string().contains("not found") {
+                ApiError::NotFound("Wanted item not found".to_string())
+            } else {
+                ApiError::Internal("Failed to remove from wanted list".to_string())
+            }
+        })?;
+
+    Ok(success_message("Removed from wanted list"))
+}
+
+/// 立即执行一次磁力监控
+pub async fn check_wanted_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let torrent_client = TorrentClient::from_env();
+    let result = check_wanted_media(state.database.pool(), &state.plugin_manager, torrent_client.as_ref()).await
+        .map_err(|e| {
+            tracing::error!("Failed to check wanted media: {}", e);
+            ApiError::Internal("Failed to check wanted list".to_string())
+        })?;
+
+    Ok(success(result))
+}
+
+/// 将已找到的磁力链接推送到下载客户端
+pub async fn push_wanted_handler(
+    Path(media_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let wanted = database::get_wanted(state.database.pool(), &media_id).await
+        .map_err(|_| ApiError::NotFound("Wanted item not found".to_string()))?;
+
+    let (Some(magnet), Some(title)) = (wanted.found_magnet, wanted.found_title) else {
+        return Err(ApiError::BadRequest("No magnet found for this item yet".to_string()));
+    };
+
+    let client = TorrentClient::from_env()
+        .ok_or_else(|| ApiError::BadRequest("Torrent client not configured (TORRENT_CLIENT_URL)".to_string()))?;
+
+    // 找到资源后 tracker 设置可能已修改，推送前重新改写
+    let trackers = database::get_magnet_tracker_settings(state.database.pool()).await?;
+    let magnet = magnet::with_trackers(&magnet, &trackers);
+    client.add_magnet(&magnet).await
+        .map_err(|e| {
+            tracing::error!("Failed to push magnet: {}", e);
+            ApiError::ExternalService(format!("Failed to push to torrent client: {}", e))
+        })?;
+
+    database::mark_wanted_found(state.database.pool(), &media_id, "pushed", &title, &magnet, wanted.found_size.as_deref()).await
+        .map_err(|e| {
+            tracing::error!("Failed to update wanted media: {}", e);
+            ApiError::Internal("Failed to update wanted list".to_string())
+        })?;
+
+    if let Err(e) = track_download(state.database.pool(), &media_id, &title, &magnet).await {
+        tracing::warn!("Failed to track pushed download: {}", e);
+    }
+
+    Ok(success_message("Magnet pushed to torrent client"))
+}
+
+/// 获取推送到下载客户端的任务
+pub async fn list_downloads_handler(
+    Query(params): Query<WantedListParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(ref status) = params.status {
+        validate_download_status(status)?;
+    }
+
+    let downloads = database::list_torrent_downloads(state.database.pool(), params.status.as_deref()).await
+        .map_err(|e| {
+            tracing::error!("Failed to list downloads: {}", e);
+            ApiError::Internal("Failed to retrieve downloads".to_string())
+        })?;
+
+    Ok(success(downloads))
+}
+
+/// 立即检查下载进度并导入已完成的任务
+pub async fn import_downloads_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let torrent_client = TorrentClient::from_env();
+    let aria2_client = Aria2Client::from_env();
+    if torrent_client.is_none() && aria2_client.is_none() {
+        return Err(ApiError::BadRequest(
+            "No download client configured (TORRENT_CLIENT_URL or ARIA2_RPC_URL)".to_string()
+        ));
+    }
+
+    let result = import_completed_downloads(state.database.pool(), torrent_client.as_ref(), aria2_client.as_ref()).await
+        .map_err(|e| {
+            tracing::error!("Failed to import downloads: {}", e);
+            ApiError::Internal("Failed to import downloads".to_string())
+        })?;
+
+    Ok(success(result))
+}
+
+/// 重新导入失败的任务（下次检查时执行）
+pub async fn retry_download_handler(
+    Path(hash): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let pool = state.database.pool();
+    let hash = hash.to_lowercase();
+    let download = database::get_torrent_download(pool, &hash).await
+        .map_err(|e| {
+            tracing::error!("Failed to get download: {}", e);
+            ApiError::Internal("Failed to retrieve download".to_string())
+        })?
+        .ok_or_else(|| ApiError::NotFound("Download not found".to_string()))?;
+
+    if download.status != "failed" {
+        return Err(ApiError::Conflict(format!("Download is {}, only failed downloads can be retried", download.status)));
+    }
+
+    database::set_torrent_download_status(pool, &hash, "downloading", None, None).await
+        .map_err(|e| {
+            tracing::error!("Failed to update download: {}", e);
+            ApiError::Internal("Failed to update download".to_string())
+        })?;
+
+    Ok(success_message("Download will be imported again"))
+}
+
+// ============ Download Links ============
+
+#[derive(Debug, Deserialize)]
+pub struct DownloadLinkRequest {
+    pub media_id: String,
+    pub url: String,
+    pub name: Option<String>,
+}
+
+/// 推送结果
+#[derive(Debug, Serialize)]
+pub struct DownloadLinkResponse {
+    /// torrent（qBittorrent）或 aria2
+    pub client: String,
+    /// info hash 或 aria2 gid
+    pub id: String,
+}
+
+/// 推送下载链接：磁力链接交给下载客户端，HTTP/FTP 直链交给 aria2，完成后自动导入到媒体
+/// POST /api/download/link
+pub async fn download_link_handler(
+    State(state): State<AppState>,
+    Json(payload): Json<DownloadLinkRequest>,
+) -> ApiResult<impl IntoResponse> {
+    let pool = state.database.pool();
+    let url = payload.url.trim();
+    database::get_media_with(pool, &payload.media_id).await?
+        .ok_or_else(|| ApiError::NotFound("Media not found".to_string()))?;
+
+    if url.starts_with("magnet:") {
+        let magnet = MagnetUri::parse(url)
+            .ok_or_else(|| ApiError::Validation("Magnet link has no valid btih info hash".to_string()))?;
+        let client = TorrentClient::from_env()
+            .ok_or_else(|| ApiError::BadRequest("Torrent client not configured (TORRENT_CLIENT_URL)".to_string()))?;
+        let link = magnet::with_trackers(url, &database::get_magnet_tracker_settings(pool).await?);
+        client.add_magnet(&link).await
+            .map_err(|e| ApiError::ExternalService(format!("Failed to push to torrent client: {}", e)))?;
+        let name = payload.name.as_deref().or(magnet.display_name.as_deref()).unwrap_or(url);
+        track_download(pool, &payload.media_id, name, &link).await?;
+        return Ok(success(DownloadLinkResponse { client: "torrent".to_string(), id: magnet.info_hash }));
+    }
+
+    if !is_aria2_url(url) {
+        return Err(ApiError::Validation(format!(
+            "Unsupported download link (aria2 handles {} links): {}", ARIA2_SCHEMES.join("/"), url
+        )));
+    }
+    let client = Aria2Client::from_env()
+        .ok_or_else(|| ApiError::BadRequest("aria2 not configured (ARIA2_RPC_URL)".to_string()))?;
+    let gid = client.add_uri(url).await
+        .map_err(|e| ApiError::ExternalService(format!("Failed to push to aria2: {}", e)))?;
+    database::record_link_download(pool, &gid, &payload.media_id, payload.name.as_deref(), url).await?;
+
+    Ok(success(DownloadLinkResponse { client: "aria2".to_string(), id: gid }))
+}
+
+/// 获取推送到 aria2 的任务
+/// GET /api/download/links
+pub async fn list_link_downloads_handler(
+    Query(params): Query<WantedListParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(ref status) = params.status {
+        validate_download_status(status)?;
+    }
+    let downloads = database::list_link_downloads(state.database.pool(), params.status.as_deref()).await?;
+    Ok(success(downloads))
+}
+
+/// 重试失败的链接任务：aria2 中下载已完成时重新导入，否则重新添加下载
+/// POST /api/download/links/:gid/retry
+pub async fn retry_link_download_handler(
+    Path(gid): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let pool = state.database.pool();
+    let download = database::get_link_download(pool, &gid).await?
+        .ok_or_else(|| ApiError::NotFound("Download not found".to_string()))?;
+    if download.status != "failed" {
+        return Err(ApiError::Conflict(format!("Download is {}, only failed downloads can be retried", download.status)));
+    }
+    let client = Aria2Client::from_env()
+        .ok_or_else(|| ApiError::BadRequest("aria2 not configured (ARIA2_RPC_URL)".to_string()))?;
+
+    let completed = client.tell_status(&gid).await.is_ok_and(|status| status.is_complete());
+    if completed {
+        database::set_link_download_status(pool, &gid, "downloading", None, None).await?;
+        return Ok(success(DownloadLinkResponse { client: "aria2".to_string(), id: gid }));
+    }
+
+    let new_gid = client.add_uri(&download.url).await
+        .map_err(|e| ApiError::ExternalService(format!("Failed to push to aria2: {}", e)))?;
+    database::restart_link_download(pool, &gid, &new_gid).await?;
+    Ok(success(DownloadLinkResponse { client: "aria2".to_string(), id: new_gid }))
+}
+
+// ============ Acquisition ============
+
+#[derive(Debug, Deserialize)]
+pub struct AcquisitionListParams {
+    pub state: Option<String>,
+}
+
+/// 获取从 TMDB 保存的媒体的获取进度（wanted / downloading / imported），可按状态筛选
+/// GET /api/acquisition
+pub async fn list_acquisition_handler(
+    Query(params): Query<AcquisitionListParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    if let Some(ref acquisition_state) = params.state {
+        if !ACQUISITION_STATES.contains(&acquisition_state.as_str()) {
+            return Err(ApiError::Validation(format!(
+                "Invalid state '{}'. Must be one of: {}", acquisition_state, ACQUISITION_STATES.join(", ")
+            )));
+        }
+    }
+    let overview = database::list_acquisition(state.database.pool(), params.state.as_deref()).await?;
+    Ok(success(overview))
+}
+
+// ============ Magnet Trackers ============
+
+/// 获取磁力链接 tracker 设置
+/// GET /api/settings/magnet-trackers
+pub async fn get_magnet_tracker_settings_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let settings = database::get_magnet_tracker_settings(state.database.pool()).await?;
+    Ok(success(settings))
+}
+
+/// 保存磁力链接 tracker 设置
+/// PUT /api/settings/magnet-trackers
+pub async fn update_magnet_tracker_settings_handler(
+    State(state): State<AppState>,
+    Json(settings): Json<MagnetTrackerSettings>,
+) -> ApiResult<impl IntoResponse> {
+    settings.validate().map_err(ApiError::Validation)?;
+    database::save_magnet_tracker_settings(state.database.pool(), &settings).await?;
+    Ok(success(settings))
+}
+
+// ============ Magnet Swarm Stats ============
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SwarmStatsParams {
+    pub media_id: Option<String>,
+    /// 隐藏 tracker 返回 0 个做种者的磁力链接
+    pub alive_only: bool,
+}
+
+/// 做种人数刷新请求
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RefreshSwarmStatsRequest {
+    /// 要刷新的媒体（为空时刷新全部媒体）
+    pub ids: Vec<String>,
+    /// 同时查询的 tracker 数（默认使用设置中的值）
+    pub concurrency: Option<usize>,
+}
+
+/// 获取已保存磁力链接的做种/下载人数
+/// GET /api/magnets/stats?media_id=&alive_only=true
+pub async fn list_swarm_stats_handler(
+    Query(params): Query<SwarmStatsParams>,
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let stats = database::list_swarm_stats(state.database.pool(), params.media_id.as_deref(), params.alive_only).await?;
+    Ok(success(stats))
+}
+
+/// 立即向 tracker 查询做种人数
+/// POST /api/magnets/stats/refresh
+pub async fn refresh_swarm_stats_handler(
+    State(state): State<AppState>,
+    body: Option<Json<RefreshSwarmStatsRequest>>,
+) -> ApiResult<impl IntoResponse> {
+    let request = body.map(|Json(r)| r).unwrap_or_default();
+    let pool = state.database.pool();
+    let concurrency = match request.concurrency {
+        Some(concurrency) => concurrency,
+        None => database::get_swarm_stats_settings(pool).await?.concurrency,
+    };
+    if concurrency == 0 || concurrency > MAX_SWARM_STATS_CONCURRENCY {
+        return Err(ApiError::Validation(format!(
+            "concurrency must be between 1 and {}",
+            MAX_SWARM_STATS_CONCURRENCY
+        )));
+    }
+
+    let ids = (!request.ids.is_empty()).then_some(request.ids.as_slice());
+    let result = refresh_swarm_stats(pool, ids, concurrency).await
+        .map_err(|e| {
+            tracing::error!("Failed to refresh swarm stats: {}", e);
+            ApiError::Internal("Failed to refresh swarm stats".to_string())
+        })?;
+    Ok(success(result))
+}
+
+/// 获取做种人数刷新设置
+/// GET /api/settings/swarm-stats
+pub async fn get_swarm_stats_settings_handler(
+    State(state): State<AppState>,
+) -> ApiResult<impl IntoResponse> {
+    let settings = database::get_swarm_stats_settings(state.database.pool()).await?;
+    Ok(success(settings))
+}
+
+/// 保存做种人数刷新设置
+/// PUT /api/settings/swarm-stats
+pub async fn update_swarm_stats_settings_handler(
+    State(state): State<AppState>,
+    Json(settings): Json<SwarmStatsSettings>,
+) -> ApiResult<impl IntoResponse> {
+    settings.validate().map_err(ApiError::Validation)?;
+    database::save_swarm_stats_settings(state.database.pool(), &settings).await?;
+    Ok(success(settings))
+}
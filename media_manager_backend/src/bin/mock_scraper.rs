@@ -0,0 +1,356 @@
+//! 模拟刮削插件
+//!
+//! 按插件协议从 stdin 读取一行请求，返回固定的测试数据，不访问任何网站。
+//! 安装方式与普通插件相同（plugin.json 的 executable 指向本程序），
+//! 用于在 CI 中端到端测试刮削、批量刮削和文件扫描接口。
+//!
+//! 环境变量：
+//! - `MOCK_SCRAPER_FIXTURES`：替换内置测试数据的 JSON 文件路径
+//! - `MOCK_SCRAPER_DELAY_MS`：响应前等待的毫秒数（用于测试超时）
+
+use std::io::{BufRead, Write};
+
+use media_manager_backend::plugins::protocol::{
+    ActorProfile, ActorRoster, MagnetResult, PluginConfig, PluginInfo, PluginResponse, PluginResponseData,
+    ScrapeResult, SearchResponse,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const PLUGIN_ID: &str = "mock_scraper";
+const BUILTIN_FIXTURES: &str = include_str!("../../tests/fixtures/mock_scraper.json");
+
+/// 测试数据
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Fixtures {
+    items: Vec<ScrapeResult>,
+    /// 返回多个结果的查询 -> 结果番号列表
+    multiple: std::collections::HashMap<String, Vec<String>>,
+    magnets: Vec<MagnetResult>,
+}
+
+impl Fixtures {
+    fn load() -> Result<Self, String> {
+        let content = match std::env::var("MOCK_SCRAPER_FIXTURES") {
+            Ok(path) => std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?,
+            Err(_) => BUILTIN_FIXTURES.to_string(),
+        };
+        serde_json::from_str(&content).map_err(|e| format!("Invalid fixtures: {}", e))
+    }
+
+    fn by_code(&self, code: &str) -> Option<&ScrapeResult> {
+        self.items.iter().find(|item| item.code.as_deref().is_some_and(|c| c.eq_ignore_ascii_case(code.trim())))
+    }
+
+    /// 按番号、标题（忽略末尾的 "(年份)"）或 系列+发售日期 查找
+    fn lookup(&self, entry: &Value) -> Option<&ScrapeResult> {
+        let field = |key: &str| entry.get(key).and_then(Value::as_str).map(str::trim).filter(|s| !s.is_empty());
+
+        if let Some(item) = field("code").or_else(|| field("id")).and_then(|code| self.by_code(code)) {
+            return Some(item);
+        }
+        if let Some(title) = field("title") {
+            let title = strip_year(title);
+            if let Some(item) = self.items.iter().find(|item| item.title.eq_ignore_ascii_case(title)) {
+                return Some(item);
+            }
+        }
+        if let (Some(series), Some(date)) = (field("series"), field("release_date")) {
+            return self.items.iter().find(|item| {
+                item.series.as_deref().is_some_and(|s| s.eq_ignore_ascii_case(series))
+                    && item.release_date.as_deref() == Some(date)
+            });
+        }
+        None
+    }
+}
+
+fn strip_year(title: &str) -> &str {
+    match title.rfind(" (") {
+        Some(pos) if title.ends_with(')') => &title[..pos],
+        _ => title,
+    }
+}
+
+/// 刮削结果加上 source 字段（主程序用它选择缓存配置）
+fn scrape_data(item: &ScrapeResult) -> Value {
+    let mut value = serde_json::to_value(item).unwrap_or_default();
+    if let Some(object) = value.as_object_mut() {
+        object.insert("source".to_string(), json!(PLUGIN_ID));
+    }
+    value
+}
+
+fn error_response(message: impl Into<String>) -> Value {
+    json!({ "success": false, "error": message.into() })
+}
+
+fn not_found(query: &str) -> Value {
+    json!({
+        "success": false,
+        "error": {
+            "code": "not_found",
+            "message": { "zh": format!("未找到: {}", query), "en": format!("Not found: {}", query) }
+        }
+    })
+}
+
+fn respond(response: PluginResponse) -> Value {
+    serde_json::to_value(response).unwrap_or_else(|e| error_response(e.to_string()))
+}
+
+fn list_response(results: Vec<ScrapeResult>) -> Value {
+    let total = results.len() as u32;
+    respond(PluginResponse::success(PluginResponseData::List(SearchResponse {
+        results,
+        page: 1,
+        total_pages: Some(1),
+        total_results: Some(total),
+    })))
+}
+
+/// 插件信息（优先读取当前目录的 plugin.json，与安装时的ID保持一致）
+fn plugin_info() -> PluginInfo {
+    let config = std::fs::read_to_string("plugin.json").ok()
+        .and_then(|content| serde_json::from_str::<PluginConfig>(&content).ok());
+    match config {
+        Some(config) => PluginInfo {
+            id: config.id,
+            name: config.name,
+            version: config.version,
+            description: config.description,
+            author: config.author,
+            id_patterns: config.id_patterns,
+            supports_search: config.supports_search,
+            url_domains: config.url_domains,
+            resolve_domains: config.resolve_domains,
+            capabilities: config.capabilities,
+            scrapers: config.scrapers,
+        },
+        None => PluginInfo {
+            id: PLUGIN_ID.to_string(),
+            name: "Mock Scraper".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            description: Some("Deterministic fixtures for integration tests".to_string()),
+            author: None,
+            id_patterns: vec!["^MOCK-".to_string()],
+            supports_search: true,
+            url_domains: vec!["mock.invalid".to_string()],
+            resolve_domains: vec!["pan.mock.invalid".to_string()],
+            capabilities: vec!["search_magnets".to_string(), "upcoming".to_string(), "releases".to_string(), "list_actors".to_string()],
+            scrapers: Vec::new(),
+        },
+    }
+}
+
+fn handle_get(fixtures: &Fixtures, request: &Value) -> Value {
+    let id = request.get("id").and_then(Value::as_str).unwrap_or_default();
+    if let Some(codes) = fixtures.multiple.iter().find(|(query, _)| query.eq_ignore_ascii_case(id)).map(|(_, codes)| codes) {
+        let results: Vec<Value> = codes.iter().filter_map(|code| fixtures.by_code(code)).map(scrape_data).collect();
+        return json!({
+            "success": true,
+            "mode": "multiple",
+            "total_count": results.len(),
+            "results": results,
+        });
+    }
+    match fixtures.lookup(&json!({ "code": id, "title": id })) {
+        Some(item) => json!({ "success": true, "data": scrape_data(item) }),
+        None => not_found(id),
+    }
+}
+
+fn handle_batch(fixtures: &Fixtures, request: &Value) -> Value {
+    let media_list = request.get("media_list").and_then(Value::as_array).cloned().unwrap_or_default();
+    let total = media_list.len();
+    let mut results = Vec::with_capacity(total);
+
+    for (index, entry) in media_list.iter().enumerate() {
+        let media_id = entry.get("id").and_then(Value::as_str).unwrap_or_default();
+        let item_name = ["code", "title", "series"].iter()
+            .find_map(|key| entry.get(*key).and_then(Value::as_str).filter(|s| !s.is_empty()))
+            .unwrap_or(media_id);
+        let found = fixtures.lookup(&json!({
+            "code": entry.get("code"),
+            "title": entry.get("title"),
+            "series": entry.get("series"),
+            "release_date": entry.get("release_date"),
+        }));
+        let status = if found.is_some() { "completed" } else { "failed" };
+        eprintln!("PROGRESS:{}", json!({
+            "current": index + 1,
+            "total": total,
+            "item_name": item_name,
+            "status": status,
+            "error": if found.is_some() { Value::Null } else { json!(format!("Not found: {}", item_name)) },
+        }));
+
+        results.push(match found {
+            Some(item) => json!({ "media_id": media_id, "success": true, "data": scrape_data(item) }),
+            None => json!({ "media_id": media_id, "success": false, "error": format!("Not found: {}", item_name) }),
+        });
+    }
+    json!({ "success": true, "data": results })
+}
+
+/// 网盘链接解析：返回分享链接 direct 参数中的地址，提取码通过 Cookie 请求头传递
+fn handle_resolve(request: &Value) -> Value {
+    let url = request.get("url").and_then(Value::as_str).unwrap_or_default();
+    let direct = url::Url::parse(url).ok()
+        .and_then(|u| u.query_pairs().find(|(key, _)| key == "direct").map(|(_, value)| value.into_owned()));
+    let Some(direct) = direct else {
+        return not_found(url);
+    };
+    let mut headers = serde_json::Map::new();
+    if let Some(password) = request.get("password").and_then(Value::as_str) {
+        headers.insert("Cookie".to_string(), json!(format!("pwd={}", password)));
+    }
+    json!({
+        "success": true,
+        "data": {
+            "url": direct,
+            "file_name": direct.rsplit('/').next(),
+            "headers": headers,
+        }
+    })
+}
+
+/// 厂商演员名单：出现在该厂商作品中的全部演员（按首次出现顺序）
+fn handle_list_actors(fixtures: &Fixtures, studio: &str) -> Value {
+    let mut names: Vec<&String> = Vec::new();
+    for item in fixtures.items.iter().filter(|item| item.studio.as_deref() == Some(studio)) {
+        for name in &item.actors {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    let actors = names.into_iter()
+        .map(|name| ActorProfile {
+            name: name.clone(),
+            avatar_url: Some(format!("https://mock.invalid/actors/{}.jpg", name.replace(' ', "_"))),
+            biography: Some(format!("{} is a performer at {}", name, studio)),
+            ..Default::default()
+        })
+        .collect();
+    respond(PluginResponse::success(PluginResponseData::Actors(ActorRoster { actors })))
+}
+
+/// 批量演员刮削：出现在任一作品中的演员返回资料，其余视为失败（不出现在结果中）
+fn handle_batch_actors(fixtures: &Fixtures, request: &Value) -> Value {
+    let names: Vec<String> = request.get("actor_names").and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).map(String::from).collect())
+        .unwrap_or_default();
+    let total = names.len();
+    let mut results = Vec::new();
+
+    for (index, name) in names.iter().enumerate() {
+        let found = fixtures.items.iter().any(|item| item.actors.contains(name));
+        eprintln!("PROGRESS:{}", json!({
+            "current": index + 1,
+            "total": total,
+            "item_name": name,
+            "status": if found { "completed" } else { "failed" },
+            "error": if found { Value::Null } else { json!(format!("Not found: {}", name)) },
+        }));
+        if found {
+            let slug = name.replace(' ', "_");
+            results.push(json!({
+                "name": name,
+                "avatar_url": format!("https://mock.invalid/actors/{}.jpg", slug),
+                "photo_urls": [format!("https://mock.invalid/actors/{}_1.jpg", slug)],
+                "biography": format!("{} is a mock performer", name),
+            }));
+        }
+    }
+    json!({ "success": true, "data": results })
+}
+
+/// 磁力搜索：模拟两个同时搜索的网站，各自完成时输出结果（第二个网站的结果与第一个 hash 相同、
+/// 链接参数不同），最终返回未去重的全部结果，由主程序去重
+fn handle_search_magnets(fixtures: &Fixtures, query: &str) -> Value {
+    let query = query.to_lowercase();
+    let magnets: Vec<MagnetResult> = fixtures.magnets.iter()
+        .filter(|m| m.title.to_lowercase().contains(&query))
+        .cloned()
+        .collect();
+    let mirrored: Vec<MagnetResult> = magnets.iter()
+        .map(|m| MagnetResult { magnet_link: format!("{}&dn=mirror", m.magnet_link), ..m.clone() })
+        .collect();
+
+    for (site, results) in [("MockSite", &magnets), ("MockMirror", &mirrored)] {
+        eprintln!("PROGRESS:{}", json!({
+            "success": true,
+            "data": { "site_name": site, "status": "completed", "result_count": results.len(), "results": results },
+        }));
+    }
+    json!({ "success": true, "data": magnets.iter().chain(&mirrored).collect::<Vec<_>>() })
+}
+
+fn handle(fixtures: &Fixtures, request: &Value) -> Value {
+    let text = |key: &str| request.get(key).and_then(Value::as_str).unwrap_or_default().to_string();
+    match request.get("action").and_then(Value::as_str) {
+        Some("info") => respond(PluginResponse::success(PluginResponseData::Info(plugin_info()))),
+        Some("get") => handle_get(fixtures, request),
+        Some("search") => {
+            let query = text("query").to_lowercase();
+            list_response(fixtures.items.iter()
+                .filter(|item| {
+                    item.title.to_lowercase().contains(&query)
+                        || item.code.as_deref().is_some_and(|c| c.to_lowercase().contains(&query))
+                })
+                .cloned()
+                .collect())
+        }
+        Some("scrape_url") => {
+            let url = text("url").to_uppercase();
+            match fixtures.items.iter().find(|item| item.code.as_deref().is_some_and(|c| url.contains(&c.to_uppercase()))) {
+                Some(item) => respond(PluginResponse::success(PluginResponseData::Single(item.clone()))),
+                None => not_found(&url),
+            }
+        }
+        Some("upcoming") | Some("releases") => {
+            let name = text("name");
+            let target_type = text("target_type");
+            list_response(fixtures.items.iter()
+                .filter(|item| match target_type.as_str() {
+                    "series" => item.series.as_deref() == Some(name.as_str()),
+                    "studio" => item.studio.as_deref() == Some(name.as_str()),
+                    "actor" => item.actors.contains(&name),
+                    _ => false,
+                })
+                .cloned()
+                .collect())
+        }
+        Some("search_magnets") => handle_search_magnets(fixtures, &text("query")),
+        Some("resolve_link") => handle_resolve(request),
+        Some("list_actors") => handle_list_actors(fixtures, &text("studio")),
+        Some("batch_scrape_media") => handle_batch(fixtures, request),
+        Some("batch_scrape_actors") => handle_batch_actors(fixtures, request),
+        Some(action) => error_response(format!("Unknown action: {}", action)),
+        None => error_response("Missing action"),
+    }
+}
+
+fn main() {
+    let mut line = String::new();
+    // stdin 关闭且没有请求时直接退出
+    if std::io::stdin().lock().read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+        return;
+    }
+
+    if let Some(delay) = std::env::var("MOCK_SCRAPER_DELAY_MS").ok().and_then(|v| v.parse().ok()) {
+        std::thread::sleep(std::time::Duration::from_millis(delay));
+    }
+
+    let response = match (Fixtures::load(), serde_json::from_str::<Value>(line.trim())) {
+        (Err(e), _) => error_response(e),
+        (_, Err(e)) => error_response(format!("Invalid request: {}", e)),
+        (Ok(fixtures), Ok(request)) => handle(&fixtures, &request),
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", response);
+    let _ = stdout.flush();
+}
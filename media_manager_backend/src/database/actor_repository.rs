@@ -1,9 +1,16 @@
-use sqlx::SqlitePool;
+use std::collections::HashMap;
+use sqlx::{sqlite::SqliteRow, QueryBuilder, Row, Sqlite, SqliteConnection, SqlitePool};
 use crate::models::{
     Actor, ActorMedia, ActorWithWorkCount, ActorFilmography, 
     ActorDetailResponse, MediaActor, CreateActorRequest, UpdateActorRequest,
-    ActorSearchFilters, ActorListResponse,
+    ActorSearchFilters, ActorListResponse, ActorPhotoCacheSettings, PreviewActor, ActorScene,
+    DuplicateActorCandidate, ActorScrapeRun,
 };
+use super::settings_repository::{get_setting, set_setting};
+
+const ACTOR_INSERT_COLUMNS: usize = 11;
+const ACTOR_MEDIA_INSERT_COLUMNS: usize = 6;
+const ACTOR_PHOTO_CACHE_KEY: &str = "actor_photo_cache";
 
 /// 创建演员（从请求）
 pub async fn create_actor(pool: &SqlitePool, request: CreateActorRequest) -> Result<Actor, sqlx::Error> {
@@ -15,6 +22,12 @@ pub async fn create_actor(pool: &SqlitePool, request: CreateActorRequest) -> Res
 
 /// 插入演员（从 Actor 对象）
 pub async fn insert_actor(pool: &SqlitePool, actor: &Actor) -> Result<(), sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    insert_actor_with(&mut conn, actor).await
+}
+
+/// 插入演员（在给定的连接或事务中执行）
+pub async fn insert_actor_with(conn: &mut SqliteConnection, actor: &Actor) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
         INSERT INTO actors (id, name, avatar_url, photo_url, poster_url, backdrop_url, biography, birth_date, nationality, created_at, updated_at)
@@ -32,7 +45,7 @@ pub async fn insert_actor(pool: &SqlitePool, actor: &Actor) -> Result<(), sqlx::
     .bind(&actor.nationality)
     .bind(&actor.created_at)
     .bind(&actor.updated_at)
-    .execute(pool)
+    .execute(conn)
     .await?;
     
     Ok(())
@@ -48,13 +61,28 @@ pub async fn get_actor(pool: &SqlitePool, id: &str) -> Result<Option<Actor>, sql
     .await
 }
 
-/// 根据名字查找演员
+/// 根据名字查找演员（名字不存在时按别名查找）
 pub async fn find_actor_by_name(pool: &SqlitePool, name: &str) -> Result<Option<Actor>, sqlx::Error> {
-    sqlx::query_as::<_, Actor>(
+    let actor = sqlx::query_as::<_, Actor>(
         "SELECT * FROM actors WHERE name = ? COLLATE NOCASE"
     )
     .bind(name)
     .fetch_optional(pool)
+    .await?;
+    
+    match actor {
+        Some(actor) => Ok(Some(actor)),
+        None => find_actor_by_alias_with(&mut *pool.acquire().await?, name).await,
+    }
+}
+
+/// 根据别名查找演员
+async fn find_actor_by_alias_with(conn: &mut SqliteConnection, alias: &str) -> Result<Option<Actor>, sqlx::Error> {
+    sqlx::query_as::<_, Actor>(
+        "SELECT a.* FROM actors a INNER JOIN actor_aliases al ON al.actor_id = a.id WHERE al.alias = ?"
+    )
+    .bind(alias)
+    .fetch_optional(conn)
     .await
 }
 
@@ -123,6 +151,11 @@ pub async fn delete_actor(pool: &SqlitePool, id: &str) -> Result<bool, sqlx::Err
         .execute(pool)
         .await?;
     
+    sqlx::query("DELETE FROM favorite_entities WHERE entity_type = 'actor' AND entity_id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    
     // 再删除演员本身
     let result = sqlx::query("DELETE FROM actors WHERE id = ?")
         .bind(id)
@@ -132,76 +165,69 @@ pub async fn delete_actor(pool: &SqlitePool, id: &str) -> Result<bool, sqlx::Err
     Ok(result.rows_affected() > 0)
 }
 
-/// 列出演员（带分页和搜索）
+/// 添加演员列表的过滤条件（WHERE ... GROUP BY a.id HAVING ...，查询需 LEFT JOIN actor_media am）
+fn push_actor_filters<'a>(builder: &mut QueryBuilder<'a, Sqlite>, filters: &'a ActorSearchFilters) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(query) = filters.query.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        builder.push(" AND a.name LIKE ").push_bind(format!("%{}%", query));
+    }
+    if let Some(nationality) = filters.nationality.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        builder.push(" AND a.nationality = ").push_bind(nationality).push(" COLLATE NOCASE");
+    }
+    if let Some(has_photo) = filters.has_photo {
+        let condition = "(COALESCE(a.avatar_url, '') != '' OR COALESCE(a.photo_url, '') != '' OR COALESCE(a.poster_url, '') != '')";
+        builder.push(if has_photo { " AND " } else { " AND NOT " }).push(condition);
+    }
+    if let Some(studio) = filters.studio.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        // 出演过该厂商的作品
+        builder.push(
+            " AND EXISTS (SELECT 1 FROM actor_media sm JOIN media_items m ON m.id = sm.media_id \
+               WHERE sm.actor_id = a.id AND m.studio = "
+        ).push_bind(studio).push(" COLLATE NOCASE)");
+    }
+    if let Some(co_star_id) = filters.co_star_id.as_deref() {
+        // 与该演员合作过（同一作品），不含其本人
+        builder.push(" AND a.id != ").push_bind(co_star_id).push(
+            " AND EXISTS (SELECT 1 FROM actor_media x JOIN actor_media y ON y.media_id = x.media_id \
+               WHERE x.actor_id = a.id AND y.actor_id = "
+        ).push_bind(co_star_id).push(")");
+    }
+    builder.push(" GROUP BY a.id");
+    if let Some(min_media_count) = filters.min_media_count {
+        builder.push(" HAVING COUNT(am.id) >= ").push_bind(min_media_count);
+    }
+}
+
+/// 列出演员（带分页、搜索、过滤和排序）
 pub async fn list_actors(pool: &SqlitePool, filters: &ActorSearchFilters) -> Result<ActorListResponse, sqlx::Error> {
     let limit = filters.limit.unwrap_or(20);
     let offset = filters.offset.unwrap_or(0);
+    let order_by = filters.sort_by.order_by(filters.sort_order.as_ref(), "a.name", "work_count", "a.created_at");
     
     // 使用 LEFT JOIN 一次性获取演员和作品数量，避免 N+1 查询
-    let (actors_with_count, total) = if let Some(ref query) = filters.query {
-        let search_pattern = format!("%{}%", query);
-        
-        // 查询演员列表和作品数量
-        let actors_with_count: Vec<ActorWithWorkCount> = sqlx::query_as(
-            r#"
-            SELECT 
-                a.id, a.name, a.avatar_url, a.photo_url, a.poster_url, 
-                a.backdrop_url, a.biography, a.birth_date, a.nationality,
-                a.created_at, a.updated_at,
-                COUNT(am.id) as work_count
-            FROM actors a
-            LEFT JOIN actor_media am ON a.id = am.actor_id
-            WHERE a.name LIKE ?
-            GROUP BY a.id
-            ORDER BY a.name ASC
-            LIMIT ? OFFSET ?
-            "#
-        )
-        .bind(&search_pattern)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(pool)
-        .await?;
-        
-        // 查询总数
-        let total: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM actors WHERE name LIKE ?"
-        )
-        .bind(&search_pattern)
-        .fetch_one(pool)
-        .await?;
-        
-        (actors_with_count, total.0)
-    } else {
-        // 查询演员列表和作品数量
-        let actors_with_count: Vec<ActorWithWorkCount> = sqlx::query_as(
-            r#"
-            SELECT 
-                a.id, a.name, a.avatar_url, a.photo_url, a.poster_url, 
-                a.backdrop_url, a.biography, a.birth_date, a.nationality,
-                a.created_at, a.updated_at,
-                COUNT(am.id) as work_count
-            FROM actors a
-            LEFT JOIN actor_media am ON a.id = am.actor_id
-            GROUP BY a.id
-            ORDER BY a.name ASC
-            LIMIT ? OFFSET ?
-            "#
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(pool)
-        .await?;
-        
-        // 查询总数
-        let total: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM actors"
-        )
-        .fetch_one(pool)
-        .await?;
-        
-        (actors_with_count, total.0)
-    };
+    let mut builder = QueryBuilder::<Sqlite>::new(
+        r#"SELECT 
+            a.id, a.name, a.avatar_url, a.photo_url, a.poster_url, 
+            a.backdrop_url, a.biography, a.birth_date, a.nationality,
+            a.created_at, a.updated_at,
+            COUNT(am.id) as work_count
+        FROM actors a
+        LEFT JOIN actor_media am ON a.id = am.actor_id"#
+    );
+    push_actor_filters(&mut builder, filters);
+    builder.push(format!(" ORDER BY {}, a.id LIMIT ", order_by))
+        .push_bind(limit)
+        .push(" OFFSET ")
+        .push_bind(offset);
+    let actors_with_count: Vec<ActorWithWorkCount> = builder.build_query_as().fetch_all(pool).await?;
+    
+    // 查询总数
+    let mut builder = QueryBuilder::<Sqlite>::new(
+        "SELECT COUNT(*) FROM (SELECT a.id FROM actors a LEFT JOIN actor_media am ON a.id = am.actor_id"
+    );
+    push_actor_filters(&mut builder, filters);
+    builder.push(")");
+    let (total,): (i64,) = builder.build_query_as().fetch_one(pool).await?;
     
     Ok(ActorListResponse {
         actors: actors_with_count,
@@ -247,17 +273,26 @@ pub async fn get_actor_with_filmography(pool: &SqlitePool, id: &str) -> Result<O
 
 /// 按名称查找或创建演员
 pub async fn find_or_create_actor_by_name(pool: &SqlitePool, name: &str) -> Result<Actor, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    find_or_create_actor_by_name_with(&mut conn, name).await
+}
+
+/// 按名称查找或创建演员（在给定的连接或事务中执行）
+pub async fn find_or_create_actor_by_name_with(conn: &mut SqliteConnection, name: &str) -> Result<Actor, sqlx::Error> {
     // 先查找
     let existing: Option<Actor> = sqlx::query_as(
         "SELECT * FROM actors WHERE name = ?"
     )
     .bind(name)
-    .fetch_optional(pool)
+    .fetch_optional(&mut *conn)
     .await?;
     
     if let Some(actor) = existing {
         return Ok(actor);
     }
+    if let Some(actor) = find_actor_by_alias_with(conn, name).await? {
+        return Ok(actor);
+    }
     
     // 不存在则创建
     let request = CreateActorRequest {
@@ -272,7 +307,87 @@ pub async fn find_or_create_actor_by_name(pool: &SqlitePool, name: &str) -> Resu
         nationality: None,
     };
     
-    create_actor(pool, request).await
+    let actor = Actor::from_create_request(request)
+        .map_err(|e| sqlx::Error::Protocol(format!("Validation error: {}", e)))?;
+    insert_actor_with(conn, &actor).await?;
+    Ok(actor)
+}
+
+/// 按名称批量查找或创建演员（一次查出已有演员，缺少的用多行 INSERT 创建），按传入顺序返回（去重）
+pub async fn find_or_create_actors_by_names(pool: &SqlitePool, names: &[&str]) -> Result<Vec<Actor>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    find_or_create_actors_by_names_with(&mut conn, names).await
+}
+
+/// 按名称批量查找或创建演员（在给定的连接或事务中执行）
+pub async fn find_or_create_actors_by_names_with(
+    conn: &mut SqliteConnection,
+    names: &[&str],
+) -> Result<Vec<Actor>, sqlx::Error> {
+    let mut unique: Vec<&str> = Vec::with_capacity(names.len());
+    for name in names {
+        if !unique.contains(name) {
+            unique.push(name);
+        }
+    }
+    
+    let mut found: HashMap<String, Actor> = HashMap::new();
+    for chunk in unique.chunks(crate::database::MAX_BIND_PARAMS) {
+        let mut builder = QueryBuilder::<Sqlite>::new("SELECT * FROM actors WHERE name IN (");
+        let mut separated = builder.separated(", ");
+        for name in chunk {
+            separated.push_bind(*name);
+        }
+        separated.push_unseparated(")");
+        let actors: Vec<Actor> = builder.build_query_as().fetch_all(&mut *conn).await?;
+        for actor in actors {
+            found.entry(actor.name.clone()).or_insert(actor);
+        }
+    }
+    
+    // 名字不存在时按别名查找（合并过的演员不会被重新创建）
+    for name in &unique {
+        if found.contains_key(*name) {
+            continue;
+        }
+        if let Some(actor) = find_actor_by_alias_with(conn, name).await? {
+            found.insert(name.to_string(), actor);
+        }
+    }
+    
+    let missing: Vec<Actor> = unique.iter()
+        .filter(|name| !found.contains_key(**name))
+        .map(|name| Actor::new(name.to_string()))
+        .collect();
+    for chunk in missing.chunks(crate::database::MAX_BIND_PARAMS / ACTOR_INSERT_COLUMNS) {
+        let mut builder = QueryBuilder::<Sqlite>::new(
+            "INSERT INTO actors (id, name, avatar_url, photo_url, poster_url, backdrop_url, biography, birth_date, nationality, created_at, updated_at) "
+        );
+        builder.push_values(chunk, |mut row, actor| {
+            row.push_bind(&actor.id)
+                .push_bind(&actor.name)
+                .push_bind(&actor.avatar_url)
+                .push_bind(&actor.photo_url)
+                .push_bind(&actor.poster_url)
+                .push_bind(&actor.backdrop_url)
+                .push_bind(&actor.biography)
+                .push_bind(&actor.birth_date)
+                .push_bind(&actor.nationality)
+                .push_bind(actor.created_at)
+                .push_bind(actor.updated_at);
+        });
+        builder.build().execute(&mut *conn).await?;
+    }
+    for actor in missing {
+        found.insert(actor.name.clone(), actor);
+    }
+    
+    // 名字和别名可能指向同一演员，按 ID 去重
+    let mut seen = std::collections::HashSet::new();
+    Ok(unique.iter()
+        .filter_map(|name| found.remove(*name))
+        .filter(|actor| seen.insert(actor.id.clone()))
+        .collect())
 }
 
 /// 添加演员到媒体
@@ -282,6 +397,18 @@ pub async fn add_actor_to_media(
     media_id: &str, 
     character_name: Option<String>,
     role: Option<String>,
+) -> Result<ActorMedia, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    add_actor_to_media_with(&mut conn, actor_id, media_id, character_name, role).await
+}
+
+/// 添加演员到媒体（在给定的连接或事务中执行）
+pub async fn add_actor_to_media_with(
+    conn: &mut SqliteConnection,
+    actor_id: &str,
+    media_id: &str,
+    character_name: Option<String>,
+    role: Option<String>,
 ) -> Result<ActorMedia, sqlx::Error> {
     // 检查是否已存在
     let existing: Option<ActorMedia> = sqlx::query_as(
@@ -289,7 +416,7 @@ pub async fn add_actor_to_media(
     )
     .bind(actor_id)
     .bind(media_id)
-    .fetch_optional(pool)
+    .fetch_optional(&mut *conn)
     .await?;
     
     if let Some(relation) = existing {
@@ -315,12 +442,64 @@ pub async fn add_actor_to_media(
     .bind(&relation.character_name)
     .bind(&relation.role)
     .bind(&relation.created_at)
-    .execute(pool)
+    .execute(conn)
     .await?;
     
     Ok(relation)
 }
 
+/// 批量添加演员到媒体（多行 INSERT，已有的关联保持不变），返回新增的关联数
+pub async fn add_actors_to_media_with(
+    conn: &mut SqliteConnection,
+    media_id: &str,
+    actor_ids: &[String],
+    role: &str,
+) -> Result<u64, sqlx::Error> {
+    let relations: Vec<ActorMedia> = actor_ids.iter()
+        .map(|actor_id| ActorMedia::new(actor_id.clone(), media_id.to_string(), None, Some(role.to_string())))
+        .collect();
+    let mut added = 0;
+    for chunk in relations.chunks(crate::database::MAX_BIND_PARAMS / ACTOR_MEDIA_INSERT_COLUMNS) {
+        let mut builder = QueryBuilder::<Sqlite>::new(
+            "INSERT OR IGNORE INTO actor_media (id, actor_id, media_id, character_name, role, created_at) "
+        );
+        builder.push_values(chunk, |mut row, relation| {
+            row.push_bind(&relation.id)
+                .push_bind(&relation.actor_id)
+                .push_bind(&relation.media_id)
+                .push_bind(&relation.character_name)
+                .push_bind(&relation.role)
+                .push_bind(relation.created_at);
+        });
+        added += builder.build().execute(&mut *conn).await?.rows_affected();
+    }
+    Ok(added)
+}
+
+/// 按名字把演员关联到演职员表中有该名字、但尚未关联的媒体，返回新增的关联数
+pub async fn link_actor_to_media_by_name(pool: &SqlitePool, actor_id: &str, name: &str) -> Result<u64, sqlx::Error> {
+    let media_ids: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT m.id
+        FROM media_items m, json_each(CASE WHEN json_valid(m.cast) THEN m.cast ELSE '[]' END) c
+        WHERE (CASE WHEN c.type = 'object' THEN json_extract(c.value, '$.name') ELSE c.value END) = ?
+          AND m.id NOT IN (SELECT media_id FROM actor_media WHERE actor_id = ?)
+        "#
+    )
+    .bind(name)
+    .bind(actor_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut conn = pool.acquire().await?;
+    let actor_ids = [actor_id.to_string()];
+    let mut added = 0;
+    for media_id in media_ids {
+        added += add_actors_to_media_with(&mut conn, &media_id, &actor_ids, "cast").await?;
+    }
+    Ok(added)
+}
+
 /// 从媒体移除演员
 pub async fn remove_actor_from_media(pool: &SqlitePool, actor_id: &str, media_id: &str) -> Result<bool, sqlx::Error> {
     let result = sqlx::query(
@@ -367,3 +546,273 @@ pub async fn get_media_ids_for_actor(pool: &SqlitePool, actor_id: &str) -> Resul
     
     Ok(rows.into_iter().map(|r| r.0).collect())
 }
+
+/// 标注预览图中出现的演员（已有的标注保持不变），返回新增的标注数
+pub async fn add_preview_actors(
+    pool: &SqlitePool,
+    media_id: &str,
+    preview_index: i64,
+    actor_ids: &[String],
+) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let now = chrono::Utc::now();
+    let mut added = 0;
+    for actor_id in actor_ids {
+        added += sqlx::query(
+            "INSERT OR IGNORE INTO preview_actor_tags (media_id, preview_index, actor_id, created_at) VALUES (?, ?, ?, ?)"
+        )
+        .bind(media_id)
+        .bind(preview_index)
+        .bind(actor_id)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+    tx.commit().await?;
+    Ok(added)
+}
+
+/// 移除预览图中的演员标注
+pub async fn remove_preview_actor(
+    pool: &SqlitePool,
+    media_id: &str,
+    preview_index: i64,
+    actor_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM preview_actor_tags WHERE media_id = ? AND preview_index = ? AND actor_id = ?"
+    )
+    .bind(media_id)
+    .bind(preview_index)
+    .bind(actor_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// 获取媒体各预览图中标注的演员
+pub async fn get_preview_actors_for_media(pool: &SqlitePool, media_id: &str) -> Result<Vec<PreviewActor>, sqlx::Error> {
+    sqlx::query_as::<_, PreviewActor>(
+        r#"
+        SELECT p.preview_index, p.actor_id, a.name AS actor_name, a.avatar_url, p.created_at
+        FROM preview_actor_tags p
+        INNER JOIN actors a ON p.actor_id = a.id
+        WHERE p.media_id = ?
+        ORDER BY p.preview_index, a.name
+        "#
+    )
+    .bind(media_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// 获取演员出镜的预览图（演员详情页画廊）
+pub async fn get_actor_scenes(pool: &SqlitePool, actor_id: &str) -> Result<Vec<ActorScene>, sqlx::Error> {
+    sqlx::query_as::<_, ActorScene>(
+        r#"
+        SELECT p.media_id, m.title AS media_title, p.preview_index,
+               CASE WHEN json_valid(m.preview_urls)
+                    THEN json_extract(m.preview_urls, '$[' || p.preview_index || ']') END AS preview_url,
+               p.created_at
+        FROM preview_actor_tags p
+        INNER JOIN media_items m ON p.media_id = m.id
+        WHERE p.actor_id = ?
+        ORDER BY m.year DESC, m.title, p.preview_index
+        "#
+    )
+    .bind(actor_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// 获取图片仍是远程 URL 的演员（用于补缓存）
+pub async fn list_actors_with_remote_images(pool: &SqlitePool) -> Result<Vec<Actor>, sqlx::Error> {
+    sqlx::query_as::<_, Actor>(
+        r#"SELECT * FROM actors
+           WHERE avatar_url LIKE 'http%' OR photo_url LIKE '%http%'
+              OR poster_url LIKE 'http%' OR backdrop_url LIKE 'http%'
+           ORDER BY name"#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// 获取演员图片缓存设置（未设置时返回默认值）
+pub async fn get_actor_photo_cache_settings(pool: &SqlitePool) -> anyhow::Result<ActorPhotoCacheSettings> {
+    Ok(match get_setting(pool, ACTOR_PHOTO_CACHE_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => ActorPhotoCacheSettings::default(),
+    })
+}
+
+/// 保存演员图片缓存设置
+pub async fn save_actor_photo_cache_settings(pool: &SqlitePool, settings: &ActorPhotoCacheSettings) -> anyhow::Result<()> {
+    let value = serde_json::to_string(settings)?;
+    set_setting(pool, ACTOR_PHOTO_CACHE_KEY, &value, Some("Cache actor photos locally as WebP")).await
+}
+
+// ============ Actor Scrape Runs ============
+
+fn scrape_run_from_row(row: &SqliteRow) -> ActorScrapeRun {
+    let results: String = row.get("results");
+    ActorScrapeRun {
+        id: row.get("id"),
+        mode: row.get("mode"),
+        status: row.get("status"),
+        message: row.get("message"),
+        total: row.get("total"),
+        success_count: row.get("success_count"),
+        failed_count: row.get("failed_count"),
+        results: serde_json::from_str(&results).unwrap_or_default(),
+        started_at: row.get("started_at"),
+        finished_at: row.get("finished_at"),
+    }
+}
+
+/// 保存批量演员刮削结果（同一会话重复保存时覆盖）
+pub async fn save_actor_scrape_run(pool: &SqlitePool, run: &ActorScrapeRun) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"INSERT OR REPLACE INTO actor_scrape_runs
+           (id, mode, status, message, total, success_count, failed_count, results, started_at, finished_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#
+    )
+    .bind(&run.id)
+    .bind(&run.mode)
+    .bind(&run.status)
+    .bind(&run.message)
+    .bind(run.total)
+    .bind(run.success_count)
+    .bind(run.failed_count)
+    .bind(serde_json::to_string(&run.results)?)
+    .bind(run.started_at)
+    .bind(run.finished_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 获取最近的批量演员刮削结果（最新的在前）
+pub async fn list_actor_scrape_runs(pool: &SqlitePool, limit: i64) -> Result<Vec<ActorScrapeRun>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM actor_scrape_runs ORDER BY finished_at DESC LIMIT ?")
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.iter().map(scrape_run_from_row).collect())
+}
+
+/// 获取单次批量演员刮削结果
+pub async fn get_actor_scrape_run(pool: &SqlitePool, id: &str) -> Result<Option<ActorScrapeRun>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM actor_scrape_runs WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.as_ref().map(scrape_run_from_row))
+}
+
+/// 获取有作品关联的演员及其作品数和别名（用于重复演员检测）
+pub async fn list_duplicate_actor_candidates(pool: &SqlitePool) -> Result<Vec<DuplicateActorCandidate>, sqlx::Error> {
+    let mut candidates = sqlx::query_as::<_, DuplicateActorCandidate>(
+        r#"
+        SELECT a.id, a.name, a.avatar_url, COUNT(am.id) AS work_count
+        FROM actors a
+        INNER JOIN actor_media am ON am.actor_id = a.id
+        GROUP BY a.id
+        ORDER BY a.name
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let aliases: Vec<(String, String)> = sqlx::query_as("SELECT actor_id, alias FROM actor_aliases ORDER BY alias")
+        .fetch_all(pool)
+        .await?;
+    let mut by_actor: HashMap<String, Vec<String>> = HashMap::new();
+    for (actor_id, alias) in aliases {
+        by_actor.entry(actor_id).or_default().push(alias);
+    }
+    for candidate in &mut candidates {
+        candidate.aliases = by_actor.remove(&candidate.id).unwrap_or_default();
+    }
+    Ok(candidates)
+}
+
+/// 把演员合并到目标演员：转移作品、预览图标注、收藏和别名，补全目标的空字段，
+/// 被合并的名字记为目标的别名，最后删除被合并的演员。返回合并的演员数
+pub async fn merge_actors(pool: &SqlitePool, target_id: &str, source_ids: &[String]) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let target: Option<Actor> = sqlx::query_as("SELECT * FROM actors WHERE id = ?")
+        .bind(target_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let Some(target) = target else {
+        return Err(sqlx::Error::RowNotFound);
+    };
+
+    let mut merged = 0;
+    for source_id in source_ids.iter().filter(|id| id.as_str() != target_id) {
+        let source: Option<Actor> = sqlx::query_as("SELECT * FROM actors WHERE id = ?")
+            .bind(source_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let Some(source) = source else {
+            continue;
+        };
+
+        // 已有相同关联的行保留目标的，其余转移到目标
+        for sql in [
+            "UPDATE OR IGNORE actor_media SET actor_id = ?1 WHERE actor_id = ?2",
+            "UPDATE OR IGNORE preview_actor_tags SET actor_id = ?1 WHERE actor_id = ?2",
+            "UPDATE OR IGNORE actor_aliases SET actor_id = ?1 WHERE actor_id = ?2",
+            "UPDATE OR IGNORE favorite_entities SET entity_id = ?1 WHERE entity_type = 'actor' AND entity_id = ?2",
+            "DELETE FROM favorite_entities WHERE entity_type = 'actor' AND entity_id = ?2",
+        ] {
+            sqlx::query(sql).bind(target_id).bind(source_id).execute(&mut *tx).await?;
+        }
+
+        if !source.name.eq_ignore_ascii_case(&target.name) {
+            sqlx::query("INSERT OR IGNORE INTO actor_aliases (alias, actor_id, created_at) VALUES (?, ?, ?)")
+                .bind(&source.name)
+                .bind(target_id)
+                .bind(chrono::Utc::now())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE actors SET
+                avatar_url = COALESCE(NULLIF(avatar_url, ''), ?),
+                photo_url = COALESCE(NULLIF(photo_url, ''), ?),
+                poster_url = COALESCE(NULLIF(poster_url, ''), ?),
+                backdrop_url = COALESCE(NULLIF(backdrop_url, ''), ?),
+                biography = COALESCE(NULLIF(biography, ''), ?),
+                birth_date = COALESCE(NULLIF(birth_date, ''), ?),
+                nationality = COALESCE(NULLIF(nationality, ''), ?),
+                updated_at = ?
+            WHERE id = ?
+            "#
+        )
+        .bind(&source.avatar_url)
+        .bind(&source.photo_url)
+        .bind(&source.poster_url)
+        .bind(&source.backdrop_url)
+        .bind(&source.biography)
+        .bind(&source.birth_date)
+        .bind(&source.nationality)
+        .bind(chrono::Utc::now())
+        .bind(target_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM actors WHERE id = ?")
+            .bind(source_id)
+            .execute(&mut *tx)
+            .await?;
+        merged += 1;
+    }
+
+    tx.commit().await?;
+    Ok(merged)
+}
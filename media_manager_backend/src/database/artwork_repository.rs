@@ -0,0 +1,21 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+
+use crate::models::DuplicateArtworkCandidate;
+
+// ============ Duplicate Artwork ============
+
+/// 获取有封面感知哈希的媒体（用于重复封面检测）
+pub async fn list_duplicate_artwork_candidates(pool: &Pool<Sqlite>) -> Result<Vec<DuplicateArtworkCandidate>> {
+    let candidates = sqlx::query_as::<_, DuplicateArtworkCandidate>(
+        r#"
+        SELECT id, code, title, year, media_type, poster_url, poster_phash
+        FROM media_items
+        WHERE poster_phash IS NOT NULL AND poster_url IS NOT NULL
+        ORDER BY title
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(candidates)
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+use crate::models::{BrowsingHistoryEntry, MediaItem, BROWSING_HISTORY_LIMIT};
+
+// ============ Browsing History ============
+
+/// 记录一次媒体详情浏览，并只保留该用户最近的记录
+pub async fn record_browsing(pool: &Pool<Sqlite>, user_key: &str, media_id: &str) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO browsing_history (user_key, media_id, view_count, viewed_at)
+           VALUES (?, ?, 1, ?)
+           ON CONFLICT(user_key, media_id) DO UPDATE SET
+               view_count = view_count + 1,
+               viewed_at = excluded.viewed_at"#
+    )
+    .bind(user_key)
+    .bind(media_id)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"DELETE FROM browsing_history
+           WHERE user_key = ? AND media_id NOT IN (
+               SELECT media_id FROM browsing_history WHERE user_key = ?
+               ORDER BY viewed_at DESC LIMIT ?
+           )"#
+    )
+    .bind(user_key)
+    .bind(user_key)
+    .bind(BROWSING_HISTORY_LIMIT)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 获取最近浏览的媒体（最近浏览的在前）
+pub async fn list_browsing_history(
+    pool: &Pool<Sqlite>,
+    user_key: &str,
+    limit: i64,
+) -> Result<Vec<(BrowsingHistoryEntry, MediaItem)>> {
+    let entries = sqlx::query_as::<_, BrowsingHistoryEntry>(
+        r#"SELECT media_id, view_count, viewed_at FROM browsing_history
+           WHERE user_key = ? ORDER BY viewed_at DESC LIMIT ?"#
+    )
+    .bind(user_key)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    let mut items = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let media = sqlx::query_as::<_, MediaItem>("SELECT * FROM media_items WHERE id = ?")
+            .bind(&entry.media_id)
+            .fetch_optional(pool)
+            .await?;
+        if let Some(media) = media {
+            items.push((entry, media));
+        }
+    }
+    Ok(items)
+}
+
+/// 清除浏览记录（指定媒体时只删除该媒体的记录），返回删除的条数
+pub async fn clear_browsing_history(pool: &Pool<Sqlite>, user_key: &str, media_id: Option<&str>) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM browsing_history WHERE user_key = ? AND (? IS NULL OR media_id = ?)"
+    )
+    .bind(user_key)
+    .bind(media_id)
+    .bind(media_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,120 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::{Follow, ReleaseCalendarEntry, CreateFollowRequest};
+
+// ============ Follow CRUD ============
+
+/// 创建关注
+pub async fn create_follow(pool: &Pool<Sqlite>, req: CreateFollowRequest) -> Result<Follow> {
+    let follow = Follow::new(req.target_type, req.target_name.trim().to_string(), req.plugin_id);
+
+    sqlx::query(
+        r#"INSERT INTO follows (id, target_type, target_name, plugin_id, created_at)
+           VALUES (?, ?, ?, ?, datetime('now'))"#
+    )
+    .bind(&follow.id)
+    .bind(&follow.target_type)
+    .bind(&follow.target_name)
+    .bind(&follow.plugin_id)
+    .execute(pool)
+    .await?;
+
+    get_follow_by_id(pool, &follow.id).await
+}
+
+/// 根据ID获取关注
+pub async fn get_follow_by_id(pool: &Pool<Sqlite>, id: &str) -> Result<Follow> {
+    let follow = sqlx::query_as::<_, Follow>("SELECT * FROM follows WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+    Ok(follow)
+}
+
+/// 获取所有关注
+pub async fn list_follows(pool: &Pool<Sqlite>) -> Result<Vec<Follow>> {
+    let follows = sqlx::query_as::<_, Follow>(
+        "SELECT * FROM follows ORDER BY target_type, target_name COLLATE NOCASE"
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(follows)
+}
+
+/// 删除关注（级联删除其日历条目）
+pub async fn delete_follow(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM release_calendar WHERE follow_id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    let result = sqlx::query("DELETE FROM follows WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(anyhow::anyhow!("Follow not found"));
+    }
+    Ok(())
+}
+
+/// 标记关注已检查
+pub async fn mark_follow_checked(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
+    sqlx::query("UPDATE follows SET last_checked_at = datetime('now') WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// ============ Release Calendar ============
+
+/// 写入或更新日历条目（同一关注下按 标题+发售日 去重）
+pub async fn upsert_release_entry(pool: &Pool<Sqlite>, entry: &ReleaseCalendarEntry) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO release_calendar
+           (id, follow_id, code, title, release_date, poster_url, studio, series, actors, plugin_id, media_id, created_at, updated_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                   (SELECT id FROM media_items WHERE ? IS NOT NULL AND code = ? COLLATE NOCASE LIMIT 1),
+                   datetime('now'), datetime('now'))
+           ON CONFLICT(follow_id, title, release_date) DO UPDATE SET
+               code = excluded.code,
+               poster_url = excluded.poster_url,
+               studio = excluded.studio,
+               series = excluded.series,
+               actors = excluded.actors,
+               plugin_id = excluded.plugin_id,
+               media_id = excluded.media_id,
+               updated_at = datetime('now')"#
+    )
+    .bind(&entry.id)
+    .bind(&entry.follow_id)
+    .bind(&entry.code)
+    .bind(&entry.title)
+    .bind(&entry.release_date)
+    .bind(&entry.poster_url)
+    .bind(&entry.studio)
+    .bind(&entry.series)
+    .bind(&entry.actors)
+    .bind(&entry.plugin_id)
+    .bind(&entry.code)
+    .bind(&entry.code)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 查询日期范围内的日历条目（日期格式 YYYY-MM-DD，闭区间）
+pub async fn list_release_calendar(pool: &Pool<Sqlite>, from: &str, to: &str) -> Result<Vec<ReleaseCalendarEntry>> {
+    let entries = sqlx::query_as::<_, ReleaseCalendarEntry>(
+        r#"SELECT * FROM release_calendar
+           WHERE release_date >= ? AND release_date <= ?
+           ORDER BY release_date ASC, title COLLATE NOCASE"#
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    Ok(entries)
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::ChangeRecord;
+
+// ============ Change Feed ============
+
+/// 获取指定序列号之后的变更（按序列号升序）
+///
+/// exclude_origin 用于对端同步：排除来自该对端的变更，避免回传
+pub async fn list_changes(
+    pool: &Pool<Sqlite>,
+    since_seq: i64,
+    limit: i64,
+    entity_type: Option<&str>,
+    exclude_origin: Option<&str>,
+) -> Result<Vec<ChangeRecord>> {
+    let mut sql = String::from("SELECT * FROM changes WHERE seq > ?");
+    if entity_type.is_some() {
+        sql.push_str(" AND entity_type = ?");
+    }
+    if exclude_origin.is_some() {
+        sql.push_str(" AND (origin IS NULL OR origin != ?)");
+    }
+    sql.push_str(" ORDER BY seq ASC LIMIT ?");
+
+    let mut query = sqlx::query_as::<_, ChangeRecord>(&sql).bind(since_seq);
+    if let Some(entity_type) = entity_type {
+        query = query.bind(entity_type);
+    }
+    if let Some(origin) = exclude_origin {
+        query = query.bind(origin);
+    }
+    let changes = query.bind(limit).fetch_all(pool).await?;
+    Ok(changes)
+}
+
+/// 获取当前最新的序列号（没有变更时为 0）
+pub async fn latest_change_seq(pool: &Pool<Sqlite>) -> Result<i64> {
+    let seq: Option<i64> = sqlx::query_scalar("SELECT MAX(seq) FROM changes")
+        .fetch_one(pool)
+        .await?;
+    Ok(seq.unwrap_or(0))
+}
+
+/// 获取保留的最早序列号（没有变更时为 None）
+pub async fn oldest_change_seq(pool: &Pool<Sqlite>) -> Result<Option<i64>> {
+    let seq: Option<i64> = sqlx::query_scalar("SELECT MIN(seq) FROM changes")
+        .fetch_one(pool)
+        .await?;
+    Ok(seq)
+}
+
+/// 可清理的变更：早于指定时间，且不是最新一条（保证 latest_seq 不回退），也不是尚未推送给已启用对端的变更
+const PRUNABLE_CHANGES: &str = r#"
+    changed_at < ?
+    AND seq < (SELECT MAX(seq) FROM changes)
+    AND seq <= COALESCE((SELECT MIN(last_pushed_seq) FROM sync_peers WHERE enabled = 1), seq)
+"#;
+
+/// 统计早于 before 的可清理变更
+pub async fn count_prunable_changes(pool: &Pool<Sqlite>, before: &str) -> Result<u64> {
+    let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM changes WHERE {}", PRUNABLE_CHANGES))
+        .bind(before)
+        .fetch_one(pool)
+        .await?;
+    Ok(count as u64)
+}
+
+/// 删除早于 before 的可清理变更，返回删除条数
+pub async fn prune_changes(pool: &Pool<Sqlite>, before: &str) -> Result<u64> {
+    let result = sqlx::query(&format!("DELETE FROM changes WHERE {}", PRUNABLE_CHANGES))
+        .bind(before)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::{CollectionStats, StatusHistoryFilters, TagCompletion, WatchStatusChange, WatchStatusCount};
+
+// ============ Collection Stats ============
+
+/// 收藏统计（全部由聚合查询计算）
+pub async fn get_collection_stats(pool: &Pool<Sqlite>) -> Result<CollectionStats> {
+    let (total, favorites, average_rating, rated, runtime_watched): (i64, i64, Option<f64>, i64, f64) = sqlx::query_as(
+        r#"SELECT COUNT(*),
+                  COALESCE(SUM(c.is_favorite), 0),
+                  AVG(c.personal_rating),
+                  COUNT(c.personal_rating),
+                  CAST(COALESCE(SUM(CASE WHEN c.watch_status = 'Completed' THEN m.runtime
+                                         ELSE m.runtime * COALESCE(c.watch_progress, 0) END), 0) AS REAL)
+           FROM collections c
+           JOIN media_items m ON m.id = c.media_id"#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let by_status = sqlx::query_as::<_, WatchStatusCount>(
+        r#"SELECT watch_status, COUNT(*) AS count
+           FROM collections
+           GROUP BY watch_status
+           ORDER BY count DESC, watch_status"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let tag_rows: Vec<(String, i64, i64)> = sqlx::query_as(
+        r#"WITH collection_tags AS (
+               SELECT c.media_id, c.watch_status, t.name AS tag
+               FROM collections c
+               JOIN media_tags mt ON mt.media_id = c.media_id
+               JOIN tags t ON t.id = mt.tag_id
+               UNION
+               SELECT c.media_id, c.watch_status, j.value AS tag
+               FROM collections c, json_each(c.user_tags) j
+               WHERE json_valid(c.user_tags) AND TRIM(j.value) != ''
+           )
+           SELECT tag,
+                  COUNT(DISTINCT media_id) AS total,
+                  COUNT(DISTINCT CASE WHEN watch_status = 'Completed' THEN media_id END) AS completed
+           FROM collection_tags
+           GROUP BY tag COLLATE NOCASE
+           ORDER BY total DESC, tag"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let completed_this_month: i64 = sqlx::query_scalar(
+        r#"SELECT COUNT(DISTINCT media_id)
+           FROM collection_status_history
+           WHERE to_status = 'Completed' AND changed_at >= datetime('now', 'start of month')"#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let tags = tag_rows.into_iter()
+        .map(|(tag, total, completed)| TagCompletion {
+            tag,
+            total,
+            completed,
+            completion_percent: (completed as f64 * 1000.0 / total as f64).round() / 10.0,
+        })
+        .collect();
+
+    Ok(CollectionStats {
+        total,
+        favorites,
+        by_status,
+        average_rating: average_rating.map(|rating| (rating * 100.0).round() / 100.0),
+        rated,
+        runtime_watched_minutes: runtime_watched.round() as i64,
+        completed_this_month,
+        tags,
+    })
+}
+
+// ============ Status History ============
+
+/// 观看状态变化记录（按时间倒序）
+pub async fn list_status_history(pool: &Pool<Sqlite>, filters: &StatusHistoryFilters) -> Result<Vec<WatchStatusChange>> {
+    let changes = sqlx::query_as::<_, WatchStatusChange>(
+        r#"SELECT h.media_id, m.title AS media_title, h.from_status, h.to_status, h.changed_at
+           FROM collection_status_history h
+           JOIN media_items m ON m.id = h.media_id
+           WHERE (? IS NULL OR h.media_id = ?)
+             AND (? IS NULL OR h.to_status = ?)
+             AND (? IS NULL OR h.changed_at >= date(?))
+             AND (? IS NULL OR h.changed_at < date(?, '+1 day'))
+           ORDER BY h.changed_at DESC, h.id DESC
+           LIMIT ?"#
+    )
+    .bind(&filters.media_id)
+    .bind(&filters.media_id)
+    .bind(filters.to_status.as_ref().map(|s| s.to_string()))
+    .bind(filters.to_status.as_ref().map(|s| s.to_string()))
+    .bind(&filters.from)
+    .bind(&filters.from)
+    .bind(&filters.to)
+    .bind(&filters.to)
+    .bind(filters.limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(changes)
+}
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+use crate::models::{EnrichmentJob, EnrichmentJobRecord, EnrichmentSettings, EnrichmentStepResult, MediaProbe};
+use super::settings_repository::{get_setting, set_setting};
+
+const ENRICHMENT_KEY: &str = "enrichment";
+
+// ============ Enrichment Settings ============
+
+/// 获取自动补全设置（未设置时返回默认值）
+pub async fn get_enrichment_settings(pool: &Pool<Sqlite>) -> Result<EnrichmentSettings> {
+    Ok(match get_setting(pool, ENRICHMENT_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => EnrichmentSettings::default(),
+    })
+}
+
+/// 保存自动补全设置
+pub async fn save_enrichment_settings(pool: &Pool<Sqlite>, settings: &EnrichmentSettings) -> Result<()> {
+    let value = serde_json::to_string(settings)?;
+    set_setting(pool, ENRICHMENT_KEY, &value, Some("Background enrichment after media creation")).await
+}
+
+// ============ Enrichment Jobs ============
+
+/// 加入补全队列：已有的任务（执行中的除外）重置为待执行，返回是否加入
+pub async fn enqueue_enrichment_job(pool: &Pool<Sqlite>, media_id: &str, reason: &str) -> Result<bool> {
+    let result = sqlx::query(
+        r#"INSERT INTO enrichment_jobs (media_id, status, reason, created_at)
+           VALUES (?, 'pending', ?, ?)
+           ON CONFLICT(media_id) DO UPDATE SET
+               status = 'pending', reason = excluded.reason, steps = '[]', error = NULL,
+               created_at = excluded.created_at, started_at = NULL, finished_at = NULL
+           WHERE enrichment_jobs.status != 'running'"#
+    )
+    .bind(media_id)
+    .bind(reason)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// 取出最早的待执行任务并标记为执行中
+pub async fn claim_next_enrichment_job(pool: &Pool<Sqlite>) -> Result<Option<EnrichmentJob>> {
+    let record = sqlx::query_as::<_, EnrichmentJobRecord>(
+        r#"UPDATE enrichment_jobs SET status = 'running', started_at = ?, attempts = attempts + 1
+           WHERE media_id = (
+               SELECT media_id FROM enrichment_jobs WHERE status = 'pending' ORDER BY created_at LIMIT 1
+           )
+           RETURNING *"#
+    )
+    .bind(Utc::now())
+    .fetch_optional(pool)
+    .await?;
+    Ok(record.map(EnrichmentJob::from))
+}
+
+/// 保存任务的执行结果
+pub async fn finish_enrichment_job(
+    pool: &Pool<Sqlite>,
+    media_id: &str,
+    status: &str,
+    steps: &[EnrichmentStepResult],
+    error: Option<&str>,
+) -> Result<()> {
+    sqlx::query("UPDATE enrichment_jobs SET status = ?, steps = ?, error = ?, finished_at = ? WHERE media_id = ?")
+        .bind(status)
+        .bind(serde_json::to_string(steps)?)
+        .bind(error)
+        .bind(Utc::now())
+        .bind(media_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 把上次退出时仍在执行的任务放回队列，返回数量
+pub async fn requeue_running_enrichment_jobs(pool: &Pool<Sqlite>) -> Result<u64> {
+    let result = sqlx::query("UPDATE enrichment_jobs SET status = 'pending', started_at = NULL WHERE status = 'running'")
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// 获取补全任务（可按状态筛选，最新的在前）
+pub async fn list_enrichment_jobs(pool: &Pool<Sqlite>, status: Option<&str>, limit: i64) -> Result<Vec<EnrichmentJob>> {
+    let records = sqlx::query_as::<_, EnrichmentJobRecord>(
+        "SELECT * FROM enrichment_jobs WHERE (? IS NULL OR status = ?) ORDER BY created_at DESC LIMIT ?"
+    )
+    .bind(status)
+    .bind(status)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(records.into_iter().map(EnrichmentJob::from).collect())
+}
+
+/// 获取媒体的补全任务
+pub async fn get_enrichment_job(pool: &Pool<Sqlite>, media_id: &str) -> Result<Option<EnrichmentJob>> {
+    let record = sqlx::query_as::<_, EnrichmentJobRecord>("SELECT * FROM enrichment_jobs WHERE media_id = ?")
+        .bind(media_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(record.map(EnrichmentJob::from))
+}
+
+// ============ Media Probes ============
+
+/// 保存文件的技术信息（同一文件覆盖旧结果）
+pub async fn save_media_probe(pool: &Pool<Sqlite>, probe: &MediaProbe) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO media_probes
+           (file_path, media_id, container, duration_secs, bit_rate, width, height, video_codec, audio_codec, probed_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+           ON CONFLICT(file_path) DO UPDATE SET
+               media_id = excluded.media_id, container = excluded.container, duration_secs = excluded.duration_secs,
+               bit_rate = excluded.bit_rate, width = excluded.width, height = excluded.height,
+               video_codec = excluded.video_codec, audio_codec = excluded.audio_codec, probed_at = excluded.probed_at"#
+    )
+    .bind(&probe.file_path)
+    .bind(&probe.media_id)
+    .bind(&probe.container)
+    .bind(probe.duration_secs)
+    .bind(probe.bit_rate)
+    .bind(probe.width)
+    .bind(probe.height)
+    .bind(&probe.video_codec)
+    .bind(&probe.audio_codec)
+    .bind(probe.probed_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 获取媒体文件的技术信息
+pub async fn list_media_probes(pool: &Pool<Sqlite>, media_id: &str) -> Result<Vec<MediaProbe>> {
+    let probes = sqlx::query_as::<_, MediaProbe>("SELECT * FROM media_probes WHERE media_id = ? ORDER BY file_path")
+        .bind(media_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(probes)
+}
@@ -0,0 +1,118 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+
+use crate::models::ExternalIds;
+
+// ============ External IDs ============
+
+/// 按外部ID查找媒体（TMDB ID 需同时匹配媒体类型，电影与电视剧的 ID 空间不同）
+pub async fn find_media_id_by_external_ids<'e, E>(
+    executor: E,
+    tmdb_id: Option<i32>,
+    tmdb_tv: bool,
+    imdb_id: Option<&str>,
+) -> Result<Option<String>>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let id = sqlx::query_scalar(
+        r#"SELECT id FROM media_items
+           WHERE (? IS NOT NULL AND json_extract(external_ids, '$.tmdb_id') = ? AND (media_type = 'Scene') = ?)
+              OR (? IS NOT NULL AND json_extract(external_ids, '$.imdb_id') = ?)
+           ORDER BY created_at
+           LIMIT 1"#
+    )
+    .bind(tmdb_id)
+    .bind(tmdb_id)
+    .bind(tmdb_tv)
+    .bind(imdb_id)
+    .bind(imdb_id)
+    .fetch_optional(executor)
+    .await?;
+    Ok(id)
+}
+
+/// 查找已使用相同外部ID的其他媒体，返回 (字段名, 媒体ID)
+pub async fn find_external_id_conflicts(
+    pool: &Pool<Sqlite>,
+    media_id: &str,
+    ids: &ExternalIds,
+    tmdb_tv: bool,
+) -> Result<Vec<(String, String)>> {
+    let mut conflicts = Vec::new();
+    if let Some(tmdb_id) = ids.tmdb_id {
+        let found: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM media_items WHERE id != ? AND json_extract(external_ids, '$.tmdb_id') = ? AND (media_type = 'Scene') = ? LIMIT 1"
+        )
+        .bind(media_id)
+        .bind(tmdb_id)
+        .bind(tmdb_tv)
+        .fetch_optional(pool)
+        .await?;
+        conflicts.extend(found.map(|id| ("tmdb_id".to_string(), id)));
+    }
+    if let Some(imdb_id) = &ids.imdb_id {
+        let found: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM media_items WHERE id != ? AND json_extract(external_ids, '$.imdb_id') = ? LIMIT 1"
+        )
+        .bind(media_id)
+        .bind(imdb_id)
+        .fetch_optional(pool)
+        .await?;
+        conflicts.extend(found.map(|id| ("imdb_id".to_string(), id)));
+    }
+    if let Some(tvdb_id) = ids.tvdb_id {
+        let found: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM media_items WHERE id != ? AND json_extract(external_ids, '$.tvdb_id') = ? LIMIT 1"
+        )
+        .bind(media_id)
+        .bind(tvdb_id)
+        .fetch_optional(pool)
+        .await?;
+        conflicts.extend(found.map(|id| ("tvdb_id".to_string(), id)));
+    }
+    for (key, value) in &ids.custom {
+        // 键已校验为小写字母、数字和下划线，可以直接拼入 JSON 路径
+        let found: Option<String> = sqlx::query_scalar(
+            "SELECT id FROM media_items WHERE id != ? AND json_extract(external_ids, '$.custom.' || ?) = ? LIMIT 1"
+        )
+        .bind(media_id)
+        .bind(key)
+        .bind(value)
+        .fetch_optional(pool)
+        .await?;
+        conflicts.extend(found.map(|id| (format!("custom.{}", key), id)));
+    }
+    Ok(conflicts)
+}
+
+// ============ Identity Lookup ============
+
+/// 按番号查找媒体（不区分大小写）
+pub async fn find_media_id_by_code<'e, E>(executor: E, code: &str) -> Result<Option<String>>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let id = sqlx::query_scalar(
+        "SELECT id FROM media_items WHERE code = ? COLLATE NOCASE ORDER BY created_at LIMIT 1"
+    )
+    .bind(code)
+    .fetch_optional(executor)
+    .await?;
+    Ok(id)
+}
+
+/// 按标题和年份精确查找媒体（年份为空时只匹配没有年份的媒体）
+pub async fn find_media_id_by_title_year<'e, E>(executor: E, title: &str, year: Option<i32>) -> Result<Option<String>>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let id = sqlx::query_scalar(
+        "SELECT id FROM media_items WHERE title = ? AND year IS ? ORDER BY created_at LIMIT 1"
+    )
+    .bind(title)
+    .bind(year)
+    .fetch_optional(executor)
+    .await?;
+    Ok(id)
+}
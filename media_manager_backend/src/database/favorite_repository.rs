@@ -0,0 +1,132 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::{FavoriteEntity, MediaItem};
+
+const FAVORITE_SELECT: &str = r#"
+    SELECT f.entity_type, f.entity_id,
+           COALESCE(a.name, s.name, se.name) AS name,
+           COALESCE(a.avatar_url, a.poster_url, s.logo_url, se.cover_url) AS image_url,
+           f.created_at
+    FROM favorite_entities f
+    LEFT JOIN actors a ON f.entity_type = 'actor' AND a.id = f.entity_id
+    LEFT JOIN studios s ON f.entity_type = 'studio' AND s.id = f.entity_id
+    LEFT JOIN series se ON f.entity_type = 'series' AND se.id = f.entity_id
+    WHERE COALESCE(a.id, s.id, se.id) IS NOT NULL"#;
+
+/// 收藏动态的筛选条件：关联收藏实体（演员按 actor_media，厂商/系列按名称）且未看完/未弃看的媒体，
+/// new_only 时只保留收藏之后新增的媒体
+const FAVORITE_FEED_WHERE: &str = r#"
+    WHERE m.id IN (
+        SELECT fm.media_id FROM (
+            SELECT am.media_id, f.created_at AS favorited_at
+            FROM favorite_entities f
+            JOIN actor_media am ON f.entity_type = 'actor' AND am.actor_id = f.entity_id
+            UNION ALL
+            SELECT mi.id, f.created_at
+            FROM favorite_entities f
+            JOIN studios s ON f.entity_type = 'studio' AND s.id = f.entity_id
+            JOIN media_items mi ON mi.studio = s.name COLLATE NOCASE
+            UNION ALL
+            SELECT mi.id, f.created_at
+            FROM favorite_entities f
+            JOIN series se ON f.entity_type = 'series' AND se.id = f.entity_id
+            JOIN media_items mi ON mi.series = se.name COLLATE NOCASE
+        ) fm
+        WHERE ? = 0 OR datetime(m.created_at) >= datetime(fm.favorited_at)
+    )
+    AND NOT EXISTS (
+        SELECT 1 FROM collections c
+        WHERE c.media_id = m.id AND c.watch_status IN ('Completed', 'Dropped')
+    )"#;
+
+// ============ Favorite Entities ============
+
+/// 实体是否存在
+async fn entity_exists(pool: &Pool<Sqlite>, entity_type: &str, entity_id: &str) -> Result<bool> {
+    let table = match entity_type {
+        "actor" => "actors",
+        "studio" => "studios",
+        "series" => "series",
+        _ => return Ok(false),
+    };
+    let sql = format!("SELECT COUNT(*) FROM {} WHERE id = ?", table);
+    let count: i64 = sqlx::query_scalar(&sql)
+        .bind(entity_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(count > 0)
+}
+
+/// 收藏实体（已收藏时保持原收藏时间），实体不存在时返回 None
+pub async fn add_favorite(pool: &Pool<Sqlite>, entity_type: &str, entity_id: &str) -> Result<Option<FavoriteEntity>> {
+    if !entity_exists(pool, entity_type, entity_id).await? {
+        return Ok(None);
+    }
+
+    sqlx::query("INSERT OR IGNORE INTO favorite_entities (entity_type, entity_id) VALUES (?, ?)")
+        .bind(entity_type)
+        .bind(entity_id)
+        .execute(pool)
+        .await?;
+
+    let sql = format!("{} AND f.entity_type = ? AND f.entity_id = ?", FAVORITE_SELECT);
+    let favorite = sqlx::query_as::<_, FavoriteEntity>(&sql)
+        .bind(entity_type)
+        .bind(entity_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(favorite)
+}
+
+/// 取消收藏，返回是否存在该收藏
+pub async fn remove_favorite(pool: &Pool<Sqlite>, entity_type: &str, entity_id: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM favorite_entities WHERE entity_type = ? AND entity_id = ?")
+        .bind(entity_type)
+        .bind(entity_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// 获取收藏的实体（可按类型筛选，最新收藏的在前）
+pub async fn list_favorites(pool: &Pool<Sqlite>, entity_type: Option<&str>) -> Result<Vec<FavoriteEntity>> {
+    let sql = format!(
+        "{} AND (? IS NULL OR f.entity_type = ?) ORDER BY f.created_at DESC, name COLLATE NOCASE",
+        FAVORITE_SELECT
+    );
+    let favorites = sqlx::query_as::<_, FavoriteEntity>(&sql)
+        .bind(entity_type)
+        .bind(entity_type)
+        .fetch_all(pool)
+        .await?;
+    Ok(favorites)
+}
+
+// ============ Favorite Feed ============
+
+/// 获取收藏动态（最新添加的媒体在前）
+pub async fn get_favorite_feed(
+    pool: &Pool<Sqlite>,
+    new_only: bool,
+    limit: i32,
+    offset: i32,
+) -> Result<(Vec<MediaItem>, i64)> {
+    let sql = format!(
+        "SELECT m.* FROM media_items m {} ORDER BY m.created_at DESC LIMIT ? OFFSET ?",
+        FAVORITE_FEED_WHERE
+    );
+    let items = sqlx::query_as::<_, MediaItem>(&sql)
+        .bind(new_only)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+
+    let count_sql = format!("SELECT COUNT(*) FROM media_items m {}", FAVORITE_FEED_WHERE);
+    let total: i64 = sqlx::query_scalar(&count_sql)
+        .bind(new_only)
+        .fetch_one(pool)
+        .await?;
+
+    Ok((items, total))
+}
@@ -0,0 +1,94 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::RwLock;
+
+use crate::models::MediaFilterOptions;
+
+// ============ Filter Options ============
+
+/// 获取筛选选项版本号（由触发器在相关字段变化时递增）
+pub async fn get_filter_options_version(pool: &Pool<Sqlite>) -> Result<i64> {
+    let version: Option<i64> = sqlx::query_scalar(
+        "SELECT version FROM filter_options_version WHERE id = 1"
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(version.unwrap_or(0))
+}
+
+/// 统计媒体筛选选项
+pub async fn load_media_filter_options(pool: &Pool<Sqlite>) -> Result<MediaFilterOptions> {
+    let media_types: Vec<String> = sqlx::query_scalar(
+        "SELECT DISTINCT media_type FROM media_items WHERE media_type IS NOT NULL ORDER BY media_type"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let studios: Vec<String> = sqlx::query_scalar(
+        "SELECT DISTINCT studio FROM media_items WHERE studio IS NOT NULL AND studio != '' ORDER BY studio"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let series: Vec<String> = sqlx::query_scalar(
+        "SELECT DISTINCT series FROM media_items WHERE series IS NOT NULL AND series != '' ORDER BY series"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let years: Vec<i32> = sqlx::query_scalar(
+        "SELECT DISTINCT year FROM media_items WHERE year IS NOT NULL ORDER BY year DESC"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // genres 存储为 JSON 数组，解析后去重
+    let genres_raw: Vec<String> = sqlx::query_scalar(
+        "SELECT DISTINCT genres FROM media_items WHERE genres IS NOT NULL AND genres != '[]'"
+    )
+    .fetch_all(pool)
+    .await?;
+    let genres: BTreeSet<String> = genres_raw.iter()
+        .filter_map(|json| serde_json::from_str::<Vec<String>>(json).ok())
+        .flatten()
+        .filter(|genre| !genre.is_empty())
+        .collect();
+
+    Ok(MediaFilterOptions {
+        media_types,
+        studios,
+        series,
+        years,
+        genres: genres.into_iter().collect(),
+    })
+}
+
+/// 缓存的筛选选项及其版本号
+type CachedFilterOptions = (i64, Arc<MediaFilterOptions>);
+
+/// 筛选选项缓存
+///
+/// 每次读取只查询版本号，版本号未变化时直接返回缓存，避免重复的全表 DISTINCT 扫描。
+#[derive(Clone, Default)]
+pub struct FilterOptionsCache {
+    cached: Arc<RwLock<Option<CachedFilterOptions>>>,
+}
+
+impl FilterOptionsCache {
+    pub async fn get(&self, pool: &Pool<Sqlite>) -> Result<Arc<MediaFilterOptions>> {
+        let version = get_filter_options_version(pool).await?;
+        if let Some((cached_version, options)) = self.cached.read().await.as_ref() {
+            if *cached_version == version {
+                return Ok(options.clone());
+            }
+        }
+
+        // 先读版本号再统计：统计期间有写入时，下次读取会因版本号变化重新统计
+        let options = Arc::new(load_media_filter_options(pool).await?);
+        *self.cached.write().await = Some((version, options.clone()));
+        Ok(options)
+    }
+}
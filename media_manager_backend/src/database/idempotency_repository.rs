@@ -0,0 +1,91 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Sqlite};
+use crate::models::IdempotencyRecord;
+
+// ============ Idempotency Keys ============
+
+/// 占用幂等键（标记为处理中），键已存在时返回 false
+pub async fn claim_idempotency_key(
+    pool: &Pool<Sqlite>,
+    key: &str,
+    method: &str,
+    path: &str,
+    request_hash: &str,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"INSERT OR IGNORE INTO idempotency_keys (idempotency_key, method, path, request_hash, created_at)
+           VALUES (?, ?, ?, ?, ?)"#
+    )
+    .bind(key)
+    .bind(method)
+    .bind(path)
+    .bind(request_hash)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// 获取幂等键记录
+pub async fn get_idempotency_record(
+    pool: &Pool<Sqlite>,
+    key: &str,
+    method: &str,
+    path: &str,
+) -> Result<Option<IdempotencyRecord>> {
+    let record = sqlx::query_as::<_, IdempotencyRecord>(
+        r#"SELECT * FROM idempotency_keys WHERE idempotency_key = ? AND method = ? AND path = ?"#
+    )
+    .bind(key)
+    .bind(method)
+    .bind(path)
+    .fetch_optional(pool)
+    .await?;
+    Ok(record)
+}
+
+/// 保存请求的响应
+pub async fn complete_idempotency_key(
+    pool: &Pool<Sqlite>,
+    key: &str,
+    method: &str,
+    path: &str,
+    status_code: u16,
+    content_type: Option<&str>,
+    response_body: &[u8],
+) -> Result<()> {
+    sqlx::query(
+        r#"UPDATE idempotency_keys SET status_code = ?, content_type = ?, response_body = ?
+           WHERE idempotency_key = ? AND method = ? AND path = ?"#
+    )
+    .bind(status_code as i64)
+    .bind(content_type)
+    .bind(response_body)
+    .bind(key)
+    .bind(method)
+    .bind(path)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 释放幂等键（请求失败时允许客户端重试）
+pub async fn release_idempotency_key(pool: &Pool<Sqlite>, key: &str, method: &str, path: &str) -> Result<()> {
+    sqlx::query("DELETE FROM idempotency_keys WHERE idempotency_key = ? AND method = ? AND path = ?")
+        .bind(key)
+        .bind(method)
+        .bind(path)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 删除过期的幂等键
+pub async fn delete_expired_idempotency_keys(pool: &Pool<Sqlite>, before: DateTime<Utc>) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM idempotency_keys WHERE created_at < ?")
+        .bind(before)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
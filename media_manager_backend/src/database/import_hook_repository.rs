@@ -0,0 +1,112 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::{HookEvent, HookResult, ImportHook, ImportHookRecord};
+
+/// 保留的执行记录条数
+const MAX_HOOK_EVENTS: i64 = 1000;
+
+// ============ Import Hooks ============
+
+/// 获取所有钩子
+pub async fn list_import_hooks(pool: &Pool<Sqlite>) -> Result<Vec<ImportHook>> {
+    let records = sqlx::query_as::<_, ImportHookRecord>("SELECT * FROM import_hooks ORDER BY created_at")
+        .fetch_all(pool)
+        .await?;
+    Ok(records.into_iter().map(ImportHook::from).collect())
+}
+
+/// 获取启用的钩子
+pub async fn list_enabled_import_hooks(pool: &Pool<Sqlite>) -> Result<Vec<ImportHook>> {
+    let records = sqlx::query_as::<_, ImportHookRecord>("SELECT * FROM import_hooks WHERE enabled = 1 ORDER BY created_at")
+        .fetch_all(pool)
+        .await?;
+    Ok(records.into_iter().map(ImportHook::from).collect())
+}
+
+/// 根据ID获取钩子
+pub async fn get_import_hook(pool: &Pool<Sqlite>, id: &str) -> Result<Option<ImportHook>> {
+    let record = sqlx::query_as::<_, ImportHookRecord>("SELECT * FROM import_hooks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(record.map(ImportHook::from))
+}
+
+/// 新增或保存钩子
+pub async fn save_import_hook(pool: &Pool<Sqlite>, hook: &ImportHook) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO import_hooks (id, name, hook_type, target, args, method, enabled, timeout_secs, created_at, updated_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+           ON CONFLICT(id) DO UPDATE SET
+               name = excluded.name, target = excluded.target, args = excluded.args, method = excluded.method,
+               enabled = excluded.enabled, timeout_secs = excluded.timeout_secs, updated_at = excluded.updated_at"#
+    )
+    .bind(&hook.id)
+    .bind(&hook.name)
+    .bind(&hook.hook_type)
+    .bind(&hook.target)
+    .bind(serde_json::to_string(&hook.args)?)
+    .bind(&hook.method)
+    .bind(hook.enabled)
+    .bind(hook.timeout_secs)
+    .bind(hook.created_at)
+    .bind(hook.updated_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 删除钩子（执行记录一并删除），不存在时返回 false
+pub async fn delete_import_hook(pool: &Pool<Sqlite>, id: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM import_hooks WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// ============ Hook Events ============
+
+/// 记录一次执行结果，只保留最近的记录
+pub async fn insert_hook_event(
+    pool: &Pool<Sqlite>,
+    hook_id: &str,
+    media_id: Option<&str>,
+    file_path: Option<&str>,
+    result: &HookResult,
+) -> Result<i64> {
+    let id = sqlx::query(
+        r#"INSERT INTO hook_events (hook_id, media_id, file_path, success, exit_code, output, error, duration_ms)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?)"#
+    )
+    .bind(hook_id)
+    .bind(media_id)
+    .bind(file_path)
+    .bind(result.success)
+    .bind(result.exit_code)
+    .bind(&result.output)
+    .bind(&result.error)
+    .bind(result.duration_ms)
+    .execute(pool)
+    .await?
+    .last_insert_rowid();
+
+    sqlx::query("DELETE FROM hook_events WHERE id <= ?")
+        .bind(id - MAX_HOOK_EVENTS)
+        .execute(pool)
+        .await?;
+    Ok(id)
+}
+
+/// 获取最近的执行记录（可按钩子筛选）
+pub async fn list_hook_events(pool: &Pool<Sqlite>, hook_id: Option<&str>, limit: i64) -> Result<Vec<HookEvent>> {
+    let events = sqlx::query_as::<_, HookEvent>(
+        "SELECT * FROM hook_events WHERE (? IS NULL OR hook_id = ?) ORDER BY id DESC LIMIT ?"
+    )
+    .bind(hook_id)
+    .bind(hook_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(events)
+}
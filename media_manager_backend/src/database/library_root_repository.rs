@@ -0,0 +1,105 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::LibraryRoot;
+
+// ============ Library Roots ============
+
+/// 获取所有根目录
+pub async fn list_library_roots(pool: &Pool<Sqlite>) -> Result<Vec<LibraryRoot>> {
+    let roots = sqlx::query_as::<_, LibraryRoot>("SELECT * FROM library_roots ORDER BY path")
+        .fetch_all(pool)
+        .await?;
+    Ok(roots)
+}
+
+/// 根据ID获取根目录
+pub async fn get_library_root(pool: &Pool<Sqlite>, id: &str) -> Result<Option<LibraryRoot>> {
+    let root = sqlx::query_as::<_, LibraryRoot>("SELECT * FROM library_roots WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(root)
+}
+
+/// 根据路径获取根目录
+pub async fn get_library_root_by_path(pool: &Pool<Sqlite>, path: &str) -> Result<Option<LibraryRoot>> {
+    let root = sqlx::query_as::<_, LibraryRoot>("SELECT * FROM library_roots WHERE path = ?")
+        .bind(path)
+        .fetch_optional(pool)
+        .await?;
+    Ok(root)
+}
+
+/// 新增根目录
+pub async fn insert_library_root(pool: &Pool<Sqlite>, root: &LibraryRoot) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO library_roots (id, path, label, content_type, auto_scan, rename_template,
+               storage_type, storage_config, created_at, updated_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#
+    )
+    .bind(&root.id)
+    .bind(&root.path)
+    .bind(&root.label)
+    .bind(&root.content_type)
+    .bind(root.auto_scan)
+    .bind(&root.rename_template)
+    .bind(&root.storage_type)
+    .bind(&root.storage_config)
+    .bind(root.created_at)
+    .bind(root.updated_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 保存根目录设置
+pub async fn update_library_root(pool: &Pool<Sqlite>, root: &LibraryRoot) -> Result<()> {
+    sqlx::query(
+        r#"UPDATE library_roots SET path = ?, label = ?, content_type = ?, auto_scan = ?, rename_template = ?,
+               storage_type = ?, storage_config = ?, path_exists = ?, reachable = ?, last_error = ?, last_checked_at = ?, last_seen_at = ?, updated_at = ?
+           WHERE id = ?"#
+    )
+    .bind(&root.path)
+    .bind(&root.label)
+    .bind(&root.content_type)
+    .bind(root.auto_scan)
+    .bind(&root.rename_template)
+    .bind(&root.storage_type)
+    .bind(&root.storage_config)
+    .bind(root.path_exists)
+    .bind(root.reachable)
+    .bind(&root.last_error)
+    .bind(root.last_checked_at)
+    .bind(root.last_seen_at)
+    .bind(root.updated_at)
+    .bind(&root.id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 保存根目录的可用性检查结果
+pub async fn update_library_root_status(pool: &Pool<Sqlite>, root: &LibraryRoot) -> Result<()> {
+    sqlx::query(
+        r#"UPDATE library_roots SET path_exists = ?, reachable = ?, last_error = ?, last_checked_at = ?, last_seen_at = ?
+           WHERE id = ?"#
+    )
+    .bind(root.path_exists)
+    .bind(root.reachable)
+    .bind(&root.last_error)
+    .bind(root.last_checked_at)
+    .bind(root.last_seen_at)
+    .bind(&root.id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 删除根目录，不存在时返回 false
+pub async fn delete_library_root(pool: &Pool<Sqlite>, id: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM library_roots WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
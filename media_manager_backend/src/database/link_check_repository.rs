@@ -0,0 +1,50 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::BrokenLink;
+
+// ============ Broken Links ============
+
+/// 用本次检查结果替换媒体的失效链接记录，返回之前记录的失效链接
+pub async fn replace_broken_links(pool: &Pool<Sqlite>, media_id: &str, links: &[BrokenLink]) -> Result<Vec<BrokenLink>> {
+    let mut tx = pool.begin().await?;
+    let previous = sqlx::query_as::<_, BrokenLink>("SELECT * FROM media_broken_links WHERE media_id = ?")
+        .bind(media_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM media_broken_links WHERE media_id = ?")
+        .bind(media_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for link in links {
+        sqlx::query(
+            r#"INSERT OR REPLACE INTO media_broken_links (media_id, field, url, status_code, error, checked_at)
+               VALUES (?, ?, ?, ?, ?, ?)"#
+        )
+        .bind(&link.media_id)
+        .bind(&link.field)
+        .bind(&link.url)
+        .bind(link.status_code)
+        .bind(&link.error)
+        .bind(link.checked_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(previous)
+}
+
+/// 获取失效链接（可按媒体筛选）
+pub async fn list_broken_links(pool: &Pool<Sqlite>, media_id: Option<&str>) -> Result<Vec<BrokenLink>> {
+    let links = sqlx::query_as::<_, BrokenLink>(
+        r#"SELECT * FROM media_broken_links
+           WHERE (? IS NULL OR media_id = ?)
+           ORDER BY media_id, field, url"#
+    )
+    .bind(media_id)
+    .bind(media_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(links)
+}
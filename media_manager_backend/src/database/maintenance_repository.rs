@@ -0,0 +1,114 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+
+use crate::models::OrphanRelations;
+
+// ============ Maintenance ============
+
+/// 数据库文件大小（字节）
+pub async fn database_size_bytes(pool: &Pool<Sqlite>) -> Result<i64> {
+    let size: i64 = sqlx::query_scalar(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()"
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(size)
+}
+
+/// 引用媒体、演员或标签的关联表：(表, 外键列, 父表)
+const ORPHAN_RELATIONS: &[(&str, &str, &str)] = &[
+    ("actor_media", "media_id", "media_items"),
+    ("actor_media", "actor_id", "actors"),
+    ("media_tags", "media_id", "media_items"),
+    ("media_tags", "tag_id", "tags"),
+    ("collections", "media_id", "media_items"),
+    ("media_files", "media_id", "media_items"),
+    ("media_extras", "media_id", "media_items"),
+    ("media_translations", "media_id", "media_items"),
+    ("media_broken_links", "media_id", "media_items"),
+    ("scrape_sources", "media_id", "media_items"),
+    ("wanted_media", "media_id", "media_items"),
+    ("torrent_downloads", "media_id", "media_items"),
+    ("link_downloads", "media_id", "media_items"),
+    ("hook_events", "hook_id", "import_hooks"),
+    ("enrichment_jobs", "media_id", "media_items"),
+    ("media_probes", "media_id", "media_items"),
+    ("media_upgrades", "media_id", "media_items"),
+    ("share_links", "media_id", "media_items"),
+    ("browsing_history", "media_id", "media_items"),
+    ("preview_actor_tags", "media_id", "media_items"),
+    ("preview_actor_tags", "actor_id", "actors"),
+    ("actor_aliases", "actor_id", "actors"),
+];
+
+fn orphan_condition(column: &str, parent: &str) -> String {
+    format!("{} NOT IN (SELECT id FROM {})", column, parent)
+}
+
+/// 统计各关联表中父记录已不存在的行（只返回有孤立行的表）
+pub async fn find_orphan_relations(pool: &Pool<Sqlite>) -> Result<Vec<OrphanRelations>> {
+    let mut found = Vec::new();
+    for &(table, column, parent) in ORPHAN_RELATIONS {
+        let rows: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM {} WHERE {}",
+            table, orphan_condition(column, parent)
+        ))
+        .fetch_one(pool)
+        .await?;
+        if rows > 0 {
+            found.push(OrphanRelations { table, column, parent, rows: rows as u64 });
+        }
+    }
+    Ok(found)
+}
+
+/// 删除各关联表中父记录已不存在的行
+pub async fn delete_orphan_relations(pool: &Pool<Sqlite>) -> Result<Vec<OrphanRelations>> {
+    let mut tx = pool.begin().await?;
+    let mut removed = Vec::new();
+    for &(table, column, parent) in ORPHAN_RELATIONS {
+        let result = sqlx::query(&format!(
+            "DELETE FROM {} WHERE {}",
+            table, orphan_condition(column, parent)
+        ))
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() > 0 {
+            removed.push(OrphanRelations { table, column, parent, rows: result.rows_affected() });
+        }
+    }
+    tx.commit().await?;
+    Ok(removed)
+}
+
+/// 按 media_items 重建全文索引，返回索引的媒体数
+pub async fn rebuild_media_search_index(pool: &Pool<Sqlite>) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM media_search_fts")
+        .execute(&mut *tx)
+        .await?;
+    let result = sqlx::query(
+        r#"INSERT INTO media_search_fts(media_id, title, original_title, overview, romanized)
+           SELECT id, title, original_title, overview, title_romanized FROM media_items"#
+    )
+    .execute(&mut *tx)
+    .await?;
+    // 合并索引段
+    sqlx::query("INSERT INTO media_search_fts(media_search_fts) VALUES('optimize')")
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(result.rows_affected())
+}
+
+/// 更新查询规划器统计信息
+pub async fn analyze_database(pool: &Pool<Sqlite>) -> Result<()> {
+    sqlx::query("PRAGMA optimize").execute(pool).await?;
+    Ok(())
+}
+
+/// 重建数据库文件以回收空闲页（不能在事务中执行）
+pub async fn vacuum_database(pool: &Pool<Sqlite>) -> Result<()> {
+    sqlx::query("VACUUM").execute(pool).await?;
+    Ok(())
+}
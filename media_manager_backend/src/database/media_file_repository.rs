@@ -0,0 +1,147 @@
+use anyhow::Result;
+use sqlx::{Connection, Pool, Sqlite, SqliteConnection};
+use crate::models::{normalize_edition, MediaExtra};
+
+// ============ Editions ============
+
+/// 设置媒体文件的所属版本（edition 为空表示默认版本），返回更新的文件数
+pub async fn set_media_files_edition(
+    pool: &Pool<Sqlite>,
+    media_id: &str,
+    file_ids: &[String],
+    edition: Option<&str>,
+) -> Result<u64> {
+    let edition = normalize_edition(edition);
+    let mut updated = 0;
+    let mut tx = pool.begin().await?;
+    for file_id in file_ids {
+        let result = sqlx::query("UPDATE media_files SET edition = ? WHERE id = ? AND media_id = ?")
+            .bind(&edition)
+            .bind(file_id)
+            .bind(media_id)
+            .execute(&mut *tx)
+            .await?;
+        updated += result.rows_affected();
+    }
+    tx.commit().await?;
+    Ok(updated)
+}
+
+// ============ Watch Progress ============
+
+/// 设置分段的观看进度（progress 为 None 时清除；达到 1 时记为看完），返回是否找到该文件
+pub async fn set_media_file_progress(
+    pool: &Pool<Sqlite>,
+    media_id: &str,
+    file_id: &str,
+    progress: Option<f32>,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE media_files SET
+               watch_progress = ?,
+               watched_at = CASE WHEN ? >= 1.0 THEN COALESCE(watched_at, datetime('now')) END
+           WHERE id = ? AND media_id = ?"#
+    )
+    .bind(progress)
+    .bind(progress)
+    .bind(file_id)
+    .bind(media_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// ============ Thumbnails ============
+
+/// 每个媒体的第一个文件（与 get_media_files 的排序一致），返回 (media_id, file_path)
+pub async fn list_primary_media_files(pool: &Pool<Sqlite>) -> Result<Vec<(String, String)>> {
+    let rows = sqlx::query_as(
+        r#"SELECT media_id, file_path FROM (
+               SELECT media_id, file_path,
+                      ROW_NUMBER() OVER (
+                          PARTITION BY media_id ORDER BY part_number ASC NULLS LAST, part_label ASC
+                      ) AS rn
+               FROM media_files
+           )
+           WHERE rn = 1
+           ORDER BY media_id"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+// ============ Extras ============
+
+/// 保存附加内容（按文件路径去重，已存在时更新归属和类型），返回保存的数量
+pub async fn save_media_extras(pool: &Pool<Sqlite>, extras: &[MediaExtra]) -> Result<u64> {
+    let mut conn = pool.acquire().await?;
+    save_media_extras_with(&mut conn, extras).await
+}
+
+/// 保存附加内容（在给定的连接或事务中执行，已在事务中时使用保存点）
+pub async fn save_media_extras_with(conn: &mut SqliteConnection, extras: &[MediaExtra]) -> Result<u64> {
+    let mut saved = 0;
+    let mut tx = conn.begin().await?;
+    for extra in extras {
+        let result = sqlx::query(
+            r#"INSERT INTO media_extras (id, media_id, file_path, file_name, file_size, extra_type, created_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?)
+               ON CONFLICT(file_path) DO UPDATE SET
+                   media_id = excluded.media_id,
+                   file_size = excluded.file_size,
+                   extra_type = excluded.extra_type"#
+        )
+        .bind(&extra.id)
+        .bind(&extra.media_id)
+        .bind(&extra.file_path)
+        .bind(&extra.file_name)
+        .bind(extra.file_size)
+        .bind(&extra.extra_type)
+        .bind(extra.created_at)
+        .execute(&mut *tx)
+        .await?;
+        saved += result.rows_affected();
+    }
+    tx.commit().await?;
+    Ok(saved)
+}
+
+/// 获取媒体的附加内容（可按类型筛选）
+pub async fn get_media_extras(pool: &Pool<Sqlite>, media_id: &str, extra_type: Option<&str>) -> Result<Vec<MediaExtra>> {
+    let extras = sqlx::query_as::<_, MediaExtra>(
+        r#"SELECT * FROM media_extras
+           WHERE media_id = ? AND (? IS NULL OR extra_type = ?)
+           ORDER BY extra_type, file_name"#
+    )
+    .bind(media_id)
+    .bind(extra_type)
+    .bind(extra_type)
+    .fetch_all(pool)
+    .await?;
+    Ok(extras)
+}
+
+/// 获取单个附加内容
+pub async fn get_media_extra(pool: &Pool<Sqlite>, media_id: &str, extra_id: &str) -> Result<Option<MediaExtra>> {
+    let extra = sqlx::query_as::<_, MediaExtra>("SELECT * FROM media_extras WHERE id = ? AND media_id = ?")
+        .bind(extra_id)
+        .bind(media_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(extra)
+}
+
+/// 查找正片文件位于指定目录（不含子目录）的媒体
+pub async fn find_media_id_by_file_dir(pool: &Pool<Sqlite>, dir: &str) -> Result<Option<String>> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT media_id, file_path FROM media_files WHERE substr(file_path, 1, length(?)) = ?"
+    )
+    .bind(dir)
+    .bind(dir)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter()
+        .find(|(_, path)| std::path::Path::new(path).parent() == Some(std::path::Path::new(dir)))
+        .map(|(media_id, _)| media_id))
+}
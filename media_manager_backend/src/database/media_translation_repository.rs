@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use anyhow::Result;
+use sqlx::{Connection, Pool, Sqlite, SqliteConnection};
+use crate::models::MediaTranslation;
+
+// ============ Translations ============
+
+/// 保存翻译（按媒体+语言覆盖），返回保存的数量
+pub async fn save_media_translations(pool: &Pool<Sqlite>, translations: &[MediaTranslation]) -> Result<u64> {
+    let mut conn = pool.acquire().await?;
+    save_media_translations_with(&mut conn, translations).await
+}
+
+/// 保存翻译（在给定的连接或事务中执行，已在事务中时使用保存点）
+pub async fn save_media_translations_with(conn: &mut SqliteConnection, translations: &[MediaTranslation]) -> Result<u64> {
+    let mut saved = 0;
+    let mut tx = conn.begin().await?;
+    for t in translations {
+        let result = sqlx::query(
+            r#"INSERT INTO media_translations (media_id, language, title, overview, source, updated_at)
+               VALUES (?, ?, ?, ?, ?, ?)
+               ON CONFLICT(media_id, language) DO UPDATE SET
+                   title = excluded.title,
+                   overview = excluded.overview,
+                   source = excluded.source,
+                   updated_at = excluded.updated_at"#
+        )
+        .bind(&t.media_id)
+        .bind(&t.language)
+        .bind(&t.title)
+        .bind(&t.overview)
+        .bind(&t.source)
+        .bind(t.updated_at)
+        .execute(&mut *tx)
+        .await?;
+        saved += result.rows_affected();
+    }
+    tx.commit().await?;
+    Ok(saved)
+}
+
+/// 删除媒体某个来源的全部翻译（如改绑 TMDB ID 后旧条目的翻译）
+pub async fn delete_media_translations_by_source(pool: &Pool<Sqlite>, media_id: &str, source: &str) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM media_translations WHERE media_id = ? AND source = ?")
+        .bind(media_id)
+        .bind(source)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// 获取媒体的所有翻译
+pub async fn get_media_translations(pool: &Pool<Sqlite>, media_id: &str) -> Result<Vec<MediaTranslation>> {
+    let translations = sqlx::query_as::<_, MediaTranslation>(
+        "SELECT * FROM media_translations WHERE media_id = ? ORDER BY language"
+    )
+    .bind(media_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(translations)
+}
+
+/// 批量获取多个媒体的翻译（列表响应按 Accept-Language 替换标题时使用）
+pub async fn get_translations_for_media(
+    pool: &Pool<Sqlite>,
+    media_ids: &[String],
+) -> Result<HashMap<String, Vec<MediaTranslation>>> {
+    let mut by_media: HashMap<String, Vec<MediaTranslation>> = HashMap::new();
+    if media_ids.is_empty() {
+        return Ok(by_media);
+    }
+    let placeholders = vec!["?"; media_ids.len()].join(", ");
+    let sql = format!("SELECT * FROM media_translations WHERE media_id IN ({})", placeholders);
+    let mut query = sqlx::query_as::<_, MediaTranslation>(&sql);
+    for id in media_ids {
+        query = query.bind(id);
+    }
+    for t in query.fetch_all(pool).await? {
+        by_media.entry(t.media_id.clone()).or_default().push(t);
+    }
+    Ok(by_media)
+}
+
+/// 删除媒体的某个语言翻译
+pub async fn delete_media_translation(pool: &Pool<Sqlite>, media_id: &str, language: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM media_translations WHERE media_id = ? AND language = ?")
+        .bind(media_id)
+        .bind(language)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
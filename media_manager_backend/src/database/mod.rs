@@ -1,22 +1,87 @@
 use sqlx::{sqlite::{SqlitePoolOptions, SqliteConnectOptions}, Pool, Sqlite};
 use anyhow::Result;
 use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::models::{MediaFilterOptions, MigrationStatus};
 
 pub mod schema;
 pub mod repository;
 pub mod query_builder;
 pub mod actor_repository;
 pub mod studio_repository;
+pub mod calendar_repository;
+pub mod wanted_repository;
+pub mod settings_repository;
+pub mod quality_repository;
+pub mod change_repository;
+pub mod peer_repository;
+pub mod share_repository;
+pub mod scan_rule_repository;
+pub mod scan_session_repository;
+pub mod media_file_repository;
+pub mod external_id_repository;
+pub mod media_translation_repository;
+pub mod link_check_repository;
+pub mod scrape_source_repository;
+pub mod favorite_repository;
+pub mod browsing_history_repository;
+pub mod tag_repository;
+pub mod idempotency_repository;
+pub mod filter_options_repository;
+pub mod maintenance_repository;
+pub mod library_root_repository;
+pub mod import_hook_repository;
+pub mod enrichment_repository;
+pub mod tmdb_list_repository;
+pub mod swarm_stats_repository;
+pub mod search_history_repository;
+pub mod collection_repository;
+pub mod offline_package_repository;
+pub mod artwork_repository;
 
-pub use repository::{DatabaseRepository, SqliteRepository};
+pub use repository::{
+    DatabaseRepository, DbTransaction, SqliteRepository, MAX_BIND_PARAMS,
+    get_media_with, insert_media_with, insert_media_batch_with, insert_media_files_with, update_media_with,
+};
 pub use query_builder::{MediaQueryBuilder, FullTextSearchBuilder};
 pub use actor_repository::*;
 pub use studio_repository::*;
+pub use calendar_repository::*;
+pub use wanted_repository::*;
+pub use settings_repository::*;
+pub use quality_repository::*;
+pub use change_repository::*;
+pub use peer_repository::*;
+pub use share_repository::*;
+pub use scan_rule_repository::*;
+pub use scan_session_repository::*;
+pub use media_file_repository::*;
+pub use external_id_repository::*;
+pub use media_translation_repository::*;
+pub use link_check_repository::*;
+pub use scrape_source_repository::*;
+pub use favorite_repository::*;
+pub use browsing_history_repository::*;
+pub use tag_repository::*;
+pub use idempotency_repository::*;
+pub use filter_options_repository::*;
+pub use maintenance_repository::*;
+pub use library_root_repository::*;
+pub use import_hook_repository::*;
+pub use enrichment_repository::*;
+pub use tmdb_list_repository::*;
+pub use swarm_stats_repository::*;
+pub use search_history_repository::*;
+pub use collection_repository::*;
+pub use offline_package_repository::*;
+pub use artwork_repository::*;
 
 #[derive(Clone)]
 pub struct Database {
     pool: Pool<Sqlite>,
     repository: SqliteRepository,
+    filter_options: FilterOptionsCache,
 }
 
 impl Database {
@@ -39,13 +104,17 @@ impl Database {
         
         // Run migrations
         tracing::info!("Running database migrations...");
-        sqlx::migrate!("./migrations").run(&pool).await?;
+        schema::check_schema_version(&pool).await?;
+        schema::MIGRATOR.run(&pool).await?;
         
         // Verify schema integrity
         schema::verify_schema(&pool).await?;
         
         // Clean up expired cache entries
         schema::cleanup_expired_cache(&pool).await?;
+
+        // Fill title sort keys / romanized forms for rows created before they existed
+        schema::backfill_title_transliteration(&pool).await?;
         
         // Log database statistics
         let stats = schema::get_database_stats(&pool).await?;
@@ -59,7 +128,7 @@ impl Database {
         
         let repository = SqliteRepository::new(pool.clone());
         
-        Ok(Self { pool, repository })
+        Ok(Self { pool, repository, filter_options: FilterOptionsCache::default() })
     }
     
     pub fn pool(&self) -> &Pool<Sqlite> {
@@ -70,6 +139,16 @@ impl Database {
         &self.repository
     }
     
+    /// 获取媒体筛选选项（带缓存）
+    pub async fn filter_options(&self) -> Result<Arc<MediaFilterOptions>> {
+        self.filter_options.get(&self.pool).await
+    }
+    
+    /// 获取数据库迁移状态
+    pub async fn migration_status(&self) -> Result<MigrationStatus> {
+        schema::get_migration_status(&self.pool).await
+    }
+    
     /// 获取数据库统计信息
     pub async fn get_stats(&self) -> Result<schema::DatabaseStats> {
         schema::get_database_stats(&self.pool).await
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Sqlite};
+use crate::models::{CreateOfflinePackageRequest, OfflinePackage};
+
+/// 创建离线包任务
+pub async fn create_offline_package(
+    pool: &Pool<Sqlite>,
+    media_id: &str,
+    request: &CreateOfflinePackageRequest,
+) -> Result<OfflinePackage> {
+    let package = sqlx::query_as::<_, OfflinePackage>(
+        r#"INSERT INTO offline_packages (id, media_id, edition, part, max_size_mb, max_height, status, created_at)
+           VALUES (?, ?, ?, ?, ?, ?, 'pending', ?)
+           RETURNING *"#
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(media_id)
+    .bind(&request.edition)
+    .bind(request.part as i64)
+    .bind(request.max_size_mb())
+    .bind(request.max_height())
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await?;
+    Ok(package)
+}
+
+/// 取出最早的待执行任务并标记为执行中
+pub async fn claim_next_offline_package(pool: &Pool<Sqlite>) -> Result<Option<OfflinePackage>> {
+    let package = sqlx::query_as::<_, OfflinePackage>(
+        r#"UPDATE offline_packages SET status = 'running', started_at = ?
+           WHERE id = (
+               SELECT id FROM offline_packages WHERE status = 'pending' ORDER BY created_at LIMIT 1
+           )
+           RETURNING *"#
+    )
+    .bind(Utc::now())
+    .fetch_optional(pool)
+    .await?;
+    Ok(package)
+}
+
+/// 转码完成：保存文件并生成下载令牌，返回记录是否仍存在（转码期间可能被删除）
+pub async fn complete_offline_package(
+    pool: &Pool<Sqlite>,
+    id: &str,
+    file_path: &str,
+    file_size: i64,
+    download_token: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE offline_packages SET status = 'ready', file_path = ?, file_size = ?, download_token = ?,
+               expires_at = ?, finished_at = ?
+           WHERE id = ?"#
+    )
+    .bind(file_path)
+    .bind(file_size)
+    .bind(download_token)
+    .bind(expires_at)
+    .bind(Utc::now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// 转码失败
+pub async fn fail_offline_package(pool: &Pool<Sqlite>, id: &str, error: &str) -> Result<()> {
+    sqlx::query("UPDATE offline_packages SET status = 'failed', error = ?, finished_at = ? WHERE id = ?")
+        .bind(error)
+        .bind(Utc::now())
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 把上次退出时仍在执行的任务放回队列、清除中断的下载，返回放回的任务数量
+pub async fn requeue_running_offline_packages(pool: &Pool<Sqlite>) -> Result<u64> {
+    sqlx::query("UPDATE offline_packages SET downloading_at = NULL WHERE downloading_at IS NOT NULL")
+        .execute(pool)
+        .await?;
+    let result = sqlx::query("UPDATE offline_packages SET status = 'pending', started_at = NULL WHERE status = 'running'")
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// 获取离线包（最新的在前）
+pub async fn list_offline_packages(pool: &Pool<Sqlite>, media_id: Option<&str>) -> Result<Vec<OfflinePackage>> {
+    let packages = sqlx::query_as::<_, OfflinePackage>(
+        "SELECT * FROM offline_packages WHERE (? IS NULL OR media_id = ?) ORDER BY created_at DESC"
+    )
+    .bind(media_id)
+    .bind(media_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(packages)
+}
+
+/// 获取离线包
+pub async fn get_offline_package(pool: &Pool<Sqlite>, id: &str) -> Result<Option<OfflinePackage>> {
+    let package = sqlx::query_as::<_, OfflinePackage>("SELECT * FROM offline_packages WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(package)
+}
+
+/// 删除离线包记录，返回被删除的记录（用于删除文件）
+pub async fn delete_offline_package(pool: &Pool<Sqlite>, id: &str) -> Result<Option<OfflinePackage>> {
+    let package = sqlx::query_as::<_, OfflinePackage>("DELETE FROM offline_packages WHERE id = ? RETURNING *")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(package)
+}
+
+/// 用令牌占用下载（已过期、已下载或正在下载时返回 None），保证同一时间只有一个下载
+pub async fn claim_offline_download(pool: &Pool<Sqlite>, token: &str) -> Result<Option<OfflinePackage>> {
+    let now = Utc::now();
+    let package = sqlx::query_as::<_, OfflinePackage>(
+        r#"UPDATE offline_packages SET downloading_at = ?
+           WHERE download_token = ? AND status = 'ready' AND downloading_at IS NULL AND expires_at > ?
+           RETURNING *"#
+    )
+    .bind(now)
+    .bind(token)
+    .bind(now)
+    .fetch_optional(pool)
+    .await?;
+    Ok(package)
+}
+
+/// 下载中断：释放占用，链接仍可再次使用
+pub async fn release_offline_download(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
+    sqlx::query("UPDATE offline_packages SET downloading_at = NULL WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 下载完成：令牌失效
+pub async fn mark_offline_downloaded(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
+    sqlx::query(
+        r#"UPDATE offline_packages SET status = 'downloaded', download_token = NULL, downloading_at = NULL, downloaded_at = ?
+           WHERE id = ?"#
+    )
+    .bind(Utc::now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 把过期且未在下载的离线包标记为过期，返回它们（用于删除文件）
+pub async fn expire_offline_packages(pool: &Pool<Sqlite>) -> Result<Vec<OfflinePackage>> {
+    let packages = sqlx::query_as::<_, OfflinePackage>(
+        r#"UPDATE offline_packages SET status = 'expired', download_token = NULL
+           WHERE status = 'ready' AND downloading_at IS NULL AND expires_at <= ?
+           RETURNING *"#
+    )
+    .bind(Utc::now())
+    .fetch_all(pool)
+    .await?;
+    Ok(packages)
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::{CreatePeerRequest, SyncPeer};
+use super::settings_repository::{get_setting, set_setting};
+
+/// 保存本实例ID的设置键
+const INSTANCE_ID_KEY: &str = "instance_id";
+
+// ============ Instance Identity ============
+
+/// 获取本实例ID（首次调用时生成并保存）
+pub async fn get_instance_id(pool: &Pool<Sqlite>) -> Result<String> {
+    if let Some(id) = get_setting(pool, INSTANCE_ID_KEY).await? {
+        return Ok(id);
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    set_setting(pool, INSTANCE_ID_KEY, &id, Some("本实例ID（多实例同步时识别变更来源）")).await?;
+    Ok(id)
+}
+
+// ============ Sync Peers ============
+
+/// 添加同步对端
+pub async fn create_peer(pool: &Pool<Sqlite>, req: CreatePeerRequest) -> Result<SyncPeer> {
+    let id = uuid::Uuid::new_v4().to_string();
+
+    sqlx::query(
+        r#"INSERT INTO sync_peers (id, name, base_url, token, enabled, created_at)
+           VALUES (?, ?, ?, ?, ?, datetime('now'))"#
+    )
+    .bind(&id)
+    .bind(req.name.trim())
+    .bind(req.base_url.trim().trim_end_matches('/'))
+    .bind(req.token.filter(|t| !t.is_empty()))
+    .bind(req.enabled.unwrap_or(true))
+    .execute(pool)
+    .await?;
+
+    get_peer(pool, &id).await
+}
+
+/// 根据ID获取同步对端
+pub async fn get_peer(pool: &Pool<Sqlite>, id: &str) -> Result<SyncPeer> {
+    let peer = sqlx::query_as::<_, SyncPeer>("SELECT * FROM sync_peers WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+    Ok(peer)
+}
+
+/// 获取所有同步对端
+pub async fn list_peers(pool: &Pool<Sqlite>, enabled_only: bool) -> Result<Vec<SyncPeer>> {
+    let sql = if enabled_only {
+        "SELECT * FROM sync_peers WHERE enabled = 1 ORDER BY created_at"
+    } else {
+        "SELECT * FROM sync_peers ORDER BY created_at"
+    };
+    let peers = sqlx::query_as::<_, SyncPeer>(sql)
+        .fetch_all(pool)
+        .await?;
+    Ok(peers)
+}
+
+/// 删除同步对端
+pub async fn delete_peer(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
+    let result = sqlx::query("DELETE FROM sync_peers WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(anyhow::anyhow!("Peer not found"));
+    }
+    Ok(())
+}
+
+/// 记录同步进度
+pub async fn update_peer_progress(
+    pool: &Pool<Sqlite>,
+    id: &str,
+    remote_instance_id: Option<&str>,
+    last_pulled_seq: i64,
+    last_pushed_seq: i64,
+) -> Result<()> {
+    sqlx::query(
+        r#"UPDATE sync_peers
+           SET remote_instance_id = COALESCE(?, remote_instance_id),
+               last_pulled_seq = ?, last_pushed_seq = ?
+           WHERE id = ?"#
+    )
+    .bind(remote_instance_id)
+    .bind(last_pulled_seq)
+    .bind(last_pushed_seq)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 记录一次同步完成（error 为空表示成功）
+pub async fn mark_peer_synced(pool: &Pool<Sqlite>, id: &str, error: Option<&str>) -> Result<()> {
+    sqlx::query("UPDATE sync_peers SET last_synced_at = datetime('now'), last_error = ? WHERE id = ?")
+        .bind(error)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite, Row};
+use crate::models::{QualityProfile, MediaUpgrade, MediaUpgradeWithInfo};
+use super::settings_repository::{get_setting, set_setting};
+
+const QUALITY_PROFILES_KEY: &str = "quality_profiles";
+
+// ============ Quality Profiles ============
+
+/// 获取质量配置列表
+pub async fn get_quality_profiles(pool: &Pool<Sqlite>) -> Result<Vec<QualityProfile>> {
+    let profiles = match get_setting(pool, QUALITY_PROFILES_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => Vec::new(),
+    };
+    Ok(profiles)
+}
+
+/// 保存质量配置列表
+pub async fn save_quality_profiles(pool: &Pool<Sqlite>, profiles: &[QualityProfile]) -> Result<()> {
+    let value = serde_json::to_string(profiles)?;
+    set_setting(
+        pool,
+        QUALITY_PROFILES_KEY,
+        &value,
+        Some("Quality profiles used by magnet monitoring and upgrade detection (JSON array)"),
+    ).await
+}
+
+// ============ Media Upgrades ============
+
+/// 记录可升级媒体（同一媒体只保留最新一条）
+pub async fn record_upgrade(pool: &Pool<Sqlite>, upgrade: &MediaUpgrade) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO media_upgrades
+           (media_id, current_quality, available_quality, source, source_title, magnet_link, file_path, detected_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, datetime('now'))
+           ON CONFLICT(media_id) DO UPDATE SET
+               current_quality = excluded.current_quality,
+               available_quality = excluded.available_quality,
+               source = excluded.source,
+               source_title = excluded.source_title,
+               magnet_link = excluded.magnet_link,
+               file_path = excluded.file_path,
+               detected_at = datetime('now')"#
+    )
+    .bind(&upgrade.media_id)
+    .bind(&upgrade.current_quality)
+    .bind(&upgrade.available_quality)
+    .bind(&upgrade.source)
+    .bind(&upgrade.source_title)
+    .bind(&upgrade.magnet_link)
+    .bind(&upgrade.file_path)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 获取单个媒体的升级记录
+pub async fn get_upgrade(pool: &Pool<Sqlite>, media_id: &str) -> Result<Option<MediaUpgrade>> {
+    let upgrade = sqlx::query_as::<_, MediaUpgrade>("SELECT * FROM media_upgrades WHERE media_id = ?")
+        .bind(media_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(upgrade)
+}
+
+/// 获取所有可升级媒体
+pub async fn list_upgrades(pool: &Pool<Sqlite>) -> Result<Vec<MediaUpgradeWithInfo>> {
+    let rows = sqlx::query(
+        r#"SELECT u.*, m.title AS media_title, m.code AS media_code, m.poster_url AS media_poster_url
+           FROM media_upgrades u
+           JOIN media_items m ON m.id = u.media_id
+           ORDER BY u.detected_at DESC"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut items = Vec::with_capacity(rows.len());
+    for row in rows {
+        let upgrade = <MediaUpgrade as sqlx::FromRow<_>>::from_row(&row)?;
+        items.push(MediaUpgradeWithInfo {
+            upgrade,
+            title: row.try_get("media_title")?,
+            code: row.try_get("media_code")?,
+            poster_url: row.try_get("media_poster_url")?,
+        });
+    }
+    Ok(items)
+}
+
+/// 移除升级记录，返回是否存在
+pub async fn remove_upgrade(pool: &Pool<Sqlite>, media_id: &str) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM media_upgrades WHERE media_id = ?")
+        .bind(media_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// 获取媒体的本地文件路径（media_files 以及兼容字段 local_file_path）
+pub async fn get_local_file_paths(pool: &Pool<Sqlite>, media_id: &str) -> Result<Vec<String>> {
+    let paths: Vec<String> = sqlx::query_scalar(
+        r#"SELECT file_path FROM media_files WHERE media_id = ?
+           UNION
+           SELECT local_file_path FROM media_items
+           WHERE id = ? AND local_file_path IS NOT NULL AND local_file_path != ''"#
+    )
+    .bind(media_id)
+    .bind(media_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(paths)
+}
@@ -1,6 +1,14 @@
 use crate::models::{SearchFilters, SortOption, SortOrder};
 use sqlx::{QueryBuilder, Sqlite};
 
+/// 厂商过滤条件（两个参数都绑定厂商名）：已关联厂商表的媒体按外键匹配，未关联的按 studio 文本字段匹配
+pub const STUDIO_FILTER: &str =
+    "(studio_id IN (SELECT id FROM studios WHERE name = ? COLLATE NOCASE) OR (studio_id IS NULL AND studio = ?))";
+
+/// 系列过滤条件（两个参数都绑定系列名），规则同 STUDIO_FILTER
+pub const SERIES_FILTER: &str =
+    "(series_id IN (SELECT id FROM series WHERE name = ? COLLATE NOCASE) OR (series_id IS NULL AND series = ?))";
+
 /// 动态查询构建器
 pub struct MediaQueryBuilder {
     query: QueryBuilder<'static, Sqlite>,
@@ -9,7 +17,12 @@ pub struct MediaQueryBuilder {
 
 impl MediaQueryBuilder {
     pub fn new() -> Self {
-        let query = QueryBuilder::new("SELECT * FROM media_items");
+        Self::select("*")
+    }
+    
+    /// 只查询指定的列（如 "id"）
+    pub fn select(columns: &str) -> Self {
+        let query = QueryBuilder::new(format!("SELECT {} FROM media_items", columns));
         Self {
             query,
             has_where: false,
@@ -74,8 +87,7 @@ impl MediaQueryBuilder {
         if let Some(ref studio) = filters.studio {
             if !studio.trim().is_empty() {
                 self.add_where_clause();
-                self.query.push("studio = ");
-                self.query.push_bind(studio.clone());
+                self.push_name_filter(STUDIO_FILTER, studio);
             }
         }
         
@@ -83,37 +95,32 @@ impl MediaQueryBuilder {
         if let Some(ref series) = filters.series {
             if !series.trim().is_empty() {
                 self.add_where_clause();
-                self.query.push("series = ");
-                self.query.push_bind(series.clone());
+                self.push_name_filter(SERIES_FILTER, series);
             }
         }
         
         self
     }
     
-    /// 添加演员过滤（需要 JOIN actor_media 表）
+    /// 按 ? 拆分过滤条件，每个参数都绑定 name
+    fn push_name_filter(&mut self, filter: &str, name: &str) {
+        let mut parts = filter.split('?').peekable();
+        while let Some(part) = parts.next() {
+            self.query.push(part);
+            if parts.peek().is_some() {
+                self.query.push_bind(name.to_string());
+            }
+        }
+    }
+    
+    /// 添加演员过滤
     pub fn with_actor_filter(mut self, filters: &SearchFilters) -> Self {
         if let Some(ref actor_id) = filters.actor_id {
             if !actor_id.trim().is_empty() {
-                // 需要重建查询以包含 JOIN
-                let current_sql = self.query.sql();
-                if current_sql.starts_with("SELECT * FROM media_items") {
-                    self.query = QueryBuilder::new(
-                        "SELECT DISTINCT m.* FROM media_items m JOIN actor_media am ON m.id = am.media_id"
-                    );
-                } else if current_sql.starts_with("SELECT m.* FROM media_items m") && !current_sql.contains("actor_media") {
-                    // 已经有其他 JOIN，添加 actor_media JOIN
-                    let new_sql = current_sql.replace(
-                        "FROM media_items m",
-                        "FROM media_items m JOIN actor_media am ON m.id = am.media_id"
-                    );
-                    self.query = QueryBuilder::new(&new_sql);
-                }
-                self.has_where = false;
-                
                 self.add_where_clause();
-                self.query.push("am.actor_id = ");
+                self.query.push("id IN (SELECT media_id FROM actor_media WHERE actor_id = ");
                 self.query.push_bind(actor_id.clone());
+                self.query.push(")");
             }
         }
         
@@ -121,18 +128,12 @@ impl MediaQueryBuilder {
     }
     
     pub fn with_collection_filters(mut self, filters: &SearchFilters) -> Self {
-        // 如果有观看状态过滤，需要JOIN collections表
-        if filters.watch_status.is_some() {
-            self.query = QueryBuilder::new(
-                "SELECT m.* FROM media_items m JOIN collections c ON m.id = c.media_id"
-            );
-            self.has_where = false;
-            
-            if let Some(ref status) = filters.watch_status {
-                self.add_where_clause();
-                self.query.push("c.watch_status = ");
-                self.query.push_bind(format!("{:?}", status));
-            }
+        // 观看状态过滤
+        if let Some(ref status) = filters.watch_status {
+            self.add_where_clause();
+            self.query.push("id IN (SELECT media_id FROM collections WHERE watch_status = ");
+            self.query.push_bind(format!("{:?}", status));
+            self.query.push(")");
         }
         
         self
@@ -140,28 +141,13 @@ impl MediaQueryBuilder {
     
     pub fn with_sorting(mut self, filters: &SearchFilters) -> Self {
         self.query.push(" ORDER BY ");
-        
-        match filters.sort_by {
-            SortOption::Title => { self.query.push("title"); },
-            SortOption::Year => { self.query.push("year"); },
-            SortOption::Rating => { self.query.push("rating"); },
-            SortOption::AddedDate => { self.query.push("created_at"); },
-            SortOption::LastWatched => {
-                // 如果按最后观看时间排序，需要JOIN collections表
-                if !self.query.sql().contains("JOIN collections") {
-                    // 重新构建查询以包含JOIN
-                    let current_sql = self.query.sql().replace("SELECT * FROM media_items", 
-                        "SELECT m.* FROM media_items m LEFT JOIN collections c ON m.id = c.media_id");
-                    self.query = QueryBuilder::new(&current_sql);
-                }
-                self.query.push("c.last_watched");
-            }
-        }
-        
+        self.query.push(sort_expression(&filters.sort_by, "media_items"));
+
         match filters.sort_order {
             SortOrder::Ascending => { self.query.push(" ASC"); },
             SortOrder::Descending => { self.query.push(" DESC"); },
         }
+        self.query.push(" NULLS LAST");
         
         self
     }
@@ -200,6 +186,40 @@ impl Default for MediaQueryBuilder {
     }
 }
 
+/// 排序表达式（table 为 media_items 在查询中的名称或别名）
+pub fn sort_expression(sort: &SortOption, table: &str) -> String {
+    match sort {
+        SortOption::Title => format!("COALESCE({t}.title_sort, {t}.title)", t = table),
+        SortOption::Year => format!("{}.year", table),
+        SortOption::Rating => format!("{}.rating", table),
+        SortOption::AddedDate => format!("{}.created_at", table),
+        SortOption::Runtime => format!("{}.runtime", table),
+        SortOption::ReleaseDate => format!("{}.release_date", table),
+        SortOption::FileSize => format!(
+            "COALESCE((SELECT SUM(mf.file_size) FROM media_files mf WHERE mf.media_id = {t}.id), {t}.file_size)",
+            t = table
+        ),
+        SortOption::LastWatched => format!(
+            "(SELECT col.last_watched FROM collections col WHERE col.media_id = {}.id)",
+            table
+        ),
+        SortOption::CollectionAdded => format!(
+            "(SELECT col.added_at FROM collections col WHERE col.media_id = {}.id)",
+            table
+        ),
+        SortOption::Random(seed) => seeded_hash(&format!("{}.rowid", table), *seed),
+    }
+}
+
+/// 行号与种子混合后的伪随机值（31 位内运算，不会溢出 INTEGER）。
+/// 用 (a | b) - (a & b) 代替 SQLite 没有的异或
+fn seeded_hash(column: &str, seed: u32) -> String {
+    let xor = |a: &str, b: &str| format!("(({a} | {b}) - ({a} & {b}))", a = a, b = b);
+    let mixed = format!("(({} * 1103515245) % 2147483648)", column);
+    let mixed = format!("(({} * 1597334677) % 2147483648)", xor(&mixed, &(seed & 0x7fff_ffff).to_string()));
+    xor(&mixed, &format!("({} >> 16)", mixed))
+}
+
 /// 全文搜索查询构建器
 pub struct FullTextSearchBuilder {
     query: QueryBuilder<'static, Sqlite>,
@@ -232,4 +252,39 @@ impl FullTextSearchBuilder {
     pub fn build(self) -> QueryBuilder<'static, Sqlite> {
         self.query
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_actor_and_status_filters_keep_other_conditions() {
+        let filters = SearchFilters {
+            query: None,
+            media_type: None,
+            genres: Vec::new(),
+            year_range: None,
+            rating_range: None,
+            watch_status: Some(crate::models::WatchStatus::Watching),
+            actor_id: Some("a1".to_string()),
+            studio: Some("S1".to_string()),
+            series: None,
+            sort_by: SortOption::Title,
+            sort_order: SortOrder::Ascending,
+            limit: None,
+            offset: None,
+        };
+        let builder = MediaQueryBuilder::select("id")
+            .with_filters(&filters)
+            .with_actor_filter(&filters)
+            .with_collection_filters(&filters)
+            .build();
+        assert_eq!(
+            builder.sql(),
+            "SELECT id FROM media_items WHERE (studio_id IN (SELECT id FROM studios WHERE name = ? COLLATE NOCASE) \
+             OR (studio_id IS NULL AND studio = ?)) \
+             AND id IN (SELECT media_id FROM actor_media WHERE actor_id = ?) \
+             AND id IN (SELECT media_id FROM collections WHERE watch_status = ?)"
+        );
+    }
+}
@@ -1,9 +1,11 @@
 use async_trait::async_trait;
-use sqlx::{Pool, Sqlite};
+use sqlx::{Pool, QueryBuilder, Sqlite, SqliteConnection};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 
-use crate::models::{MediaItem, MediaFile, Collection, SearchFilters};
+use crate::models::{MediaItem, MediaFile, Collection, SearchFilters, SortOption};
+use super::query_builder::{sort_expression, SERIES_FILTER, STUDIO_FILTER};
+use crate::services::transliteration;
 
 /// 数据库仓库接口
 #[async_trait]
@@ -11,8 +13,10 @@ pub trait DatabaseRepository: Send + Sync {
     // 媒体项目操作
     async fn get_media_list(&self, limit: i32, offset: i32) -> Result<Vec<MediaItem>>;
     async fn get_media_list_filtered(&self, limit: i32, offset: i32, filters: &MediaListFilters) -> Result<(Vec<MediaItem>, i64)>;
+    async fn get_media_ids_filtered(&self, filters: &MediaListFilters) -> Result<Vec<String>>;
     async fn get_media_by_id(&self, id: &str) -> Result<Option<MediaItem>>;
     async fn insert_media(&self, media: &MediaItem) -> Result<()>;
+    async fn insert_media_batch(&self, media: &[MediaItem]) -> Result<()>;
     async fn update_media(&self, media: &MediaItem) -> Result<()>;
     async fn delete_media(&self, id: &str) -> Result<()>;
     async fn media_exists(&self, id: &str) -> Result<bool>;
@@ -28,6 +32,7 @@ pub trait DatabaseRepository: Send + Sync {
     // 搜索操作
     async fn search_media(&self, query: &str) -> Result<Vec<MediaItem>>;
     async fn search_media_with_filters(&self, filters: &SearchFilters) -> Result<Vec<MediaItem>>;
+    async fn search_media_ids_with_filters(&self, filters: &SearchFilters) -> Result<Vec<String>>;
     async fn get_media_count(&self) -> Result<i64>;
     async fn get_collection_count(&self) -> Result<i64>;
     
@@ -61,8 +66,15 @@ pub trait DatabaseRepository: Send + Sync {
     async fn get_media_files(&self, media_id: &str) -> Result<Vec<MediaFile>>;
     async fn update_media_file_info(&self, media_id: &str, first_file_path: &str, total_size: i64) -> Result<()>;
     async fn delete_media_files(&self, media_id: &str) -> Result<()>;
+    
+    // 事务
+    async fn begin(&self) -> Result<DbTransaction>;
+    async fn commit(&self, tx: DbTransaction) -> Result<()>;
 }
 
+/// 数据库事务（多步写入时使用，提交前失败会自动回滚）
+pub type DbTransaction = sqlx::Transaction<'static, Sqlite>;
+
 /// 媒体列表筛选条件
 #[derive(Debug, Clone, Default)]
 pub struct MediaListFilters {
@@ -72,8 +84,102 @@ pub struct MediaListFilters {
     pub keyword: Option<String>,
     pub year: Option<i32>,
     pub genre: Option<String>,
+    /// true: 只要没有海报的；false: 只要有海报的
+    pub missing_poster: Option<bool>,
+    /// 发售日期范围（YYYY-MM-DD，包含边界）
+    pub release_date_from: Option<String>,
+    pub release_date_to: Option<String>,
     pub sort_by: String,
     pub sort_order: String,
+    /// 随机排序的种子
+    pub seed: Option<u32>,
+}
+
+/// 筛选条件的绑定参数
+enum FilterArg {
+    Text(String),
+    Int(i32),
+}
+
+/// 按顺序绑定筛选参数（query_as / query_scalar 通用）
+macro_rules! bind_filter_args {
+    ($query:expr, $args:expr) => {{
+        let mut query = $query;
+        for arg in $args {
+            query = match arg {
+                FilterArg::Text(value) => query.bind(value.clone()),
+                FilterArg::Int(value) => query.bind(*value),
+            };
+        }
+        query
+    }};
+}
+
+impl MediaListFilters {
+    /// 构建 WHERE 子句及对应的绑定参数
+    fn where_clause(&self) -> (String, Vec<FilterArg>) {
+        let mut conditions = Vec::new();
+        let mut args = Vec::new();
+        
+        if let Some(ref media_type) = self.media_type {
+            conditions.push("media_type = ?");
+            args.push(FilterArg::Text(media_type.clone()));
+        }
+        if let Some(ref studio) = self.studio {
+            conditions.push(STUDIO_FILTER);
+            args.push(FilterArg::Text(studio.clone()));
+            args.push(FilterArg::Text(studio.clone()));
+        }
+        if let Some(ref series) = self.series {
+            conditions.push(SERIES_FILTER);
+            args.push(FilterArg::Text(series.clone()));
+            args.push(FilterArg::Text(series.clone()));
+        }
+        if let Some(year) = self.year {
+            conditions.push("year = ?");
+            args.push(FilterArg::Int(year));
+        }
+        if let Some(ref genre) = self.genre {
+            conditions.push("genres LIKE ?");
+            args.push(FilterArg::Text(format!("%{}%", genre)));
+        }
+        if let Some(ref keyword) = self.keyword {
+            if !keyword.is_empty() {
+                conditions.push("(code LIKE ? OR title LIKE ? OR original_title LIKE ? OR overview LIKE ? OR title_romanized LIKE ?)");
+                let like_pattern = format!("%{}%", keyword);
+                for _ in 0..5 {
+                    args.push(FilterArg::Text(like_pattern.clone()));
+                }
+            }
+        }
+        match self.missing_poster {
+            Some(true) => conditions.push("(poster_url IS NULL OR poster_url = '')"),
+            Some(false) => conditions.push("(poster_url IS NOT NULL AND poster_url != '')"),
+            None => {}
+        }
+        if let Some(ref from) = self.release_date_from {
+            conditions.push("release_date >= ?");
+            args.push(FilterArg::Text(from.clone()));
+        }
+        if let Some(ref to) = self.release_date_to {
+            conditions.push("release_date <= ?");
+            args.push(FilterArg::Text(to.clone()));
+        }
+        
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        (where_clause, args)
+    }
+    
+    /// 构建 ORDER BY 子句（无效的 sort_by 按创建时间排序）
+    fn order_clause(&self) -> String {
+        let sort = self.sort_by.parse().unwrap_or(SortOption::AddedDate).with_seed(self.seed);
+        let sort_order = if self.sort_order.to_lowercase() == "asc" { "ASC" } else { "DESC" };
+        format!("{} {} NULLS LAST", sort_expression(&sort, "media_items"), sort_order)
+    }
 }
 
 /// SQLite 数据库仓库实现
@@ -88,6 +194,172 @@ impl SqliteRepository {
     }
 }
 
+// ============ Media Writes ============
+
+/// 按ID获取媒体（可传入连接池或事务）
+pub async fn get_media_with<'e, E>(executor: E, id: &str) -> Result<Option<MediaItem>>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    let media = sqlx::query_as::<_, MediaItem>("SELECT * FROM media_items WHERE id = ?")
+        .bind(id)
+        .fetch_optional(executor)
+        .await?;
+    Ok(media)
+}
+
+/// SQLite 单条语句允许的最多绑定参数（兼容旧版本的默认上限）
+pub const MAX_BIND_PARAMS: usize = 999;
+
+const MEDIA_INSERT_COLUMNS: usize = 34;
+
+/// 构造多行 INSERT 语句（行数由调用方按 MAX_BIND_PARAMS 分批）
+fn media_insert_query(media: &[MediaItem]) -> QueryBuilder<'_, Sqlite> {
+    let mut builder = QueryBuilder::new(
+        r#"INSERT INTO media_items (
+            id, code, external_ids, title, original_title, year, media_type,
+            genres, rating, vote_count, poster_url, backdrop_url, overview,
+            runtime, release_date, cast, crew, language, country,
+            budget, revenue, status, play_links, download_links,
+            preview_urls, preview_video_urls, cover_video_url, trailer_url, studio, series,
+            title_sort, title_romanized, created_at, updated_at
+        ) "#
+    );
+    builder.push_values(media, |mut row, media| {
+        row.push_bind(&media.id)
+            .push_bind(&media.code)
+            .push_bind(&media.external_ids)
+            .push_bind(&media.title)
+            .push_bind(&media.original_title)
+            .push_bind(media.year)
+            .push_bind(&media.media_type)
+            .push_bind(&media.genres)
+            .push_bind(media.rating)
+            .push_bind(media.vote_count)
+            .push_bind(&media.poster_url)
+            .push_bind(&media.backdrop_url)
+            .push_bind(&media.overview)
+            .push_bind(media.runtime)
+            .push_bind(&media.release_date)
+            .push_bind(&media.cast)
+            .push_bind(&media.crew)
+            .push_bind(&media.language)
+            .push_bind(&media.country)
+            .push_bind(media.budget)
+            .push_bind(media.revenue)
+            .push_bind(&media.status)
+            .push_bind(&media.play_links)
+            .push_bind(&media.download_links)
+            .push_bind(&media.preview_urls)
+            .push_bind(&media.preview_video_urls)
+            .push_bind(&media.cover_video_url)
+            .push_bind(&media.trailer_url)
+            .push_bind(&media.studio)
+            .push_bind(&media.series)
+            .push_bind(transliteration::sort_key(&media.title))
+            .push_bind(transliteration::searchable_form(&media.title, media.original_title.as_deref()))
+            .push_bind(media.created_at)
+            .push_bind(media.updated_at);
+    });
+    builder
+}
+
+/// 插入媒体（可传入连接池或事务）
+pub async fn insert_media_with<'e, E>(executor: E, media: &MediaItem) -> Result<()>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    media_insert_query(std::slice::from_ref(media)).build().execute(executor).await?;
+    Ok(())
+}
+
+/// 批量插入媒体（多行 VALUES，按参数上限分批），调用方负责事务
+pub async fn insert_media_batch_with(conn: &mut SqliteConnection, media: &[MediaItem]) -> Result<()> {
+    for chunk in media.chunks(MAX_BIND_PARAMS / MEDIA_INSERT_COLUMNS) {
+        media_insert_query(chunk).build().execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+const MEDIA_FILE_INSERT_COLUMNS: usize = 8;
+
+/// 批量插入媒体文件记录（多行 VALUES，按参数上限分批），调用方负责事务
+pub async fn insert_media_files_with(conn: &mut SqliteConnection, files: &[MediaFile]) -> Result<()> {
+    for chunk in files.chunks(MAX_BIND_PARAMS / MEDIA_FILE_INSERT_COLUMNS) {
+        let mut builder = QueryBuilder::<Sqlite>::new(
+            "INSERT INTO media_files (id, media_id, file_path, file_size, part_number, part_label, created_at, edition) "
+        );
+        builder.push_values(chunk, |mut row, file| {
+            row.push_bind(&file.id)
+                .push_bind(&file.media_id)
+                .push_bind(&file.file_path)
+                .push_bind(file.file_size)
+                .push_bind(file.part_number)
+                .push_bind(&file.part_label)
+                .push_bind(file.created_at)
+                .push_bind(&file.edition);
+        });
+        builder.build().execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// 更新媒体（可传入连接池或事务）
+pub async fn update_media_with<'e, E>(executor: E, media: &MediaItem) -> Result<()>
+where
+    E: sqlx::Executor<'e, Database = Sqlite>,
+{
+    sqlx::query(
+        r#"
+        UPDATE media_items SET
+            code = ?, external_ids = ?, title = ?, original_title = ?, year = ?, media_type = ?,
+            genres = ?, rating = ?, vote_count = ?, poster_url = ?, backdrop_url = ?,
+            overview = ?, runtime = ?, release_date = ?, cast = ?, crew = ?,
+            language = ?, country = ?, budget = ?, revenue = ?, status = ?,
+            play_links = ?, download_links = ?, preview_urls = ?, preview_video_urls = ?,
+            cover_video_url = ?, trailer_url = ?, studio = ?, series = ?,
+            title_sort = ?, title_romanized = ?, updated_at = datetime('now')
+        WHERE id = ?
+        "#
+    )
+    .bind(&media.code)
+    .bind(&media.external_ids)
+    .bind(&media.title)
+    .bind(&media.original_title)
+    .bind(media.year)
+    .bind(&media.media_type)
+    .bind(&media.genres)
+    .bind(media.rating)
+    .bind(media.vote_count)
+    .bind(&media.poster_url)
+    .bind(&media.backdrop_url)
+    .bind(&media.overview)
+    .bind(media.runtime)
+    .bind(&media.release_date)
+    .bind(&media.cast)
+    .bind(&media.crew)
+    .bind(&media.language)
+    .bind(&media.country)
+    .bind(media.budget)
+    .bind(media.revenue)
+    .bind(&media.status)
+    .bind(&media.play_links)
+    .bind(&media.download_links)
+    .bind(&media.preview_urls)
+    .bind(&media.preview_video_urls)
+    .bind(&media.cover_video_url)
+    .bind(&media.trailer_url)
+    .bind(&media.studio)
+    .bind(&media.series)
+    .bind(transliteration::sort_key(&media.title))
+    .bind(transliteration::searchable_form(&media.title, media.original_title.as_deref()))
+    .bind(&media.id)
+    .execute(executor)
+    .await?;
+    
+    Ok(())
+}
+
 #[async_trait]
 impl DatabaseRepository for SqliteRepository {
     async fn get_media_list(&self, limit: i32, offset: i32) -> Result<Vec<MediaItem>> {
@@ -103,48 +375,12 @@ impl DatabaseRepository for SqliteRepository {
     }
     
     async fn get_media_list_filtered(&self, limit: i32, offset: i32, filters: &MediaListFilters) -> Result<(Vec<MediaItem>, i64)> {
-        // 构建 WHERE 子句
-        let mut conditions = Vec::new();
-        
-        if filters.media_type.is_some() {
-            conditions.push("media_type = ?");
-        }
-        if filters.studio.is_some() {
-            conditions.push("studio = ?");
-        }
-        if filters.series.is_some() {
-            conditions.push("series = ?");
-        }
-        if filters.year.is_some() {
-            conditions.push("year = ?");
-        }
-        if filters.genre.is_some() {
-            conditions.push("genres LIKE ?");
-        }
-        if filters.keyword.as_ref().map(|k| !k.is_empty()).unwrap_or(false) {
-            conditions.push("(code LIKE ? OR title LIKE ? OR original_title LIKE ? OR overview LIKE ?)");
-        }
-        
-        let where_clause = if conditions.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", conditions.join(" AND "))
-        };
-        
-        // 排序
-        let sort_column = match filters.sort_by.as_str() {
-            "year" => "year",
-            "rating" => "rating",
-            "title" => "title",
-            "release_date" => "release_date",
-            _ => "created_at",
-        };
-        let sort_order = if filters.sort_order.to_lowercase() == "asc" { "ASC" } else { "DESC" };
+        let (where_clause, args) = filters.where_clause();
         
         // 查询数据
         let query = format!(
-            "SELECT * FROM media_items {} ORDER BY {} {} NULLS LAST LIMIT ? OFFSET ?",
-            where_clause, sort_column, sort_order
+            "SELECT * FROM media_items {} ORDER BY {} LIMIT ? OFFSET ?",
+            where_clause, filters.order_clause()
         );
         
         let count_query = format!(
@@ -153,71 +389,29 @@ impl DatabaseRepository for SqliteRepository {
         );
         
         // 构建动态查询 - 数据查询
-        let mut query_builder = sqlx::query_as::<_, MediaItem>(&query);
-        
-        if let Some(ref media_type) = filters.media_type {
-            query_builder = query_builder.bind(media_type);
-        }
-        if let Some(ref studio) = filters.studio {
-            query_builder = query_builder.bind(studio);
-        }
-        if let Some(ref series) = filters.series {
-            query_builder = query_builder.bind(series);
-        }
-        if let Some(year) = filters.year {
-            query_builder = query_builder.bind(year);
-        }
-        if let Some(ref genre) = filters.genre {
-            let like_pattern = format!("%{}%", genre);
-            query_builder = query_builder.bind(like_pattern);
-        }
-        if let Some(ref keyword) = filters.keyword {
-            if !keyword.is_empty() {
-                let like_pattern = format!("%{}%", keyword);
-                query_builder = query_builder.bind(like_pattern.clone()); // code
-                query_builder = query_builder.bind(like_pattern.clone()); // title
-                query_builder = query_builder.bind(like_pattern.clone()); // original_title
-                query_builder = query_builder.bind(like_pattern);         // overview
-            }
-        }
-        query_builder = query_builder.bind(limit).bind(offset);
-        
-        let media_list = query_builder.fetch_all(&self.pool).await?;
+        let query_builder = bind_filter_args!(sqlx::query_as::<_, MediaItem>(&query), &args);
+        let media_list = query_builder.bind(limit).bind(offset).fetch_all(&self.pool).await?;
         
         // 构建动态查询 - 计数查询
-        let mut count_builder = sqlx::query_scalar::<_, i64>(&count_query);
-        
-        if let Some(ref media_type) = filters.media_type {
-            count_builder = count_builder.bind(media_type);
-        }
-        if let Some(ref studio) = filters.studio {
-            count_builder = count_builder.bind(studio);
-        }
-        if let Some(ref series) = filters.series {
-            count_builder = count_builder.bind(series);
-        }
-        if let Some(year) = filters.year {
-            count_builder = count_builder.bind(year);
-        }
-        if let Some(ref genre) = filters.genre {
-            let like_pattern = format!("%{}%", genre);
-            count_builder = count_builder.bind(like_pattern);
-        }
-        if let Some(ref keyword) = filters.keyword {
-            if !keyword.is_empty() {
-                let like_pattern = format!("%{}%", keyword);
-                count_builder = count_builder.bind(like_pattern.clone()); // code
-                count_builder = count_builder.bind(like_pattern.clone()); // title
-                count_builder = count_builder.bind(like_pattern.clone()); // original_title
-                count_builder = count_builder.bind(like_pattern);         // overview
-            }
-        }
-        
+        let count_builder = bind_filter_args!(sqlx::query_scalar::<_, i64>(&count_query), &args);
         let total_count = count_builder.fetch_one(&self.pool).await?;
         
         Ok((media_list, total_count))
     }
     
+    async fn get_media_ids_filtered(&self, filters: &MediaListFilters) -> Result<Vec<String>> {
+        let (where_clause, args) = filters.where_clause();
+        let query = format!(
+            "SELECT id FROM media_items {} ORDER BY {}",
+            where_clause, filters.order_clause()
+        );
+        
+        let query_builder = bind_filter_args!(sqlx::query_scalar::<_, String>(&query), &args);
+        let ids = query_builder.fetch_all(&self.pool).await?;
+        
+        Ok(ids)
+    }
+    
     async fn get_media_by_id(&self, id: &str) -> Result<Option<MediaItem>> {
         let media_item = sqlx::query_as::<_, MediaItem>(
             "SELECT * FROM media_items WHERE id = ?"
@@ -230,100 +424,21 @@ impl DatabaseRepository for SqliteRepository {
     }
     
     async fn insert_media(&self, media: &MediaItem) -> Result<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO media_items (
-                id, code, external_ids, title, original_title, year, media_type,
-                genres, rating, vote_count, poster_url, backdrop_url, overview,
-                runtime, release_date, cast, crew, language, country,
-                budget, revenue, status, play_links, download_links,
-                preview_urls, preview_video_urls, cover_video_url, studio, series, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#
-        )
-        .bind(&media.id)
-        .bind(&media.code)
-        .bind(&media.external_ids)
-        .bind(&media.title)
-        .bind(&media.original_title)
-        .bind(media.year)
-        .bind(&media.media_type)
-        .bind(&media.genres)
-        .bind(media.rating)
-        .bind(media.vote_count)
-        .bind(&media.poster_url)
-        .bind(&media.backdrop_url)
-        .bind(&media.overview)
-        .bind(media.runtime)
-        .bind(&media.release_date)
-        .bind(&media.cast)
-        .bind(&media.crew)
-        .bind(&media.language)
-        .bind(&media.country)
-        .bind(media.budget)
-        .bind(media.revenue)
-        .bind(&media.status)
-        .bind(&media.play_links)
-        .bind(&media.download_links)
-        .bind(&media.preview_urls)
-        .bind(&media.preview_video_urls)
-        .bind(&media.cover_video_url)
-        .bind(&media.studio)
-        .bind(&media.series)
-        .bind(&media.created_at)
-        .bind(&media.updated_at)
-        .execute(&self.pool)
-        .await?;
-        
+        insert_media_with(&self.pool, media).await
+    }
+    
+    async fn insert_media_batch(&self, media: &[MediaItem]) -> Result<()> {
+        if media.is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.pool.begin().await?;
+        insert_media_batch_with(&mut tx, media).await?;
+        tx.commit().await?;
         Ok(())
     }
     
     async fn update_media(&self, media: &MediaItem) -> Result<()> {
-        sqlx::query(
-            r#"
-            UPDATE media_items SET
-                code = ?, external_ids = ?, title = ?, original_title = ?, year = ?, media_type = ?,
-                genres = ?, rating = ?, vote_count = ?, poster_url = ?, backdrop_url = ?,
-                overview = ?, runtime = ?, release_date = ?, cast = ?, crew = ?,
-                language = ?, country = ?, budget = ?, revenue = ?, status = ?,
-                play_links = ?, download_links = ?, preview_urls = ?, preview_video_urls = ?,
-                cover_video_url = ?, studio = ?, series = ?, updated_at = datetime('now')
-            WHERE id = ?
-            "#
-        )
-        .bind(&media.code)
-        .bind(&media.external_ids)
-        .bind(&media.title)
-        .bind(&media.original_title)
-        .bind(media.year)
-        .bind(&media.media_type)
-        .bind(&media.genres)
-        .bind(media.rating)
-        .bind(media.vote_count)
-        .bind(&media.poster_url)
-        .bind(&media.backdrop_url)
-        .bind(&media.overview)
-        .bind(media.runtime)
-        .bind(&media.release_date)
-        .bind(&media.cast)
-        .bind(&media.crew)
-        .bind(&media.language)
-        .bind(&media.country)
-        .bind(media.budget)
-        .bind(media.revenue)
-        .bind(&media.status)
-        .bind(&media.play_links)
-        .bind(&media.download_links)
-        .bind(&media.preview_urls)
-        .bind(&media.preview_video_urls)
-        .bind(&media.cover_video_url)
-        .bind(&media.studio)
-        .bind(&media.series)
-        .bind(&media.id)
-        .execute(&self.pool)
-        .await?;
-        
-        Ok(())
+        update_media_with(&self.pool, media).await
     }
     
     async fn delete_media(&self, id: &str) -> Result<()> {
@@ -351,6 +466,60 @@ impl DatabaseRepository for SqliteRepository {
             .execute(&self.pool)
             .await?;
         
+        // 删除想要列表记录
+        sqlx::query("DELETE FROM wanted_media WHERE media_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        // 删除可升级记录
+        sqlx::query("DELETE FROM media_upgrades WHERE media_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        // 删除分享链接
+        sqlx::query("DELETE FROM share_links WHERE media_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        // 删除附加内容记录
+        sqlx::query("DELETE FROM media_extras WHERE media_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        // 删除多语言标题/简介
+        sqlx::query("DELETE FROM media_translations WHERE media_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        // 删除失效链接记录
+        sqlx::query("DELETE FROM media_broken_links WHERE media_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        // 删除刮削来源存档
+        sqlx::query("DELETE FROM scrape_sources WHERE media_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        // 删除浏览记录
+        sqlx::query("DELETE FROM browsing_history WHERE media_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        // 已保存的扫描结果中关联到该媒体的文件恢复为未匹配
+        sqlx::query("UPDATE scan_files SET status = 'unmatched', media_id = NULL WHERE media_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
         // 最后删除媒体本身
         sqlx::query("DELETE FROM media_items WHERE id = ?")
             .bind(id)
@@ -480,6 +649,7 @@ impl DatabaseRepository for SqliteRepository {
         
         let query_builder = MediaQueryBuilder::new()
             .with_filters(filters)
+            .with_actor_filter(filters)
             .with_collection_filters(filters)
             .with_sorting(filters)
             .with_pagination(filters);
@@ -493,6 +663,21 @@ impl DatabaseRepository for SqliteRepository {
         Ok(media_items)
     }
     
+    async fn search_media_ids_with_filters(&self, filters: &SearchFilters) -> Result<Vec<String>> {
+        use crate::database::MediaQueryBuilder;
+        
+        let ids = MediaQueryBuilder::select("id")
+            .with_filters(filters)
+            .with_actor_filter(filters)
+            .with_collection_filters(filters)
+            .build()
+            .build_query_scalar::<String>()
+            .fetch_all(&self.pool)
+            .await?;
+        
+        Ok(ids)
+    }
+    
     async fn get_media_count(&self) -> Result<i64> {
         let count = sqlx::query_scalar("SELECT COUNT(*) FROM media_items")
             .fetch_one(&self.pool)
@@ -744,31 +929,17 @@ impl DatabaseRepository for SqliteRepository {
         }
         
         // 批量插入文件记录
-        for file in files {
-            sqlx::query(
-                r#"
-                INSERT INTO media_files (id, media_id, file_path, file_size, part_number, part_label, created_at)
-                VALUES (?, ?, ?, ?, ?, ?, ?)
-                "#
-            )
-            .bind(&file.id)
-            .bind(&file.media_id)
-            .bind(&file.file_path)
-            .bind(file.file_size)
-            .bind(file.part_number)
-            .bind(&file.part_label)
-            .bind(&file.created_at)
-            .execute(&self.pool)
-            .await?;
-        }
-        
+        let mut tx = self.pool.begin().await?;
+        insert_media_files_with(&mut tx, files).await?;
+        tx.commit().await?;
         Ok(())
     }
     
     async fn get_media_files(&self, media_id: &str) -> Result<Vec<MediaFile>> {
         let files = sqlx::query_as::<_, MediaFile>(
             r#"
-            SELECT id, media_id, file_path, file_size, part_number, part_label, created_at
+            SELECT id, media_id, file_path, file_size, part_number, part_label, created_at, edition,
+                   watch_progress, watched_at
             FROM media_files
             WHERE media_id = ?
             ORDER BY part_number ASC NULLS LAST, part_label ASC
@@ -808,6 +979,15 @@ impl DatabaseRepository for SqliteRepository {
         
         Ok(())
     }
+    
+    async fn begin(&self) -> Result<DbTransaction> {
+        Ok(self.pool.begin().await?)
+    }
+    
+    async fn commit(&self, tx: DbTransaction) -> Result<()> {
+        tx.commit().await?;
+        Ok(())
+    }
 }
 
 // 辅助数据结构
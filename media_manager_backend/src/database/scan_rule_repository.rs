@@ -0,0 +1,51 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::{AutoConfirmSettings, FilenameRule};
+use super::settings_repository::{get_setting, set_setting};
+
+const FILENAME_RULES_KEY: &str = "filename_rules";
+const AUTO_CONFIRM_KEY: &str = "scan_auto_confirm";
+
+// ============ Filename Rules ============
+
+/// 获取文件名解析规则
+pub async fn get_filename_rules(pool: &Pool<Sqlite>) -> Result<Vec<FilenameRule>> {
+    let rules = match get_setting(pool, FILENAME_RULES_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => Vec::new(),
+    };
+    Ok(rules)
+}
+
+/// 保存文件名解析规则（整体替换）
+pub async fn save_filename_rules(pool: &Pool<Sqlite>, rules: &[FilenameRule]) -> Result<()> {
+    let value = serde_json::to_string(rules)?;
+    set_setting(
+        pool,
+        FILENAME_RULES_KEY,
+        &value,
+        Some("Custom filename parsing rules applied before the built-in scanner patterns (JSON array)"),
+    ).await
+}
+
+// ============ Auto Confirm ============
+
+/// 获取匹配自动确认设置
+pub async fn get_auto_confirm_settings(pool: &Pool<Sqlite>) -> Result<AutoConfirmSettings> {
+    let settings = match get_setting(pool, AUTO_CONFIRM_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => AutoConfirmSettings::default(),
+    };
+    Ok(settings)
+}
+
+/// 保存匹配自动确认设置
+pub async fn save_auto_confirm_settings(pool: &Pool<Sqlite>, settings: &AutoConfirmSettings) -> Result<()> {
+    let value = serde_json::to_string(settings)?;
+    set_setting(
+        pool,
+        AUTO_CONFIRM_KEY,
+        &value,
+        Some("Automatically link scanned files whose match confidence reaches the threshold"),
+    ).await
+}
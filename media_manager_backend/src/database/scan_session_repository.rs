@@ -0,0 +1,293 @@
+use anyhow::{anyhow, Result};
+use sqlx::{sqlite::SqliteRow, Connection, Pool, QueryBuilder, Row, Sqlite, SqliteConnection};
+use crate::models::{MediaFile, ScanFileRecord, ScanSession, SCAN_FILE_IGNORED, SCAN_FILE_MATCHED, SCAN_FILE_UNMATCHED};
+
+// ============ Scan Sessions ============
+
+const SESSION_SELECT: &str = r#"
+    SELECT s.*,
+        (SELECT COUNT(*) FROM scan_files f WHERE f.session_id = s.id AND f.status = 'unmatched') AS unmatched_count,
+        (SELECT COUNT(*) FROM scan_files f WHERE f.session_id = s.id AND f.status = 'matched') AS matched_count,
+        (SELECT COUNT(*) FROM scan_files f WHERE f.session_id = s.id AND f.status = 'ignored') AS ignored_count
+    FROM scan_sessions s"#;
+
+fn session_from_row(row: &SqliteRow) -> ScanSession {
+    let paths: String = row.get("paths");
+    ScanSession {
+        id: row.get("id"),
+        paths: serde_json::from_str(&paths).unwrap_or_default(),
+        recursive: row.get::<i64, _>("recursive") != 0,
+        total_files: row.get("total_files"),
+        unmatched_count: row.get("unmatched_count"),
+        matched_count: row.get("matched_count"),
+        ignored_count: row.get("ignored_count"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+/// 保存一次扫描的结果，已关联到媒体或已忽略的文件直接标记状态，返回会话ID
+pub async fn create_scan_session(
+    pool: &Pool<Sqlite>,
+    paths: &[String],
+    recursive: bool,
+    files: &[ScanFileRecord],
+) -> Result<String> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"INSERT INTO scan_sessions (id, paths, recursive, total_files, created_at, updated_at)
+           VALUES (?, ?, ?, ?, ?, ?)"#
+    )
+    .bind(&id)
+    .bind(serde_json::to_string(paths)?)
+    .bind(recursive)
+    .bind(files.len() as i64)
+    .bind(now)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?;
+
+    for file in files {
+        sqlx::query(
+            r#"INSERT OR IGNORE INTO scan_files
+               (session_id, file_path, file_name, file_size, parsed_code, parsed_title,
+                parsed_year, parsed_series, parsed_date, status, updated_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#
+        )
+        .bind(&id)
+        .bind(&file.file_path)
+        .bind(&file.file_name)
+        .bind(file.file_size)
+        .bind(&file.parsed_code)
+        .bind(&file.parsed_title)
+        .bind(file.parsed_year)
+        .bind(&file.parsed_series)
+        .bind(&file.parsed_date)
+        .bind(SCAN_FILE_UNMATCHED)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query(
+        r#"UPDATE scan_files SET status = ?,
+               media_id = (SELECT mf.media_id FROM media_files mf WHERE mf.file_path = scan_files.file_path)
+           WHERE session_id = ? AND file_path IN (SELECT file_path FROM media_files)"#
+    )
+    .bind(SCAN_FILE_MATCHED)
+    .bind(&id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "UPDATE scan_files SET status = ? WHERE session_id = ? AND file_path IN (SELECT file_path FROM ignored_files)"
+    )
+    .bind(SCAN_FILE_IGNORED)
+    .bind(&id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(id)
+}
+
+/// 获取所有扫描会话（最新的在前）
+pub async fn list_scan_sessions(pool: &Pool<Sqlite>) -> Result<Vec<ScanSession>> {
+    let rows = sqlx::query(&format!("{} ORDER BY s.created_at DESC", SESSION_SELECT))
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.iter().map(session_from_row).collect())
+}
+
+/// 获取单个扫描会话
+pub async fn get_scan_session(pool: &Pool<Sqlite>, id: &str) -> Result<Option<ScanSession>> {
+    let row = sqlx::query(&format!("{} WHERE s.id = ?", SESSION_SELECT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.as_ref().map(session_from_row))
+}
+
+/// 获取会话中的文件（可按状态筛选）
+pub async fn get_scan_files(
+    pool: &Pool<Sqlite>,
+    session_id: &str,
+    status: Option<&str>,
+) -> Result<Vec<ScanFileRecord>> {
+    let files = sqlx::query_as::<_, ScanFileRecord>(
+        r#"SELECT * FROM scan_files
+           WHERE session_id = ? AND (? IS NULL OR status = ?)
+           ORDER BY file_path"#
+    )
+    .bind(session_id)
+    .bind(status)
+    .bind(status)
+    .fetch_all(pool)
+    .await?;
+    Ok(files)
+}
+
+/// 按文件路径更新所有会话中的文件状态（关联媒体或忽略后调用）
+pub async fn mark_scan_files(
+    pool: &Pool<Sqlite>,
+    file_paths: &[String],
+    status: &str,
+    media_id: Option<&str>,
+) -> Result<u64> {
+    let mut conn = pool.acquire().await?;
+    mark_scan_files_with(&mut conn, file_paths, status, media_id).await
+}
+
+/// 按文件路径批量更新文件状态（在给定的连接或事务中执行，已在事务中时使用保存点）
+pub async fn mark_scan_files_with(
+    conn: &mut SqliteConnection,
+    file_paths: &[String],
+    status: &str,
+    media_id: Option<&str>,
+) -> Result<u64> {
+    let now = chrono::Utc::now();
+    let mut updated = 0;
+    let mut tx = conn.begin().await?;
+    for chunk in file_paths.chunks(crate::database::MAX_BIND_PARAMS - 3) {
+        let mut builder = QueryBuilder::<Sqlite>::new("UPDATE scan_files SET status = ");
+        builder.push_bind(status)
+            .push(", media_id = ").push_bind(media_id)
+            .push(", updated_at = ").push_bind(now)
+            .push(" WHERE file_path IN (");
+        push_path_list(&mut builder, chunk);
+        let result = builder.build().execute(&mut *tx).await?;
+        if result.rows_affected() == 0 {
+            continue;
+        }
+        updated += result.rows_affected();
+
+        let mut builder = QueryBuilder::<Sqlite>::new("UPDATE scan_sessions SET updated_at = ");
+        builder.push_bind(now)
+            .push(" WHERE id IN (SELECT session_id FROM scan_files WHERE file_path IN (");
+        push_path_list(&mut builder, chunk);
+        builder.push(")");
+        builder.build().execute(&mut *tx).await?;
+    }
+    tx.commit().await?;
+    Ok(updated)
+}
+
+/// 追加 `?, ?, ...)` 形式的路径列表
+fn push_path_list<'a>(builder: &mut QueryBuilder<'a, Sqlite>, paths: &'a [String]) {
+    let mut separated = builder.separated(", ");
+    for path in paths {
+        separated.push_bind(path);
+    }
+    separated.push_unseparated(")");
+}
+
+/// 更新文件的解析结果（规则修改后重新解析）
+pub async fn update_scan_file_parse(pool: &Pool<Sqlite>, file: &ScanFileRecord) -> Result<()> {
+    sqlx::query(
+        r#"UPDATE scan_files SET parsed_code = ?, parsed_title = ?, parsed_year = ?,
+               parsed_series = ?, parsed_date = ?, updated_at = ?
+           WHERE id = ?"#
+    )
+    .bind(&file.parsed_code)
+    .bind(&file.parsed_title)
+    .bind(file.parsed_year)
+    .bind(&file.parsed_series)
+    .bind(&file.parsed_date)
+    .bind(chrono::Utc::now())
+    .bind(file.id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 删除扫描会话及其文件
+pub async fn delete_scan_session(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM scan_files WHERE session_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    let result = sqlx::query("DELETE FROM scan_sessions WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(anyhow!("Scan session not found: {}", id));
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 清空所有扫描会话，返回删除的会话数
+pub async fn clear_scan_sessions(pool: &Pool<Sqlite>) -> Result<u64> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM scan_files").execute(&mut *tx).await?;
+    let result = sqlx::query("DELETE FROM scan_sessions").execute(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(result.rows_affected())
+}
+
+// ============ File Linking ============
+
+/// 返回已出现过的文件路径：已关联到媒体、已忽略或已记录在扫描会话中
+pub async fn find_known_file_paths(pool: &Pool<Sqlite>) -> Result<std::collections::HashSet<String>> {
+    let paths: Vec<String> = sqlx::query_scalar(
+        r#"SELECT file_path FROM media_files
+           UNION SELECT file_path FROM ignored_files
+           UNION SELECT file_path FROM scan_files"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(paths.into_iter().collect())
+}
+
+/// 返回已关联到媒体的文件路径
+pub async fn find_linked_file_paths(pool: &Pool<Sqlite>, file_paths: &[String]) -> Result<Vec<String>> {
+    let mut linked = Vec::new();
+    for path in file_paths {
+        let exists: Option<String> = sqlx::query_scalar("SELECT file_path FROM media_files WHERE file_path = ?")
+            .bind(path)
+            .fetch_optional(pool)
+            .await?;
+        linked.extend(exists);
+    }
+    Ok(linked)
+}
+
+/// 在一个事务中关联文件到媒体：写入 media_files、更新媒体文件信息、清除升级记录并标记扫描文件
+pub async fn link_media_files(pool: &Pool<Sqlite>, media_id: &str, files: &[MediaFile]) -> Result<()> {
+    let mut conn = pool.acquire().await?;
+    link_media_files_with(&mut conn, media_id, files).await
+}
+
+/// 关联文件到媒体（在给定的连接或事务中执行，已在事务中时使用保存点）
+pub async fn link_media_files_with(conn: &mut SqliteConnection, media_id: &str, files: &[MediaFile]) -> Result<()> {
+    let first_file = files.first().ok_or_else(|| anyhow!("No files to link"))?;
+    let total_size: i64 = files.iter().map(|f| f.file_size).sum();
+    let paths: Vec<String> = files.iter().map(|f| f.file_path.clone()).collect();
+    let mut tx = conn.begin().await?;
+
+    crate::database::insert_media_files_with(&mut tx, files).await?;
+    mark_scan_files_with(&mut tx, &paths, SCAN_FILE_MATCHED, Some(media_id)).await?;
+
+    sqlx::query(
+        "UPDATE media_items SET local_file_path = ?, file_size = ?, last_scanned_at = datetime('now') WHERE id = ?"
+    )
+    .bind(&first_file.file_path)
+    .bind(total_size)
+    .bind(media_id)
+    .execute(&mut *tx)
+    .await?;
+
+    // 新文件已入库，之前检测到的升级记录失效
+    sqlx::query("DELETE FROM media_upgrades WHERE media_id = ?")
+        .bind(media_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
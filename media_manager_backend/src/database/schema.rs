@@ -1,6 +1,77 @@
-use sqlx::{Pool, Sqlite, Row};
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{migrate::Migrator, Pool, Sqlite, Row};
 use anyhow::Result;
 
+use crate::models::{MigrationInfo, MigrationStatus};
+use crate::services::transliteration;
+
+/// 程序内置的数据库迁移
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// 获取数据库迁移状态
+pub async fn get_migration_status(pool: &Pool<Sqlite>) -> Result<MigrationStatus> {
+    let table_exists = sqlx::query("SELECT name FROM sqlite_master WHERE type='table' AND name='_sqlx_migrations'")
+        .fetch_optional(pool)
+        .await?
+        .is_some();
+    let rows: Vec<(i64, String, DateTime<Utc>, Vec<u8>)> = if table_exists {
+        sqlx::query_as("SELECT version, description, installed_on, checksum FROM _sqlx_migrations WHERE success = 1 ORDER BY version")
+            .fetch_all(pool)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    let known: HashMap<i64, &sqlx::migrate::Migration> = MIGRATOR.iter().map(|m| (m.version, m)).collect();
+    let mut status = MigrationStatus {
+        schema_version: rows.iter().map(|r| r.0).max(),
+        latest_version: MIGRATOR.iter().map(|m| m.version).max().unwrap_or(0),
+        ..Default::default()
+    };
+
+    for (version, description, installed_on, checksum) in &rows {
+        let info = MigrationInfo {
+            version: *version,
+            description: description.clone(),
+            installed_on: Some(*installed_on),
+            checksum_mismatch: known.get(version).is_some_and(|m| m.checksum.as_ref() != checksum.as_slice()),
+        };
+        if known.contains_key(version) {
+            status.applied.push(info);
+        } else {
+            status.unknown.push(info);
+        }
+    }
+    for migration in MIGRATOR.iter() {
+        if !rows.iter().any(|r| r.0 == migration.version) {
+            status.pending.push(MigrationInfo {
+                version: migration.version,
+                description: migration.description.to_string(),
+                installed_on: None,
+                checksum_mismatch: false,
+            });
+        }
+    }
+
+    Ok(status)
+}
+
+/// 拒绝使用由更新版本程序迁移过的数据库（多个实例通过网络共享同一个数据库文件时尤其重要）
+pub async fn check_schema_version(pool: &Pool<Sqlite>) -> Result<()> {
+    let status = get_migration_status(pool).await?;
+    if let Some(newest) = status.unknown.iter().map(|m| m.version).max() {
+        return Err(anyhow::anyhow!(
+            "Database schema version {} is newer than this build supports (latest migration {}). \
+             The database was upgraded by a newer media_manager_backend; upgrade this instance or use a matching database file",
+            newest,
+            status.latest_version
+        ));
+    }
+    Ok(())
+}
+
 /// 验证数据库schema完整性
 pub async fn verify_schema(pool: &Pool<Sqlite>) -> Result<()> {
     // 检查所有必需的表是否存在
@@ -103,6 +174,32 @@ pub async fn cleanup_expired_cache(pool: &Pool<Sqlite>) -> Result<u64> {
     Ok(result.rows_affected())
 }
 
+/// 为缺少转写字段的媒体生成排序键和罗马字形式（升级后的旧数据、从旧版本节点同步的数据）
+pub async fn backfill_title_transliteration(pool: &Pool<Sqlite>) -> Result<u64> {
+    let rows: Vec<(String, String, Option<String>)> = sqlx::query_as(
+        "SELECT id, title, original_title FROM media_items WHERE title_sort IS NULL"
+    )
+    .fetch_all(pool)
+    .await?;
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let mut tx = pool.begin().await?;
+    for (id, title, original_title) in &rows {
+        sqlx::query("UPDATE media_items SET title_sort = ?, title_romanized = ? WHERE id = ?")
+            .bind(transliteration::sort_key(title))
+            .bind(transliteration::searchable_form(title, original_title.as_deref()))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    tracing::info!("Generated title transliteration for {} media items", rows.len());
+    Ok(rows.len() as u64)
+}
+
 /// 数据库统计信息
 #[derive(Debug)]
 pub struct DatabaseStats {
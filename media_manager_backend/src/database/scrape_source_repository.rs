@@ -0,0 +1,111 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite};
+use crate::models::{ScrapeArchiveSettings, ScrapeSource};
+use super::settings_repository::{get_setting, set_setting};
+
+const SCRAPE_ARCHIVE_KEY: &str = "scrape_archive";
+
+const SCRAPE_SOURCE_COLUMNS: &str =
+    "id, media_id, source, query, source_url, content_type, original_size, compressed_size, created_at";
+
+// ============ Scrape Sources ============
+
+/// 新的刮削来源存档（payload 为压缩后的数据）
+pub struct NewScrapeSource<'a> {
+    pub media_id: &'a str,
+    pub source: Option<&'a str>,
+    pub query: Option<&'a str>,
+    pub source_url: Option<&'a str>,
+    pub content_type: &'a str,
+    pub payload: &'a [u8],
+    pub original_size: usize,
+}
+
+/// 保存刮削来源存档，返回存档 ID
+pub async fn insert_scrape_source(pool: &Pool<Sqlite>, source: &NewScrapeSource<'_>) -> Result<i64> {
+    let result = sqlx::query(
+        r#"INSERT INTO scrape_sources
+           (media_id, source, query, source_url, content_type, payload, original_size, compressed_size, created_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"#
+    )
+    .bind(source.media_id)
+    .bind(source.source)
+    .bind(source.query)
+    .bind(source.source_url)
+    .bind(source.content_type)
+    .bind(source.payload)
+    .bind(source.original_size as i64)
+    .bind(source.payload.len() as i64)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// 获取媒体的刮削来源存档（不含原始数据，最新的在前）
+pub async fn list_scrape_sources(pool: &Pool<Sqlite>, media_id: &str) -> Result<Vec<ScrapeSource>> {
+    let sql = format!(
+        "SELECT {} FROM scrape_sources WHERE media_id = ? ORDER BY created_at DESC, id DESC",
+        SCRAPE_SOURCE_COLUMNS
+    );
+    let sources = sqlx::query_as::<_, ScrapeSource>(&sql)
+        .bind(media_id)
+        .fetch_all(pool)
+        .await?;
+    Ok(sources)
+}
+
+/// 获取刮削来源存档及其压缩数据
+pub async fn get_scrape_source_payload(pool: &Pool<Sqlite>, id: i64) -> Result<Option<(ScrapeSource, Vec<u8>)>> {
+    let sql = format!("SELECT {}, payload FROM scrape_sources WHERE id = ?", SCRAPE_SOURCE_COLUMNS);
+    let row = sqlx::query(&sql)
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let source = <ScrapeSource as sqlx::FromRow<_>>::from_row(&row)?;
+    let payload: Vec<u8> = row.try_get("payload")?;
+    Ok(Some((source, payload)))
+}
+
+/// 存档总大小超过上限时删除最早的存档，返回删除的条数
+pub async fn prune_scrape_sources(pool: &Pool<Sqlite>, max_total_bytes: i64) -> Result<u64> {
+    // 按时间倒序累计大小，删除累计值超过上限的存档
+    let result = sqlx::query(
+        r#"DELETE FROM scrape_sources WHERE id IN (
+               SELECT id FROM (
+                   SELECT id, SUM(compressed_size) OVER (ORDER BY created_at DESC, id DESC) AS running_total
+                   FROM scrape_sources
+               ) WHERE running_total > ?
+           )"#
+    )
+    .bind(max_total_bytes)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+// ============ Scrape Archive Settings ============
+
+/// 获取刮削来源存档设置
+pub async fn get_scrape_archive_settings(pool: &Pool<Sqlite>) -> Result<ScrapeArchiveSettings> {
+    let settings = match get_setting(pool, SCRAPE_ARCHIVE_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => ScrapeArchiveSettings::default(),
+    };
+    Ok(settings)
+}
+
+/// 保存刮削来源存档设置
+pub async fn save_scrape_archive_settings(pool: &Pool<Sqlite>, settings: &ScrapeArchiveSettings) -> Result<()> {
+    let value = serde_json::to_string(settings)?;
+    set_setting(
+        pool,
+        SCRAPE_ARCHIVE_KEY,
+        &value,
+        Some("Store compressed raw scraper payloads for provenance (size-capped)"),
+    ).await
+}
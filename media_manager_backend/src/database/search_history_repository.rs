@@ -0,0 +1,144 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::{SearchDayCount, SearchHistoryStat, SearchSuggestion, SEARCH_HISTORY_LIMIT, SUGGESTION_CODE, SUGGESTION_PERSON, SUGGESTION_TITLE};
+
+// ============ Search History ============
+
+/// LIKE 前缀匹配模式（转义 % 和 _）
+fn like_prefix(prefix: &str) -> String {
+    let escaped = prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("{}%", escaped)
+}
+
+/// 记录一次搜索，并只保留该用户最近的记录
+pub async fn record_search_query(pool: &Pool<Sqlite>, user_key: &str, query: &str) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO search_history (id, query, result_count, searched_at, user_key)
+           VALUES (?, ?, 0, datetime('now'), ?)"#
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(query)
+    .bind(user_key)
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        r#"DELETE FROM search_history
+           WHERE user_key = ? AND id NOT IN (
+               SELECT id FROM search_history WHERE user_key = ?
+               ORDER BY searched_at DESC LIMIT ?
+           )"#
+    )
+    .bind(user_key)
+    .bind(user_key)
+    .bind(SEARCH_HISTORY_LIMIT)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 用户搜索过的、以 prefix 开头的关键词（忽略大小写汇总）
+pub async fn list_search_history_stats(pool: &Pool<Sqlite>, user_key: &str, prefix: &str) -> Result<Vec<SearchHistoryStat>> {
+    let stats = sqlx::query_as::<_, SearchHistoryStat>(
+        r#"SELECT query, COUNT(*) AS search_count,
+                  julianday('now') - julianday(MAX(searched_at)) AS age_days
+           FROM search_history
+           WHERE user_key = ? AND query LIKE ? ESCAPE '\'
+           GROUP BY query COLLATE NOCASE"#
+    )
+    .bind(user_key)
+    .bind(like_prefix(prefix))
+    .fetch_all(pool)
+    .await?;
+    Ok(stats)
+}
+
+/// 最近 window_days 天内所有用户每个关键词每天的搜索次数
+pub async fn list_search_day_counts(pool: &Pool<Sqlite>, window_days: u32) -> Result<Vec<SearchDayCount>> {
+    let counts = sqlx::query_as::<_, SearchDayCount>(
+        r#"SELECT query, COUNT(*) AS search_count,
+                  julianday('now') - julianday(MAX(searched_at)) AS age_days
+           FROM search_history
+           WHERE searched_at >= datetime('now', ?)
+           GROUP BY query COLLATE NOCASE, date(searched_at)"#
+    )
+    .bind(format!("-{} days", window_days))
+    .fetch_all(pool)
+    .await?;
+    Ok(counts)
+}
+
+/// 清空用户的搜索历史（指定 query 时只删除该关键词，忽略大小写），返回删除的记录数
+pub async fn clear_user_search_history(pool: &Pool<Sqlite>, user_key: &str, query: Option<&str>) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM search_history WHERE user_key = ? AND (? IS NULL OR query = ? COLLATE NOCASE)"
+    )
+    .bind(user_key)
+    .bind(query)
+    .bind(query)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+// ============ Library Suggestions ============
+
+/// 标题中以 prefix 开头的词（全文索引前缀匹配，含原标题和罗马字）
+pub async fn suggest_titles(pool: &Pool<Sqlite>, prefix: &str, limit: i64) -> Result<Vec<SearchSuggestion>> {
+    let pattern = format!("{{title original_title romanized}} : \"{}\"*", prefix.replace('"', "\"\""));
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"SELECT m.title, COUNT(*) AS media_count
+           FROM media_search_fts f
+           JOIN media_items m ON m.id = f.media_id
+           WHERE media_search_fts MATCH ?
+           GROUP BY m.title COLLATE NOCASE
+           ORDER BY media_count DESC, m.title
+           LIMIT ?"#
+    )
+    .bind(pattern)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(to_suggestions(rows, SUGGESTION_TITLE))
+}
+
+/// 以 prefix 开头的演员名（按作品数排序）
+pub async fn suggest_actors(pool: &Pool<Sqlite>, prefix: &str, limit: i64) -> Result<Vec<SearchSuggestion>> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"SELECT a.name, COUNT(am.media_id) AS media_count
+           FROM actors a
+           LEFT JOIN actor_media am ON am.actor_id = a.id
+           WHERE a.name LIKE ? ESCAPE '\'
+           GROUP BY a.id
+           ORDER BY media_count DESC, a.name
+           LIMIT ?"#
+    )
+    .bind(like_prefix(prefix))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(to_suggestions(rows, SUGGESTION_PERSON))
+}
+
+/// 以 prefix 开头的番号
+pub async fn suggest_codes(pool: &Pool<Sqlite>, prefix: &str, limit: i64) -> Result<Vec<SearchSuggestion>> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"SELECT code, COUNT(*) AS media_count
+           FROM media_items
+           WHERE code LIKE ? ESCAPE '\'
+           GROUP BY code COLLATE NOCASE
+           ORDER BY code
+           LIMIT ?"#
+    )
+    .bind(like_prefix(prefix))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(to_suggestions(rows, SUGGESTION_CODE))
+}
+
+fn to_suggestions(rows: Vec<(String, i64)>, type_: &str) -> Vec<SearchSuggestion> {
+    rows.into_iter()
+        .map(|(text, count)| SearchSuggestion { text, type_: type_.to_string(), count: count as i32 })
+        .collect()
+}
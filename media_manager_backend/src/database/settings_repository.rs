@@ -0,0 +1,63 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::{FfmpegSettings, StreamingSettings};
+
+const FFMPEG_KEY: &str = "ffmpeg";
+const STREAMING_KEY: &str = "streaming";
+
+// ============ User Settings ============
+
+/// 读取设置值
+pub async fn get_setting(pool: &Pool<Sqlite>, key: &str) -> Result<Option<String>> {
+    let value: Option<String> = sqlx::query_scalar("SELECT value FROM user_settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    Ok(value)
+}
+
+/// 写入设置值（不存在时创建）
+pub async fn set_setting(pool: &Pool<Sqlite>, key: &str, value: &str, description: Option<&str>) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO user_settings (key, value, description) VALUES (?, ?, ?)
+           ON CONFLICT(key) DO UPDATE SET value = excluded.value"#
+    )
+    .bind(key)
+    .bind(value)
+    .bind(description)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// ============ FFmpeg ============
+
+/// 获取 FFmpeg 任务调度设置（未设置时返回默认值）
+pub async fn get_ffmpeg_settings(pool: &Pool<Sqlite>) -> Result<FfmpegSettings> {
+    Ok(match get_setting(pool, FFMPEG_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => FfmpegSettings::default(),
+    })
+}
+
+/// 保存 FFmpeg 任务调度设置
+pub async fn save_ffmpeg_settings(pool: &Pool<Sqlite>, settings: &FfmpegSettings) -> Result<()> {
+    let value = serde_json::to_string(settings)?;
+    set_setting(pool, FFMPEG_KEY, &value, Some("Concurrency, nice level and thread limit for ffmpeg/ffprobe jobs")).await
+}
+
+// ============ Streaming ============
+
+/// 获取播放会话设置（未设置时返回默认值）
+pub async fn get_streaming_settings(pool: &Pool<Sqlite>) -> Result<StreamingSettings> {
+    Ok(match get_setting(pool, STREAMING_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => StreamingSettings::default(),
+    })
+}
+
+/// 保存播放会话设置
+pub async fn save_streaming_settings(pool: &Pool<Sqlite>, settings: &StreamingSettings) -> Result<()> {
+    let value = serde_json::to_string(settings)?;
+    set_setting(pool, STREAMING_KEY, &value, Some("Maximum concurrent streams per user")).await
+}
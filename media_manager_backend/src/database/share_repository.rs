@@ -0,0 +1,69 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::ShareLink;
+
+// ============ Share Links ============
+
+/// 创建分享链接
+pub async fn create_share_link(pool: &Pool<Sqlite>, link: &ShareLink) -> Result<ShareLink> {
+    sqlx::query(
+        r#"INSERT INTO share_links (id, media_id, expires_at, access_count, created_at)
+           VALUES (?, ?, ?, 0, ?)"#
+    )
+    .bind(&link.id)
+    .bind(&link.media_id)
+    .bind(link.expires_at)
+    .bind(link.created_at)
+    .execute(pool)
+    .await?;
+
+    get_share_link(pool, &link.id).await
+}
+
+/// 根据ID获取分享链接
+pub async fn get_share_link(pool: &Pool<Sqlite>, id: &str) -> Result<ShareLink> {
+    let link = sqlx::query_as::<_, ShareLink>("SELECT * FROM share_links WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+    Ok(link)
+}
+
+/// 获取媒体的所有分享链接（最新的在前）
+pub async fn list_share_links(pool: &Pool<Sqlite>, media_id: &str) -> Result<Vec<ShareLink>> {
+    let links = sqlx::query_as::<_, ShareLink>(
+        "SELECT * FROM share_links WHERE media_id = ? ORDER BY created_at DESC"
+    )
+    .bind(media_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(links)
+}
+
+/// 撤销分享链接
+pub async fn revoke_share_link(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
+    let result = sqlx::query(
+        "UPDATE share_links SET revoked_at = ? WHERE id = ? AND revoked_at IS NULL"
+    )
+    .bind(chrono::Utc::now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(anyhow::anyhow!("Share link not found or already revoked"));
+    }
+    Ok(())
+}
+
+/// 记录一次访问
+pub async fn record_share_access(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
+    sqlx::query(
+        "UPDATE share_links SET access_count = access_count + 1, last_accessed_at = ? WHERE id = ?"
+    )
+    .bind(chrono::Utc::now())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -4,8 +4,10 @@ use crate::models::{
     Studio, Series, StudioWithSeries, SeriesWithStudio,
     CreateStudioRequest, UpdateStudioRequest,
     CreateSeriesRequest, UpdateSeriesRequest,
-    StudioListResponse, SeriesListResponse,
+    StudioListResponse, SeriesListResponse, StudioListFilters,
     SeriesMatchResult, SeriesMatchType,
+    SeriesDetectCandidate, KnownSeries, StudioReassignResult, MediaLinkResult, UnmatchedName,
+    StudioOverview, StudioYearCount, StudioTopSeries, StudioTopActor, StudioRecentMedia,
 };
 
 // ============ Studio CRUD ============
@@ -65,7 +67,7 @@ pub async fn find_or_create_studio(pool: &Pool<Sqlite>, name: &str) -> Result<St
     }).await
 }
 
-/// 更新厂商
+/// 更新厂商（改名时在同一事务中把媒体的厂商名改为新名称）
 pub async fn update_studio(pool: &Pool<Sqlite>, id: &str, req: UpdateStudioRequest) -> Result<Studio> {
     let mut updates = Vec::new();
     let mut params: Vec<String> = Vec::new();
@@ -87,21 +89,99 @@ pub async fn update_studio(pool: &Pool<Sqlite>, id: &str, req: UpdateStudioReque
         return get_studio_by_id(pool, id).await;
     }
     
+    let current = get_studio_by_id(pool, id).await?;
     let sql = format!(
         "UPDATE studios SET {} WHERE id = ?",
         updates.join(", ")
     );
     
+    let mut tx = pool.begin().await?;
     let mut query = sqlx::query(&sql);
     for param in &params {
         query = query.bind(param);
     }
     query = query.bind(id);
-    query.execute(pool).await?;
+    query.execute(&mut *tx).await?;
+    
+    if let Some(name) = req.name.as_deref().filter(|name| *name != current.name) {
+        sqlx::query(
+            "UPDATE media_items SET studio = ?, updated_at = datetime('now') WHERE studio = ? COLLATE NOCASE"
+        )
+        .bind(name)
+        .bind(&current.name)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
     
     get_studio_by_id(pool, id).await
 }
 
+/// 把厂商的全部媒体和系列移到另一个厂商（目标厂商已有同名系列时合并），
+/// delete_source 为 true 时随后删除原厂商
+pub async fn reassign_studio(
+    pool: &Pool<Sqlite>,
+    source: &Studio,
+    target: &Studio,
+    delete_source: bool,
+) -> Result<StudioReassignResult> {
+    let mut tx = pool.begin().await?;
+    
+    let moved_media = sqlx::query(
+        "UPDATE media_items SET studio = ?, updated_at = datetime('now') WHERE studio = ? COLLATE NOCASE"
+    )
+    .bind(&target.name)
+    .bind(&source.name)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    
+    // 目标厂商已有同名系列：删除原系列，媒体中的系列名不变
+    let merged_series = sqlx::query(
+        r#"DELETE FROM series
+           WHERE studio_id = ?1 AND EXISTS (
+               SELECT 1 FROM series t WHERE t.studio_id = ?2 AND t.name = series.name COLLATE NOCASE
+           )"#
+    )
+    .bind(&source.id)
+    .bind(&target.id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    sqlx::query(
+        r#"DELETE FROM favorite_entities
+           WHERE entity_type = 'series' AND entity_id NOT IN (SELECT id FROM series)"#
+    )
+    .execute(&mut *tx)
+    .await?;
+    
+    let moved_series = sqlx::query("UPDATE series SET studio_id = ? WHERE studio_id = ?")
+        .bind(&target.id)
+        .bind(&source.id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    
+    if delete_source {
+        sqlx::query("DELETE FROM favorite_entities WHERE entity_type = 'studio' AND entity_id = ?")
+            .bind(&source.id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM studios WHERE id = ?")
+            .bind(&source.id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    
+    Ok(StudioReassignResult {
+        moved_media,
+        moved_series,
+        merged_series,
+        source_deleted: delete_source,
+    })
+}
+
 /// 删除厂商
 pub async fn delete_studio(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
     // 将关联的系列设为无厂商
@@ -110,6 +190,11 @@ pub async fn delete_studio(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
         .execute(pool)
         .await?;
     
+    sqlx::query("DELETE FROM favorite_entities WHERE entity_type = 'studio' AND entity_id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    
     sqlx::query("DELETE FROM studios WHERE id = ?")
         .bind(id)
         .execute(pool)
@@ -118,22 +203,30 @@ pub async fn delete_studio(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
     Ok(())
 }
 
+/// 名称模糊匹配模式，未提供或为空时返回 None
+fn name_pattern(query: Option<&str>) -> Option<String> {
+    query.map(str::trim).filter(|q| !q.is_empty()).map(|q| format!("%{}%", q))
+}
+
 /// 获取厂商列表（带系列）
-pub async fn list_studios(pool: &Pool<Sqlite>, limit: Option<i32>, offset: Option<i32>) -> Result<StudioListResponse> {
-    let limit = limit.unwrap_or(100);
-    let offset = offset.unwrap_or(0);
+pub async fn list_studios(pool: &Pool<Sqlite>, filters: &StudioListFilters) -> Result<StudioListResponse> {
+    let search_pattern = name_pattern(filters.query.as_deref());
     
     // 获取总数
-    let (total,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM studios")
+    let (total,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM studios WHERE (?1 IS NULL OR name LIKE ?1)")
+        .bind(&search_pattern)
         .fetch_one(pool)
         .await?;
     
     // 获取厂商列表
-    let studios: Vec<Studio> = sqlx::query_as(
-        "SELECT * FROM studios ORDER BY media_count DESC, name COLLATE NOCASE LIMIT ? OFFSET ?"
-    )
-    .bind(limit)
-    .bind(offset)
+    let order_by = filters.sort_by.order_by(filters.sort_order.as_ref(), "name", "media_count", "created_at");
+    let studios: Vec<Studio> = sqlx::query_as(&format!(
+        "SELECT * FROM studios WHERE (?1 IS NULL OR name LIKE ?1) ORDER BY {}, id LIMIT ?2 OFFSET ?3",
+        order_by
+    ))
+    .bind(&search_pattern)
+    .bind(filters.limit)
+    .bind(filters.offset)
     .fetch_all(pool)
     .await?;
     
@@ -177,7 +270,89 @@ pub async fn list_studios(pool: &Pool<Sqlite>, limit: Option<i32>, offset: Optio
         result.push(StudioWithSeries { studio, series_list });
     }
     
-    Ok(StudioListResponse { studios: result, total })
+    Ok(StudioListResponse { studios: result, total, limit: filters.limit, offset: filters.offset })
+}
+
+/// 厂商详情页汇总：按年份统计、作品最多的系列和演员、最近添加的作品及收藏比例（各列表最多 limit 条）
+pub async fn get_studio_overview(pool: &Pool<Sqlite>, studio: Studio, limit: i64) -> Result<StudioOverview> {
+    let (total_media, collected_media): (i64, i64) = sqlx::query_as(
+        r#"SELECT COUNT(*),
+                  COUNT(*) FILTER (WHERE EXISTS (SELECT 1 FROM collections c WHERE c.media_id = m.id))
+           FROM media_items m
+           WHERE m.studio = ? COLLATE NOCASE"#
+    )
+    .bind(&studio.name)
+    .fetch_one(pool)
+    .await?;
+    
+    let media_by_year: Vec<StudioYearCount> = sqlx::query_as(
+        r#"SELECT year, COUNT(*) AS media_count
+           FROM media_items
+           WHERE studio = ? COLLATE NOCASE
+           GROUP BY year
+           ORDER BY year IS NULL, year DESC"#
+    )
+    .bind(&studio.name)
+    .fetch_all(pool)
+    .await?;
+    
+    let top_series: Vec<StudioTopSeries> = sqlx::query_as(
+        r#"SELECT (SELECT s.id FROM series s WHERE s.name = m.series COLLATE NOCASE LIMIT 1) AS id,
+                  m.series AS name, COUNT(*) AS media_count
+           FROM media_items m
+           WHERE m.studio = ? COLLATE NOCASE AND COALESCE(m.series, '') != ''
+           GROUP BY m.series COLLATE NOCASE
+           ORDER BY media_count DESC, name COLLATE NOCASE
+           LIMIT ?"#
+    )
+    .bind(&studio.name)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    
+    let top_actors: Vec<StudioTopActor> = sqlx::query_as(
+        r#"SELECT a.id, a.name, a.avatar_url, COUNT(DISTINCT m.id) AS media_count
+           FROM actors a
+           JOIN actor_media am ON am.actor_id = a.id
+           JOIN media_items m ON m.id = am.media_id
+           WHERE m.studio = ? COLLATE NOCASE
+           GROUP BY a.id
+           ORDER BY media_count DESC, a.name COLLATE NOCASE
+           LIMIT ?"#
+    )
+    .bind(&studio.name)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    
+    let recent_media: Vec<StudioRecentMedia> = sqlx::query_as(
+        r#"SELECT id, title, code, year, poster_url, created_at
+           FROM media_items
+           WHERE studio = ? COLLATE NOCASE
+           ORDER BY created_at DESC
+           LIMIT ?"#
+    )
+    .bind(&studio.name)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    
+    let collected_percent = if total_media > 0 {
+        (collected_media as f64 * 1000.0 / total_media as f64).round() / 10.0
+    } else {
+        0.0
+    };
+    
+    Ok(StudioOverview {
+        studio,
+        total_media,
+        collected_media,
+        collected_percent,
+        media_by_year,
+        top_series,
+        top_actors,
+        recent_media,
+    })
 }
 
 /// 搜索厂商（模糊匹配）
@@ -427,7 +602,7 @@ pub async fn smart_match_or_create_series(
     }
 }
 
-/// 更新系列
+/// 更新系列（改名时在同一事务中把媒体的系列名改为新名称）
 pub async fn update_series(pool: &Pool<Sqlite>, id: &str, req: UpdateSeriesRequest) -> Result<Series> {
     let mut updates = Vec::new();
     
@@ -450,23 +625,49 @@ pub async fn update_series(pool: &Pool<Sqlite>, id: &str, req: UpdateSeriesReque
     
     // 使用动态SQL
     let current = get_series_by_id(pool, id).await?;
+    let renamed_to = req.name.clone().filter(|name| *name != current.name);
     
+    let mut tx = pool.begin().await?;
     sqlx::query(
         "UPDATE series SET name = ?, studio_id = ?, description = ?, cover_url = ? WHERE id = ?"
     )
-    .bind(req.name.unwrap_or(current.name))
-    .bind(req.studio_id.or(current.studio_id))
+    .bind(req.name.unwrap_or(current.name.clone()))
+    .bind(req.studio_id.or(current.studio_id.clone()))
     .bind(req.description.or(current.description))
     .bind(req.cover_url.or(current.cover_url))
     .bind(id)
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
     
+    // 改名时同步媒体的系列名；其他厂商有同名系列时只改本厂商（或无厂商）的媒体
+    if let Some(name) = renamed_to {
+        sqlx::query(
+            r#"UPDATE media_items SET series = ?1, updated_at = datetime('now')
+               WHERE series = ?2 COLLATE NOCASE
+                 AND (
+                     NOT EXISTS (SELECT 1 FROM series s WHERE s.id != ?3 AND s.name = ?2 COLLATE NOCASE)
+                     OR COALESCE(studio, '') = COALESCE((SELECT st.name FROM studios st WHERE st.id = ?4), '') COLLATE NOCASE
+                 )"#
+        )
+        .bind(&name)
+        .bind(&current.name)
+        .bind(id)
+        .bind(&current.studio_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    
     get_series_by_id(pool, id).await
 }
 
 /// 删除系列
 pub async fn delete_series(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
+    sqlx::query("DELETE FROM favorite_entities WHERE entity_type = 'series' AND entity_id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    
     sqlx::query("DELETE FROM series WHERE id = ?")
         .bind(id)
         .execute(pool)
@@ -476,41 +677,30 @@ pub async fn delete_series(pool: &Pool<Sqlite>, id: &str) -> Result<()> {
 }
 
 /// 获取系列列表（带厂商信息）
-pub async fn list_series(pool: &Pool<Sqlite>, studio_id: Option<&str>, limit: Option<i32>, offset: Option<i32>) -> Result<SeriesListResponse> {
-    let limit = limit.unwrap_or(100);
-    let offset = offset.unwrap_or(0);
-    
-    let (series_list, total): (Vec<Series>, i64) = if let Some(studio_id) = studio_id {
-        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM series WHERE studio_id = ?")
-            .bind(studio_id)
-            .fetch_one(pool)
-            .await?;
-        
-        let list: Vec<Series> = sqlx::query_as(
-            "SELECT * FROM series WHERE studio_id = ? ORDER BY media_count DESC, name COLLATE NOCASE LIMIT ? OFFSET ?"
-        )
-        .bind(studio_id)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(pool)
-        .await?;
-        
-        (list, count)
-    } else {
-        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM series")
-            .fetch_one(pool)
-            .await?;
-        
-        let list: Vec<Series> = sqlx::query_as(
-            "SELECT * FROM series ORDER BY media_count DESC, name COLLATE NOCASE LIMIT ? OFFSET ?"
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(pool)
-        .await?;
-        
-        (list, count)
-    };
+pub async fn list_series(pool: &Pool<Sqlite>, filters: &StudioListFilters) -> Result<SeriesListResponse> {
+    let search_pattern = name_pattern(filters.query.as_deref());
+    
+    let (total,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM series WHERE (?1 IS NULL OR studio_id = ?1) AND (?2 IS NULL OR name LIKE ?2)"
+    )
+    .bind(&filters.studio_id)
+    .bind(&search_pattern)
+    .fetch_one(pool)
+    .await?;
+    
+    let order_by = filters.sort_by.order_by(filters.sort_order.as_ref(), "name", "media_count", "created_at");
+    let series_list: Vec<Series> = sqlx::query_as(&format!(
+        r#"SELECT * FROM series
+           WHERE (?1 IS NULL OR studio_id = ?1) AND (?2 IS NULL OR name LIKE ?2)
+           ORDER BY {}, id LIMIT ?3 OFFSET ?4"#,
+        order_by
+    ))
+    .bind(&filters.studio_id)
+    .bind(&search_pattern)
+    .bind(filters.limit)
+    .bind(filters.offset)
+    .fetch_all(pool)
+    .await?;
     
     // 获取厂商名称
     let mut result = Vec::new();
@@ -524,38 +714,24 @@ pub async fn list_series(pool: &Pool<Sqlite>, studio_id: Option<&str>, limit: Op
         result.push(SeriesWithStudio { series, studio_name });
     }
     
-    Ok(SeriesListResponse { series: result, total })
+    Ok(SeriesListResponse { series: result, total, limit: filters.limit, offset: filters.offset })
 }
 
-/// 更新厂商的媒体计数
-pub async fn update_studio_media_count(pool: &Pool<Sqlite>, studio_name: &str) -> Result<()> {
-    sqlx::query(
-        r#"UPDATE studios SET media_count = (
-            SELECT COUNT(*) FROM media_items WHERE studio = studios.name COLLATE NOCASE
-        ) WHERE name = ? COLLATE NOCASE"#
-    )
-    .bind(studio_name)
-    .execute(pool)
-    .await?;
-    
-    Ok(())
-}
-
-/// 更新系列的媒体计数
-pub async fn update_series_media_count(pool: &Pool<Sqlite>, series_name: &str) -> Result<()> {
-    sqlx::query(
-        r#"UPDATE series SET media_count = (
-            SELECT COUNT(*) FROM media_items WHERE series = series.name COLLATE NOCASE
-        ) WHERE name = ? COLLATE NOCASE"#
+/// 获取系列下所有媒体的番号和标题
+pub async fn get_series_media_keys(pool: &Pool<Sqlite>, series_name: &str) -> Result<Vec<(Option<String>, String)>> {
+    let keys = sqlx::query_as::<_, (Option<String>, String)>(
+        "SELECT code, title FROM media_items WHERE series = ? COLLATE NOCASE"
     )
     .bind(series_name)
-    .execute(pool)
+    .fetch_all(pool)
     .await?;
     
-    Ok(())
+    Ok(keys)
 }
 
-/// 同步所有厂商和系列的媒体计数
+/// 重建所有厂商和系列的媒体计数
+///
+/// 计数平时由触发器增量维护，这里按当前数据全部重新统计，用于修复计数
 pub async fn sync_all_counts(pool: &Pool<Sqlite>) -> Result<()> {
     // 更新所有厂商计数
     sqlx::query(
@@ -577,3 +753,118 @@ pub async fn sync_all_counts(pool: &Pool<Sqlite>) -> Result<()> {
     
     Ok(())
 }
+
+/// 按 studio、series 文本字段为尚未关联的媒体填写 studio_id、series_id
+///
+/// 同名系列优先选择与媒体同一厂商的；返回无法关联的名称，便于发现拼写不一致的数据
+pub async fn link_media_studio_series(pool: &Pool<Sqlite>) -> Result<MediaLinkResult> {
+    let mut tx = pool.begin().await?;
+
+    let linked_studios = sqlx::query(
+        r#"UPDATE media_items SET studio_id = (
+               SELECT id FROM studios WHERE name = media_items.studio COLLATE NOCASE
+           )
+           WHERE studio_id IS NULL AND COALESCE(studio, '') != ''
+             AND EXISTS (SELECT 1 FROM studios WHERE name = media_items.studio COLLATE NOCASE)"#
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    // 先关联同一厂商的同名系列，再关联其余的同名系列
+    let mut linked_series = 0;
+    for same_studio in [true, false] {
+        linked_series += sqlx::query(
+            r#"UPDATE media_items SET series_id = (
+                   SELECT id FROM series
+                   WHERE name = media_items.series COLLATE NOCASE
+                     AND (? = 0 OR studio_id IS media_items.studio_id)
+                   LIMIT 1
+               )
+               WHERE series_id IS NULL AND COALESCE(series, '') != ''
+                 AND EXISTS (
+                     SELECT 1 FROM series
+                     WHERE name = media_items.series COLLATE NOCASE
+                       AND (? = 0 OR studio_id IS media_items.studio_id)
+                 )"#
+        )
+        .bind(same_studio)
+        .bind(same_studio)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+
+    let unmatched_studios = sqlx::query_as::<_, UnmatchedName>(
+        r#"SELECT studio AS name, COUNT(*) AS media_count
+           FROM media_items
+           WHERE studio_id IS NULL AND COALESCE(studio, '') != ''
+           GROUP BY studio COLLATE NOCASE
+           ORDER BY media_count DESC, name"#
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let unmatched_series = sqlx::query_as::<_, UnmatchedName>(
+        r#"SELECT series AS name, COUNT(*) AS media_count
+           FROM media_items
+           WHERE series_id IS NULL AND COALESCE(series, '') != ''
+           GROUP BY series COLLATE NOCASE
+           ORDER BY media_count DESC, name"#
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(MediaLinkResult { linked_studios, linked_series, unmatched_studios, unmatched_series })
+}
+
+// ============ Series Detection ============
+
+/// 未设置系列的媒体
+pub async fn list_series_detect_candidates(pool: &Pool<Sqlite>) -> Result<Vec<SeriesDetectCandidate>> {
+    let candidates = sqlx::query_as(
+        "SELECT id, title, code, studio FROM media_items WHERE COALESCE(series, '') = '' ORDER BY created_at"
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(candidates)
+}
+
+/// 已知系列名：系列表中的系列（带厂商名）和媒体中已出现的系列名（带媒体的厂商）
+pub async fn list_known_series(pool: &Pool<Sqlite>) -> Result<Vec<KnownSeries>> {
+    let series = sqlx::query_as(
+        r#"SELECT s.name, st.name AS studio
+           FROM series s
+           LEFT JOIN studios st ON st.id = s.studio_id
+           UNION
+           SELECT series AS name, studio
+           FROM media_items
+           WHERE COALESCE(series, '') != ''"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(series)
+}
+
+/// 已设置系列且有番号的媒体（番号, 系列）
+pub async fn list_coded_series(pool: &Pool<Sqlite>) -> Result<Vec<(String, String)>> {
+    let rows = sqlx::query_as(
+        "SELECT code, series FROM media_items WHERE COALESCE(code, '') != '' AND COALESCE(series, '') != ''"
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// 为未设置系列的媒体设置系列，媒体不存在或已有系列时返回 false
+pub async fn assign_media_series(pool: &Pool<Sqlite>, media_id: &str, series: &str) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE media_items SET series = ?, updated_at = datetime('now') WHERE id = ? AND COALESCE(series, '') = ''"
+    )
+    .bind(series)
+    .bind(media_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,69 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use crate::models::{MagnetSwarmStats, SwarmStatsSettings};
+use super::settings_repository::{get_setting, set_setting};
+
+const SWARM_STATS_KEY: &str = "swarm_stats";
+
+const SELECT_STATS: &str = "SELECT *, (trackers_ok > 0 AND seeders = 0) AS dead FROM magnet_swarm_stats";
+
+// ============ Swarm Stats ============
+
+/// 用本次刷新结果替换媒体的磁力统计（不再存在的磁力链接一并移除）
+pub async fn replace_swarm_stats(pool: &Pool<Sqlite>, media_id: &str, stats: &[MagnetSwarmStats]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM magnet_swarm_stats WHERE media_id = ?")
+        .bind(media_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for item in stats {
+        sqlx::query(
+            r#"INSERT OR REPLACE INTO magnet_swarm_stats
+               (media_id, info_hash, seeders, leechers, completed, trackers_ok, error, checked_at)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?)"#
+        )
+        .bind(&item.media_id)
+        .bind(&item.info_hash)
+        .bind(item.seeders)
+        .bind(item.leechers)
+        .bind(item.completed)
+        .bind(item.trackers_ok)
+        .bind(&item.error)
+        .bind(item.checked_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// 获取磁力统计（可按媒体筛选，alive_only 时隐藏没有做种者的磁力链接）
+pub async fn list_swarm_stats(pool: &Pool<Sqlite>, media_id: Option<&str>, alive_only: bool) -> Result<Vec<MagnetSwarmStats>> {
+    let stats = sqlx::query_as::<_, MagnetSwarmStats>(&format!(
+        r#"{} WHERE (? IS NULL OR media_id = ?)
+             AND (? = 0 OR NOT (trackers_ok > 0 AND seeders = 0))
+           ORDER BY media_id, seeders DESC"#,
+        SELECT_STATS
+    ))
+    .bind(media_id)
+    .bind(media_id)
+    .bind(alive_only)
+    .fetch_all(pool)
+    .await?;
+    Ok(stats)
+}
+
+/// 获取做种人数刷新设置（未设置时返回默认值）
+pub async fn get_swarm_stats_settings(pool: &Pool<Sqlite>) -> Result<SwarmStatsSettings> {
+    Ok(match get_setting(pool, SWARM_STATS_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => SwarmStatsSettings::default(),
+    })
+}
+
+/// 保存做种人数刷新设置
+pub async fn save_swarm_stats_settings(pool: &Pool<Sqlite>, settings: &SwarmStatsSettings) -> Result<()> {
+    let value = serde_json::to_string(settings)?;
+    set_setting(pool, SWARM_STATS_KEY, &value, Some("Periodic tracker scrape of stored magnet seeders/leechers")).await
+}
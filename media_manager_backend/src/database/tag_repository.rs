@@ -0,0 +1,131 @@
+use anyhow::Result;
+use sqlx::{Sqlite, Transaction};
+use crate::models::{BatchTagResult, TagChange};
+
+// ============ Batch Tagging ============
+
+async fn find_tag_id(tx: &mut Transaction<'_, Sqlite>, name: &str) -> Result<Option<String>> {
+    let id = sqlx::query_scalar("SELECT id FROM tags WHERE name = ? COLLATE NOCASE")
+        .bind(name)
+        .fetch_optional(&mut **tx)
+        .await?;
+    Ok(id)
+}
+
+async fn refresh_usage_count(tx: &mut Transaction<'_, Sqlite>, tag_id: &str) -> Result<()> {
+    sqlx::query("UPDATE tags SET usage_count = (SELECT COUNT(*) FROM media_tags WHERE tag_id = ?) WHERE id = ?")
+        .bind(tag_id)
+        .bind(tag_id)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+/// 为一批媒体添加/移除标签（标签名不区分大小写，添加不存在的标签时自动创建），
+/// 试运行时只统计将会变化的数量
+pub async fn batch_tag_media(
+    pool: &sqlx::Pool<Sqlite>,
+    media_ids: &[String],
+    add_tags: &[String],
+    remove_tags: &[String],
+    dry_run: bool,
+) -> Result<BatchTagResult> {
+    let ids_json = serde_json::to_string(media_ids)?;
+    let mut tx = pool.begin().await?;
+    let mut result = BatchTagResult {
+        matched: media_ids.len(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        created_tags: Vec::new(),
+        dry_run,
+    };
+
+    for name in add_tags {
+        let tag_id = match find_tag_id(&mut tx, name).await? {
+            Some(id) => id,
+            None if dry_run => {
+                // 新标签会加到每个存在的媒体上（与实际写入一样忽略不存在的 ID）
+                let affected = sqlx::query_scalar(
+                    r#"SELECT COUNT(DISTINCT j.value) FROM json_each(?) j
+                       WHERE EXISTS (SELECT 1 FROM media_items WHERE id = j.value)"#
+                )
+                .bind(&ids_json)
+                .fetch_one(&mut *tx)
+                .await?;
+                result.created_tags.push(name.clone());
+                result.added.push(TagChange { tag: name.clone(), affected });
+                continue;
+            }
+            None => {
+                let id = uuid::Uuid::new_v4().to_string();
+                sqlx::query("INSERT INTO tags (id, name) VALUES (?, ?)")
+                    .bind(&id)
+                    .bind(name)
+                    .execute(&mut *tx)
+                    .await?;
+                result.created_tags.push(name.clone());
+                id
+            }
+        };
+
+        let affected = if dry_run {
+            sqlx::query_scalar(
+                r#"SELECT COUNT(DISTINCT j.value) FROM json_each(?) j
+                   WHERE EXISTS (SELECT 1 FROM media_items WHERE id = j.value)
+                     AND NOT EXISTS (SELECT 1 FROM media_tags WHERE media_id = j.value AND tag_id = ?)"#
+            )
+            .bind(&ids_json)
+            .bind(&tag_id)
+            .fetch_one(&mut *tx)
+            .await?
+        } else {
+            let inserted = sqlx::query(
+                r#"INSERT OR IGNORE INTO media_tags (media_id, tag_id)
+                   SELECT j.value, ? FROM json_each(?) j
+                   WHERE EXISTS (SELECT 1 FROM media_items WHERE id = j.value)"#
+            )
+            .bind(&tag_id)
+            .bind(&ids_json)
+            .execute(&mut *tx)
+            .await?;
+            refresh_usage_count(&mut tx, &tag_id).await?;
+            inserted.rows_affected() as i64
+        };
+        result.added.push(TagChange { tag: name.clone(), affected });
+    }
+
+    for name in remove_tags {
+        let Some(tag_id) = find_tag_id(&mut tx, name).await? else {
+            result.removed.push(TagChange { tag: name.clone(), affected: 0 });
+            continue;
+        };
+
+        let affected = if dry_run {
+            sqlx::query_scalar(
+                "SELECT COUNT(*) FROM media_tags WHERE tag_id = ? AND media_id IN (SELECT value FROM json_each(?))"
+            )
+            .bind(&tag_id)
+            .bind(&ids_json)
+            .fetch_one(&mut *tx)
+            .await?
+        } else {
+            let deleted = sqlx::query(
+                "DELETE FROM media_tags WHERE tag_id = ? AND media_id IN (SELECT value FROM json_each(?))"
+            )
+            .bind(&tag_id)
+            .bind(&ids_json)
+            .execute(&mut *tx)
+            .await?;
+            refresh_usage_count(&mut tx, &tag_id).await?;
+            deleted.rows_affected() as i64
+        };
+        result.removed.push(TagChange { tag: name.clone(), affected });
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(result)
+}
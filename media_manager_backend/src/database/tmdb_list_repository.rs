@@ -0,0 +1,72 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Sqlite};
+use crate::models::{TmdbListCacheSettings, TmdbListPage};
+use super::settings_repository::{get_setting, set_setting};
+
+const TMDB_LIST_CACHE_KEY: &str = "tmdb_list_cache";
+
+// ============ TMDB List Cache Settings ============
+
+/// 获取 TMDB 列表缓存设置（未设置时返回默认值）
+pub async fn get_tmdb_list_cache_settings(pool: &Pool<Sqlite>) -> Result<TmdbListCacheSettings> {
+    Ok(match get_setting(pool, TMDB_LIST_CACHE_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => TmdbListCacheSettings::default(),
+    })
+}
+
+/// 保存 TMDB 列表缓存设置
+pub async fn save_tmdb_list_cache_settings(pool: &Pool<Sqlite>, settings: &TmdbListCacheSettings) -> Result<()> {
+    let value = serde_json::to_string(settings)?;
+    set_setting(pool, TMDB_LIST_CACHE_KEY, &value, Some("Nightly TMDB popular/trending prefetch")).await
+}
+
+// ============ TMDB List Pages ============
+
+/// 保存一页列表（覆盖旧数据）
+pub async fn save_tmdb_list_page<T: serde::Serialize>(
+    pool: &Pool<Sqlite>,
+    list: &str,
+    media_type: &str,
+    page: u32,
+    results: &T,
+) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO tmdb_list_cache (list, media_type, page, results, fetched_at)
+           VALUES (?, ?, ?, ?, ?)
+           ON CONFLICT(list, media_type, page) DO UPDATE SET
+               results = excluded.results, fetched_at = excluded.fetched_at"#
+    )
+    .bind(list)
+    .bind(media_type)
+    .bind(page)
+    .bind(serde_json::to_string(results)?)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// 获取缓存的一页列表
+pub async fn get_tmdb_list_page(pool: &Pool<Sqlite>, list: &str, media_type: &str, page: u32) -> Result<Option<TmdbListPage>> {
+    let row: Option<(String, DateTime<Utc>)> = sqlx::query_as(
+        "SELECT results, fetched_at FROM tmdb_list_cache WHERE list = ? AND media_type = ? AND page = ?"
+    )
+    .bind(list)
+    .bind(media_type)
+    .bind(page)
+    .fetch_optional(pool)
+    .await?;
+    
+    match row {
+        Some((results, fetched_at)) => Ok(Some(TmdbListPage {
+            list: list.to_string(),
+            media_type: media_type.to_string(),
+            page,
+            results: serde_json::from_str(&results)?,
+            fetched_at,
+        })),
+        None => Ok(None),
+    }
+}
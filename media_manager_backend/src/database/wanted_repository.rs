@@ -0,0 +1,469 @@
+use anyhow::Result;
+use sqlx::{Pool, Sqlite, Row};
+use std::collections::BTreeMap;
+use crate::models::{
+    acquisition_transition, AcquisitionItem, AcquisitionOverview, LinkDownload, MagnetTrackerSettings, TorrentDownload,
+    WantedMedia, WantedMediaWithInfo, AddWantedRequest, UpdateWantedRequest, ACQUISITION_STATES,
+};
+use super::settings_repository::{get_setting, set_setting};
+
+const MAGNET_TRACKERS_KEY: &str = "magnet_trackers";
+
+// ============ Wanted CRUD ============
+
+/// 添加到想要列表
+pub async fn add_wanted(pool: &Pool<Sqlite>, req: AddWantedRequest) -> Result<WantedMedia> {
+    let quality_filter = match req.quality_filter {
+        Some(ref filter) => Some(serde_json::to_string(filter)?),
+        None => None,
+    };
+
+    sqlx::query(
+        r#"INSERT INTO wanted_media (media_id, status, action, quality_filter, plugin_id, search_query, created_at, updated_at)
+           VALUES (?, 'monitoring', ?, ?, ?, ?, datetime('now'), datetime('now'))"#
+    )
+    .bind(&req.media_id)
+    .bind(req.action.as_deref().unwrap_or("notify"))
+    .bind(&quality_filter)
+    .bind(&req.plugin_id)
+    .bind(&req.search_query)
+    .execute(pool)
+    .await?;
+
+    get_wanted(pool, &req.media_id).await
+}
+
+/// 获取想要列表条目
+pub async fn get_wanted(pool: &Pool<Sqlite>, media_id: &str) -> Result<WantedMedia> {
+    let wanted = sqlx::query_as::<_, WantedMedia>("SELECT * FROM wanted_media WHERE media_id = ?")
+        .bind(media_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(wanted)
+}
+
+/// 查找单个想要条目（不存在时为 None）
+pub async fn find_wanted(pool: &Pool<Sqlite>, media_id: &str) -> Result<Option<WantedMedia>> {
+    let wanted = sqlx::query_as::<_, WantedMedia>("SELECT * FROM wanted_media WHERE media_id = ?")
+        .bind(media_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(wanted)
+}
+
+/// 获取想要列表（可按状态筛选）
+pub async fn list_wanted(pool: &Pool<Sqlite>, status: Option<&str>) -> Result<Vec<WantedMediaWithInfo>> {
+    let mut sql = String::from(
+        r#"SELECT w.*, m.title AS media_title, m.code AS media_code, m.poster_url AS media_poster_url
+           FROM wanted_media w
+           JOIN media_items m ON m.id = w.media_id"#
+    );
+    if status.is_some() {
+        sql.push_str(" WHERE w.status = ?");
+    }
+    sql.push_str(" ORDER BY w.created_at DESC");
+
+    let mut query = sqlx::query(&sql);
+    if let Some(status) = status {
+        query = query.bind(status);
+    }
+    let rows = query.fetch_all(pool).await?;
+
+    let mut items = Vec::with_capacity(rows.len());
+    for row in rows {
+        let wanted = <WantedMedia as sqlx::FromRow<_>>::from_row(&row)?;
+        items.push(WantedMediaWithInfo {
+            wanted,
+            title: row.try_get("media_title")?,
+            code: row.try_get("media_code")?,
+            poster_url: row.try_get("media_poster_url")?,
+        });
+    }
+    Ok(items)
+}
+
+/// 更新想要列表条目
+pub async fn update_wanted(pool: &Pool<Sqlite>, media_id: &str, req: UpdateWantedRequest) -> Result<Option<WantedMedia>> {
+    let mut updates = Vec::new();
+    let mut params: Vec<String> = Vec::new();
+
+    if let Some(ref status) = req.status {
+        updates.push("status = ?");
+        params.push(status.clone());
+    }
+    if let Some(ref action) = req.action {
+        updates.push("action = ?");
+        params.push(action.clone());
+    }
+    if let Some(ref filter) = req.quality_filter {
+        updates.push("quality_filter = ?");
+        params.push(serde_json::to_string(filter)?);
+    }
+    if let Some(ref plugin_id) = req.plugin_id {
+        updates.push("plugin_id = ?");
+        params.push(plugin_id.clone());
+    }
+    if let Some(ref search_query) = req.search_query {
+        updates.push("search_query = ?");
+        params.push(search_query.clone());
+    }
+
+    if updates.is_empty() {
+        return find_wanted(pool, media_id).await;
+    }
+
+    let sql = format!(
+        "UPDATE wanted_media SET {}, updated_at = datetime('now') WHERE media_id = ?",
+        updates.join(", ")
+    );
+
+    let mut query = sqlx::query(&sql);
+    for param in &params {
+        query = query.bind(param);
+    }
+    query = query.bind(media_id);
+    if query.execute(pool).await?.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    find_wanted(pool, media_id).await
+}
+
+/// 从想要列表移除
+pub async fn remove_wanted(pool: &Pool<Sqlite>, media_id: &str) -> Result<()> {
+    let result = sqlx::query("DELETE FROM wanted_media WHERE media_id = ?")
+        .bind(media_id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(anyhow::anyhow!("Wanted item not found"));
+    }
+    Ok(())
+}
+
+// ============ Monitor Helpers ============
+
+/// 获取需要监控的条目（状态为 monitoring）
+pub async fn list_wanted_for_monitoring(pool: &Pool<Sqlite>) -> Result<Vec<WantedMediaWithInfo>> {
+    list_wanted(pool, Some("monitoring")).await
+}
+
+/// 记录一次搜索
+pub async fn mark_wanted_searched(pool: &Pool<Sqlite>, media_id: &str) -> Result<()> {
+    sqlx::query("UPDATE wanted_media SET last_searched_at = datetime('now') WHERE media_id = ?")
+        .bind(media_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// 记录找到的磁力资源并更新状态（found / pushed）
+pub async fn mark_wanted_found(
+    pool: &Pool<Sqlite>,
+    media_id: &str,
+    status: &str,
+    title: &str,
+    magnet: &str,
+    size: Option<&str>,
+) -> Result<()> {
+    sqlx::query(
+        r#"UPDATE wanted_media SET
+               status = ?, found_title = ?, found_magnet = ?, found_size = ?,
+               found_at = datetime('now'), updated_at = datetime('now')
+           WHERE media_id = ?"#
+    )
+    .bind(status)
+    .bind(title)
+    .bind(magnet)
+    .bind(size)
+    .bind(media_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// ============ Magnet Trackers ============
+
+/// 获取磁力链接 tracker 设置（未设置时返回默认值）
+pub async fn get_magnet_tracker_settings(pool: &Pool<Sqlite>) -> Result<MagnetTrackerSettings> {
+    Ok(match get_setting(pool, MAGNET_TRACKERS_KEY).await? {
+        Some(value) => serde_json::from_str(&value)?,
+        None => MagnetTrackerSettings::default(),
+    })
+}
+
+/// 保存磁力链接 tracker 设置
+pub async fn save_magnet_tracker_settings(pool: &Pool<Sqlite>, settings: &MagnetTrackerSettings) -> Result<()> {
+    let value = serde_json::to_string(settings)?;
+    set_setting(pool, MAGNET_TRACKERS_KEY, &value, Some("Trackers appended to / stripped from magnet links")).await
+}
+
+// ============ Torrent Downloads ============
+
+/// 记录推送到下载客户端的任务（重复推送同一资源时重新开始跟踪）
+pub async fn record_torrent_download(
+    pool: &Pool<Sqlite>,
+    info_hash: &str,
+    media_id: &str,
+    title: Option<&str>,
+    magnet_link: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"INSERT INTO torrent_downloads (info_hash, media_id, title, magnet_link)
+           VALUES (?, ?, ?, ?)
+           ON CONFLICT(info_hash) DO UPDATE SET
+               media_id = excluded.media_id, title = excluded.title, magnet_link = excluded.magnet_link,
+               status = 'downloading', progress = 0, error = NULL, completed_at = NULL, imported_at = NULL,
+               updated_at = datetime('now')"#
+    )
+    .bind(info_hash)
+    .bind(media_id)
+    .bind(title)
+    .bind(magnet_link)
+    .execute(pool)
+    .await?;
+    advance_acquisition(pool, media_id, "downloading").await
+}
+
+/// 获取下载任务（可按状态筛选）
+pub async fn list_torrent_downloads(pool: &Pool<Sqlite>, status: Option<&str>) -> Result<Vec<TorrentDownload>> {
+    let downloads = match status {
+        Some(status) => sqlx::query_as::<_, TorrentDownload>(
+            "SELECT * FROM torrent_downloads WHERE status = ? ORDER BY created_at DESC"
+        )
+        .bind(status)
+        .fetch_all(pool)
+        .await?,
+        None => sqlx::query_as::<_, TorrentDownload>("SELECT * FROM torrent_downloads ORDER BY created_at DESC")
+            .fetch_all(pool)
+            .await?,
+    };
+    Ok(downloads)
+}
+
+/// 获取单个下载任务
+pub async fn get_torrent_download(pool: &Pool<Sqlite>, info_hash: &str) -> Result<Option<TorrentDownload>> {
+    let download = sqlx::query_as::<_, TorrentDownload>("SELECT * FROM torrent_downloads WHERE info_hash = ?")
+        .bind(info_hash)
+        .fetch_optional(pool)
+        .await?;
+    Ok(download)
+}
+
+/// 更新下载进度
+pub async fn update_torrent_progress(pool: &Pool<Sqlite>, info_hash: &str, progress: f64) -> Result<()> {
+    update_download_progress(pool, "torrent_downloads", "info_hash", info_hash, progress).await
+}
+
+/// 保存下载任务状态（imported 时记录整理后的文件，failed 时记录错误）
+pub async fn set_torrent_download_status(
+    pool: &Pool<Sqlite>,
+    info_hash: &str,
+    status: &str,
+    imported_files: Option<&[String]>,
+    error: Option<&str>,
+) -> Result<()> {
+    set_download_status(pool, "torrent_downloads", "info_hash", info_hash, status, imported_files, error).await
+}
+
+// ============ Link Downloads ============
+
+/// 记录推送到 aria2 的任务
+pub async fn record_link_download(
+    pool: &Pool<Sqlite>,
+    gid: &str,
+    media_id: &str,
+    name: Option<&str>,
+    url: &str,
+) -> Result<()> {
+    sqlx::query("INSERT INTO link_downloads (gid, media_id, name, url) VALUES (?, ?, ?, ?)")
+        .bind(gid)
+        .bind(media_id)
+        .bind(name)
+        .bind(url)
+        .execute(pool)
+        .await?;
+    advance_acquisition(pool, media_id, "downloading").await
+}
+
+/// 重新下载时换成新的 aria2 任务ID
+pub async fn restart_link_download(pool: &Pool<Sqlite>, gid: &str, new_gid: &str) -> Result<()> {
+    sqlx::query(
+        r#"UPDATE link_downloads SET gid = ?, status = 'downloading', progress = 0, error = NULL,
+               completed_at = NULL, imported_at = NULL, updated_at = datetime('now')
+           WHERE gid = ?"#
+    )
+    .bind(new_gid)
+    .bind(gid)
+    .execute(pool)
+    .await?;
+    set_download_acquisition(pool, "link_downloads", "gid", new_gid, "downloading").await
+}
+
+/// 获取链接下载任务（可按状态筛选）
+pub async fn list_link_downloads(pool: &Pool<Sqlite>, status: Option<&str>) -> Result<Vec<LinkDownload>> {
+    let downloads = match status {
+        Some(status) => sqlx::query_as::<_, LinkDownload>(
+            "SELECT * FROM link_downloads WHERE status = ? ORDER BY created_at DESC"
+        )
+        .bind(status)
+        .fetch_all(pool)
+        .await?,
+        None => sqlx::query_as::<_, LinkDownload>("SELECT * FROM link_downloads ORDER BY created_at DESC")
+            .fetch_all(pool)
+            .await?,
+    };
+    Ok(downloads)
+}
+
+/// 获取单个链接下载任务
+pub async fn get_link_download(pool: &Pool<Sqlite>, gid: &str) -> Result<Option<LinkDownload>> {
+    let download = sqlx::query_as::<_, LinkDownload>("SELECT * FROM link_downloads WHERE gid = ?")
+        .bind(gid)
+        .fetch_optional(pool)
+        .await?;
+    Ok(download)
+}
+
+/// 更新链接下载进度
+pub async fn update_link_progress(pool: &Pool<Sqlite>, gid: &str, progress: f64) -> Result<()> {
+    update_download_progress(pool, "link_downloads", "gid", gid, progress).await
+}
+
+/// 保存链接下载任务状态
+pub async fn set_link_download_status(
+    pool: &Pool<Sqlite>,
+    gid: &str,
+    status: &str,
+    imported_files: Option<&[String]>,
+    error: Option<&str>,
+) -> Result<()> {
+    set_download_status(pool, "link_downloads", "gid", gid, status, imported_files, error).await
+}
+
+async fn update_download_progress(pool: &Pool<Sqlite>, table: &str, key_column: &str, key: &str, progress: f64) -> Result<()> {
+    sqlx::query(&format!("UPDATE {} SET progress = ?, updated_at = datetime('now') WHERE {} = ?", table, key_column))
+        .bind(progress)
+        .bind(key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+async fn set_download_status(
+    pool: &Pool<Sqlite>,
+    table: &str,
+    key_column: &str,
+    key: &str,
+    status: &str,
+    imported_files: Option<&[String]>,
+    error: Option<&str>,
+) -> Result<()> {
+    let imported_files = imported_files.map(serde_json::to_string).transpose()?;
+    sqlx::query(&format!(
+        r#"UPDATE {} SET
+               status = ?,
+               imported_files = COALESCE(?, imported_files),
+               error = ?,
+               progress = CASE WHEN ? IN ('importing', 'imported') THEN 1 ELSE progress END,
+               completed_at = CASE WHEN ? = 'importing' THEN datetime('now') ELSE completed_at END,
+               imported_at = CASE WHEN ? = 'imported' THEN datetime('now') ELSE imported_at END,
+               updated_at = datetime('now')
+           WHERE {} = ?"#,
+        table, key_column
+    ))
+    .bind(status)
+    .bind(imported_files)
+    .bind(error)
+    .bind(status)
+    .bind(status)
+    .bind(status)
+    .bind(key)
+    .execute(pool)
+    .await?;
+    set_download_acquisition(pool, table, key_column, key, status).await
+}
+
+/// 按下载任务所属的媒体推进获取状态
+async fn set_download_acquisition(pool: &Pool<Sqlite>, table: &str, key_column: &str, key: &str, status: &str) -> Result<()> {
+    let media_id: Option<String> = sqlx::query_scalar(&format!("SELECT media_id FROM {} WHERE {} = ?", table, key_column))
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+    match media_id {
+        Some(media_id) => advance_acquisition(pool, &media_id, status).await,
+        None => Ok(()),
+    }
+}
+
+// ============ Acquisition ============
+
+/// 没有本地文件且未在跟踪中的媒体标记为 wanted，返回是否标记
+pub async fn start_acquisition(pool: &Pool<Sqlite>, media_id: &str) -> Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE media_items SET acquisition_status = 'wanted', acquisition_updated_at = datetime('now')
+           WHERE id = ? AND acquisition_status IS NULL
+             AND (local_file_path IS NULL OR local_file_path = '')
+             AND NOT EXISTS (SELECT 1 FROM media_files WHERE media_id = media_items.id)"#
+    )
+    .bind(media_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// 下载任务状态变化时推进媒体的获取状态（未跟踪的媒体和不允许的转换保持不变）
+async fn advance_acquisition(pool: &Pool<Sqlite>, media_id: &str, download_status: &str) -> Result<()> {
+    let Some((to, from)) = acquisition_transition(download_status) else {
+        return Ok(());
+    };
+    let placeholders = vec!["?"; from.len()].join(", ");
+    let sql = format!(
+        "UPDATE media_items SET acquisition_status = ?, acquisition_updated_at = datetime('now') WHERE id = ? AND acquisition_status IN ({})",
+        placeholders
+    );
+    let mut query = sqlx::query(&sql).bind(to).bind(media_id);
+    for state in from {
+        query = query.bind(*state);
+    }
+    query.execute(pool).await?;
+    Ok(())
+}
+
+/// 获取各获取状态的数量及条目（可按状态筛选）
+pub async fn list_acquisition(pool: &Pool<Sqlite>, state: Option<&str>) -> Result<AcquisitionOverview> {
+    let rows: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT acquisition_status, COUNT(*) FROM media_items WHERE acquisition_status IS NOT NULL GROUP BY acquisition_status"
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut counts: BTreeMap<String, i64> = ACQUISITION_STATES.iter().map(|s| (s.to_string(), 0)).collect();
+    counts.extend(rows);
+
+    let mut sql = String::from(
+        r#"SELECT id AS media_id, title, code, poster_url, acquisition_status, acquisition_updated_at
+           FROM media_items WHERE acquisition_status IS NOT NULL"#
+    );
+    if state.is_some() {
+        sql.push_str(" AND acquisition_status = ?");
+    }
+    sql.push_str(" ORDER BY acquisition_updated_at DESC");
+    let mut query = sqlx::query_as::<_, AcquisitionItem>(&sql);
+    if let Some(state) = state {
+        query = query.bind(state);
+    }
+    let items = query.fetch_all(pool).await?;
+
+    Ok(AcquisitionOverview { counts, items })
+}
+
+/// 导入完成后把媒体加入收藏（待观看），已在收藏中时不改变观看状态
+pub async fn ensure_in_collection(pool: &Pool<Sqlite>, media_id: &str) -> Result<()> {
+    sqlx::query("INSERT OR IGNORE INTO collections (id, media_id, watch_status) VALUES (?, ?, 'WantToWatch')")
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(media_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
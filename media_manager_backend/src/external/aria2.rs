@@ -0,0 +1,155 @@
+//! aria2 JSON-RPC 客户端
+//!
+//! 用于下载 HTTP/FTP 直链，通过环境变量配置：
+//! - ARIA2_RPC_URL: RPC 地址，如 http://127.0.0.1:6800/jsonrpc
+//! - ARIA2_RPC_SECRET: RPC 密钥（--rpc-secret，可选）
+//! - ARIA2_DOWNLOAD_DIR: 下载目录（可选，默认使用 aria2 的设置）
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// aria2 能下载的链接协议
+pub const ARIA2_SCHEMES: [&str; 4] = ["http", "https", "ftp", "sftp"];
+
+/// aria2 客户端
+#[derive(Debug, Clone)]
+pub struct Aria2Client {
+    client: Client,
+    rpc_url: String,
+    secret: Option<String>,
+    download_dir: Option<String>,
+}
+
+/// 任务状态（aria2.tellStatus）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Aria2Status {
+    pub gid: String,
+    /// active、waiting、paused、error、complete、removed
+    pub status: String,
+    #[serde(default)]
+    pub total_length: String,
+    #[serde(default)]
+    pub completed_length: String,
+    #[serde(default)]
+    pub files: Vec<Aria2File>,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Aria2File {
+    /// 本地完整路径
+    pub path: String,
+    #[serde(default)]
+    pub length: String,
+}
+
+impl Aria2Status {
+    pub fn is_complete(&self) -> bool {
+        self.status == "complete"
+    }
+
+    /// 出错或被移除的任务不会再完成
+    pub fn is_failed(&self) -> bool {
+        matches!(self.status.as_str(), "error" | "removed")
+    }
+
+    /// 下载进度（0.0 - 1.0），总大小未知时为 0
+    pub fn progress(&self) -> f64 {
+        let total: f64 = self.total_length.parse().unwrap_or(0.0);
+        let completed: f64 = self.completed_length.parse().unwrap_or(0.0);
+        if total > 0.0 { (completed / total).min(1.0) } else { 0.0 }
+    }
+}
+
+impl Aria2Client {
+    pub fn new(rpc_url: String, secret: Option<String>, download_dir: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            rpc_url,
+            secret: secret.filter(|s| !s.is_empty()),
+            download_dir: download_dir.filter(|s| !s.is_empty()),
+        }
+    }
+
+    /// 从环境变量创建，未配置 ARIA2_RPC_URL 时返回 None
+    pub fn from_env() -> Option<Self> {
+        let rpc_url = std::env::var("ARIA2_RPC_URL").ok().filter(|s| !s.is_empty())?;
+        Some(Self::new(
+            rpc_url,
+            std::env::var("ARIA2_RPC_SECRET").ok(),
+            std::env::var("ARIA2_DOWNLOAD_DIR").ok(),
+        ))
+    }
+
+    /// 调用 RPC 方法（配置了密钥时作为第一个参数传入）
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Vec<Value>) -> Result<T> {
+        let mut all_params = Vec::with_capacity(params.len() + 1);
+        if let Some(ref secret) = self.secret {
+            all_params.push(json!(format!("token:{}", secret)));
+        }
+        all_params.extend(params);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": uuid::Uuid::new_v4().to_string(),
+            "method": method,
+            "params": all_params,
+        });
+        let response: Value = self.client.post(&self.rpc_url).json(&body).send().await?.json().await?;
+        if let Some(error) = response.get("error") {
+            let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+            return Err(anyhow!("aria2 {} failed: {}", method, message));
+        }
+        let result = response.get("result").cloned().ok_or_else(|| anyhow!("aria2 {} returned no result", method))?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// 添加下载任务，返回任务ID（gid）
+    pub async fn add_uri(&self, url: &str) -> Result<String> {
+        let mut options = serde_json::Map::new();
+        if let Some(ref dir) = self.download_dir {
+            options.insert("dir".to_string(), json!(dir));
+        }
+        let gid: String = self.call("aria2.addUri", vec![json!([url]), Value::Object(options)]).await?;
+        tracing::info!("已推送下载链接到 aria2: {}", gid);
+        Ok(gid)
+    }
+
+    /// 查询任务状态
+    pub async fn tell_status(&self, gid: &str) -> Result<Aria2Status> {
+        let keys = json!(["gid", "status", "totalLength", "completedLength", "files", "errorMessage"]);
+        self.call("aria2.tellStatus", vec![json!(gid), keys]).await
+    }
+}
+
+/// 链接能否交给 aria2 下载
+pub fn is_aria2_url(url: &str) -> bool {
+    url::Url::parse(url).is_ok_and(|url| ARIA2_SCHEMES.contains(&url.scheme()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aria2_status_progress() {
+        let status: Aria2Status = serde_json::from_value(json!({
+            "gid": "2089b05ecca3d829",
+            "status": "active",
+            "totalLength": "1000",
+            "completedLength": "250",
+            "files": [{ "index": "1", "path": "/downloads/a.mp4", "length": "1000" }],
+        })).unwrap();
+        assert_eq!(status.progress(), 0.25);
+        assert!(!status.is_complete() && !status.is_failed());
+        assert_eq!(status.files[0].path, "/downloads/a.mp4");
+
+        assert!(is_aria2_url("https://example.com/a.mp4"));
+        assert!(!is_aria2_url("ed2k://|file|a.mp4|1|ABC|/"));
+        assert!(!is_aria2_url("magnet:?xt=urn:btih:abc"));
+    }
+}
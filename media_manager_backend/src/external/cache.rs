@@ -4,6 +4,7 @@ use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 
 use crate::models::MediaItem;
+use super::tmdb::TmdbRegionProviders;
 
 /// 缓存条目
 #[derive(Debug, Clone)]
@@ -71,6 +72,12 @@ impl<T: Clone> MemoryCache<T> {
         }
     }
     
+    pub fn remove_prefix(&self, prefix: &str) {
+        if let Ok(mut cache) = self.cache.write() {
+            cache.retain(|key, _| !key.starts_with(prefix));
+        }
+    }
+    
     pub fn clear(&self) {
         if let Ok(mut cache) = self.cache.write() {
             cache.clear();
@@ -94,6 +101,8 @@ pub struct TmdbCache {
     search_cache: MemoryCache<Vec<MediaItem>>,
     details_cache: MemoryCache<MediaItem>,
     popular_cache: MemoryCache<Vec<MediaItem>>,
+    /// 观看渠道按地区缓存，None 表示该地区没有渠道
+    providers_cache: MemoryCache<Option<TmdbRegionProviders>>,
 }
 
 impl TmdbCache {
@@ -105,6 +114,8 @@ impl TmdbCache {
             details_cache: MemoryCache::new(Duration::from_secs(2 * 60 * 60)),
             // 热门内容缓存1小时
             popular_cache: MemoryCache::new(Duration::from_secs(60 * 60)),
+            // 观看渠道缓存12小时
+            providers_cache: MemoryCache::new(Duration::from_secs(12 * 60 * 60)),
         }
     }
     
@@ -118,11 +129,26 @@ impl TmdbCache {
         format!("details:{}:{}", media_type, id)
     }
     
+    /// 生成发现缓存键
+    fn discover_cache_key(&self, media_type: &str, query: &str) -> String {
+        format!("discover:{}:{}", media_type, query)
+    }
+    
     /// 生成热门内容缓存键
     fn popular_cache_key(&self, media_type: &str, page: u32) -> String {
         format!("popular:{}:{}", media_type, page)
     }
     
+    /// 生成趋势内容缓存键
+    fn trending_cache_key(&self, media_type: &str, page: u32) -> String {
+        format!("trending:{}:{}", media_type, page)
+    }
+    
+    /// 生成观看渠道缓存键
+    fn providers_cache_key(&self, media_type: &str, id: u32, region: &str) -> String {
+        format!("providers:{}:{}:{}", media_type, id, region.to_uppercase())
+    }
+    
     /// 获取搜索结果缓存
     pub fn get_search_results(&self, query: &str, media_type: &str, page: u32) -> Option<Vec<MediaItem>> {
         let key = self.search_cache_key(query, media_type, page);
@@ -147,6 +173,18 @@ impl TmdbCache {
         self.details_cache.set(key, details);
     }
     
+    /// 获取发现结果缓存（与搜索结果共用缓存时长）
+    pub fn get_discover(&self, media_type: &str, query: &str) -> Option<Vec<MediaItem>> {
+        let key = self.discover_cache_key(media_type, query);
+        self.search_cache.get(&key)
+    }
+    
+    /// 设置发现结果缓存
+    pub fn set_discover(&self, media_type: &str, query: &str, results: Vec<MediaItem>) {
+        let key = self.discover_cache_key(media_type, query);
+        self.search_cache.set(key, results);
+    }
+    
     /// 获取热门内容缓存
     pub fn get_popular(&self, media_type: &str, page: u32) -> Option<Vec<MediaItem>> {
         let key = self.popular_cache_key(media_type, page);
@@ -159,11 +197,42 @@ impl TmdbCache {
         self.popular_cache.set(key, results);
     }
     
+    /// 获取趋势内容缓存（与热门内容共用缓存时长）
+    pub fn get_trending(&self, media_type: &str, page: u32) -> Option<Vec<MediaItem>> {
+        let key = self.trending_cache_key(media_type, page);
+        self.popular_cache.get(&key)
+    }
+    
+    /// 设置趋势内容缓存
+    pub fn set_trending(&self, media_type: &str, page: u32, results: Vec<MediaItem>) {
+        let key = self.trending_cache_key(media_type, page);
+        self.popular_cache.set(key, results);
+    }
+    
+    /// 获取观看渠道缓存（外层 None 表示未缓存）
+    pub fn get_providers(&self, media_type: &str, id: u32, region: &str) -> Option<Option<TmdbRegionProviders>> {
+        let key = self.providers_cache_key(media_type, id, region);
+        self.providers_cache.get(&key)
+    }
+    
+    /// 设置观看渠道缓存
+    pub fn set_providers(&self, media_type: &str, id: u32, region: &str, providers: Option<TmdbRegionProviders>) {
+        let key = self.providers_cache_key(media_type, id, region);
+        self.providers_cache.set(key, providers);
+    }
+    
+    /// 清除某个 TMDB 条目的详情和各地区观看渠道缓存（媒体改绑 TMDB ID 时调用）
+    pub fn invalidate_item(&self, media_type: &str, id: u32) {
+        self.details_cache.remove(&self.details_cache_key(media_type, id));
+        self.providers_cache.remove_prefix(&format!("providers:{}:{}:", media_type, id));
+    }
+    
     /// 清理过期缓存
     pub fn cleanup_expired(&self) {
         self.search_cache.cleanup_expired();
         self.details_cache.cleanup_expired();
         self.popular_cache.cleanup_expired();
+        self.providers_cache.cleanup_expired();
     }
     
     /// 清空所有缓存
@@ -171,6 +240,7 @@ impl TmdbCache {
         self.search_cache.clear();
         self.details_cache.clear();
         self.popular_cache.clear();
+        self.providers_cache.clear();
     }
     
     /// 获取缓存统计信息
@@ -179,6 +249,7 @@ impl TmdbCache {
             search_cache_size: self.search_cache.size(),
             details_cache_size: self.details_cache.size(),
             popular_cache_size: self.popular_cache.size(),
+            providers_cache_size: self.providers_cache.size(),
         }
     }
 }
@@ -195,6 +266,7 @@ pub struct CacheStats {
     pub search_cache_size: usize,
     pub details_cache_size: usize,
     pub popular_cache_size: usize,
+    pub providers_cache_size: usize,
 }
 
 /// 缓存清理任务
@@ -1,9 +1,11 @@
 pub mod tmdb;
 pub mod cache;
+pub mod aria2;
 
 use anyhow::Result;
-pub use tmdb::{TmdbClient, TmdbConverter};
+pub use tmdb::{TmdbClient, TmdbConverter, TmdbDiscoverParams, TmdbRegionProviders, TmdbTranslation, TmdbVideo, TmdbWatchProvider};
 pub use cache::{TmdbCache, CacheStats};
+pub use aria2::Aria2Client;
 
 use crate::models::MediaItem;
 
@@ -17,7 +19,10 @@ impl ExternalApiClient {
     pub fn new() -> Self {
         let tmdb_client = std::env::var("TMDB_API_KEY")
             .ok()
-            .map(|api_key| TmdbClient::new(api_key));
+            .map(|api_key| match std::env::var("TMDB_BASE_URL") {
+                Ok(base_url) if !base_url.is_empty() => TmdbClient::new(api_key).with_base_url(base_url),
+                _ => TmdbClient::new(api_key),
+            });
         
         Self {
             tmdb_client,
@@ -131,6 +136,42 @@ impl ExternalApiClient {
         }
     }
     
+    /// 获取最合适的预告片（media_type 为 movie 或 tv）
+    pub async fn get_trailer(&self, media_type: &str, tmdb_id: u32) -> Result<Option<TmdbVideo>> {
+        if let Some(ref client) = self.tmdb_client {
+            let videos = client.get_videos(media_type, tmdb_id).await?;
+            Ok(TmdbVideo::pick_trailer(&videos.results).cloned())
+        } else {
+            Err(anyhow::anyhow!("TMDB API key not configured"))
+        }
+    }
+    
+    /// 获取电影/电视剧的全部翻译
+    pub async fn get_translations(&self, media_type: &str, tmdb_id: u32) -> Result<Vec<TmdbTranslation>> {
+        if let Some(ref client) = self.tmdb_client {
+            Ok(client.get_translations(media_type, tmdb_id).await?.translations)
+        } else {
+            Err(anyhow::anyhow!("TMDB API key not configured"))
+        }
+    }
+    
+    /// 获取指定地区的观看渠道（带缓存，按地区缓存）
+    pub async fn get_watch_providers(&self, media_type: &str, tmdb_id: u32, region: &str) -> Result<Option<TmdbRegionProviders>> {
+        if let Some(cached) = self.cache.get_providers(media_type, tmdb_id, region) {
+            tracing::debug!("Cache hit for watch providers: {} {} ({})", media_type, tmdb_id, region);
+            return Ok(cached);
+        }
+        
+        if let Some(ref client) = self.tmdb_client {
+            let response = client.get_watch_providers(media_type, tmdb_id).await?;
+            let providers = response.region(region).cloned();
+            self.cache.set_providers(media_type, tmdb_id, region, providers.clone());
+            Ok(providers)
+        } else {
+            Err(anyhow::anyhow!("TMDB API key not configured"))
+        }
+    }
+    
     /// 获取热门电影（带缓存）
     pub async fn get_popular_movies(&self, page: Option<u32>) -> Result<Vec<MediaItem>> {
         let page = page.unwrap_or(1);
@@ -193,6 +234,118 @@ impl ExternalApiClient {
         }
     }
     
+    /// 获取本周趋势电影（带缓存）
+    pub async fn get_trending_movies(&self, page: Option<u32>) -> Result<Vec<MediaItem>> {
+        let page = page.unwrap_or(1);
+        
+        // 检查缓存
+        if let Some(cached_results) = self.cache.get_trending("movie", page) {
+            tracing::debug!("Cache hit for trending movies (page {})", page);
+            return Ok(cached_results);
+        }
+        
+        if let Some(ref client) = self.tmdb_client {
+            let response = client.get_trending_movies(Some(page)).await?;
+            let mut media_items = Vec::new();
+            
+            for movie in response.results {
+                match TmdbConverter::movie_to_media_item(&movie, client) {
+                    Ok(media_item) => media_items.push(media_item),
+                    Err(e) => tracing::warn!("Failed to convert trending movie {}: {}", movie.title, e),
+                }
+            }
+            
+            // 缓存结果
+            self.cache.set_trending("movie", page, media_items.clone());
+            tracing::debug!("Cached trending movies (page {})", page);
+            
+            Ok(media_items)
+        } else {
+            Err(anyhow::anyhow!("TMDB API key not configured"))
+        }
+    }
+    
+    /// 获取本周趋势电视剧（带缓存）
+    pub async fn get_trending_tv_shows(&self, page: Option<u32>) -> Result<Vec<MediaItem>> {
+        let page = page.unwrap_or(1);
+        
+        // 检查缓存
+        if let Some(cached_results) = self.cache.get_trending("tv", page) {
+            tracing::debug!("Cache hit for trending TV shows (page {})", page);
+            return Ok(cached_results);
+        }
+        
+        if let Some(ref client) = self.tmdb_client {
+            let response = client.get_trending_tv_shows(Some(page)).await?;
+            let mut media_items = Vec::new();
+            
+            for scene in response.results {
+                match TmdbConverter::scene_to_media_item(&scene, client) {
+                    Ok(media_item) => media_items.push(media_item),
+                    Err(e) => tracing::warn!("Failed to convert trending scene {}: {}", scene.name, e),
+                }
+            }
+            
+            // 缓存结果
+            self.cache.set_trending("tv", page, media_items.clone());
+            tracing::debug!("Cached trending TV shows (page {})", page);
+            
+            Ok(media_items)
+        } else {
+            Err(anyhow::anyhow!("TMDB API key not configured"))
+        }
+    }
+    
+    /// 按条件发现电影或电视剧（带缓存），media_type 为 movie 或 tv
+    pub async fn discover(&self, media_type: &str, params: &TmdbDiscoverParams) -> Result<Vec<MediaItem>> {
+        let cache_key = params.cache_key(media_type);
+        
+        // 检查缓存
+        if let Some(cached_results) = self.cache.get_discover(media_type, &cache_key) {
+            tracing::debug!("Cache hit for discover {}: {}", media_type, cache_key);
+            return Ok(cached_results);
+        }
+        
+        let Some(ref client) = self.tmdb_client else {
+            return Err(anyhow::anyhow!("TMDB API key not configured"));
+        };
+        let mut media_items = Vec::new();
+        match media_type {
+            "movie" => {
+                for movie in client.discover_movies(params).await?.results {
+                    match TmdbConverter::movie_to_media_item(&movie, client) {
+                        Ok(media_item) => media_items.push(media_item),
+                        Err(e) => tracing::warn!("Failed to convert discovered movie {}: {}", movie.title, e),
+                    }
+                }
+            }
+            "tv" => {
+                for scene in client.discover_tv_shows(params).await?.results {
+                    match TmdbConverter::scene_to_media_item(&scene, client) {
+                        Ok(media_item) => media_items.push(media_item),
+                        Err(e) => tracing::warn!("Failed to convert discovered scene {}: {}", scene.name, e),
+                    }
+                }
+            }
+            _ => return Err(anyhow::anyhow!("Unsupported media type: {}", media_type)),
+        }
+        
+        // 缓存结果
+        self.cache.set_discover(media_type, &cache_key, media_items.clone());
+        Ok(media_items)
+    }
+    
+    /// 获取热门或趋势列表，list 为 popular 或 trending，media_type 为 movie 或 tv
+    pub async fn get_list(&self, list: &str, media_type: &str, page: u32) -> Result<Vec<MediaItem>> {
+        match (list, media_type) {
+            ("popular", "movie") => self.get_popular_movies(Some(page)).await,
+            ("popular", "tv") => self.get_popular_tv_shows(Some(page)).await,
+            ("trending", "movie") => self.get_trending_movies(Some(page)).await,
+            ("trending", "tv") => self.get_trending_tv_shows(Some(page)).await,
+            _ => Err(anyhow::anyhow!("Unsupported TMDB list: {} {}", list, media_type)),
+        }
+    }
+    
     /// 检查TMDB客户端是否可用
     pub fn is_tmdb_available(&self) -> bool {
         self.tmdb_client.is_some()
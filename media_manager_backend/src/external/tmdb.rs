@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, anyhow};
@@ -21,6 +22,12 @@ impl TmdbClient {
         }
     }
     
+    /// 使用自定义 API 地址（镜像或反向代理）
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+    
     /// 搜索电影
     pub async fn search_movies(&self, query: &str, page: Option<u32>) -> Result<TmdbSearchResponse> {
         let url = format!("{}/search/movie", self.base_url);
@@ -159,6 +166,139 @@ impl TmdbClient {
         Ok(result)
     }
     
+    /// 获取本周趋势电影
+    pub async fn get_trending_movies(&self, page: Option<u32>) -> Result<TmdbSearchResponse> {
+        let url = format!("{}/trending/movie/week", self.base_url);
+        let page = page.unwrap_or(1);
+        
+        let response = self.client
+            .get(&url)
+            .query(&[
+                ("api_key", &self.api_key),
+                ("page", &page.to_string()),
+                ("language", &"zh-CN".to_string()),
+            ])
+            .send()
+            .await?;
+            
+        if !response.status().is_success() {
+            return Err(anyhow!("TMDB API error: {}", response.status()));
+        }
+        
+        let result: TmdbSearchResponse = response.json().await?;
+        Ok(result)
+    }
+    
+    /// 获取本周趋势电视剧
+    pub async fn get_trending_tv_shows(&self, page: Option<u32>) -> Result<TmdbTvSearchResponse> {
+        let url = format!("{}/trending/tv/week", self.base_url);
+        let page = page.unwrap_or(1);
+        
+        let response = self.client
+            .get(&url)
+            .query(&[
+                ("api_key", &self.api_key),
+                ("page", &page.to_string()),
+                ("language", &"zh-CN".to_string()),
+            ])
+            .send()
+            .await?;
+            
+        if !response.status().is_success() {
+            return Err(anyhow!("TMDB API error: {}", response.status()));
+        }
+        
+        let result: TmdbTvSearchResponse = response.json().await?;
+        Ok(result)
+    }
+    
+    /// 按条件发现电影
+    pub async fn discover_movies(&self, params: &TmdbDiscoverParams) -> Result<TmdbSearchResponse> {
+        let response = self.discover("movie", params).await?;
+        let result: TmdbSearchResponse = response.json().await?;
+        Ok(result)
+    }
+    
+    /// 按条件发现电视剧
+    pub async fn discover_tv_shows(&self, params: &TmdbDiscoverParams) -> Result<TmdbTvSearchResponse> {
+        let response = self.discover("tv", params).await?;
+        let result: TmdbTvSearchResponse = response.json().await?;
+        Ok(result)
+    }
+    
+    async fn discover(&self, media_type: &str, params: &TmdbDiscoverParams) -> Result<reqwest::Response> {
+        let url = format!("{}/discover/{}", self.base_url, media_type);
+        
+        let response = self.client
+            .get(&url)
+            .query(&[("api_key", self.api_key.as_str()), ("language", "zh-CN")])
+            .query(&params.query_pairs(media_type))
+            .send()
+            .await?;
+            
+        if !response.status().is_success() {
+            return Err(anyhow!("TMDB API error: {}", response.status()));
+        }
+        Ok(response)
+    }
+    
+    /// 获取电影/电视剧的视频（预告片、花絮等），media_type 为 movie 或 tv
+    pub async fn get_videos(&self, media_type: &str, id: u32) -> Result<TmdbVideosResponse> {
+        let url = format!("{}/{}/{}/videos", self.base_url, media_type, id);
+        
+        let response = self.client
+            .get(&url)
+            .query(&[
+                ("api_key", &self.api_key),
+                ("include_video_language", &"zh,en,ja,null".to_string()),
+            ])
+            .send()
+            .await?;
+            
+        if !response.status().is_success() {
+            return Err(anyhow!("TMDB API error: {}", response.status()));
+        }
+        
+        let result: TmdbVideosResponse = response.json().await?;
+        Ok(result)
+    }
+    
+    /// 获取电影/电视剧的观看渠道（按地区分组，数据来自 JustWatch）
+    pub async fn get_watch_providers(&self, media_type: &str, id: u32) -> Result<TmdbWatchProvidersResponse> {
+        let url = format!("{}/{}/{}/watch/providers", self.base_url, media_type, id);
+        
+        let response = self.client
+            .get(&url)
+            .query(&[("api_key", &self.api_key)])
+            .send()
+            .await?;
+            
+        if !response.status().is_success() {
+            return Err(anyhow!("TMDB API error: {}", response.status()));
+        }
+        
+        let result: TmdbWatchProvidersResponse = response.json().await?;
+        Ok(result)
+    }
+    
+    /// 获取电影/电视剧的全部翻译（标题和简介）
+    pub async fn get_translations(&self, media_type: &str, id: u32) -> Result<TmdbTranslationsResponse> {
+        let url = format!("{}/{}/{}/translations", self.base_url, media_type, id);
+        
+        let response = self.client
+            .get(&url)
+            .query(&[("api_key", &self.api_key)])
+            .send()
+            .await?;
+            
+        if !response.status().is_success() {
+            return Err(anyhow!("TMDB API error: {}", response.status()));
+        }
+        
+        let result: TmdbTranslationsResponse = response.json().await?;
+        Ok(result)
+    }
+    
     /// 构建图片URL
     pub fn build_image_url(&self, path: &str, size: ImageSize) -> String {
         let size_str = match size {
@@ -187,6 +327,221 @@ pub enum ImageSize {
     Original,
 }
 
+/// TMDB视频列表响应
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TmdbVideosResponse {
+    pub results: Vec<TmdbVideo>,
+}
+
+/// TMDB视频（site 为 YouTube / Vimeo，key 为站内视频ID）
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbVideo {
+    pub key: String,
+    pub name: String,
+    pub site: String,
+    #[serde(rename = "type")]
+    pub video_type: String,
+    #[serde(default)]
+    pub official: bool,
+    #[serde(default)]
+    pub iso_639_1: Option<String>,
+    #[serde(default)]
+    pub published_at: Option<String>,
+}
+
+impl TmdbVideo {
+    /// 视频页面地址
+    pub fn url(&self) -> Option<String> {
+        match self.site.as_str() {
+            "YouTube" => Some(format!("https://www.youtube.com/watch?v={}", self.key)),
+            "Vimeo" => Some(format!("https://vimeo.com/{}", self.key)),
+            _ => None,
+        }
+    }
+    
+    /// 选择最合适的预告片：Trailer 优先于 Teaser，官方优先，同级取最新发布
+    pub fn pick_trailer(videos: &[TmdbVideo]) -> Option<&TmdbVideo> {
+        let rank = |v: &TmdbVideo| match v.video_type.as_str() {
+            "Trailer" => 2,
+            "Teaser" => 1,
+            _ => 0,
+        };
+        videos.iter()
+            .filter(|v| rank(v) > 0 && v.url().is_some())
+            .max_by(|a, b| {
+                (rank(a), a.official, &a.published_at).cmp(&(rank(b), b.official, &b.published_at))
+            })
+    }
+}
+
+/// TMDB翻译列表响应
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TmdbTranslationsResponse {
+    #[serde(default)]
+    pub translations: Vec<TmdbTranslation>,
+}
+
+/// TMDB翻译（iso_639_1 为语言，iso_3166_1 为地区）
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbTranslation {
+    pub iso_639_1: String,
+    #[serde(default)]
+    pub iso_3166_1: Option<String>,
+    #[serde(default)]
+    pub data: TmdbTranslationData,
+}
+
+/// 翻译内容（电影为 title，电视剧为 name）
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TmdbTranslationData {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub overview: Option<String>,
+}
+
+impl TmdbTranslation {
+    /// 语言标签，如 zh-CN、en-US
+    pub fn language(&self) -> String {
+        match self.iso_3166_1.as_deref().filter(|r| !r.is_empty()) {
+            Some(region) => format!("{}-{}", self.iso_639_1, region),
+            None => self.iso_639_1.clone(),
+        }
+    }
+    
+    pub fn title(&self) -> Option<&str> {
+        self.data.title.as_deref().or(self.data.name.as_deref())
+    }
+}
+
+/// TMDB观看渠道响应（results 以 ISO 3166-1 地区代码为键）
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TmdbWatchProvidersResponse {
+    #[serde(default)]
+    pub results: HashMap<String, TmdbRegionProviders>,
+}
+
+impl TmdbWatchProvidersResponse {
+    /// 获取指定地区的观看渠道（地区代码不区分大小写）
+    pub fn region(&self, region: &str) -> Option<&TmdbRegionProviders> {
+        self.results.get(&region.to_uppercase())
+    }
+}
+
+/// 某个地区的观看渠道：订阅 / 免费 / 含广告 / 租赁 / 购买
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TmdbRegionProviders {
+    /// TMDB 上该地区的观看页面
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default)]
+    pub flatrate: Vec<TmdbWatchProvider>,
+    #[serde(default)]
+    pub free: Vec<TmdbWatchProvider>,
+    #[serde(default)]
+    pub ads: Vec<TmdbWatchProvider>,
+    #[serde(default)]
+    pub rent: Vec<TmdbWatchProvider>,
+    #[serde(default)]
+    pub buy: Vec<TmdbWatchProvider>,
+}
+
+impl TmdbRegionProviders {
+    /// 是否可以在流媒体上直接观看（订阅、免费或含广告）
+    pub fn is_streamable(&self) -> bool {
+        !self.flatrate.is_empty() || !self.free.is_empty() || !self.ads.is_empty()
+    }
+}
+
+/// 观看渠道（流媒体服务）
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmdbWatchProvider {
+    pub provider_id: u32,
+    pub provider_name: String,
+    #[serde(default)]
+    pub logo_path: Option<String>,
+    #[serde(default)]
+    pub display_priority: Option<i32>,
+}
+
+/// 支持的发现排序字段（release_date 按类型映射为上映日期或首播日期）
+pub const TMDB_DISCOVER_SORT_FIELDS: [&str; 4] = ["popularity", "vote_average", "vote_count", "release_date"];
+
+/// TMDB 发现（discover）筛选条件
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TmdbDiscoverParams {
+    /// 类型ID，多个之间为“且”的关系
+    pub genre_ids: Vec<u32>,
+    pub year_from: Option<i32>,
+    pub year_to: Option<i32>,
+    pub min_vote_average: Option<f32>,
+    pub min_vote_count: Option<u32>,
+    /// 排序，如 popularity.desc、release_date.asc
+    pub sort_by: Option<String>,
+    pub page: u32,
+}
+
+impl TmdbDiscoverParams {
+    /// 校验年份范围、评分和排序字段
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if let (Some(from), Some(to)) = (self.year_from, self.year_to) {
+            if from > to {
+                return Err("year_from must not be after year_to".to_string());
+            }
+        }
+        if self.min_vote_average.is_some_and(|v| !(0.0..=10.0).contains(&v)) {
+            return Err("vote_average_gte must be between 0 and 10".to_string());
+        }
+        if let Some(sort_by) = &self.sort_by {
+            let valid = sort_by.rsplit_once('.')
+                .is_some_and(|(field, order)| TMDB_DISCOVER_SORT_FIELDS.contains(&field) && matches!(order, "asc" | "desc"));
+            if !valid {
+                return Err(format!(
+                    "Invalid sort_by '{}', expected <field>.asc or <field>.desc with field one of: {}",
+                    sort_by, TMDB_DISCOVER_SORT_FIELDS.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 转换为 TMDB discover 接口的查询参数，media_type 为 movie 或 tv
+    pub fn query_pairs(&self, media_type: &str) -> Vec<(String, String)> {
+        let date_field = if media_type == "tv" { "first_air_date" } else { "primary_release_date" };
+        let mut pairs = vec![("page".to_string(), self.page.max(1).to_string())];
+        if !self.genre_ids.is_empty() {
+            let genres: Vec<String> = self.genre_ids.iter().map(|id| id.to_string()).collect();
+            pairs.push(("with_genres".to_string(), genres.join(",")));
+        }
+        if let Some(from) = self.year_from {
+            pairs.push((format!("{}.gte", date_field), format!("{}-01-01", from)));
+        }
+        if let Some(to) = self.year_to {
+            pairs.push((format!("{}.lte", date_field), format!("{}-12-31", to)));
+        }
+        if let Some(vote) = self.min_vote_average {
+            pairs.push(("vote_average.gte".to_string(), vote.to_string()));
+        }
+        if let Some(count) = self.min_vote_count {
+            pairs.push(("vote_count.gte".to_string(), count.to_string()));
+        }
+        if let Some(sort_by) = &self.sort_by {
+            pairs.push(("sort_by".to_string(), sort_by.replace("release_date", date_field)));
+        }
+        pairs
+    }
+
+    /// 缓存键（同一组筛选条件命中同一缓存）
+    pub fn cache_key(&self, media_type: &str) -> String {
+        self.query_pairs(media_type).iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
 /// TMDB搜索响应
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TmdbSearchResponse {
@@ -356,6 +711,7 @@ impl TmdbConverter {
             tmdb_id: Some(movie.id as i32),
             imdb_id: None,
             omdb_id: None,
+            ..Default::default()
         };
         
         let year = movie.release_date.as_ref()
@@ -387,6 +743,7 @@ impl TmdbConverter {
             tmdb_id: Some(scene.id as i32),
             imdb_id: None,
             omdb_id: None,
+            ..Default::default()
         };
         
         let year = scene.first_air_date.as_ref()
@@ -418,6 +775,7 @@ impl TmdbConverter {
             tmdb_id: Some(details.id as i32),
             imdb_id: details.imdb_id.clone(),
             omdb_id: None,
+            ..Default::default()
         };
         
         let year = details.release_date.as_ref()
@@ -488,6 +846,7 @@ impl TmdbConverter {
             tmdb_id: Some(details.id as i32),
             imdb_id: None,
             omdb_id: None,
+            ..Default::default()
         };
         
         let year = details.first_air_date.as_ref()
@@ -548,4 +907,88 @@ impl TmdbConverter {
         
         Ok(media)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video(key: &str, site: &str, video_type: &str, official: bool, published_at: &str) -> TmdbVideo {
+        TmdbVideo {
+            key: key.to_string(),
+            name: key.to_string(),
+            site: site.to_string(),
+            video_type: video_type.to_string(),
+            official,
+            iso_639_1: None,
+            published_at: Some(published_at.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_pick_trailer() {
+        let videos = vec![
+            video("clip", "YouTube", "Clip", true, "2024-03-01"),
+            video("teaser", "YouTube", "Teaser", true, "2024-02-01"),
+            video("fan", "YouTube", "Trailer", false, "2024-04-01"),
+            video("old", "YouTube", "Trailer", true, "2024-01-01"),
+            video("new", "YouTube", "Trailer", true, "2024-01-15"),
+            video("other", "Dailymotion", "Trailer", true, "2024-05-01"),
+        ];
+        let trailer = TmdbVideo::pick_trailer(&videos).unwrap();
+        assert_eq!(trailer.key, "new");
+        assert_eq!(trailer.url().unwrap(), "https://www.youtube.com/watch?v=new");
+
+        assert_eq!(TmdbVideo::pick_trailer(&videos[..2]).unwrap().key, "teaser");
+        assert!(TmdbVideo::pick_trailer(&videos[..1]).is_none());
+    }
+
+    #[test]
+    fn test_watch_providers_region() {
+        let response: TmdbWatchProvidersResponse = serde_json::from_value(serde_json::json!({
+            "id": 550,
+            "results": {
+                "US": {
+                    "link": "https://www.themoviedb.org/movie/550/watch?locale=US",
+                    "flatrate": [{"provider_id": 8, "provider_name": "Netflix", "logo_path": "/n.jpg", "display_priority": 1}],
+                    "rent": [{"provider_id": 2, "provider_name": "Apple TV"}]
+                },
+                "JP": {"buy": [{"provider_id": 2, "provider_name": "Apple TV"}]}
+            }
+        })).unwrap();
+
+        let us = response.region("us").unwrap();
+        assert!(us.is_streamable());
+        assert_eq!(us.flatrate[0].provider_name, "Netflix");
+        assert_eq!(us.rent.len(), 1);
+        assert!(!response.region("JP").unwrap().is_streamable());
+        assert!(response.region("CN").is_none());
+    }
+
+    #[test]
+    fn test_discover_query_pairs() {
+        let params = TmdbDiscoverParams {
+            genre_ids: vec![28, 12],
+            year_from: Some(2000),
+            year_to: Some(2009),
+            min_vote_average: Some(7.5),
+            sort_by: Some("release_date.desc".to_string()),
+            page: 2,
+            ..Default::default()
+        };
+        assert!(params.validate().is_ok());
+        let pairs = params.query_pairs("tv");
+        let get = |key: &str| pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert_eq!(get("with_genres"), Some("28,12"));
+        assert_eq!(get("first_air_date.gte"), Some("2000-01-01"));
+        assert_eq!(get("first_air_date.lte"), Some("2009-12-31"));
+        assert_eq!(get("sort_by"), Some("first_air_date.desc"));
+        assert_eq!(get("page"), Some("2"));
+        assert_eq!(params.query_pairs("movie").iter().find(|(k, _)| k == "sort_by").unwrap().1, "primary_release_date.desc");
+        assert_ne!(params.cache_key("movie"), params.cache_key("tv"));
+
+        assert!(TmdbDiscoverParams { sort_by: Some("title.desc".to_string()), ..Default::default() }.validate().is_err());
+        assert!(TmdbDiscoverParams { year_from: Some(2010), year_to: Some(2000), ..Default::default() }.validate().is_err());
+        assert!(TmdbDiscoverParams { min_vote_average: Some(11.0), ..Default::default() }.validate().is_err());
+    }
+}
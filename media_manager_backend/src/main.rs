@@ -19,6 +19,7 @@ mod external;
 mod models;
 mod services;
 mod plugins;
+mod server;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -31,6 +32,15 @@ async fn main() -> anyhow::Result<()> {
     // Initialize database
     let database = database::Database::new().await?;
     
+    // Load ffmpeg job limits (shared by thumbnails and file probing)
+    if let Err(e) = services::ffmpeg::load_settings(database.pool()).await {
+        tracing::warn!("Failed to load ffmpeg settings, using defaults: {}", e);
+    }
+    tokio::spawn(services::hwaccel::init());
+    if let Err(e) = services::stream_sessions::load_settings(database.pool()).await {
+        tracing::warn!("Failed to load streaming settings, using defaults: {}", e);
+    }
+    
     // Initialize database service
     let db_service = services::DatabaseService::new(database.repository().clone());
     
@@ -94,49 +104,198 @@ async fn main() -> anyhow::Result<()> {
     );
     tokio::spawn(cache_cleanup_task.start());
     
+    // Start TMDB popular/trending prefetch task
+    let tmdb_list_task = services::TmdbListPrefetchTask::new(
+        database.pool().clone(),
+        external_client.clone(),
+        Duration::from_secs(60 * 60), // 每小时检查是否到预取时间
+    );
+    tokio::spawn(tmdb_list_task.start());
+    
+    // Start release calendar refresh task
+    let release_calendar_task = services::ReleaseCalendarTask::new(
+        database.pool().clone(),
+        plugin_manager.clone(),
+        Duration::from_secs(6 * 60 * 60), // 每6小时刷新一次
+    );
+    tokio::spawn(release_calendar_task.start());
+    
+    // Start wanted list magnet monitor task
+    let wanted_monitor_task = services::WantedMonitorTask::new(
+        database.pool().clone(),
+        plugin_manager.clone(),
+        services::TorrentClient::from_env(),
+        Duration::from_secs(60 * 60), // 每小时检查一次
+    );
+    tokio::spawn(wanted_monitor_task.start());
+    
+    // Start download import task (only when a torrent client or aria2 is configured)
+    let torrent_client = services::TorrentClient::from_env();
+    let aria2_client = external::Aria2Client::from_env();
+    if torrent_client.is_some() || aria2_client.is_some() {
+        let download_import_task = services::DownloadImportTask::new(
+            database.pool().clone(),
+            torrent_client,
+            aria2_client,
+            Duration::from_secs(2 * 60), // 每2分钟检查一次
+        );
+        tokio::spawn(download_import_task.start());
+    }
+    
+    // Start peer sync task
+    let peer_sync_task = services::PeerSyncTask::new(
+        database.pool().clone(),
+        Duration::from_secs(5 * 60), // 每5分钟与对端同步一次
+    );
+    tokio::spawn(peer_sync_task.start());
+    
+    // Start broken link check task
+    let link_check_task = services::LinkCheckTask::new(
+        database.pool().clone(),
+        Duration::from_secs(24 * 60 * 60), // 每天检查一次
+    );
+    tokio::spawn(link_check_task.start());
+    
+    // Start magnet swarm stats refresh task (interval and concurrency come from settings)
+    let swarm_stats_task = services::SwarmStatsTask::new(
+        database.pool().clone(),
+        Duration::from_secs(5 * 60), // 每5分钟检查是否到刷新时间
+    );
+    tokio::spawn(swarm_stats_task.start());
+    
+    // Start library root health check task
+    let root_health_task = services::RootHealthTask::new(
+        database.pool().clone(),
+        Duration::from_secs(5 * 60), // 每5分钟检查一次
+    );
+    tokio::spawn(root_health_task.start());
+    
+    // Start library auto scan task
+    let library_scan_task = services::LibraryScanTask::new(
+        database.pool().clone(),
+        Duration::from_secs(30 * 60), // 每30分钟扫描一次
+    );
+    tokio::spawn(library_scan_task.start());
+    
+    // Start orphan cleanup task
+    let orphan_cleanup_task = services::OrphanCleanupTask::new(
+        database.pool().clone(),
+        cache_service.clone(),
+        Duration::from_secs(24 * 60 * 60), // 每天清理一次
+    );
+    tokio::spawn(orphan_cleanup_task.start());
+    
+    // Start duplicate actor scan task
+    let actor_dedup_task = services::ActorDedupTask::new(
+        database.pool().clone(),
+        Duration::from_secs(24 * 60 * 60), // 每天扫描一次
+    );
+    tokio::spawn(actor_dedup_task.start());
+    
+    // Start series detection task
+    let series_detect_task = services::SeriesDetectTask::new(
+        database.pool().clone(),
+        Duration::from_secs(24 * 60 * 60), // 每天检测一次
+    );
+    tokio::spawn(series_detect_task.start());
+    
+    // Start database optimize task (only when DB_OPTIMIZE_INTERVAL_HOURS is set)
+    if let Some(interval) = services::maintenance::optimize_interval_from_env() {
+        tokio::spawn(services::MaintenanceTask::new(database.pool().clone(), interval).start());
+    }
+    
+    let app_state = api::AppState {
+        database: database.clone(),
+        db_service: std::sync::Arc::new(db_service),
+        external_client,
+        plugin_manager: plugin_manager.clone(),
+        cache_service: cache_service.clone(),
+    };
+    
+    // Start background enrichment task for newly created media
+    let enrichment_task = services::EnrichmentTask::new(
+        app_state.clone(),
+        Duration::from_secs(60), // 队列为空时每分钟检查一次
+    );
+    tokio::spawn(enrichment_task.start());
+    
+    // Start offline package transcoding task
+    let offline_package_task = services::OfflinePackageTask::new(
+        database.clone(),
+        Duration::from_secs(60 * 60), // 队列为空时每小时检查一次（同时清理过期的包）
+    );
+    tokio::spawn(offline_package_task.start());
+    
     // Initialize sync trigger state
     let sync_trigger_state = Arc::new(api::sync::SyncTriggerState::new());
     
+    // 创建/批量接口支持 Idempotency-Key
+    let idempotent = axum::middleware::from_fn_with_state(database.clone(), api::idempotency::idempotent);
+    
     // Build our application with routes
     let app = Router::new()
         .route("/", get(|| async { "Media Manager Backend API v1.0" }))
         // Health and stats
         .route("/api/health", get(api::health::health_check))
         .route("/api/stats", get(api::health::get_stats))
+        .route("/api/errors", get(api::error::get_error_catalog))
         .route("/api/cache/cleanup", post(api::health::cleanup_cache))
         .route("/api/cache/clear", post(api::health::clear_cache))
         // Media management
         .route("/api/media", get(api::media::get_media_list))
         .route("/api/media/filters", get(api::media::get_filter_options))
+        .route("/api/media/duplicate-artwork", get(api::media::get_duplicate_artwork))
         .route("/api/media/:id", get(api::media::get_media_detail))
-        .route("/api/media", post(api::media::create_media))
+        .route("/api/media/:id/full", get(api::media::get_media_full))
+        .route("/api/media", post(api::media::create_media).layer(idempotent.clone()))
+        .route("/api/media/upsert", post(api::media::upsert_media).layer(idempotent.clone()))
         .route("/api/media/:id", axum::routing::put(api::media::update_media))
         .route("/api/media/:id", axum::routing::delete(api::media::delete_media))
         // Collections
         .route("/api/collections", get(api::collections::get_collections))
-        .route("/api/collections", post(api::collections::add_to_collection))
+        .route("/api/collections", post(api::collections::add_to_collection).layer(idempotent.clone()))
+        .route("/api/collections/stats", get(api::collections::get_collection_stats))
+        .route("/api/collections/history", get(api::collections::get_status_history))
+        .route("/api/collections/:media_id/history", get(api::collections::get_collection_status_history))
         .route("/api/collections/:media_id", axum::routing::delete(api::collections::remove_from_collection))
         .route("/api/collections/:media_id/status", axum::routing::put(api::collections::update_collection_status))
         // TMDB integration
         .route("/api/tmdb/details", get(api::media::get_tmdb_details))
         .route("/api/tmdb/popular", get(api::media::get_popular_content))
-        .route("/api/tmdb/save", post(api::media::save_tmdb_media))
+        .route("/api/tmdb/trending", get(api::media::get_trending_content))
+        .route("/api/tmdb/discover", get(api::media::discover_tmdb_content))
+        .route("/api/tmdb/lists/prefetch", post(api::media::prefetch_tmdb_lists))
+        .route("/api/tmdb/save", post(api::media::save_tmdb_media).layer(idempotent.clone()))
         // Batch operations
-        .route("/api/batch/import", post(api::media::batch_import_media))
-        .route("/api/batch/collection", post(api::media::batch_collection_operation))
+        .route("/api/batch/import", post(api::media::batch_import_media).layer(idempotent.clone()))
+        .route("/api/batch/collection", post(api::media::batch_collection_operation).layer(idempotent.clone()))
         .route("/api/batch/delete", post(api::media::batch_delete_media))
-        .route("/api/batch/edit", post(api::media::batch_edit_media))
+        .route("/api/batch/edit", post(api::media::batch_edit_media).layer(idempotent.clone()))
+        .route("/api/batch/refresh-artwork", post(api::media::batch_refresh_artwork))
+        .route("/api/batch/tag-by-filter", post(api::media::batch_tag_by_filter).layer(idempotent.clone()))
         // Data export/import
         .route("/api/data/export", get(api::media::export_all_data))
-        .route("/api/data/import", post(api::media::import_data))
+        .route("/api/data/import", post(api::media::import_data).layer(idempotent.clone()))
+        .route("/api/data/import/list", post(api::media::import_list).layer(idempotent.clone()))
+        .route("/api/data/import/validate", post(api::media::validate_import_data))
+        .route("/api/data/import/session", post(api::import_session::create_import_session))
+        .route("/api/data/import/session/:id", get(api::import_session::get_import_session)
+            .delete(api::import_session::delete_import_session))
+        .route("/api/data/import/session/:id/chunks/:index", axum::routing::put(api::import_session::upload_import_chunk)
+            .layer(axum::extract::DefaultBodyLimit::max(api::import_session::MAX_IMPORT_CHUNK_BYTES)))
+        .route("/api/data/import/session/:id/commit", post(api::import_session::commit_import_session))
         // Search
         .route("/api/search", get(api::search::search_media))
         .route("/api/search/advanced", post(api::search::advanced_search))
         .route("/api/search/suggestions", get(api::search::get_search_suggestions))
+        .route("/api/search/history", axum::routing::delete(api::search::clear_search_history))
         .route("/api/search/trending", get(api::search::get_trending_searches))
         // Actors
         .route("/api/actors", get(api::actors::list_actors_handler))
-        .route("/api/actors", post(api::actors::create_actor_handler))
+        .route("/api/actors", post(api::actors::create_actor_handler).layer(idempotent.clone()))
+        .route("/api/actors/import-from-plugin", post(api::actors::import_actors_from_plugin_handler))
+        .route("/api/actors/duplicate-suggestions", get(api::actors::get_duplicate_actor_suggestions_handler))
+        .route("/api/actors/duplicate-suggestions/merge", post(api::actors::merge_duplicate_actors_handler))
         .route("/api/actors/:id", get(api::actors::get_actor_handler))
         .route("/api/actors/:id", axum::routing::put(api::actors::update_actor_handler))
         .route("/api/actors/:id", axum::routing::delete(api::actors::delete_actor_handler))
@@ -144,35 +303,138 @@ async fn main() -> anyhow::Result<()> {
         .route("/api/media/:id/actors", get(api::actors::get_media_actors_handler))
         .route("/api/media/:id/actors", post(api::actors::add_actor_to_media_handler))
         .route("/api/media/:media_id/actors/:actor_id", axum::routing::delete(api::actors::remove_actor_from_media_handler))
+        .route("/api/media/:id/previews/actors", get(api::actors::get_preview_actors_handler))
+        .route("/api/media/:id/previews/:index/actors", post(api::actors::tag_preview_actors_handler))
+        .route("/api/media/:id/previews/:index/actors/:actor_id", axum::routing::delete(api::actors::untag_preview_actor_handler))
+        .route("/api/actors/:id/scenes", get(api::actors::get_actor_scenes_handler))
         // Studios
         .route("/api/studios", get(api::studios::list_studios_handler))
-        .route("/api/studios", post(api::studios::create_studio_handler))
+        .route("/api/studios", post(api::studios::create_studio_handler).layer(idempotent.clone()))
         .route("/api/studios/search", get(api::studios::search_studios_handler))
         .route("/api/studios/:id", get(api::studios::get_studio_handler))
+        .route("/api/studios/:id/overview", get(api::studios::get_studio_overview_handler))
+        .route("/api/studios/:id/reassign", post(api::studios::reassign_studio_handler))
         .route("/api/studios/:id", axum::routing::put(api::studios::update_studio_handler))
         .route("/api/studios/:id", axum::routing::delete(api::studios::delete_studio_handler))
         // Series
         .route("/api/series", get(api::studios::list_series_handler))
-        .route("/api/series", post(api::studios::create_series_handler))
+        .route("/api/series", post(api::studios::create_series_handler).layer(idempotent.clone()))
         .route("/api/series/search", get(api::studios::search_series_handler))
+        .route("/api/series/suggestions", get(api::studios::get_series_suggestions_handler))
+        .route("/api/series/suggestions/apply", post(api::studios::apply_series_suggestions_handler))
         .route("/api/series/:id", get(api::studios::get_series_handler))
         .route("/api/series/:id", axum::routing::put(api::studios::update_series_handler))
         .route("/api/series/:id", axum::routing::delete(api::studios::delete_series_handler))
+        .route("/api/series/:id/gaps", get(api::studios::get_series_gaps_handler))
         .route("/api/studios-series/sync-counts", post(api::studios::sync_counts_handler))
+        .route("/api/studios-series/link-media", post(api::studios::link_media_handler))
+        // Favorite actors/studios/series
+        .route("/api/favorites", get(api::favorites::list_favorites_handler))
+        .route("/api/favorites/:entity_type/:id", axum::routing::put(api::favorites::add_favorite_handler))
+        .route("/api/favorites/:entity_type/:id", axum::routing::delete(api::favorites::remove_favorite_handler))
+        .route("/api/feed/favorites", get(api::favorites::favorite_feed_handler))
+        // Browsing history
+        .route("/api/history/browsing", get(api::history::list_browsing_history_handler))
+        .route("/api/history/browsing", axum::routing::delete(api::history::clear_browsing_history_handler))
+        .route("/api/history/browsing/:media_id", axum::routing::delete(api::history::remove_browsing_history_handler))
+        // Follows & release calendar
+        .route("/api/follows", get(api::calendar::list_follows_handler))
+        .route("/api/follows", post(api::calendar::create_follow_handler))
+        .route("/api/follows/:id", axum::routing::delete(api::calendar::delete_follow_handler))
+        .route("/api/calendar", get(api::calendar::get_calendar_handler))
+        .route("/api/calendar/refresh", post(api::calendar::refresh_calendar_handler))
+        // Wanted list
+        .route("/api/wanted", get(api::wanted::list_wanted_handler))
+        .route("/api/wanted", post(api::wanted::add_wanted_handler).layer(idempotent.clone()))
+        .route("/api/wanted/check", post(api::wanted::check_wanted_handler))
+        .route("/api/library/check-links", post(api::library::check_links_handler))
+        .route("/api/library/broken-links", get(api::library::list_broken_links_handler))
+        .route("/api/library/roots", get(api::library::list_library_roots_handler))
+        .route("/api/library/roots", post(api::library::create_library_root_handler).layer(idempotent.clone()))
+        .route("/api/library/roots/:id", axum::routing::put(api::library::update_library_root_handler))
+        .route("/api/library/roots/:id", axum::routing::delete(api::library::delete_library_root_handler))
+        .route("/api/hooks", get(api::hooks::list_hooks_handler))
+        .route("/api/hooks", post(api::hooks::create_hook_handler).layer(idempotent.clone()))
+        .route("/api/hooks/events", get(api::hooks::list_hook_events_handler))
+        .route("/api/hooks/:id", axum::routing::put(api::hooks::update_hook_handler))
+        .route("/api/hooks/:id", axum::routing::delete(api::hooks::delete_hook_handler))
+        .route("/api/hooks/:id/test", post(api::hooks::test_hook_handler))
+        .route("/api/fs/browse", get(api::library::browse_directories_handler))
+        .route("/api/enrichment/jobs", get(api::enrichment::list_enrichment_jobs_handler))
+        .route("/api/settings/enrichment", get(api::enrichment::get_enrichment_settings_handler))
+        .route("/api/settings/enrichment", axum::routing::put(api::enrichment::update_enrichment_settings_handler))
+        .route("/api/media/:id/enrichment", get(api::enrichment::get_media_enrichment_handler))
+        .route("/api/media/:id/enrich", post(api::enrichment::enqueue_enrichment_handler))
+        .route("/api/wanted/:media_id", axum::routing::put(api::wanted::update_wanted_handler))
+        .route("/api/wanted/:media_id", axum::routing::delete(api::wanted::remove_wanted_handler))
+        .route("/api/wanted/:media_id/push", post(api::wanted::push_wanted_handler))
+        .route("/api/downloads", get(api::wanted::list_downloads_handler))
+        .route("/api/downloads/import", post(api::wanted::import_downloads_handler))
+        .route("/api/downloads/:hash/retry", post(api::wanted::retry_download_handler))
+        .route("/api/download/link", post(api::wanted::download_link_handler))
+        .route("/api/download/links", get(api::wanted::list_link_downloads_handler))
+        .route("/api/download/links/:gid/retry", post(api::wanted::retry_link_download_handler))
+        .route("/api/acquisition", get(api::wanted::list_acquisition_handler))
+        .route("/api/settings/magnet-trackers", get(api::wanted::get_magnet_tracker_settings_handler))
+        .route("/api/settings/magnet-trackers", axum::routing::put(api::wanted::update_magnet_tracker_settings_handler))
+        .route("/api/magnets/stats", get(api::wanted::list_swarm_stats_handler))
+        .route("/api/magnets/stats/refresh", post(api::wanted::refresh_swarm_stats_handler))
+        .route("/api/settings/swarm-stats", get(api::wanted::get_swarm_stats_settings_handler))
+        .route("/api/settings/swarm-stats", axum::routing::put(api::wanted::update_swarm_stats_settings_handler))
+        // Quality profiles & upgrades
+        .route("/api/settings/quality-profiles", get(api::quality::get_quality_profiles_handler))
+        .route("/api/settings/quality-profiles", axum::routing::put(api::quality::update_quality_profiles_handler))
+        .route("/api/library/upgrades", get(api::quality::list_upgrades_handler))
+        .route("/api/library/upgrades/:media_id", axum::routing::delete(api::quality::dismiss_upgrade_handler))
+        // Maintenance
+        .route("/api/maintenance/optimize", post(api::maintenance::optimize_handler))
+        .route("/api/maintenance/cleanup-orphans", post(api::maintenance::cleanup_orphans_handler))
+        .route("/api/maintenance/migrations", get(api::maintenance::migration_status_handler))
+        .route("/api/maintenance/thumbnails", get(api::maintenance::thumbnail_queue_handler))
+        .route("/api/maintenance/thumbnails/backfill", post(api::maintenance::backfill_thumbnails_handler))
+        .route("/api/settings/ffmpeg", get(api::maintenance::get_ffmpeg_settings_handler))
+        .route("/api/settings/ffmpeg", axum::routing::put(api::maintenance::update_ffmpeg_settings_handler))
+        // Sync change feed
+        .route("/api/sync/changes", get(api::sync::get_changes))
+        // Peer sync
+        .route("/api/sync/peers", get(api::sync::list_peers))
+        .route("/api/sync/peers", post(api::sync::create_peer))
+        .route("/api/sync/peers/:id", axum::routing::delete(api::sync::delete_peer))
+        .route("/api/sync/peers/:id/sync", post(api::sync::sync_peer))
+        .route("/api/sync/push", post(api::sync::push_changes))
         // Scrape plugins
         .route("/api/scrape/plugins", get(api::scrape::list_plugins))
         .route("/api/scrape/plugins/reload", post(api::scrape::reload_plugins))
+        .route("/api/scrape/plugins/schema-report", get(api::scrape::get_scrape_schema_report))
+        .route("/api/scrape/plugins/:id/selftest", post(api::scrape::selftest_plugin))
+        .route("/api/scrape/fields", get(api::scrape::list_scrape_fields))
+        .route("/api/scrape/url", post(api::scrape::scrape_url))
         // 统一刮削API
         .route("/api/scrape/media/:media_id", post(api::scrape::scrape_media))
         .route("/api/scrape/media/:media_id/multiple", post(api::scrape::scrape_media_multiple))
         .route("/api/scrape/media/batch", post(api::scrape::batch_scrape_media_unified))
-        .route("/api/scrape/media/batch-import", post(api::scrape::batch_import_media))
+        .route("/api/scrape/media/batch/preview", post(api::scrape::preview_batch_scrape_media))
+        .route("/api/scrape/media/batch-import", post(api::scrape::batch_import_media).layer(idempotent.clone()))
         .route("/api/scrape/actor/:actor_id", post(api::actors::scrape_actor))
         .route("/api/scrape/actor/batch", post(api::actors::batch_scrape_actor_unified))
+        .route("/api/scrape/actor/runs", get(api::actors::list_actor_scrape_runs_handler))
+        .route("/api/scrape/actor/runs/:id", get(api::actors::get_actor_scrape_run_handler))
+        .route("/api/settings/tmdb-list-cache", get(api::media::get_tmdb_list_cache_settings))
+        .route("/api/settings/tmdb-list-cache", axum::routing::put(api::media::update_tmdb_list_cache_settings))
+        .route("/api/settings/actor-photo-cache", get(api::actors::get_actor_photo_cache_settings_handler))
+        .route("/api/settings/actor-photo-cache", axum::routing::put(api::actors::update_actor_photo_cache_settings_handler))
         // 统一进度查询端点（媒体和演员刮削共用）
-        .route("/api/scrape/progress/:session_id", get(api::scrape::get_scrape_progress))
+        .route("/api/scrape/progress/:session_id", get(api::scrape::get_scrape_progress).delete(api::scrape::delete_scrape_progress))
+        .route("/api/scrape/progress/:session_id/events", get(api::scrape::scrape_progress_events))
+        // 刮削来源存档
+        .route("/api/media/:id/scrape-sources", get(api::scrape::list_scrape_sources))
+        .route("/api/media/:id/refresh", post(api::scrape::refresh_media_from_provider))
+        .route("/api/scrape-sources/:id", get(api::scrape::get_scrape_source))
+        .route("/api/settings/scrape-archive", get(api::scrape::get_scrape_archive_settings))
+        .route("/api/settings/scrape-archive", axum::routing::put(api::scrape::update_scrape_archive_settings))
         // 磁力搜索和通用刮削
         .route("/api/scrape/magnets/progress/:session_id", get(api::scrape::get_magnet_search_progress))
+        .route("/api/scrape/magnets/progress/:session_id/events", get(api::scrape::magnet_search_progress_events))
         .route("/api/scrape/magnets/:plugin_id", get(api::scrape::search_magnets))
         .route("/api/scrape/:id", get(api::scrape::scrape_auto))
         .route("/api/scrape/:plugin_id/:id", get(api::scrape::scrape_with_plugin))
@@ -184,6 +446,18 @@ async fn main() -> anyhow::Result<()> {
         // HLS proxy
         .route("/api/proxy/hls", get(api::proxy::proxy_hls))
         .route("/api/proxy/hls/segment", get(api::proxy::proxy_hls_segment))
+        .route("/api/media/:id/translations", get(api::media::get_media_translations))
+        .route("/api/media/:id/translations", axum::routing::put(api::media::save_media_translations))
+        .route("/api/media/:id/translations/fetch", post(api::media::fetch_media_translations))
+        .route("/api/media/:id/translations/:language", axum::routing::delete(api::media::delete_media_translation))
+        .route("/api/media/:id/trailer", get(api::media::get_trailer))
+        .route("/api/media/:id/availability", get(api::media::get_media_availability))
+        .route("/api/media/:id/external-ids", axum::routing::put(api::media::update_external_ids))
+        .route("/api/media/:id/trailer/fetch", post(api::media::fetch_trailer))
+        .route("/api/media/:id/trailer/play", get(api::proxy::proxy_trailer))
+        .route("/api/media/:id/preview/resolve", get(api::proxy::resolve_preview_video))
+        .route("/api/media/:id/links/:index/resolve", get(api::media::resolve_download_link))
+        .route("/api/media/:id/links/:index/play", get(api::proxy::proxy_download_link))
         // File scan
         .route("/api/scan/start", post(api::file_scan::start_scan))
         .route("/api/scan/match", post(api::file_scan::match_files))
@@ -193,23 +467,54 @@ async fn main() -> anyhow::Result<()> {
         .route("/api/scan/ignore", post(api::file_scan::ignore_file))
         .route("/api/scan/ignored", get(api::file_scan::get_ignored_files))
         .route("/api/scan/ignored/remove", post(api::file_scan::remove_ignored_file))
+        .route("/api/scan/rules", get(api::file_scan::get_filename_rules))
+        .route("/api/scan/rules", axum::routing::put(api::file_scan::update_filename_rules))
+        .route("/api/scan/rules/test", post(api::file_scan::test_filename_rules))
+        .route("/api/scan/parse-preview", post(api::file_scan::parse_preview))
+        .route("/api/scan/reparse", post(api::file_scan::reparse_unmatched))
+        .route("/api/scan/candidates", post(api::file_scan::get_match_candidates))
+        .route("/api/scan/identify", post(api::file_scan::confirm_identify))
+        .route("/api/scan/auto-confirm", get(api::file_scan::get_auto_confirm_settings))
+        .route("/api/scan/auto-confirm", axum::routing::put(api::file_scan::update_auto_confirm_settings))
+        .route("/api/scan/sessions", get(api::file_scan::list_scan_sessions))
+        .route("/api/scan/sessions", axum::routing::delete(api::file_scan::clear_scan_sessions))
+        .route("/api/scan/sessions/:id", get(api::file_scan::get_scan_session))
+        .route("/api/scan/sessions/:id", axum::routing::delete(api::file_scan::delete_scan_session))
         .route("/api/media/:id/files", get(api::file_scan::get_media_files))  // 新增：获取媒体文件列表
+        .route("/api/media/:id/files/edition", axum::routing::put(api::file_scan::set_media_files_edition))
+        .route("/api/media/:id/files/:file_id/watch", axum::routing::put(api::file_scan::set_media_file_watch))
+        .route("/api/media/:id/extras", get(api::file_scan::get_media_extras))
+        .route("/api/media/:id/extras/:extra_id/stream", get(api::streaming::stream_media_extra))
         // Streaming
         .route("/api/media/:id/thumbnail", get(api::streaming::get_media_thumbnail))
         .route("/api/media/:id/video", get(api::streaming::stream_video))
+        .route("/api/media/:id/video/transcode", get(api::streaming::stream_transcoded))
+        .route("/api/media/:id/play-decision", post(api::streaming::play_decision))
+        .route("/api/sessions", get(api::sessions::list_sessions_handler))
+        .route("/api/sessions/:id", axum::routing::delete(api::sessions::terminate_session_handler))
+        .route("/api/settings/streaming", get(api::sessions::get_streaming_settings_handler))
+        .route("/api/settings/streaming", axum::routing::put(api::sessions::update_streaming_settings_handler))
+        // Offline packages
+        .route("/api/media/:id/offline-package", post(api::offline::create_offline_package_handler))
+        .route("/api/offline-packages", get(api::offline::list_offline_packages_handler))
+        .route("/api/offline-packages/:id", get(api::offline::get_offline_package_handler))
+        .route("/api/offline-packages/:id", axum::routing::delete(api::offline::delete_offline_package_handler))
+        .route("/api/public/offline/:token", get(api::offline::public_offline_download))
+        // Share links
+        .route("/api/media/:id/share", post(api::share::create_share_handler).layer(idempotent.clone()))
+        .route("/api/media/:id/shares", get(api::share::list_shares_handler))
+        .route("/api/shares/:id", axum::routing::delete(api::share::revoke_share_handler))
+        .route("/api/public/share/:token", get(api::share::public_share_detail))
+        .route("/api/public/share/:token/video", get(api::share::public_share_video))
         // Cache management (using AppState)
         .route("/api/cache/stats", get(api::cache::get_cache_stats))
         .route("/api/media/:id/cache", axum::routing::delete(api::cache::clear_media_cache))
         .route("/api/cache/all", axum::routing::delete(api::cache::clear_all_cache))
         .route("/api/cache/orphaned", axum::routing::delete(api::cache::clear_orphaned_cache))
+        .route("/api/cache/actors/backfill", post(api::cache::backfill_actor_photos))
+        .route("/cache/*path", get(api::cache::serve_cached_file))
         .layer(CorsLayer::permissive())
-        .with_state(api::AppState {
-            database: database.clone(),
-            db_service: std::sync::Arc::new(db_service),
-            external_client,
-            plugin_manager: plugin_manager.clone(),
-            cache_service: cache_service.clone(),
-        });
+        .with_state(app_state);
     
     // Add cache config routes with separate state
     let cache_config_state = Arc::new(api::cache::CacheConfigState {
@@ -233,7 +538,13 @@ async fn main() -> anyhow::Result<()> {
         .with_state(sync_trigger_state);
     
     // Merge routes
-    let app = app.merge(cache_routes).merge(sync_routes);
+    let app = app.merge(cache_routes).merge(sync_routes)
+        .layer(axum::middleware::from_fn(api::i18n::localize_errors));
+    
+    // 响应压缩（流媒体和范围请求的响应不压缩）
+    let compression = api::compression::CompressionConfig::from_env();
+    tracing::info!("Response compression: {:?}", compression);
+    let app = app.layer(api::compression::compression_layer(&compression));
 
     // Run the server - 从环境变量读取配置，支持手机访问
     let host = std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
@@ -246,8 +557,12 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("🚀 Server listening on {}", addr);
     tracing::info!("📊 Cache cleanup task started (interval: 5 minutes)");
     
+    // 连接参数（HTTP/2、keep-alive 等）
+    let server_config = server::ServerConfig::from_env();
+    tracing::info!("Server connection settings: {:?}", server_config);
+    
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    server::serve(listener, app, &server_config).await?;
 
     Ok(())
 }
\ No newline at end of file
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use super::{ListSortOption, SortOrder};
+
 /// 演员实体
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Actor {
@@ -113,6 +115,32 @@ pub struct AddActorToMediaRequest {
     pub role: Option<String>,
 }
 
+/// 预览图中标注的演员（用于媒体详情页预览图）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PreviewActor {
+    pub preview_index: i64,
+    pub actor_id: String,
+    pub actor_name: String,
+    pub avatar_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 演员出镜画面（用于演员详情页画廊）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ActorScene {
+    pub media_id: String,
+    pub media_title: String,
+    pub preview_index: i64,
+    pub preview_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 标注预览图演员请求
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagPreviewActorsRequest {
+    pub actor_ids: Vec<String>,
+}
+
 /// 演员列表响应
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ActorListResponse {
@@ -126,10 +154,136 @@ pub struct ActorListResponse {
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ActorSearchFilters {
     pub query: Option<String>,
+    /// 最少作品数
+    pub min_media_count: Option<i64>,
+    /// 是否有头像/写真/封面
+    pub has_photo: Option<bool>,
+    /// 国籍（忽略大小写）
+    pub nationality: Option<String>,
+    /// 出演过该厂商的作品
+    pub studio: Option<String>,
+    /// 与该演员合作过
+    pub co_star_id: Option<String>,
+    pub sort_by: ListSortOption,
+    /// 未指定时按排序方式的默认顺序
+    pub sort_order: Option<SortOrder>,
     pub limit: Option<i32>,
     pub offset: Option<i32>,
 }
 
+/// 重复演员检测的候选演员（有作品关联的演员及其别名）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DuplicateActorCandidate {
+    pub id: String,
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub work_count: i64,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// 疑似重复的一组演员
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateActorGroup {
+    /// 建议保留的演员（作品最多的）
+    pub target_id: String,
+    pub actors: Vec<DuplicateActorCandidate>,
+    /// 判定原因：normalized_name、kana_romaji、name_order、edit_distance、alias
+    pub reasons: Vec<String>,
+}
+
+/// 重复演员合并建议报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateActorReport {
+    pub generated_at: DateTime<Utc>,
+    /// 检查的演员数
+    pub actors_scanned: usize,
+    pub groups: Vec<DuplicateActorGroup>,
+}
+
+/// 把 source_ids 合并到 target_id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorMergeGroup {
+    pub target_id: String,
+    pub source_ids: Vec<String>,
+}
+
+/// 批量合并重复演员请求（不传 groups 时合并当前报告中的全部建议）
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MergeDuplicateActorsRequest {
+    #[serde(default)]
+    pub groups: Option<Vec<ActorMergeGroup>>,
+}
+
+/// 批量合并结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ActorMergeResult {
+    /// 成功合并的组数
+    pub merged_groups: usize,
+    /// 被合并（删除）的演员数
+    pub merged_actors: usize,
+    pub errors: Vec<String>,
+}
+
+/// 批量演员刮削中单个演员的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorScrapeItemResult {
+    pub actor_id: String,
+    pub name: String,
+    /// completed / failed
+    pub status: String,
+    /// 实际发生变化的字段（如 avatar_url、biography）
+    pub fields_updated: Vec<String>,
+    /// 是否下载了演员图片到本地缓存（未开启演员图片缓存时为 false）
+    pub photo_downloaded: bool,
+    pub error: Option<String>,
+}
+
+/// 一次批量演员刮削的结果汇总（刮削结束后保存，供事后查看）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorScrapeRun {
+    /// 与进度会话ID相同
+    pub id: String,
+    pub mode: String,
+    pub status: String,
+    pub message: Option<String>,
+    pub total: i64,
+    pub success_count: i64,
+    pub failed_count: i64,
+    pub results: Vec<ActorScrapeItemResult>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// 可缓存的演员图片字段
+pub const ACTOR_PHOTO_FIELDS: [&str; 4] = ["avatar", "photo", "poster", "backdrop"];
+
+/// 演员图片缓存设置（保存在 user_settings 的 actor_photo_cache 键中）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ActorPhotoCacheSettings {
+    /// 保存演员时是否自动下载远程图片并转换为 WebP
+    #[serde(default)]
+    pub enabled: bool,
+    /// 要缓存的字段，为空时缓存全部字段
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+impl ActorPhotoCacheSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(field) = self.fields.iter().find(|f| !ACTOR_PHOTO_FIELDS.contains(&f.as_str())) {
+            return Err(format!("Unknown field '{}', expected one of: {}", field, ACTOR_PHOTO_FIELDS.join(", ")));
+        }
+        Ok(())
+    }
+
+    /// 字段是否需要缓存
+    pub fn caches_field(&self, field: &str) -> bool {
+        self.fields.is_empty() || self.fields.iter().any(|f| f == field)
+    }
+}
+
 impl Actor {
     pub fn new(name: String) -> Self {
         let now = Utc::now();
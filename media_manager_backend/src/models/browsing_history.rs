@@ -0,0 +1,28 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::MediaItemResponse;
+
+/// 未指定用户时使用的用户标识
+pub const DEFAULT_HISTORY_USER: &str = "default";
+
+/// 每个用户保留的最近浏览记录数
+pub const BROWSING_HISTORY_LIMIT: i64 = 200;
+
+/// 最近浏览记录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BrowsingHistoryEntry {
+    pub media_id: String,
+    pub view_count: i64,
+    pub viewed_at: DateTime<Utc>,
+}
+
+/// 带媒体信息的最近浏览记录（用于API响应）
+#[derive(Debug, Serialize)]
+pub struct BrowsingHistoryItem {
+    #[serde(flatten)]
+    pub media: MediaItemResponse,
+    pub view_count: i64,
+    pub viewed_at: DateTime<Utc>,
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 可关注的目标类型
+pub const FOLLOW_TARGET_TYPES: [&str; 3] = ["series", "studio", "actor"];
+
+/// 关注（系列/厂商/演员）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Follow {
+    pub id: String,
+    pub target_type: String,
+    pub target_name: String,
+    pub plugin_id: Option<String>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Follow {
+    pub fn new(target_type: String, target_name: String, plugin_id: Option<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            target_type,
+            target_name,
+            plugin_id,
+            last_checked_at: None,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// 发售日历条目
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReleaseCalendarEntry {
+    pub id: String,
+    pub follow_id: String,
+    pub code: Option<String>,
+    pub title: String,
+    pub release_date: String,
+    pub poster_url: Option<String>,
+    pub studio: Option<String>,
+    pub series: Option<String>,
+    pub actors: Option<String>,
+    pub plugin_id: String,
+    pub media_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// ============ Request/Response DTOs ============
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFollowRequest {
+    pub target_type: String,
+    pub target_name: String,
+    pub plugin_id: Option<String>,
+}
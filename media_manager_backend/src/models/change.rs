@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 变更流记录（changes 表原始行）
+#[derive(Debug, Clone, FromRow)]
+pub struct ChangeRecord {
+    pub seq: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub operation: String,
+    pub changed_fields: Option<String>,
+    pub data: Option<String>,
+    pub changed_at: String,
+    pub origin: Option<String>,
+}
+
+/// 变更流条目（用于API响应，JSON 字段已解析）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEntry {
+    pub seq: i64,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub operation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changed_fields: Option<Vec<String>>,
+    /// 变更后的完整数据，delete（墓碑）时为空
+    pub data: Option<serde_json::Value>,
+    pub changed_at: String,
+    /// 来源实例ID（本地变更为空，远端同步过来的变更为对端实例ID）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+}
+
+impl From<ChangeRecord> for ChangeEntry {
+    fn from(record: ChangeRecord) -> Self {
+        Self {
+            seq: record.seq,
+            entity_type: record.entity_type,
+            entity_id: record.entity_id,
+            operation: record.operation,
+            changed_fields: record.changed_fields
+                .as_deref()
+                .and_then(|s| serde_json::from_str(s).ok()),
+            data: record.data
+                .as_deref()
+                .and_then(|s| serde_json::from_str(s).ok()),
+            changed_at: record.changed_at,
+            origin: record.origin,
+        }
+    }
+}
+
+/// 变更流响应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeFeedResponse {
+    pub changes: Vec<ChangeEntry>,
+    /// 本次返回的最后一个序列号，下次请求作为 since_seq
+    pub next_seq: i64,
+    /// 当前最新的序列号
+    pub latest_seq: i64,
+    pub has_more: bool,
+    /// since_seq 之后的部分变更已被清理，客户端需要全量重新加载后从 latest_seq 继续
+    #[serde(default)]
+    pub reset_required: bool,
+    /// 本实例ID（供对端同步时识别来源）
+    pub instance_id: String,
+}
@@ -55,6 +55,70 @@ impl std::str::FromStr for WatchStatus {
     }
 }
 
+/// 某种观看状态的收藏数
+#[derive(Debug, Serialize, FromRow)]
+pub struct WatchStatusCount {
+    pub watch_status: String,
+    pub count: i64,
+}
+
+/// 某个标签下收藏的完成情况（标签包括媒体标签和收藏的用户标签）
+#[derive(Debug, Serialize)]
+pub struct TagCompletion {
+    pub tag: String,
+    pub total: i64,
+    pub completed: i64,
+    /// 已看完的百分比（0-100）
+    pub completion_percent: f64,
+}
+
+/// 收藏统计
+#[derive(Debug, Serialize)]
+pub struct CollectionStats {
+    pub total: i64,
+    pub favorites: i64,
+    /// 按观看状态（没有收藏的状态不返回）
+    pub by_status: Vec<WatchStatusCount>,
+    /// 已评分收藏的平均个人评分
+    pub average_rating: Option<f64>,
+    pub rated: i64,
+    /// 已观看的总时长（分钟）：看完的按完整时长，其他按观看进度折算
+    pub runtime_watched_minutes: i64,
+    /// 本月变为已看完的收藏数
+    pub completed_this_month: i64,
+    /// 按收藏数倒序
+    pub tags: Vec<TagCompletion>,
+}
+
+/// 默认返回的状态变化记录数
+pub const DEFAULT_STATUS_HISTORY_LIMIT: i64 = 100;
+
+/// 最多返回的状态变化记录数
+pub const MAX_STATUS_HISTORY_LIMIT: i64 = 500;
+
+/// 一次观看状态变化
+#[derive(Debug, Serialize, FromRow)]
+pub struct WatchStatusChange {
+    pub media_id: String,
+    pub media_title: String,
+    /// 加入收藏时为 None
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// 状态变化记录过滤器
+#[derive(Debug, Default)]
+pub struct StatusHistoryFilters {
+    pub media_id: Option<String>,
+    /// 变化后的状态
+    pub to_status: Option<WatchStatus>,
+    /// 日期范围（YYYY-MM-DD，包含两端）
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub limit: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AddToCollectionRequest {
     pub media_id: String,
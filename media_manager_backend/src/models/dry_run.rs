@@ -0,0 +1,157 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{MediaItem, MediaItemResponse};
+
+/// 比较时忽略的字段（主键、时间戳和计算字段）
+const IGNORED_FIELDS: [&str; 7] = [
+    "id", "created_at", "updated_at",
+    "display_title", "year_string", "rating_string", "runtime_string",
+];
+
+/// 单个字段的变化
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// 试运行结果：某个媒体将会发生的变化（不写入数据库）
+#[derive(Debug, Clone, Serialize)]
+pub struct DryRunItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_id: Option<String>,
+    pub title: String,
+    /// create / update / unchanged / error
+    pub action: String,
+    pub changes: Vec<FieldChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl DryRunItem {
+    /// 更新现有媒体
+    pub fn update(before: &MediaItem, after: &MediaItem) -> Self {
+        let changes = diff_media(Some(before), after);
+        Self {
+            media_id: Some(before.id.clone()),
+            title: after.title.clone(),
+            action: if changes.is_empty() { "unchanged" } else { "update" }.to_string(),
+            changes,
+            error: None,
+        }
+    }
+
+    /// 创建新媒体
+    pub fn create(media: &MediaItem) -> Self {
+        Self {
+            media_id: None,
+            title: media.title.clone(),
+            action: "create".to_string(),
+            changes: diff_media(None, media),
+            error: None,
+        }
+    }
+
+    pub fn error(media_id: Option<String>, title: impl Into<String>, error: impl Into<String>) -> Self {
+        Self {
+            media_id,
+            title: title.into(),
+            action: "error".to_string(),
+            changes: Vec::new(),
+            error: Some(error.into()),
+        }
+    }
+
+    /// 追加额外的变化（如演员、标签等不在媒体表中的关联）
+    pub fn with_change(mut self, field: &str, old: Value, new: Value) -> Self {
+        if old != new {
+            self.changes.push(FieldChange { field: field.to_string(), old, new });
+            if self.action == "unchanged" {
+                self.action = "update".to_string();
+            }
+        }
+        self
+    }
+}
+
+/// 比较两个 JSON 对象的顶层字段，返回有变化的字段
+pub fn diff_json(before: &Value, after: &Value, ignored: &[&str]) -> Vec<FieldChange> {
+    let empty = serde_json::Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields.into_iter()
+        .filter(|f| !ignored.contains(&f.as_str()))
+        .filter_map(|f| {
+            let old = before.get(f).cloned().unwrap_or(Value::Null);
+            let new = after.get(f).cloned().unwrap_or(Value::Null);
+            (old != new).then(|| FieldChange { field: f.clone(), old, new })
+        })
+        .collect()
+}
+
+/// 比较媒体修改前后的字段（before 为空表示新建，只列出有值的字段）
+pub fn diff_media(before: Option<&MediaItem>, after: &MediaItem) -> Vec<FieldChange> {
+    let to_value = |m: &MediaItem| serde_json::to_value(MediaItemResponse::from(m.clone())).unwrap_or(Value::Null);
+    let after_value = to_value(after);
+
+    match before {
+        Some(before) => diff_json(&to_value(before), &after_value, &IGNORED_FIELDS),
+        None => diff_json(&Value::Null, &after_value, &IGNORED_FIELDS)
+            .into_iter()
+            .filter(|c| !is_empty_value(&c.new))
+            .collect(),
+    }
+}
+
+fn is_empty_value(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.values().all(is_empty_value),
+        Value::String(s) => s.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::models::MediaType;
+
+    #[test]
+    fn test_diff_json() {
+        let before = json!({"id": "1", "title": "A", "year": 2020, "tags": ["x"]});
+        let after = json!({"id": "2", "title": "A", "year": 2021, "studio": "S"});
+        let changes = diff_json(&before, &after, &["id"]);
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["studio", "tags", "year"]);
+        assert_eq!(changes[2], FieldChange { field: "year".to_string(), old: json!(2020), new: json!(2021) });
+    }
+
+    #[test]
+    fn test_dry_run_update_and_create() {
+        let before = MediaItem::new("Title".to_string(), MediaType::Movie).unwrap();
+        let mut after = before.clone();
+        assert_eq!(DryRunItem::update(&before, &after).action, "unchanged");
+
+        after.studio = Some("Studio".to_string());
+        after.updated_at = chrono::Utc::now();
+        let item = DryRunItem::update(&before, &after);
+        assert_eq!(item.action, "update");
+        assert_eq!(item.changes.len(), 1);
+        assert_eq!(item.changes[0].field, "studio");
+
+        let created = DryRunItem::create(&after);
+        assert_eq!(created.action, "create");
+        assert!(created.changes.iter().any(|c| c.field == "title"));
+        assert!(created.changes.iter().all(|c| c.field != "overview"));
+    }
+}
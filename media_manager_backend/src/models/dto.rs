@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
-use super::{MediaItem, Collection, MediaType, WatchStatus, Person, ExternalIds, PlayLink, DownloadLink};
+use super::{MediaItem, Collection, MediaType, WatchStatus, Person, ExternalIds, PlayLink, DownloadLink, MediaTranslation, ImageColors};
 
 /// 媒体项目响应DTO
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,6 +17,15 @@ pub struct MediaItemResponse {
     pub vote_count: Option<i32>,
     pub poster_url: Option<String>,
     pub backdrop_url: Vec<String>, // 支持多个背景图
+    /// 封面的 BlurHash 占位图（图片缓存后才有）
+    #[serde(default)]
+    pub poster_blurhash: Option<String>,
+    /// 背景图的 BlurHash 占位图，与 backdrop_url 一一对应
+    #[serde(default)]
+    pub backdrop_blurhash: Vec<Option<String>>,
+    /// 封面的主色和强调色（用于详情页主题色，封面缓存后才有）
+    #[serde(default)]
+    pub poster_colors: Option<ImageColors>,
     pub overview: Option<String>,
     pub runtime: Option<i32>,
     pub release_date: Option<String>,
@@ -33,6 +42,7 @@ pub struct MediaItemResponse {
     pub preview_urls: Vec<String>,
     pub preview_video_urls: Vec<serde_json::Value>,  // 支持结构化数据
     pub cover_video_url: Option<String>,
+    pub trailer_url: Option<String>,
     pub studio: Option<String>,
     pub series: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -43,15 +53,40 @@ pub struct MediaItemResponse {
     pub year_string: String,
     pub rating_string: String,
     pub runtime_string: String,
+    /// 按 Accept-Language 替换标题/简介时使用的翻译语言
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation_language: Option<String>,
+}
+
+impl MediaItemResponse {
+    /// 用翻译替换标题和简介（翻译中为空的字段保留原值）
+    pub fn apply_translation(&mut self, translation: &MediaTranslation) {
+        if let Some(title) = &translation.title {
+            self.title = title.clone();
+            self.display_title = title.clone();
+        }
+        if let Some(overview) = &translation.overview {
+            self.overview = Some(overview.clone());
+        }
+        self.translation_language = Some(translation.language.clone());
+    }
 }
 
 impl From<MediaItem> for MediaItemResponse {
     fn from(item: MediaItem) -> Self {
+        let placeholders = item.get_image_placeholders();
+        let backdrop_url: Vec<String> = item.backdrop_url.as_ref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_else(|| vec![]);
         Self {
+            poster_blurhash: item.poster_url.as_ref().and_then(|url| placeholders.get(url).cloned()),
+            poster_colors: item.get_poster_colors(),
+            backdrop_blurhash: backdrop_url.iter().map(|url| placeholders.get(url).cloned()).collect(),
             display_title: item.display_title().to_string(),
             year_string: item.year_string(),
             rating_string: item.rating_string(),
             runtime_string: item.runtime_string(),
+            translation_language: None,
             external_ids: item.get_external_ids().unwrap_or_default(),
             media_type: item.get_media_type().unwrap_or(MediaType::Movie),
             genres: item.get_genres().unwrap_or_default(),
@@ -64,6 +99,7 @@ impl From<MediaItem> for MediaItemResponse {
                 .and_then(|s| serde_json::from_str(s).ok())
                 .unwrap_or_else(|| vec![]),
             cover_video_url: item.cover_video_url,
+            trailer_url: item.trailer_url,
             id: item.id,
             code: item.code,  // 添加 code 字段映射
             title: item.title,
@@ -72,9 +108,7 @@ impl From<MediaItem> for MediaItemResponse {
             rating: item.rating,
             vote_count: item.vote_count,
             poster_url: item.poster_url,
-            backdrop_url: item.backdrop_url.as_ref()
-                .and_then(|s| serde_json::from_str(s).ok())
-                .unwrap_or_else(|| vec![]),
+            backdrop_url,
             overview: item.overview,
             runtime: item.runtime,
             release_date: item.release_date,
@@ -203,6 +237,25 @@ pub struct TagInfo {
     pub usage_count: i32,
 }
 
+/// 批量标签操作中单个标签的变化数量
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagChange {
+    pub tag: String,
+    /// 新增或移除关联的媒体数量（试运行时为将会变化的数量）
+    pub affected: i64,
+}
+
+/// 按筛选条件批量设置标签的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchTagResult {
+    pub matched: usize,
+    pub added: Vec<TagChange>,
+    pub removed: Vec<TagChange>,
+    /// 新创建的标签
+    pub created_tags: Vec<String>,
+    pub dry_run: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MediaTypeCount {
     pub media_type: MediaType,
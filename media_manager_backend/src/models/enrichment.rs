@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 补全步骤（按执行顺序）
+pub const ENRICHMENT_STEPS: [&str; 4] = ["scrape", "artwork", "actors", "probe"];
+
+/// 补全任务状态
+pub const ENRICHMENT_STATUSES: [&str; 4] = ["pending", "running", "completed", "failed"];
+
+/// 加入队列的原因
+pub const ENRICHMENT_REASON_CREATE: &str = "create";
+pub const ENRICHMENT_REASON_SCAN: &str = "scan";
+pub const ENRICHMENT_REASON_MANUAL: &str = "manual";
+
+/// 自动补全设置（保存在 user_settings 的 enrichment 键中）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EnrichmentSettings {
+    /// 新建媒体（手动创建或扫描）后是否自动加入补全队列
+    #[serde(default)]
+    pub enabled: bool,
+    /// 要执行的步骤，为空时执行全部步骤
+    #[serde(default)]
+    pub steps: Vec<String>,
+    /// 两个任务之间的间隔秒数，避免占满插件和网络
+    #[serde(default = "default_job_delay_secs")]
+    pub job_delay_secs: u64,
+}
+
+fn default_job_delay_secs() -> u64 {
+    5
+}
+
+impl Default for EnrichmentSettings {
+    fn default() -> Self {
+        Self { enabled: false, steps: Vec::new(), job_delay_secs: default_job_delay_secs() }
+    }
+}
+
+impl EnrichmentSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(step) = self.steps.iter().find(|s| !ENRICHMENT_STEPS.contains(&s.as_str())) {
+            return Err(format!("Unknown step '{}', expected one of: {}", step, ENRICHMENT_STEPS.join(", ")));
+        }
+        if self.job_delay_secs > 3600 {
+            return Err("job_delay_secs must be at most 3600".to_string());
+        }
+        Ok(())
+    }
+
+    /// 步骤是否需要执行
+    pub fn runs_step(&self, step: &str) -> bool {
+        self.steps.is_empty() || self.steps.iter().any(|s| s == step)
+    }
+}
+
+/// 单个步骤的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EnrichmentStepResult {
+    pub step: String,
+    /// completed、skipped 或 failed
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl EnrichmentStepResult {
+    pub fn completed(step: &str, message: impl Into<String>) -> Self {
+        Self { step: step.to_string(), status: "completed".to_string(), message: Some(message.into()) }
+    }
+
+    pub fn skipped(step: &str, message: impl Into<String>) -> Self {
+        Self { step: step.to_string(), status: "skipped".to_string(), message: Some(message.into()) }
+    }
+
+    pub fn failed(step: &str, message: impl Into<String>) -> Self {
+        Self { step: step.to_string(), status: "failed".to_string(), message: Some(message.into()) }
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.status == "failed"
+    }
+}
+
+/// 补全任务（enrichment_jobs 表原始行）
+#[derive(Debug, Clone, FromRow)]
+pub struct EnrichmentJobRecord {
+    pub media_id: String,
+    pub status: String,
+    pub reason: String,
+    pub steps: String,
+    pub error: Option<String>,
+    pub attempts: i64,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// 媒体的后台补全任务
+#[derive(Debug, Clone, Serialize)]
+pub struct EnrichmentJob {
+    pub media_id: String,
+    pub status: String,
+    pub reason: String,
+    pub steps: Vec<EnrichmentStepResult>,
+    pub error: Option<String>,
+    pub attempts: i64,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl From<EnrichmentJobRecord> for EnrichmentJob {
+    fn from(record: EnrichmentJobRecord) -> Self {
+        Self {
+            media_id: record.media_id,
+            status: record.status,
+            reason: record.reason,
+            steps: serde_json::from_str(&record.steps).unwrap_or_default(),
+            error: record.error,
+            attempts: record.attempts,
+            created_at: record.created_at,
+            started_at: record.started_at,
+            finished_at: record.finished_at,
+        }
+    }
+}
+
+/// 媒体文件的技术信息（ffprobe 探测结果）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct MediaProbe {
+    pub file_path: String,
+    pub media_id: String,
+    pub container: Option<String>,
+    pub duration_secs: Option<f64>,
+    pub bit_rate: Option<i64>,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub probed_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enrichment_settings() {
+        let settings: EnrichmentSettings = serde_json::from_str(r#"{"enabled": true}"#).unwrap();
+        assert_eq!(settings.job_delay_secs, 5);
+        assert!(settings.runs_step("probe"));
+
+        let settings = EnrichmentSettings { steps: vec!["scrape".to_string()], ..Default::default() };
+        assert!(settings.runs_step("scrape") && !settings.runs_step("artwork"));
+        assert!(settings.validate().is_ok());
+        assert!(EnrichmentSettings { steps: vec!["nope".to_string()], ..Default::default() }.validate().is_err());
+    }
+}
@@ -185,6 +185,7 @@ impl ExternalIdsFactory {
             tmdb_id: Some(tmdb_id),
             imdb_id: None,
             omdb_id: None,
+            ..Default::default()
         }
     }
     
@@ -194,6 +195,7 @@ impl ExternalIdsFactory {
             tmdb_id: None,
             imdb_id: Some(imdb_id),
             omdb_id: None,
+            ..Default::default()
         }
     }
     
@@ -203,6 +205,7 @@ impl ExternalIdsFactory {
             tmdb_id,
             imdb_id,
             omdb_id,
+            ..Default::default()
         }
     }
 }
@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 可收藏的实体类型
+pub const FAVORITE_ENTITY_TYPES: [&str; 3] = ["actor", "studio", "series"];
+
+/// 收藏的演员/厂商/系列
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FavoriteEntity {
+    pub entity_type: String,
+    pub entity_id: String,
+    pub name: String,
+    pub image_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+/// 允许的最大同时运行的 FFmpeg/ffprobe 进程数
+pub const MAX_FFMPEG_CONCURRENCY: usize = 16;
+
+/// 每个进程允许的最大线程数
+pub const MAX_FFMPEG_THREADS: u32 = 64;
+
+/// FFmpeg 任务调度设置（保存在 user_settings 的 ffmpeg 键中），缩略图和文件探测共用
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FfmpegSettings {
+    /// 同时运行的进程数，超出的任务排队等待
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+    /// 进程的 nice 值（0-19，越大优先级越低；Windows 下忽略）
+    #[serde(default = "default_nice")]
+    pub nice: i32,
+    /// 每个 FFmpeg 进程的解码线程数，0 表示由 FFmpeg 决定
+    #[serde(default)]
+    pub threads: u32,
+}
+
+fn default_max_concurrent() -> usize {
+    let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    (cpus / 2).clamp(1, 8)
+}
+
+fn default_nice() -> i32 {
+    10
+}
+
+impl Default for FfmpegSettings {
+    fn default() -> Self {
+        Self { max_concurrent: default_max_concurrent(), nice: default_nice(), threads: 0 }
+    }
+}
+
+impl FfmpegSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_concurrent == 0 || self.max_concurrent > MAX_FFMPEG_CONCURRENCY {
+            return Err(format!("max_concurrent must be between 1 and {}", MAX_FFMPEG_CONCURRENCY));
+        }
+        if !(0..=19).contains(&self.nice) {
+            return Err("nice must be between 0 and 19".to_string());
+        }
+        if self.threads > MAX_FFMPEG_THREADS {
+            return Err(format!("threads must be between 0 and {}", MAX_FFMPEG_THREADS));
+        }
+        Ok(())
+    }
+}
+
+/// FFmpeg 任务调度状态
+#[derive(Debug, Clone, Serialize)]
+pub struct FfmpegSchedulerStatus {
+    #[serde(flatten)]
+    pub settings: FfmpegSettings,
+    /// 正在运行的进程数
+    pub running: usize,
+}
+
+/// 硬件加速方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HwAccel {
+    Nvenc,
+    Qsv,
+    Vaapi,
+}
+
+impl HwAccel {
+    /// 自动选择时的优先顺序
+    pub const ALL: [HwAccel; 3] = [HwAccel::Nvenc, HwAccel::Qsv, HwAccel::Vaapi];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HwAccel::Nvenc => "nvenc",
+            HwAccel::Qsv => "qsv",
+            HwAccel::Vaapi => "vaapi",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|accel| accel.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+
+    /// `ffmpeg -hwaccels` 中对应的解码加速名
+    pub fn hwaccel_name(&self) -> &'static str {
+        match self {
+            HwAccel::Nvenc => "cuda",
+            HwAccel::Qsv => "qsv",
+            HwAccel::Vaapi => "vaapi",
+        }
+    }
+
+    /// H.264 硬件编码器
+    pub fn h264_encoder(&self) -> &'static str {
+        match self {
+            HwAccel::Nvenc => "h264_nvenc",
+            HwAccel::Qsv => "h264_qsv",
+            HwAccel::Vaapi => "h264_vaapi",
+        }
+    }
+}
+
+/// 启动时检测到的硬件加速能力
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HwAccelCapabilities {
+    /// 是否找到 ffmpeg
+    pub ffmpeg_available: bool,
+    /// HW_ACCEL 的值（auto / none / nvenc / qsv / vaapi）
+    pub requested: String,
+    /// 通过测试编码的加速方式
+    pub available: Vec<HwAccel>,
+    /// 实际使用的加速方式，为空时使用软件编解码
+    pub selected: Option<HwAccel>,
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+/// 用户自定义的文件名解析规则（保存在 user_settings 的 filename_rules 键中）
+///
+/// 按顺序匹配去掉扩展名后的文件名，第一条命中的规则生效；都不命中时使用内置解析。
+/// 字段取值：有模板时用捕获组展开模板（如 `$prefix-$num`、`20${yy}-${mm}-${dd}`），
+/// 否则取同名的命名捕获组（code / series / date / title / year）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FilenameRule {
+    pub name: String,
+    /// 正则表达式
+    pub pattern: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_template: Option<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for FilenameRule {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            pattern: String::new(),
+            enabled: true,
+            code_template: None,
+            series_template: None,
+            date_template: None,
+            title_template: None,
+        }
+    }
+}
+
+/// 规则可输出的命名捕获组
+pub const FILENAME_RULE_GROUPS: [&str; 5] = ["code", "series", "date", "title", "year"];
+
+impl FilenameRule {
+    /// 校验规则：名称非空、正则有效、至少能输出一个字段
+    pub fn validate(&self) -> Result<regex::Regex, String> {
+        if self.name.trim().is_empty() {
+            return Err("Rule name cannot be empty".to_string());
+        }
+        let regex = regex::Regex::new(&self.pattern)
+            .map_err(|e| format!("Rule '{}': invalid pattern: {}", self.name, e))?;
+
+        let has_group = regex.capture_names().flatten().any(|n| FILENAME_RULE_GROUPS.contains(&n));
+        let has_template = self.code_template.is_some()
+            || self.series_template.is_some()
+            || self.date_template.is_some()
+            || self.title_template.is_some();
+        if !has_group && !has_template {
+            return Err(format!(
+                "Rule '{}': pattern needs a named group ({}) or a template",
+                self.name,
+                FILENAME_RULE_GROUPS.join("/")
+            ));
+        }
+        Ok(regex)
+    }
+}
@@ -0,0 +1,11 @@
+use serde::Serialize;
+
+/// 媒体筛选选项（筛选下拉框的可选值）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MediaFilterOptions {
+    pub media_types: Vec<String>,
+    pub studios: Vec<String>,
+    pub series: Vec<String>,
+    pub years: Vec<i32>,
+    pub genres: Vec<String>,
+}
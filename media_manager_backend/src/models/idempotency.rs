@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+/// 幂等键保存时长（小时）
+pub const IDEMPOTENCY_WINDOW_HOURS: i64 = 24;
+
+/// 幂等键最大长度
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// 幂等键记录
+#[derive(Debug, Clone, FromRow)]
+pub struct IdempotencyRecord {
+    pub idempotency_key: String,
+    pub method: String,
+    pub path: String,
+    pub request_hash: String,
+    /// 处理中时为空
+    pub status_code: Option<i64>,
+    pub content_type: Option<String>,
+    pub response_body: Option<Vec<u8>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl IdempotencyRecord {
+    /// 请求是否仍在处理中
+    pub fn is_pending(&self) -> bool {
+        self.status_code.is_none()
+    }
+}
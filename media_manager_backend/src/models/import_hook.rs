@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::validate_template_fields;
+
+/// 钩子类型
+pub const HOOK_TYPES: [&str; 2] = ["command", "webhook"];
+
+/// Webhook 支持的请求方法
+pub const HOOK_METHODS: [&str; 3] = ["GET", "POST", "PUT"];
+
+/// 命令参数和 Webhook 地址中可用的字段
+pub const HOOK_TEMPLATE_FIELDS: [&str; 7] = ["media_id", "title", "original_title", "code", "year", "path", "dir"];
+
+/// 单次执行的最长时间（秒）
+pub const MAX_HOOK_TIMEOUT_SECS: i64 = 600;
+
+/// 允许命令钩子的环境变量（命令钩子会在服务器上执行程序，默认关闭）
+pub const HOOK_COMMANDS_ENV: &str = "IMPORT_HOOK_COMMANDS_ENABLED";
+
+/// 服务器是否允许命令钩子（IMPORT_HOOK_COMMANDS_ENABLED=true）
+pub fn command_hooks_enabled() -> bool {
+    std::env::var(HOOK_COMMANDS_ENV)
+        .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes"))
+        .unwrap_or(false)
+}
+
+/// 导入钩子（import_hooks 表原始行）
+#[derive(Debug, Clone, FromRow)]
+pub struct ImportHookRecord {
+    pub id: String,
+    pub name: String,
+    pub hook_type: String,
+    pub target: String,
+    pub args: String,
+    pub method: String,
+    pub enabled: bool,
+    pub timeout_secs: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 文件导入/匹配到媒体后执行的命令或 Webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportHook {
+    pub id: String,
+    pub name: String,
+    /// command 或 webhook
+    pub hook_type: String,
+    /// 命令路径或 Webhook 地址，如 `http://plex:32400/library/sections/1/refresh`（只有 Webhook 地址可以使用占位符）
+    pub target: String,
+    /// 命令参数（不经过 shell，每一项单独传给命令）
+    pub args: Vec<String>,
+    /// Webhook 请求方法，POST/PUT 时以 JSON 发送导入信息
+    pub method: String,
+    pub enabled: bool,
+    pub timeout_secs: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ImportHookRecord> for ImportHook {
+    fn from(record: ImportHookRecord) -> Self {
+        Self {
+            id: record.id,
+            name: record.name,
+            hook_type: record.hook_type,
+            target: record.target,
+            args: serde_json::from_str(&record.args).unwrap_or_default(),
+            method: record.method,
+            enabled: record.enabled,
+            timeout_secs: record.timeout_secs,
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateImportHookRequest {
+    pub name: String,
+    pub hook_type: String,
+    pub target: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub method: Option<String>,
+    pub enabled: Option<bool>,
+    pub timeout_secs: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateImportHookRequest {
+    pub name: Option<String>,
+    pub target: Option<String>,
+    pub args: Option<Vec<String>>,
+    pub method: Option<String>,
+    pub enabled: Option<bool>,
+    pub timeout_secs: Option<i64>,
+}
+
+impl ImportHook {
+    pub fn new(req: CreateImportHookRequest) -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: req.name.trim().to_string(),
+            hook_type: req.hook_type,
+            target: req.target.trim().to_string(),
+            args: req.args,
+            method: req.method.map(|m| m.to_uppercase()).unwrap_or_else(|| "POST".to_string()),
+            enabled: req.enabled.unwrap_or(true),
+            timeout_secs: req.timeout_secs.unwrap_or(30),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn apply_update(&mut self, req: UpdateImportHookRequest) {
+        if let Some(name) = req.name {
+            self.name = name.trim().to_string();
+        }
+        if let Some(target) = req.target {
+            self.target = target.trim().to_string();
+        }
+        if let Some(args) = req.args {
+            self.args = args;
+        }
+        if let Some(method) = req.method {
+            self.method = method.to_uppercase();
+        }
+        if let Some(enabled) = req.enabled {
+            self.enabled = enabled;
+        }
+        if let Some(timeout_secs) = req.timeout_secs {
+            self.timeout_secs = timeout_secs;
+        }
+        self.updated_at = Utc::now();
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("name cannot be empty".to_string());
+        }
+        if !HOOK_TYPES.contains(&self.hook_type.as_str()) {
+            return Err(format!("Invalid hook_type '{}'. Must be one of: {}", self.hook_type, HOOK_TYPES.join(", ")));
+        }
+        if self.target.is_empty() {
+            return Err("target cannot be empty".to_string());
+        }
+        if !(1..=MAX_HOOK_TIMEOUT_SECS).contains(&self.timeout_secs) {
+            return Err(format!("timeout_secs must be between 1 and {}", MAX_HOOK_TIMEOUT_SECS));
+        }
+        // 命令本身固定，占位符只能出现在参数中，避免刮削到的标题等决定执行哪个程序
+        if self.hook_type == "command" && self.target.contains(['{', '}']) {
+            return Err("command target cannot contain placeholders, use args instead".to_string());
+        }
+        validate_template_fields(&self.target, &HOOK_TEMPLATE_FIELDS, "hook target")?;
+        for arg in &self.args {
+            validate_template_fields(arg, &HOOK_TEMPLATE_FIELDS, "hook argument")?;
+        }
+
+        if self.hook_type == "webhook" {
+            if !HOOK_METHODS.contains(&self.method.as_str()) {
+                return Err(format!("Invalid method '{}'. Must be one of: {}", self.method, HOOK_METHODS.join(", ")));
+            }
+            // 占位符替换后必须是 http(s) 地址
+            let sample = HookContext::default().template_values(true);
+            match url::Url::parse(&super::fill_template(&self.target, &sample)) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                _ => return Err(format!("invalid webhook url: {}", self.target)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 传给钩子的导入信息（Webhook 的请求体）
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HookContext {
+    pub event: String,
+    pub media_id: String,
+    pub title: String,
+    pub original_title: Option<String>,
+    pub code: Option<String>,
+    pub year: Option<i32>,
+    /// 第一个文件的路径
+    pub path: Option<String>,
+    /// 第一个文件所在目录
+    pub dir: Option<String>,
+    pub files: Vec<String>,
+}
+
+impl HookContext {
+    /// 模板字段的值，`url_encode` 为 true 时按 URL 查询参数编码
+    pub fn template_values(&self, url_encode: bool) -> HashMap<&'static str, String> {
+        let fields = [
+            ("media_id", Some(self.media_id.clone())),
+            ("title", Some(self.title.clone())),
+            ("original_title", self.original_title.clone()),
+            ("code", self.code.clone()),
+            ("year", self.year.map(|y| y.to_string())),
+            ("path", self.path.clone()),
+            ("dir", self.dir.clone()),
+        ];
+        fields.into_iter()
+            .map(|(field, value)| {
+                let value = value.unwrap_or_default();
+                let value = if url_encode { urlencoding::encode(&value).into_owned() } else { value };
+                (field, value)
+            })
+            .collect()
+    }
+}
+
+/// 一次钩子执行的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct HookResult {
+    pub success: bool,
+    /// 命令退出码或 Webhook 响应状态码
+    pub exit_code: Option<i32>,
+    /// 命令输出或响应内容（截断）
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
+
+/// 钩子执行记录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct HookEvent {
+    pub id: i64,
+    pub hook_id: String,
+    pub media_id: Option<String>,
+    pub file_path: Option<String>,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+    pub fired_at: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(hook_type: &str, target: &str) -> ImportHook {
+        ImportHook::new(CreateImportHookRequest {
+            name: "Plex".to_string(),
+            hook_type: hook_type.to_string(),
+            target: target.to_string(),
+            args: Vec::new(),
+            method: None,
+            enabled: None,
+            timeout_secs: None,
+        })
+    }
+
+    #[test]
+    fn test_validate_import_hook() {
+        assert!(hook("webhook", "http://plex:32400/library/sections/1/refresh?path={dir}").validate().is_ok());
+        assert!(hook("webhook", "{dir}").validate().is_err());
+        assert!(hook("webhook", "http://plex/{unknown}").validate().is_err());
+        assert!(hook("shell", "/bin/true").validate().is_err());
+
+        let mut command = hook("command", "/usr/local/bin/notify");
+        command.args = vec!["--title={title}".to_string(), "{path}".to_string()];
+        assert!(command.validate().is_ok());
+        command.apply_update(UpdateImportHookRequest { timeout_secs: Some(0), ..Default::default() });
+        assert!(command.validate().is_err());
+
+        // 命令路径不能使用占位符
+        assert!(hook("command", "{title}").validate().is_err());
+        assert!(hook("command", "/opt/{code}/run").validate().is_err());
+    }
+
+    #[test]
+    fn test_hook_template_values() {
+        let context = HookContext {
+            title: "A & B".to_string(),
+            dir: Some("/mnt/media/ABC-123".to_string()),
+            ..Default::default()
+        };
+        let values = context.template_values(false);
+        assert_eq!(values["title"], "A & B");
+        assert_eq!(values["code"], "");
+        assert_eq!(context.template_values(true)["dir"], "%2Fmnt%2Fmedia%2FABC-123");
+    }
+}
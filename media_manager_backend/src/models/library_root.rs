@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::StorageConfig;
+
+/// 重命名模板中可用的字段
+pub const RENAME_TEMPLATE_FIELDS: [&str; 7] = ["title", "original_title", "code", "year", "studio", "series", "part"];
+
+/// 媒体库根目录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LibraryRoot {
+    pub id: String,
+    pub path: String,
+    pub label: Option<String>,
+    /// 该目录下新建媒体的默认内容类型（Scene、Movie 等）
+    pub content_type: Option<String>,
+    /// 是否由后台任务定期扫描新文件
+    pub auto_scan: bool,
+    /// 导入时整理文件用的重命名模板，如 `{studio}/{code} {title}`
+    pub rename_template: Option<String>,
+    /// 存储后端：local、s3、webdav
+    pub storage_type: String,
+    /// 对象存储的连接参数（JSON，含密钥，不返回给客户端）
+    #[serde(skip)]
+    pub storage_config: Option<String>,
+    /// 以下为最近一次可用性检查的结果，未检查过时为空
+    pub path_exists: Option<bool>,
+    pub reachable: Option<bool>,
+    pub last_error: Option<String>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    /// 最近一次可访问的时间
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 根目录可用性检查结果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RootAvailability {
+    pub path_exists: bool,
+    /// 能列出目录内容且目录非空（网络共享掉线时挂载点通常为空目录）
+    pub reachable: bool,
+    pub error: Option<String>,
+}
+
+/// 目录浏览结果（未指定路径时列出所有根目录）
+#[derive(Debug, Serialize)]
+pub struct DirectoryListing {
+    pub path: Option<String>,
+    /// 上级目录，已经是根目录时为空
+    pub parent: Option<String>,
+    pub root_id: Option<String>,
+    pub entries: Vec<DirectoryEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectoryEntry {
+    pub name: String,
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateLibraryRootRequest {
+    pub path: String,
+    pub label: Option<String>,
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub auto_scan: bool,
+    pub rename_template: Option<String>,
+    /// 不填时为本地目录
+    pub storage: Option<StorageConfig>,
+}
+
+/// 更新根目录（字段为空字符串时清空）
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateLibraryRootRequest {
+    pub path: Option<String>,
+    pub label: Option<String>,
+    pub content_type: Option<String>,
+    pub auto_scan: Option<bool>,
+    pub rename_template: Option<String>,
+    /// 更换存储后端（需要重新提供密钥）
+    pub storage: Option<StorageConfig>,
+}
+
+impl LibraryRoot {
+    pub fn new(req: CreateLibraryRootRequest) -> Self {
+        let now = Utc::now();
+        let mut root = Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            path: normalize_root_path(&req.path),
+            label: non_empty(req.label),
+            content_type: non_empty(req.content_type),
+            auto_scan: req.auto_scan,
+            rename_template: non_empty(req.rename_template),
+            storage_type: "local".to_string(),
+            storage_config: None,
+            path_exists: None,
+            reachable: None,
+            last_error: None,
+            last_checked_at: None,
+            last_seen_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        root.set_storage(req.storage.unwrap_or(StorageConfig::Local));
+        root
+    }
+
+    pub fn apply_update(&mut self, req: UpdateLibraryRootRequest) {
+        if let Some(path) = req.path {
+            let path = normalize_root_path(&path);
+            if path != self.path {
+                // 换了目录，之前的检查结果不再适用
+                self.clear_availability();
+            }
+            self.path = path;
+        }
+        if let Some(storage) = req.storage {
+            self.set_storage(storage);
+            self.clear_availability();
+        }
+        if req.label.is_some() {
+            self.label = non_empty(req.label);
+        }
+        if req.content_type.is_some() {
+            self.content_type = non_empty(req.content_type);
+        }
+        if let Some(auto_scan) = req.auto_scan {
+            self.auto_scan = auto_scan;
+        }
+        if req.rename_template.is_some() {
+            self.rename_template = non_empty(req.rename_template);
+        }
+        self.updated_at = Utc::now();
+    }
+
+    /// 存储后端配置（本地目录返回 Local）
+    pub fn storage(&self) -> Result<StorageConfig, String> {
+        match &self.storage_config {
+            Some(json) => serde_json::from_str(json).map_err(|e| format!("invalid storage config: {}", e)),
+            None => Ok(StorageConfig::Local),
+        }
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.storage_type == "local"
+    }
+
+    fn set_storage(&mut self, storage: StorageConfig) {
+        self.storage_type = storage.storage_type().to_string();
+        self.storage_config = if storage.is_local() {
+            None
+        } else {
+            serde_json::to_string(&storage).ok()
+        };
+    }
+
+    fn clear_availability(&mut self) {
+        self.path_exists = None;
+        self.reachable = None;
+        self.last_error = None;
+        self.last_checked_at = None;
+        self.last_seen_at = None;
+    }
+
+    /// 文件相对根目录的路径（用作对象存储的键），不在根目录下时返回 None
+    pub fn relative_path<'a>(&self, file_path: &'a str) -> Option<&'a str> {
+        if !self.contains(file_path) {
+            return None;
+        }
+        Some(file_path[self.path.len()..].trim_start_matches(['/', '\\']))
+    }
+
+    /// 根目录下子目录的完整路径
+    pub fn child_path(&self, dir: &str, name: &str) -> String {
+        if self.is_local() {
+            std::path::Path::new(dir).join(name).to_string_lossy().into_owned()
+        } else {
+            format!("{}/{}", dir.trim_end_matches('/'), name)
+        }
+    }
+
+    /// 上级目录，`dir` 已经是根目录时返回 None
+    pub fn parent_path(&self, dir: &str) -> Option<String> {
+        let relative = self.relative_path(dir)?;
+        if relative.is_empty() {
+            return None;
+        }
+        Some(match relative.rsplit_once(['/', '\\']) {
+            Some((parent, _)) => self.child_path(&self.path, parent),
+            None => self.path.clone(),
+        })
+    }
+
+    /// 记录一次可用性检查的结果
+    pub fn record_availability(&mut self, availability: &RootAvailability) {
+        let now = Utc::now();
+        self.path_exists = Some(availability.path_exists);
+        self.reachable = Some(availability.reachable);
+        self.last_error = availability.error.clone();
+        self.last_checked_at = Some(now);
+        if availability.reachable {
+            self.last_seen_at = Some(now);
+        }
+    }
+
+    /// 文件是否位于该根目录下
+    pub fn contains(&self, file_path: &str) -> bool {
+        match file_path.strip_prefix(self.path.as_str()) {
+            Some(rest) => rest.is_empty() || self.path.ends_with(['/', '\\']) || rest.starts_with(['/', '\\']),
+            None => false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.path.is_empty() {
+            return Err("path cannot be empty".to_string());
+        }
+        if let Some(template) = &self.rename_template {
+            validate_rename_template(template)?;
+        }
+        self.storage()?.validate()
+    }
+}
+
+/// 查找文件所属的根目录（嵌套时取最深的一个）
+pub fn find_library_root<'a>(roots: &'a [LibraryRoot], file_path: &str) -> Option<&'a LibraryRoot> {
+    roots.iter()
+        .filter(|root| root.contains(file_path))
+        .max_by_key(|root| root.path.len())
+}
+
+/// 去掉首尾空白和末尾的路径分隔符（保留根目录本身）
+pub fn normalize_root_path(path: &str) -> String {
+    let path = path.trim();
+    let trimmed = path.trim_end_matches(['/', '\\']);
+    if trimmed.is_empty() || trimmed.ends_with(':') {
+        // "/" 或 "C:\"
+        path.chars().take(trimmed.len() + 1).collect()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// 检查重命名模板的占位符是否都是支持的字段
+pub fn validate_rename_template(template: &str) -> Result<(), String> {
+    validate_template_fields(template, &RENAME_TEMPLATE_FIELDS, "rename template")
+}
+
+/// 检查模板中的 `{字段}` 占位符是否完整且都在 `fields` 中
+pub fn validate_template_fields(template: &str, fields: &[&str], kind: &str) -> Result<(), String> {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let end = after.find('}')
+            .ok_or_else(|| format!("unclosed placeholder in {}: {}", kind, template))?;
+        let field = &after[..end];
+        if !fields.contains(&field) {
+            return Err(format!(
+                "unknown placeholder {{{}}} in {} (supported: {})",
+                field,
+                kind,
+                fields.join(", ")
+            ));
+        }
+        rest = &after[end + 1..];
+    }
+    if rest.contains('}') {
+        return Err(format!("unmatched '}}' in {}: {}", kind, template));
+    }
+    Ok(())
+}
+
+/// 替换模板中的占位符，缺少的字段替换为空
+pub fn fill_template(template: &str, values: &HashMap<&str, String>) -> String {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            rest = after;
+            break;
+        };
+        if let Some(value) = values.get(&after[..end]) {
+            filled.push_str(value);
+        }
+        rest = &after[end + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// 按重命名模板生成相对路径（不含扩展名）
+///
+/// 字段值中的路径分隔符和非法字符会被替换；缺少的字段留空，留下的空括号和空目录层级会被去掉
+pub fn render_rename_template(template: &str, values: &HashMap<&str, String>) -> String {
+    let values: HashMap<&str, String> = values.iter()
+        .map(|(field, value)| (*field, sanitize_path_segment(value)))
+        .collect();
+    let rendered = fill_template(template, &values);
+
+    rendered.split(['/', '\\'])
+        .map(|segment| {
+            let segment = segment.replace("()", "").replace("[]", "");
+            let segment = segment.split_whitespace().collect::<Vec<_>>().join(" ");
+            segment.trim_matches(|c: char| c == '.' || c == '-' || c.is_whitespace()).to_string()
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// 替换文件名中不允许出现的字符
+fn sanitize_path_segment(value: &str) -> String {
+    value.chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(path: &str) -> LibraryRoot {
+        LibraryRoot::new(CreateLibraryRootRequest {
+            path: path.to_string(),
+            label: None,
+            content_type: None,
+            auto_scan: false,
+            rename_template: None,
+            storage: None,
+        })
+    }
+
+    #[test]
+    fn test_normalize_root_path() {
+        assert_eq!(normalize_root_path(" /mnt/media/ "), "/mnt/media");
+        assert_eq!(normalize_root_path("/"), "/");
+        assert_eq!(normalize_root_path("D:\\Videos\\"), "D:\\Videos");
+        assert_eq!(normalize_root_path("D:\\"), "D:\\");
+    }
+
+    #[test]
+    fn test_find_library_root() {
+        let roots = vec![root("/mnt/media"), root("/mnt/media/jav"), root("/")];
+        assert_eq!(find_library_root(&roots, "/mnt/media/jav/a.mp4").unwrap().path, "/mnt/media/jav");
+        assert_eq!(find_library_root(&roots, "/mnt/media/movie.mkv").unwrap().path, "/mnt/media");
+        assert_eq!(find_library_root(&roots, "/mnt/media2/x.mp4").unwrap().path, "/");
+        assert!(find_library_root(&roots[..2], "/mnt/media2/x.mp4").is_none());
+    }
+
+    #[test]
+    fn test_remote_storage_root() {
+        let mut root = root("s3://media/movies");
+        assert!(root.is_local());
+        root.apply_update(UpdateLibraryRootRequest {
+            storage: Some(StorageConfig::Webdav { url: "https://nas/dav".to_string(), username: None, password: None }),
+            ..Default::default()
+        });
+        assert_eq!(root.storage_type, "webdav");
+        assert!(root.validate().is_ok());
+        assert_eq!(root.relative_path("s3://media/movies/a/b.mp4"), Some("a/b.mp4"));
+        assert_eq!(root.relative_path("s3://media/other.mp4"), None);
+        assert_eq!(root.child_path("s3://media/movies", "jav"), "s3://media/movies/jav");
+        assert_eq!(root.parent_path("s3://media/movies/jav/uncensored").as_deref(), Some("s3://media/movies/jav"));
+        assert_eq!(root.parent_path("s3://media/movies/jav").as_deref(), Some("s3://media/movies"));
+        assert_eq!(root.parent_path("s3://media/movies"), None);
+    }
+
+    #[test]
+    fn test_validate_rename_template() {
+        assert!(validate_rename_template("{studio}/{code} {title} ({year})").is_ok());
+        assert!(validate_rename_template("plain").is_ok());
+        assert!(validate_rename_template("{unknown}").is_err());
+        assert!(validate_rename_template("{title").is_err());
+        assert!(validate_rename_template("title}").is_err());
+    }
+
+    #[test]
+    fn test_render_rename_template() {
+        let values = HashMap::from([
+            ("code", "ABC-123".to_string()),
+            ("title", "Part A/B: \"Final\"".to_string()),
+            ("studio", String::new()),
+        ]);
+        assert_eq!(render_rename_template("{studio}/{code} {title} ({year})", &values), "ABC-123 Part A B Final");
+        assert_eq!(render_rename_template("{code}/{code} [{part}]", &values), "ABC-123/ABC-123");
+    }
+}
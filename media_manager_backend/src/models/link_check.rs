@@ -0,0 +1,129 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::{MediaItem, SCRAPE_FIELDS};
+
+/// 失效的远程链接
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct BrokenLink {
+    pub media_id: String,
+    /// 链接所在字段（poster_url、preview_urls、download_links 等）
+    pub field: String,
+    pub url: String,
+    /// HTTP 状态码，连接失败或超时时为空
+    pub status_code: Option<i64>,
+    pub error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// 媒体上需要检查的远程链接
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteLink {
+    pub field: &'static str,
+    pub url: String,
+}
+
+/// 一次链接检查的结果
+#[derive(Debug, Default, Serialize)]
+pub struct LinkCheckResult {
+    pub checked_media: usize,
+    pub checked_urls: usize,
+    pub broken: usize,
+    /// 之前标记为失效、本次恢复可访问的链接数
+    pub recovered: usize,
+    /// 存在失效链接的媒体ID
+    pub broken_media: Vec<String>,
+    /// 已触发重新刮削的媒体数
+    pub rescraped: usize,
+    pub errors: Vec<String>,
+}
+
+/// 收集媒体上所有 http(s) 链接（本地路径、磁力链接等不检查），同一地址只保留一次
+pub fn collect_remote_links(media: &MediaItem) -> Vec<RemoteLink> {
+    // JSON 数组中的元素可能是地址字符串，也可能是带 url 字段的对象（如带清晰度的预览视频）
+    let json_urls = |value: &Option<String>| -> Vec<String> {
+        value.as_deref()
+            .and_then(|v| serde_json::from_str::<Vec<serde_json::Value>>(v).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|item| match item {
+                serde_json::Value::String(url) => Some(url),
+                other => other.get("url").and_then(|u| u.as_str()).map(String::from),
+            })
+            .collect()
+    };
+
+    let mut candidates: Vec<(&'static str, String)> = Vec::new();
+    candidates.extend(media.poster_url.clone().map(|url| ("poster_url", url)));
+    // backdrop_url 为 JSON 数组，兼容旧数据中的单个地址
+    match media.backdrop_url.as_deref() {
+        Some(value) if value.trim_start().starts_with('[') => {
+            candidates.extend(json_urls(&media.backdrop_url).into_iter().map(|url| ("backdrop_url", url)));
+        }
+        Some(value) => candidates.push(("backdrop_url", value.to_string())),
+        None => {}
+    }
+    candidates.extend(json_urls(&media.preview_urls).into_iter().map(|url| ("preview_urls", url)));
+    candidates.extend(json_urls(&media.preview_video_urls).into_iter().map(|url| ("preview_video_urls", url)));
+    candidates.extend(media.cover_video_url.clone().map(|url| ("cover_video_url", url)));
+    candidates.extend(media.get_play_links().unwrap_or_default().into_iter().map(|l| ("play_links", l.url)));
+    candidates.extend(media.get_download_links().unwrap_or_default().into_iter().map(|l| ("download_links", l.url)));
+
+    let mut links: Vec<RemoteLink> = Vec::new();
+    for (field, url) in candidates {
+        let url = url.trim().to_string();
+        let is_remote = url.starts_with("http://") || url.starts_with("https://");
+        if is_remote && !links.iter().any(|l| l.url == url) {
+            links.push(RemoteLink { field, url });
+        }
+    }
+    links
+}
+
+/// 失效链接中可以通过重新刮削修复的字段
+pub fn rescrape_fields(links: &[BrokenLink]) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    for link in links {
+        if SCRAPE_FIELDS.contains(&link.field.as_str()) && !fields.contains(&link.field) {
+            fields.push(link.field.clone());
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MediaType;
+
+    #[test]
+    fn test_collect_remote_links() {
+        let mut media = MediaItem::new("Test".to_string(), MediaType::Movie).unwrap();
+        media.poster_url = Some("https://img.example.com/p.jpg".to_string());
+        media.backdrop_url = Some(r#"["https://img.example.com/b.jpg","/cache/b.webp"]"#.to_string());
+        media.preview_urls = Some(r#"["https://img.example.com/p.jpg","http://img.example.com/1.jpg"]"#.to_string());
+        media.preview_video_urls = Some(r#"[{"quality":"720p","url":"https://v.example.com/1.mp4"}]"#.to_string());
+        media.download_links = Some(
+            r#"[{"name":"m","url":"magnet:?xt=urn:btih:abc","link_type":"magnet","size":null,"password":null}]"#.to_string(),
+        );
+
+        let links = collect_remote_links(&media);
+        let fields: Vec<&str> = links.iter().map(|l| l.field).collect();
+        assert_eq!(fields, vec!["poster_url", "backdrop_url", "preview_urls", "preview_video_urls"]);
+        assert_eq!(links[2].url, "http://img.example.com/1.jpg");
+
+        let broken = |field: &str| BrokenLink {
+            media_id: media.id.clone(),
+            field: field.to_string(),
+            url: String::new(),
+            status_code: Some(404),
+            error: None,
+            checked_at: Utc::now(),
+        };
+        assert_eq!(
+            rescrape_fields(&[broken("poster_url"), broken("play_links"), broken("poster_url")]),
+            vec!["poster_url".to_string()]
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// 一次数据库优化的结果
+#[derive(Debug, Default, Serialize)]
+pub struct OptimizeResult {
+    pub size_before_bytes: i64,
+    pub size_after_bytes: i64,
+    /// VACUUM 回收的空间
+    pub reclaimed_bytes: i64,
+    /// 删除的孤立关联行数（指向已删除的媒体、演员或标签）
+    pub orphan_relations_removed: u64,
+    /// 重建后全文索引中的媒体数
+    pub search_index_rows: u64,
+    pub duration_ms: u64,
+}
+
+/// 某个关联表中父记录已不存在的行
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanRelations {
+    pub table: &'static str,
+    pub column: &'static str,
+    /// 父记录所在的表
+    pub parent: &'static str,
+    pub rows: u64,
+}
+
+/// 单个数据库迁移
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    /// 未执行时为空
+    pub installed_on: Option<DateTime<Utc>>,
+    /// 已执行的迁移文件在此之后被修改过
+    pub checksum_mismatch: bool,
+}
+
+/// 数据库迁移状态
+#[derive(Debug, Default, Serialize)]
+pub struct MigrationStatus {
+    /// 数据库中已执行的最新迁移版本，全新数据库为空
+    pub schema_version: Option<i64>,
+    /// 当前程序包含的最新迁移版本
+    pub latest_version: i64,
+    pub applied: Vec<MigrationInfo>,
+    pub pending: Vec<MigrationInfo>,
+    /// 数据库中已执行、但当前程序不包含的迁移（由更新版本的程序执行）
+    pub unknown: Vec<MigrationInfo>,
+}
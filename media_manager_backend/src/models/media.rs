@@ -1,10 +1,11 @@
+use std::collections::{BTreeMap, HashMap};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
 use super::validation::{ValidationError, StringValidator, NumberValidator, CollectionValidator, Validator};
 
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, PartialEq, FromRow)]
 pub struct MediaItem {
     pub id: String,
     pub code: Option<String>,             // 识别号/识别码
@@ -33,13 +34,76 @@ pub struct MediaItem {
     pub preview_urls: Option<String>,       // JSON array of preview image URLs
     pub preview_video_urls: Option<String>, // JSON array of preview video URLs
     pub cover_video_url: Option<String>,    // 封面视频URL（短小的视频缩略图，用于悬停播放）
+    pub trailer_url: Option<String>,        // 预告片地址（视频直链或 YouTube/Vimeo 页面）
     pub studio: Option<String>,             // 厂商/制作公司
     pub series: Option<String>,             // 系列
     pub scraper_name: Option<String>,       // 刮削器名称（用于缓存统计）
+    #[sqlx(default)]
+    pub image_placeholders: Option<String>, // JSON object: 图片地址 -> BlurHash（缓存图片时生成）
+    #[sqlx(default)]
+    pub poster_colors: Option<String>,      // JSON: 封面主色调（缓存封面时提取）
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// 图片的主色和强调色（#rrggbb）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageColors {
+    pub dominant: String,
+    pub accent: String,
+}
+
+/// 保存的封面主色调，记录提取时的封面地址（原始 URL 和缓存路径），封面更换后不再使用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredPosterColors {
+    pub urls: Vec<String>,
+    #[serde(flatten)]
+    pub colors: ImageColors,
+}
+
+/// 保存的封面感知哈希（16 位十六进制），与主色调一样记录提取时的封面地址
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredPosterHash {
+    pub urls: Vec<String>,
+    pub hash: String,
+}
+
+/// 相似度默认阈值：汉明距离不超过该值视为同一张封面
+pub const DUPLICATE_ARTWORK_DEFAULT_DISTANCE: u32 = 6;
+
+/// 允许的最大阈值（再大误判明显增多）
+pub const DUPLICATE_ARTWORK_MAX_DISTANCE: u32 = 16;
+
+/// 重复封面检测的候选媒体
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DuplicateArtworkCandidate {
+    pub id: String,
+    pub code: Option<String>,
+    pub title: String,
+    pub year: Option<i32>,
+    pub media_type: String,
+    pub poster_url: Option<String>,
+    #[serde(skip)]
+    pub poster_phash: Option<String>,
+}
+
+/// 封面几乎相同的一组媒体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateArtworkGroup {
+    pub media: Vec<DuplicateArtworkCandidate>,
+    /// 组内相连封面的最大汉明距离（0 表示完全相同）
+    pub distance: u32,
+}
+
+/// 重复封面报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateArtworkReport {
+    /// 检查的封面数（封面更换后尚未重新计算的不计）
+    pub media_scanned: usize,
+    pub max_distance: u32,
+    pub groups: Vec<DuplicateArtworkGroup>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum MediaType {
     Movie,
@@ -84,6 +148,11 @@ pub struct ExternalIds {
     pub tmdb_id: Option<i32>,
     pub imdb_id: Option<String>,
     pub omdb_id: Option<String>,
+    #[serde(default)]
+    pub tvdb_id: Option<i32>,
+    /// 自定义来源的ID（键为来源名，如 anidb、douban）
+    #[serde(default)]
+    pub custom: BTreeMap<String, String>,
 }
 
 impl Default for ExternalIds {
@@ -92,10 +161,41 @@ impl Default for ExternalIds {
             tmdb_id: None,
             imdb_id: None,
             omdb_id: None,
+            tvdb_id: None,
+            custom: BTreeMap::new(),
         }
     }
 }
 
+impl ExternalIds {
+    /// 校验ID格式：数字ID须为正数，IMDB ID 形如 tt1234567，自定义来源名只能包含小写字母、数字和下划线
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tmdb_id.is_some_and(|id| id <= 0) {
+            return Err("tmdb_id must be a positive integer".to_string());
+        }
+        if self.tvdb_id.is_some_and(|id| id <= 0) {
+            return Err("tvdb_id must be a positive integer".to_string());
+        }
+        if let Some(imdb_id) = &self.imdb_id {
+            let valid = imdb_id.strip_prefix("tt")
+                .is_some_and(|digits| digits.len() >= 7 && digits.chars().all(|c| c.is_ascii_digit()));
+            if !valid {
+                return Err(format!("Invalid imdb_id '{}', expected format tt1234567", imdb_id));
+            }
+        }
+        for (key, value) in &self.custom {
+            let valid_key = !key.is_empty() && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+            if !valid_key {
+                return Err(format!("Invalid custom id key '{}', use lowercase letters, digits and underscores", key));
+            }
+            if value.trim().is_empty() {
+                return Err(format!("Custom id '{}' must not be empty", key));
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Person {
     pub name: String,
@@ -264,6 +364,7 @@ pub struct UpdateMediaRequest {
     pub preview_urls: Option<Vec<String>>,
     pub preview_video_urls: Option<Vec<String>>,
     pub cover_video_url: Option<String>,
+    pub trailer_url: Option<String>,
     pub studio: Option<String>,
     pub series: Option<String>,
 }
@@ -303,9 +404,12 @@ impl MediaItem {
             preview_urls: Some("[]".to_string()),
             preview_video_urls: Some("[]".to_string()),
             cover_video_url: None,
+            trailer_url: None,
             studio: None,
             series: None,
             scraper_name: None,
+            image_placeholders: None,
+            poster_colors: None,
             created_at: now,
             updated_at: now,
         })
@@ -350,9 +454,12 @@ impl MediaItem {
             preview_urls: Some("[]".to_string()),
             preview_video_urls: Some("[]".to_string()),
             cover_video_url: None,
+            trailer_url: None,
             studio: None,
             series: None,
             scraper_name: None,
+            image_placeholders: None,
+            poster_colors: None,
             created_at: now,
             updated_at: now,
         })
@@ -575,6 +682,11 @@ impl MediaItem {
             self.set_cover_video_url(url)?;
         }
         
+        if let Some(trailer_url) = request.trailer_url {
+            let url = if trailer_url.trim().is_empty() { None } else { Some(trailer_url) };
+            self.set_trailer_url(url)?;
+        }
+        
         if let Some(studio) = request.studio {
             self.studio = if studio.trim().is_empty() { None } else { Some(studio) };
         }
@@ -810,6 +922,20 @@ impl MediaItem {
         }
     }
     
+    /// 解析图片占位图（图片地址 -> BlurHash）
+    pub fn get_image_placeholders(&self) -> HashMap<String, String> {
+        self.image_placeholders.as_ref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default()
+    }
+    
+    /// 当前封面的主色调（封面已更换或未提取时返回 None）
+    pub fn get_poster_colors(&self) -> Option<ImageColors> {
+        let poster_url = self.poster_url.as_ref()?;
+        let stored: StoredPosterColors = serde_json::from_str(self.poster_colors.as_ref()?).ok()?;
+        stored.urls.contains(poster_url).then_some(stored.colors)
+    }
+    
     /// 设置预览图URL列表
     pub fn set_preview_urls(&mut self, urls: &[String]) -> Result<(), ValidationError> {
         self.preview_urls = Some(serde_json::to_string(urls).unwrap_or_else(|_| "[]".to_string()));
@@ -845,6 +971,14 @@ impl MediaItem {
         Ok(())
     }
     
+    /// 设置预告片地址（带验证）
+    pub fn set_trailer_url(&mut self, url: Option<String>) -> Result<(), ValidationError> {
+        StringValidator::validate_url(&url)?;
+        self.trailer_url = url;
+        self.updated_at = Utc::now();
+        Ok(())
+    }
+    
     /// 检查是否为电影
     pub fn is_movie(&self) -> bool {
         matches!(self.get_media_type(), Ok(MediaType::Movie))
@@ -922,7 +1056,7 @@ impl Serialize for MediaItem {
     {
         use serde::ser::SerializeStruct;
         
-        let mut state = serializer.serialize_struct("MediaItem", 31)?;
+        let mut state = serializer.serialize_struct("MediaItem", 32)?;
         
         state.serialize_field("id", &self.id)?;
         state.serialize_field("code", &self.code)?;
@@ -993,6 +1127,7 @@ impl Serialize for MediaItem {
         state.serialize_field("preview_video_urls", &preview_video_urls)?;
         
         state.serialize_field("cover_video_url", &self.cover_video_url)?;
+        state.serialize_field("trailer_url", &self.trailer_url)?;
         state.serialize_field("studio", &self.studio)?;
         state.serialize_field("series", &self.series)?;
         state.serialize_field("scraper_name", &self.scraper_name)?;
@@ -1003,44 +1138,107 @@ impl Serialize for MediaItem {
     }
 }
 
-// 实现 Deserialize（用于从 JSON 创建 MediaItem，虽然通常不需要）
+// 实现 Deserialize（与 Serialize 对应：JSON 字段既可以是已解析的值，也可以是 JSON 字符串）
 impl<'de> Deserialize<'de> for MediaItem {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        use serde::de::{self, MapAccess, Visitor};
-        use std::fmt;
-        
+        use serde_json::Value;
+
         #[derive(Deserialize)]
-        #[serde(field_identifier, rename_all = "snake_case")]
-        enum Field {
-            Id, Code, ExternalIds, Title, OriginalTitle, Year, MediaType, Genres,
-            Rating, VoteCount, PosterUrl, BackdropUrl, Overview, Runtime, ReleaseDate,
-            Cast, Crew, Language, Country, Budget, Revenue, Status,
-            PlayLinks, DownloadLinks, PreviewUrls, PreviewVideoUrls, CoverVideoUrl,
-            Studio, Series, CreatedAt, UpdatedAt,
-        }
-        
-        struct MediaItemVisitor;
-        
-        impl<'de> Visitor<'de> for MediaItemVisitor {
-            type Value = MediaItem;
-            
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("struct MediaItem")
-            }
-            
-            fn visit_map<V>(self, _map: V) -> Result<MediaItem, V::Error>
-            where
-                V: MapAccess<'de>,
-            {
-                // 这里简化处理，实际使用中通常不需要从 JSON 反序列化到 MediaItem
-                // 因为 MediaItem 主要是从数据库加载的
-                Err(de::Error::custom("MediaItem deserialization not fully implemented"))
+        struct RawMediaItem {
+            id: String,
+            code: Option<String>,
+            #[serde(default)]
+            external_ids: Value,
+            title: String,
+            original_title: Option<String>,
+            year: Option<i32>,
+            media_type: String,
+            #[serde(default)]
+            genres: Value,
+            rating: Option<f32>,
+            vote_count: Option<i32>,
+            poster_url: Option<String>,
+            #[serde(default)]
+            backdrop_url: Value,
+            overview: Option<String>,
+            runtime: Option<i32>,
+            release_date: Option<String>,
+            #[serde(default)]
+            cast: Value,
+            #[serde(default)]
+            crew: Value,
+            language: Option<String>,
+            country: Option<String>,
+            budget: Option<i64>,
+            revenue: Option<i64>,
+            status: Option<String>,
+            #[serde(default)]
+            play_links: Value,
+            #[serde(default)]
+            download_links: Value,
+            #[serde(default)]
+            preview_urls: Value,
+            #[serde(default)]
+            preview_video_urls: Value,
+            cover_video_url: Option<String>,
+            trailer_url: Option<String>,
+            studio: Option<String>,
+            series: Option<String>,
+            scraper_name: Option<String>,
+            created_at: Option<DateTime<Utc>>,
+            updated_at: Option<DateTime<Utc>>,
+        }
+
+        /// 数据库中以 JSON 字符串保存的字段：字符串原样保存，其他值序列化为字符串
+        fn json_text(value: Value) -> Option<String> {
+            match value {
+                Value::Null => None,
+                Value::String(s) => Some(s),
+                other => Some(other.to_string()),
             }
         }
-        
-        deserializer.deserialize_struct("MediaItem", &[], MediaItemVisitor)
+
+        let raw = RawMediaItem::deserialize(deserializer)?;
+        let now = Utc::now();
+        Ok(MediaItem {
+            id: raw.id,
+            code: raw.code,
+            external_ids: json_text(raw.external_ids).unwrap_or_else(|| "{}".to_string()),
+            title: raw.title,
+            original_title: raw.original_title,
+            year: raw.year,
+            media_type: raw.media_type,
+            genres: json_text(raw.genres).unwrap_or_else(|| "[]".to_string()),
+            rating: raw.rating,
+            vote_count: raw.vote_count,
+            poster_url: raw.poster_url,
+            backdrop_url: json_text(raw.backdrop_url),
+            overview: raw.overview,
+            runtime: raw.runtime,
+            release_date: raw.release_date,
+            cast: json_text(raw.cast),
+            crew: json_text(raw.crew),
+            language: raw.language,
+            country: raw.country,
+            budget: raw.budget,
+            revenue: raw.revenue,
+            status: raw.status,
+            play_links: json_text(raw.play_links),
+            download_links: json_text(raw.download_links),
+            preview_urls: json_text(raw.preview_urls),
+            preview_video_urls: json_text(raw.preview_video_urls),
+            cover_video_url: raw.cover_video_url,
+            trailer_url: raw.trailer_url,
+            studio: raw.studio,
+            series: raw.series,
+            scraper_name: raw.scraper_name,
+            image_placeholders: None,
+            poster_colors: None,
+            created_at: raw.created_at.unwrap_or(now),
+            updated_at: raw.updated_at.unwrap_or(now),
+        })
     }
 }
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::path::Path;
+
+/// 附加内容所在的文件夹名（位于媒体文件所在目录下）
+pub const EXTRAS_DIR_NAME: &str = "extras";
+
+/// 附加内容类型
+pub const EXTRA_TYPES: [&str; 7] = [
+    "trailer", "behind_the_scenes", "featurette", "deleted_scene", "interview", "gallery", "other",
+];
+
+/// 附加内容支持的扩展名（视频 + 图集图片）
+const EXTRA_IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "gif", "bmp"];
+
+/// 子文件夹名 / 文件名关键字 → 类型（按顺序匹配，比较时去掉空格、下划线和连字符）
+const EXTRA_KEYWORDS: &[(&str, &str)] = &[
+    ("trailer", "trailer"),
+    ("teaser", "trailer"),
+    ("behindthescenes", "behind_the_scenes"),
+    ("makingof", "behind_the_scenes"),
+    ("featurette", "featurette"),
+    ("deletedscene", "deleted_scene"),
+    ("deleted", "deleted_scene"),
+    ("interview", "interview"),
+    ("gallery", "gallery"),
+    ("galleries", "gallery"),
+    ("photos", "gallery"),
+    ("images", "gallery"),
+];
+
+/// 媒体附加内容（预告片、花絮、图集等）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MediaExtra {
+    pub id: String,
+    pub media_id: String,
+    pub file_path: String,
+    pub file_name: String,
+    pub file_size: i64,
+    pub extra_type: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MediaExtra {
+    pub fn new(media_id: String, file_path: String, file_name: String, file_size: i64, extra_type: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            media_id,
+            file_path,
+            file_name,
+            file_size,
+            extra_type,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// 是否为附加内容文件夹
+pub fn is_extras_dir(path: &Path) -> bool {
+    path.file_name()
+        .map(|n| n.to_string_lossy().eq_ignore_ascii_case(EXTRAS_DIR_NAME))
+        .unwrap_or(false)
+}
+
+/// 是否为图集图片
+pub fn is_extra_image(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| EXTRA_IMAGE_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// 根据 Extras/ 下的相对路径判断类型：先看子文件夹名，再看文件名，图片归为图集
+pub fn classify_extra(relative_path: &Path) -> &'static str {
+    let normalize = |s: &str| s.to_lowercase().replace([' ', '_', '-', '.'], "");
+    let find = |name: &str| {
+        let name = normalize(name);
+        EXTRA_KEYWORDS.iter()
+            .find(|(keyword, _)| name.contains(keyword))
+            .map(|(_, extra_type)| *extra_type)
+    };
+
+    let dirs = relative_path.parent()
+        .map(|p| p.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    if let Some(extra_type) = dirs.iter().rev().find_map(|d| find(d)) {
+        return extra_type;
+    }
+    if let Some(extra_type) = relative_path.file_stem().and_then(|s| find(&s.to_string_lossy())) {
+        return extra_type;
+    }
+    if is_extra_image(relative_path) {
+        "gallery"
+    } else {
+        "other"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_extra() {
+        assert_eq!(classify_extra(Path::new("Trailers/main.mp4")), "trailer");
+        assert_eq!(classify_extra(Path::new("Behind The Scenes/day1.mkv")), "behind_the_scenes");
+        assert_eq!(classify_extra(Path::new("Deleted Scenes/scene.mp4")), "deleted_scene");
+        assert_eq!(classify_extra(Path::new("movie-trailer.mp4")), "trailer");
+        assert_eq!(classify_extra(Path::new("Making_Of.mp4")), "behind_the_scenes");
+        assert_eq!(classify_extra(Path::new("stills/001.jpg")), "gallery");
+        assert_eq!(classify_extra(Path::new("bonus.mp4")), "other");
+        assert!(is_extras_dir(Path::new("/movies/ABC-123/Extras")));
+        assert!(!is_extras_dir(Path::new("/movies/Extras Edition")));
+    }
+}
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use super::collection::WatchStatus;
+
 /// 媒体文件模型 - 用于存储多分段视频文件
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct MediaFile {
@@ -12,8 +14,31 @@ pub struct MediaFile {
     pub part_number: Option<i32>,
     pub part_label: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// 所属版本（如 Director's Cut、4K Remaster），为空表示默认版本
+    #[serde(default)]
+    pub edition: Option<String>,
+    /// 观看进度（0-1），未看过为空
+    #[serde(default)]
+    pub watch_progress: Option<f32>,
+    /// 看完的时间，未看完为空
+    #[serde(default)]
+    pub watched_at: Option<DateTime<Utc>>,
+}
+
+/// 媒体的一个版本及其文件（按分段排序）
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaEdition {
+    pub edition: Option<String>,
+    pub label: String,
+    pub files: Vec<MediaFile>,
+    pub total_size: i64,
+    /// 已看完的分段数
+    pub watched_parts: usize,
 }
 
+/// 默认版本的显示名称
+pub const DEFAULT_EDITION_LABEL: &str = "Default";
+
 impl MediaFile {
     /// 创建新的媒体文件记录
     pub fn new(
@@ -31,9 +56,18 @@ impl MediaFile {
             part_number,
             part_label,
             created_at: Utc::now(),
+            edition: None,
+            watch_progress: None,
+            watched_at: None,
         }
     }
 
+    /// 设置所属版本（空白视为默认版本）
+    pub fn with_edition(mut self, edition: Option<String>) -> Self {
+        self.edition = normalize_edition(edition.as_deref());
+        self
+    }
+
     /// 获取显示名称
     pub fn display_name(&self) -> String {
         if let Some(ref label) = self.part_label {
@@ -50,12 +84,82 @@ impl MediaFile {
         }
     }
 
+    /// 是否已看完
+    pub fn is_watched(&self) -> bool {
+        self.watched_at.is_some()
+    }
+
+    /// 观看进度（看完的按 1 计算）
+    pub fn progress(&self) -> f32 {
+        if self.is_watched() {
+            1.0
+        } else {
+            self.watch_progress.unwrap_or(0.0)
+        }
+    }
+
     /// 格式化文件大小
     pub fn formatted_size(&self) -> String {
         format_file_size(self.file_size)
     }
 }
 
+/// 规范化版本名称：去掉首尾空白，空字符串视为默认版本
+pub fn normalize_edition(edition: Option<&str>) -> Option<String> {
+    edition.map(str::trim).filter(|e| !e.is_empty()).map(String::from)
+}
+
+/// 按版本分组文件（默认版本在前，其余按首次出现顺序），保持组内原有顺序
+pub fn group_by_edition(files: Vec<MediaFile>) -> Vec<MediaEdition> {
+    let mut editions: Vec<MediaEdition> = Vec::new();
+    for file in files {
+        let key = normalize_edition(file.edition.as_deref());
+        let index = match editions.iter().position(|e| e.edition.as_deref().map(str::to_lowercase) == key.as_deref().map(str::to_lowercase)) {
+            Some(index) => index,
+            None => {
+                editions.push(MediaEdition {
+                    label: key.clone().unwrap_or_else(|| DEFAULT_EDITION_LABEL.to_string()),
+                    edition: key,
+                    files: Vec::new(),
+                    total_size: 0,
+                    watched_parts: 0,
+                });
+                editions.len() - 1
+            }
+        };
+        editions[index].total_size += file.file_size;
+        editions[index].watched_parts += usize::from(file.is_watched());
+        editions[index].files.push(file);
+    }
+    editions.sort_by_key(|e| e.edition.is_some());
+    editions
+}
+
+/// 选择要播放的文件：指定版本（不区分大小写，为空时取第一个版本）中的第 part 个文件
+pub fn select_edition_file(files: Vec<MediaFile>, edition: Option<&str>, part: usize) -> Option<MediaFile> {
+    let editions = group_by_edition(files);
+    let edition = match normalize_edition(edition) {
+        Some(name) => editions.into_iter().find(|e| e.edition.as_deref().is_some_and(|n| n.eq_ignore_ascii_case(&name)))?,
+        None => editions.into_iter().next()?,
+    };
+    edition.files.into_iter().nth(part)
+}
+
+/// 由各分段的进度推算整体观看状态和进度：全部看完为已完成，看过任一分段为正在观看，否则为想看
+pub fn derive_watch_state(parts: &[MediaFile]) -> (WatchStatus, f32) {
+    if parts.is_empty() {
+        return (WatchStatus::WantToWatch, 0.0);
+    }
+    let progress = parts.iter().map(MediaFile::progress).sum::<f32>() / parts.len() as f32;
+    if parts.iter().all(MediaFile::is_watched) {
+        (WatchStatus::Completed, 1.0)
+    } else if progress > 0.0 {
+        (WatchStatus::Watching, progress.min(0.99))
+    } else {
+        (WatchStatus::WantToWatch, 0.0)
+    }
+}
+
 /// 格式化文件大小为人类可读格式
 pub fn format_file_size(size: i64) -> String {
     const KB: i64 = 1024;
@@ -118,4 +222,47 @@ mod tests {
         );
         assert_eq!(file3.display_name(), "movie.mp4");
     }
+
+    #[test]
+    fn test_derive_watch_state() {
+        let mut parts: Vec<MediaFile> = (1..=4)
+            .map(|part| MediaFile::new("media-123".to_string(), format!("/cd{}.mp4", part), 100, Some(part), None))
+            .collect();
+        assert_eq!(derive_watch_state(&parts), (WatchStatus::WantToWatch, 0.0));
+
+        parts[0].watched_at = Some(Utc::now());
+        parts[1].watch_progress = Some(0.5);
+        assert_eq!(derive_watch_state(&parts), (WatchStatus::Watching, 0.375));
+
+        for part in &mut parts {
+            part.watched_at = Some(Utc::now());
+        }
+        assert_eq!(derive_watch_state(&parts), (WatchStatus::Completed, 1.0));
+    }
+
+    #[test]
+    fn test_group_by_edition() {
+        let file = |path: &str, part: i32, edition: Option<&str>| {
+            MediaFile::new("media-123".to_string(), path.to_string(), 100, Some(part), None)
+                .with_edition(edition.map(String::from))
+        };
+        let files = vec![
+            file("/dc/cd1.mp4", 1, Some("Director's Cut")),
+            file("/cd1.mp4", 1, None),
+            file("/dc/cd2.mp4", 2, Some("director's cut")),
+            file("/cd2.mp4", 2, Some("  ")),
+            file("/4k.mp4", 1, Some("4K Remaster")),
+        ];
+
+        let editions = group_by_edition(files.clone());
+        let labels: Vec<&str> = editions.iter().map(|e| e.label.as_str()).collect();
+        assert_eq!(labels, vec![DEFAULT_EDITION_LABEL, "Director's Cut", "4K Remaster"]);
+        assert_eq!(editions[0].files.len(), 2);
+        assert_eq!(editions[1].total_size, 200);
+
+        assert_eq!(select_edition_file(files.clone(), None, 1).unwrap().file_path, "/cd2.mp4");
+        assert_eq!(select_edition_file(files.clone(), Some("DIRECTOR'S CUT"), 1).unwrap().file_path, "/dc/cd2.mp4");
+        assert!(select_edition_file(files.clone(), Some("4K Remaster"), 1).is_none());
+        assert!(select_edition_file(files, Some("Unknown"), 0).is_none());
+    }
 }
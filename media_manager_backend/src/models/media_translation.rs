@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 媒体的单语言标题和简介
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, PartialEq)]
+pub struct MediaTranslation {
+    pub media_id: String,
+    pub language: String,
+    pub title: Option<String>,
+    pub overview: Option<String>,
+    pub source: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 写入翻译的请求项（也是刮削插件 translations 字段的格式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationInput {
+    pub language: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overview: Option<String>,
+}
+
+impl MediaTranslation {
+    /// 从请求项创建，语言标签无效或标题简介都为空时返回 None
+    pub fn from_input(media_id: &str, input: &TranslationInput, source: &str) -> Option<Self> {
+        let non_empty = |s: &Option<String>| s.as_deref().map(str::trim).filter(|s| !s.is_empty()).map(String::from);
+        let (title, overview) = (non_empty(&input.title), non_empty(&input.overview));
+        if title.is_none() && overview.is_none() {
+            return None;
+        }
+        Some(Self {
+            media_id: media_id.to_string(),
+            language: normalize_language(&input.language)?,
+            title,
+            overview,
+            source: Some(source.to_string()),
+            updated_at: Utc::now(),
+        })
+    }
+}
+
+/// 规范化语言标签：语言小写、地区大写（zh_cn → zh-CN），无效时返回 None
+pub fn normalize_language(tag: &str) -> Option<String> {
+    let mut parts = tag.trim().split(['-', '_']);
+    let language = parts.next().filter(|l| (2..=3).contains(&l.len()) && l.chars().all(|c| c.is_ascii_alphabetic()))?;
+    let mut normalized = language.to_lowercase();
+    if let Some(region) = parts.next().filter(|r| !r.is_empty()) {
+        if !region.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        normalized.push('-');
+        normalized.push_str(&if region.len() == 2 { region.to_uppercase() } else { region.to_string() });
+    }
+    Some(normalized)
+}
+
+/// 解析 Accept-Language 请求头，按权重从高到低返回语言标签（忽略 * 和 q=0）
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut languages: Vec<(String, f32)> = header.split(',')
+        .filter_map(|part| {
+            let mut pieces = part.split(';');
+            let language = normalize_language(pieces.next()?)?;
+            let quality = pieces
+                .find_map(|p| p.trim().strip_prefix("q=").and_then(|q| q.parse::<f32>().ok()))
+                .unwrap_or(1.0);
+            (quality > 0.0).then_some((language, quality))
+        })
+        .collect();
+    languages.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    languages.into_iter().map(|(l, _)| l).collect()
+}
+
+/// 按偏好语言选出翻译：先完全匹配，再按主语言匹配（zh-TW 可回退到 zh、zh-CN）
+pub fn pick_translation<'a>(translations: &'a [MediaTranslation], preferred: &[String]) -> Option<&'a MediaTranslation> {
+    let primary = |tag: &str| tag.split('-').next().unwrap_or(tag).to_string();
+    preferred.iter().find_map(|language| {
+        translations.iter().find(|t| &t.language == language)
+            .or_else(|| translations.iter().find(|t| primary(&t.language) == primary(language)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translation(language: &str) -> MediaTranslation {
+        MediaTranslation {
+            media_id: "m".to_string(),
+            language: language.to_string(),
+            title: Some(language.to_string()),
+            overview: None,
+            source: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_language() {
+        assert_eq!(normalize_language("zh_cn").as_deref(), Some("zh-CN"));
+        assert_eq!(normalize_language(" EN ").as_deref(), Some("en"));
+        assert_eq!(normalize_language("zh-Hant").as_deref(), Some("zh-Hant"));
+        assert_eq!(normalize_language("*"), None);
+        assert_eq!(normalize_language(""), None);
+    }
+
+    #[test]
+    fn test_accept_language_pick() {
+        let preferred = parse_accept_language("ja;q=0.5, zh-TW, en;q=0.8, *;q=0.1, fr;q=0");
+        assert_eq!(preferred, vec!["zh-TW", "en", "ja"]);
+
+        let translations = vec![translation("en"), translation("zh-CN"), translation("ja")];
+        assert_eq!(pick_translation(&translations, &preferred).unwrap().language, "zh-CN");
+        assert_eq!(pick_translation(&translations, &["ja".to_string()]).unwrap().language, "ja");
+        assert!(pick_translation(&translations, &["de".to_string()]).is_none());
+    }
+}
@@ -1,5 +1,7 @@
 pub mod media;
 pub mod media_file;
+pub mod media_extra;
+pub mod media_translation;
 pub mod collection;
 pub mod search;
 pub mod validation;
@@ -7,13 +9,73 @@ pub mod dto;
 pub mod factory;
 pub mod actor;
 pub mod studio;
+pub mod calendar;
+pub mod wanted;
+pub mod quality;
+pub mod change;
+pub mod peer;
+pub mod share;
+pub mod dry_run;
+pub mod scrape_field;
+pub mod filename_rule;
+pub mod scan_session;
+pub mod link_check;
+pub mod scrape_source;
+pub mod favorite;
+pub mod browsing_history;
+pub mod idempotency;
+pub mod filter_options;
+pub mod maintenance;
+pub mod library_root;
+pub mod import_hook;
+pub mod storage;
+pub mod enrichment;
+pub mod tmdb_list;
+pub mod swarm_stats;
+pub mod search_suggestion;
+pub mod thumbnail;
+pub mod ffmpeg;
+pub mod playback;
+pub mod stream_session;
+pub mod offline_package;
 
 pub use media::*;
 pub use media_file::*;
+pub use media_extra::*;
+pub use media_translation::*;
 pub use collection::*;
 pub use search::*;
 pub use validation::ValidationError;
 pub use dto::*;
 pub use factory::*;
 pub use actor::*;
-pub use studio::*;
\ No newline at end of file
+pub use studio::*;
+pub use calendar::*;
+pub use wanted::*;
+pub use quality::*;
+pub use change::*;
+pub use peer::*;
+pub use share::*;
+pub use dry_run::*;
+pub use scrape_field::*;
+pub use filename_rule::*;
+pub use scan_session::*;
+pub use link_check::*;
+pub use scrape_source::*;
+pub use favorite::*;
+pub use browsing_history::*;
+pub use idempotency::*;
+pub use filter_options::*;
+pub use maintenance::*;
+pub use library_root::*;
+pub use import_hook::*;
+pub use storage::*;
+pub use enrichment::*;
+pub use tmdb_list::*;
+pub use swarm_stats::*;
+pub use search_suggestion::*;
+pub use thumbnail::*;
+pub use ffmpeg::*;
+pub use playback::*;
+pub use stream_session::*;
+pub use offline_package::*;
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 离线包状态
+pub const OFFLINE_PENDING: &str = "pending";
+pub const OFFLINE_RUNNING: &str = "running";
+pub const OFFLINE_READY: &str = "ready";
+pub const OFFLINE_FAILED: &str = "failed";
+pub const OFFLINE_DOWNLOADED: &str = "downloaded";
+pub const OFFLINE_EXPIRED: &str = "expired";
+
+/// 默认/最小/最大包大小（MB）
+pub const DEFAULT_OFFLINE_MAX_SIZE_MB: i64 = 1500;
+pub const MIN_OFFLINE_MAX_SIZE_MB: i64 = 50;
+pub const MAX_OFFLINE_MAX_SIZE_MB: i64 = 8192;
+
+/// 默认/最小/最大视频高度
+pub const DEFAULT_OFFLINE_MAX_HEIGHT: i64 = 720;
+pub const MIN_OFFLINE_MAX_HEIGHT: i64 = 240;
+pub const MAX_OFFLINE_MAX_HEIGHT: i64 = 1080;
+
+/// 转码完成后下载链接的有效期（小时），过期后删除文件
+pub const OFFLINE_PACKAGE_TTL_HOURS: i64 = 72;
+
+/// 离线包的音频码率（bit/s）
+pub const OFFLINE_AUDIO_BITRATE: i64 = 128_000;
+
+/// 视频码率下限（bit/s），低于该值时画面不可用，直接失败
+pub const MIN_OFFLINE_VIDEO_BITRATE: i64 = 200_000;
+
+/// 离线下载包
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OfflinePackage {
+    pub id: String,
+    pub media_id: String,
+    pub edition: Option<String>,
+    pub part: i64,
+    pub max_size_mb: i64,
+    pub max_height: i64,
+    /// pending / running / ready / failed / downloaded / expired
+    pub status: String,
+    #[serde(skip)]
+    pub file_path: Option<String>,
+    pub file_size: Option<i64>,
+    pub error: Option<String>,
+    #[serde(skip)]
+    pub download_token: Option<String>,
+    pub downloading_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub downloaded_at: Option<DateTime<Utc>>,
+}
+
+/// 离线包及下载地址（就绪后才有）
+#[derive(Debug, Serialize)]
+pub struct OfflinePackageResponse {
+    #[serde(flatten)]
+    pub package: OfflinePackage,
+    /// 一次性下载地址（相对路径）
+    pub download_url: Option<String>,
+}
+
+impl From<OfflinePackage> for OfflinePackageResponse {
+    fn from(package: OfflinePackage) -> Self {
+        let download_url = package.download_token.as_deref()
+            .filter(|_| package.status == OFFLINE_READY)
+            .map(|token| format!("/api/public/offline/{}", token));
+        Self { package, download_url }
+    }
+}
+
+/// 创建离线包请求
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CreateOfflinePackageRequest {
+    /// 版本名称（默认版本）
+    pub edition: Option<String>,
+    /// 版本内的分段索引（从 0 开始）
+    pub part: usize,
+    /// 文件大小上限（MB），默认 1500
+    pub max_size_mb: Option<i64>,
+    /// 视频高度上限，默认 720
+    pub max_height: Option<i64>,
+}
+
+impl CreateOfflinePackageRequest {
+    pub fn max_size_mb(&self) -> i64 {
+        self.max_size_mb.unwrap_or(DEFAULT_OFFLINE_MAX_SIZE_MB)
+    }
+
+    pub fn max_height(&self) -> i64 {
+        self.max_height.unwrap_or(DEFAULT_OFFLINE_MAX_HEIGHT)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_OFFLINE_MAX_SIZE_MB..=MAX_OFFLINE_MAX_SIZE_MB).contains(&self.max_size_mb()) {
+            return Err(format!(
+                "max_size_mb must be between {} and {}", MIN_OFFLINE_MAX_SIZE_MB, MAX_OFFLINE_MAX_SIZE_MB
+            ));
+        }
+        if !(MIN_OFFLINE_MAX_HEIGHT..=MAX_OFFLINE_MAX_HEIGHT).contains(&self.max_height()) {
+            return Err(format!(
+                "max_height must be between {} and {}", MIN_OFFLINE_MAX_HEIGHT, MAX_OFFLINE_MAX_HEIGHT
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 按时长和大小上限计算视频码率（预留 5% 给容器开销），太低时返回 None
+pub fn offline_video_bitrate(max_size_mb: i64, duration_secs: f64) -> Option<i64> {
+    if duration_secs <= 0.0 {
+        return None;
+    }
+    let total = (max_size_mb * 1024 * 1024 * 8) as f64 * 0.95 / duration_secs;
+    Some(total as i64 - OFFLINE_AUDIO_BITRATE).filter(|bitrate| *bitrate >= MIN_OFFLINE_VIDEO_BITRATE)
+}
+
+/// 输出比源文件短超过 1 秒或 1%（取较大者）时视为被 -fs 截断
+pub fn offline_output_truncated(source_secs: f64, output_secs: f64) -> bool {
+    source_secs - output_secs > (source_secs * 0.01).max(1.0)
+}
+
+/// 输出被截断后按实际编码的比例降低视频码率（再预留 5%），太低时返回 None
+pub fn reduced_offline_bitrate(video_bitrate: i64, source_secs: f64, output_secs: f64) -> Option<i64> {
+    let ratio = (output_secs / source_secs).clamp(0.0, 1.0) * 0.95;
+    Some((video_bitrate as f64 * ratio) as i64).filter(|bitrate| *bitrate >= MIN_OFFLINE_VIDEO_BITRATE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_video_bitrate() {
+        // 2 小时的电影压到 1500MB：约 1.53 Mbit/s 视频
+        let bitrate = offline_video_bitrate(1500, 7200.0).unwrap();
+        assert!((1_400_000..1_600_000).contains(&bitrate), "{}", bitrate);
+        assert_eq!(offline_video_bitrate(50, 7200.0), None);
+        assert_eq!(offline_video_bitrate(1500, 0.0), None);
+    }
+
+    #[test]
+    fn test_truncated_output() {
+        assert!(!offline_output_truncated(7200.0, 7199.5));
+        assert!(!offline_output_truncated(7200.0, 7150.0));
+        assert!(offline_output_truncated(7200.0, 6800.0));
+        assert!(offline_output_truncated(60.0, 58.5));
+
+        // 只编码了 90%，码率降到约 85%
+        assert_eq!(reduced_offline_bitrate(1_000_000, 7200.0, 6480.0), Some(855_000));
+        assert_eq!(reduced_offline_bitrate(1_000_000, 7200.0, 360.0), None);
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert!(CreateOfflinePackageRequest::default().validate().is_ok());
+        let request = CreateOfflinePackageRequest { max_size_mb: Some(10), ..Default::default() };
+        assert!(request.validate().unwrap_err().starts_with("max_size_mb"));
+        let request = CreateOfflinePackageRequest { max_height: Some(2160), ..Default::default() };
+        assert!(request.validate().unwrap_err().starts_with("max_height"));
+    }
+}
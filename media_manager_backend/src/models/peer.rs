@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::ChangeEntry;
+
+/// 同步对端
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SyncPeer {
+    pub id: String,
+    pub name: String,
+    pub base_url: String,
+    /// 对端要求的同步令牌（不在API响应中返回）
+    #[serde(skip_serializing)]
+    pub token: Option<String>,
+    pub remote_instance_id: Option<String>,
+    pub last_pulled_seq: i64,
+    pub last_pushed_seq: i64,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 添加同步对端请求
+#[derive(Debug, Deserialize)]
+pub struct CreatePeerRequest {
+    pub name: String,
+    /// 对端地址，如 https://media.example.com
+    pub base_url: String,
+    pub token: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// 对端推送过来的变更
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PushChangesRequest {
+    /// 推送方实例ID
+    pub origin: String,
+    pub changes: Vec<ChangeEntry>,
+}
+
+/// 变更应用结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApplyChangesResult {
+    pub applied: usize,
+    /// 因本地更新（最后写入优先）或无需处理而跳过的变更
+    pub skipped: usize,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+/// 与单个对端的同步结果
+#[derive(Debug, Default, Serialize)]
+pub struct PeerSyncResult {
+    pub peer_id: String,
+    pub pulled: usize,
+    pub pushed: usize,
+    pub applied: usize,
+    pub skipped: usize,
+    pub errors: Vec<String>,
+}
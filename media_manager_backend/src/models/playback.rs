@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+use super::MediaProbe;
+
+/// 播放方式：直接播放原文件
+pub const PLAY_DIRECT: &str = "direct_play";
+/// 播放方式：不重新编码，只换容器
+pub const PLAY_REMUX: &str = "remux";
+/// 播放方式：重新编码
+pub const PLAY_TRANSCODE: &str = "transcode";
+
+/// 转码输出支持的容器
+pub const TRANSCODE_CONTAINERS: &[&str] = &["mp4", "matroska"];
+/// 转码输出的视频编码（copy 表示不重新编码）
+pub const TRANSCODE_VIDEO_CODECS: &[&str] = &["copy", "h264"];
+/// 转码输出的音频编码（copy 表示不重新编码）
+pub const TRANSCODE_AUDIO_CODECS: &[&str] = &["copy", "aac"];
+
+/// 客户端上报的播放能力（列表为空表示不限制）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PlayDecisionRequest {
+    /// 版本名称（默认播放默认版本）
+    pub edition: Option<String>,
+    /// 版本内的分段索引（从 0 开始）
+    pub part: usize,
+    /// 支持的容器，如 mp4、mkv、webm
+    pub containers: Vec<String>,
+    /// 支持的视频编码，如 h264、hevc、av1
+    pub video_codecs: Vec<String>,
+    /// 支持的音频编码，如 aac、mp3、opus
+    pub audio_codecs: Vec<String>,
+    /// 最大码率（bit/s）
+    pub max_bitrate: Option<i64>,
+    /// 最大视频高度（像素）
+    pub max_height: Option<i64>,
+}
+
+/// 播放方案
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlayPlan {
+    /// direct_play / remux / transcode
+    pub method: String,
+    /// 输出容器（直接播放时为原文件的容器）
+    pub container: Option<String>,
+    /// 输出视频编码，copy 表示不重新编码
+    pub video_codec: Option<String>,
+    /// 输出音频编码，copy 表示不重新编码
+    pub audio_codec: Option<String>,
+    /// 不能直接播放的原因
+    pub reasons: Vec<String>,
+}
+
+/// 播放决策结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayDecision {
+    #[serde(flatten)]
+    pub plan: PlayPlan,
+    pub stream_url: String,
+    pub file_id: String,
+    /// 是否有探测到的技术信息（没有时默认直接播放）
+    pub probed: bool,
+}
+
+/// 统一编码名称的写法
+fn normalize_codec(codec: &str) -> String {
+    match codec.trim().to_lowercase().as_str() {
+        "avc" | "avc1" | "h.264" | "x264" => "h264".to_string(),
+        "h265" | "h.265" | "hvc1" | "hev1" | "x265" => "hevc".to_string(),
+        "vp09" => "vp9".to_string(),
+        "av01" => "av1".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// 统一容器名称的写法（ffprobe 的 format_name 为逗号分隔的多个名称）
+fn normalize_container(container: &str) -> String {
+    let names: Vec<String> = container.split(',').map(|name| name.trim().to_lowercase()).collect();
+    let has = |name: &str| names.iter().any(|n| n == name);
+    if has("mp4") || has("mov") || has("m4v") {
+        "mp4".to_string()
+    } else if has("matroska") || has("mkv") {
+        "matroska".to_string()
+    } else if has("mpegts") || has("ts") {
+        "mpegts".to_string()
+    } else {
+        names.into_iter().next().unwrap_or_default()
+    }
+}
+
+fn supports(list: &[String], value: &str, normalize: fn(&str) -> String) -> bool {
+    list.is_empty() || list.iter().any(|item| normalize(item) == normalize(value))
+}
+
+/// 根据文件的技术信息和客户端能力决定播放方式：
+/// 编码和码率/分辨率都满足时直接播放（容器不支持时只换容器），否则重新编码不支持的部分
+pub fn decide_playback(probe: Option<&MediaProbe>, request: &PlayDecisionRequest) -> PlayPlan {
+    let Some(probe) = probe else {
+        return PlayPlan {
+            method: PLAY_DIRECT.to_string(),
+            container: None,
+            video_codec: None,
+            audio_codec: None,
+            reasons: vec!["not_probed".to_string()],
+        };
+    };
+
+    let mut reasons = Vec::new();
+    let video_ok = probe.video_codec.as_deref().is_none_or(|codec| supports(&request.video_codecs, codec, normalize_codec));
+    if !video_ok {
+        reasons.push("video_codec_not_supported".to_string());
+    }
+    let audio_ok = probe.audio_codec.as_deref().is_none_or(|codec| supports(&request.audio_codecs, codec, normalize_codec));
+    if !audio_ok {
+        reasons.push("audio_codec_not_supported".to_string());
+    }
+    let bitrate_ok = match (probe.bit_rate, request.max_bitrate) {
+        (Some(bit_rate), Some(max)) => bit_rate <= max,
+        _ => true,
+    };
+    if !bitrate_ok {
+        reasons.push("bitrate_exceeds_limit".to_string());
+    }
+    let height_ok = match (probe.height, request.max_height) {
+        (Some(height), Some(max)) => height <= max,
+        _ => true,
+    };
+    if !height_ok {
+        reasons.push("resolution_exceeds_limit".to_string());
+    }
+    let container = probe.container.as_deref().map(normalize_container);
+    let container_ok = container.as_deref().is_none_or(|c| supports(&request.containers, c, normalize_container));
+    if !container_ok {
+        reasons.push("container_not_supported".to_string());
+    }
+
+    let copy_video = video_ok && bitrate_ok && height_ok;
+    if copy_video && audio_ok && container_ok {
+        return PlayPlan {
+            method: PLAY_DIRECT.to_string(),
+            container,
+            video_codec: probe.video_codec.clone(),
+            audio_codec: probe.audio_codec.clone(),
+            reasons,
+        };
+    }
+
+    // 输出容器：客户端支持 mp4 或 mkv 时用支持的那个，否则用兼容性最好的 mp4
+    let target = TRANSCODE_CONTAINERS.iter()
+        .find(|c| !request.containers.is_empty() && supports(&request.containers, c, normalize_container))
+        .unwrap_or(&"mp4");
+    let method = if copy_video && audio_ok { PLAY_REMUX } else { PLAY_TRANSCODE };
+    PlayPlan {
+        method: method.to_string(),
+        container: Some(target.to_string()),
+        video_codec: Some(if copy_video { "copy" } else { "h264" }.to_string()),
+        audio_codec: Some(if audio_ok { "copy" } else { "aac" }.to_string()),
+        reasons,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn probe(container: &str, video: &str, audio: &str, height: i64) -> MediaProbe {
+        MediaProbe {
+            file_path: "/library/ABC-123.mkv".to_string(),
+            media_id: "m1".to_string(),
+            container: Some(container.to_string()),
+            duration_secs: Some(3600.0),
+            bit_rate: Some(8_000_000),
+            width: None,
+            height: Some(height),
+            video_codec: Some(video.to_string()),
+            audio_codec: Some(audio.to_string()),
+            probed_at: Utc::now(),
+        }
+    }
+
+    fn client(containers: &[&str], video: &[&str], audio: &[&str]) -> PlayDecisionRequest {
+        let list = |items: &[&str]| items.iter().map(|s| s.to_string()).collect();
+        PlayDecisionRequest {
+            containers: list(containers),
+            video_codecs: list(video),
+            audio_codecs: list(audio),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_decide_playback() {
+        let browser = client(&["mp4", "webm"], &["avc", "vp9"], &["aac", "opus"]);
+
+        let plan = decide_playback(Some(&probe("mov,mp4,m4a,3gp,3g2,mj2", "h264", "aac", 1080)), &browser);
+        assert_eq!(plan.method, PLAY_DIRECT);
+        assert!(plan.reasons.is_empty());
+
+        // 编码都支持，只换容器
+        let plan = decide_playback(Some(&probe("matroska,webm", "h264", "aac", 1080)), &browser);
+        assert_eq!(plan.method, PLAY_REMUX);
+        assert_eq!(plan.container.as_deref(), Some("mp4"));
+        assert_eq!(plan.reasons, vec!["container_not_supported"]);
+
+        // 只重新编码不支持的音频
+        let plan = decide_playback(Some(&probe("matroska,webm", "h264", "dts", 1080)), &browser);
+        assert_eq!(plan.method, PLAY_TRANSCODE);
+        assert_eq!((plan.video_codec.as_deref(), plan.audio_codec.as_deref()), (Some("copy"), Some("aac")));
+
+        // 超出分辨率限制时重新编码视频
+        let limited = PlayDecisionRequest { max_height: Some(720), ..client(&["mkv"], &["h265"], &[]) };
+        let plan = decide_playback(Some(&probe("matroska,webm", "hevc", "flac", 2160)), &limited);
+        assert_eq!(plan.method, PLAY_TRANSCODE);
+        assert_eq!(plan.container.as_deref(), Some("matroska"));
+        assert_eq!(plan.video_codec.as_deref(), Some("h264"));
+        assert_eq!(plan.reasons, vec!["resolution_exceeds_limit"]);
+
+        assert_eq!(decide_playback(None, &browser).method, PLAY_DIRECT);
+    }
+}
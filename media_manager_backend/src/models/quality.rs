@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 质量配置（保存在 user_settings 的 quality_profiles 键中）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct QualityProfile {
+    pub name: String,
+    /// 是否为默认配置
+    #[serde(default)]
+    pub is_default: bool,
+    /// 最低可接受分辨率（如 1080），低于此分辨率的资源会被忽略
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_resolution: Option<u32>,
+    /// 期望分辨率（如 2160），达到后不再检测升级
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_resolution: Option<u32>,
+    /// 期望编码（如 hevc / h264 / av1）
+    #[serde(default)]
+    pub preferred_codecs: Vec<String>,
+}
+
+/// 从文件名或资源标题识别出的质量
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ReleaseQuality {
+    pub resolution: Option<u32>,
+    pub codec: Option<String>,
+}
+
+impl ReleaseQuality {
+    /// 可读标签，如 "2160p HEVC"
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(resolution) = self.resolution {
+            parts.push(format!("{}p", resolution));
+        }
+        if let Some(ref codec) = self.codec {
+            parts.push(codec.to_uppercase());
+        }
+        if parts.is_empty() {
+            "unknown".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+/// 可升级的媒体记录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MediaUpgrade {
+    pub media_id: String,
+    pub current_quality: Option<String>,
+    pub available_quality: String,
+    pub source: String,
+    pub source_title: String,
+    pub magnet_link: Option<String>,
+    pub file_path: Option<String>,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// 带媒体信息的升级记录（用于API响应）
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaUpgradeWithInfo {
+    #[serde(flatten)]
+    pub upgrade: MediaUpgrade,
+    pub title: String,
+    pub code: Option<String>,
+    pub poster_url: Option<String>,
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 扫描文件状态
+pub const SCAN_FILE_UNMATCHED: &str = "unmatched";
+pub const SCAN_FILE_MATCHED: &str = "matched";
+pub const SCAN_FILE_IGNORED: &str = "ignored";
+
+/// 匹配自动确认设置（保存在 user_settings 的 scan_auto_confirm 键中）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AutoConfirmSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 置信度达到该阈值的匹配直接关联，其余留待人工确认
+    #[serde(default = "default_auto_confirm_threshold")]
+    pub threshold: f32,
+}
+
+fn default_auto_confirm_threshold() -> f32 {
+    0.95
+}
+
+impl Default for AutoConfirmSettings {
+    fn default() -> Self {
+        Self { enabled: false, threshold: default_auto_confirm_threshold() }
+    }
+}
+
+/// 扫描会话（含各状态文件数）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSession {
+    pub id: String,
+    pub paths: Vec<String>,
+    pub recursive: bool,
+    pub total_files: i64,
+    pub unmatched_count: i64,
+    pub matched_count: i64,
+    pub ignored_count: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 扫描会话中的文件记录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScanFileRecord {
+    pub id: i64,
+    pub session_id: String,
+    pub file_path: String,
+    pub file_name: String,
+    pub file_size: i64,
+    pub parsed_code: Option<String>,
+    pub parsed_title: Option<String>,
+    pub parsed_year: Option<i32>,
+    pub parsed_series: Option<String>,
+    pub parsed_date: Option<String>,
+    pub status: String,
+    pub media_id: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 刮削结果中可以单独选择应用的字段
+pub const SCRAPE_FIELDS: [&str; 23] = [
+    "code", "title", "original_title", "year", "rating", "runtime", "overview",
+    "poster_url", "backdrop_url", "studio", "series", "release_date", "media_type",
+    "director", "language", "country", "genres", "actors",
+    "preview_urls", "preview_video_urls", "cover_video_url", "download_links", "translations",
+];
+
+/// 刮削器名称等元数据字段，不受字段选择影响
+const ALWAYS_KEPT: [&str; 1] = ["source"];
+
+/// 刮削字段选择
+/// - include_fields 为空：应用全部字段
+/// - exclude_fields：在 include_fields 基础上排除
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ScrapeFieldMask {
+    #[serde(default)]
+    pub include_fields: Vec<String>,
+    #[serde(default)]
+    pub exclude_fields: Vec<String>,
+}
+
+/// 支持的刮削字段列表响应
+#[derive(Debug, Serialize)]
+pub struct ScrapeFieldsResponse {
+    pub fields: Vec<&'static str>,
+}
+
+impl ScrapeFieldMask {
+    pub fn is_empty(&self) -> bool {
+        self.include_fields.is_empty() && self.exclude_fields.is_empty()
+    }
+
+    /// 检查字段名是否都受支持
+    pub fn validate(&self) -> Result<(), String> {
+        let unknown: Vec<&str> = self.include_fields.iter()
+            .chain(self.exclude_fields.iter())
+            .map(String::as_str)
+            .filter(|f| !SCRAPE_FIELDS.contains(f))
+            .collect();
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Unsupported scrape fields: {}", unknown.join(", ")))
+        }
+    }
+
+    /// 字段是否应用
+    pub fn allows(&self, field: &str) -> bool {
+        if ALWAYS_KEPT.contains(&field) {
+            return true;
+        }
+        (self.include_fields.is_empty() || self.include_fields.iter().any(|f| f == field))
+            && !self.exclude_fields.iter().any(|f| f == field)
+    }
+
+    /// 按字段选择过滤刮削数据，未选择的字段直接移除
+    pub fn apply(&self, scrape_data: &Value) -> Value {
+        match scrape_data.as_object() {
+            Some(obj) if !self.is_empty() => Value::Object(
+                obj.iter()
+                    .filter(|(k, _)| self.allows(k))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect(),
+            ),
+            _ => scrape_data.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_mask_apply() {
+        let data = json!({"source": "s", "title": "T", "actors": ["A"], "genres": ["G"]});
+
+        let all = ScrapeFieldMask::default();
+        assert_eq!(all.apply(&data), data);
+
+        let include = ScrapeFieldMask {
+            include_fields: vec!["actors".to_string(), "genres".to_string()],
+            exclude_fields: vec![],
+        };
+        assert_eq!(include.apply(&data), json!({"source": "s", "actors": ["A"], "genres": ["G"]}));
+
+        let exclude = ScrapeFieldMask {
+            include_fields: vec![],
+            exclude_fields: vec!["title".to_string()],
+        };
+        assert!(!exclude.allows("title"));
+        assert!(exclude.allows("actors"));
+        assert_eq!(exclude.apply(&data).get("title"), None);
+    }
+
+    #[test]
+    fn test_mask_validate() {
+        let mask = ScrapeFieldMask {
+            include_fields: vec!["title".to_string(), "bogus".to_string()],
+            exclude_fields: vec![],
+        };
+        assert_eq!(mask.validate(), Err("Unsupported scrape fields: bogus".to_string()));
+        assert!(ScrapeFieldMask::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 刮削来源存档设置（保存在 user_settings 的 scrape_archive 键中）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScrapeArchiveSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 单条原始数据的大小上限（压缩前，KB），超过时不存档
+    #[serde(default = "default_max_payload_kb")]
+    pub max_payload_kb: u64,
+    /// 存档总大小上限（压缩后，MB），超过时删除最早的存档
+    #[serde(default = "default_max_total_mb")]
+    pub max_total_mb: u64,
+}
+
+fn default_max_payload_kb() -> u64 {
+    2048
+}
+
+fn default_max_total_mb() -> u64 {
+    200
+}
+
+impl Default for ScrapeArchiveSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_payload_kb: default_max_payload_kb(),
+            max_total_mb: default_max_total_mb(),
+        }
+    }
+}
+
+impl ScrapeArchiveSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_payload_kb == 0 || self.max_total_mb == 0 {
+            return Err("max_payload_kb and max_total_mb must be greater than 0".to_string());
+        }
+        if self.max_payload_kb > self.max_total_mb * 1024 {
+            return Err("max_payload_kb cannot exceed max_total_mb".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 刮削来源存档（不含原始数据）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScrapeSource {
+    pub id: i64,
+    pub media_id: String,
+    pub source: Option<String>,
+    pub query: Option<String>,
+    pub source_url: Option<String>,
+    pub content_type: String,
+    pub original_size: i64,
+    pub compressed_size: i64,
+    pub created_at: DateTime<Utc>,
+}
@@ -18,17 +18,173 @@ pub struct SearchFilters {
     pub offset: Option<i32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SortOption {
     Title,
     Year,
     Rating,
     AddedDate,
     LastWatched,
+    Runtime,
+    /// 所有分段文件大小之和
+    FileSize,
+    ReleaseDate,
+    /// 按种子打乱：同一种子顺序固定，翻页时不会重复或遗漏
+    Random(u32),
+    /// 加入收藏的时间
+    CollectionAdded,
+}
+
+impl SortOption {
+    /// 允许的 sort_by 参数值
+    pub const PARAMS: &'static [&'static str] = &[
+        "created_at", "title", "year", "rating", "release_date", "runtime",
+        "file_size", "last_watched", "collection_added", "random",
+    ];
+
+    /// 设置随机排序的种子（未提供时为 0，其他排序方式不受影响）
+    pub fn with_seed(self, seed: Option<u32>) -> Self {
+        match self {
+            SortOption::Random(_) => SortOption::Random(seed.unwrap_or(0)),
+            other => other,
+        }
+    }
+}
+
+impl std::str::FromStr for SortOption {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created_at" | "added_date" => Ok(SortOption::AddedDate),
+            "title" => Ok(SortOption::Title),
+            "year" => Ok(SortOption::Year),
+            "rating" => Ok(SortOption::Rating),
+            "release_date" => Ok(SortOption::ReleaseDate),
+            "runtime" => Ok(SortOption::Runtime),
+            "file_size" => Ok(SortOption::FileSize),
+            "last_watched" => Ok(SortOption::LastWatched),
+            "collection_added" => Ok(SortOption::CollectionAdded),
+            "random" => Ok(SortOption::Random(0)),
+            _ => Err(format!(
+                "Invalid sort_by: {} (expected one of: {})",
+                s,
+                SortOption::PARAMS.join(", ")
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SortOrder {
     Ascending,
     Descending,
-}
\ No newline at end of file
+}
+
+impl SortOrder {
+    /// 解析 sort_order 参数（asc / desc）
+    pub fn from_param(order: &str) -> Result<Self, String> {
+        match order.to_ascii_lowercase().as_str() {
+            "asc" => Ok(SortOrder::Ascending),
+            "desc" => Ok(SortOrder::Descending),
+            _ => Err(format!("Invalid sort_order: {} (expected asc or desc)", order)),
+        }
+    }
+}
+
+/// 演员、厂商、系列列表每页最多返回的条数
+pub const MAX_LIST_LIMIT: i32 = 500;
+
+/// 演员、厂商、系列列表的排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ListSortOption {
+    #[default]
+    Name,
+    /// 作品数
+    MediaCount,
+    /// 最近添加
+    AddedDate,
+}
+
+impl ListSortOption {
+    /// 允许的 sort_by 参数值
+    pub const PARAMS: &'static [&'static str] = &["name", "media_count", "created_at"];
+
+    /// ORDER BY 子句：未指定顺序时名称升序、其他降序，名称作为第二排序键。
+    /// 列名由调用方提供（不含用户输入）
+    pub fn order_by(&self, order: Option<&SortOrder>, name_column: &str, count_column: &str, created_column: &str) -> String {
+        let descending = match order {
+            Some(order) => matches!(order, SortOrder::Descending),
+            None => *self != ListSortOption::Name,
+        };
+        let direction = if descending { "DESC" } else { "ASC" };
+        match self {
+            ListSortOption::Name => format!("{} COLLATE NOCASE {}", name_column, direction),
+            ListSortOption::MediaCount => format!("{} {}, {} COLLATE NOCASE", count_column, direction, name_column),
+            ListSortOption::AddedDate => format!("{} {}, {} COLLATE NOCASE", created_column, direction, name_column),
+        }
+    }
+}
+
+impl std::str::FromStr for ListSortOption {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(ListSortOption::Name),
+            "media_count" | "work_count" => Ok(ListSortOption::MediaCount),
+            "created_at" | "added_date" => Ok(ListSortOption::AddedDate),
+            _ => Err(format!(
+                "Invalid sort_by: {} (expected one of: {})",
+                s,
+                ListSortOption::PARAMS.join(", ")
+            )),
+        }
+    }
+}
+
+/// 解析列表接口的 sort_by / sort_order 参数，未指定 sort_by 时使用 default
+pub fn parse_list_sort(
+    sort_by: Option<&str>,
+    sort_order: Option<&str>,
+    default: ListSortOption,
+) -> Result<(ListSortOption, Option<SortOrder>), String> {
+    let sort_by = sort_by.map(str::parse).transpose()?.unwrap_or(default);
+    let sort_order = sort_order.map(SortOrder::from_param).transpose()?;
+    Ok((sort_by, sort_order))
+}
+
+/// 限制列表接口的 limit / offset
+pub fn clamp_list_page(limit: Option<i32>, offset: Option<i32>, default_limit: i32) -> (i32, i32) {
+    (limit.unwrap_or(default_limit).clamp(1, MAX_LIST_LIMIT), offset.unwrap_or(0).max(0))
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_option_whitelist() {
+        for param in SortOption::PARAMS {
+            assert!(param.parse::<SortOption>().is_ok(), "{}", param);
+        }
+        assert_eq!("file_size".parse::<SortOption>(), Ok(SortOption::FileSize));
+        assert_eq!("random".parse::<SortOption>().map(|s| s.with_seed(Some(7))), Ok(SortOption::Random(7)));
+        assert_eq!(SortOption::Title.with_seed(Some(7)), SortOption::Title);
+        assert!("title; DROP TABLE media_items".parse::<SortOption>().is_err());
+    }
+
+    #[test]
+    fn test_list_sort_option() {
+        for param in ListSortOption::PARAMS {
+            assert!(param.parse::<ListSortOption>().is_ok(), "{}", param);
+        }
+        assert_eq!(ListSortOption::Name.order_by(None, "name", "media_count", "created_at"), "name COLLATE NOCASE ASC");
+        assert_eq!(
+            ListSortOption::MediaCount.order_by(Some(&SortOrder::Ascending), "name", "media_count", "created_at"),
+            "media_count ASC, name COLLATE NOCASE"
+        );
+        assert!(parse_list_sort(Some("name; --"), None, ListSortOption::Name).is_err());
+        assert!(parse_list_sort(None, Some("up"), ListSortOption::Name).is_err());
+        assert_eq!(clamp_list_page(Some(100000), Some(-5), 20), (MAX_LIST_LIMIT, 0));
+    }
+}
@@ -0,0 +1,134 @@
+use serde::Serialize;
+
+/// 搜索建议来源
+pub const SUGGESTION_HISTORY: &str = "history";
+pub const SUGGESTION_TITLE: &str = "title";
+pub const SUGGESTION_PERSON: &str = "person";
+pub const SUGGESTION_CODE: &str = "code";
+
+/// 默认返回的建议数
+pub const DEFAULT_SUGGESTION_LIMIT: usize = 10;
+
+/// 最多返回的建议数
+pub const MAX_SUGGESTION_LIMIT: usize = 50;
+
+/// 热门搜索默认统计的天数
+pub const DEFAULT_TRENDING_WINDOW_DAYS: u32 = 30;
+
+/// 热门搜索最多统计的天数
+pub const MAX_TRENDING_WINDOW_DAYS: u32 = 365;
+
+/// 每个用户保留的搜索历史条数
+pub const SEARCH_HISTORY_LIMIT: i64 = 500;
+
+/// 搜索建议
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SearchSuggestion {
+    pub text: String,
+    /// history / title / person / code
+    pub type_: String,
+    /// 搜索次数（history）或匹配的媒体数
+    pub count: i32,
+}
+
+/// 用户搜索过的关键词（按关键词汇总）
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SearchHistoryStat {
+    pub query: String,
+    pub search_count: i64,
+    /// 距最近一次搜索的天数
+    pub age_days: f64,
+}
+
+impl SearchHistoryStat {
+    /// 排序分数：搜索次数按时间衰减（一周前的搜索权重减半）
+    pub fn score(&self) -> f64 {
+        self.search_count as f64 / (1.0 + self.age_days.max(0.0) / 7.0)
+    }
+}
+
+/// 某个关键词在某一天的搜索次数（所有用户）
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SearchDayCount {
+    pub query: String,
+    pub search_count: i64,
+    /// 距该天的天数
+    pub age_days: f64,
+}
+
+/// 热门搜索：每次搜索的权重按时间指数衰减（半衰期为统计天数的 1/3），按总权重排序取前 limit 个关键词
+pub fn rank_trending(counts: Vec<SearchDayCount>, window_days: u32, limit: usize) -> Vec<String> {
+    let half_life = (window_days as f64 / 3.0).max(1.0);
+    let mut scores: Vec<(String, f64)> = Vec::new();
+    for count in counts {
+        let weight = count.search_count as f64 * 0.5f64.powf(count.age_days.max(0.0) / half_life);
+        match scores.iter_mut().find(|(query, _)| query.eq_ignore_ascii_case(&count.query)) {
+            Some((_, score)) => *score += weight,
+            None => scores.push((count.query, weight)),
+        }
+    }
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scores.into_iter().take(limit).map(|(query, _)| query).collect()
+}
+
+/// 合并搜索历史和媒体库匹配：历史关键词在前（按次数和时间排序），媒体库匹配按媒体数排序，
+/// 忽略大小写去重后取前 limit 条
+pub fn merge_suggestions(
+    mut history: Vec<SearchHistoryStat>,
+    mut library: Vec<SearchSuggestion>,
+    limit: usize,
+) -> Vec<SearchSuggestion> {
+    history.sort_by(|a, b| b.score().total_cmp(&a.score()));
+    library.sort_by_key(|suggestion| std::cmp::Reverse(suggestion.count));
+
+    let mut seen = std::collections::HashSet::new();
+    history.into_iter()
+        .map(|stat| SearchSuggestion {
+            text: stat.query,
+            type_: SUGGESTION_HISTORY.to_string(),
+            count: stat.search_count as i32,
+        })
+        .chain(library)
+        .filter(|suggestion| seen.insert(suggestion.text.to_lowercase()))
+        .take(limit)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stat(query: &str, search_count: i64, age_days: f64) -> SearchHistoryStat {
+        SearchHistoryStat { query: query.to_string(), search_count, age_days }
+    }
+
+    #[test]
+    fn test_merge_suggestions() {
+        let library = vec![
+            SearchSuggestion { text: "Matrix Reloaded".to_string(), type_: SUGGESTION_TITLE.to_string(), count: 1 },
+            SearchSuggestion { text: "matrix".to_string(), type_: SUGGESTION_TITLE.to_string(), count: 3 },
+        ];
+        // 很久以前搜索多次的关键词排在最近搜索一次的之后
+        let history = vec![stat("matrix", 4, 60.0), stat("matrix 4k", 1, 0.0)];
+
+        let merged = merge_suggestions(history, library, 10);
+        let texts: Vec<&str> = merged.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["matrix 4k", "matrix", "Matrix Reloaded"]);
+        assert_eq!(merged[1].type_, SUGGESTION_HISTORY);
+        assert_eq!(merge_suggestions(vec![], merged, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_rank_trending() {
+        let day = |query: &str, search_count: i64, age_days: f64| SearchDayCount {
+            query: query.to_string(),
+            search_count,
+            age_days,
+        };
+        // 一个月前搜索 5 次的权重低于今天搜索 2 次的
+        let counts = vec![day("old", 5, 29.0), day("new", 1, 0.0), day("NEW", 1, 0.0), day("mid", 1, 10.0)];
+        assert_eq!(rank_trending(counts.clone(), 30, 10), vec!["new", "old", "mid"]);
+        // 统计天数越长衰减越慢
+        assert_eq!(rank_trending(counts, 365, 1), vec!["old"]);
+    }
+}
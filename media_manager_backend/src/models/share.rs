@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 分享链接默认/最长有效期（小时）
+pub const DEFAULT_SHARE_HOURS: i64 = 72;
+pub const MAX_SHARE_HOURS: i64 = 24 * 30;
+
+/// 只读公开分享链接
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ShareLink {
+    pub id: String,
+    pub media_id: String,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub access_count: i64,
+    pub last_accessed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ShareLink {
+    pub fn new(media_id: String, expires_at: DateTime<Utc>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            media_id,
+            expires_at,
+            revoked_at: None,
+            access_count: 0,
+            last_accessed_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// 是否仍可访问（未撤销且未过期）
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.expires_at > now
+    }
+}
+
+/// 创建分享链接请求
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateShareRequest {
+    /// 有效期（小时），默认 72，最长 720
+    pub expires_in_hours: Option<i64>,
+}
+
+/// 创建分享链接响应（令牌只在创建时返回）
+#[derive(Debug, Serialize)]
+pub struct CreateShareResponse {
+    #[serde(flatten)]
+    pub link: ShareLink,
+    pub token: String,
+    /// 公开详情地址（相对路径）
+    pub url: String,
+    /// 公开视频地址（相对路径）
+    pub video_url: String,
+}
+
+/// 分享页公开的最小媒体信息
+#[derive(Debug, Serialize)]
+pub struct PublicMediaDetail {
+    pub title: String,
+    pub original_title: Option<String>,
+    pub year: Option<i32>,
+    pub overview: Option<String>,
+    pub poster_url: Option<String>,
+    pub runtime: Option<i32>,
+    pub release_date: Option<String>,
+    pub genres: Vec<String>,
+    pub expires_at: DateTime<Utc>,
+    /// 有本地文件时可播放
+    pub video_url: Option<String>,
+}
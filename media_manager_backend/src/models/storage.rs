@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+/// 文件存储位置（本地磁盘或对象存储）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum StorageConfig {
+    Local,
+    /// S3 兼容存储（AWS、MinIO 等），使用 path-style 地址
+    S3 {
+        /// 如 `https://s3.amazonaws.com`、`http://minio:9000`
+        endpoint: String,
+        bucket: String,
+        #[serde(default = "default_s3_region")]
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+        /// 对象键前缀
+        #[serde(default)]
+        prefix: String,
+    },
+    Webdav {
+        /// 根目录对应的 WebDAV 地址
+        url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+impl StorageConfig {
+    /// 存储类型名称（local、s3、webdav）
+    pub fn storage_type(&self) -> &'static str {
+        match self {
+            StorageConfig::Local => "local",
+            StorageConfig::S3 { .. } => "s3",
+            StorageConfig::Webdav { .. } => "webdav",
+        }
+    }
+
+    pub fn is_local(&self) -> bool {
+        matches!(self, StorageConfig::Local)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            StorageConfig::Local => Ok(()),
+            StorageConfig::S3 { endpoint, bucket, access_key_id, secret_access_key, .. } => {
+                validate_url(endpoint)?;
+                if bucket.trim().is_empty() {
+                    return Err("s3 bucket cannot be empty".to_string());
+                }
+                if access_key_id.is_empty() || secret_access_key.is_empty() {
+                    return Err("s3 access_key_id and secret_access_key are required".to_string());
+                }
+                Ok(())
+            }
+            StorageConfig::Webdav { url, .. } => validate_url(url),
+        }
+    }
+}
+
+fn validate_url(value: &str) -> Result<(), String> {
+    match url::Url::parse(value) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(()),
+        _ => Err(format!("invalid storage url: {}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_storage_config() {
+        let config: StorageConfig = serde_json::from_str(
+            r#"{"type":"s3","endpoint":"http://minio:9000","bucket":"media","access_key_id":"a","secret_access_key":"b"}"#
+        ).unwrap();
+        assert_eq!(config.storage_type(), "s3");
+        assert!(config.validate().is_ok());
+        match config {
+            StorageConfig::S3 { region, prefix, .. } => {
+                assert_eq!(region, "us-east-1");
+                assert_eq!(prefix, "");
+            }
+            _ => unreachable!(),
+        }
+
+        let config: StorageConfig = serde_json::from_str(r#"{"type":"webdav","url":"ftp://nas"}"#).unwrap();
+        assert!(config.validate().is_err());
+        let config: StorageConfig = serde_json::from_str(r#"{"type":"local"}"#).unwrap();
+        assert!(config.is_local());
+    }
+}
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 每个用户最多同时播放数的上限
+pub const MAX_STREAMS_PER_USER: usize = 100;
+
+/// 播放会话类型：直接读取原文件
+pub const STREAM_DIRECT: &str = "direct";
+/// 播放会话类型：换容器或转码
+pub const STREAM_TRANSCODE: &str = "transcode";
+
+/// 正在进行的播放会话（同一用户对同一文件的多个请求，如拖动进度时的 Range 请求，属于同一个会话）
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamSession {
+    pub id: String,
+    pub user: String,
+    pub media_id: String,
+    pub edition: Option<String>,
+    pub part: usize,
+    /// direct / transcode
+    pub kind: String,
+    pub user_agent: Option<String>,
+    pub started_at: DateTime<Utc>,
+    /// 当前的连接数
+    pub connections: usize,
+    /// 已发送的字节数
+    pub bytes_sent: u64,
+}
+
+/// 播放会话设置（保存在 user_settings 的 streaming 键中）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct StreamingSettings {
+    /// 每个用户最多同时播放的会话数，0 表示不限制
+    #[serde(default)]
+    pub max_streams_per_user: usize,
+}
+
+impl StreamingSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_streams_per_user > MAX_STREAMS_PER_USER {
+            return Err(format!("max_streams_per_user must be between 0 and {}", MAX_STREAMS_PER_USER));
+        }
+        Ok(())
+    }
+}
@@ -2,6 +2,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use super::{ListSortOption, SortOrder};
+
 /// 厂商/制作公司
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Studio {
@@ -74,6 +76,123 @@ pub struct StudioWithSeries {
     pub series_list: Vec<Series>,
 }
 
+/// 厂商某一年的作品数（year 为空表示未知年份）
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct StudioYearCount {
+    pub year: Option<i32>,
+    pub media_count: i64,
+}
+
+/// 厂商作品最多的系列（id 为空表示系列表中没有对应记录）
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct StudioTopSeries {
+    pub id: Option<String>,
+    pub name: String,
+    pub media_count: i64,
+}
+
+/// 在厂商作品中出演最多的演员
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct StudioTopActor {
+    pub id: String,
+    pub name: String,
+    pub avatar_url: Option<String>,
+    pub media_count: i64,
+}
+
+/// 厂商最近添加的作品
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct StudioRecentMedia {
+    pub id: String,
+    pub title: String,
+    pub code: Option<String>,
+    pub year: Option<i32>,
+    pub poster_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 厂商详情页汇总（GET /api/studios/:id/overview）
+#[derive(Debug, Clone, Serialize)]
+pub struct StudioOverview {
+    #[serde(flatten)]
+    pub studio: Studio,
+    /// 媒体库中该厂商的作品数
+    pub total_media: i64,
+    /// 已收藏的作品数
+    pub collected_media: i64,
+    /// 已收藏作品的百分比（0-100）
+    pub collected_percent: f64,
+    /// 按年份倒序
+    pub media_by_year: Vec<StudioYearCount>,
+    pub top_series: Vec<StudioTopSeries>,
+    pub top_actors: Vec<StudioTopActor>,
+    pub recent_media: Vec<StudioRecentMedia>,
+}
+
+/// 系列自动检测：建议来源
+pub const SERIES_MATCH_TITLE: &str = "title";
+pub const SERIES_MATCH_CODE_PREFIX: &str = "code_prefix";
+
+/// 未设置系列的媒体（系列检测候选）
+#[derive(Debug, Clone, FromRow)]
+pub struct SeriesDetectCandidate {
+    pub id: String,
+    pub title: String,
+    pub code: Option<String>,
+    pub studio: Option<String>,
+}
+
+/// 已知系列名：系列表中的系列（带厂商名）和媒体中已出现的系列
+#[derive(Debug, Clone, FromRow)]
+pub struct KnownSeries {
+    pub name: String,
+    pub studio: Option<String>,
+}
+
+/// 为媒体建议的系列
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeriesSuggestion {
+    pub media_id: String,
+    pub title: String,
+    pub code: Option<String>,
+    pub studio: Option<String>,
+    pub series: String,
+    /// title：标题中包含已知系列名；code_prefix：同番号前缀的其他媒体都属于该系列
+    pub reason: String,
+}
+
+/// 系列检测报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesSuggestionReport {
+    pub generated_at: DateTime<Utc>,
+    /// 检查的未设置系列的媒体数
+    pub media_scanned: usize,
+    pub suggestions: Vec<SeriesSuggestion>,
+}
+
+/// 把媒体设置为指定系列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesAssignment {
+    pub media_id: String,
+    pub series: String,
+}
+
+/// 批量应用系列建议请求（不传 assignments 时应用当前报告中的全部建议）
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApplySeriesSuggestionsRequest {
+    #[serde(default)]
+    pub assignments: Option<Vec<SeriesAssignment>>,
+}
+
+/// 批量应用结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SeriesApplyResult {
+    /// 设置了系列的媒体数
+    pub applied: usize,
+    /// 媒体不存在或已有系列而跳过的数量
+    pub skipped: usize,
+}
+
 // ============ Request/Response DTOs ============
 
 #[derive(Debug, Deserialize)]
@@ -99,6 +218,43 @@ pub struct CreateSeriesRequest {
     pub cover_url: Option<String>,
 }
 
+/// 把厂商的媒体移到另一个厂商
+#[derive(Debug, Deserialize)]
+pub struct ReassignStudioRequest {
+    pub target_studio_id: String,
+    /// 移动完成后删除原厂商
+    #[serde(default)]
+    pub delete_source: bool,
+}
+
+/// 厂商媒体移动结果
+#[derive(Debug, Default, Serialize)]
+pub struct StudioReassignResult {
+    pub moved_media: u64,
+    /// 改为属于目标厂商的系列数
+    pub moved_series: u64,
+    /// 目标厂商已有同名系列而合并的系列数
+    pub merged_series: u64,
+    pub source_deleted: bool,
+}
+
+/// 按名称出现在媒体中、但厂商/系列表中没有对应记录的名称
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct UnmatchedName {
+    pub name: String,
+    pub media_count: i64,
+}
+
+/// 媒体关联厂商/系列表的结果
+#[derive(Debug, Default, Serialize)]
+pub struct MediaLinkResult {
+    pub linked_studios: u64,
+    pub linked_series: u64,
+    /// 无法关联的厂商名（拼写错误或尚未创建的厂商）
+    pub unmatched_studios: Vec<UnmatchedName>,
+    pub unmatched_series: Vec<UnmatchedName>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateSeriesRequest {
     pub name: Option<String>,
@@ -107,16 +263,34 @@ pub struct UpdateSeriesRequest {
     pub cover_url: Option<String>,
 }
 
+/// 厂商/系列列表过滤器
+#[derive(Debug, Default)]
+pub struct StudioListFilters {
+    /// 按名称过滤
+    pub query: Option<String>,
+    /// 只列出该厂商的系列（厂商列表忽略）
+    pub studio_id: Option<String>,
+    pub sort_by: ListSortOption,
+    /// 未指定时按排序方式的默认顺序
+    pub sort_order: Option<SortOrder>,
+    pub limit: i32,
+    pub offset: i32,
+}
+
 #[derive(Debug, Serialize)]
 pub struct StudioListResponse {
     pub studios: Vec<StudioWithSeries>,
     pub total: i64,
+    pub limit: i32,
+    pub offset: i32,
 }
 
 #[derive(Debug, Serialize)]
 pub struct SeriesListResponse {
     pub series: Vec<SeriesWithStudio>,
     pub total: i64,
+    pub limit: i32,
+    pub offset: i32,
 }
 
 /// 系列匹配结果（用于导入时的智能匹配）
@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 允许的最大并发 tracker 数
+pub const MAX_SWARM_STATS_CONCURRENCY: usize = 32;
+
+/// 已保存磁力链接的做种/下载人数
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct MagnetSwarmStats {
+    pub media_id: String,
+    pub info_hash: String,
+    /// 所有 tracker 中的最大值，查询失败时为空
+    pub seeders: Option<i64>,
+    pub leechers: Option<i64>,
+    /// 完成下载次数
+    pub completed: Option<i64>,
+    /// 成功返回统计的 tracker 数
+    pub trackers_ok: i64,
+    pub error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+    /// tracker 返回了统计但没有做种者（前端可隐藏）
+    pub dead: bool,
+}
+
+impl MagnetSwarmStats {
+    pub fn is_dead(&self) -> bool {
+        self.trackers_ok > 0 && self.seeders == Some(0)
+    }
+}
+
+/// 做种人数刷新设置（保存在 user_settings 的 swarm_stats 键中）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SwarmStatsSettings {
+    /// 是否定期刷新（会向磁力链接中的 tracker 发送请求）
+    #[serde(default)]
+    pub enabled: bool,
+    /// 刷新间隔（分钟）
+    #[serde(default = "default_interval_minutes")]
+    pub interval_minutes: u64,
+    /// 同时查询的 tracker 数
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+}
+
+fn default_interval_minutes() -> u64 {
+    6 * 60
+}
+
+fn default_concurrency() -> usize {
+    4
+}
+
+impl Default for SwarmStatsSettings {
+    fn default() -> Self {
+        Self { enabled: false, interval_minutes: default_interval_minutes(), concurrency: default_concurrency() }
+    }
+}
+
+impl SwarmStatsSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(10..=7 * 24 * 60).contains(&self.interval_minutes) {
+            return Err("interval_minutes must be between 10 and 10080".to_string());
+        }
+        if self.concurrency == 0 || self.concurrency > MAX_SWARM_STATS_CONCURRENCY {
+            return Err(format!("concurrency must be between 1 and {}", MAX_SWARM_STATS_CONCURRENCY));
+        }
+        Ok(())
+    }
+}
+
+/// 一次刷新的结果
+#[derive(Debug, Default, Serialize)]
+pub struct SwarmStatsRefreshResult {
+    pub checked_media: usize,
+    pub checked_magnets: usize,
+    pub checked_trackers: usize,
+    /// 有做种者的磁力链接数
+    pub alive: usize,
+    /// tracker 返回 0 个做种者的磁力链接数
+    pub dead: usize,
+    /// 没有 tracker 或所有 tracker 都查询失败的磁力链接数
+    pub unknown: usize,
+    pub errors: Vec<String>,
+}
@@ -0,0 +1,32 @@
+use serde::Serialize;
+
+/// 缩略图生成优先级（请求时生成优先于后台补全）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThumbnailPriority {
+    Backfill,
+    OnDemand,
+}
+
+/// 缩略图生成队列状态
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ThumbnailQueueStats {
+    pub workers: usize,
+    pub pending_on_demand: usize,
+    pub pending_backfill: usize,
+    pub running: usize,
+    /// 启动以来生成成功/失败的次数
+    pub completed: u64,
+    pub failed: u64,
+}
+
+/// 缩略图补全结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ThumbnailBackfillResult {
+    /// 加入队列的数量
+    pub queued: usize,
+    /// 已有缓存的数量
+    pub cached: usize,
+    /// 视频文件不存在的数量
+    pub missing: usize,
+}
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 支持缓存的 TMDB 列表
+pub const TMDB_LISTS: [&str; 2] = ["popular", "trending"];
+
+/// 支持缓存的 TMDB 媒体类型
+pub const TMDB_LIST_MEDIA_TYPES: [&str; 2] = ["movie", "tv"];
+
+/// 每个列表最多预取的页数
+pub const MAX_TMDB_LIST_PAGES: u32 = 20;
+
+/// TMDB 列表缓存设置（保存在 user_settings 的 tmdb_list_cache 键中）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TmdbListCacheSettings {
+    /// 是否每晚预取并保存列表
+    #[serde(default)]
+    pub enabled: bool,
+    /// 预取的列表，为空时预取全部
+    #[serde(default)]
+    pub lists: Vec<String>,
+    /// 预取的媒体类型，为空时预取全部
+    #[serde(default)]
+    pub media_types: Vec<String>,
+    /// 每个列表预取的页数
+    #[serde(default = "default_pages")]
+    pub pages: u32,
+    /// 每天预取的时间（UTC 小时）
+    #[serde(default = "default_refresh_hour")]
+    pub refresh_hour: u32,
+    /// 缓存在多少小时内直接使用，不再请求 TMDB
+    #[serde(default = "default_max_age_hours")]
+    pub max_age_hours: u32,
+}
+
+fn default_pages() -> u32 {
+    3
+}
+
+fn default_refresh_hour() -> u32 {
+    3
+}
+
+fn default_max_age_hours() -> u32 {
+    24
+}
+
+impl Default for TmdbListCacheSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lists: Vec::new(),
+            media_types: Vec::new(),
+            pages: default_pages(),
+            refresh_hour: default_refresh_hour(),
+            max_age_hours: default_max_age_hours(),
+        }
+    }
+}
+
+impl TmdbListCacheSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(list) = self.lists.iter().find(|l| !TMDB_LISTS.contains(&l.as_str())) {
+            return Err(format!("Unknown list '{}', expected one of: {}", list, TMDB_LISTS.join(", ")));
+        }
+        if let Some(media_type) = self.media_types.iter().find(|t| !TMDB_LIST_MEDIA_TYPES.contains(&t.as_str())) {
+            return Err(format!("Unknown media type '{}', expected one of: {}", media_type, TMDB_LIST_MEDIA_TYPES.join(", ")));
+        }
+        if self.pages == 0 || self.pages > MAX_TMDB_LIST_PAGES {
+            return Err(format!("pages must be between 1 and {}", MAX_TMDB_LIST_PAGES));
+        }
+        if self.refresh_hour > 23 {
+            return Err("refresh_hour must be between 0 and 23".to_string());
+        }
+        if self.max_age_hours == 0 {
+            return Err("max_age_hours must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// 列表页是否在预取范围内
+    pub fn covers(&self, list: &str, media_type: &str, page: u32) -> bool {
+        (self.lists.is_empty() || self.lists.iter().any(|l| l == list))
+            && (self.media_types.is_empty() || self.media_types.iter().any(|t| t == media_type))
+            && page <= self.pages
+    }
+}
+
+/// 本地缓存的一页列表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TmdbListPage {
+    pub list: String,
+    pub media_type: String,
+    pub page: u32,
+    /// 该页的媒体列表（TMDB 转换后的 MediaItem）
+    pub results: serde_json::Value,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// 列表查询结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TmdbListContent {
+    #[serde(flatten)]
+    pub page: TmdbListPage,
+    pub total: usize,
+    /// 数据来源：tmdb（实时请求）或 cache（本地缓存）
+    pub source: String,
+    /// 缓存是否已超过 max_age_hours（TMDB 无法访问时返回的旧数据）
+    pub stale: bool,
+}
+
+/// 预取结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TmdbListPrefetchResult {
+    pub fetched: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_validate_and_covers() {
+        let settings = TmdbListCacheSettings { media_types: vec!["movie".to_string()], ..Default::default() };
+        assert!(settings.validate().is_ok());
+        assert!(settings.covers("trending", "movie", 3));
+        assert!(!settings.covers("trending", "movie", 4));
+        assert!(!settings.covers("popular", "tv", 1));
+        assert!(TmdbListCacheSettings { lists: vec!["top".to_string()], ..Default::default() }.validate().is_err());
+        assert!(TmdbListCacheSettings { pages: 0, ..Default::default() }.validate().is_err());
+        assert!(TmdbListCacheSettings { refresh_hour: 24, ..Default::default() }.validate().is_err());
+    }
+}
@@ -73,6 +73,35 @@ pub enum ValidationError {
     InvalidJson,
 }
 
+impl ValidationError {
+    /// 出错的请求字段名（用于字段级错误详情）
+    pub fn field(&self) -> &'static str {
+        match self {
+            ValidationError::EmptyTitle | ValidationError::TitleTooLong => "title",
+            ValidationError::InvalidId => "id",
+            ValidationError::InvalidYear(_) => "year",
+            ValidationError::InvalidRating(_) => "rating",
+            ValidationError::InvalidRuntime(_) => "runtime",
+            ValidationError::InvalidProgress(_) => "progress",
+            ValidationError::OverviewTooLong => "overview",
+            ValidationError::InvalidUrl(_) => "url",
+            ValidationError::InvalidLanguageCode(_) => "language",
+            ValidationError::InvalidCountryCode(_) => "country",
+            ValidationError::NegativeBudget => "budget",
+            ValidationError::NegativeRevenue => "revenue",
+            ValidationError::NotesTooLong => "notes",
+            ValidationError::TooManyGenres | ValidationError::GenreNameTooLong => "genres",
+            ValidationError::TooManyCastMembers
+            | ValidationError::EmptyPersonName
+            | ValidationError::PersonNameTooLong
+            | ValidationError::RoleTooLong
+            | ValidationError::CharacterNameTooLong => "cast",
+            ValidationError::TooManyCrewMembers => "crew",
+            ValidationError::InvalidJson => "body",
+        }
+    }
+}
+
 /// 验证器trait
 pub trait Validator {
     type Error;
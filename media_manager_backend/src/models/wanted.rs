@@ -0,0 +1,205 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 想要列表状态
+pub const WANTED_STATUSES: [&str; 4] = ["monitoring", "found", "pushed", "paused"];
+
+/// 找到匹配资源后的动作
+pub const WANTED_ACTIONS: [&str; 2] = ["notify", "auto_push"];
+
+/// 想要列表条目（没有本地文件、需要监控磁力资源的媒体）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WantedMedia {
+    pub media_id: String,
+    pub status: String,
+    pub action: String,
+    pub quality_filter: Option<String>,
+    pub plugin_id: Option<String>,
+    pub search_query: Option<String>,
+    pub found_title: Option<String>,
+    pub found_magnet: Option<String>,
+    pub found_size: Option<String>,
+    pub found_at: Option<DateTime<Utc>>,
+    pub last_searched_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl WantedMedia {
+    /// 解析质量过滤条件（JSON 字段）
+    pub fn get_quality_filter(&self) -> MagnetQualityFilter {
+        self.quality_filter
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// 磁力资源质量过滤条件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MagnetQualityFilter {
+    /// 最小体积（MB）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_size_mb: Option<f64>,
+    /// 最大体积（MB）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size_mb: Option<f64>,
+    /// 标题必须包含的关键词（全部匹配，不区分大小写）
+    #[serde(default)]
+    pub required_keywords: Vec<String>,
+    /// 标题不能包含的关键词（不区分大小写）
+    #[serde(default)]
+    pub excluded_keywords: Vec<String>,
+}
+
+/// tracker 地址支持的协议
+pub const TRACKER_SCHEMES: [&str; 5] = ["udp", "http", "https", "ws", "wss"];
+
+/// 磁力链接 tracker 设置（保存在 user_settings 的 magnet_trackers 键中）
+///
+/// 返回搜索结果和推送到下载客户端前按此设置改写磁力链接
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MagnetTrackerSettings {
+    /// 追加到磁力链接的 tracker（已存在的不重复追加）
+    #[serde(default)]
+    pub trackers: Vec<String>,
+    /// 是否移除已失效的 tracker
+    #[serde(default)]
+    pub strip_dead: bool,
+    /// 已失效的 tracker
+    #[serde(default)]
+    pub dead_trackers: Vec<String>,
+}
+
+impl MagnetTrackerSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        for tracker in self.trackers.iter().chain(&self.dead_trackers) {
+            let scheme = url::Url::parse(tracker).map(|url| url.scheme().to_string())
+                .map_err(|_| format!("Invalid tracker URL '{}'", tracker))?;
+            if !TRACKER_SCHEMES.contains(&scheme.as_str()) {
+                return Err(format!("Unsupported tracker scheme '{}', expected one of: {}", scheme, TRACKER_SCHEMES.join(", ")));
+            }
+        }
+        Ok(())
+    }
+
+    /// 是否需要改写磁力链接
+    pub fn is_active(&self) -> bool {
+        !self.trackers.is_empty() || (self.strip_dead && !self.dead_trackers.is_empty())
+    }
+
+    /// tracker 是否已失效（开启 strip_dead 时才生效）
+    pub fn is_dead(&self, tracker: &str) -> bool {
+        self.strip_dead && self.dead_trackers.iter().any(|dead| same_tracker(dead, tracker))
+    }
+}
+
+/// 比较 tracker 地址（忽略大小写和末尾的 /）
+pub fn same_tracker(a: &str, b: &str) -> bool {
+    a.trim().trim_end_matches('/').eq_ignore_ascii_case(b.trim().trim_end_matches('/'))
+}
+
+/// 下载任务状态
+pub const DOWNLOAD_STATUSES: [&str; 4] = ["downloading", "importing", "imported", "failed"];
+
+/// 推送到下载客户端的磁力任务
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TorrentDownload {
+    pub info_hash: String,
+    pub media_id: String,
+    pub title: Option<String>,
+    pub magnet_link: String,
+    pub status: String,
+    /// 下载进度（0.0 - 1.0）
+    pub progress: f64,
+    /// 整理后的文件路径（JSON 数组）
+    pub imported_files: Option<String>,
+    pub error: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub imported_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 推送到 aria2 的 HTTP/FTP 下载任务
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LinkDownload {
+    pub gid: String,
+    pub media_id: String,
+    pub name: Option<String>,
+    pub url: String,
+    pub status: String,
+    /// 下载进度（0.0 - 1.0）
+    pub progress: f64,
+    /// 整理后的文件路径（JSON 数组）
+    pub imported_files: Option<String>,
+    pub error: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub imported_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 媒体获取状态（从 TMDB 保存、没有本地文件的媒体）
+pub const ACQUISITION_STATES: [&str; 3] = ["wanted", "downloading", "imported"];
+
+/// 下载任务进入某个状态时，媒体应转换到的获取状态及允许的原状态
+///
+/// 下载失败时退回 wanted，之后重新推送可再次进入 downloading
+pub fn acquisition_transition(download_status: &str) -> Option<(&'static str, &'static [&'static str])> {
+    match download_status {
+        "downloading" | "importing" => Some(("downloading", &["wanted"])),
+        "imported" => Some(("imported", &["wanted", "downloading"])),
+        "failed" => Some(("wanted", &["downloading"])),
+        _ => None,
+    }
+}
+
+/// 处于某个获取状态的媒体
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AcquisitionItem {
+    pub media_id: String,
+    pub title: String,
+    pub code: Option<String>,
+    pub poster_url: Option<String>,
+    pub acquisition_status: String,
+    pub acquisition_updated_at: Option<DateTime<Utc>>,
+}
+
+/// 获取状态列表（各状态数量 + 条目）
+#[derive(Debug, Clone, Serialize)]
+pub struct AcquisitionOverview {
+    pub counts: std::collections::BTreeMap<String, i64>,
+    pub items: Vec<AcquisitionItem>,
+}
+
+/// 带媒体信息的想要列表条目（用于API响应）
+#[derive(Debug, Clone, Serialize)]
+pub struct WantedMediaWithInfo {
+    #[serde(flatten)]
+    pub wanted: WantedMedia,
+    pub title: String,
+    pub code: Option<String>,
+    pub poster_url: Option<String>,
+}
+
+// ============ Request/Response DTOs ============
+
+#[derive(Debug, Deserialize)]
+pub struct AddWantedRequest {
+    pub media_id: String,
+    pub action: Option<String>,
+    pub quality_filter: Option<MagnetQualityFilter>,
+    pub plugin_id: Option<String>,
+    pub search_query: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateWantedRequest {
+    pub status: Option<String>,
+    pub action: Option<String>,
+    pub quality_filter: Option<MagnetQualityFilter>,
+    pub plugin_id: Option<String>,
+    pub search_query: Option<String>,
+}
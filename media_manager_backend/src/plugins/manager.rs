@@ -14,34 +14,28 @@ use tracing::{info, warn, error, debug};
 use super::protocol::*;
 use serde::Deserialize;
 
-/// 格式化插件错误信息
-fn format_plugin_error(error: Option<serde_json::Value>) -> String {
-    match error {
-        Some(serde_json::Value::String(s)) => s,
-        Some(serde_json::Value::Object(obj)) => {
-            // 尝试提取 message 字段
-            if let Some(msg) = obj.get("message") {
-                match msg {
-                    serde_json::Value::String(s) => s.clone(),
-                    serde_json::Value::Object(msg_obj) => {
-                        // 多语言消息，优先中文
-                        msg_obj.get("zh")
-                            .or_else(|| msg_obj.get("en"))
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("Unknown error")
-                            .to_string()
-                    }
-                    _ => msg.to_string(),
-                }
-            } else {
-                serde_json::to_string(&obj).unwrap_or_else(|_| "Unknown error".to_string())
-            }
+/// 选择插件失败（API 层按变体返回 404 / 400，不依赖错误文本）
+#[derive(Debug, Clone)]
+pub enum PluginSelectError {
+    /// 指定的插件不存在或未加载
+    NotFound(String),
+    /// 没有插件支持该请求
+    NoneSupports(String),
+    /// 指定的插件不支持该请求
+    Unsupported(String),
+}
+
+impl std::fmt::Display for PluginSelectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginSelectError::NotFound(id) => write!(f, "Plugin not found: {}", id),
+            PluginSelectError::NoneSupports(message) | PluginSelectError::Unsupported(message) => f.write_str(message),
         }
-        Some(v) => v.to_string(),
-        None => "Unknown error".to_string(),
     }
 }
 
+impl std::error::Error for PluginSelectError {}
+
 /// 已加载的插件
 #[derive(Debug, Clone)]
 pub struct LoadedPlugin {
@@ -56,9 +50,44 @@ impl LoadedPlugin {
     pub fn supports_id(&self, id: &str) -> bool {
         self.compiled_patterns.iter().any(|p| p.is_match(id))
     }
+    
+    /// 检查是否支持该页面URL（按 url_domains 匹配域名）
+    pub fn supports_url(&self, url: &str) -> bool {
+        url_matches(&self.config.url_domains, url)
+    }
+    
+    /// 检查能否解析该网盘链接（按 resolve_domains 匹配域名）
+    pub fn supports_resolve(&self, url: &str) -> bool {
+        url_matches(&self.config.resolve_domains, url)
+    }
+    
+    /// 检查是否声明了该能力（如 search_magnets）
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.config.capabilities.iter().any(|c| c == capability)
+    }
+}
+
+fn url_matches(patterns: &[String], url: &str) -> bool {
+    let Some(host) = url::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_lowercase)) else {
+        return false;
+    };
+    patterns.iter().any(|pattern| domain_matches(pattern, &host))
+}
+
+/// 域名匹配：`example.com` 匹配自身及其子域名，`*.example.com` 只匹配子域名
+fn domain_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    if let Some(base) = pattern.strip_prefix("*.") {
+        host.ends_with(&format!(".{}", base))
+    } else {
+        host == pattern || host.ends_with(&format!(".{}", pattern))
+    }
 }
 
 /// 插件管理器
+///
+/// 克隆只复制插件配置，可用于在释放锁后执行耗时的插件调用
+#[derive(Clone)]
 pub struct PluginManager {
     plugins_dir: PathBuf,
     plugins: HashMap<String, LoadedPlugin>,
@@ -167,6 +196,29 @@ impl PluginManager {
         self.plugins.values().collect()
     }
     
+    /// 获取声明了磁力搜索能力的插件ID（按ID排序）
+    pub fn magnet_plugin_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.plugins.values()
+            .filter(|p| p.has_capability(CAPABILITY_SEARCH_MAGNETS))
+            .map(|p| p.config.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+    
+    /// 获取声明了指定能力的第一个插件ID（按ID排序），用于未指定插件时自动选择
+    pub fn plugin_with_capability(&self, capability: &str) -> Option<String> {
+        self.plugins.values()
+            .filter(|p| p.has_capability(capability))
+            .map(|p| p.config.id.clone())
+            .min()
+    }
+    
+    /// 按ID获取已加载的插件
+    pub fn get_plugin(&self, plugin_id: &str) -> Option<&LoadedPlugin> {
+        self.plugins.get(plugin_id)
+    }
+    
     /// 获取插件信息列表
     pub fn get_plugin_infos(&self) -> Vec<PluginInfo> {
         self.plugins.values().map(|p| PluginInfo {
@@ -177,6 +229,9 @@ impl PluginManager {
             author: p.config.author.clone(),
             id_patterns: p.config.id_patterns.clone(),
             supports_search: p.config.supports_search,
+            url_domains: p.config.url_domains.clone(),
+            resolve_domains: p.config.resolve_domains.clone(),
+            capabilities: p.config.capabilities.clone(),
             scrapers: p.config.scrapers.clone(),
         }).collect()
     }
@@ -243,7 +298,52 @@ impl PluginManager {
                 info!("Scrape result - release_date: {:?}, year: {:?}", result.release_date, result.year);
                 Ok(result)
             },
-            _ => Err(anyhow!(format_plugin_error(response.error))),
+            _ => Err(PluginError::from_value(response.error.as_ref()).into()),
+        }
+    }
+    
+    /// 通过页面URL刮削，未指定插件时按域名自动选择，返回使用的插件ID和结果
+    pub async fn scrape_url(&self, url: &str, plugin_id: Option<&str>) -> Result<(String, ScrapeResult)> {
+        let plugin = match plugin_id {
+            Some(id) => {
+                let plugin = self.plugins.get(id)
+                    .ok_or_else(|| PluginSelectError::NotFound(id.to_string()))?;
+                if !plugin.supports_url(url) {
+                    return Err(PluginSelectError::Unsupported(format!("Plugin '{}' does not support URL: {}", id, url)).into());
+                }
+                plugin
+            }
+            None => self.plugins.values()
+                .find(|p| p.supports_url(url))
+                .ok_or_else(|| PluginSelectError::NoneSupports(format!("No plugin supports URL: {}", url)))?,
+        };
+        debug!("Selected plugin '{}' for URL '{}'", plugin.config.id, url);
+        
+        let request = PluginRequest::ScrapeUrl { url: url.to_string() };
+        let response = self.call_plugin(plugin, &request).await?;
+        
+        match response.data {
+            Some(PluginResponseData::Single(result)) => Ok((plugin.config.id.clone(), result)),
+            _ => Err(PluginError::from_value(response.error.as_ref()).into()),
+        }
+    }
+    
+    /// 解析网盘链接为直链，按 resolve_domains 自动选择插件，返回使用的插件ID和结果
+    pub async fn resolve_link(&self, url: &str, password: Option<&str>) -> Result<(String, ResolvedLink)> {
+        let plugin = self.plugins.values()
+            .find(|p| p.supports_resolve(url))
+            .ok_or_else(|| anyhow!("No plugin supports resolving: {}", url))?;
+        debug!("Selected plugin '{}' to resolve '{}'", plugin.config.id, url);
+        
+        let request = PluginRequest::ResolveLink {
+            url: url.to_string(),
+            password: password.map(str::to_string),
+        };
+        let response = self.call_plugin(plugin, &request).await?;
+        
+        match response.data {
+            Some(PluginResponseData::Resolved(link)) => Ok((plugin.config.id.clone(), link)),
+            _ => Err(PluginError::from_value(response.error.as_ref()).into()),
         }
     }
     
@@ -264,7 +364,67 @@ impl PluginManager {
         
         match response.data {
             Some(PluginResponseData::List(results)) => Ok(results),
-            _ => Err(anyhow!(format_plugin_error(response.error))),
+            _ => Err(PluginError::from_value(response.error.as_ref()).into()),
+        }
+    }
+    
+    /// 获取关注目标的即将发售作品（插件需声明 upcoming 能力，返回搜索结果列表格式）
+    pub async fn fetch_upcoming_releases(&self, plugin_id: &str, target_type: &str, name: &str) -> Result<Vec<ScrapeResult>> {
+        let plugin = self.plugins.get(plugin_id)
+            .ok_or_else(|| anyhow!("Plugin not found: {}", plugin_id))?;
+        
+        if !plugin.has_capability(CAPABILITY_UPCOMING) {
+            return Err(anyhow!("Plugin '{}' does not support upcoming releases", plugin_id));
+        }
+        
+        let request = PluginRequest::Upcoming {
+            target_type: target_type.to_string(),
+            name: name.to_string(),
+        };
+        let response = self.call_plugin(plugin, &request).await?;
+        
+        match response.data {
+            Some(PluginResponseData::List(results)) => Ok(results.results),
+            _ => Err(PluginError::from_value(response.error.as_ref()).into()),
+        }
+    }
+    
+    /// 获取目标的全部已知作品（插件需声明 releases 能力，返回搜索结果列表格式）
+    pub async fn fetch_all_releases(&self, plugin_id: &str, target_type: &str, name: &str) -> Result<Vec<ScrapeResult>> {
+        let plugin = self.plugins.get(plugin_id)
+            .ok_or_else(|| anyhow!("Plugin not found: {}", plugin_id))?;
+        
+        if !plugin.has_capability(CAPABILITY_RELEASES) {
+            return Err(PluginSelectError::Unsupported(format!("Plugin '{}' does not support release lists", plugin_id)).into());
+        }
+        
+        let request = PluginRequest::Releases {
+            target_type: target_type.to_string(),
+            name: name.to_string(),
+        };
+        let response = self.call_plugin(plugin, &request).await?;
+        
+        match response.data {
+            Some(PluginResponseData::List(results)) => Ok(results.results),
+            _ => Err(PluginError::from_value(response.error.as_ref()).into()),
+        }
+    }
+    
+    /// 获取厂商的全部演员名单（插件需声明 list_actors 能力）
+    pub async fn list_actors(&self, plugin_id: &str, studio: &str) -> Result<Vec<ActorProfile>> {
+        let plugin = self.plugins.get(plugin_id)
+            .ok_or_else(|| anyhow!("Plugin not found: {}", plugin_id))?;
+        
+        if !plugin.has_capability(CAPABILITY_LIST_ACTORS) {
+            return Err(PluginSelectError::Unsupported(format!("Plugin '{}' does not support actor lists", plugin_id)).into());
+        }
+        
+        let request = PluginRequest::ListActors { studio: studio.to_string() };
+        let response = self.call_plugin(plugin, &request).await?;
+        
+        match response.data {
+            Some(PluginResponseData::Actors(roster)) => Ok(roster.actors),
+            _ => Err(PluginError::from_value(response.error.as_ref()).into()),
         }
     }
     
@@ -371,6 +531,7 @@ impl PluginManager {
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)  // 全部插件搜索超时后结束仍在运行的插件
             .spawn()
             .context("Failed to spawn plugin process")?;
         
@@ -641,3 +802,17 @@ pub struct BatchScrapeMediaResult {
     pub data: Option<serde_json::Value>,
     pub error: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_matches() {
+        assert!(domain_matches("example.com", "example.com"));
+        assert!(domain_matches("Example.com", "www.example.com"));
+        assert!(!domain_matches("example.com", "badexample.com"));
+        assert!(domain_matches("*.example.com", "shop.example.com"));
+        assert!(!domain_matches("*.example.com", "example.com"));
+    }
+}
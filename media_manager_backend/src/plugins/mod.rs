@@ -1,2 +1,3 @@
 pub mod protocol;
 pub mod manager;
+pub mod testkit;
@@ -3,7 +3,19 @@
 //! 插件通过 stdin/stdout 与主程序通信，使用 JSON 格式
 
 use serde::{Deserialize, Serialize};
-use crate::models::{UpdateMediaRequest, Person, DownloadLink as MediaDownloadLink, DownloadLinkType as MediaDownloadLinkType};
+use crate::models::{UpdateMediaRequest, TranslationInput, Person, DownloadLink as MediaDownloadLink, DownloadLinkType as MediaDownloadLinkType};
+
+/// 磁力搜索能力（插件声明后参与全部插件磁力搜索）
+pub const CAPABILITY_SEARCH_MAGNETS: &str = "search_magnets";
+
+/// 即将发售查询能力（插件声明后才会用于发售日历刷新）
+pub const CAPABILITY_UPCOMING: &str = "upcoming";
+
+/// 全部已知作品查询能力（插件声明后才会用于系列缺失作品检查）
+pub const CAPABILITY_RELEASES: &str = "releases";
+
+/// 厂商演员名单查询能力（插件声明后才会用于导入厂商演员）
+pub const CAPABILITY_LIST_ACTORS: &str = "list_actors";
 
 /// 磁力链接搜索结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +30,9 @@ pub struct MagnetResult {
     pub date: Option<String>,
     #[serde(default)]
     pub files: Vec<FileInfo>,
+    /// 来源插件ID（由主程序填写）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 /// 文件信息
@@ -37,6 +52,9 @@ pub struct SiteSearchProgress {
     pub result_count: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// 该网站找到的结果（completed 时携带，后端按 info hash 去重后并入会话）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub results: Vec<MagnetResult>,
 }
 
 /// 插件请求
@@ -53,6 +71,20 @@ pub enum PluginRequest {
     },
     /// 搜索
     Search { query: String, page: Option<u32> },
+    /// 获取关注目标（系列/厂商/演员）的即将发售作品
+    Upcoming { target_type: String, name: String },
+    /// 获取关注目标（系列/厂商/演员）的全部已知作品
+    Releases { target_type: String, name: String },
+    /// 通过来源页面URL获取详情
+    ScrapeUrl { url: String },
+    /// 把网盘分享链接解析为可直接下载/播放的地址
+    ResolveLink {
+        url: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        password: Option<String>,
+    },
+    /// 获取厂商的全部演员名单
+    ListActors { studio: String },
     /// 获取插件信息
     Info,
 }
@@ -77,6 +109,63 @@ pub enum PluginResponseData {
     List(SearchResponse),
     /// 插件信息
     Info(PluginInfo),
+    /// 网盘链接解析结果
+    Resolved(ResolvedLink),
+    /// 演员名单
+    Actors(ActorRoster),
+}
+
+/// 演员名单（list_actors 动作的返回数据）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorRoster {
+    pub actors: Vec<ActorProfile>,
+}
+
+/// 演员资料
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ActorProfile {
+    /// 演员名
+    pub name: String,
+    /// 头像URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<String>,
+    /// 写真URLs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub photo_urls: Vec<String>,
+    /// 封面URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poster_url: Option<String>,
+    /// 背景图URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backdrop_url: Option<String>,
+    /// 简介
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub biography: Option<String>,
+    /// 出生日期 (YYYY-MM-DD)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub birth_date: Option<String>,
+    /// 国籍
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nationality: Option<String>,
+}
+
+/// 网盘链接解析结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedLink {
+    /// 直链地址
+    pub url: String,
+    /// 文件名
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
+    /// 文件大小（字节）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// 请求直链时需要附带的请求头（如 Cookie、User-Agent）
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub headers: std::collections::HashMap<String, String>,
+    /// 直链过期时间（RFC 3339）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
 }
 
 /// 刮削结果 - 统一数据结构
@@ -170,6 +259,10 @@ pub struct ScrapeResult {
     /// 下载链接列表
     #[serde(default)]
     pub download_links: Vec<DownloadLink>,
+    
+    /// 其他语言的标题/简介
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub translations: Vec<TranslationInput>,
 }
 
 /// 下载链接类型
@@ -232,6 +325,15 @@ pub struct PluginInfo {
     /// 是否支持搜索
     #[serde(default)]
     pub supports_search: bool,
+    /// 支持直接刮削的页面域名
+    #[serde(default)]
+    pub url_domains: Vec<String>,
+    /// 可解析的网盘链接域名
+    #[serde(default)]
+    pub resolve_domains: Vec<String>,
+    /// 额外支持的动作（如 search_magnets）
+    #[serde(default)]
+    pub capabilities: Vec<String>,
     /// 刮削器列表
     #[serde(default)]
     pub scrapers: Vec<ScraperInfo>,
@@ -263,6 +365,18 @@ pub struct PluginConfig {
     /// 是否启用
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// 支持直接刮削的页面域名（如 "example.com"、"*.example.com"），
+    /// 声明后插件需处理 scrape_url 动作
+    #[serde(default)]
+    pub url_domains: Vec<String>,
+    /// 可解析的网盘链接域名（如 "115.com"、"pan.example.com"），
+    /// 声明后插件需处理 resolve_link 动作
+    #[serde(default)]
+    pub resolve_domains: Vec<String>,
+    /// 额外支持的动作，声明 search_magnets 的插件参与全部插件磁力搜索，
+    /// 声明 upcoming / releases / list_actors 的插件才会被用于发售日历 / 系列缺失作品检查 / 导入厂商演员
+    #[serde(default)]
+    pub capabilities: Vec<String>,
     /// 刮削器列表
     #[serde(default)]
     pub scrapers: Vec<ScraperInfo>,
@@ -300,6 +414,87 @@ impl PluginResponse {
     }
 }
 
+/// 多语言消息（插件错误的 message 可以是 {"zh": ..., "en": ...}）
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LocalizedMessage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zh: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub en: Option<String>,
+}
+
+impl LocalizedMessage {
+    pub fn new(zh: impl Into<String>, en: impl Into<String>) -> Self {
+        Self { zh: Some(zh.into()), en: Some(en.into()) }
+    }
+
+    /// 按语言取消息，缺失时回退到另一种语言
+    pub fn get(&self, lang: &str) -> Option<&str> {
+        let (preferred, fallback) = if lang == "en" { (&self.en, &self.zh) } else { (&self.zh, &self.en) };
+        preferred.as_deref().or(fallback.as_deref())
+    }
+
+    /// 给每种语言的消息加上前缀
+    pub fn prefixed(&self, zh: &str, en: &str) -> Self {
+        Self {
+            zh: self.zh.as_ref().map(|m| format!("{}{}", zh, m)),
+            en: self.en.as_ref().map(|m| format!("{}{}", en, m)),
+        }
+    }
+}
+
+/// 插件返回的错误（保留错误码和多语言消息）
+#[derive(Debug, Clone)]
+pub struct PluginError {
+    /// 插件自定义的错误码，如 "not_found"
+    pub code: Option<String>,
+    pub message: String,
+    pub localized: Option<LocalizedMessage>,
+}
+
+impl PluginError {
+    /// 解析响应中的 error 字段（字符串或 {code, message} 对象）
+    pub fn from_value(error: Option<&serde_json::Value>) -> Self {
+        use serde_json::Value;
+        let mut code = None;
+        let mut localized = None;
+        let message = match error {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Object(obj)) => {
+                code = obj.get("code").and_then(Value::as_str).map(str::to_string);
+                match obj.get("message") {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(Value::Object(msg_obj)) => {
+                        let message = LocalizedMessage {
+                            zh: msg_obj.get("zh").and_then(Value::as_str).map(str::to_string),
+                            en: msg_obj.get("en").and_then(Value::as_str).map(str::to_string),
+                        };
+                        // 多语言消息，默认优先中文
+                        let text = message.get("zh").unwrap_or("Unknown error").to_string();
+                        if message.zh.is_some() || message.en.is_some() {
+                            localized = Some(message);
+                        }
+                        text
+                    }
+                    Some(msg) => msg.to_string(),
+                    None => serde_json::to_string(obj).unwrap_or_else(|_| "Unknown error".to_string()),
+                }
+            }
+            Some(v) => v.to_string(),
+            None => "Unknown error".to_string(),
+        };
+        Self { code, message, localized }
+    }
+}
+
+impl std::fmt::Display for PluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for PluginError {}
+
 impl ScrapeResult {
     /// 将刮削结果转换为 UpdateMediaRequest
     /// actors -> cast (Vec<Person>)
@@ -383,8 +578,219 @@ impl ScrapeResult {
             preview_urls: if self.preview_urls.is_empty() { None } else { Some(self.preview_urls.clone()) },
             preview_video_urls: if preview_video_urls.is_empty() { None } else { Some(preview_video_urls) },
             cover_video_url: self.cover_video_url.clone(),
+            trailer_url: None,
             studio: self.studio.clone(),
             series: self.series.clone(),
         }
     }
 }
+
+/// 单个或多个值（兼容插件返回字符串或字符串数组）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+/// 预览视频：字符串 URL 或带清晰度的对象
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PreviewVideoUrl {
+    Url(String),
+    Quality {
+        #[serde(default)]
+        quality: Option<String>,
+        url: String,
+    },
+}
+
+/// 插件返回的刮削数据（入库前按此结构校验字段类型）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScrapeData {
+    /// 刮削器名称
+    pub source: Option<String>,
+    pub code: Option<String>,
+    pub title: Option<String>,
+    pub original_title: Option<String>,
+    pub release_date: Option<String>,
+    pub year: Option<i32>,
+    pub studio: Option<String>,
+    pub series: Option<String>,
+    pub director: Option<String>,
+    #[serde(default)]
+    pub actors: Vec<String>,
+    #[serde(default)]
+    pub genres: Vec<String>,
+    pub poster_url: Option<String>,
+    pub backdrop_url: Option<OneOrMany<String>>,
+    #[serde(default)]
+    pub preview_urls: Vec<String>,
+    #[serde(default)]
+    pub preview_video_urls: Vec<PreviewVideoUrl>,
+    pub cover_video_url: Option<String>,
+    pub overview: Option<String>,
+    pub rating: Option<f32>,
+    pub runtime: Option<i32>,
+    pub media_type: Option<String>,
+    pub language: Option<String>,
+    pub country: Option<String>,
+    #[serde(default)]
+    pub download_links: Vec<DownloadLink>,
+    #[serde(default)]
+    pub translations: Vec<TranslationInput>,
+    /// 来源页面及原始内容（仅用于归档）
+    pub source_url: Option<String>,
+    pub raw_html: Option<String>,
+}
+
+/// ScrapeData 的所有字段
+pub const SCRAPE_DATA_FIELDS: &[&str] = &[
+    "source", "code", "title", "original_title", "release_date", "year", "studio", "series",
+    "director", "actors", "genres", "poster_url", "backdrop_url", "preview_urls",
+    "preview_video_urls", "cover_video_url", "overview", "rating", "runtime", "media_type",
+    "language", "country", "download_links", "translations", "source_url", "raw_html",
+];
+
+/// 刮削数据中格式错误的字段
+#[derive(Debug, Clone, Serialize)]
+pub struct ScrapeSchemaError {
+    pub field: String,
+    pub message: String,
+}
+
+/// 刮削数据校验结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScrapeSchemaReport {
+    /// 未知字段（入库时忽略）
+    pub unknown_fields: Vec<String>,
+    /// 类型不符的字段
+    pub errors: Vec<ScrapeSchemaError>,
+}
+
+impl ScrapeSchemaReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// 错误描述，如 `year: invalid type: string "2020", expected i32`
+    pub fn error_message(&self) -> String {
+        self.errors.iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+impl ScrapeData {
+    /// 逐个字段校验插件返回的数据，null 视为未提供
+    pub fn check(value: &serde_json::Value) -> ScrapeSchemaReport {
+        let mut report = ScrapeSchemaReport::default();
+        let Some(object) = value.as_object() else {
+            report.errors.push(ScrapeSchemaError {
+                field: String::new(),
+                message: "expected a JSON object".to_string(),
+            });
+            return report;
+        };
+
+        for (key, field_value) in object {
+            if !SCRAPE_DATA_FIELDS.contains(&key.as_str()) {
+                report.unknown_fields.push(key.clone());
+                continue;
+            }
+            if field_value.is_null() {
+                continue;
+            }
+            let single = serde_json::Value::Object(
+                std::iter::once((key.clone(), field_value.clone())).collect()
+            );
+            if let Err(e) = serde_json::from_value::<ScrapeData>(single) {
+                report.errors.push(ScrapeSchemaError { field: key.clone(), message: e.to_string() });
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_plugin_error_localized() {
+        let value = json!({ "code": "not_found", "message": { "zh": "未找到", "en": "Not found" } });
+        let error = PluginError::from_value(Some(&value));
+        assert_eq!(error.code.as_deref(), Some("not_found"));
+        assert_eq!(error.to_string(), "未找到");
+        let localized = error.localized.unwrap();
+        assert_eq!(localized.get("en"), Some("Not found"));
+        assert_eq!(localized.prefixed("刮削失败: ", "Scrape failed: ").get("zh"), Some("刮削失败: 未找到"));
+
+        let error = PluginError::from_value(Some(&json!("boom")));
+        assert_eq!(error.to_string(), "boom");
+        assert!(error.localized.is_none());
+        assert_eq!(LocalizedMessage { zh: Some("仅中文".to_string()), en: None }.get("en"), Some("仅中文"));
+    }
+
+    #[test]
+    fn test_scrape_data_check() {
+        let report = ScrapeData::check(&json!({
+            "title": "Title",
+            "backdrop_url": "https://example.com/a.jpg",
+            "preview_video_urls": ["https://example.com/a.mp4", {"quality": "4K", "url": "https://example.com/b.mp4"}],
+            "actors": null,
+            "extra": 1
+        }));
+        assert!(report.is_valid());
+        assert_eq!(report.unknown_fields, vec!["extra".to_string()]);
+
+        let report = ScrapeData::check(&json!({
+            "year": "2020",
+            "genres": "Drama",
+            "download_links": [{"name": "a", "url": "magnet:?", "link_type": "unknown"}]
+        }));
+        let mut fields: Vec<_> = report.errors.iter().map(|e| e.field.as_str()).collect();
+        fields.sort();
+        assert_eq!(fields, vec!["download_links", "genres", "year"]);
+
+        assert!(!ScrapeData::check(&json!([])).is_valid());
+    }
+
+    #[test]
+    fn test_resolved_link_response() {
+        let response: PluginResponse = serde_json::from_value(json!({
+            "success": true,
+            "data": { "url": "https://cdn.example.com/a.mp4", "headers": { "Cookie": "uid=1" } }
+        })).unwrap();
+        match response.data {
+            Some(PluginResponseData::Resolved(link)) => {
+                assert_eq!(link.url, "https://cdn.example.com/a.mp4");
+                assert_eq!(link.headers["Cookie"], "uid=1");
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+
+        let request = serde_json::to_value(PluginRequest::ResolveLink { url: "https://115.com/s/abc".to_string(), password: None }).unwrap();
+        assert_eq!(request, json!({ "action": "resolve_link", "url": "https://115.com/s/abc" }));
+    }
+
+    #[test]
+    fn test_actor_roster_response() {
+        let response: PluginResponse = serde_json::from_value(json!({
+            "success": true,
+            "data": { "actors": [{ "name": "Alice", "photo_urls": ["https://img.example.com/a.jpg"] }] }
+        })).unwrap();
+        match response.data {
+            Some(PluginResponseData::Actors(roster)) => {
+                assert_eq!(roster.actors[0].name, "Alice");
+                assert_eq!(roster.actors[0].photo_urls.len(), 1);
+            }
+            other => panic!("unexpected data: {:?}", other),
+        }
+
+        let request = serde_json::to_value(PluginRequest::ListActors { studio: "S1".to_string() }).unwrap();
+        assert_eq!(request, json!({ "action": "list_actors", "studio": "S1" }));
+    }
+}
@@ -0,0 +1,366 @@
+//! 插件协议一致性测试
+//!
+//! 对插件可执行文件运行一组固定的协议检查（info、search、get、异常输入、超时行为），
+//! 生成一致性报告，方便第三方插件开发时自测。
+
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use super::manager::LoadedPlugin;
+use super::protocol::*;
+
+/// 单项检查的默认超时秒数
+pub const DEFAULT_SELFTEST_TIMEOUT_SECS: u64 = 30;
+/// 单项检查允许的最大超时秒数（与插件调用超时一致）
+pub const MAX_SELFTEST_TIMEOUT_SECS: u64 = 120;
+
+/// 自测参数
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SelftestOptions {
+    /// 用于 get 检查的番号/ID，为空时跳过 get 检查
+    pub sample_id: Option<String>,
+    /// 用于 search 检查的关键词
+    pub sample_query: String,
+    /// 单项检查超时秒数
+    pub timeout_secs: u64,
+}
+
+impl Default for SelftestOptions {
+    fn default() -> Self {
+        Self {
+            sample_id: None,
+            sample_query: "test".to_string(),
+            timeout_secs: DEFAULT_SELFTEST_TIMEOUT_SECS,
+        }
+    }
+}
+
+/// 检查结果状态
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    /// 可以工作但不符合协议建议
+    Warning,
+    Failed,
+    Skipped,
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ConformanceCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    pub duration_ms: u64,
+}
+
+/// 一致性报告
+#[derive(Debug, Clone, Serialize)]
+pub struct ConformanceReport {
+    pub plugin_id: String,
+    pub plugin_version: String,
+    /// 没有失败的检查
+    pub passed: bool,
+    pub passed_count: usize,
+    pub warning_count: usize,
+    pub failed_count: usize,
+    pub skipped_count: usize,
+    pub checks: Vec<ConformanceCheck>,
+}
+
+/// 插件进程的原始输出
+struct RawOutput {
+    timed_out: bool,
+    exit_success: bool,
+    stdout: String,
+    stderr: String,
+    elapsed: Duration,
+}
+
+impl RawOutput {
+    /// 第一行 JSON（与插件管理器的解析方式一致）
+    fn first_json_line(&self) -> Option<&str> {
+        self.stdout.lines().map(str::trim).find(|line| line.starts_with('{'))
+    }
+}
+
+/// 向插件写入原始输入并等待退出
+async fn run_raw(plugin: &LoadedPlugin, input: &[u8], timeout: Duration) -> anyhow::Result<RawOutput> {
+    let started = Instant::now();
+    let mut child = Command::new(&plugin.executable_path)
+        .current_dir(&plugin.path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // 插件可能不读取 stdin 直接退出，写入失败不算错误
+        let _ = stdin.write_all(input).await;
+        drop(stdin);
+    }
+
+    match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => {
+            let output = output?;
+            Ok(RawOutput {
+                timed_out: false,
+                exit_success: output.status.success(),
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                elapsed: started.elapsed(),
+            })
+        }
+        Err(_) => Ok(RawOutput {
+            timed_out: true,
+            exit_success: false,
+            stdout: String::new(),
+            stderr: String::new(),
+            elapsed: started.elapsed(),
+        }),
+    }
+}
+
+fn check(name: &'static str, status: CheckStatus, message: impl Into<String>, elapsed: Duration) -> ConformanceCheck {
+    ConformanceCheck {
+        name,
+        status,
+        message: message.into(),
+        duration_ms: elapsed.as_millis() as u64,
+    }
+}
+
+/// 发送请求并解析为 PluginResponse，失败时返回对应的检查结果
+async fn call(
+    name: &'static str,
+    plugin: &LoadedPlugin,
+    request: &serde_json::Value,
+    timeout: Duration,
+) -> Result<(PluginResponse, Duration), ConformanceCheck> {
+    let mut input = request.to_string().into_bytes();
+    input.push(b'\n');
+    let output = match run_raw(plugin, &input, timeout).await {
+        Ok(output) => output,
+        Err(e) => return Err(check(name, CheckStatus::Failed, format!("Failed to run plugin: {}", e), Duration::ZERO)),
+    };
+    if output.timed_out {
+        return Err(check(name, CheckStatus::Failed, format!("No response within {}s", timeout.as_secs()), output.elapsed));
+    }
+    if !output.exit_success {
+        return Err(check(name, CheckStatus::Failed, format!("Plugin exited with error: {}", output.stderr.trim()), output.elapsed));
+    }
+    let Some(line) = output.first_json_line() else {
+        return Err(check(name, CheckStatus::Failed, "No JSON line on stdout", output.elapsed));
+    };
+    match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(value) => match serde_json::from_value::<PluginResponse>(value) {
+            Ok(response) => Ok((response, output.elapsed)),
+            Err(e) => Err(check(name, CheckStatus::Failed, format!("Response does not match protocol: {}", e), output.elapsed)),
+        },
+        Err(e) => Err(check(name, CheckStatus::Failed, format!("Invalid JSON response: {}", e), output.elapsed)),
+    }
+}
+
+/// info：必须返回与 plugin.json 一致的插件信息
+async fn check_info(plugin: &LoadedPlugin, timeout: Duration) -> ConformanceCheck {
+    const NAME: &str = "info";
+    let request = serde_json::to_value(PluginRequest::Info).unwrap_or_default();
+    let (response, elapsed) = match call(NAME, plugin, &request, timeout).await {
+        Ok(result) => result,
+        Err(check) => return check,
+    };
+    match response.data {
+        Some(PluginResponseData::Info(info)) if response.success => {
+            if info.id != plugin.config.id {
+                check(NAME, CheckStatus::Warning, format!("Reported id '{}' differs from plugin.json '{}'", info.id, plugin.config.id), elapsed)
+            } else if info.version != plugin.config.version {
+                check(NAME, CheckStatus::Warning, format!("Reported version '{}' differs from plugin.json '{}'", info.version, plugin.config.version), elapsed)
+            } else {
+                check(NAME, CheckStatus::Passed, format!("{} v{}", info.name, info.version), elapsed)
+            }
+        }
+        _ => check(NAME, CheckStatus::Failed, "Expected success with plugin info data", elapsed),
+    }
+}
+
+/// 检查结果列表中每一项都符合刮削数据结构
+fn check_results(name: &'static str, results: &[ScrapeResult], elapsed: Duration) -> ConformanceCheck {
+    let errors: Vec<String> = results.iter()
+        .filter_map(|result| serde_json::to_value(result).ok())
+        .map(|value| ScrapeData::check(&value))
+        .filter(|report| !report.is_valid())
+        .map(|report| report.error_message())
+        .collect();
+    if errors.is_empty() {
+        check(name, CheckStatus::Passed, format!("{} result(s)", results.len()), elapsed)
+    } else {
+        check(name, CheckStatus::Failed, errors.join("; "), elapsed)
+    }
+}
+
+/// search：支持搜索时必须返回结果列表，或者结构化的错误
+async fn check_search(plugin: &LoadedPlugin, query: &str, timeout: Duration) -> ConformanceCheck {
+    const NAME: &str = "search";
+    if !plugin.config.supports_search {
+        return check(NAME, CheckStatus::Skipped, "Plugin does not declare supports_search", Duration::ZERO);
+    }
+    let request = serde_json::to_value(PluginRequest::Search { query: query.to_string(), page: Some(1) }).unwrap_or_default();
+    let (response, elapsed) = match call(NAME, plugin, &request, timeout).await {
+        Ok(result) => result,
+        Err(check) => return check,
+    };
+    match response.data {
+        Some(PluginResponseData::List(list)) if response.success => check_results(NAME, &list.results, elapsed),
+        _ if !response.success && response.error.is_some() => {
+            check(NAME, CheckStatus::Warning, "Search returned an error for the sample query", elapsed)
+        }
+        _ => check(NAME, CheckStatus::Failed, "Expected success with a result list", elapsed),
+    }
+}
+
+/// get：返回单个刮削结果，或者结构化的错误
+async fn check_get(plugin: &LoadedPlugin, sample_id: Option<&str>, timeout: Duration) -> ConformanceCheck {
+    const NAME: &str = "get";
+    let Some(sample_id) = sample_id else {
+        return check(NAME, CheckStatus::Skipped, "No sample_id given", Duration::ZERO);
+    };
+    let request = serde_json::to_value(PluginRequest::Get {
+        id: sample_id.to_string(),
+        content_type: None,
+        series: None,
+    }).unwrap_or_default();
+    let (response, elapsed) = match call(NAME, plugin, &request, timeout).await {
+        Ok(result) => result,
+        Err(check) => return check,
+    };
+    match response.data {
+        Some(PluginResponseData::Single(result)) if response.success => check_results(NAME, &[result], elapsed),
+        _ if !response.success && response.error.is_some() => {
+            check(NAME, CheckStatus::Warning, "Get returned an error for the sample id", elapsed)
+        }
+        _ => check(NAME, CheckStatus::Failed, "Expected success with a single scrape result", elapsed),
+    }
+}
+
+/// 异常输入：非 JSON 或未知动作必须返回 success=false 的 JSON，而不是崩溃或挂起
+async fn check_rejects(name: &'static str, plugin: &LoadedPlugin, input: &[u8], timeout: Duration) -> ConformanceCheck {
+    let output = match run_raw(plugin, input, timeout).await {
+        Ok(output) => output,
+        Err(e) => return check(name, CheckStatus::Failed, format!("Failed to run plugin: {}", e), Duration::ZERO),
+    };
+    if output.timed_out {
+        return check(name, CheckStatus::Failed, format!("Plugin hung for {}s", timeout.as_secs()), output.elapsed);
+    }
+    let response = output.first_json_line()
+        .and_then(|line| serde_json::from_str::<serde_json::Value>(line).ok());
+    match response {
+        Some(value) if value.get("success").and_then(|v| v.as_bool()) == Some(false) => {
+            if value.get("error").is_some_and(|e| !e.is_null()) {
+                check(name, CheckStatus::Passed, "Rejected with an error response", output.elapsed)
+            } else {
+                check(name, CheckStatus::Warning, "Rejected without an error message", output.elapsed)
+            }
+        }
+        Some(_) => check(name, CheckStatus::Failed, "Invalid input was reported as success", output.elapsed),
+        None => check(name, CheckStatus::Warning, "No JSON error response (plugin exited without output)", output.elapsed),
+    }
+}
+
+/// 超时行为：stdin 关闭且没有输入时插件必须及时退出
+async fn check_exits_without_input(plugin: &LoadedPlugin, timeout: Duration) -> ConformanceCheck {
+    const NAME: &str = "exits_on_closed_stdin";
+    match run_raw(plugin, b"", timeout).await {
+        Ok(output) if output.timed_out => {
+            check(NAME, CheckStatus::Failed, format!("Plugin did not exit within {}s after stdin closed", timeout.as_secs()), output.elapsed)
+        }
+        Ok(output) => check(NAME, CheckStatus::Passed, "Exited after stdin closed", output.elapsed),
+        Err(e) => check(NAME, CheckStatus::Failed, format!("Failed to run plugin: {}", e), Duration::ZERO),
+    }
+}
+
+/// 运行全部一致性检查
+pub async fn run_conformance(plugin: &LoadedPlugin, options: &SelftestOptions) -> ConformanceReport {
+    let timeout = Duration::from_secs(options.timeout_secs.clamp(1, MAX_SELFTEST_TIMEOUT_SECS));
+
+    let checks = vec![
+        check_info(plugin, timeout).await,
+        check_search(plugin, &options.sample_query, timeout).await,
+        check_get(plugin, options.sample_id.as_deref(), timeout).await,
+        check_rejects("malformed_input", plugin, b"{not json\n", timeout).await,
+        check_rejects("unknown_action", plugin, b"{\"action\":\"__selftest_unknown__\"}\n", timeout).await,
+        check_exits_without_input(plugin, timeout).await,
+    ];
+
+    let count = |status: CheckStatus| checks.iter().filter(|c| c.status == status).count();
+    ConformanceReport {
+        plugin_id: plugin.config.id.clone(),
+        plugin_version: plugin.config.version.clone(),
+        passed: count(CheckStatus::Failed) == 0,
+        passed_count: count(CheckStatus::Passed),
+        warning_count: count(CheckStatus::Warning),
+        failed_count: count(CheckStatus::Failed),
+        skipped_count: count(CheckStatus::Skipped),
+        checks,
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::plugins::manager::PluginManager;
+    use std::os::unix::fs::PermissionsExt;
+
+    async fn load_script_plugin(dir: &std::path::Path, script: &str) -> LoadedPlugin {
+        let plugin_dir = dir.join("selftest_plugin");
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        std::fs::write(
+            plugin_dir.join("plugin.json"),
+            r#"{"id":"selftest_plugin","name":"Selftest","version":"1.0","executable":"run.sh","supports_search":false}"#,
+        ).unwrap();
+        let script_path = plugin_dir.join("run.sh");
+        std::fs::write(&script_path, script).unwrap();
+        std::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut manager = PluginManager::new(dir);
+        manager.scan_plugins().await.unwrap();
+        manager.get_plugin("selftest_plugin").unwrap().clone()
+    }
+
+    #[tokio::test]
+    async fn test_conformance_report() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let plugin = load_script_plugin(dir.path(), r#"#!/bin/sh
+read line
+case "$line" in
+  *'"action":"info"'*) echo '{"success":true,"data":{"id":"selftest_plugin","name":"Selftest","version":"1.0"}}';;
+  *) echo '{"success":false,"error":"unsupported request"}';;
+esac
+"#).await;
+
+        let report = run_conformance(&plugin, &SelftestOptions { timeout_secs: 5, ..Default::default() }).await;
+        let status = |name: &str| report.checks.iter().find(|c| c.name == name).unwrap().status;
+        assert_eq!(status("info"), CheckStatus::Passed);
+        assert_eq!(status("search"), CheckStatus::Skipped);
+        assert_eq!(status("get"), CheckStatus::Skipped);
+        assert_eq!(status("malformed_input"), CheckStatus::Passed);
+        assert_eq!(status("unknown_action"), CheckStatus::Passed);
+        assert_eq!(status("exits_on_closed_stdin"), CheckStatus::Passed);
+        assert!(report.passed);
+    }
+
+    #[tokio::test]
+    async fn test_conformance_detects_hang() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let plugin = load_script_plugin(dir.path(), "#!/bin/sh\nsleep 5\n").await;
+
+        let report = run_conformance(&plugin, &SelftestOptions { timeout_secs: 1, ..Default::default() }).await;
+        assert!(!report.passed);
+        assert!(report.checks.iter().any(|c| c.name == "exits_on_closed_stdin" && c.status == CheckStatus::Failed));
+    }
+}
@@ -0,0 +1,157 @@
+//! HTTP 服务器连接参数
+//!
+//! 通过环境变量配置：
+//! - HTTP2_ENABLED：是否支持 HTTP/2（明文 h2c，与 HTTP/1.1 自动协商），默认 true
+//! - HTTP_KEEP_ALIVE：HTTP/1.1 是否保持连接，默认 true
+//! - HTTP_IDLE_TIMEOUT_SECS：HTTP/1.1 连接空闲（等待下一个请求头）的超时秒数，默认 75
+//! - HTTP2_MAX_CONCURRENT_STREAMS：每个 HTTP/2 连接的最大并发流数，默认 256
+//! - HTTP2_KEEP_ALIVE_INTERVAL_SECS：HTTP/2 心跳（PING）间隔秒数，0 表示不发送，默认 30
+//! - HTTP2_KEEP_ALIVE_TIMEOUT_SECS：HTTP/2 心跳响应超时秒数，默认 20
+//! - TCP_NODELAY：是否禁用 Nagle 算法，默认 true
+//!
+//! 手机上浏览图片较多的页面时会并发大量小请求，复用连接（HTTP/2 多路复用或 HTTP/1.1 keep-alive）
+//! 可以省去每个请求的 TCP 握手
+
+use std::time::Duration;
+
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::service::TowerToHyperService;
+use tokio::net::TcpListener;
+
+/// 服务器连接参数
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub http2: bool,
+    pub keep_alive: bool,
+    pub idle_timeout: Duration,
+    pub max_concurrent_streams: u32,
+    /// 为 None 时不发送心跳
+    pub http2_keep_alive_interval: Option<Duration>,
+    pub http2_keep_alive_timeout: Duration,
+    pub tcp_nodelay: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            http2: true,
+            keep_alive: true,
+            idle_timeout: Duration::from_secs(75),
+            max_concurrent_streams: 256,
+            http2_keep_alive_interval: Some(Duration::from_secs(30)),
+            http2_keep_alive_timeout: Duration::from_secs(20),
+            tcp_nodelay: true,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// 按变量名读取配置，未设置或无法解析的使用默认值
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let flag = |key: &str, default: bool| match lookup(key).map(|v| v.trim().to_lowercase()).as_deref() {
+            Some("true" | "1" | "yes") => true,
+            Some("false" | "0" | "no") => false,
+            _ => default,
+        };
+        let number = |key: &str| lookup(key).and_then(|v| v.trim().parse::<u64>().ok());
+        let seconds = |key: &str, default: Duration| number(key).map(Duration::from_secs).unwrap_or(default);
+
+        Self {
+            http2: flag("HTTP2_ENABLED", defaults.http2),
+            keep_alive: flag("HTTP_KEEP_ALIVE", defaults.keep_alive),
+            idle_timeout: seconds("HTTP_IDLE_TIMEOUT_SECS", defaults.idle_timeout),
+            max_concurrent_streams: number("HTTP2_MAX_CONCURRENT_STREAMS")
+                .filter(|n| *n > 0)
+                .map(|n| n.min(u32::MAX as u64) as u32)
+                .unwrap_or(defaults.max_concurrent_streams),
+            http2_keep_alive_interval: match number("HTTP2_KEEP_ALIVE_INTERVAL_SECS") {
+                Some(0) => None,
+                Some(secs) => Some(Duration::from_secs(secs)),
+                None => defaults.http2_keep_alive_interval,
+            },
+            http2_keep_alive_timeout: seconds("HTTP2_KEEP_ALIVE_TIMEOUT_SECS", defaults.http2_keep_alive_timeout),
+            tcp_nodelay: flag("TCP_NODELAY", defaults.tcp_nodelay),
+        }
+    }
+
+    /// 从环境变量读取配置
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    fn builder(&self) -> Builder<TokioExecutor> {
+        let mut builder = Builder::new(TokioExecutor::new());
+        builder.http1()
+            .keep_alive(self.keep_alive)
+            .header_read_timeout(self.idle_timeout)
+            .timer(TokioTimer::new());
+        builder.http2()
+            .max_concurrent_streams(self.max_concurrent_streams)
+            .keep_alive_interval(self.http2_keep_alive_interval)
+            .keep_alive_timeout(self.http2_keep_alive_timeout)
+            .timer(TokioTimer::new());
+        if self.http2 {
+            builder
+        } else {
+            builder.http1_only()
+        }
+    }
+}
+
+/// 接受连接并处理请求（每个连接一个任务）
+pub async fn serve(listener: TcpListener, app: Router, config: &ServerConfig) -> std::io::Result<()> {
+    let builder = config.builder();
+    loop {
+        let (stream, remote) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                // 文件描述符耗尽等临时错误，稍后重试
+                tracing::warn!("Failed to accept connection: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        if config.tcp_nodelay {
+            if let Err(e) = stream.set_nodelay(true) {
+                tracing::debug!("Failed to set TCP_NODELAY for {}: {}", remote, e);
+            }
+        }
+
+        let builder = builder.clone();
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            // 没有 WebSocket 接口，不需要协议升级（serve_connection_with_upgrades 会忽略 http1_only）
+            if let Err(e) = builder.serve_connection(TokioIo::new(stream), service).await {
+                tracing::debug!("Connection from {} closed with error: {}", remote, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_from_lookup() {
+        assert_eq!(ServerConfig::from_lookup(|_| None), ServerConfig::default());
+
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("HTTP2_ENABLED", "false"),
+            ("HTTP_IDLE_TIMEOUT_SECS", "120"),
+            ("HTTP2_MAX_CONCURRENT_STREAMS", "0"),
+            ("HTTP2_KEEP_ALIVE_INTERVAL_SECS", "0"),
+            ("TCP_NODELAY", "maybe"),
+        ]);
+        let config = ServerConfig::from_lookup(|key| env.get(key).map(|v| v.to_string()));
+        assert!(!config.http2);
+        assert_eq!(config.idle_timeout, Duration::from_secs(120));
+        assert_eq!(config.max_concurrent_streams, 256);
+        assert_eq!(config.http2_keep_alive_interval, None);
+        assert!(config.tcp_nodelay);
+    }
+}
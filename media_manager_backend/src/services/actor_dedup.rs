@@ -0,0 +1,358 @@
+//! 重复演员检测服务
+//!
+//! 扫描有作品关联的演员，按规范化名字（假名转罗马字、忽略大小写和标点、合并长音）、
+//! 姓名顺序、别名和编辑距离找出疑似同一人的演员，生成合并建议报告
+
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::RwLock;
+
+use crate::database;
+use crate::models::{ActorMergeGroup, ActorMergeResult, DuplicateActorCandidate, DuplicateActorGroup, DuplicateActorReport};
+use super::transliteration::romanize_kana;
+
+/// 参与编辑距离比较的最短名字长度（太短的名字相差一个字符很可能是不同的人）
+const MIN_FUZZY_KEY_LEN: usize = 6;
+
+lazy_static::lazy_static! {
+    /// 最近一次生成的报告
+    static ref LATEST_REPORT: RwLock<Option<DuplicateActorReport>> = RwLock::new(None);
+}
+
+/// 名字的比较键：假名转罗马字，转小写，去掉空格和标点，合并长音
+pub fn name_key(name: &str) -> String {
+    let romanized: String = romanize_kana(name)
+        .to_lowercase()
+        .chars()
+        .map(strip_macron)
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+    collapse_long_vowels(&romanized)
+}
+
+/// 两段式名字调换顺序后的比较键（"Yui Hatano" 与 "Hatano Yui"）
+fn reversed_name_key(name: &str) -> Option<String> {
+    let tokens: Vec<&str> = name
+        .split(|c: char| c.is_whitespace() || matches!(c, '・' | '·' | '.' | ',' | '_' | '-'))
+        .filter(|t| !t.is_empty())
+        .collect();
+    match tokens.as_slice() {
+        [first, last] => Some(name_key(&format!("{}{}", last, first))),
+        _ => None,
+    }
+}
+
+fn strip_macron(c: char) -> char {
+    match c {
+        'ā' | 'â' => 'a',
+        'ī' | 'î' => 'i',
+        'ū' | 'û' => 'u',
+        'ē' | 'ê' => 'e',
+        'ō' | 'ô' => 'o',
+        _ => c,
+    }
+}
+
+/// 合并罗马字长音写法（ou/oo → o，uu → u）
+fn collapse_long_vowels(key: &str) -> String {
+    key.replace("ou", "o").replace("oo", "o").replace("uu", "u")
+}
+
+/// 编辑距离（按字符）
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+pub(crate) struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    pub(crate) fn new(len: usize) -> Self {
+        Self { parent: (0..len).collect() }
+    }
+
+    pub(crate) fn find(&mut self, i: usize) -> usize {
+        let parent = self.parent[i];
+        if parent == i {
+            return i;
+        }
+        let root = self.find(parent);
+        self.parent[i] = root;
+        root
+    }
+
+    pub(crate) fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[b] = a;
+        }
+    }
+}
+
+/// 比较键的来源，数值越大判定原因越具体
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum KeyKind {
+    NormalizedName,
+    KanaRomaji,
+    NameOrder,
+    Alias,
+}
+
+impl KeyKind {
+    fn reason(self) -> &'static str {
+        match self {
+            KeyKind::NormalizedName => "normalized_name",
+            KeyKind::KanaRomaji => "kana_romaji",
+            KeyKind::NameOrder => "name_order",
+            KeyKind::Alias => "alias",
+        }
+    }
+}
+
+fn candidate_keys(candidate: &DuplicateActorCandidate) -> Vec<(String, KeyKind)> {
+    let name_kind = if romanize_kana(&candidate.name) != candidate.name {
+        KeyKind::KanaRomaji
+    } else {
+        KeyKind::NormalizedName
+    };
+    let mut keys = vec![(name_key(&candidate.name), name_kind)];
+    if let Some(key) = reversed_name_key(&candidate.name) {
+        keys.push((key, KeyKind::NameOrder));
+    }
+    for alias in &candidate.aliases {
+        keys.push((name_key(alias), KeyKind::Alias));
+        if let Some(key) = reversed_name_key(alias) {
+            keys.push((key, KeyKind::Alias));
+        }
+    }
+    keys.retain(|(key, _)| !key.is_empty());
+    keys
+}
+
+/// 找出疑似重复的演员组（每组至少两人，建议保留作品最多的演员）
+pub fn find_duplicate_groups(candidates: &[DuplicateActorCandidate]) -> Vec<DuplicateActorGroup> {
+    let mut uf = UnionFind::new(candidates.len());
+    let mut pair_reasons: Vec<(usize, usize, &'static str)> = Vec::new();
+
+    // 比较键完全相同
+    let mut by_key: HashMap<String, Vec<(usize, KeyKind)>> = HashMap::new();
+    for (i, candidate) in candidates.iter().enumerate() {
+        for (key, kind) in candidate_keys(candidate) {
+            by_key.entry(key).or_default().push((i, kind));
+        }
+    }
+    for entries in by_key.values() {
+        for (n, &(a, kind_a)) in entries.iter().enumerate() {
+            for &(b, kind_b) in &entries[n + 1..] {
+                if a != b {
+                    uf.union(a, b);
+                    pair_reasons.push((a, b, kind_a.max(kind_b).reason()));
+                }
+            }
+        }
+    }
+
+    // 编辑距离为 1：先用删除一个字符后的变体找候选，再计算实际距离
+    let primary: Vec<Vec<char>> = candidates.iter().map(|c| name_key(&c.name).chars().collect()).collect();
+    let mut by_variant: HashMap<String, BTreeSet<usize>> = HashMap::new();
+    for (i, key) in primary.iter().enumerate() {
+        if key.len() < MIN_FUZZY_KEY_LEN {
+            continue;
+        }
+        by_variant.entry(key.iter().collect()).or_default().insert(i);
+        for skip in 0..key.len() {
+            let variant: String = key.iter().enumerate().filter(|(j, _)| *j != skip).map(|(_, c)| c).collect();
+            by_variant.entry(variant).or_default().insert(i);
+        }
+    }
+    let mut compared = BTreeSet::new();
+    for indices in by_variant.values().filter(|set| set.len() > 1) {
+        let indices: Vec<usize> = indices.iter().copied().collect();
+        for (n, &a) in indices.iter().enumerate() {
+            for &b in &indices[n + 1..] {
+                if primary[a] != primary[b] && compared.insert((a, b)) && levenshtein(&primary[a], &primary[b]) == 1 {
+                    uf.union(a, b);
+                    pair_reasons.push((a, b, "edit_distance"));
+                }
+            }
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..candidates.len() {
+        let root = uf.find(i);
+        members.entry(root).or_default().push(i);
+    }
+    let mut reasons: HashMap<usize, BTreeSet<&'static str>> = HashMap::new();
+    for (a, _, reason) in pair_reasons {
+        let root = uf.find(a);
+        reasons.entry(root).or_default().insert(reason);
+    }
+
+    let mut groups: Vec<DuplicateActorGroup> = members
+        .into_iter()
+        .filter(|(_, indices)| indices.len() > 1)
+        .map(|(root, indices)| {
+            let mut actors: Vec<DuplicateActorCandidate> = indices.iter().map(|&i| candidates[i].clone()).collect();
+            // 作品多的优先，其次是有头像的
+            actors.sort_by(|a, b| {
+                b.work_count.cmp(&a.work_count)
+                    .then_with(|| b.avatar_url.is_some().cmp(&a.avatar_url.is_some()))
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+            DuplicateActorGroup {
+                target_id: actors[0].id.clone(),
+                actors,
+                reasons: reasons.remove(&root).unwrap_or_default().into_iter().map(String::from).collect(),
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| a.actors[0].name.cmp(&b.actors[0].name));
+    groups
+}
+
+/// 扫描数据库生成报告并缓存
+pub async fn generate_report(pool: &Pool<Sqlite>) -> Result<DuplicateActorReport> {
+    let candidates = database::list_duplicate_actor_candidates(pool).await?;
+    let report = DuplicateActorReport {
+        generated_at: Utc::now(),
+        actors_scanned: candidates.len(),
+        groups: find_duplicate_groups(&candidates),
+    };
+    *LATEST_REPORT.write().await = Some(report.clone());
+    Ok(report)
+}
+
+/// 获取最近一次的报告（没有报告或 refresh 为 true 时重新生成）
+pub async fn latest_report(pool: &Pool<Sqlite>, refresh: bool) -> Result<DuplicateActorReport> {
+    if !refresh {
+        if let Some(report) = LATEST_REPORT.read().await.clone() {
+            return Ok(report);
+        }
+    }
+    generate_report(pool).await
+}
+
+/// 批量合并（groups 为 None 时合并当前报告中的全部建议），完成后重新生成报告
+pub async fn merge_groups(pool: &Pool<Sqlite>, groups: Option<Vec<ActorMergeGroup>>) -> Result<ActorMergeResult> {
+    let groups = match groups {
+        Some(groups) => groups,
+        None => latest_report(pool, false).await?
+            .groups
+            .into_iter()
+            .map(|group| ActorMergeGroup {
+                source_ids: group.actors.into_iter().map(|a| a.id).filter(|id| *id != group.target_id).collect(),
+                target_id: group.target_id,
+            })
+            .collect(),
+    };
+
+    let mut result = ActorMergeResult::default();
+    for group in groups {
+        match database::merge_actors(pool, &group.target_id, &group.source_ids).await {
+            Ok(merged) => {
+                result.merged_groups += 1;
+                result.merged_actors += merged;
+            }
+            Err(sqlx::Error::RowNotFound) => result.errors.push(format!("Actor {} not found", group.target_id)),
+            Err(e) => result.errors.push(format!("Failed to merge into {}: {}", group.target_id, e)),
+        }
+    }
+
+    generate_report(pool).await?;
+    Ok(result)
+}
+
+/// 定期生成重复演员报告的后台任务
+pub struct ActorDedupTask {
+    pool: Pool<Sqlite>,
+    interval: Duration,
+}
+
+impl ActorDedupTask {
+    pub fn new(pool: Pool<Sqlite>, interval: Duration) -> Self {
+        Self { pool, interval }
+    }
+
+    /// 启动定期扫描任务（启动后等待一个周期再执行首次扫描）
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            match generate_report(&self.pool).await {
+                Ok(report) => tracing::info!(
+                    "Duplicate actor scan completed: {} actors, {} groups",
+                    report.actors_scanned, report.groups.len()
+                ),
+                Err(e) => tracing::warn!("Duplicate actor scan failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: &str, name: &str, work_count: i64) -> DuplicateActorCandidate {
+        DuplicateActorCandidate {
+            id: id.to_string(),
+            name: name.to_string(),
+            avatar_url: None,
+            work_count,
+            aliases: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_name_key() {
+        assert_eq!(name_key("Yui Hatano"), "yuihatano");
+        assert_eq!(name_key("はたの ゆい"), "hatanoyui");
+        assert_eq!(name_key("Yūko Satō"), name_key("Yuuko Satou"));
+        assert_eq!(reversed_name_key("Hatano Yui").as_deref(), Some("yuihatano"));
+        assert_eq!(reversed_name_key("Hatano"), None);
+    }
+
+    #[test]
+    fn test_find_duplicate_groups() {
+        let candidates = vec![
+            candidate("1", "Yui Hatano", 5),
+            candidate("2", "Hatano Yui", 1),
+            candidate("3", "ゆいはたの", 2),
+            candidate("4", "Yui Hatamo", 1),
+            candidate("5", "Someone Else", 3),
+            candidate("6", "Ai", 1),
+            candidate("7", "Aki", 1),
+        ];
+        let groups = find_duplicate_groups(&candidates);
+        assert_eq!(groups.len(), 1);
+        let group = &groups[0];
+        assert_eq!(group.target_id, "1");
+        assert_eq!(group.actors.len(), 4);
+        assert_eq!(group.reasons, vec!["edit_distance", "kana_romaji", "name_order"]);
+    }
+
+    #[test]
+    fn test_alias_matches() {
+        let mut target = candidate("1", "波多野結衣", 3);
+        target.aliases.push("Yui Hatano".to_string());
+        let groups = find_duplicate_groups(&[target, candidate("2", "hatano yui", 1)]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].reasons, vec!["alias"]);
+    }
+}
@@ -0,0 +1,120 @@
+//! 重复封面检测服务
+//!
+//! 比较缓存封面时计算的感知哈希，找出封面几乎相同的媒体。
+//! 不同刮削器对同一作品各建一个条目时封面通常来自同一张原图，这类组基本都是重复条目。
+
+use std::collections::HashMap;
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+
+use crate::database;
+use crate::models::{DuplicateArtworkCandidate, DuplicateArtworkGroup, DuplicateArtworkReport, StoredPosterHash};
+use super::actor_dedup::UnionFind;
+use super::cache::phash;
+
+/// 当前封面的感知哈希（封面更换后旧哈希不再使用）
+fn poster_hash(candidate: &DuplicateArtworkCandidate) -> Option<u64> {
+    let poster_url = candidate.poster_url.as_ref()?;
+    let stored: StoredPosterHash = serde_json::from_str(candidate.poster_phash.as_ref()?).ok()?;
+    if !stored.urls.contains(poster_url) {
+        return None;
+    }
+    phash::from_hex(&stored.hash)
+}
+
+/// 找出封面汉明距离不超过 max_distance 的媒体组（距离可传递，每组至少两个）
+pub fn find_duplicate_groups(candidates: &[DuplicateArtworkCandidate], max_distance: u32) -> (usize, Vec<DuplicateArtworkGroup>) {
+    let hashed: Vec<(&DuplicateArtworkCandidate, u64)> = candidates.iter()
+        .filter_map(|candidate| Some((candidate, poster_hash(candidate)?)))
+        .collect();
+
+    let mut uf = UnionFind::new(hashed.len());
+    let mut links: Vec<(usize, u32)> = Vec::new();
+    for (i, (_, a)) in hashed.iter().enumerate() {
+        for (j, (_, b)) in hashed.iter().enumerate().skip(i + 1) {
+            let distance = phash::distance(*a, *b);
+            if distance <= max_distance {
+                uf.union(i, j);
+                links.push((i, distance));
+            }
+        }
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..hashed.len() {
+        let root = uf.find(i);
+        members.entry(root).or_default().push(i);
+    }
+    let mut distances: HashMap<usize, u32> = HashMap::new();
+    for (i, distance) in links {
+        let root = uf.find(i);
+        let max = distances.entry(root).or_default();
+        *max = (*max).max(distance);
+    }
+
+    let mut groups: Vec<DuplicateArtworkGroup> = members
+        .into_iter()
+        .filter(|(_, indices)| indices.len() > 1)
+        .map(|(root, indices)| {
+            let mut media: Vec<DuplicateArtworkCandidate> = indices.iter().map(|&i| hashed[i].0.clone()).collect();
+            media.sort_by(|a, b| a.title.cmp(&b.title).then_with(|| a.id.cmp(&b.id)));
+            DuplicateArtworkGroup {
+                media,
+                distance: distances.get(&root).copied().unwrap_or_default(),
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| a.media[0].title.cmp(&b.media[0].title));
+    (hashed.len(), groups)
+}
+
+/// 扫描数据库生成重复封面报告
+pub async fn generate_report(pool: &Pool<Sqlite>, max_distance: u32) -> Result<DuplicateArtworkReport> {
+    let candidates = database::list_duplicate_artwork_candidates(pool).await?;
+    let (media_scanned, groups) = find_duplicate_groups(&candidates, max_distance);
+    Ok(DuplicateArtworkReport { media_scanned, max_distance, groups })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: &str, poster_url: &str, stored_urls: &[&str], hash: u64) -> DuplicateArtworkCandidate {
+        let stored = StoredPosterHash {
+            urls: stored_urls.iter().map(|s| s.to_string()).collect(),
+            hash: phash::to_hex(hash),
+        };
+        DuplicateArtworkCandidate {
+            id: id.to_string(),
+            code: None,
+            title: format!("Movie {}", id),
+            year: None,
+            media_type: "Movie".to_string(),
+            poster_url: Some(poster_url.to_string()),
+            poster_phash: Some(serde_json::to_string(&stored).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_find_duplicate_groups() {
+        let candidates = vec![
+            candidate("1", "/cache/a.webp", &["http://a/1.jpg", "/cache/a.webp"], 0xFF00),
+            // 与 1 相差 2 位，与 3 相差 3 位（传递后同组）
+            candidate("2", "http://b/2.jpg", &["http://b/2.jpg", "/cache/b.webp"], 0xFF03),
+            candidate("3", "http://c/3.jpg", &["http://c/3.jpg"], 0xFF1F),
+            // 完全不同的封面
+            candidate("4", "http://d/4.jpg", &["http://d/4.jpg"], !0xFF00),
+            // 封面已更换，旧哈希不参与比较
+            candidate("5", "http://e/new.jpg", &["http://e/old.jpg"], 0xFF00),
+        ];
+        let (scanned, groups) = find_duplicate_groups(&candidates, 3);
+        assert_eq!(scanned, 4);
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<&str> = groups[0].media.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["1", "2", "3"]);
+        assert_eq!(groups[0].distance, 3);
+
+        let (_, groups) = find_duplicate_groups(&candidates, 0);
+        assert!(groups.is_empty());
+    }
+}
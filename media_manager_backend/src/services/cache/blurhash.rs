@@ -0,0 +1,145 @@
+// BlurHash 编码 - 图片占位图
+//
+// 把图片压缩为 20~30 个字符的 BlurHash 字符串，前端在真实图片加载前解码为模糊的占位图。
+// 算法见 https://github.com/woltapp/blurhash ，编码前先把图片缩小到 32 像素以内，计算量可以忽略。
+
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use std::f32::consts::PI;
+
+/// 编码前缩小到的最大边长
+const SAMPLE_SIZE: u32 = 32;
+
+const BASE83_CHARS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+fn encode_base83(value: u32, length: u32, out: &mut String) {
+    for i in 1..=length {
+        let digit = (value / 83u32.pow(length - i)) % 83;
+        out.push(BASE83_CHARS[digit as usize] as char);
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let v = value as f32 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u32 {
+    let v = value.clamp(0.0, 1.0);
+    if v <= 0.003_130_8 {
+        (v * 12.92 * 255.0 + 0.5) as u32
+    } else {
+        ((1.055 * v.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as u32
+    }
+}
+
+fn sign_pow(value: f32, exp: f32) -> f32 {
+    value.abs().powf(exp).copysign(value)
+}
+
+/// 按图片方向选择分量数：横图 4x3，竖图 3x4
+fn components(width: u32, height: u32) -> (u32, u32) {
+    if width >= height { (4, 3) } else { (3, 4) }
+}
+
+/// 计算图片的 BlurHash（空图片返回 None）
+pub fn encode_image(img: &DynamicImage) -> Option<String> {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let sample = if width > SAMPLE_SIZE || height > SAMPLE_SIZE {
+        img.resize(SAMPLE_SIZE, SAMPLE_SIZE, FilterType::Triangle)
+    } else {
+        img.clone()
+    };
+    let rgba = sample.to_rgba8();
+    let (width, height) = rgba.dimensions();
+    let (cx, cy) = components(width, height);
+
+    let pixels: Vec<[f32; 3]> = rgba.pixels()
+        .map(|p| [srgb_to_linear(p[0]), srgb_to_linear(p[1]), srgb_to_linear(p[2])])
+        .collect();
+    let mut factors = Vec::with_capacity((cx * cy) as usize);
+    for j in 0..cy {
+        for i in 0..cx {
+            let normalisation = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+            let mut sum = [0.0f32; 3];
+            for y in 0..height {
+                let basis_y = (PI * j as f32 * y as f32 / height as f32).cos();
+                for x in 0..width {
+                    let basis = basis_y * (PI * i as f32 * x as f32 / width as f32).cos();
+                    let pixel = pixels[(y * width + x) as usize];
+                    for c in 0..3 {
+                        sum[c] += basis * pixel[c];
+                    }
+                }
+            }
+            let scale = normalisation / (width * height) as f32;
+            factors.push(sum.map(|v| v * scale));
+        }
+    }
+
+    let mut hash = String::with_capacity(4 + 2 * factors.len());
+    encode_base83((cx - 1) + (cy - 1) * 9, 1, &mut hash);
+
+    let (dc, ac) = factors.split_first()?;
+    let max_value = if ac.is_empty() {
+        encode_base83(0, 1, &mut hash);
+        1.0
+    } else {
+        let actual_max = ac.iter().flatten().fold(0.0f32, |max, v| max.max(v.abs()));
+        let quantised = (actual_max * 166.0 - 0.5).floor().clamp(0.0, 82.0) as u32;
+        encode_base83(quantised, 1, &mut hash);
+        (quantised + 1) as f32 / 166.0
+    };
+
+    let dc_value = (linear_to_srgb(dc[0]) << 16) + (linear_to_srgb(dc[1]) << 8) + linear_to_srgb(dc[2]);
+    encode_base83(dc_value, 4, &mut hash);
+    for factor in ac {
+        let quant = |v: f32| (sign_pow(v / max_value, 0.5) * 9.0 + 9.5).floor().clamp(0.0, 18.0) as u32;
+        encode_base83(quant(factor[0]) * 19 * 19 + quant(factor[1]) * 19 + quant(factor[2]), 2, &mut hash);
+    }
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_solid_color() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(64, 36, image::Rgb([255, 0, 0])));
+        let hash = encode_image(&img).unwrap();
+        // 4x3 分量：1 位尺寸 + 1 位最大值 + 4 位平均色 + 11 个 2 位的交流分量
+        assert_eq!(hash.len(), 28);
+        assert!(hash.starts_with('L'));
+        // 平均色为纯红 0xFF0000
+        assert_eq!(&hash[2..6], "TI:j");
+        // 绿色和蓝色通道为 0，交流分量的 G、B 量化值都是中间值 9
+        assert!(hash.as_bytes()[6..].chunks(2).all(|c| {
+            let digit = |b: u8| BASE83_CHARS.iter().position(|&x| x == b).unwrap() as u32;
+            let quant = digit(c[0]) * 83 + digit(c[1]);
+            (quant / 19) % 19 == 9 && quant % 19 == 9
+        }));
+    }
+
+    #[test]
+    fn test_encode_portrait_gradient() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(200, 300, |_, y| {
+            let v = (y * 255 / 299) as u8;
+            image::Rgb([v, v, v])
+        }));
+        let hash = encode_image(&img).unwrap();
+        // 竖图使用 3x4 分量，尺寸位为 2 + 3 * 9 = 29
+        assert_eq!(hash.len(), 28);
+        assert!(hash.starts_with('T'));
+        assert_ne!(&hash[1..2], "0");
+
+        let empty = DynamicImage::ImageRgb8(image::RgbImage::new(0, 0));
+        assert_eq!(encode_image(&empty), None);
+    }
+}
@@ -6,18 +6,34 @@
 // - 处理媒体保存时的缓存逻辑
 // - 提供缓存统计和清理功能
 
+use crate::models::{Actor, ActorPhotoCacheSettings, StorageConfig, StoredPosterColors, StoredPosterHash, ACTOR_PHOTO_FIELDS};
 use crate::services::cache::{
     CacheError, CacheField, ConfigManager, ImageDownloader, UrlDetector, VideoSelector,
-    PreviewVideoUrl, DownloadTask, CachePath,
+    PreviewVideoUrl, DownloadTask, CachePath, phash,
 };
+use crate::services::storage::{open_storage, ByteStream, LocalStorage, StorageBackend};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Sqlite};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::fs;
 use tracing::{debug, error, info, warn};
 
+/// 从环境变量 CACHE_STORAGE 读取远程缓存存储配置（JSON，格式同媒体库根目录的 storage）
+fn cache_storage_from_env() -> Result<Option<StorageConfig>, CacheError> {
+    let Ok(json) = std::env::var("CACHE_STORAGE") else {
+        return Ok(None);
+    };
+    if json.trim().is_empty() {
+        return Ok(None);
+    }
+    let config: StorageConfig = serde_json::from_str(&json)
+        .map_err(|e| CacheError::Config(format!("CACHE_STORAGE 格式错误: {}", e)))?;
+    config.validate().map_err(CacheError::Config)?;
+    Ok(Some(config).filter(|config| !config.is_local()))
+}
+
 /// 缓存服务
 ///
 /// 协调所有缓存组件，提供统一的缓存管理接口
@@ -59,8 +75,13 @@ impl CacheService {
         
         let config_manager = Arc::new(ConfigManager::load(config_path).await?);
 
-        // 创建图片下载器
-        let downloader = Arc::new(ImageDownloader::new(cache_dir).await?);
+        // 创建图片下载器，配置了 CACHE_STORAGE 时缓存文件同时写入对象存储
+        let mut downloader = ImageDownloader::new(cache_dir.clone()).await?;
+        if let Some(storage) = cache_storage_from_env()? {
+            info!("图片缓存同时写入 {} 存储", storage.storage_type());
+            downloader = downloader.with_remote_storage(open_storage(&storage, &cache_dir));
+        }
+        let downloader = Arc::new(downloader);
 
         Ok(Self {
             config_manager,
@@ -247,6 +268,7 @@ impl CacheService {
         // 4. 批量下载图片
         if !tasks.is_empty() {
             let results = self.downloader.download_batch(tasks).await;
+            self.save_image_results(media_id, &results).await;
             self.update_image_urls(media_id, results).await?;
         }
 
@@ -291,12 +313,217 @@ impl CacheService {
 
         if !tasks.is_empty() {
             let results = self.downloader.download_batch(tasks).await;
+            self.save_image_results(media_id, &results).await;
             self.update_image_urls(media_id, results).await?;
         }
 
         Ok(())
     }
 
+    /// 重新下载并转换封面和背景图
+    ///
+    /// 不受刮削器缓存配置限制，覆盖已有的缓存文件（如修改图片设置后重新生成）。
+    /// 已是缓存路径的图片使用缓存时记录的原始 URL，找不到原始 URL 的本地图片跳过；数据库中的 URL 保持不变。
+    ///
+    /// # 参数
+    /// - `media_id`: 媒体 ID
+    /// - `media_data`: 媒体数据
+    ///
+    /// # 返回
+    /// - `ArtworkRefreshResult`: 成功、失败和跳过的图片数
+    pub async fn refresh_media_artwork(
+        &self,
+        media_id: &str,
+        media_data: &MediaData,
+    ) -> ArtworkRefreshResult {
+        let mut result = ArtworkRefreshResult::default();
+        let mut tasks = Vec::new();
+
+        let sources = self.load_image_sources(media_id).await;
+        let images = media_data.poster_url.iter()
+            .map(|url| ("poster".to_string(), None, url))
+            .chain(media_data.backdrop_urls.iter().enumerate()
+                .map(|(index, url)| (format!("backdrop_{}", index), Some(index), url)));
+
+        for (field_name, index, url) in images {
+            // 已替换为缓存路径的图片从缓存时记录的原始 URL 重新下载
+            let source = if url.starts_with("http://") || url.starts_with("https://") {
+                url
+            } else if let Some(source) = sources.get(url) {
+                source
+            } else {
+                result.skipped += 1;
+                continue;
+            };
+            let kind = if index.is_some() { "backdrop" } else { "poster" };
+            let save_path = CachePath::image_path(media_id, kind, index);
+            tasks.push(DownloadTask::new(field_name, index, source.clone(), save_path));
+        }
+
+        let downloads = self.downloader.download_batch(tasks).await;
+        self.save_image_results(media_id, &downloads).await;
+        for download in downloads {
+            match download.result {
+                Ok(_) => result.refreshed += 1,
+                Err(e) => {
+                    warn!("图片刷新失败: media_id={}, field={}, error={:?}", media_id, download.field_name, e);
+                    result.failed += 1;
+                }
+            }
+        }
+
+        info!(
+            "图片刷新完成: media_id={}, 成功={}, 失败={}, 跳过={}",
+            media_id, result.refreshed, result.failed, result.skipped
+        );
+        result
+    }
+
+    /// 处理演员保存时的图片缓存
+    ///
+    /// 开启演员图片缓存后，异步下载演员的远程图片并转换为 WebP，
+    /// 不阻塞演员保存流程。
+    ///
+    /// # 参数
+    /// - `actor`: 刚保存的演员
+    pub async fn handle_actor_save(&self, actor: &Actor) -> Result<(), CacheError> {
+        let settings = crate::database::get_actor_photo_cache_settings(&self.db_pool)
+            .await
+            .map_err(|e| CacheError::Database(format!("读取演员图片缓存设置失败: {}", e)))?;
+        if !settings.enabled {
+            return Ok(());
+        }
+
+        let service = self.clone_for_task();
+        let actor = actor.clone();
+        tokio::spawn(async move {
+            service.cache_actor_photos(&actor, &settings).await;
+        });
+
+        Ok(())
+    }
+
+    /// 开启演员图片缓存时立即下载演员图片并等待完成（批量刮削需要汇报下载结果）
+    ///
+    /// # 返回
+    /// - `Some(ArtworkRefreshResult)`: 下载结果
+    /// - `None`: 未开启演员图片缓存
+    pub async fn cache_actor_photos_now(&self, actor: &Actor) -> Result<Option<ArtworkRefreshResult>, CacheError> {
+        let settings = crate::database::get_actor_photo_cache_settings(&self.db_pool)
+            .await
+            .map_err(|e| CacheError::Database(format!("读取演员图片缓存设置失败: {}", e)))?;
+        if !settings.enabled {
+            return Ok(None);
+        }
+        Ok(Some(self.cache_actor_photos(actor, &settings).await))
+    }
+
+    /// 下载并缓存演员图片
+    ///
+    /// 只处理远程 URL，已是本地路径的图片跳过。下载成功后把数据库中的 URL
+    /// 替换为本地路径（字段在下载期间被修改时不覆盖），失败时保留原始 URL。
+    ///
+    /// # 参数
+    /// - `actor`: 演员
+    /// - `settings`: 演员图片缓存设置（决定缓存哪些字段）
+    ///
+    /// # 返回
+    /// - `ArtworkRefreshResult`: 成功、失败和跳过的图片数
+    pub async fn cache_actor_photos(
+        &self,
+        actor: &Actor,
+        settings: &ActorPhotoCacheSettings,
+    ) -> ArtworkRefreshResult {
+        let mut result = ArtworkRefreshResult::default();
+        let mut tasks = Vec::new();
+        let mut columns = HashMap::new();
+
+        for field in ACTOR_PHOTO_FIELDS {
+            let (column, value) = match field {
+                "avatar" => ("avatar_url", &actor.avatar_url),
+                "photo" => ("photo_url", &actor.photo_url),
+                "poster" => ("poster_url", &actor.poster_url),
+                _ => ("backdrop_url", &actor.backdrop_url),
+            };
+            let Some(value) = value.as_deref().filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            if !settings.caches_field(field) {
+                continue;
+            }
+
+            // 写真为逗号分隔的多张图片，其他字段只有一张
+            let urls: Vec<String> = if field == "photo" {
+                value.split(',').map(|url| url.trim().to_string()).filter(|url| !url.is_empty()).collect()
+            } else {
+                vec![value.to_string()]
+            };
+            for (index, url) in urls.iter().enumerate() {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    result.skipped += 1;
+                    continue;
+                }
+                let file_index = (field == "photo").then_some(index);
+                let save_path = CachePath::actor_image_path(&actor.id, field, file_index);
+                tasks.push(DownloadTask::new(field.to_string(), Some(index), url.clone(), save_path));
+            }
+            columns.insert(field, (column, value.to_string(), urls));
+        }
+
+        let mut changed = HashSet::new();
+        for download in self.downloader.download_batch(tasks).await {
+            match download.result {
+                Ok(local_path) => {
+                    if let (Some((_, _, urls)), Some(index)) = (columns.get_mut(download.field_name.as_str()), download.index) {
+                        urls[index] = local_path;
+                        changed.insert(download.field_name);
+                    }
+                    result.refreshed += 1;
+                }
+                Err(e) => {
+                    warn!("演员图片下载失败，保留原始 URL: actor_id={}, field={}, error={:?}", actor.id, download.field_name, e);
+                    result.failed += 1;
+                }
+            }
+        }
+
+        for (field, (column, original, urls)) in columns {
+            if !changed.contains(field) {
+                continue;
+            }
+            if let Err(e) = self.update_actor_image_url(&actor.id, column, &original, &urls.join(",")).await {
+                warn!("更新演员图片 URL 失败: actor_id={}, field={}, error={:?}", actor.id, field, e);
+            }
+        }
+
+        info!(
+            "演员图片缓存完成: actor_id={}, 成功={}, 失败={}, 跳过={}",
+            actor.id, result.refreshed, result.failed, result.skipped
+        );
+        result
+    }
+
+    /// 更新演员图片 URL（仅在字段仍为原始值时更新）
+    async fn update_actor_image_url(
+        &self,
+        actor_id: &str,
+        column: &str,
+        original: &str,
+        local_value: &str,
+    ) -> Result<(), CacheError> {
+        let sql = format!("UPDATE actors SET {column} = ? WHERE id = ? AND {column} = ?");
+        sqlx::query(&sql)
+            .bind(local_value)
+            .bind(actor_id)
+            .bind(original)
+            .execute(&self.db_pool)
+            .await
+            .map_err(|e| CacheError::Database(format!("更新演员图片 URL 失败: {}", e)))?;
+
+        debug!("已更新演员图片 URL: actor_id={}, column={}", actor_id, column);
+        Ok(())
+    }
+
     /// 缓存预览视频（智能选择最高清晰度）
     async fn cache_preview_video(
         &self,
@@ -369,6 +596,134 @@ impl CacheService {
         Ok(())
     }
 
+    /// 保存下载结果附带的信息：占位图、封面主色调和感知哈希、图片来源
+    async fn save_image_results(&self, media_id: &str, results: &[crate::services::cache::image_downloader::DownloadResult]) {
+        self.save_image_placeholders(media_id, results).await;
+        self.save_poster_analysis(media_id, results).await;
+        self.save_image_sources(media_id, results).await;
+    }
+
+    /// 读取缓存路径到原始 URL 的映射
+    async fn load_image_sources(&self, media_id: &str) -> HashMap<String, String> {
+        let row: Result<Option<(Option<String>,)>, sqlx::Error> =
+            sqlx::query_as("SELECT image_sources FROM media_items WHERE id = ?")
+                .bind(media_id)
+                .fetch_optional(&self.db_pool)
+                .await;
+        match row {
+            Ok(row) => row
+                .and_then(|(sources,)| sources)
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+            Err(e) => {
+                warn!("读取图片来源失败: media_id={}, error={}", media_id, e);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// 记录封面和背景图缓存路径对应的原始 URL
+    ///
+    /// 数据库中的地址被替换为缓存路径后，刷新图片时据此重新下载。失败只记录日志。
+    async fn save_image_sources(&self, media_id: &str, results: &[crate::services::cache::image_downloader::DownloadResult]) {
+        let downloaded: Vec<(&str, &str)> = results.iter()
+            .filter(|r| r.field_name == "poster" || r.field_name.starts_with("backdrop_"))
+            .filter(|r| r.url.starts_with("http://") || r.url.starts_with("https://"))
+            .filter_map(|r| Some((r.result.as_deref().ok()?, r.url.as_str())))
+            .collect();
+        if downloaded.is_empty() {
+            return;
+        }
+
+        let mut sources = self.load_image_sources(media_id).await;
+        for (local_path, url) in downloaded {
+            sources.insert(local_path.to_string(), url.to_string());
+        }
+        let result = sqlx::query("UPDATE media_items SET image_sources = ? WHERE id = ?")
+            .bind(serde_json::to_string(&sources).unwrap_or_default())
+            .bind(media_id)
+            .execute(&self.db_pool)
+            .await;
+        match result {
+            Ok(_) => debug!("已保存图片来源: media_id={}", media_id),
+            Err(e) => warn!("保存图片来源失败: media_id={}, error={}", media_id, e),
+        }
+    }
+
+    /// 保存封面和背景图的 BlurHash 占位图
+    ///
+    /// 以原始 URL 和缓存路径为键（数据库中保存的可能是其中任意一个），
+    /// 重新缓存时覆盖旧值。失败只记录日志。
+    async fn save_image_placeholders(&self, media_id: &str, results: &[crate::services::cache::image_downloader::DownloadResult]) {
+        let placeholders: Vec<(&str, &str, &str)> = results.iter()
+            .filter(|r| r.field_name == "poster" || r.field_name.starts_with("backdrop_"))
+            .filter_map(|r| Some((r.url.as_str(), r.result.as_deref().ok()?, r.blurhash.as_deref()?)))
+            .collect();
+        if placeholders.is_empty() {
+            return;
+        }
+
+        let result: Result<(), CacheError> = async {
+            let row: Option<(Option<String>,)> = sqlx::query_as("SELECT image_placeholders FROM media_items WHERE id = ?")
+                .bind(media_id)
+                .fetch_optional(&self.db_pool)
+                .await
+                .map_err(|e| CacheError::Database(format!("查询 image_placeholders 失败: {}", e)))?;
+            let Some((existing,)) = row else {
+                return Ok(());
+            };
+            let mut map: serde_json::Map<String, serde_json::Value> = existing
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default();
+            for (url, local_path, blurhash) in placeholders {
+                map.insert(url.to_string(), blurhash.into());
+                map.insert(local_path.to_string(), blurhash.into());
+            }
+
+            sqlx::query("UPDATE media_items SET image_placeholders = ? WHERE id = ?")
+                .bind(serde_json::Value::Object(map).to_string())
+                .bind(media_id)
+                .execute(&self.db_pool)
+                .await
+                .map_err(|e| CacheError::Database(format!("更新 image_placeholders 失败: {}", e)))?;
+            Ok(())
+        }.await;
+
+        match result {
+            Ok(()) => debug!("已保存图片占位图: media_id={}", media_id),
+            Err(e) => warn!("保存图片占位图失败: media_id={}, error={:?}", media_id, e),
+        }
+    }
+
+    /// 保存封面的主色调和感知哈希
+    ///
+    /// 同时记录原始 URL 和缓存路径，封面更换后旧的结果不再使用。失败只记录日志。
+    async fn save_poster_analysis(&self, media_id: &str, results: &[crate::services::cache::image_downloader::DownloadResult]) {
+        let Some((local_path, poster)) = results.iter()
+            .filter(|r| r.field_name == "poster")
+            .find_map(|r| Some((r.result.as_ref().ok()?, r)))
+        else {
+            return;
+        };
+        let urls = vec![poster.url.clone(), local_path.clone()];
+        let colors = poster.colors.clone().map(|colors| StoredPosterColors { urls: urls.clone(), colors });
+        let phash = poster.phash.map(|hash| StoredPosterHash { urls, hash: phash::to_hex(hash) });
+        if colors.is_none() && phash.is_none() {
+            return;
+        }
+
+        let result = sqlx::query("UPDATE media_items SET poster_colors = ?, poster_phash = ? WHERE id = ?")
+            .bind(colors.and_then(|colors| serde_json::to_string(&colors).ok()))
+            .bind(phash.and_then(|phash| serde_json::to_string(&phash).ok()))
+            .bind(media_id)
+            .execute(&self.db_pool)
+            .await;
+        match result {
+            Ok(_) => debug!("已保存封面主色调和感知哈希: media_id={}", media_id),
+            Err(e) => warn!("保存封面主色调和感知哈希失败: media_id={}, error={}", media_id, e),
+        }
+    }
+
     /// 更新 poster URL
     async fn update_poster_url(&self, media_id: &str, local_path: &str) -> Result<(), CacheError> {
         sqlx::query("UPDATE media SET poster_url = ? WHERE id = ?")
@@ -706,6 +1061,8 @@ impl CacheService {
             deleted_files += self.remove_dir_all(&videos_dir).await?;
         }
 
+        self.clear_remote(&[images_dir, videos_dir]).await?;
+
         info!(
             "媒体缓存清理完成: media_id={}, 删除文件数={}",
             media_id, deleted_files
@@ -746,87 +1103,120 @@ impl CacheService {
             })?;
         }
 
+        self.clear_remote(&[images_dir, videos_dir]).await?;
+
         info!("所有缓存清理完成: 删除文件数={}", deleted_files);
 
         Ok(())
     }
 
-    /// 清理孤立缓存
+    /// 删除远程缓存存储中的目录
+    async fn clear_remote(&self, dirs: &[PathBuf]) -> Result<(), CacheError> {
+        if let Some(remote) = self.downloader.remote_storage() {
+            for dir in dirs {
+                let key = dir.to_string_lossy().replace('\\', "/");
+                remote.delete_prefix(&key).await
+                    .map_err(|e| CacheError::Config(format!("清理远程缓存失败: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 读取缓存文件（`key` 如 `cache/images/media/{id}/poster.webp`）
     ///
-    /// 删除数据库中不存在的媒体的缓存文件
+    /// 优先读取本地副本，本地不存在时从远程缓存存储读取
+    pub async fn read_cached_file(&self, key: &str) -> Option<ByteStream> {
+        let local = LocalStorage::new(self.downloader.cache_dir());
+        if let Ok(Some(_)) = local.size(key).await {
+            return local.read(key, None).await.ok();
+        }
+        let remote = self.downloader.remote_storage()?;
+        match remote.read(key, None).await {
+            Ok(stream) => Some(stream),
+            Err(e) => {
+                debug!("远程缓存读取失败: {} ({})", key, e);
+                None
+            }
+        }
+    }
+
+    /// 查找孤立缓存
+    ///
+    /// 列出数据库中已不存在的媒体的缓存目录，不删除任何文件
     ///
     /// # 返回
-    /// - `Ok(())`: 清理成功
-    /// - `Err(CacheError)`: 清理失败
+    /// - `Ok(Vec<OrphanedCache>)`: 孤立的缓存目录
+    /// - `Err(CacheError)`: 查找失败
     ///
     /// # 实现细节
-    /// - 遍历缓存目录中的所有媒体 ID
+    /// - 遍历 images/media 和 videos/media 下的媒体 ID 目录
     /// - 查询数据库检查媒体是否存在
-    /// - 如果不存在，删除该媒体的缓存目录
-    pub async fn clear_orphaned_cache(&self) -> Result<(), CacheError> {
-        info!("开始清理孤立缓存");
+    pub async fn find_orphaned_cache(&self) -> Result<Vec<OrphanedCache>, CacheError> {
+        let mut orphaned = Vec::new();
 
-        let mut deleted_files = 0;
+        for root in [CachePath::images_root(), CachePath::videos_root()] {
+            let dir = root.join("media");
+            if !dir.exists() {
+                continue;
+            }
 
-        // 清理孤立的图片缓存
-        let images_dir = CachePath::images_root().join("media");
-        if images_dir.exists() {
-            deleted_files += self.clear_orphaned_in_dir(&images_dir).await?;
-        }
+            let mut entries = fs::read_dir(&dir).await.map_err(|e| {
+                CacheError::FileSystem(crate::services::cache::FileSystemError::IoError(e))
+            })?;
 
-        // 清理孤立的视频缓存
-        let videos_dir = CachePath::videos_root().join("media");
-        if videos_dir.exists() {
-            deleted_files += self.clear_orphaned_in_dir(&videos_dir).await?;
-        }
+            while let Some(entry) = entries.next_entry().await.map_err(|e| {
+                CacheError::FileSystem(crate::services::cache::FileSystemError::IoError(e))
+            })? {
+                let path = entry.path();
 
-        info!("孤立缓存清理完成: 删除文件数={}", deleted_files);
+                // 跳过非目录
+                if !path.is_dir() {
+                    continue;
+                }
 
-        Ok(())
+                // 获取媒体 ID（目录名）
+                let media_id = match path.file_name().and_then(|n| n.to_str()) {
+                    Some(id) => id.to_string(),
+                    None => continue,
+                };
+
+                if self.media_exists(&media_id).await? {
+                    continue;
+                }
+
+                debug!("发现孤立缓存: media_id={}", media_id);
+                let (size_bytes, files) = self.calculate_dir_size(&path).await?;
+                orphaned.push(OrphanedCache {
+                    media_id,
+                    path: path.display().to_string(),
+                    files,
+                    size_bytes,
+                });
+            }
+        }
+
+        Ok(orphaned)
     }
 
-    /// 清理指定目录中的孤立缓存（内部方法）
+    /// 清理孤立缓存
     ///
-    /// # 参数
-    /// - `dir`: 要清理的目录（images/media 或 videos/media）
+    /// 删除数据库中不存在的媒体的缓存文件
     ///
     /// # 返回
-    /// - `Ok(usize)`: 删除的文件数
+    /// - `Ok(Vec<OrphanedCache>)`: 已删除的缓存目录
     /// - `Err(CacheError)`: 清理失败
-    async fn clear_orphaned_in_dir(&self, dir: &PathBuf) -> Result<usize, CacheError> {
-        let mut deleted_files = 0;
-
-        let mut entries = fs::read_dir(dir).await.map_err(|e| {
-            CacheError::FileSystem(crate::services::cache::FileSystemError::IoError(e))
-        })?;
-
-        while let Some(entry) = entries.next_entry().await.map_err(|e| {
-            CacheError::FileSystem(crate::services::cache::FileSystemError::IoError(e))
-        })? {
-            let path = entry.path();
-
-            // 跳过非目录
-            if !path.is_dir() {
-                continue;
-            }
-
-            // 获取媒体 ID（目录名）
-            let media_id = match path.file_name().and_then(|n| n.to_str()) {
-                Some(id) => id,
-                None => continue,
-            };
-
-            // 检查媒体是否存在于数据库
-            let exists = self.media_exists(media_id).await?;
+    pub async fn clear_orphaned_cache(&self) -> Result<Vec<OrphanedCache>, CacheError> {
+        info!("开始清理孤立缓存");
 
-            if !exists {
-                // 媒体不存在，删除缓存
-                debug!("发现孤立缓存: media_id={}", media_id);
-                deleted_files += self.remove_dir_all(&path).await?;
-            }
+        let orphaned = self.find_orphaned_cache().await?;
+        let mut deleted_files = 0;
+        for entry in &orphaned {
+            deleted_files += self.remove_dir_all(&PathBuf::from(&entry.path)).await?;
         }
 
-        Ok(deleted_files)
+        info!("孤立缓存清理完成: 删除文件数={}", deleted_files);
+
+        Ok(orphaned)
     }
 
     /// 检查媒体是否存在于数据库
@@ -838,7 +1228,7 @@ impl CacheService {
     /// - `Ok(bool)`: 是否存在
     /// - `Err(CacheError)`: 查询失败
     async fn media_exists(&self, media_id: &str) -> Result<bool, CacheError> {
-        let result: Option<(i64,)> = sqlx::query_as("SELECT COUNT(*) FROM media WHERE id = ?")
+        let result: Option<(i64,)> = sqlx::query_as("SELECT COUNT(*) FROM media_items WHERE id = ?")
             .bind(media_id)
             .fetch_optional(&self.db_pool)
             .await
@@ -889,6 +1279,35 @@ pub struct CacheStats {
     pub by_scraper: HashMap<String, ScraperCacheStats>,
 }
 
+/// 孤立的缓存目录（所属媒体已被删除）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedCache {
+    /// 媒体 ID（目录名）
+    pub media_id: String,
+
+    /// 缓存目录路径
+    pub path: String,
+
+    /// 文件数
+    pub files: usize,
+
+    /// 占用空间（字节）
+    pub size_bytes: u64,
+}
+
+/// 单个媒体的图片刷新结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArtworkRefreshResult {
+    /// 重新下载并转换成功的图片数
+    pub refreshed: usize,
+
+    /// 下载或转换失败的图片数
+    pub failed: usize,
+
+    /// 本地图片且没有记录原始 URL 而跳过的图片数
+    pub skipped: usize,
+}
+
 /// 单个刮削器的缓存统计
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScraperCacheStats {
@@ -0,0 +1,120 @@
+// 主色调提取 - 详情页主题色
+//
+// 把缩小后的图片按颜色分桶（每通道 4 位），像素最多的桶为主色（dominant），
+// 与主色差异足够大的桶中按饱和度和占比选出强调色（accent），没有合适的桶时强调色与主色相同。
+
+use crate::models::ImageColors;
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use std::collections::HashMap;
+
+/// 提取前缩小到的最大边长
+const SAMPLE_SIZE: u32 = 64;
+
+/// 强调色至少占全部像素的比例
+const MIN_ACCENT_SHARE: f32 = 0.01;
+
+/// 强调色与主色在 RGB 空间的最小距离
+const MIN_ACCENT_DISTANCE: f32 = 60.0;
+
+#[derive(Default)]
+struct Bucket {
+    count: u32,
+    sum: [u64; 3],
+}
+
+impl Bucket {
+    fn average(&self) -> [u8; 3] {
+        let count = self.count.max(1) as u64;
+        self.sum.map(|c| (c / count) as u8)
+    }
+}
+
+/// HSV 饱和度和亮度（0~1）
+fn saturation_value(rgb: [u8; 3]) -> (f32, f32) {
+    let max = *rgb.iter().max().unwrap() as f32 / 255.0;
+    let min = *rgb.iter().min().unwrap() as f32 / 255.0;
+    let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+    (saturation, max)
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| (*x as f32 - y as f32).powi(2)).sum::<f32>().sqrt()
+}
+
+fn hex(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+/// 提取图片的主色和强调色（空图片或完全透明时返回 None）
+pub fn extract_colors(img: &DynamicImage) -> Option<ImageColors> {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let sample = if width > SAMPLE_SIZE || height > SAMPLE_SIZE {
+        img.resize(SAMPLE_SIZE, SAMPLE_SIZE, FilterType::Triangle)
+    } else {
+        img.clone()
+    };
+
+    let mut buckets: HashMap<u16, Bucket> = HashMap::new();
+    let mut total = 0u32;
+    for pixel in sample.to_rgba8().pixels() {
+        // 忽略大部分透明的像素
+        if pixel[3] < 128 {
+            continue;
+        }
+        let key = ((pixel[0] as u16 >> 4) << 8) | ((pixel[1] as u16 >> 4) << 4) | (pixel[2] as u16 >> 4);
+        let bucket = buckets.entry(key).or_default();
+        bucket.count += 1;
+        for c in 0..3 {
+            bucket.sum[c] += pixel[c] as u64;
+        }
+        total += 1;
+    }
+
+    let dominant = buckets.values().max_by_key(|bucket| bucket.count)?.average();
+    let accent = buckets.values()
+        .filter(|bucket| bucket.count as f32 >= total as f32 * MIN_ACCENT_SHARE)
+        .map(|bucket| bucket.average())
+        .filter(|rgb| distance(*rgb, dominant) >= MIN_ACCENT_DISTANCE)
+        .map(|rgb| {
+            let (saturation, value) = saturation_value(rgb);
+            // 过暗或过亮的颜色不适合做强调色
+            let brightness = 1.0 - (value - 0.65).abs();
+            (rgb, saturation * brightness)
+        })
+        .filter(|(_, score)| *score > 0.15)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(rgb, _)| rgb)
+        .unwrap_or(dominant);
+
+    Some(ImageColors { dominant: hex(dominant), accent: hex(accent) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_colors() {
+        // 深蓝背景上的一块橙色
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(40, 60, |x, y| {
+            if (10..30).contains(&x) && (20..40).contains(&y) {
+                image::Rgb([240, 120, 20])
+            } else {
+                image::Rgb([16, 32, 64])
+            }
+        }));
+        let colors = extract_colors(&img).unwrap();
+        assert_eq!(colors.dominant, "#102040");
+        assert_eq!(colors.accent, "#f07814");
+
+        // 纯色图片的强调色与主色相同
+        let solid = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(10, 10, image::Rgb([200, 200, 200])));
+        let colors = extract_colors(&solid).unwrap();
+        assert_eq!(colors.accent, colors.dominant);
+
+        assert_eq!(extract_colors(&DynamicImage::ImageRgb8(image::RgbImage::new(0, 0))), None);
+    }
+}
@@ -5,10 +5,12 @@
 // - 批量并发下载（限制并发数）
 // - 超时控制
 // - 失败重试
-// - WebP 转换集成
+// - WebP 转换集成（同时生成 BlurHash 占位图、提取主色调、计算感知哈希）
 
+use crate::models::ImageColors;
 use crate::services::cache::error::{CacheError, DownloadError};
 use crate::services::cache::webp_converter::WebPConverter;
+use crate::services::storage::StorageBackend;
 use reqwest::Client;
 use std::cmp::Ordering;
 use std::path::PathBuf;
@@ -107,8 +109,29 @@ pub struct DownloadResult {
     pub field_name: String,
     /// 索引
     pub index: Option<usize>,
+    /// 原始 URL
+    pub url: String,
     /// 结果（成功返回本地路径，失败返回错误）
     pub result: Result<String, CacheError>,
+    /// BlurHash 占位图（下载成功时生成）
+    pub blurhash: Option<String>,
+    /// 主色调（下载成功时提取）
+    pub colors: Option<ImageColors>,
+    /// 感知哈希（下载成功时计算）
+    pub phash: Option<u64>,
+}
+
+/// 已缓存的图片
+#[derive(Debug, Clone)]
+pub struct CachedImage {
+    /// 本地路径（用于 API）
+    pub path: String,
+    /// BlurHash 占位图
+    pub blurhash: Option<String>,
+    /// 主色调
+    pub colors: Option<ImageColors>,
+    /// 感知哈希
+    pub phash: Option<u64>,
 }
 
 /// 图片下载器
@@ -126,6 +149,9 @@ pub struct ImageDownloader {
 
     /// 转换并发控制（最多 3 个同时转换）
     conversion_semaphore: Arc<Semaphore>,
+
+    /// 远程缓存存储（S3/WebDAV），配置后缓存文件同时写入远程
+    remote: Option<Arc<dyn StorageBackend>>,
 }
 
 impl ImageDownloader {
@@ -166,9 +192,36 @@ impl ImageDownloader {
             cache_dir,
             download_semaphore: Arc::new(Semaphore::new(5)), // 最多 5 个并发下载
             conversion_semaphore: Arc::new(Semaphore::new(3)), // 最多 3 个并发转换
+            remote: None,
         })
     }
 
+    /// 设置远程缓存存储
+    pub fn with_remote_storage(mut self, remote: Arc<dyn StorageBackend>) -> Self {
+        self.remote = Some(remote);
+        self
+    }
+
+    /// 缓存根目录
+    pub fn cache_dir(&self) -> &PathBuf {
+        &self.cache_dir
+    }
+
+    /// 远程缓存存储
+    pub fn remote_storage(&self) -> Option<&Arc<dyn StorageBackend>> {
+        self.remote.as_ref()
+    }
+
+    /// 把缓存文件写入远程存储（失败时只记录日志，本地副本仍可用）
+    async fn mirror_to_remote(&self, save_path: &std::path::Path, data: Vec<u8>) {
+        if let Some(remote) = &self.remote {
+            let key = save_path.to_string_lossy().replace('\\', "/");
+            if let Err(e) = remote.write(&key, data).await {
+                warn!("写入远程缓存失败: {} ({})", key, e);
+            }
+        }
+    }
+
     /// 下载并缓存单张图片
     ///
     /// 下载图片后自动转换为 WebP 格式并保存到指定路径。
@@ -209,6 +262,15 @@ impl ImageDownloader {
         url: &str,
         save_path: PathBuf,
     ) -> Result<String, CacheError> {
+        self.download_and_cache_image(url, save_path).await.map(|image| image.path)
+    }
+
+    /// 下载并缓存图片，同时返回 BlurHash 占位图、主色调和感知哈希（超时和重试同 `download_and_cache`）
+    pub async fn download_and_cache_image(
+        &self,
+        url: &str,
+        save_path: PathBuf,
+    ) -> Result<CachedImage, CacheError> {
         debug!("开始下载图片: {} -> {:?}", url, save_path);
 
         // 重试逻辑：最多尝试 3 次（1 次初始 + 2 次重试）
@@ -219,12 +281,12 @@ impl ImageDownloader {
 
         for attempt in 1..=max_attempts {
             match self.download_and_cache_once(url, &save_path).await {
-                Ok(local_path) => {
+                Ok(image) => {
                     info!(
                         "图片下载成功: {} -> {} (尝试 {}/{})",
-                        url, local_path, attempt, max_attempts
+                        url, image.path, attempt, max_attempts
                     );
-                    return Ok(local_path);
+                    return Ok(image);
                 }
                 Err(e) => {
                     warn!(
@@ -329,13 +391,21 @@ impl ImageDownloader {
                 );
 
                 let result = downloader
-                    .download_and_cache(&task.url, task.save_path)
+                    .download_and_cache_image(&task.url, task.save_path)
                     .await;
+                let (blurhash, colors, phash) = match &result {
+                    Ok(image) => (image.blurhash.clone(), image.colors.clone(), image.phash),
+                    Err(_) => (None, None, None),
+                };
 
                 DownloadResult {
                     field_name,
                     index,
-                    result,
+                    url: task.url,
+                    result: result.map(|image| image.path),
+                    blurhash,
+                    colors,
+                    phash,
                 }
             });
 
@@ -353,7 +423,11 @@ impl ImageDownloader {
                     results.push(DownloadResult {
                         field_name: "unknown".to_string(),
                         index: None,
+                        url: String::new(),
                         result: Err(CacheError::Config(format!("任务执行失败: {}", e))),
+                        blurhash: None,
+                        colors: None,
+                        phash: None,
                     });
                 }
             }
@@ -377,6 +451,7 @@ impl ImageDownloader {
             cache_dir: self.cache_dir.clone(),
             download_semaphore: Arc::clone(&self.download_semaphore),
             conversion_semaphore: Arc::clone(&self.conversion_semaphore),
+            remote: self.remote.clone(),
         }
     }
 
@@ -385,7 +460,7 @@ impl ImageDownloader {
         &self,
         url: &str,
         save_path: &PathBuf,
-    ) -> Result<String, CacheError> {
+    ) -> Result<CachedImage, CacheError> {
         // 1. 下载图片（带超时）- 使用下载信号量控制并发
         let _download_permit = self.download_semaphore.acquire().await.map_err(|e| {
             CacheError::Config(format!("获取下载许可失败: {}", e))
@@ -403,7 +478,7 @@ impl ImageDownloader {
             CacheError::Config(format!("获取转换许可失败: {}", e))
         })?;
 
-        let webp_data = WebPConverter::convert_to_webp_async(image_data).await?;
+        let converted = WebPConverter::convert_and_analyze_async(image_data).await?;
 
         // 释放转换许可
         drop(_conversion_permit);
@@ -416,13 +491,18 @@ impl ImageDownloader {
             fs::create_dir_all(parent).await?;
         }
 
-        fs::write(&full_path, webp_data).await?;
+        fs::write(&full_path, &converted.data).await?;
 
         debug!("图片已保存: {:?}", full_path);
-
-        // 4. 返回相对路径（用于 API）
-        let relative_path = format!("/{}", save_path.display());
-        Ok(relative_path)
+        self.mirror_to_remote(save_path, converted.data).await;
+
+        // 4. 返回相对路径（用于 API）、占位图、主色调和感知哈希
+        Ok(CachedImage {
+            path: format!("/{}", save_path.display()),
+            blurhash: converted.blurhash,
+            colors: converted.colors,
+            phash: converted.phash,
+        })
     }
 
     /// 下载图片（带超时控制）
@@ -564,9 +644,10 @@ impl ImageDownloader {
             fs::create_dir_all(parent).await?;
         }
 
-        fs::write(&full_path, video_data).await?;
+        fs::write(&full_path, &video_data).await?;
 
         debug!("视频已保存: {:?}", full_path);
+        self.mirror_to_remote(save_path, video_data).await;
 
         // 3. 返回相对路径（用于 API）
         let relative_path = format!("/{}", save_path.display());
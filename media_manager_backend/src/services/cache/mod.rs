@@ -3,26 +3,29 @@
 // 本模块提供媒体图片的智能缓存功能，包括：
 // - 临时 URL 检测
 // - 自动开启缓存
-// - 图片下载与 WebP 转换
+// - 图片下载与 WebP 转换（同时生成 BlurHash 占位图、提取主色调、计算感知哈希）
 // - 视频智能缓存
 // - 缓存管理
 
+pub mod blurhash;
 pub mod cache_service;
+pub mod colors;
 pub mod config;
 pub mod config_manager;
 pub mod error;
 pub mod image_downloader;
 pub mod path;
+pub mod phash;
 pub mod url_detector;
 pub mod video_selector;
 pub mod webp_converter;
 
-pub use cache_service::{CacheService, CacheStats, MediaData, ScraperCacheStats};
+pub use cache_service::{ArtworkRefreshResult, CacheService, CacheStats, MediaData, OrphanedCache, ScraperCacheStats};
 pub use config::{CacheConfig, CacheField, ScraperCacheConfig};
 pub use config_manager::ConfigManager;
 pub use error::{CacheError, ConversionError, DownloadError, FileSystemError};
-pub use image_downloader::{DownloadTask, ImageDownloader};
+pub use image_downloader::{CachedImage, DownloadTask, ImageDownloader};
 pub use path::CachePath;
 pub use url_detector::UrlDetector;
 pub use video_selector::{PreviewVideoUrl, VideoQuality, VideoSelector};
-pub use webp_converter::WebPConverter;
+pub use webp_converter::{ConvertedImage, WebPConverter};
@@ -22,6 +22,9 @@ impl CachePath {
     /// 媒体子目录
     const MEDIA_DIR: &'static str = "media";
 
+    /// 演员子目录
+    const ACTORS_DIR: &'static str = "actors";
+
     /// 生成图片缓存路径
     ///
     /// # 参数
@@ -58,6 +61,37 @@ impl CachePath {
             .join(filename)
     }
 
+    /// 生成演员图片缓存路径
+    ///
+    /// # 参数
+    /// - `actor_id`: 演员 ID
+    /// - `field_name`: 字段名称（如 "avatar", "photo"）
+    /// - `index`: 可选的索引（用于多张写真）
+    ///
+    /// # 返回
+    /// 本地文件路径，格式：`cache/images/actors/{actor_id}/avatar.webp`
+    ///
+    /// # 示例
+    /// ```
+    /// use media_manager_backend::services::cache::CachePath;
+    ///
+    /// let path = CachePath::actor_image_path("abc-123", "photo", Some(1));
+    /// assert_eq!(path.to_str().unwrap(), "cache/images/actors/abc-123/photo_1.webp");
+    /// ```
+    pub fn actor_image_path(actor_id: &str, field_name: &str, index: Option<usize>) -> PathBuf {
+        let filename = if let Some(idx) = index {
+            format!("{}_{}.webp", field_name, idx)
+        } else {
+            format!("{}.webp", field_name)
+        };
+
+        PathBuf::from(Self::CACHE_ROOT)
+            .join(Self::IMAGES_DIR)
+            .join(Self::ACTORS_DIR)
+            .join(actor_id)
+            .join(filename)
+    }
+
     /// 生成视频缓存路径
     ///
     /// # 参数
@@ -177,6 +211,18 @@ mod tests {
         assert!(path.to_string_lossy().contains("abc-123"));
     }
 
+    #[test]
+    fn test_actor_image_path() {
+        let path = CachePath::actor_image_path("abc-123", "avatar", None);
+        assert_eq!(path.file_name().unwrap(), "avatar.webp");
+        assert!(path.to_string_lossy().contains("actors"));
+        assert!(path.to_string_lossy().contains("abc-123"));
+
+        let path = CachePath::actor_image_path("abc-123", "photo", Some(2));
+        assert_eq!(path.file_name().unwrap(), "photo_2.webp");
+        assert_ne!(path, CachePath::image_path("abc-123", "photo", Some(2)));
+    }
+
     #[test]
     fn test_video_path() {
         let path = CachePath::video_path("abc-123", "preview_video");
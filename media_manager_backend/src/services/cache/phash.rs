@@ -0,0 +1,98 @@
+// 感知哈希（pHash）- 重复封面检测
+//
+// 把图片转为灰度并缩放到 32x32，做二维 DCT 后取左上角 8x8 的低频系数，
+// 大于中位数（不含直流分量）的位记为 1，得到 64 位哈希。缩放、压缩和轻微调色后哈希基本不变，
+// 两个哈希的汉明距离越小图片越相似。
+
+use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use std::f32::consts::PI;
+
+/// DCT 前缩放到的边长
+const SAMPLE_SIZE: usize = 32;
+
+/// 取低频系数的边长
+const HASH_SIZE: usize = 8;
+
+/// 一维 DCT-II（只计算前 HASH_SIZE 个系数）
+fn dct(input: &[f32]) -> [f32; HASH_SIZE] {
+    let n = input.len() as f32;
+    let mut output = [0.0f32; HASH_SIZE];
+    for (k, out) in output.iter_mut().enumerate() {
+        *out = input.iter()
+            .enumerate()
+            .map(|(i, v)| v * (PI * k as f32 * (2.0 * i as f32 + 1.0) / (2.0 * n)).cos())
+            .sum();
+    }
+    output
+}
+
+/// 计算图片的感知哈希（空图片返回 None）
+pub fn compute(img: &DynamicImage) -> Option<u64> {
+    let (width, height) = img.dimensions();
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let gray = img.resize_exact(SAMPLE_SIZE as u32, SAMPLE_SIZE as u32, FilterType::Triangle).to_luma8();
+    let pixels: Vec<f32> = gray.pixels().map(|p| p[0] as f32).collect();
+
+    // 先按行再按列，只保留需要的低频部分
+    let rows: Vec<[f32; HASH_SIZE]> = pixels.chunks(SAMPLE_SIZE).map(dct).collect();
+    let columns: Vec<[f32; HASH_SIZE]> = (0..HASH_SIZE)
+        .map(|u| dct(&rows.iter().map(|row| row[u]).collect::<Vec<f32>>()))
+        .collect();
+    let coefficients: Vec<f32> = (0..HASH_SIZE)
+        .flat_map(|v| columns.iter().map(move |column| column[v]))
+        .collect();
+
+    let mut ac: Vec<f32> = coefficients[1..].to_vec();
+    ac.sort_by(f32::total_cmp);
+    let median = ac[ac.len() / 2];
+    Some(coefficients.iter()
+        .enumerate()
+        .filter(|(_, c)| **c > median)
+        .fold(0u64, |hash, (i, _)| hash | (1 << i)))
+}
+
+/// 两个哈希的汉明距离（0~64）
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// 十六进制表示（16 个字符）
+pub fn to_hex(hash: u64) -> String {
+    format!("{:016x}", hash)
+}
+
+/// 解析十六进制表示
+pub fn from_hex(hex: &str) -> Option<u64> {
+    u64::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(width: u32, height: u32, shift: i32) -> DynamicImage {
+        DynamicImage::ImageRgb8(image::RgbImage::from_fn(width, height, |x, y| {
+            let (fx, fy) = (x as f32 / width as f32, y as f32 / height as f32);
+            let v = ((fx * 7.0).sin() * (fy * 5.0).cos() * 100.0 + 128.0) as i32 + shift;
+            let v = v.clamp(0, 255) as u8;
+            image::Rgb([v, v / 2, 255 - v])
+        }))
+    }
+
+    #[test]
+    fn test_similar_images() {
+        let original = compute(&pattern(200, 300, 0)).unwrap();
+        // 缩小并整体调亮后仍然接近
+        let resized = compute(&pattern(100, 150, 20)).unwrap();
+        assert!(distance(original, resized) <= 4, "{}", distance(original, resized));
+
+        // 上下翻转后差异很大
+        let flipped = compute(&pattern(200, 300, 0).flipv()).unwrap();
+        assert!(distance(original, flipped) > 16, "{}", distance(original, flipped));
+
+        assert_eq!(from_hex(&to_hex(original)), Some(original));
+        assert_eq!(compute(&DynamicImage::ImageRgb8(image::RgbImage::new(0, 0))), None);
+    }
+}
@@ -144,6 +144,32 @@ impl VideoSelector {
             .max_by_key(|video| VideoQuality::parse(&video.quality))
             .cloned()
     }
+    
+    /// 按清晰度从高到低排序，清晰度相同时保持原有顺序
+    pub fn rank_by_quality(urls: &[PreviewVideoUrl]) -> Vec<PreviewVideoUrl> {
+        let mut ranked = urls.to_vec();
+        ranked.sort_by_key(|video| std::cmp::Reverse(VideoQuality::parse(&video.quality)));
+        ranked
+    }
+    
+    /// 解析数据库中保存的 preview_video_urls，兼容纯字符串 URL 和带清晰度的对象
+    pub fn parse_stored(json: &str) -> Vec<PreviewVideoUrl> {
+        let Ok(serde_json::Value::Array(items)) = serde_json::from_str::<serde_json::Value>(json) else {
+            return Vec::new();
+        };
+        items.into_iter()
+            .filter_map(|item| match item {
+                serde_json::Value::String(url) => Some(PreviewVideoUrl::new(VideoQuality::Unknown.as_str().to_string(), url)),
+                serde_json::Value::Object(obj) => {
+                    let url = obj.get("url")?.as_str()?.to_string();
+                    let quality = obj.get("quality").and_then(|q| q.as_str()).unwrap_or(VideoQuality::Unknown.as_str());
+                    Some(PreviewVideoUrl::new(quality.to_string(), url))
+                }
+                _ => None,
+            })
+            .filter(|video| !video.url.trim().is_empty())
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -333,4 +359,21 @@ mod tests {
         let deserialized: PreviewVideoUrl = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized, video);
     }
+
+    #[test]
+    fn test_rank_stored_preview_videos() {
+        let urls = VideoSelector::parse_stored(r#"[
+            "https://example.com/plain.mp4",
+            {"quality": "720P", "url": "https://example.com/720p.mp4"},
+            {"quality": "4K", "url": "https://example.com/4k.mp4"},
+            {"url": ""},
+            1
+        ]"#);
+        assert_eq!(urls.len(), 3);
+        assert_eq!(urls[0].quality, "Unknown");
+
+        let ranked: Vec<_> = VideoSelector::rank_by_quality(&urls).into_iter().map(|v| v.quality).collect();
+        assert_eq!(ranked, vec!["4K", "720P", "Unknown"]);
+        assert!(VideoSelector::parse_stored("not json").is_empty());
+    }
 }
@@ -5,7 +5,10 @@
 // - 动态图片转换（gif）
 // - 无损压缩
 // - 性能优化（流式处理、分块处理、异步处理）
+// - 转换时顺便生成 BlurHash 占位图、提取主色调、计算感知哈希
 
+use crate::models::ImageColors;
+use crate::services::cache::{blurhash, colors, phash};
 use crate::services::cache::error::ConversionError;
 use image::{AnimationDecoder, DynamicImage, GenericImageView, ImageFormat};
 use std::io::Cursor;
@@ -14,6 +17,19 @@ use tokio::task;
 /// WebP 转换器
 pub struct WebPConverter;
 
+/// 转换结果
+#[derive(Debug, Clone)]
+pub struct ConvertedImage {
+    /// WebP 数据
+    pub data: Vec<u8>,
+    /// BlurHash 占位图（图片为空时为 None）
+    pub blurhash: Option<String>,
+    /// 主色和强调色（图片为空时为 None）
+    pub colors: Option<ImageColors>,
+    /// 感知哈希（图片为空时为 None）
+    pub phash: Option<u64>,
+}
+
 /// 图片类型
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ImageType {
@@ -54,6 +70,20 @@ impl WebPConverter {
             .map_err(|e| ConversionError::ConversionFailed(format!("任务执行失败: {}", e)))?
     }
 
+    /// 异步将图片转换为 WebP 格式，同时生成 BlurHash 占位图、提取主色调并计算感知哈希（复用解码结果）
+    ///
+    /// # 参数
+    /// - `image_data`: 原始图片数据（jpg、png、gif 等）
+    ///
+    /// # 返回
+    /// - `Ok(ConvertedImage)`: WebP 数据、占位图、主色调和感知哈希
+    /// - `Err(ConversionError)`: 转换失败
+    pub async fn convert_and_analyze_async(image_data: Vec<u8>) -> Result<ConvertedImage, ConversionError> {
+        task::spawn_blocking(move || Self::convert(&image_data, true))
+            .await
+            .map_err(|e| ConversionError::ConversionFailed(format!("任务执行失败: {}", e)))?
+    }
+
     /// 将图片转换为 WebP 格式（无损压缩）
     ///
     /// # 参数
@@ -72,12 +102,17 @@ impl WebPConverter {
     /// std::fs::write("image.webp", webp_data).unwrap();
     /// ```
     pub fn convert_to_webp(image_data: &[u8]) -> Result<Vec<u8>, ConversionError> {
+        Self::convert(image_data, false).map(|converted| converted.data)
+    }
+
+    /// 转换图片，按需生成占位图、主色调和感知哈希
+    fn convert(image_data: &[u8], analyze: bool) -> Result<ConvertedImage, ConversionError> {
         // 检测图片类型
         let image_type = Self::detect_image_type(image_data)?;
 
         match image_type {
-            ImageType::Static => Self::convert_static_image(image_data),
-            ImageType::Animated => Self::convert_animated_image(image_data),
+            ImageType::Static => Self::convert_static_image(image_data, analyze),
+            ImageType::Animated => Self::convert_animated_image(image_data, analyze),
         }
     }
 
@@ -116,23 +151,30 @@ impl WebPConverter {
     }
 
     /// 转换静态图片（jpg、png、单帧 gif）
-    fn convert_static_image(image_data: &[u8]) -> Result<Vec<u8>, ConversionError> {
+    fn convert_static_image(image_data: &[u8], analyze: bool) -> Result<ConvertedImage, ConversionError> {
         // 解码图片
         let img = image::load_from_memory(image_data).map_err(|e| {
             ConversionError::DecodeFailed(format!("图片解码失败: {}", e))
         })?;
+        let (blurhash, colors, phash) = if analyze { Self::analyze(&img) } else { (None, None, None) };
 
         // 检查图片大小，大图片使用优化处理
         let (width, height) = img.dimensions();
         let size_mb = (width * height * 4) as f64 / (1024.0 * 1024.0);
 
-        if size_mb > 5.0 {
+        let data = if size_mb > 5.0 {
             // 大图片（>5MB 未压缩）使用分块处理
-            Self::convert_large_static_image(img)
+            Self::convert_large_static_image(img)?
         } else {
             // 小图片直接转换
-            Self::encode_static_webp(img)
-        }
+            Self::encode_static_webp(img)?
+        };
+        Ok(ConvertedImage { data, blurhash, colors, phash })
+    }
+
+    /// 生成占位图、提取主色调并计算感知哈希
+    fn analyze(img: &DynamicImage) -> (Option<String>, Option<ImageColors>, Option<u64>) {
+        (blurhash::encode_image(img), colors::extract_colors(img), phash::compute(img))
     }
 
     /// 编码静态图片为 WebP
@@ -178,7 +220,7 @@ impl WebPConverter {
     }
 
     /// 转换动态图片（多帧 GIF）
-    fn convert_animated_image(image_data: &[u8]) -> Result<Vec<u8>, ConversionError> {
+    fn convert_animated_image(image_data: &[u8], analyze: bool) -> Result<ConvertedImage, ConversionError> {
         // 解码 GIF 动画
         let cursor = Cursor::new(image_data);
         let decoder = image::codecs::gif::GifDecoder::new(cursor).map_err(|e| {
@@ -199,6 +241,11 @@ impl WebPConverter {
         let first_frame = &frames[0];
         let buffer = first_frame.buffer();
         let (width, height) = buffer.dimensions();
+        let (blurhash, colors, phash) = if analyze {
+            Self::analyze(&DynamicImage::ImageRgba8(buffer.clone()))
+        } else {
+            (None, None, None)
+        };
 
         // 创建动画 WebP 编码器
         // 注意：webp crate 的动画支持有限，这里我们先转换第一帧
@@ -208,7 +255,7 @@ impl WebPConverter {
 
         // TODO: 实现完整的动画 WebP 支持
         // 目前只转换第一帧，保持功能可用
-        Ok(webp_data.to_vec())
+        Ok(ConvertedImage { data: webp_data.to_vec(), blurhash, colors, phash })
     }
 }
 
@@ -334,6 +381,15 @@ mod tests {
         assert_eq!(&webp_data[8..12], b"WEBP");
     }
 
+    #[tokio::test]
+    async fn test_convert_and_analyze_async() {
+        let converted = WebPConverter::convert_and_analyze_async(create_test_png()).await.unwrap();
+        assert_eq!(&converted.data[0..4], b"RIFF");
+        assert_eq!(converted.blurhash.unwrap().len(), 28);
+        assert_eq!(converted.colors.unwrap().dominant, "#ff0000");
+        assert!(converted.phash.is_some());
+    }
+
     #[tokio::test]
     async fn test_convert_large_image_async() {
         // 创建一个较大的测试图片（100x100）
@@ -19,6 +19,13 @@ impl DatabaseService {
     
     /// 创建新的媒体项目
     pub async fn create_media(&self, title: String, media_type: MediaType) -> Result<MediaItem> {
+        let media = self.new_media(title, media_type).await?;
+        self.repository.insert_media(&media).await?;
+        Ok(media)
+    }
+    
+    /// 校验并构造新的媒体项目（不写入数据库，填好字段后再一次性插入）
+    pub async fn new_media(&self, title: String, media_type: MediaType) -> Result<MediaItem> {
         let media = MediaItem::new(title, media_type)
             .map_err(|e| anyhow::anyhow!("Validation error: {:?}", e))?;
         
@@ -28,7 +35,6 @@ impl DatabaseService {
             return Err(anyhow::anyhow!("Media with similar title already exists"));
         }
         
-        self.repository.insert_media(&media).await?;
         Ok(media)
     }
     
@@ -160,6 +166,11 @@ impl DatabaseService {
         Ok(results)
     }
     
+    /// 获取匹配高级搜索条件的全部媒体ID（忽略排序和分页）
+    pub async fn search_ids_with_filters(&self, filters: &SearchFilters) -> Result<Vec<String>> {
+        self.repository.search_media_ids_with_filters(filters).await
+    }
+    
     /// 获取分页媒体列表
     pub async fn get_media_list(&self, page: i32, page_size: i32) -> Result<(Vec<MediaItem>, i64)> {
         let offset = (page - 1) * page_size;
@@ -178,21 +189,18 @@ impl DatabaseService {
     ) -> Result<(Vec<MediaItem>, i64)> {
         let offset = (page - 1) * page_size;
         
-        // 转换为 repository 的筛选结构
-        let repo_filters = crate::database::repository::MediaListFilters {
-            media_type: filters.media_type.clone(),
-            studio: filters.studio.clone(),
-            series: filters.series.clone(),
-            keyword: filters.keyword.clone(),
-            year: filters.year,
-            genre: filters.genre.clone(),
-            sort_by: filters.sort_by.clone(),
-            sort_order: filters.sort_order.clone(),
-        };
+        let repo_filters = to_repo_filters(filters);
         
         self.repository.get_media_list_filtered(page_size, offset, &repo_filters).await
     }
     
+    /// 获取匹配筛选条件的全部媒体ID（用于按筛选条件批量操作）
+    pub async fn get_media_ids_filtered(&self, filters: &crate::api::media::MediaFilters) -> Result<Vec<String>> {
+        let repo_filters = to_repo_filters(filters);
+        
+        self.repository.get_media_ids_filtered(&repo_filters).await
+    }
+    
     /// 获取收藏列表
     pub async fn get_collections(&self) -> Result<Vec<Collection>> {
         self.repository.get_collections().await
@@ -224,6 +232,24 @@ impl DatabaseService {
     }
 }
 
+/// 转换为 repository 的筛选结构
+fn to_repo_filters(filters: &crate::api::media::MediaFilters) -> crate::database::repository::MediaListFilters {
+    crate::database::repository::MediaListFilters {
+        media_type: filters.media_type.clone(),
+        studio: filters.studio.clone(),
+        series: filters.series.clone(),
+        keyword: filters.keyword.clone(),
+        year: filters.year,
+        genre: filters.genre.clone(),
+        missing_poster: filters.missing_poster,
+        release_date_from: filters.release_date_from.clone(),
+        release_date_to: filters.release_date_to.clone(),
+        sort_by: filters.sort_by.clone(),
+        sort_order: filters.sort_order.clone(),
+        seed: filters.seed,
+    }
+}
+
 /// 数据库统计信息
 #[derive(Debug)]
 pub struct DatabaseStatistics {
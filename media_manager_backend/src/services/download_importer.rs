@@ -0,0 +1,398 @@
+//! 下载完成后的自动导入
+//!
+//! 定期查询下载客户端（qBittorrent、aria2）中推送的任务，下载完成后：
+//! 选出与媒体匹配的视频文件，按媒体库根目录的重命名模板硬链接（跨磁盘时复制）到库中，
+//! 关联到媒体并加入收藏（待观看），最后更新想要列表和任务状态
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+
+use crate::database;
+use crate::models::{render_rename_template, LibraryRoot, MediaFile, MediaItem, UpdateWantedRequest};
+use crate::services::file_scanner::VIDEO_EXTENSIONS;
+use crate::services::import_hooks::spawn_import_hooks;
+use crate::services::quality::{best_quality_of, default_profile, meets_cutoff};
+use crate::external::Aria2Client;
+use crate::services::torrent_client::TorrentClient;
+use crate::services::{FileGrouper, FileScanner};
+
+/// 一次导入检查的结果
+#[derive(Debug, Default, Serialize)]
+pub struct DownloadImportResult {
+    pub checked: usize,
+    pub imported: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
+}
+
+/// 检查下载中的任务，导入已完成的任务（单个下载客户端不可用时记录错误，不影响另一个）
+pub async fn import_completed_downloads(
+    pool: &Pool<Sqlite>,
+    torrent_client: Option<&TorrentClient>,
+    aria2_client: Option<&Aria2Client>,
+) -> Result<DownloadImportResult> {
+    let mut result = DownloadImportResult::default();
+    if let Some(client) = torrent_client {
+        if let Err(e) = import_torrents(pool, client, &mut result).await {
+            result.errors.push(format!("torrent client: {}", e));
+        }
+    }
+    if let Some(client) = aria2_client {
+        if let Err(e) = import_links(pool, client, &mut result).await {
+            result.errors.push(format!("aria2: {}", e));
+        }
+    }
+    Ok(result)
+}
+
+async fn import_torrents(pool: &Pool<Sqlite>, client: &TorrentClient, result: &mut DownloadImportResult) -> Result<()> {
+    let downloads = database::list_torrent_downloads(pool, Some("downloading")).await?;
+    if downloads.is_empty() {
+        return Ok(());
+    }
+
+    let hashes: Vec<String> = downloads.iter().map(|d| d.info_hash.clone()).collect();
+    let torrents = client.list_torrents(&hashes).await?;
+
+    for download in downloads {
+        // 刚推送的任务可能还没出现在客户端中
+        let Some(torrent) = torrents.iter().find(|t| t.hash.eq_ignore_ascii_case(&download.info_hash)) else {
+            continue;
+        };
+        result.checked += 1;
+
+        if !torrent.is_complete() {
+            database::update_torrent_progress(pool, &download.info_hash, torrent.progress).await?;
+            continue;
+        }
+
+        let key = DownloadKey::Torrent(&download.info_hash);
+        key.set_status(pool, "importing", None, None).await?;
+        let imported = match client.torrent_files(&download.info_hash).await {
+            Ok(files) => {
+                let files: Vec<DownloadedFile> = files.into_iter()
+                    .map(|f| DownloadedFile { path: Path::new(&torrent.save_path).join(&f.name), size: f.size })
+                    .collect();
+                import_download(pool, &download.media_id, &files).await
+            }
+            Err(e) => Err(e),
+        };
+        finish_import(pool, key, &torrent.name, imported, result).await?;
+    }
+
+    Ok(())
+}
+
+async fn import_links(pool: &Pool<Sqlite>, client: &Aria2Client, result: &mut DownloadImportResult) -> Result<()> {
+    for download in database::list_link_downloads(pool, Some("downloading")).await? {
+        let status = client.tell_status(&download.gid).await?;
+        result.checked += 1;
+        let key = DownloadKey::Link(&download.gid);
+        let name = download.name.as_deref().unwrap_or(&download.url);
+
+        if status.is_failed() {
+            let error = status.error_message.as_deref().unwrap_or("download was removed from aria2");
+            finish_import(pool, key, name, Err(anyhow!("{}", error)), result).await?;
+            continue;
+        }
+        if !status.is_complete() {
+            database::update_link_progress(pool, &download.gid, status.progress()).await?;
+            continue;
+        }
+
+        key.set_status(pool, "importing", None, None).await?;
+        let files: Vec<DownloadedFile> = status.files.iter()
+            .map(|f| DownloadedFile { path: PathBuf::from(&f.path), size: f.length.parse().unwrap_or(0) })
+            .collect();
+        let imported = import_download(pool, &download.media_id, &files).await;
+        finish_import(pool, key, name, imported, result).await?;
+    }
+    Ok(())
+}
+
+/// 下载任务（磁力任务按 info hash，链接任务按 aria2 gid）
+#[derive(Clone, Copy)]
+enum DownloadKey<'a> {
+    Torrent(&'a str),
+    Link(&'a str),
+}
+
+impl DownloadKey<'_> {
+    async fn set_status(self, pool: &Pool<Sqlite>, status: &str, files: Option<&[String]>, error: Option<&str>) -> Result<()> {
+        match self {
+            DownloadKey::Torrent(hash) => database::set_torrent_download_status(pool, hash, status, files, error).await,
+            DownloadKey::Link(gid) => database::set_link_download_status(pool, gid, status, files, error).await,
+        }
+    }
+}
+
+/// 保存导入结果
+async fn finish_import(
+    pool: &Pool<Sqlite>,
+    key: DownloadKey<'_>,
+    name: &str,
+    imported: Result<Vec<String>>,
+    result: &mut DownloadImportResult,
+) -> Result<()> {
+    match imported {
+        Ok(paths) => {
+            key.set_status(pool, "imported", Some(&paths), None).await?;
+            tracing::info!("📥 已导入下载: {} ({} 个文件)", name, paths.len());
+            result.imported += 1;
+        }
+        Err(e) => {
+            let error = e.to_string();
+            key.set_status(pool, "failed", None, Some(&error)).await?;
+            tracing::warn!("导入下载失败 '{}': {}", name, error);
+            result.errors.push(format!("{}: {}", name, error));
+            result.failed += 1;
+        }
+    }
+    Ok(())
+}
+
+/// 下载完成的文件
+#[derive(Debug, Clone)]
+struct DownloadedFile {
+    path: PathBuf,
+    size: i64,
+}
+
+impl DownloadedFile {
+    fn file_name(&self) -> String {
+        self.path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+}
+
+/// 导入一个已完成的任务，返回库中的文件路径
+async fn import_download(pool: &Pool<Sqlite>, media_id: &str, files: &[DownloadedFile]) -> Result<Vec<String>> {
+    let media = database::get_media_with(pool, media_id).await?
+        .ok_or_else(|| anyhow!("media {} no longer exists", media_id))?;
+
+    let scanner = FileScanner::with_rules(&database::get_filename_rules(pool).await?);
+    let videos = select_video_files(&scanner, files, media.code.as_deref());
+    if videos.is_empty() {
+        return Err(anyhow!("no video files in download"));
+    }
+
+    let roots = database::list_library_roots(pool).await?;
+    let root = select_library_root(&roots, &media.media_type);
+    let grouper = FileGrouper::new();
+    let mut media_files = Vec::new();
+
+    for file in &videos {
+        let file_name = file.file_name();
+        let part = if videos.len() > 1 { grouper.parse_part_info(&file_name) } else { None };
+
+        // 没有可用的本地根目录时保留在下载目录中
+        let target = match root {
+            Some(root) => {
+                let target = library_target(root, &media, &file_name, part.as_ref().map(|p| p.part_label.as_str()));
+                place_file(&file.path, &target).await?;
+                target
+            }
+            None => file.path.clone(),
+        };
+
+        media_files.push(MediaFile::new(
+            media.id.clone(),
+            target.to_string_lossy().into_owned(),
+            file.size,
+            part.as_ref().map(|p| p.part_number),
+            part.map(|p| p.part_label),
+        ));
+    }
+
+    database::link_media_files(pool, &media.id, &media_files).await?;
+    database::ensure_in_collection(pool, &media.id).await?;
+
+    let paths: Vec<String> = media_files.into_iter().map(|f| f.file_path).collect();
+    update_wanted(pool, &media.id, &paths).await?;
+    spawn_import_hooks(pool.clone(), media.id.clone(), paths.clone());
+    Ok(paths)
+}
+
+/// 选出要导入的视频文件：跳过 sample，有番号时优先取番号匹配的文件
+fn select_video_files<'a>(scanner: &FileScanner, files: &'a [DownloadedFile], code: Option<&str>) -> Vec<&'a DownloadedFile> {
+    let mut videos: Vec<&DownloadedFile> = files.iter()
+        .filter(|f| {
+            let name = f.file_name().to_lowercase();
+            let is_video = name.rsplit_once('.').is_some_and(|(_, ext)| VIDEO_EXTENSIONS.contains(&ext));
+            is_video && !name.contains("sample")
+        })
+        .collect();
+    videos.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if let Some(code) = code {
+        let normalized = normalize_code(code);
+        let matched: Vec<&DownloadedFile> = videos.iter()
+            .copied()
+            .filter(|f| {
+                let parsed = scanner.parse(&f.file_name().to_uppercase());
+                parsed.code.is_some_and(|c| normalize_code(&c) == normalized)
+            })
+            .collect();
+        if !matched.is_empty() {
+            return matched;
+        }
+    }
+    videos
+}
+
+fn normalize_code(code: &str) -> String {
+    code.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_uppercase()
+}
+
+/// 选择导入的根目录：优先内容类型一致的本地根目录，跳过不可访问的目录
+fn select_library_root<'a>(roots: &'a [LibraryRoot], media_type: &str) -> Option<&'a LibraryRoot> {
+    let candidates: Vec<&LibraryRoot> = roots.iter()
+        .filter(|root| root.is_local() && root.reachable != Some(false))
+        .collect();
+    candidates.iter()
+        .find(|root| root.content_type.as_deref().is_some_and(|t| t.eq_ignore_ascii_case(media_type)))
+        .or_else(|| candidates.first())
+        .copied()
+}
+
+/// 文件在库中的位置：有重命名模板时按模板生成，否则使用原文件名
+fn library_target(root: &LibraryRoot, media: &MediaItem, file_name: &str, part: Option<&str>) -> PathBuf {
+    let Some(template) = &root.rename_template else {
+        return Path::new(&root.path).join(file_name);
+    };
+
+    let mut values = HashMap::from([("title", media.title.clone())]);
+    for (field, value) in [
+        ("original_title", media.original_title.clone()),
+        ("code", media.code.clone()),
+        ("year", media.year.map(|y| y.to_string())),
+        ("studio", media.studio.clone()),
+        ("series", media.series.clone()),
+        ("part", part.map(|p| p.to_string())),
+    ] {
+        if let Some(value) = value {
+            values.insert(field, value);
+        }
+    }
+
+    let mut relative = render_rename_template(template, &values);
+    // 模板中没有 {part} 时追加分段标签，避免多个分段重名
+    if let (Some(part), false) = (part, template.contains("{part}")) {
+        relative = format!("{} - {}", relative, part);
+    }
+    if relative.is_empty() {
+        relative = media.title.clone();
+    }
+    match file_name.rsplit_once('.') {
+        Some((_, ext)) => Path::new(&root.path).join(format!("{}.{}", relative, ext.to_lowercase())),
+        None => Path::new(&root.path).join(relative),
+    }
+}
+
+/// 硬链接到库中（跨文件系统时复制），目标已存在时直接使用
+async fn place_file(source: &Path, target: &Path) -> Result<()> {
+    if tokio::fs::try_exists(target).await? {
+        return Ok(());
+    }
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    if let Err(e) = tokio::fs::hard_link(source, target).await {
+        tracing::debug!("硬链接失败，改为复制 {}: {}", source.display(), e);
+        tokio::fs::copy(source, target).await
+            .map_err(|e| anyhow!("failed to copy {} to {}: {}", source.display(), target.display(), e))?;
+    }
+    Ok(())
+}
+
+/// 已达到期望质量时移出想要列表，否则恢复监控以便继续检测升级
+async fn update_wanted(pool: &Pool<Sqlite>, media_id: &str, paths: &[String]) -> Result<()> {
+    if database::get_wanted(pool, media_id).await.is_err() {
+        return Ok(());
+    }
+    let profile = default_profile(&database::get_quality_profiles(pool).await?);
+    let quality = best_quality_of(paths.iter().map(|p| p.as_str()), &profile);
+    if meets_cutoff(&quality, &profile) {
+        database::remove_wanted(pool, media_id).await?;
+    } else {
+        database::update_wanted(pool, media_id, UpdateWantedRequest {
+            status: Some("monitoring".to_string()),
+            action: None,
+            quality_filter: None,
+            plugin_id: None,
+            search_query: None,
+        }).await?;
+    }
+    Ok(())
+}
+
+/// 下载导入定时任务
+pub struct DownloadImportTask {
+    pool: Pool<Sqlite>,
+    torrent_client: Option<TorrentClient>,
+    aria2_client: Option<Aria2Client>,
+    interval: Duration,
+}
+
+impl DownloadImportTask {
+    pub fn new(
+        pool: Pool<Sqlite>,
+        torrent_client: Option<TorrentClient>,
+        aria2_client: Option<Aria2Client>,
+        interval: Duration,
+    ) -> Self {
+        Self { pool, torrent_client, aria2_client, interval }
+    }
+
+    /// 启动定期导入任务
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+            match import_completed_downloads(&self.pool, self.torrent_client.as_ref(), self.aria2_client.as_ref()).await {
+                Ok(result) => tracing::debug!("Download import completed: {:?}", result),
+                Err(e) => tracing::warn!("Download import failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str) -> DownloadedFile {
+        DownloadedFile { path: Path::new("/downloads").join(name), size: 1 }
+    }
+
+    #[test]
+    fn test_select_video_files() {
+        let files = vec![
+            file("ABC-123/abc-123.mp4"),
+            file("ABC-123/sample/abc-123-sample.mp4"),
+            file("ABC-123/XYZ-999.mkv"),
+            file("ABC-123/cover.jpg"),
+        ];
+        let names = |videos: Vec<&DownloadedFile>| videos.iter().map(|f| f.file_name()).collect::<Vec<_>>();
+        assert_eq!(names(select_video_files(&FileScanner::new(), &files, Some("ABC-123"))), vec!["abc-123.mp4"]);
+        assert_eq!(names(select_video_files(&FileScanner::new(), &files, None)), vec!["XYZ-999.mkv", "abc-123.mp4"]);
+    }
+
+    #[test]
+    fn test_select_video_files_with_rules() {
+        let files = vec![file("pack/abc_vol123.mp4"), file("pack/extra.mp4")];
+        let rules = vec![crate::models::FilenameRule {
+            name: "prefixed".to_string(),
+            pattern: r"^(?P<p>[A-Z]+)_VOL(?P<n>\d+)$".to_string(),
+            code_template: Some("${p}-${n}".to_string()),
+            ..Default::default()
+        }];
+        let names = |videos: Vec<&DownloadedFile>| videos.iter().map(|f| f.file_name()).collect::<Vec<_>>();
+        assert_eq!(names(select_video_files(&FileScanner::new(), &files, Some("ABC-123"))), vec!["abc_vol123.mp4", "extra.mp4"]);
+        assert_eq!(names(select_video_files(&FileScanner::with_rules(&rules), &files, Some("ABC-123"))), vec!["abc_vol123.mp4"]);
+    }
+}
@@ -0,0 +1,280 @@
+//! 新建媒体后的后台补全
+//!
+//! 手动创建或扫描得到只有标题/番号的媒体后，加入低优先级的补全队列，由后台任务逐个执行：
+//! 补充刮削（supplement 模式）、缓存图片、补全演员资料、用 ffprobe 探测本地文件的技术信息。
+//! 每个步骤的结果记录在 enrichment_jobs 中，单个步骤失败不影响后续步骤
+
+use std::path::Path as FsPath;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use axum::extract::{Path, State};
+use axum::Json;
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::Notify;
+
+use crate::api::actors::{scrape_actor, ScrapeActorRequest};
+use crate::api::scrape::{scrape_media, ScrapeMediaRequest};
+use crate::api::AppState;
+use crate::database::{self, DatabaseRepository};
+use crate::models::{EnrichmentJob, EnrichmentSettings, EnrichmentStepResult, MediaProbe, ScrapeFieldMask};
+use crate::services::cache::MediaData;
+use crate::services::ffmpeg;
+
+/// 单个文件的探测超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 有新任务加入队列时唤醒后台任务
+static ENRICHMENT_QUEUED: Notify = Notify::const_new();
+
+/// 新建媒体后按设置加入补全队列（未开启自动补全时不加入），失败只记录日志
+pub async fn queue_new_media(pool: &Pool<Sqlite>, media_id: &str, reason: &str) {
+    let result = async {
+        if !database::get_enrichment_settings(pool).await?.enabled {
+            return Ok(false);
+        }
+        enqueue(pool, media_id, reason).await
+    }.await;
+    if let Err(e) = result {
+        tracing::warn!("加入补全队列失败 {}: {}", media_id, e);
+    }
+}
+
+/// 加入补全队列并唤醒后台任务，返回是否加入（正在执行的任务不会重复加入）
+pub async fn enqueue(pool: &Pool<Sqlite>, media_id: &str, reason: &str) -> Result<bool> {
+    let queued = database::enqueue_enrichment_job(pool, media_id, reason).await?;
+    if queued {
+        ENRICHMENT_QUEUED.notify_one();
+    }
+    Ok(queued)
+}
+
+/// 执行一个补全任务的各个步骤
+pub async fn run_enrichment_job(state: &AppState, job: &EnrichmentJob, settings: &EnrichmentSettings) -> Vec<EnrichmentStepResult> {
+    let mut results = Vec::new();
+    for step in crate::models::ENRICHMENT_STEPS {
+        if !settings.runs_step(step) {
+            continue;
+        }
+        let result = match step {
+            "scrape" => scrape_step(state, &job.media_id).await,
+            "artwork" => artwork_step(state, &job.media_id).await,
+            "actors" => actors_step(state, &job.media_id).await,
+            "probe" => probe_step(state, &job.media_id).await,
+            _ => unreachable!(),
+        };
+        let result = result.unwrap_or_else(|e| EnrichmentStepResult::failed(step, e.to_string()));
+        if result.is_failed() {
+            tracing::warn!("补全步骤 {} 失败 {}: {}", step, job.media_id, result.message.as_deref().unwrap_or_default());
+        }
+        results.push(result);
+    }
+    results
+}
+
+/// 补充刮削：只填充空字段，返回多个结果时跳过（需要用户手动选择）
+async fn scrape_step(state: &AppState, media_id: &str) -> Result<EnrichmentStepResult> {
+    let media = state.db_service.get_media_detail(media_id).await?
+        .ok_or_else(|| anyhow!("media {} not found", media_id))?;
+    let request = ScrapeMediaRequest {
+        mode: "supplement".to_string(),
+        code: None,
+        content_type: None,
+        series: media.series.clone(),
+        studio: media.studio.clone(),
+        data: None,
+        result_token: None,
+        result_index: None,
+        create_new: false,
+        fields: ScrapeFieldMask::default(),
+    };
+    let Json(body) = scrape_media(State(state.clone()), Path(media_id.to_string()), Json(request)).await
+        .map_err(|e| anyhow!("{}", e))?;
+    if body["data"]["mode"].as_str() == Some("multiple") {
+        return Ok(EnrichmentStepResult::skipped("scrape", "multiple results, choose one manually"));
+    }
+    Ok(EnrichmentStepResult::completed("scrape", "supplemented missing fields"))
+}
+
+/// 缓存海报和背景图
+async fn artwork_step(state: &AppState, media_id: &str) -> Result<EnrichmentStepResult> {
+    let media = state.db_service.get_media_detail(media_id).await?
+        .ok_or_else(|| anyhow!("media {} not found", media_id))?;
+    let result = state.cache_service.refresh_media_artwork(media_id, &MediaData::from_media_item(&media)).await;
+    let message = format!("{} cached, {} failed, {} skipped", result.refreshed, result.failed, result.skipped);
+    Ok(if result.refreshed == 0 && result.failed > 0 {
+        EnrichmentStepResult::failed("artwork", message)
+    } else if result.refreshed == 0 {
+        EnrichmentStepResult::skipped("artwork", "no remote images")
+    } else {
+        EnrichmentStepResult::completed("artwork", message)
+    })
+}
+
+/// 补全还没有头像的演员资料
+async fn actors_step(state: &AppState, media_id: &str) -> Result<EnrichmentStepResult> {
+    let actors = database::get_actors_for_media(state.database.pool(), media_id).await?;
+    let pending: Vec<_> = actors.into_iter()
+        .filter(|actor| actor.avatar_url.as_deref().is_none_or(str::is_empty))
+        .collect();
+    if pending.is_empty() {
+        return Ok(EnrichmentStepResult::skipped("actors", "no actors to enrich"));
+    }
+
+    let mut enriched = 0;
+    let mut errors = Vec::new();
+    for actor in &pending {
+        let request = ScrapeActorRequest { mode: "supplement".to_string(), name: None };
+        match scrape_actor(State(state.clone()), Path(actor.id.clone()), Json(request)).await {
+            Ok(_) => enriched += 1,
+            Err(e) => errors.push(format!("{}: {}", actor.name, e)),
+        }
+    }
+    let message = format!("{} of {} actors enriched", enriched, pending.len());
+    Ok(match (enriched, errors.is_empty()) {
+        (0, false) => EnrichmentStepResult::failed("actors", format!("{} ({})", message, errors.join("; "))),
+        _ => EnrichmentStepResult::completed("actors", message),
+    })
+}
+
+/// 探测本地文件的容器、时长、分辨率和编码
+async fn probe_step(state: &AppState, media_id: &str) -> Result<EnrichmentStepResult> {
+    let pool = state.database.pool();
+    let files = state.database.repository().get_media_files(media_id).await?;
+    let local: Vec<_> = files.iter().filter(|f| FsPath::new(&f.file_path).is_file()).collect();
+    if local.is_empty() {
+        return Ok(EnrichmentStepResult::skipped("probe", "no local files"));
+    }
+
+    let mut probed = 0;
+    for file in &local {
+        let output = match run_ffprobe(&file.file_path).await {
+            Ok(output) => output,
+            Err(e) if e.downcast_ref::<std::io::Error>().is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound) => {
+                return Ok(EnrichmentStepResult::skipped("probe", "ffprobe is not installed"));
+            }
+            Err(e) => return Ok(EnrichmentStepResult::failed("probe", format!("{}: {}", file.file_path, e))),
+        };
+        let probe = parse_ffprobe_output(&output, media_id, &file.file_path)?;
+        database::save_media_probe(pool, &probe).await?;
+        probed += 1;
+    }
+    Ok(EnrichmentStepResult::completed("probe", format!("{} files probed", probed)))
+}
+
+pub(crate) async fn run_ffprobe(path: &str) -> Result<Value> {
+    let _permit = ffmpeg::acquire().await;
+    let output = tokio::time::timeout(
+        PROBE_TIMEOUT,
+        ffmpeg::ffprobe_command(["-v", "quiet", "-print_format", "json", "-show_format", "-show_streams", path])
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| anyhow!("timed out after {}s", PROBE_TIMEOUT.as_secs()))??;
+    if !output.status.success() {
+        return Err(anyhow!("ffprobe exited with {}", output.status));
+    }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+/// 从 ffprobe 的 JSON 输出中取出技术信息（数值字段在 ffprobe 中是字符串）
+pub fn parse_ffprobe_output(output: &Value, media_id: &str, file_path: &str) -> Result<MediaProbe> {
+    let format = output.get("format").ok_or_else(|| anyhow!("ffprobe output has no format"))?;
+    let streams = output["streams"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let stream = |codec_type: &str| streams.iter().find(|s| s["codec_type"].as_str() == Some(codec_type));
+    let video = stream("video");
+    let text = |value: &Value| value.as_str().map(str::to_string);
+
+    Ok(MediaProbe {
+        file_path: file_path.to_string(),
+        media_id: media_id.to_string(),
+        container: text(&format["format_name"]),
+        duration_secs: format["duration"].as_str().and_then(|d| d.parse().ok()),
+        bit_rate: format["bit_rate"].as_str().and_then(|b| b.parse().ok()),
+        width: video.and_then(|v| v["width"].as_i64()),
+        height: video.and_then(|v| v["height"].as_i64()),
+        video_codec: video.and_then(|v| text(&v["codec_name"])),
+        audio_codec: stream("audio").and_then(|a| text(&a["codec_name"])),
+        probed_at: Utc::now(),
+    })
+}
+
+/// 补全队列后台任务：逐个执行任务，两个任务之间按设置等待，队列为空时等待新任务或定期检查
+pub struct EnrichmentTask {
+    state: AppState,
+    interval: Duration,
+}
+
+impl EnrichmentTask {
+    pub fn new(state: AppState, interval: Duration) -> Self {
+        Self { state, interval }
+    }
+
+    /// 启动补全任务
+    pub async fn start(self) {
+        let pool = self.state.database.pool().clone();
+        match database::requeue_running_enrichment_jobs(&pool).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("重新加入 {} 个未完成的补全任务", count),
+            Err(e) => tracing::warn!("恢复补全任务失败: {}", e),
+        }
+
+        loop {
+            match self.run_next(&pool).await {
+                Ok(Some(delay)) => tokio::time::sleep(delay).await,
+                Ok(None) => {
+                    let _ = tokio::time::timeout(self.interval, ENRICHMENT_QUEUED.notified()).await;
+                }
+                Err(e) => {
+                    tracing::warn!("补全任务执行失败: {}", e);
+                    tokio::time::sleep(self.interval).await;
+                }
+            }
+        }
+    }
+
+    /// 执行下一个任务，返回到下一个任务前的等待时间（队列为空时返回 None）
+    async fn run_next(&self, pool: &Pool<Sqlite>) -> Result<Option<Duration>> {
+        let Some(job) = database::claim_next_enrichment_job(pool).await? else {
+            return Ok(None);
+        };
+        let settings = database::get_enrichment_settings(pool).await?;
+        let steps = run_enrichment_job(&self.state, &job, &settings).await;
+
+        let failed: Vec<&str> = steps.iter().filter(|s| s.is_failed()).map(|s| s.step.as_str()).collect();
+        let (status, error) = if failed.is_empty() {
+            ("completed", None)
+        } else {
+            ("failed", Some(format!("failed steps: {}", failed.join(", "))))
+        };
+        database::finish_enrichment_job(pool, &job.media_id, status, &steps, error.as_deref()).await?;
+        tracing::info!("🧩 补全任务完成 {}: {}", job.media_id, status);
+        Ok(Some(Duration::from_secs(settings.job_delay_secs)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ffprobe_output() {
+        let output = serde_json::json!({
+            "streams": [
+                { "codec_type": "audio", "codec_name": "aac" },
+                { "codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080 },
+            ],
+            "format": { "format_name": "mov,mp4,m4a,3gp,3g2,mj2", "duration": "5400.120000", "bit_rate": "8000000" },
+        });
+        let probe = parse_ffprobe_output(&output, "m1", "/library/ABC-123.mp4").unwrap();
+        assert_eq!(probe.duration_secs, Some(5400.12));
+        assert_eq!(probe.bit_rate, Some(8_000_000));
+        assert_eq!((probe.width, probe.height), (Some(1920), Some(1080)));
+        assert_eq!(probe.video_codec.as_deref(), Some("h264"));
+        assert_eq!(probe.audio_codec.as_deref(), Some("aac"));
+
+        assert!(parse_ffprobe_output(&serde_json::json!({}), "m1", "/x.mp4").is_err());
+    }
+}
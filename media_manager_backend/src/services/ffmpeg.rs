@@ -0,0 +1,182 @@
+//! FFmpeg 任务调度
+//!
+//! 缩略图生成和文件探测共用一个全局的进程数上限，避免同时启动过多 FFmpeg/ffprobe 进程
+//! 占满 CPU 导致接口响应变慢。进程以设置的 nice 值运行，FFmpeg 的解码线程数可限制。
+//! 设置通过 /api/settings/ffmpeg 修改，立即生效（降低上限时已运行的进程不受影响）。
+
+use std::ffi::OsStr;
+use std::sync::{Mutex, RwLock};
+
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use tokio::process::Command;
+use tokio::sync::Notify;
+
+use crate::database;
+use crate::models::{FfmpegSchedulerStatus, FfmpegSettings};
+
+lazy_static::lazy_static! {
+    static ref SCHEDULER: FfmpegScheduler = FfmpegScheduler::new(FfmpegSettings::default());
+}
+
+/// 限制同时运行的进程数
+struct FfmpegScheduler {
+    settings: RwLock<FfmpegSettings>,
+    running: Mutex<usize>,
+    released: Notify,
+}
+
+/// 运行进程的许可，drop 时释放
+pub struct FfmpegPermit<'a> {
+    scheduler: &'a FfmpegScheduler,
+}
+
+impl Drop for FfmpegPermit<'_> {
+    fn drop(&mut self) {
+        *self.scheduler.running.lock().unwrap() -= 1;
+        self.scheduler.released.notify_waiters();
+    }
+}
+
+impl FfmpegScheduler {
+    fn new(settings: FfmpegSettings) -> Self {
+        Self {
+            settings: RwLock::new(settings),
+            running: Mutex::new(0),
+            released: Notify::new(),
+        }
+    }
+
+    fn settings(&self) -> FfmpegSettings {
+        self.settings.read().unwrap().clone()
+    }
+
+    fn apply(&self, settings: FfmpegSettings) {
+        *self.settings.write().unwrap() = settings;
+        // 上限提高时唤醒等待的任务
+        self.released.notify_waiters();
+    }
+
+    async fn acquire(&self) -> FfmpegPermit<'_> {
+        loop {
+            // 先注册等待再检查，避免错过检查之后的释放通知
+            let released = self.released.notified();
+            {
+                let max_concurrent = self.settings.read().unwrap().max_concurrent;
+                let mut running = self.running.lock().unwrap();
+                if *running < max_concurrent {
+                    *running += 1;
+                    return FfmpegPermit { scheduler: self };
+                }
+            }
+            released.await;
+        }
+    }
+
+    fn status(&self) -> FfmpegSchedulerStatus {
+        FfmpegSchedulerStatus {
+            settings: self.settings(),
+            running: *self.running.lock().unwrap(),
+        }
+    }
+}
+
+/// 等待运行许可（持有期间占用一个进程名额）
+pub async fn acquire() -> FfmpegPermit<'static> {
+    SCHEDULER.acquire().await
+}
+
+/// 当前设置
+pub fn settings() -> FfmpegSettings {
+    SCHEDULER.settings()
+}
+
+/// 应用新的设置（调用前需校验）
+pub fn apply_settings(settings: FfmpegSettings) {
+    SCHEDULER.apply(settings);
+}
+
+/// 调度状态
+pub fn status() -> FfmpegSchedulerStatus {
+    SCHEDULER.status()
+}
+
+/// 启动时读取保存的设置
+pub async fn load_settings(pool: &Pool<Sqlite>) -> Result<()> {
+    let settings = database::get_ffmpeg_settings(pool).await?;
+    tracing::info!(
+        "FFmpeg jobs: max {} concurrent, nice {}, {} threads",
+        settings.max_concurrent, settings.nice, settings.threads
+    );
+    apply_settings(settings);
+    Ok(())
+}
+
+/// 按 nice 值创建命令（Unix 下通过 nice 启动）
+fn niced_command(program: &str, nice: i32) -> Command {
+    if cfg!(unix) && nice > 0 {
+        let mut command = Command::new("nice");
+        command.arg("-n").arg(nice.to_string()).arg(program);
+        command
+    } else {
+        Command::new(program)
+    }
+}
+
+/// 创建 FFmpeg 命令（应用 nice 值和线程数限制）
+pub fn ffmpeg_command<I, S>(args: I) -> Command
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let settings = settings();
+    let mut command = niced_command("ffmpeg", settings.nice);
+    if settings.threads > 0 {
+        command.arg("-threads").arg(settings.threads.to_string());
+    }
+    command.args(args);
+    command
+}
+
+/// 创建 ffprobe 命令（应用 nice 值）
+pub fn ffprobe_command<I, S>(args: I) -> Command
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = niced_command("ffprobe", settings().nice);
+    command.args(args);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_acquire_respects_max_concurrent() {
+        let scheduler = FfmpegScheduler::new(FfmpegSettings { max_concurrent: 1, nice: 0, threads: 0 });
+        let first = scheduler.acquire().await;
+        assert!(tokio::time::timeout(Duration::from_millis(50), scheduler.acquire()).await.is_err());
+
+        // 释放后等待的任务继续
+        let waiting = async {
+            let _permit = scheduler.acquire().await;
+            scheduler.status().running
+        };
+        let release = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(first);
+        };
+        let (running, _) = tokio::join!(waiting, release);
+        assert_eq!(running, 1);
+        assert_eq!(scheduler.status().running, 0);
+
+        // 提高上限后可以同时运行
+        let _first = scheduler.acquire().await;
+        scheduler.apply(FfmpegSettings { max_concurrent: 2, nice: 0, threads: 0 });
+        let _second = tokio::time::timeout(Duration::from_millis(50), scheduler.acquire()).await.unwrap();
+        assert_eq!(scheduler.status().running, 2);
+    }
+}
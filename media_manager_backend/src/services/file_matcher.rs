@@ -12,6 +12,15 @@ pub enum MatchType {
     None,       // 未匹配
 }
 
+/// 候选媒体及其置信度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchCandidate {
+    pub media: MediaItem,
+    pub confidence: f32,
+    /// 匹配依据：code / series_date / title
+    pub reason: String,
+}
+
 /// 单个文件的匹配结果
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatchResult {
@@ -20,6 +29,12 @@ pub struct MatchResult {
     pub matched_media: Option<MediaItem>,
     pub confidence: f32,  // 匹配置信度 0.0-1.0
     pub suggestions: Vec<MediaItem>,  // 可能的匹配建议
+    /// 按置信度排序的前 N 个候选
+    #[serde(default)]
+    pub candidates: Vec<MatchCandidate>,
+    /// 是否已按阈值自动确认
+    #[serde(default)]
+    pub auto_confirmed: bool,
 }
 
 /// 文件组的匹配结果
@@ -30,6 +45,29 @@ pub struct GroupMatchResult {
     pub matched_media: Option<MediaItem>,
     pub confidence: f32,
     pub suggestions: Vec<MediaItem>,
+    #[serde(default)]
+    pub candidates: Vec<MatchCandidate>,
+    #[serde(default)]
+    pub auto_confirmed: bool,
+}
+
+/// 默认返回的候选数
+pub const DEFAULT_TOP_CANDIDATES: usize = 5;
+/// 进入候选列表的最低置信度
+const MIN_CANDIDATE_CONFIDENCE: f32 = 0.3;
+/// 作为建议返回的最低置信度
+const SUGGESTION_CONFIDENCE: f32 = 0.6;
+/// 认为模糊匹配成功的置信度
+const FUZZY_MATCH_CONFIDENCE: f32 = 0.8;
+/// 系列+发布日期都一致时的置信度
+const SERIES_DATE_CONFIDENCE: f32 = 0.95;
+
+/// 由候选列表得出的匹配结论
+struct Verdict {
+    match_type: MatchType,
+    matched_media: Option<MediaItem>,
+    confidence: f32,
+    suggestions: Vec<MediaItem>,
 }
 
 /// 文件匹配器
@@ -40,10 +78,19 @@ impl FileMatcher {
     pub fn match_files(
         scanned_files: Vec<ScannedFile>,
         all_media: Vec<MediaItem>,
+    ) -> Vec<MatchResult> {
+        Self::match_files_ranked(scanned_files, &all_media, DEFAULT_TOP_CANDIDATES)
+    }
+
+    /// 匹配文件并返回每个文件的前 top_n 个候选
+    pub fn match_files_ranked(
+        scanned_files: Vec<ScannedFile>,
+        all_media: &[MediaItem],
+        top_n: usize,
     ) -> Vec<MatchResult> {
         scanned_files
             .into_iter()
-            .map(|file| Self::match_single_file(file, &all_media))
+            .map(|file| Self::match_single_file(file, all_media, top_n))
             .collect()
     }
 
@@ -51,150 +98,150 @@ impl FileMatcher {
     pub fn match_file_groups(
         file_groups: Vec<FileGroup>,
         all_media: Vec<MediaItem>,
+    ) -> Vec<GroupMatchResult> {
+        Self::match_file_groups_ranked(file_groups, &all_media, DEFAULT_TOP_CANDIDATES)
+    }
+
+    /// 匹配文件组并返回每组的前 top_n 个候选
+    pub fn match_file_groups_ranked(
+        file_groups: Vec<FileGroup>,
+        all_media: &[MediaItem],
+        top_n: usize,
     ) -> Vec<GroupMatchResult> {
         file_groups
             .into_iter()
-            .map(|group| Self::match_single_group(group, &all_media))
+            .map(|group| Self::match_single_group(group, all_media, top_n))
             .collect()
     }
 
-    /// 匹配单个文件组
-    fn match_single_group(group: FileGroup, all_media: &[MediaItem]) -> GroupMatchResult {
-        // 使用第一个文件的信息进行匹配
-        if let Some(first_file) = group.files.first() {
-            let file = &first_file.scanned_file;
-
-            // 1. 尝试通过识别号精确匹配
-            if let Some(ref code) = file.parsed_code {
-                if let Some(media) = Self::find_by_code(code, all_media) {
-                    return GroupMatchResult {
-                        file_group: group,
-                        match_type: MatchType::Exact,
-                        matched_media: Some(media.clone()),
-                        confidence: 1.0,
-                        suggestions: vec![],
-                    };
-                }
-            }
-
-            // 2. 尝试通过基础名称模糊匹配
-            let fuzzy_matches = Self::find_by_title_fuzzy(&group.base_name, all_media, 0.6);
-
-            if !fuzzy_matches.is_empty() {
-                let best_match = &fuzzy_matches[0];
-                let confidence = best_match.1;
-
-                if confidence > 0.8 {
-                    // 高置信度，认为是匹配
-                    return GroupMatchResult {
-                        file_group: group,
-                        match_type: MatchType::Fuzzy,
-                        matched_media: Some(best_match.0.clone()),
-                        confidence,
-                        suggestions: fuzzy_matches
-                            .iter()
-                            .skip(1)
-                            .take(3)
-                            .map(|(m, _)| (*m).clone())
-                            .collect(),
-                    };
-                } else {
-                    // 中等置信度，提供建议
-                    return GroupMatchResult {
-                        file_group: group,
-                        match_type: MatchType::None,
-                        matched_media: None,
-                        confidence: 0.0,
-                        suggestions: fuzzy_matches
-                            .iter()
-                            .take(5)
-                            .map(|(m, _)| (*m).clone())
-                            .collect(),
-                    };
-                }
-            }
-        }
+    /// 匹配单个文件组（使用第一个文件的识别信息和组的基础名称）
+    fn match_single_group(group: FileGroup, all_media: &[MediaItem], top_n: usize) -> GroupMatchResult {
+        let candidates = match group.files.first() {
+            Some(first_file) => Self::rank_candidates(&first_file.scanned_file, Some(&group.base_name), all_media, top_n),
+            None => Vec::new(),
+        };
+        let verdict = Self::verdict(&candidates);
 
-        // 3. 未匹配
         GroupMatchResult {
             file_group: group,
-            match_type: MatchType::None,
-            matched_media: None,
-            confidence: 0.0,
-            suggestions: vec![],
+            match_type: verdict.match_type,
+            matched_media: verdict.matched_media,
+            confidence: verdict.confidence,
+            suggestions: verdict.suggestions,
+            candidates,
+            auto_confirmed: false,
         }
     }
 
     /// 匹配单个文件
-    fn match_single_file(file: ScannedFile, all_media: &[MediaItem]) -> MatchResult {
-        // 1. 尝试通过识别号精确匹配
-        if let Some(ref code) = file.parsed_code {
-            if let Some(media) = Self::find_by_code(code, all_media) {
-                return MatchResult {
-                    scanned_file: file,
-                    match_type: MatchType::Exact,
-                    matched_media: Some(media.clone()),
-                    confidence: 1.0,
-                    suggestions: vec![],
-                };
-            }
+    fn match_single_file(file: ScannedFile, all_media: &[MediaItem], top_n: usize) -> MatchResult {
+        let candidates = Self::rank_candidates(&file, None, all_media, top_n);
+        let verdict = Self::verdict(&candidates);
+
+        MatchResult {
+            scanned_file: file,
+            match_type: verdict.match_type,
+            matched_media: verdict.matched_media,
+            confidence: verdict.confidence,
+            suggestions: verdict.suggestions,
+            candidates,
+            auto_confirmed: false,
         }
+    }
 
-        // 2. 尝试通过标题模糊匹配
-        if let Some(ref title) = file.parsed_title {
-            let fuzzy_matches = Self::find_by_title_fuzzy(title, all_media, 0.6);
-            
-            if !fuzzy_matches.is_empty() {
-                let best_match = &fuzzy_matches[0];
-                let confidence = best_match.1;
-                
-                if confidence > 0.8 {
-                    // 高置信度，认为是匹配
-                    return MatchResult {
-                        scanned_file: file,
-                        match_type: MatchType::Fuzzy,
-                        matched_media: Some(best_match.0.clone()),
-                        confidence,
-                        suggestions: fuzzy_matches.iter().skip(1).take(3).map(|(m, _)| (*m).clone()).collect(),
-                    };
-                } else {
-                    // 中等置信度，提供建议
-                    return MatchResult {
-                        scanned_file: file,
-                        match_type: MatchType::None,
-                        matched_media: None,
-                        confidence: 0.0,
-                        suggestions: fuzzy_matches.iter().take(5).map(|(m, _)| (*m).clone()).collect(),
-                    };
-                }
+    /// 计算文件与每个媒体的置信度，返回排序后的前 top_n 个候选
+    pub fn rank_candidates(
+        file: &ScannedFile,
+        title_override: Option<&str>,
+        all_media: &[MediaItem],
+        top_n: usize,
+    ) -> Vec<MatchCandidate> {
+        let title = title_override.or(file.parsed_title.as_deref());
+        let mut candidates: Vec<MatchCandidate> = all_media
+            .iter()
+            .filter_map(|media| {
+                let (confidence, reason) = Self::score(file, title, media)?;
+                (confidence >= MIN_CANDIDATE_CONFIDENCE).then(|| MatchCandidate {
+                    media: media.clone(),
+                    confidence,
+                    reason: reason.to_string(),
+                })
+            })
+            .collect();
+
+        // 置信度相同时识别号匹配优先
+        candidates.sort_by(|a, b| {
+            b.confidence.partial_cmp(&a.confidence)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| (b.reason == "code").cmp(&(a.reason == "code")))
+        });
+        candidates.truncate(top_n.max(1));
+        candidates
+    }
+
+    /// 单个媒体的置信度：识别号一致 > 系列+日期一致 > 标题相似度
+    fn score(file: &ScannedFile, title: Option<&str>, media: &MediaItem) -> Option<(f32, &'static str)> {
+        if let (Some(code), Some(media_code)) = (&file.parsed_code, &media.code) {
+            if Self::normalize_code(code) == Self::normalize_code(media_code) {
+                return Some((1.0, "code"));
             }
         }
 
-        // 3. 未匹配
-        MatchResult {
-            scanned_file: file,
-            match_type: MatchType::None,
-            matched_media: None,
-            confidence: 0.0,
-            suggestions: vec![],
+        if let (Some(series), Some(date), Some(media_series), Some(media_date)) =
+            (&file.parsed_series, &file.parsed_date, &media.series, &media.release_date)
+        {
+            if series.eq_ignore_ascii_case(media_series) && media_date.starts_with(date.as_str()) {
+                return Some((SERIES_DATE_CONFIDENCE, "series_date"));
+            }
         }
+
+        let similarity = Self::calculate_similarity(title?, &media.title);
+        (similarity > 0.0).then_some((similarity, "title"))
     }
 
-    /// 通过识别号查找媒体
-    fn find_by_code<'a>(code: &str, all_media: &'a [MediaItem]) -> Option<&'a MediaItem> {
-        // 标准化识别号：移除连字符、下划线，转为大写
-        let normalized_code = Self::normalize_code(code);
-        
-        all_media.iter().find(|m| {
-            if let Some(ref media_code) = m.code {
-                let normalized_media_code = Self::normalize_code(media_code);
-                normalized_code == normalized_media_code
-            } else {
-                false
+    /// 由候选列表得出匹配类型、匹配媒体和建议
+    fn verdict(candidates: &[MatchCandidate]) -> Verdict {
+        let Some(best) = candidates.first() else {
+            return Verdict { match_type: MatchType::None, matched_media: None, confidence: 0.0, suggestions: vec![] };
+        };
+
+        if best.reason == "code" {
+            return Verdict {
+                match_type: MatchType::Exact,
+                matched_media: Some(best.media.clone()),
+                confidence: 1.0,
+                suggestions: vec![],
+            };
+        }
+
+        let suggestions = |skip: usize, take: usize| -> Vec<MediaItem> {
+            candidates.iter()
+                .filter(|c| c.confidence >= SUGGESTION_CONFIDENCE)
+                .skip(skip)
+                .take(take)
+                .map(|c| c.media.clone())
+                .collect()
+        };
+
+        if best.confidence > FUZZY_MATCH_CONFIDENCE {
+            // 高置信度，认为是匹配
+            Verdict {
+                match_type: MatchType::Fuzzy,
+                matched_media: Some(best.media.clone()),
+                confidence: best.confidence,
+                suggestions: suggestions(1, 3),
+            }
+        } else {
+            // 中等置信度，只提供建议
+            Verdict {
+                match_type: MatchType::None,
+                matched_media: None,
+                confidence: 0.0,
+                suggestions: suggestions(0, 5),
             }
-        })
+        }
     }
-    
+
     /// 标准化识别号格式（移除连字符、下划线、空格，转为大写）
     fn normalize_code(code: &str) -> String {
         code.replace("-", "")
@@ -203,32 +250,6 @@ impl FileMatcher {
             .to_uppercase()
     }
 
-    /// 通过标题模糊匹配
-    fn find_by_title_fuzzy<'a>(
-        title: &str,
-        all_media: &'a [MediaItem],
-        threshold: f32,
-    ) -> Vec<(&'a MediaItem, f32)> {
-        let mut matches: Vec<(&MediaItem, f32)> = all_media
-            .iter()
-            .filter_map(|media| {
-                let similarity = Self::calculate_similarity(title, &media.title);
-                if similarity >= threshold {
-                    Some((media, similarity))
-                } else {
-                    None
-                }
-            })
-            .collect();
-
-        // 按相似度降序排序
-        matches.sort_by(|a, b| {
-            b.1.partial_cmp(&a.1)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-        matches
-    }
-
     /// 计算字符串相似度（简单的 Jaccard 相似度）
     fn calculate_similarity(s1: &str, s2: &str) -> f32 {
         let s1_lower = s1.to_lowercase();
@@ -272,4 +293,47 @@ mod tests {
         let sim = FileMatcher::calculate_similarity("hello world", "hello");
         assert!(sim > 0.0 && sim < 1.0);
     }
+
+    fn media(title: &str, code: Option<&str>) -> MediaItem {
+        let mut media = MediaItem::new(title.to_string(), crate::models::MediaType::Movie).unwrap();
+        media.code = code.map(String::from);
+        media
+    }
+
+    fn scanned(code: Option<&str>, title: Option<&str>) -> ScannedFile {
+        ScannedFile {
+            file_path: "/data/file.mp4".to_string(),
+            file_name: "file.mp4".to_string(),
+            file_size: 1,
+            parsed_code: code.map(String::from),
+            parsed_title: title.map(String::from),
+            parsed_year: None,
+            parsed_series: None,
+            parsed_date: None,
+        }
+    }
+
+    #[test]
+    fn test_ranked_candidates() {
+        let library = vec![
+            media("blue river story", None),
+            media("blue river", Some("ABC-123")),
+            media("red mountain", None),
+            media("blue river story extended", None),
+        ];
+
+        let results = FileMatcher::match_files_ranked(vec![scanned(Some("abc123"), Some("blue river story"))], &library, 2);
+        let result = &results[0];
+        assert_eq!(result.match_type, MatchType::Exact);
+        assert_eq!(result.candidates.len(), 2);
+        assert_eq!(result.candidates[0].reason, "code");
+        assert_eq!(result.candidates[1].media.title, "blue river story");
+
+        let results = FileMatcher::match_files_ranked(vec![scanned(None, Some("blue river story"))], &library, 5);
+        let result = &results[0];
+        assert_eq!(result.match_type, MatchType::Fuzzy);
+        assert_eq!(result.confidence, 1.0);
+        assert!(result.candidates.iter().all(|c| c.media.title != "red mountain"));
+        assert!(result.candidates.windows(2).all(|w| w[0].confidence >= w[1].confidence));
+    }
 }
@@ -3,8 +3,10 @@ use std::fs;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+use crate::models::{classify_extra, is_extra_image, is_extras_dir, FilenameRule, ScanFileRecord};
+
 /// 支持的视频文件扩展名
-const VIDEO_EXTENSIONS: &[&str] = &[
+pub const VIDEO_EXTENSIONS: &[&str] = &[
     "mp4", "mkv", "avi", "wmv", "flv", "mov", "m4v", "mpg", "mpeg", "webm", "ts", "m2ts"
 ];
 
@@ -21,11 +23,114 @@ pub struct ScannedFile {
     pub parsed_date: Option<String>,      // 发布日期（欧美，如 2026-01-23）
 }
 
+impl From<ScanFileRecord> for ScannedFile {
+    fn from(record: ScanFileRecord) -> Self {
+        Self {
+            file_path: record.file_path,
+            file_name: record.file_name,
+            file_size: record.file_size.max(0) as u64,
+            parsed_code: record.parsed_code,
+            parsed_title: record.parsed_title,
+            parsed_year: record.parsed_year,
+            parsed_series: record.parsed_series,
+            parsed_date: record.parsed_date,
+        }
+    }
+}
+
+impl ScannedFile {
+    /// 转换为待保存的扫描记录（id、会话和状态由数据库填充）
+    pub fn to_record(&self) -> ScanFileRecord {
+        ScanFileRecord {
+            id: 0,
+            session_id: String::new(),
+            file_path: self.file_path.clone(),
+            file_name: self.file_name.clone(),
+            file_size: self.file_size as i64,
+            parsed_code: self.parsed_code.clone(),
+            parsed_title: self.parsed_title.clone(),
+            parsed_year: self.parsed_year,
+            parsed_series: self.parsed_series.clone(),
+            parsed_date: self.parsed_date.clone(),
+            status: crate::models::SCAN_FILE_UNMATCHED.to_string(),
+            media_id: None,
+            updated_at: chrono::Utc::now(),
+        }
+    }
+}
+
+/// 单个文件名的解析结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ParsedFilename {
+    pub code: Option<String>,
+    pub title: Option<String>,
+    pub year: Option<i32>,
+    pub series: Option<String>,
+    pub date: Option<String>,
+    /// 命中的自定义规则名称（None 表示使用内置解析）
+    pub matched_rule: Option<String>,
+}
+
+/// 编译后的自定义规则
+struct CompiledRule {
+    rule: FilenameRule,
+    regex: Regex,
+}
+
+impl CompiledRule {
+    /// 取字段值：有模板时展开模板，否则取同名捕获组
+    fn field(&self, caps: &regex::Captures, group: &str, template: Option<&String>) -> Option<String> {
+        let value = match template {
+            Some(template) => {
+                let mut expanded = String::new();
+                caps.expand(template, &mut expanded);
+                expanded
+            }
+            None => caps.name(group).map(|m| m.as_str().to_string()).unwrap_or_default(),
+        };
+        let value = value.trim().to_string();
+        if value.is_empty() { None } else { Some(value) }
+    }
+
+    fn parse(&self, name: &str) -> Option<ParsedFilename> {
+        let caps = self.regex.captures(name)?;
+        let code = self.field(&caps, "code", self.rule.code_template.as_ref())
+            .map(|c| c.to_uppercase());
+        let date = self.field(&caps, "date", self.rule.date_template.as_ref());
+        let year = caps.name("year")
+            .and_then(|m| m.as_str().parse::<i32>().ok())
+            .or_else(|| date.as_ref().and_then(|d| d.get(..4)).and_then(|y| y.parse().ok()));
+
+        Some(ParsedFilename {
+            code,
+            title: self.field(&caps, "title", self.rule.title_template.as_ref())
+                .map(|t| t.replace(['_', '.'], " ").trim().to_string()),
+            year,
+            series: self.field(&caps, "series", self.rule.series_template.as_ref()),
+            date,
+            matched_rule: Some(self.rule.name.clone()),
+        })
+    }
+}
+
+/// Extras/ 文件夹中的附加内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScannedExtra {
+    pub file_path: String,
+    pub file_name: String,
+    pub file_size: u64,
+    pub extra_type: String,
+    /// Extras/ 所在目录（即正片所在目录）
+    pub owner_dir: String,
+}
+
 /// 扫描结果
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ScanResult {
     pub total_files: usize,
     pub scanned_files: Vec<ScannedFile>,
+    #[serde(default)]
+    pub extras: Vec<ScannedExtra>,
 }
 
 /// 文件扫描器
@@ -50,6 +155,8 @@ pub struct FileScanner {
     western_series_title_regex: Regex,
     // 欧美纯标题（英文字母+空格，没有系列名）
     western_pure_title_regex: Regex,
+    // 用户自定义规则（优先于内置规则）
+    custom_rules: Vec<CompiledRule>,
 }
 
 impl FileScanner {
@@ -95,6 +202,47 @@ impl FileScanner {
             // 欧美纯标题: 英文字母+空格组成，至少包含一个空格（排除单词）
             western_pure_title_regex: Regex::new(r"^[a-zA-Z][a-zA-Z\s]+[a-zA-Z]$")
                 .expect("Invalid western pure title regex pattern"),
+            custom_rules: Vec::new(),
+        }
+    }
+
+    /// 创建带自定义规则的扫描器（跳过禁用和无效的规则）
+    pub fn with_rules(rules: &[FilenameRule]) -> Self {
+        let mut scanner = Self::new();
+        scanner.custom_rules = rules.iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| match rule.validate() {
+                Ok(regex) => Some(CompiledRule { rule: rule.clone(), regex }),
+                Err(e) => {
+                    tracing::warn!("Skipping filename rule: {}", e);
+                    None
+                }
+            })
+            .collect();
+        scanner
+    }
+
+    /// 解析文件名：先尝试自定义规则，再使用内置解析
+    pub fn parse(&self, filename: &str) -> ParsedFilename {
+        let name_without_ext = filename.rsplit_once('.').map(|(n, _)| n).unwrap_or(filename);
+        if let Some(parsed) = self.custom_rules.iter().find_map(|rule| rule.parse(name_without_ext)) {
+            return parsed;
+        }
+
+        let (code, title, year, series, date) = self.parse_filename(filename);
+        ParsedFilename { code, title, year, series, date, matched_rule: None }
+    }
+
+    /// 使用当前规则重新解析已扫描的文件（不访问文件系统）
+    pub fn reparse(&self, file: &ScannedFile) -> ScannedFile {
+        let parsed = self.parse(&file.file_name);
+        ScannedFile {
+            parsed_code: parsed.code,
+            parsed_title: parsed.title,
+            parsed_year: parsed.year,
+            parsed_series: parsed.series,
+            parsed_date: parsed.date,
+            ..file.clone()
         }
     }
 
@@ -111,20 +259,59 @@ impl FileScanner {
         }
 
         let mut scanned_files = Vec::new();
-        self.scan_dir_recursive(path, recursive, &mut scanned_files)?;
+        let mut extras = Vec::new();
+        self.scan_dir_recursive(path, recursive, &mut scanned_files, &mut extras)?;
 
         Ok(ScanResult {
             total_files: scanned_files.len(),
             scanned_files,
+            extras,
         })
     }
 
+    /// 查找目录下 Extras/ 文件夹中的附加内容
+    pub fn find_extras(&self, dir: &Path) -> Vec<ScannedExtra> {
+        let mut extras = Vec::new();
+        if let Ok(entries) = fs::read_dir(dir) {
+            for path in entries.flatten().map(|e| e.path()) {
+                if path.is_dir() && is_extras_dir(&path) {
+                    self.collect_extras(&path, &path, dir, &mut extras);
+                }
+            }
+        }
+        extras
+    }
+
+    /// 收集 Extras/ 下的文件（包括子文件夹），读取失败的项跳过
+    fn collect_extras(&self, extras_root: &Path, dir: &Path, owner_dir: &Path, extras: &mut Vec<ScannedExtra>) {
+        let Ok(entries) = fs::read_dir(dir) else { return };
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.is_dir() {
+                self.collect_extras(extras_root, &path, owner_dir, extras);
+                continue;
+            }
+            if !self.is_video_file(&path) && !is_extra_image(&path) {
+                continue;
+            }
+            let (Some(file_name), Ok(metadata)) = (path.file_name(), fs::metadata(&path)) else { continue };
+            let relative = path.strip_prefix(extras_root).unwrap_or(&path);
+            extras.push(ScannedExtra {
+                file_path: path.to_string_lossy().to_string(),
+                file_name: file_name.to_string_lossy().to_string(),
+                file_size: metadata.len(),
+                extra_type: classify_extra(relative).to_string(),
+                owner_dir: owner_dir.to_string_lossy().to_string(),
+            });
+        }
+    }
+
     /// 递归扫描目录
     fn scan_dir_recursive(
         &self,
         dir: &Path,
         recursive: bool,
         files: &mut Vec<ScannedFile>,
+        extras: &mut Vec<ScannedExtra>,
     ) -> Result<(), String> {
         let entries = fs::read_dir(dir)
             .map_err(|e| format!("无法读取目录 {}: {}", dir.display(), e))?;
@@ -139,9 +326,12 @@ impl FileScanner {
                         files.push(scanned_file);
                     }
                 }
+            } else if path.is_dir() && is_extras_dir(&path) {
+                // Extras/ 中是附加内容，不作为正片匹配
+                self.collect_extras(&path, &path, dir, extras);
             } else if path.is_dir() && recursive {
                 // 递归扫描子目录
-                self.scan_dir_recursive(&path, recursive, files)?;
+                self.scan_dir_recursive(&path, recursive, files, extras)?;
             }
         }
 
@@ -168,17 +358,17 @@ impl FileScanner {
         let file_size = metadata.len();
 
         // 解析文件名
-        let (parsed_code, parsed_title, parsed_year, parsed_series, parsed_date) = self.parse_filename(&file_name);
+        let parsed = self.parse(&file_name);
 
         Some(ScannedFile {
             file_path,
             file_name,
             file_size,
-            parsed_code,
-            parsed_title,
-            parsed_year,
-            parsed_series,
-            parsed_date,
+            parsed_code: parsed.code,
+            parsed_title: parsed.title,
+            parsed_year: parsed.year,
+            parsed_series: parsed.series,
+            parsed_date: parsed.date,
         })
     }
 
@@ -552,4 +742,90 @@ mod tests {
         assert_eq!(series, None);
         assert_eq!(date, None);
     }
+
+    #[test]
+    fn test_custom_rules() {
+        let rules = vec![
+            FilenameRule {
+                name: "screenshot".to_string(),
+                pattern: r"^Screen_(?P<series>[A-Za-z]+)_(?P<yy>\d{2})(?P<mm>\d{2})(?P<dd>\d{2})".to_string(),
+                date_template: Some("20${yy}-${mm}-${dd}".to_string()),
+                ..Default::default()
+            },
+            FilenameRule {
+                name: "prefixed".to_string(),
+                pattern: r"^site-(?P<p>[a-z]+)(?P<n>\d+)$".to_string(),
+                code_template: Some("${p}-${n}".to_string()),
+                ..Default::default()
+            },
+            FilenameRule {
+                name: "disabled".to_string(),
+                pattern: r"^(?P<code>IPX-\d+)".to_string(),
+                enabled: false,
+                ..Default::default()
+            },
+        ];
+        let scanner = FileScanner::with_rules(&rules);
+
+        let parsed = scanner.parse("Screen_Foo_240115.png");
+        assert_eq!(parsed.series, Some("Foo".to_string()));
+        assert_eq!(parsed.date, Some("2024-01-15".to_string()));
+        assert_eq!(parsed.year, Some(2024));
+        assert_eq!(parsed.matched_rule, Some("screenshot".to_string()));
+
+        let parsed = scanner.parse("site-abc123.mp4");
+        assert_eq!(parsed.code, Some("ABC-123".to_string()));
+
+        // 禁用的规则不生效，回退到内置解析
+        let parsed = scanner.parse("IPX-177.mp4");
+        assert_eq!(parsed.code, Some("IPX-177".to_string()));
+        assert_eq!(parsed.matched_rule, None);
+    }
+
+    #[test]
+    fn test_reparse_keeps_file_info() {
+        let file = ScannedFile {
+            file_path: "/data/Shot_xyz-9.mp4".to_string(),
+            file_name: "Shot_xyz-9.mp4".to_string(),
+            file_size: 42,
+            parsed_code: None,
+            parsed_title: Some("Shot xyz 9".to_string()),
+            parsed_year: None,
+            parsed_series: None,
+            parsed_date: None,
+        };
+        let rules = vec![FilenameRule {
+            name: "shot".to_string(),
+            pattern: r"^Shot_(?P<code>[a-z]+-\d+)".to_string(),
+            ..Default::default()
+        }];
+        let reparsed = FileScanner::with_rules(&rules).reparse(&file);
+        assert_eq!(reparsed.parsed_code, Some("XYZ-9".to_string()));
+        assert_eq!(reparsed.parsed_title, None);
+        assert_eq!(reparsed.file_size, 42);
+        assert_eq!(reparsed.file_path, file.file_path);
+    }
+
+    #[test]
+    fn test_scan_skips_extras() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("Extras/Trailers")).unwrap();
+        fs::write(root.join("ABC-123.mp4"), b"movie").unwrap();
+        fs::write(root.join("Extras/Trailers/teaser.mp4"), b"trailer").unwrap();
+        fs::write(root.join("Extras/still.jpg"), b"img").unwrap();
+        fs::write(root.join("Extras/notes.txt"), b"txt").unwrap();
+
+        let scanner = FileScanner::new();
+        let result = scanner.scan_directory(&root.to_string_lossy(), false).unwrap();
+        assert_eq!(result.total_files, 1);
+        assert_eq!(result.scanned_files[0].file_name, "ABC-123.mp4");
+
+        let mut extras = result.extras;
+        extras.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        let types: Vec<(&str, &str)> = extras.iter().map(|e| (e.file_name.as_str(), e.extra_type.as_str())).collect();
+        assert_eq!(types, vec![("still.jpg", "gallery"), ("teaser.mp4", "trailer")]);
+        assert_eq!(extras[0].owner_dir, root.to_string_lossy());
+        assert_eq!(scanner.find_extras(root).len(), 2);
+    }
 }
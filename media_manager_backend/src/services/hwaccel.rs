@@ -0,0 +1,161 @@
+//! FFmpeg 硬件加速检测
+//!
+//! 启动时检测 ffmpeg 支持的硬件加速（NVENC / QSV / VAAPI）：先看 `ffmpeg -hwaccels` 是否列出，
+//! 再用对应的硬件编码器做一次测试编码，成功才算可用。通过环境变量选择：
+//! - HW_ACCEL：auto（默认，按 NVENC、QSV、VAAPI 的顺序选第一个可用的）、none、nvenc、qsv、vaapi；
+//!   指定的方式不可用时使用软件编解码
+//! - HW_ACCEL_DEVICE：VAAPI 设备，默认 /dev/dri/renderD128
+//!
+//! 检测结果在 /api/health 中返回。选中的加速方式用于 FFmpeg 解码（缩略图），失败时退回软件解码。
+
+use std::process::Output;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use tokio::process::Command;
+
+use crate::models::{HwAccel, HwAccelCapabilities};
+
+/// 每条检测命令的超时
+const DETECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+const DEFAULT_VAAPI_DEVICE: &str = "/dev/dri/renderD128";
+
+lazy_static::lazy_static! {
+    /// 检测完成前为 None
+    static ref CAPABILITIES: RwLock<Option<HwAccelCapabilities>> = RwLock::new(None);
+}
+
+fn vaapi_device() -> String {
+    std::env::var("HW_ACCEL_DEVICE").unwrap_or_else(|_| DEFAULT_VAAPI_DEVICE.to_string())
+}
+
+/// `ffmpeg -hwaccels` 输出中列出的加速名
+pub fn parse_hwaccels(output: &str) -> Vec<String> {
+    output.lines()
+        .skip_while(|line| !line.trim_end().ends_with(':'))
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// 按 HW_ACCEL 的值从可用的加速方式中选择
+pub fn select(requested: &str, available: &[HwAccel]) -> Option<HwAccel> {
+    match requested.trim().to_lowercase().as_str() {
+        "" | "auto" => HwAccel::ALL.into_iter().find(|accel| available.contains(accel)),
+        "none" | "off" | "software" => None,
+        other => HwAccel::parse(other).filter(|accel| available.contains(accel)),
+    }
+}
+
+/// 解码参数（放在 -i 之前）
+pub fn decode_args(accel: HwAccel) -> Vec<String> {
+    let mut args = vec!["-hwaccel".to_string(), accel.hwaccel_name().to_string()];
+    if accel == HwAccel::Vaapi {
+        args.extend(["-hwaccel_device".to_string(), vaapi_device()]);
+    }
+    args
+}
+
+/// 用硬件编码器编码一小段测试画面的参数
+fn test_encode_args(accel: HwAccel) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-loglevel", "error"].map(String::from).to_vec();
+    if accel == HwAccel::Vaapi {
+        args.extend(["-vaapi_device".to_string(), vaapi_device()]);
+    }
+    args.extend(["-f", "lavfi", "-i", "color=black:s=256x256:d=0.1"].map(String::from));
+    let filter = match accel {
+        HwAccel::Vaapi => "format=nv12,hwupload",
+        _ => "format=nv12",
+    };
+    args.extend(["-vf", filter, "-c:v", accel.h264_encoder(), "-f", "null", "-"].map(String::from));
+    args
+}
+
+async fn run_ffmpeg(args: &[String]) -> Option<Output> {
+    let output = Command::new("ffmpeg").args(args).kill_on_drop(true).output();
+    tokio::time::timeout(DETECT_TIMEOUT, output).await.ok()?.ok()
+}
+
+/// 检测可用的硬件加速并按 HW_ACCEL 选择
+pub async fn detect() -> HwAccelCapabilities {
+    let requested = std::env::var("HW_ACCEL").unwrap_or_else(|_| "auto".to_string());
+    let mut capabilities = HwAccelCapabilities { requested: requested.clone(), ..Default::default() };
+
+    let hwaccels = ["-hide_banner", "-hwaccels"].map(String::from);
+    let Some(output) = run_ffmpeg(&hwaccels).await.filter(|output| output.status.success()) else {
+        return capabilities;
+    };
+    capabilities.ffmpeg_available = true;
+
+    let listed = parse_hwaccels(&String::from_utf8_lossy(&output.stdout));
+    for accel in HwAccel::ALL {
+        if !listed.iter().any(|name| name == accel.hwaccel_name()) {
+            continue;
+        }
+        match run_ffmpeg(&test_encode_args(accel)).await {
+            Some(output) if output.status.success() => capabilities.available.push(accel),
+            Some(output) => tracing::debug!(
+                "Hardware acceleration {} is not usable: {}",
+                accel.as_str(), String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            None => tracing::debug!("Hardware acceleration {} test timed out", accel.as_str()),
+        }
+    }
+
+    capabilities.selected = select(&requested, &capabilities.available);
+    capabilities
+}
+
+/// 启动时检测并保存结果
+pub async fn init() {
+    let capabilities = detect().await;
+    match capabilities.selected {
+        Some(accel) => tracing::info!("Using {} hardware acceleration for ffmpeg", accel.as_str()),
+        None if !capabilities.ffmpeg_available => tracing::info!("ffmpeg not found, hardware acceleration disabled"),
+        None => {
+            let requested = capabilities.requested.trim().to_lowercase();
+            if HwAccel::parse(&requested).is_some() {
+                tracing::warn!("Hardware acceleration {} is not available, falling back to software", requested);
+            } else {
+                tracing::info!("Using software decoding for ffmpeg");
+            }
+        }
+    }
+    *CAPABILITIES.write().unwrap() = Some(capabilities);
+}
+
+/// 检测结果（检测完成前为 None）
+pub fn capabilities() -> Option<HwAccelCapabilities> {
+    CAPABILITIES.read().unwrap().clone()
+}
+
+/// 当前使用的硬件加速
+pub fn selected() -> Option<HwAccel> {
+    CAPABILITIES.read().unwrap().as_ref().and_then(|capabilities| capabilities.selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hwaccels() {
+        let output = "Hardware acceleration methods:\nvdpau\ncuda\nvaapi\n\n";
+        assert_eq!(parse_hwaccels(output), vec!["vdpau", "cuda", "vaapi"]);
+        assert!(parse_hwaccels("").is_empty());
+    }
+
+    #[test]
+    fn test_select() {
+        let available = [HwAccel::Vaapi, HwAccel::Qsv];
+        assert_eq!(select("auto", &available), Some(HwAccel::Qsv));
+        assert_eq!(select("VAAPI", &available), Some(HwAccel::Vaapi));
+        // 指定的方式不可用时使用软件编解码
+        assert_eq!(select("nvenc", &available), None);
+        assert_eq!(select("none", &available), None);
+        assert_eq!(select("auto", &[]), None);
+    }
+}
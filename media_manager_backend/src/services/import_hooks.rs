@@ -0,0 +1,163 @@
+//! 导入后处理钩子
+//!
+//! 文件导入或匹配到媒体后，依次执行启用的钩子（如通知 Plex 刷新媒体库、运行自定义脚本），
+//! 每次执行的退出码/响应状态和输出记录在 hook_events 中。钩子在后台执行，不影响导入结果
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Result};
+use sqlx::{Pool, Sqlite};
+
+use crate::database;
+use crate::models::{command_hooks_enabled, fill_template, HookContext, HookResult, ImportHook, MediaItem, HOOK_COMMANDS_ENV};
+
+/// 记录的输出最大长度（字节）
+const MAX_HOOK_OUTPUT: usize = 4096;
+
+/// 在后台执行导入钩子
+pub fn spawn_import_hooks(pool: Pool<Sqlite>, media_id: String, files: Vec<String>) {
+    tokio::spawn(async move {
+        if let Err(e) = run_import_hooks(&pool, &media_id, &files).await {
+            tracing::warn!("导入钩子执行失败 {}: {}", media_id, e);
+        }
+    });
+}
+
+/// 对导入的文件执行所有启用的钩子
+pub async fn run_import_hooks(pool: &Pool<Sqlite>, media_id: &str, files: &[String]) -> Result<Vec<HookResult>> {
+    let hooks = database::list_enabled_import_hooks(pool).await?;
+    if hooks.is_empty() {
+        return Ok(Vec::new());
+    }
+    let media = database::get_media_with(pool, media_id).await?
+        .ok_or_else(|| anyhow!("media {} not found", media_id))?;
+    let context = hook_context(&media, files);
+
+    let mut results = Vec::new();
+    for hook in &hooks {
+        results.push(fire_hook(pool, hook, &context).await?);
+    }
+    Ok(results)
+}
+
+/// 执行一个钩子并记录结果
+pub async fn fire_hook(pool: &Pool<Sqlite>, hook: &ImportHook, context: &HookContext) -> Result<HookResult> {
+    let result = execute_hook(hook, context).await;
+    if !result.success {
+        tracing::warn!(
+            "钩子 '{}' 执行失败: {}",
+            hook.name,
+            result.error.as_deref().or(result.output.as_deref()).unwrap_or("non-zero exit status")
+        );
+    }
+    let media_id = (!context.media_id.is_empty()).then_some(context.media_id.as_str());
+    database::insert_hook_event(pool, &hook.id, media_id, context.path.as_deref(), &result).await?;
+    Ok(result)
+}
+
+/// 钩子使用的导入信息
+pub fn hook_context(media: &MediaItem, files: &[String]) -> HookContext {
+    let path = files.first().cloned();
+    HookContext {
+        event: "import".to_string(),
+        media_id: media.id.clone(),
+        title: media.title.clone(),
+        original_title: media.original_title.clone(),
+        code: media.code.clone(),
+        year: media.year,
+        dir: path.as_deref()
+            .and_then(|p| Path::new(p).parent())
+            .map(|dir| dir.to_string_lossy().into_owned()),
+        path,
+        files: files.to_vec(),
+    }
+}
+
+async fn execute_hook(hook: &ImportHook, context: &HookContext) -> HookResult {
+    let started = Instant::now();
+    let timeout = Duration::from_secs(hook.timeout_secs as u64);
+    let outcome = match hook.hook_type.as_str() {
+        "command" => tokio::time::timeout(timeout, run_command(hook, context)).await,
+        _ => tokio::time::timeout(timeout, call_webhook(hook, context)).await,
+    };
+    let duration_ms = started.elapsed().as_millis() as i64;
+
+    match outcome {
+        Ok(Ok((success, exit_code, output))) => HookResult {
+            success,
+            exit_code,
+            output: (!output.is_empty()).then(|| truncate_output(output)),
+            error: None,
+            duration_ms,
+        },
+        Ok(Err(e)) => HookResult { success: false, exit_code: None, output: None, error: Some(e.to_string()), duration_ms },
+        Err(_) => HookResult {
+            success: false,
+            exit_code: None,
+            output: None,
+            error: Some(format!("timed out after {}s", hook.timeout_secs)),
+            duration_ms,
+        },
+    }
+}
+
+/// 执行命令（不经过 shell，命令路径固定，参数中的占位符替换为原始值）
+async fn run_command(hook: &ImportHook, context: &HookContext) -> Result<(bool, Option<i32>, String)> {
+    // 关闭开关后，之前创建的命令钩子也不再执行
+    if !command_hooks_enabled() {
+        return Err(anyhow!("command hooks are disabled (set {}=true to enable)", HOOK_COMMANDS_ENV));
+    }
+    let values = context.template_values(false);
+    let args: Vec<String> = hook.args.iter().map(|arg| fill_template(arg, &values)).collect();
+    let output = tokio::process::Command::new(&hook.target)
+        .args(&args)
+        .kill_on_drop(true)
+        .output()
+        .await?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((output.status.success(), output.status.code(), text))
+}
+
+/// 调用 Webhook（地址中的占位符按 URL 编码替换，POST/PUT 时以 JSON 发送导入信息）
+async fn call_webhook(hook: &ImportHook, context: &HookContext) -> Result<(bool, Option<i32>, String)> {
+    let url = fill_template(&hook.target, &context.template_values(true));
+    let method = reqwest::Method::from_bytes(hook.method.as_bytes())?;
+    let client = reqwest::Client::new();
+    let request = if method == reqwest::Method::GET {
+        client.get(&url)
+    } else {
+        client.request(method, &url).json(context)
+    };
+
+    let response = request.send().await?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    Ok((status.is_success(), Some(status.as_u16() as i32), body))
+}
+
+fn truncate_output(mut output: String) -> String {
+    if output.len() > MAX_HOOK_OUTPUT {
+        let mut end = MAX_HOOK_OUTPUT;
+        while !output.is_char_boundary(end) {
+            end -= 1;
+        }
+        output.truncate(end);
+    }
+    output.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_output() {
+        assert_eq!(truncate_output("ok\n".to_string()), "ok");
+        let long = "测".repeat(MAX_HOOK_OUTPUT);
+        let truncated = truncate_output(long);
+        assert!(truncated.len() <= MAX_HOOK_OUTPUT);
+        assert!(truncated.chars().all(|c| c == '测'));
+    }
+}
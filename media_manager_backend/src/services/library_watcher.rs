@@ -0,0 +1,201 @@
+//! 媒体库根目录的可用性检查和定期扫描
+//!
+//! 定期检查根目录是否可访问（SMB/NFS 等网络共享可能掉线），扫描时跳过不可访问的目录；
+//! 定期扫描开启了 auto_scan 的根目录，把首次出现的文件保存为新的扫描会话，在扫描页面中继续匹配
+
+use std::path::PathBuf;
+use std::time::Duration;
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+
+use crate::database;
+use crate::models::{LibraryRoot, RootAvailability};
+use crate::services::storage::root_storage;
+use crate::services::{FileScanner, ScannedFile};
+
+/// 单个根目录的检查超时（掉线的网络共享可能长时间无响应）
+const ROOT_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 检查目录是否存在、能否列出内容
+pub async fn check_root_availability(path: &str) -> RootAvailability {
+    let dir = PathBuf::from(path);
+    let check = tokio::task::spawn_blocking(move || -> RootAvailability {
+        match std::fs::metadata(&dir) {
+            Ok(meta) if !meta.is_dir() => RootAvailability {
+                path_exists: true,
+                reachable: false,
+                error: Some("not a directory".to_string()),
+            },
+            Ok(_) => match std::fs::read_dir(&dir).map(|mut entries| entries.next().is_some()) {
+                Ok(true) => RootAvailability {
+                    path_exists: true,
+                    reachable: true,
+                    error: None,
+                },
+                Ok(false) => RootAvailability {
+                    path_exists: true,
+                    reachable: false,
+                    error: Some("directory is empty (network share not mounted?)".to_string()),
+                },
+                Err(e) => RootAvailability { path_exists: true, reachable: false, error: Some(e.to_string()) },
+            },
+            Err(e) => RootAvailability { path_exists: false, reachable: false, error: Some(e.to_string()) },
+        }
+    });
+
+    match tokio::time::timeout(ROOT_CHECK_TIMEOUT, check).await {
+        Ok(Ok(availability)) => availability,
+        Ok(Err(e)) => RootAvailability { path_exists: false, reachable: false, error: Some(e.to_string()) },
+        Err(_) => RootAvailability {
+            path_exists: false,
+            reachable: false,
+            error: Some(format!("timed out after {}s", ROOT_CHECK_TIMEOUT.as_secs())),
+        },
+    }
+}
+
+/// 检查对象存储是否可访问
+async fn check_remote_availability(root: &LibraryRoot) -> RootAvailability {
+    let result = match root_storage(root) {
+        Ok(storage) => match tokio::time::timeout(ROOT_CHECK_TIMEOUT, storage.check()).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow::anyhow!("timed out after {}s", ROOT_CHECK_TIMEOUT.as_secs())),
+        },
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => RootAvailability { path_exists: true, reachable: true, error: None },
+        Err(e) => RootAvailability { path_exists: false, reachable: false, error: Some(e.to_string()) },
+    }
+}
+
+/// 检查根目录并保存结果，返回是否可访问
+pub async fn refresh_root_status(pool: &Pool<Sqlite>, root: &mut LibraryRoot) -> Result<bool> {
+    let availability = if root.is_local() {
+        check_root_availability(&root.path).await
+    } else {
+        check_remote_availability(root).await
+    };
+    if root.reachable == Some(true) && !availability.reachable {
+        tracing::warn!(
+            "Library root {} became unreachable: {}",
+            root.path,
+            availability.error.as_deref().unwrap_or("unknown error")
+        );
+    }
+    root.record_availability(&availability);
+    database::update_library_root_status(pool, root).await?;
+    Ok(availability.reachable)
+}
+
+/// 一次定期扫描的结果
+#[derive(Debug, Default)]
+pub struct LibraryScanResult {
+    pub scanned_roots: usize,
+    pub new_files: usize,
+    /// 发现新文件时创建的扫描会话
+    pub session_id: Option<String>,
+}
+
+/// 扫描开启了 auto_scan 的根目录，返回新发现的文件数
+pub async fn scan_auto_roots(pool: &Pool<Sqlite>) -> Result<LibraryScanResult> {
+    let roots: Vec<_> = database::list_library_roots(pool).await?
+        .into_iter()
+        // 对象存储上的根目录不支持扫描
+        .filter(|root| root.auto_scan && root.is_local())
+        .collect();
+    let mut result = LibraryScanResult::default();
+    if roots.is_empty() {
+        return Ok(result);
+    }
+
+    let scanner = FileScanner::with_rules(&database::get_filename_rules(pool).await?);
+    let known = database::find_known_file_paths(pool).await?;
+    let mut paths = Vec::new();
+    let mut records = Vec::new();
+    for mut root in roots {
+        if !refresh_root_status(pool, &mut root).await? {
+            tracing::info!("Skipping unreachable library root {}", root.path);
+            continue;
+        }
+        match scanner.scan_directory(&root.path, true) {
+            Ok(scan) => {
+                result.scanned_roots += 1;
+                paths.push(root.path.clone());
+                records.extend(scan.scanned_files.iter()
+                    .filter(|file| !known.contains(&file.file_path))
+                    .map(ScannedFile::to_record));
+            }
+            Err(e) => tracing::warn!("Auto scan of library root {} failed: {}", root.path, e),
+        }
+    }
+
+    result.new_files = records.len();
+    if !records.is_empty() {
+        result.session_id = Some(database::create_scan_session(pool, &paths, true, &records).await?);
+    }
+    Ok(result)
+}
+
+/// 定期检查根目录可用性的后台任务
+pub struct RootHealthTask {
+    pool: Pool<Sqlite>,
+    interval: Duration,
+}
+
+impl RootHealthTask {
+    pub fn new(pool: Pool<Sqlite>, interval: Duration) -> Self {
+        Self { pool, interval }
+    }
+
+    /// 启动定期检查任务（启动后立即检查一次）
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+            let roots = match database::list_library_roots(&self.pool).await {
+                Ok(roots) => roots,
+                Err(e) => {
+                    tracing::warn!("Failed to load library roots: {}", e);
+                    continue;
+                }
+            };
+            for mut root in roots {
+                if let Err(e) = refresh_root_status(&self.pool, &mut root).await {
+                    tracing::warn!("Failed to check library root {}: {}", root.path, e);
+                }
+            }
+        }
+    }
+}
+
+/// 定期扫描媒体库根目录的后台任务
+pub struct LibraryScanTask {
+    pool: Pool<Sqlite>,
+    interval: Duration,
+}
+
+impl LibraryScanTask {
+    pub fn new(pool: Pool<Sqlite>, interval: Duration) -> Self {
+        Self { pool, interval }
+    }
+
+    /// 启动定期扫描任务（启动后等待一个周期再执行首次扫描）
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            match scan_auto_roots(&self.pool).await {
+                Ok(result) if result.new_files > 0 => tracing::info!(
+                    "Library auto scan found {} new files in {} roots",
+                    result.new_files, result.scanned_roots
+                ),
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Library auto scan failed: {}", e),
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! 失效链接检查服务
+//!
+//! 对媒体的海报、背景图、预览图/视频、播放和下载链接发送 HEAD 请求（不支持时退回 GET），
+//! 限制并发数，把无法访问的链接记录到 media_broken_links，恢复可访问的链接自动移除
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use chrono::Utc;
+use reqwest::{Client, StatusCode};
+use sqlx::{Pool, Sqlite};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::database::{self, DatabaseRepository, SqliteRepository};
+use crate::models::{collect_remote_links, BrokenLink, LinkCheckResult, MediaItem, RemoteLink};
+
+/// 默认同时检查的链接数
+pub const DEFAULT_LINK_CHECK_CONCURRENCY: usize = 8;
+
+/// 允许的最大并发数
+pub const MAX_LINK_CHECK_CONCURRENCY: usize = 32;
+
+/// 单个链接的请求超时
+const LINK_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 状态码是否表示链接失效：4xx（鉴权、防盗链和限流除外）和 5xx
+pub fn is_dead_status(status: StatusCode) -> bool {
+    let access_limited = matches!(
+        status,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+    );
+    (status.is_client_error() && !access_limited) || status.is_server_error()
+}
+
+/// 检查单个链接，失效时返回 (状态码, 错误信息)
+async fn check_url(client: &Client, url: &str) -> Option<(Option<u16>, Option<String>)> {
+    let response = match client.head(url).send().await {
+        // 不支持 HEAD 的服务器退回只取首字节的 GET
+        Ok(r) if matches!(r.status(), StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED) => {
+            client.get(url).header(reqwest::header::RANGE, "bytes=0-0").send().await
+        }
+        other => other,
+    };
+
+    match response {
+        Ok(r) if is_dead_status(r.status()) => Some((Some(r.status().as_u16()), None)),
+        Ok(_) => None,
+        Err(e) => Some((None, Some(e.to_string()))),
+    }
+}
+
+/// 检查指定媒体（为空时检查全部媒体）的远程链接
+pub async fn check_media_links(
+    pool: &Pool<Sqlite>,
+    media_ids: Option<&[String]>,
+    concurrency: usize,
+) -> Result<LinkCheckResult> {
+    let repository = SqliteRepository::new(pool.clone());
+    let media: Vec<MediaItem> = match media_ids {
+        Some(ids) => {
+            let mut media = Vec::with_capacity(ids.len());
+            for id in ids {
+                media.extend(repository.get_media_by_id(id).await?);
+            }
+            media
+        }
+        None => repository.get_all_media().await?,
+    };
+
+    let client = Client::builder()
+        .timeout(LINK_CHECK_TIMEOUT)
+        .user_agent("Mozilla/5.0 (compatible; MediaManager link checker)")
+        .build()?;
+    let semaphore = Arc::new(Semaphore::new(concurrency.clamp(1, MAX_LINK_CHECK_CONCURRENCY)));
+    let mut result = LinkCheckResult::default();
+    let mut tasks = JoinSet::new();
+
+    for item in &media {
+        for RemoteLink { field, url } in collect_remote_links(item) {
+            result.checked_urls += 1;
+            let (client, semaphore, media_id) = (client.clone(), semaphore.clone(), item.id.clone());
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await.ok()?;
+                let (status_code, error) = check_url(&client, &url).await?;
+                Some(BrokenLink {
+                    media_id,
+                    field: field.to_string(),
+                    url,
+                    status_code: status_code.map(i64::from),
+                    error,
+                    checked_at: Utc::now(),
+                })
+            });
+        }
+    }
+
+    let mut broken: HashMap<String, Vec<BrokenLink>> = HashMap::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(Some(link)) => broken.entry(link.media_id.clone()).or_default().push(link),
+            Ok(None) => {}
+            Err(e) => result.errors.push(format!("Link check task failed: {}", e)),
+        }
+    }
+
+    for item in &media {
+        let links = broken.remove(&item.id).unwrap_or_default();
+        let previous = database::replace_broken_links(pool, &item.id, &links).await?;
+        result.checked_media += 1;
+        result.broken += links.len();
+        result.recovered += previous.iter()
+            .filter(|p| !links.iter().any(|l| l.url == p.url))
+            .count();
+        if !links.is_empty() {
+            result.broken_media.push(item.id.clone());
+        }
+    }
+
+    Ok(result)
+}
+
+/// 定期检查全部媒体链接的后台任务
+pub struct LinkCheckTask {
+    pool: Pool<Sqlite>,
+    interval: Duration,
+}
+
+impl LinkCheckTask {
+    pub fn new(pool: Pool<Sqlite>, interval: Duration) -> Self {
+        Self { pool, interval }
+    }
+
+    /// 启动定期检查任务（启动后等待一个周期再执行首次检查）
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            match check_media_links(&self.pool, None, DEFAULT_LINK_CHECK_CONCURRENCY).await {
+                Ok(result) => tracing::info!(
+                    "Link check completed: {} urls checked, {} broken, {} recovered",
+                    result.checked_urls, result.broken, result.recovered
+                ),
+                Err(e) => tracing::warn!("Link check failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_dead_status() {
+        assert!(is_dead_status(StatusCode::NOT_FOUND));
+        assert!(is_dead_status(StatusCode::GONE));
+        assert!(is_dead_status(StatusCode::BAD_GATEWAY));
+        assert!(!is_dead_status(StatusCode::OK));
+        assert!(!is_dead_status(StatusCode::FORBIDDEN));
+        assert!(!is_dead_status(StatusCode::TOO_MANY_REQUESTS));
+    }
+}
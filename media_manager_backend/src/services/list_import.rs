@@ -0,0 +1,229 @@
+//! IMDb / Letterboxd 列表导入
+//!
+//! 解析 IMDb 导出的评分/片单 CSV 和 Letterboxd 导出的 watchlist/watched/ratings/diary CSV，
+//! 转换为统一的行记录，后续由 API 层通过 TMDB 搜索解析为媒体
+
+use serde::Serialize;
+
+use crate::models::MediaItem;
+
+/// 列表来源格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ListFormat {
+    Imdb,
+    Letterboxd,
+}
+
+impl std::str::FromStr for ListFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "imdb" => Ok(ListFormat::Imdb),
+            "letterboxd" => Ok(ListFormat::Letterboxd),
+            _ => Err(format!("Unsupported list format: {}", s)),
+        }
+    }
+}
+
+/// 列表中的一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListRow {
+    /// CSV 中的行号（表头为第 1 行）
+    pub line: usize,
+    pub title: String,
+    pub year: Option<i32>,
+    pub imdb_id: Option<String>,
+    /// TMDB 类型：movie 或 tv
+    pub media_type: &'static str,
+    /// 个人评分（统一为 0-10）
+    pub rating: Option<f32>,
+    /// 是否已观看（有评分或观看日期）
+    pub watched: bool,
+    /// 不支持导入的原因（如剧集单集）
+    pub skip_reason: Option<String>,
+}
+
+/// 解析 CSV 文本（支持引号、转义引号和字段内换行）
+pub fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// 根据表头识别格式：IMDb 有 Const 列，Letterboxd 有 Letterboxd URI 列
+pub fn detect_format(headers: &[String]) -> Option<ListFormat> {
+    if headers.iter().any(|h| h == "Const") {
+        Some(ListFormat::Imdb)
+    } else if headers.iter().any(|h| h == "Letterboxd URI") {
+        Some(ListFormat::Letterboxd)
+    } else {
+        None
+    }
+}
+
+/// 解析列表 CSV，format 为空时自动识别
+pub fn parse_list(content: &str, format: Option<ListFormat>) -> Result<(ListFormat, Vec<ListRow>), String> {
+    let mut rows = parse_csv(content).into_iter();
+    let headers: Vec<String> = rows.next()
+        .ok_or_else(|| "CSV is empty".to_string())?
+        .into_iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+    let format = format.or_else(|| detect_format(&headers))
+        .ok_or_else(|| "Cannot detect list format (expected an IMDb or Letterboxd CSV export)".to_string())?;
+
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let title_col = match format {
+        ListFormat::Imdb => column("Title"),
+        ListFormat::Letterboxd => column("Name"),
+    }
+    .ok_or_else(|| "CSV has no title column".to_string())?;
+    let year_col = column("Year");
+    let imdb_col = column("Const");
+    let type_col = column("Title Type");
+    let rating_col = match format {
+        ListFormat::Imdb => column("Your Rating"),
+        ListFormat::Letterboxd => column("Rating"),
+    };
+    let watched_col = column("Watched Date").or_else(|| column("Date Rated"));
+
+    let parsed = rows
+        .enumerate()
+        .filter(|(_, row)| row.iter().any(|f| !f.trim().is_empty()))
+        .filter_map(|(i, row)| {
+            let get = |col: Option<usize>| {
+                col.and_then(|c| row.get(c))
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty())
+            };
+            let title = get(Some(title_col))?.to_string();
+            let rating = get(rating_col)
+                .and_then(|r| r.parse::<f32>().ok())
+                .map(|r| if format == ListFormat::Letterboxd { r * 2.0 } else { r });
+            let title_type = get(type_col).unwrap_or("movie").to_lowercase().replace(' ', "");
+            let skip_reason = title_type.contains("episode")
+                .then(|| "TV episodes are not supported".to_string());
+            let media_type = if title_type.contains("series") { "tv" } else { "movie" };
+
+            Some(ListRow {
+                line: i + 2,
+                title,
+                year: get(year_col).and_then(|y| y.parse().ok()),
+                imdb_id: get(imdb_col).filter(|id| id.starts_with("tt")).map(String::from),
+                media_type,
+                watched: rating.is_some() || get(watched_col).is_some(),
+                rating,
+                skip_reason,
+            })
+        })
+        .collect();
+
+    Ok((format, parsed))
+}
+
+/// 从 TMDB 搜索结果中选出与年份最接近的条目：优先同年，其次相差一年；没有年份时取第一个
+pub fn pick_by_year(results: Vec<MediaItem>, year: Option<i32>) -> Option<MediaItem> {
+    let Some(year) = year else {
+        return results.into_iter().next();
+    };
+    let distance = |m: &MediaItem| m.year.map(|y| (y - year).abs()).filter(|d| *d <= 1);
+    results.into_iter()
+        .filter_map(|m| distance(&m).map(|d| (d, m)))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, m)| m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MediaType;
+
+    #[test]
+    fn test_parse_csv_quotes() {
+        let rows = parse_csv("a,b\r\n\"x, y\",\"say \"\"hi\"\"\"\n");
+        assert_eq!(rows, vec![
+            vec!["a".to_string(), "b".to_string()],
+            vec!["x, y".to_string(), "say \"hi\"".to_string()],
+        ]);
+    }
+
+    #[test]
+    fn test_parse_imdb_list() {
+        let csv = "Const,Your Rating,Date Rated,Title,URL,Title Type,Year\n\
+            tt0137523,9,2024-01-01,Fight Club,https://www.imdb.com/title/tt0137523/,Movie,1999\n\
+            tt0903747,,,\"Breaking Bad\",https://www.imdb.com/title/tt0903747/,TV Series,2008\n\
+            tt0959621,,,Pilot,https://www.imdb.com/title/tt0959621/,TV Episode,2008\n";
+        let (format, rows) = parse_list(csv, None).unwrap();
+        assert_eq!(format, ListFormat::Imdb);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].imdb_id.as_deref(), Some("tt0137523"));
+        assert_eq!(rows[0].rating, Some(9.0));
+        assert!(rows[0].watched);
+        assert_eq!(rows[1].media_type, "tv");
+        assert!(!rows[1].watched);
+        assert_eq!(rows[1].line, 3);
+        assert!(rows[2].skip_reason.is_some());
+    }
+
+    #[test]
+    fn test_parse_letterboxd_list() {
+        let csv = "Date,Name,Year,Letterboxd URI,Rating\n\
+            2024-02-01,Parasite,2019,https://boxd.it/abc,4.5\n\
+            2024-02-02,Alien,1979,https://boxd.it/def,\n";
+        let (format, rows) = parse_list(csv, None).unwrap();
+        assert_eq!(format, ListFormat::Letterboxd);
+        assert_eq!(rows[0].rating, Some(9.0));
+        assert_eq!(rows[0].year, Some(2019));
+        assert!(rows[0].imdb_id.is_none());
+        assert!(!rows[1].watched);
+
+        assert!(parse_list("foo,bar\n1,2\n", None).is_err());
+    }
+
+    #[test]
+    fn test_pick_by_year() {
+        let media = |title: &str, year: i32| {
+            let mut m = MediaItem::new(title.to_string(), MediaType::Movie).unwrap();
+            m.year = Some(year);
+            m
+        };
+        let results = vec![media("Remake", 2019), media("Next", 1980), media("Original", 1979)];
+        assert_eq!(pick_by_year(results.clone(), Some(1979)).unwrap().title, "Original");
+        assert_eq!(pick_by_year(results.clone(), Some(2020)).unwrap().title, "Remake");
+        assert_eq!(pick_by_year(results.clone(), None).unwrap().title, "Remake");
+        assert!(pick_by_year(results, Some(2000)).is_none());
+    }
+}
@@ -0,0 +1,265 @@
+//! 磁力链接工具
+//!
+//! 校验磁力链接、统一 info hash（十六进制/base32 -> 小写十六进制）、解析显示名称和 tracker，
+//! 以及跨来源去重。插件只需返回原始结果，去重统一在主程序完成。
+//! 返回和推送磁力链接前按 tracker 设置追加/移除 tracker。
+
+use std::collections::HashMap;
+
+use crate::models::{same_tracker, MagnetTrackerSettings};
+use crate::plugins::protocol::MagnetResult;
+
+/// 解析后的磁力链接
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MagnetUri {
+    /// info hash（小写十六进制）
+    pub info_hash: String,
+    /// 显示名称（dn 参数）
+    pub display_name: Option<String>,
+    /// tracker 地址（tr 参数，按出现顺序去重）
+    pub trackers: Vec<String>,
+}
+
+impl MagnetUri {
+    /// 解析磁力链接，不是 magnet: 链接或没有有效的 btih info hash 时返回 None
+    pub fn parse(uri: &str) -> Option<Self> {
+        let uri = uri.trim();
+        let query = uri.get(..8)
+            .filter(|scheme| scheme.eq_ignore_ascii_case("magnet:?"))
+            .map(|_| &uri[8..])?;
+
+        let mut info_hash = None;
+        let mut display_name = None;
+        let mut trackers: Vec<String> = Vec::new();
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "xt" if info_hash.is_none() => {
+                    info_hash = value.get(..9)
+                        .filter(|prefix| prefix.eq_ignore_ascii_case("urn:btih:"))
+                        .and_then(|_| normalize_info_hash(&value[9..]));
+                }
+                "dn" if display_name.is_none() && !value.trim().is_empty() => {
+                    display_name = Some(value.trim().to_string());
+                }
+                "tr" if !value.is_empty() && !trackers.iter().any(|t| *t == value) => {
+                    trackers.push(value.into_owned());
+                }
+                _ => {}
+            }
+        }
+
+        Some(Self { info_hash: info_hash?, display_name, trackers })
+    }
+}
+
+/// 从磁力链接中取出 info hash（统一为小写十六进制，支持 base32 形式）
+pub fn info_hash(magnet: &str) -> Option<String> {
+    MagnetUri::parse(magnet).map(|uri| uri.info_hash)
+}
+
+/// 是否为有效的磁力链接（含可解析的 btih info hash）
+pub fn is_valid(magnet: &str) -> bool {
+    MagnetUri::parse(magnet).is_some()
+}
+
+/// 把 40 位十六进制或 32 位 base32 的 info hash 统一为小写十六进制
+pub fn normalize_info_hash(hash: &str) -> Option<String> {
+    match hash.len() {
+        40 if hash.chars().all(|c| c.is_ascii_hexdigit()) => Some(hash.to_lowercase()),
+        32 => decode_base32(hash).map(|bytes| bytes.iter().map(|b| format!("{:02x}", b)).collect()),
+        _ => None,
+    }
+}
+
+/// RFC 4648 base32 解码（不带填充）
+fn decode_base32(value: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(value.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in value.chars() {
+        let digit = match c.to_ascii_uppercase() {
+            c @ 'A'..='Z' => c as u32 - 'A' as u32,
+            c @ '2'..='7' => c as u32 - '2' as u32 + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | digit;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// 把新结果并入已有结果，返回新增数
+///
+/// - 无效的磁力链接直接丢弃
+/// - 按 info hash 去重，保留先到的结果，用重复结果补全其缺失的大小、日期和文件列表
+/// - 标题为空时使用磁力链接的显示名称
+pub fn merge_results(existing: &mut Vec<MagnetResult>, incoming: Vec<MagnetResult>) -> usize {
+    let mut positions: HashMap<String, usize> = existing.iter()
+        .enumerate()
+        .filter_map(|(index, result)| info_hash(&result.magnet_link).map(|hash| (hash, index)))
+        .collect();
+    let before = existing.len();
+
+    for mut result in incoming {
+        let Some(uri) = MagnetUri::parse(&result.magnet_link) else {
+            tracing::warn!("丢弃无效的磁力链接: {} ({})", result.title, result.magnet_link);
+            continue;
+        };
+        if let Some(&index) = positions.get(&uri.info_hash) {
+            fill_missing(&mut existing[index], result);
+            continue;
+        }
+        if result.title.trim().is_empty() {
+            result.title = uri.display_name.unwrap_or_else(|| uri.info_hash.clone());
+        }
+        positions.insert(uri.info_hash, existing.len());
+        existing.push(result);
+    }
+    existing.len() - before
+}
+
+/// 合并去重一组结果
+pub fn dedup(results: Vec<MagnetResult>) -> Vec<MagnetResult> {
+    let mut merged = Vec::with_capacity(results.len());
+    merge_results(&mut merged, results);
+    merged
+}
+
+/// 按 tracker 设置改写磁力链接：移除已失效的 tracker，追加配置的 tracker（已存在的不重复追加）
+///
+/// 其他参数保持原样，无效的磁力链接原样返回
+pub fn with_trackers(magnet: &str, settings: &MagnetTrackerSettings) -> String {
+    let magnet = magnet.trim();
+    let Some(uri) = MagnetUri::parse(magnet) else {
+        return magnet.to_string();
+    };
+    if !settings.is_active() {
+        return magnet.to_string();
+    }
+
+    let params: Vec<&str> = magnet[8..].split('&')
+        .filter(|param| match url::form_urlencoded::parse(param.as_bytes()).next() {
+            Some((key, value)) => !(key == "tr" && settings.is_dead(&value)),
+            None => false,
+        })
+        .collect();
+    let mut trackers: Vec<String> = uri.trackers.into_iter().filter(|t| !settings.is_dead(t)).collect();
+
+    let mut enriched = format!("{}{}", &magnet[..8], params.join("&"));
+    for tracker in &settings.trackers {
+        if settings.is_dead(tracker) || trackers.iter().any(|t| same_tracker(t, tracker)) {
+            continue;
+        }
+        enriched.push_str("&tr=");
+        enriched.extend(url::form_urlencoded::byte_serialize(tracker.as_bytes()));
+        trackers.push(tracker.clone());
+    }
+    enriched
+}
+
+/// 按 tracker 设置改写一组结果的磁力链接
+pub fn apply_trackers(results: &mut [MagnetResult], settings: &MagnetTrackerSettings) {
+    if settings.is_active() {
+        for result in results {
+            result.magnet_link = with_trackers(&result.magnet_link, settings);
+        }
+    }
+}
+
+fn fill_missing(kept: &mut MagnetResult, duplicate: MagnetResult) {
+    if kept.size.is_none() {
+        kept.size = duplicate.size;
+    }
+    if kept.file_count.is_none() {
+        kept.file_count = duplicate.file_count;
+    }
+    if kept.date.is_none() {
+        kept.date = duplicate.date;
+    }
+    if kept.files.is_empty() {
+        kept.files = duplicate.files;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEX: &str = "c12fe1c06bba254a9dc9f519b335aa7c1367a88a";
+
+    fn magnet(title: &str, link: &str, size: Option<&str>) -> MagnetResult {
+        MagnetResult {
+            title: title.to_string(),
+            magnet_link: link.to_string(),
+            size: size.map(|s| s.to_string()),
+            file_count: None,
+            date: None,
+            files: Vec::new(),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_info_hash() {
+        assert_eq!(
+            info_hash("magnet:?xt=urn:btih:C12FE1C06BBA254A9DC9F519B335AA7C1367A88A&dn=test").as_deref(),
+            Some(HEX)
+        );
+        assert_eq!(info_hash("magnet:?xt=urn:btih:YEX6DQDLXISUVHOJ6UM3GNNKPQJWPKEK").as_deref(), Some(HEX));
+        assert_eq!(info_hash("magnet:?dn=no-hash"), None);
+        assert_eq!(info_hash("magnet:?xt=urn:btih:1234"), None);
+        assert!(!is_valid("https://example.com/?xt=urn:btih:C12FE1C06BBA254A9DC9F519B335AA7C1367A88A"));
+    }
+
+    #[test]
+    fn test_parse_display_name_and_trackers() {
+        let uri = MagnetUri::parse(&format!(
+            "MAGNET:?dn=ABC-123+1080p&xt=urn:btih:{}&tr=udp%3A%2F%2Ftracker.example%3A80&tr=udp%3A%2F%2Ftracker.example%3A80&tr=http%3A%2F%2Fother%2Fannounce",
+            HEX
+        )).unwrap();
+        assert_eq!(uri.info_hash, HEX);
+        assert_eq!(uri.display_name.as_deref(), Some("ABC-123 1080p"));
+        assert_eq!(uri.trackers, vec!["udp://tracker.example:80", "http://other/announce"]);
+    }
+
+    #[test]
+    fn test_merge_results_across_sources() {
+        let mut merged = vec![magnet("First", &format!("magnet:?xt=urn:btih:{}", HEX), None)];
+        let added = merge_results(&mut merged, vec![
+            magnet("Same (base32)", "magnet:?xt=urn:btih:YEX6DQDLXISUVHOJ6UM3GNNKPQJWPKEK", Some("1.2 GB")),
+            magnet("", "magnet:?xt=urn:btih:0000000000000000000000000000000000000001&dn=Named", None),
+            magnet("Broken", "magnet:?xt=urn:btih:nothex", None),
+        ]);
+        assert_eq!(added, 1);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].title, "First");
+        assert_eq!(merged[0].size.as_deref(), Some("1.2 GB"));
+        assert_eq!(merged[1].title, "Named");
+    }
+
+    #[test]
+    fn test_with_trackers() {
+        let settings = MagnetTrackerSettings {
+            trackers: vec!["udp://open.example:1337/announce".to_string(), "http://dead.example/announce".to_string()],
+            strip_dead: true,
+            dead_trackers: vec!["http://dead.example/announce/".to_string()],
+        };
+        let link = format!(
+            "magnet:?xt=urn:btih:{}&dn=A%20B&tr=http%3A%2F%2Fdead.example%2Fannounce&tr=udp%3A%2F%2Fopen.example%3A1337%2Fannounce",
+            HEX
+        );
+        assert_eq!(
+            with_trackers(&link, &settings),
+            format!("magnet:?xt=urn:btih:{}&dn=A%20B&tr=udp%3A%2F%2Fopen.example%3A1337%2Fannounce", HEX)
+        );
+
+        let enriched = with_trackers(&format!("magnet:?xt=urn:btih:{}", HEX), &settings);
+        assert_eq!(MagnetUri::parse(&enriched).unwrap().trackers, vec!["udp://open.example:1337/announce"]);
+        assert_eq!(with_trackers(&enriched, &settings), enriched);
+        assert_eq!(with_trackers("magnet:?dn=no-hash", &settings), "magnet:?dn=no-hash");
+    }
+}
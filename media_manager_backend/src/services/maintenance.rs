@@ -0,0 +1,183 @@
+//! 数据库维护服务
+//!
+//! 清理孤立的关联数据、重建全文索引、执行 `PRAGMA optimize` 和 `VACUUM`，
+//! 可通过接口手动触发，也可按 `DB_OPTIMIZE_INTERVAL_HOURS` 定期执行；
+//! 孤立的关联行、缓存文件、超过 `CHANGES_RETENTION_DAYS` 的变更流记录和过期的幂等键每天自动清理一次
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use serde::Serialize;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::Mutex;
+
+use crate::database;
+use crate::models::{OptimizeResult, OrphanRelations, IDEMPOTENCY_WINDOW_HOURS};
+use crate::services::cache::{CacheService, OrphanedCache};
+
+/// 同一时间只允许一次优化
+static OPTIMIZE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// 优化数据库，已有优化在执行时返回 None
+pub async fn optimize_database(pool: &Pool<Sqlite>) -> Result<Option<OptimizeResult>> {
+    let Ok(_guard) = OPTIMIZE_LOCK.try_lock() else {
+        return Ok(None);
+    };
+    let started = Instant::now();
+
+    let mut result = OptimizeResult {
+        size_before_bytes: database::database_size_bytes(pool).await?,
+        ..Default::default()
+    };
+    result.orphan_relations_removed = database::delete_orphan_relations(pool).await?
+        .iter()
+        .map(|r| r.rows)
+        .sum();
+    result.search_index_rows = database::rebuild_media_search_index(pool).await?;
+    database::analyze_database(pool).await?;
+    database::vacuum_database(pool).await?;
+    result.size_after_bytes = database::database_size_bytes(pool).await?;
+    result.reclaimed_bytes = (result.size_before_bytes - result.size_after_bytes).max(0);
+    result.duration_ms = started.elapsed().as_millis() as u64;
+
+    Ok(Some(result))
+}
+
+/// 孤立数据清理报告
+#[derive(Debug, Default, Serialize)]
+pub struct OrphanCleanupReport {
+    /// 为 true 时只统计，未删除任何数据
+    pub dry_run: bool,
+    /// 有孤立行的关联表
+    pub relations: Vec<OrphanRelations>,
+    pub relation_rows: u64,
+    /// 所属媒体已删除的缓存目录
+    pub cache: Vec<OrphanedCache>,
+    pub cache_files: usize,
+    pub cache_bytes: u64,
+    /// 超过保留期的变更流记录
+    pub changes_removed: u64,
+}
+
+/// 变更流默认保留天数
+const DEFAULT_CHANGES_RETENTION_DAYS: i64 = 90;
+
+/// 读取变更流保留天数，设为 0 时不清理
+fn changes_retention_from_env() -> Option<chrono::Duration> {
+    let days = std::env::var("CHANGES_RETENTION_DAYS").ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .unwrap_or(DEFAULT_CHANGES_RETENTION_DAYS);
+    (days > 0).then(|| chrono::Duration::days(days))
+}
+
+/// 清理父记录已不存在的关联行、所属媒体已删除的缓存文件和过期的变更流记录，dry_run 时只统计
+pub async fn cleanup_orphans(
+    pool: &Pool<Sqlite>,
+    cache_service: &CacheService,
+    dry_run: bool,
+) -> Result<OrphanCleanupReport> {
+    let relations = if dry_run {
+        database::find_orphan_relations(pool).await?
+    } else {
+        database::delete_orphan_relations(pool).await?
+    };
+    let cache = if dry_run {
+        cache_service.find_orphaned_cache().await?
+    } else {
+        cache_service.clear_orphaned_cache().await?
+    };
+    let changes_removed = match changes_retention_from_env() {
+        Some(retention) => {
+            let before = (chrono::Utc::now() - retention).format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string();
+            if dry_run {
+                database::count_prunable_changes(pool, &before).await?
+            } else {
+                database::prune_changes(pool, &before).await?
+            }
+        }
+        None => 0,
+    };
+
+    Ok(OrphanCleanupReport {
+        dry_run,
+        relation_rows: relations.iter().map(|r| r.rows).sum(),
+        relations,
+        cache_files: cache.iter().map(|c| c.files).sum(),
+        cache_bytes: cache.iter().map(|c| c.size_bytes).sum(),
+        cache,
+        changes_removed,
+    })
+}
+
+/// 读取定期优化间隔，未配置或为 0 时不定期执行
+pub fn optimize_interval_from_env() -> Option<Duration> {
+    std::env::var("DB_OPTIMIZE_INTERVAL_HOURS").ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|hours| *hours > 0)
+        .map(|hours| Duration::from_secs(hours * 60 * 60))
+}
+
+/// 定期优化数据库的后台任务
+pub struct MaintenanceTask {
+    pool: Pool<Sqlite>,
+    interval: Duration,
+}
+
+impl MaintenanceTask {
+    pub fn new(pool: Pool<Sqlite>, interval: Duration) -> Self {
+        Self { pool, interval }
+    }
+
+    /// 启动定期优化任务（启动后等待一个周期再执行首次优化）
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            match optimize_database(&self.pool).await {
+                Ok(Some(result)) => tracing::info!(
+                    "Database optimized: {} orphan relations removed, {} bytes reclaimed",
+                    result.orphan_relations_removed, result.reclaimed_bytes
+                ),
+                Ok(None) => tracing::info!("Database optimization already running, skipped"),
+                Err(e) => tracing::warn!("Database optimization failed: {}", e),
+            }
+        }
+    }
+}
+
+/// 定期清理孤立数据的后台任务
+pub struct OrphanCleanupTask {
+    pool: Pool<Sqlite>,
+    cache_service: Arc<CacheService>,
+    interval: Duration,
+}
+
+impl OrphanCleanupTask {
+    pub fn new(pool: Pool<Sqlite>, cache_service: Arc<CacheService>, interval: Duration) -> Self {
+        Self { pool, cache_service, interval }
+    }
+
+    /// 启动定期清理任务（启动后等待一个周期再执行首次清理）
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            match cleanup_orphans(&self.pool, &self.cache_service, false).await {
+                Ok(report) => tracing::info!(
+                    "Orphan cleanup completed: {} relation rows, {} cache files, {} expired changes removed",
+                    report.relation_rows, report.cache_files, report.changes_removed
+                ),
+                Err(e) => tracing::warn!("Orphan cleanup failed: {}", e),
+            }
+            let cutoff = chrono::Utc::now() - chrono::Duration::hours(IDEMPOTENCY_WINDOW_HOURS);
+            match database::delete_expired_idempotency_keys(&self.pool, cutoff).await {
+                Ok(removed) => tracing::info!("Expired idempotency keys removed: {}", removed),
+                Err(e) => tracing::warn!("Failed to delete expired idempotency keys: {}", e),
+            }
+        }
+    }
+}
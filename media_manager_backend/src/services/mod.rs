@@ -3,9 +3,54 @@ pub mod database_service;
 pub mod file_scanner;
 pub mod file_matcher;
 pub mod file_grouper;
+pub mod release_calendar;
+pub mod torrent_client;
+pub mod wanted_monitor;
+pub mod download_importer;
+pub mod import_hooks;
+pub mod enrichment;
+pub mod quality;
+pub mod peer_sync;
+pub mod share_link;
+pub mod trailer;
+pub mod list_import;
+pub mod series_gaps;
+pub mod transliteration;
+pub mod link_checker;
+pub mod scrape_archive;
+pub mod maintenance;
+pub mod library_watcher;
+pub mod storage;
+pub mod actor_dedup;
+pub mod tmdb_list_cache;
+pub mod progress;
+pub mod magnet;
+pub mod search_query;
+pub mod swarm_stats;
+pub mod series_detect;
+pub mod thumbnail;
+pub mod ffmpeg;
+pub mod hwaccel;
+pub mod stream_sessions;
+pub mod offline_package;
+pub mod artwork_dedup;
 
 pub use cache::{CacheConfig, CacheField, ScraperCacheConfig, CacheError, CachePath, ConfigManager, CacheService, CacheStats};
 pub use database_service::DatabaseService;
-pub use file_scanner::{FileScanner, ScannedFile};
-pub use file_matcher::{FileMatcher, MatchResult, GroupMatchResult, MatchType};
-pub use file_grouper::{FileGrouper, FileGroup};
\ No newline at end of file
+pub use file_scanner::{FileScanner, ScannedExtra, ScannedFile};
+pub use file_matcher::{FileMatcher, MatchResult, GroupMatchResult, MatchType, MatchCandidate};
+pub use file_grouper::{FileGrouper, FileGroup};
+pub use release_calendar::{ReleaseCalendarTask, CalendarRefreshResult};
+pub use torrent_client::TorrentClient;
+pub use wanted_monitor::{WantedMonitorTask, WantedCheckResult};
+pub use download_importer::{DownloadImportTask, DownloadImportResult};
+pub use peer_sync::PeerSyncTask;
+pub use link_checker::LinkCheckTask;
+pub use maintenance::{MaintenanceTask, OrphanCleanupTask};
+pub use library_watcher::{LibraryScanTask, RootHealthTask};
+pub use enrichment::EnrichmentTask;
+pub use actor_dedup::ActorDedupTask;
+pub use tmdb_list_cache::TmdbListPrefetchTask;
+pub use swarm_stats::SwarmStatsTask;
+pub use series_detect::SeriesDetectTask;
+pub use offline_package::OfflinePackageTask;
@@ -0,0 +1,276 @@
+//! 离线下载包
+//!
+//! 把媒体的一个分段在后台转码为适合手机的 MP4（H.264 + AAC 双声道，限制高度，按时长和大小上限计算码率，
+//! faststart 便于边下边播），完成后生成一次性下载链接。码率控制超出上限时 -fs 会截断输出，
+//! 转码后比较输出和源文件的时长，被截断时降低码率重新转码。任务逐个执行，FFmpeg 进程占用全局调度名额。
+//! 下载完整传输后链接失效并删除文件；中断的下载可以用同一链接重试。超过有效期未下载的包定期清理。
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::Notify;
+
+use crate::database::{self, Database, DatabaseRepository};
+use crate::models::{
+    offline_output_truncated, offline_video_bitrate, reduced_offline_bitrate, select_edition_file,
+    CreateOfflinePackageRequest, HwAccel, OfflinePackage, OFFLINE_AUDIO_BITRATE, OFFLINE_PACKAGE_TTL_HOURS,
+};
+use crate::services::enrichment::{parse_ffprobe_output, run_ffprobe};
+use crate::services::{ffmpeg, hwaccel};
+
+/// 输出被截断时最多转码的次数
+const MAX_ENCODE_ATTEMPTS: usize = 3;
+
+/// 有新任务加入队列时唤醒后台任务
+static OFFLINE_QUEUED: Notify = Notify::const_new();
+
+/// 离线包存放目录（CACHE_DIR/offline）
+fn offline_dir() -> PathBuf {
+    let cache_dir = std::env::var("CACHE_DIR").unwrap_or_else(|_| "./cache".to_string());
+    PathBuf::from(cache_dir).join("offline")
+}
+
+/// 创建离线包任务并唤醒后台任务
+pub async fn enqueue(pool: &Pool<Sqlite>, media_id: &str, request: &CreateOfflinePackageRequest) -> Result<OfflinePackage> {
+    let package = database::create_offline_package(pool, media_id, request).await?;
+    OFFLINE_QUEUED.notify_one();
+    Ok(package)
+}
+
+/// 删除离线包文件（不存在时忽略）
+pub async fn remove_file(package: &OfflinePackage) {
+    let Some(path) = package.file_path.as_deref() else {
+        return;
+    };
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => tracing::warn!("Failed to remove offline package {}: {}", path, e),
+    }
+}
+
+/// 下载结束：完整传输时令牌失效并删除文件，否则释放占用以便重试
+pub async fn finish_download(pool: &Pool<Sqlite>, package: &OfflinePackage, complete: bool) {
+    let result = if complete {
+        remove_file(package).await;
+        database::mark_offline_downloaded(pool, &package.id).await
+    } else {
+        database::release_offline_download(pool, &package.id).await
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to update offline package {}: {}", package.id, e);
+    }
+}
+
+/// FFmpeg 参数（选中硬件加速时用硬件解码和编码）
+fn ffmpeg_args(input: &str, output: &Path, package: &OfflinePackage, video_bitrate: i64, accel: Option<HwAccel>) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-loglevel", "error", "-y"].map(String::from).to_vec();
+    if let Some(accel) = accel {
+        args.extend(hwaccel::decode_args(accel));
+    }
+    args.extend(["-i", input, "-map", "0:v:0", "-map", "0:a:0?", "-sn", "-dn"].map(String::from));
+
+    let mut filters = vec![format!("scale=-2:'min({},ih)'", package.max_height)];
+    if accel == Some(HwAccel::Vaapi) {
+        filters.push("format=nv12,hwupload".to_string());
+    } else {
+        filters.push("format=yuv420p".to_string());
+    }
+    args.extend(["-vf".to_string(), filters.join(",")]);
+    match accel {
+        Some(accel) => args.extend(["-c:v".to_string(), accel.h264_encoder().to_string()]),
+        None => args.extend(["-c:v", "libx264", "-preset", "medium", "-profile:v", "high"].map(String::from)),
+    }
+    args.extend([
+        "-b:v".to_string(), video_bitrate.to_string(),
+        "-maxrate".to_string(), video_bitrate.to_string(),
+        "-bufsize".to_string(), (video_bitrate * 2).to_string(),
+        "-c:a".to_string(), "aac".to_string(),
+        "-b:a".to_string(), OFFLINE_AUDIO_BITRATE.to_string(),
+        "-ac".to_string(), "2".to_string(),
+        // 码率控制有误差，用 -fs 保证不超过上限（截断的输出由调用方检测后重新转码）
+        "-fs".to_string(), (package.max_size_mb * 1024 * 1024).to_string(),
+        "-movflags".to_string(), "+faststart".to_string(),
+        "-f".to_string(), "mp4".to_string(),
+    ]);
+    args.push(output.to_string_lossy().into_owned());
+    args
+}
+
+/// 离线包后台任务：逐个转码，队列为空时等待新任务或定期检查并清理过期的包
+pub struct OfflinePackageTask {
+    database: Database,
+    interval: Duration,
+}
+
+impl OfflinePackageTask {
+    pub fn new(database: Database, interval: Duration) -> Self {
+        Self { database, interval }
+    }
+
+    /// 启动离线包任务
+    pub async fn start(self) {
+        let pool = self.database.pool().clone();
+        match database::requeue_running_offline_packages(&pool).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Requeued {} unfinished offline packages", count),
+            Err(e) => tracing::warn!("Failed to requeue offline packages: {}", e),
+        }
+
+        loop {
+            self.cleanup_expired(&pool).await;
+            match self.run_next(&pool).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    let _ = tokio::time::timeout(self.interval, OFFLINE_QUEUED.notified()).await;
+                }
+                Err(e) => {
+                    tracing::warn!("Offline package task failed: {}", e);
+                    tokio::time::sleep(self.interval).await;
+                }
+            }
+        }
+    }
+
+    /// 删除过期未下载的包
+    async fn cleanup_expired(&self, pool: &Pool<Sqlite>) {
+        match database::expire_offline_packages(pool).await {
+            Ok(expired) => {
+                for package in &expired {
+                    remove_file(package).await;
+                }
+                if !expired.is_empty() {
+                    tracing::info!("Removed {} expired offline packages", expired.len());
+                }
+            }
+            Err(e) => tracing::warn!("Failed to expire offline packages: {}", e),
+        }
+    }
+
+    /// 执行下一个任务，返回是否执行了任务
+    async fn run_next(&self, pool: &Pool<Sqlite>) -> Result<bool> {
+        let Some(package) = database::claim_next_offline_package(pool).await? else {
+            return Ok(false);
+        };
+        match self.build(&package).await {
+            Ok((file_path, file_size)) => {
+                let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+                let expires_at = Utc::now() + chrono::Duration::hours(OFFLINE_PACKAGE_TTL_HOURS);
+                if database::complete_offline_package(pool, &package.id, &file_path, file_size, &token, expires_at).await? {
+                    tracing::info!("📦 Offline package ready {} ({} bytes)", package.id, file_size);
+                } else {
+                    // 转码期间被删除
+                    let _ = tokio::fs::remove_file(&file_path).await;
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Offline package {} failed: {}", package.id, e);
+                database::fail_offline_package(pool, &package.id, &e.to_string()).await?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// 转码，返回文件路径和大小
+    async fn build(&self, package: &OfflinePackage) -> Result<(String, i64)> {
+        let files = self.database.repository().get_media_files(&package.media_id).await?;
+        let file = select_edition_file(files, package.edition.as_deref(), package.part as usize)
+            .ok_or_else(|| anyhow!("media file not found"))?;
+        if !Path::new(&file.file_path).is_file() {
+            return Err(anyhow!("only local files can be packaged"));
+        }
+
+        let duration = self.duration(&package.media_id, &file.file_path).await?;
+        let mut video_bitrate = offline_video_bitrate(package.max_size_mb, duration)
+            .ok_or_else(|| anyhow!("{} MB is too small for {:.0} seconds of video", package.max_size_mb, duration))?;
+
+        let dir = offline_dir();
+        tokio::fs::create_dir_all(&dir).await?;
+        let output = dir.join(format!("{}.mp4", package.id));
+        let partial = dir.join(format!("{}.part.mp4", package.id));
+
+        let accel = hwaccel::selected();
+        for attempt in 1..=MAX_ENCODE_ATTEMPTS {
+            let mut result = self.encode(&file.file_path, &partial, package, video_bitrate, accel).await;
+            if result.is_err() && accel.is_some() {
+                tracing::debug!("Hardware encoding failed for offline package {}, retrying in software", package.id);
+                result = self.encode(&file.file_path, &partial, package, video_bitrate, None).await;
+            }
+            let encoded = match result {
+                Ok(()) => self.output_duration(&package.media_id, &partial).await,
+                Err(e) => Err(e),
+            };
+            let encoded = match encoded {
+                Ok(encoded) => encoded,
+                Err(e) => {
+                    let _ = tokio::fs::remove_file(&partial).await;
+                    return Err(e);
+                }
+            };
+            if !offline_output_truncated(duration, encoded) {
+                break;
+            }
+
+            let _ = tokio::fs::remove_file(&partial).await;
+            let reduced = reduced_offline_bitrate(video_bitrate, duration, encoded)
+                .filter(|_| attempt < MAX_ENCODE_ATTEMPTS);
+            let Some(reduced) = reduced else {
+                return Err(anyhow!(
+                    "output was truncated to {:.0} of {:.0} seconds by the {} MB limit",
+                    encoded, duration, package.max_size_mb
+                ));
+            };
+            tracing::info!(
+                "Offline package {} truncated at {:.0}s of {:.0}s, retrying at {} bit/s",
+                package.id, encoded, duration, reduced
+            );
+            video_bitrate = reduced;
+        }
+
+        tokio::fs::rename(&partial, &output).await?;
+        let file_size = tokio::fs::metadata(&output).await?.len() as i64;
+        Ok((output.to_string_lossy().into_owned(), file_size))
+    }
+
+    /// 时长：优先使用补全时探测的结果，没有时现场探测
+    async fn duration(&self, media_id: &str, file_path: &str) -> Result<f64> {
+        let probed = database::list_media_probes(self.database.pool(), media_id).await?
+            .into_iter()
+            .find(|probe| probe.file_path == file_path)
+            .and_then(|probe| probe.duration_secs);
+        if let Some(duration) = probed {
+            return Ok(duration);
+        }
+        let output = run_ffprobe(file_path).await.map_err(|e| anyhow!("ffprobe failed: {}", e))?;
+        parse_ffprobe_output(&output, media_id, file_path)?
+            .duration_secs
+            .ok_or_else(|| anyhow!("unknown duration"))
+    }
+
+    /// 转码输出的实际时长
+    async fn output_duration(&self, media_id: &str, path: &Path) -> Result<f64> {
+        let path = path.to_string_lossy();
+        let output = run_ffprobe(&path).await.map_err(|e| anyhow!("ffprobe failed on output: {}", e))?;
+        parse_ffprobe_output(&output, media_id, &path)?
+            .duration_secs
+            .ok_or_else(|| anyhow!("unknown output duration"))
+    }
+
+    async fn encode(&self, input: &str, output: &Path, package: &OfflinePackage, video_bitrate: i64, accel: Option<HwAccel>) -> Result<()> {
+        let _permit = ffmpeg::acquire().await;
+        let result = ffmpeg::ffmpeg_command(ffmpeg_args(input, output, package, video_bitrate, accel))
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| anyhow!("failed to start ffmpeg: {}", e))?;
+        if !result.status.success() {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            return Err(anyhow!("ffmpeg exited with {}: {}", result.status, stderr.trim()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,539 @@
+//! 多实例双向同步服务
+//!
+//! 与对端交换变更流：先拉取对端 /api/sync/changes 应用到本地，再把本地变更推送到对端 /api/sync/push。
+//! 冲突处理按字段最后写入优先（比较变更的原始时间戳），删除以墓碑同步。
+//! 应用远端变更时在 sync_apply_context 中写入来源和原始时间，触发器据此记录变更，避免回传
+
+use std::collections::HashMap;
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::Sha256;
+use sqlx::{Pool, Row, Sqlite, SqliteConnection};
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+
+use crate::database;
+use crate::models::{ApplyChangesResult, ChangeEntry, ChangeFeedResponse, PeerSyncResult, PushChangesRequest, SyncPeer};
+
+/// 同步令牌请求头（推送变更时必须携带，与对端的 SYNC_PEER_TOKEN 一致）
+pub const SYNC_TOKEN_HEADER: &str = "X-Sync-Token";
+
+/// 校验对端携带的同步令牌（比较两者的 HMAC，耗时与令牌内容无关）
+pub fn verify_sync_token(expected: &str, provided: &str) -> bool {
+    let mac_for = |token: &str| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(expected.as_bytes()).expect("HMAC accepts any key length");
+        mac.update(token.as_bytes());
+        mac
+    };
+    mac_for(provided).verify_slice(&mac_for(expected).finalize().into_bytes()).is_ok()
+}
+
+/// 每次拉取/推送的变更条数
+const SYNC_PAGE_SIZE: i64 = 500;
+
+/// 实体类型对应的表名和主键列
+pub fn entity_table(entity_type: &str) -> Option<(&'static str, &'static [&'static str])> {
+    match entity_type {
+        "media" => Some(("media_items", &["id"])),
+        "collection" => Some(("collections", &["id"])),
+        "actor" => Some(("actors", &["id"])),
+        "actor_media" => Some(("actor_media", &["id"])),
+        "studio" => Some(("studios", &["id"])),
+        "series" => Some(("series", &["id"])),
+        "media_file" => Some(("media_files", &["id"])),
+        "tag" => Some(("tags", &["id"])),
+        "media_tag" => Some(("media_tags", &["media_id", "tag_id"])),
+        _ => None,
+    }
+}
+
+// ============ Conflict Resolution ============
+
+/// 本地某个实体的变更版本（各字段最后修改时间）
+#[derive(Debug, Default)]
+pub struct LocalVersions {
+    pub exists: bool,
+    inserted_at: Option<String>,
+    deleted_at: Option<String>,
+    fields: HashMap<String, String>,
+}
+
+impl LocalVersions {
+    /// 记录一条本地变更
+    pub fn record(&mut self, operation: &str, changed_fields: Option<Vec<String>>, changed_at: &str) {
+        fn bump(slot: &mut Option<String>, at: &str) {
+            if slot.as_deref().is_none_or(|current| current < at) {
+                *slot = Some(at.to_string());
+            }
+        }
+        match operation {
+            "insert" => bump(&mut self.inserted_at, changed_at),
+            "delete" => bump(&mut self.deleted_at, changed_at),
+            _ => {
+                for field in changed_fields.unwrap_or_default() {
+                    let slot = self.fields.entry(field).or_default();
+                    if slot.as_str() < changed_at {
+                        *slot = changed_at.to_string();
+                    }
+                }
+            }
+        }
+    }
+
+    /// 字段的最后修改时间（未单独修改过时取插入时间）
+    fn field_time(&self, field: &str) -> Option<&str> {
+        let updated = self.fields.get(field).map(|s| s.as_str());
+        updated.max(self.inserted_at.as_deref())
+    }
+
+    /// 实体的最后修改时间
+    fn latest(&self) -> Option<&str> {
+        self.fields.values()
+            .map(|s| s.as_str())
+            .chain(self.inserted_at.as_deref())
+            .chain(self.deleted_at.as_deref())
+            .max()
+    }
+}
+
+/// 远端变更的处理方式
+#[derive(Debug, PartialEq)]
+pub enum Resolution {
+    Skip,
+    /// 本地不存在，插入完整数据
+    Insert,
+    /// 只更新比本地新的字段
+    Update(Vec<String>),
+    Delete,
+}
+
+/// 按字段最后写入优先决定如何应用远端变更（时间相同时保留本地）
+pub fn resolve_change(change: &ChangeEntry, local: &LocalVersions) -> Resolution {
+    let remote_at = change.changed_at.as_str();
+
+    if change.operation == "delete" {
+        let local_newer = local.latest().is_some_and(|at| at >= remote_at);
+        return if local.exists && !local_newer { Resolution::Delete } else { Resolution::Skip };
+    }
+
+    let Some(Value::Object(data)) = change.data.as_ref() else {
+        return Resolution::Skip;
+    };
+
+    if !local.exists {
+        // 本地墓碑比远端写入新时保持删除，否则以远端数据重建
+        let deleted_newer = local.deleted_at.as_deref().is_some_and(|at| at >= remote_at);
+        return if deleted_newer { Resolution::Skip } else { Resolution::Insert };
+    }
+
+    let candidates: Vec<String> = match (change.operation.as_str(), change.changed_fields.as_ref()) {
+        ("update", Some(fields)) => fields.clone(),
+        _ => data.keys().cloned().collect(),
+    };
+    let fields: Vec<String> = candidates.into_iter()
+        .filter(|f| data.contains_key(f))
+        .filter(|f| local.field_time(f).is_none_or(|at| at < remote_at))
+        .collect();
+
+    if fields.is_empty() { Resolution::Skip } else { Resolution::Update(fields) }
+}
+
+// ============ Applying Changes ============
+
+fn bind_value<'q>(query: Query<'q, Sqlite, SqliteArguments<'q>>, value: &Value) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        other => query.bind(other.to_string()),
+    }
+}
+
+/// 拆分实体ID为主键值（media_tag 为 media_id:tag_id）
+fn split_entity_id<'a>(entity_id: &'a str, pk: &[&str]) -> Result<Vec<&'a str>> {
+    let values: Vec<&str> = if pk.len() == 1 {
+        vec![entity_id]
+    } else {
+        entity_id.splitn(pk.len(), ':').collect()
+    };
+    if values.len() != pk.len() {
+        return Err(anyhow!("Invalid entity id: {}", entity_id));
+    }
+    Ok(values)
+}
+
+async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>> {
+    let rows = sqlx::query(&format!("PRAGMA table_info({})", table))
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows.iter().map(|r| r.get::<String, _>("name")).collect())
+}
+
+async fn load_local_versions(
+    conn: &mut SqliteConnection,
+    change: &ChangeEntry,
+    table: &str,
+    pk: &[&str],
+) -> Result<LocalVersions> {
+    let mut local = LocalVersions::default();
+
+    let rows = sqlx::query(
+        "SELECT operation, changed_fields, changed_at FROM changes WHERE entity_type = ? AND entity_id = ?"
+    )
+    .bind(&change.entity_type)
+    .bind(&change.entity_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in rows {
+        let fields = row.get::<Option<String>, _>("changed_fields")
+            .and_then(|s| serde_json::from_str(&s).ok());
+        local.record(&row.get::<String, _>("operation"), fields, &row.get::<String, _>("changed_at"));
+    }
+
+    let condition = pk.iter().map(|c| format!("{} = ?", c)).collect::<Vec<_>>().join(" AND ");
+    let sql = format!("SELECT COUNT(*) FROM {} WHERE {}", table, condition);
+    let mut query = sqlx::query_scalar::<_, i64>(&sql);
+    for value in split_entity_id(&change.entity_id, pk)? {
+        query = query.bind(value);
+    }
+    local.exists = query.fetch_one(&mut *conn).await? > 0;
+
+    Ok(local)
+}
+
+/// 应用单条远端变更，返回是否有实际修改
+async fn apply_change(conn: &mut SqliteConnection, origin: &str, change: &ChangeEntry) -> Result<bool> {
+    let (table, pk) = entity_table(&change.entity_type)
+        .ok_or_else(|| anyhow!("Unknown entity type: {}", change.entity_type))?;
+
+    let local = load_local_versions(conn, change, table, pk).await?;
+    let resolution = resolve_change(change, &local);
+    if resolution == Resolution::Skip {
+        return Ok(false);
+    }
+
+    sqlx::query("INSERT INTO sync_apply_context (origin, changed_at) VALUES (?, ?)")
+        .bind(origin)
+        .bind(&change.changed_at)
+        .execute(&mut *conn)
+        .await?;
+
+    let pk_values = split_entity_id(&change.entity_id, pk)?;
+    let condition = pk.iter().map(|c| format!("{} = ?", c)).collect::<Vec<_>>().join(" AND ");
+    let data = change.data.as_ref().and_then(|d| d.as_object());
+    // 只写入本地表中存在的列（对端版本可能不同）
+    let columns = table_columns(conn, table).await?;
+    let known = |field: &String| columns.contains(field) && !pk.contains(&field.as_str());
+
+    match resolution {
+        Resolution::Insert => {
+            let data = data.ok_or_else(|| anyhow!("Missing data"))?;
+            let fields: Vec<&String> = data.keys().filter(|f| columns.contains(f)).collect();
+            let sql = format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                table,
+                fields.iter().map(|f| f.as_str()).collect::<Vec<_>>().join(", "),
+                vec!["?"; fields.len()].join(", ")
+            );
+            let mut query = sqlx::query(&sql);
+            for field in fields {
+                query = bind_value(query, &data[field.as_str()]);
+            }
+            query.execute(&mut *conn).await?;
+        }
+        Resolution::Update(fields) => {
+            let data = data.ok_or_else(|| anyhow!("Missing data"))?;
+            let fields: Vec<String> = fields.into_iter().filter(|f| known(f)).collect();
+            if !fields.is_empty() {
+                let sql = format!(
+                    "UPDATE {} SET {} WHERE {}",
+                    table,
+                    fields.iter().map(|f| format!("{} = ?", f)).collect::<Vec<_>>().join(", "),
+                    condition
+                );
+                let mut query = sqlx::query(&sql);
+                for field in &fields {
+                    query = bind_value(query, &data[field.as_str()]);
+                }
+                for value in &pk_values {
+                    query = query.bind(*value);
+                }
+                query.execute(&mut *conn).await?;
+            }
+        }
+        Resolution::Delete => {
+            let sql = format!("DELETE FROM {} WHERE {}", table, condition);
+            let mut query = sqlx::query(&sql);
+            for value in &pk_values {
+                query = query.bind(*value);
+            }
+            query.execute(&mut *conn).await?;
+        }
+        Resolution::Skip => {}
+    }
+
+    sqlx::query("DELETE FROM sync_apply_context")
+        .execute(&mut *conn)
+        .await?;
+    Ok(true)
+}
+
+/// 应用一批远端变更（每条变更单独事务，失败的变更不影响其他变更）
+pub async fn apply_remote_changes(
+    pool: &Pool<Sqlite>,
+    sender: &str,
+    changes: &[ChangeEntry],
+) -> Result<ApplyChangesResult> {
+    let instance_id = database::get_instance_id(pool).await?;
+    let mut result = ApplyChangesResult::default();
+
+    for change in changes {
+        // 经其他实例转发的变更保留最初来源
+        let origin = change.origin.as_deref().unwrap_or(sender);
+        if origin == instance_id {
+            result.skipped += 1;
+            continue;
+        }
+
+        let mut tx = pool.begin().await?;
+        match apply_change(&mut tx, origin, change).await {
+            Ok(true) => {
+                tx.commit().await?;
+                result.applied += 1;
+            }
+            Ok(false) => result.skipped += 1,
+            Err(e) => {
+                tracing::warn!("应用同步变更失败 {} {}: {}", change.entity_type, change.entity_id, e);
+                result.errors.push(format!("{} {}: {}", change.entity_type, change.entity_id, e));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+// ============ Peer Exchange ============
+
+/// 对端API响应包装
+#[derive(Debug, Deserialize)]
+struct PeerResponse<T> {
+    data: Option<T>,
+    message: Option<String>,
+}
+
+impl<T> PeerResponse<T> {
+    fn into_data(self) -> Result<T> {
+        self.data.ok_or_else(|| anyhow!(self.message.unwrap_or_else(|| "Empty response from peer".to_string())))
+    }
+}
+
+fn with_token(request: reqwest::RequestBuilder, peer: &SyncPeer) -> reqwest::RequestBuilder {
+    match peer.token {
+        Some(ref token) => request.header(SYNC_TOKEN_HEADER, token),
+        None => request,
+    }
+}
+
+/// 与单个对端同步：拉取对端变更并应用，再推送本地变更
+pub async fn sync_with_peer(pool: &Pool<Sqlite>, peer: &SyncPeer) -> Result<PeerSyncResult> {
+    let instance_id = database::get_instance_id(pool).await?;
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()?;
+    let mut result = PeerSyncResult { peer_id: peer.id.clone(), ..Default::default() };
+
+    // 拉取：排除对端从本实例同步过去的变更
+    let mut pulled_seq = peer.last_pulled_seq;
+    let mut remote_instance_id: Option<String>;
+    loop {
+        let response = with_token(client.get(format!("{}/api/sync/changes", peer.base_url)), peer)
+            .query(&[
+                ("since_seq", pulled_seq.to_string()),
+                ("limit", SYNC_PAGE_SIZE.to_string()),
+                ("exclude_origin", instance_id.clone()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+        let feed = response.json::<PeerResponse<ChangeFeedResponse>>().await?.into_data()?;
+
+        if feed.instance_id == instance_id {
+            return Err(anyhow!("Peer {} is this instance", peer.base_url));
+        }
+        remote_instance_id = Some(feed.instance_id.clone());
+        if feed.reset_required {
+            // 对端已清理部分未拉取的变更，只能继续同步剩余部分
+            tracing::warn!("Peer {} pruned changes after seq {}", peer.base_url, pulled_seq);
+            result.errors.push(format!("peer pruned changes after seq {}, a full resync is required", pulled_seq));
+        }
+
+        let applied = apply_remote_changes(pool, &feed.instance_id, &feed.changes).await?;
+        result.pulled += feed.changes.len();
+        result.applied += applied.applied;
+        result.skipped += applied.skipped;
+        result.errors.extend(applied.errors);
+
+        pulled_seq = feed.next_seq;
+        database::update_peer_progress(pool, &peer.id, remote_instance_id.as_deref(), pulled_seq, peer.last_pushed_seq).await?;
+        if !feed.has_more {
+            break;
+        }
+    }
+
+    // 推送：排除来自对端的变更
+    let mut pushed_seq = peer.last_pushed_seq;
+    loop {
+        let records = database::list_changes(pool, pushed_seq, SYNC_PAGE_SIZE, None, remote_instance_id.as_deref()).await?;
+        let Some(last_seq) = records.last().map(|r| r.seq) else {
+            break;
+        };
+
+        let request = PushChangesRequest {
+            origin: instance_id.clone(),
+            changes: records.into_iter().map(ChangeEntry::from).collect(),
+        };
+        let response = with_token(client.post(format!("{}/api/sync/push", peer.base_url)), peer)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?;
+        let applied = response.json::<PeerResponse<ApplyChangesResult>>().await?.into_data()?;
+        result.pushed += request.changes.len();
+        result.errors.extend(applied.errors.into_iter().map(|e| format!("peer: {}", e)));
+
+        pushed_seq = last_seq;
+        database::update_peer_progress(pool, &peer.id, remote_instance_id.as_deref(), pulled_seq, pushed_seq).await?;
+    }
+
+    Ok(result)
+}
+
+/// 同步并记录结果（供手动触发和定时任务使用）
+pub async fn run_peer_sync(pool: &Pool<Sqlite>, peer: &SyncPeer) -> Result<PeerSyncResult> {
+    match sync_with_peer(pool, peer).await {
+        Ok(result) => {
+            database::mark_peer_synced(pool, &peer.id, None).await?;
+            tracing::info!(
+                "🔄 与对端 {} 同步完成: 拉取 {}, 应用 {}, 推送 {}",
+                peer.name, result.pulled, result.applied, result.pushed
+            );
+            Ok(result)
+        }
+        Err(e) => {
+            database::mark_peer_synced(pool, &peer.id, Some(&e.to_string())).await?;
+            Err(e)
+        }
+    }
+}
+
+/// 对端定时同步任务
+pub struct PeerSyncTask {
+    pool: Pool<Sqlite>,
+    interval: Duration,
+}
+
+impl PeerSyncTask {
+    pub fn new(pool: Pool<Sqlite>, interval: Duration) -> Self {
+        Self { pool, interval }
+    }
+
+    /// 启动定期同步任务
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+            let peers = match database::list_peers(&self.pool, true).await {
+                Ok(peers) => peers,
+                Err(e) => {
+                    tracing::warn!("Failed to load sync peers: {}", e);
+                    continue;
+                }
+            };
+            for peer in peers {
+                if let Err(e) = run_peer_sync(&self.pool, &peer).await {
+                    tracing::warn!("Peer sync with {} failed: {}", peer.base_url, e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn change(operation: &str, fields: Option<&[&str]>, at: &str) -> ChangeEntry {
+        ChangeEntry {
+            seq: 1,
+            entity_type: "studio".to_string(),
+            entity_id: "s1".to_string(),
+            operation: operation.to_string(),
+            changed_fields: fields.map(|f| f.iter().map(|s| s.to_string()).collect()),
+            data: (operation != "delete").then(|| json!({"id": "s1", "name": "A", "description": "d"})),
+            changed_at: at.to_string(),
+            origin: None,
+        }
+    }
+
+    fn local(exists: bool) -> LocalVersions {
+        let mut local = LocalVersions { exists, ..Default::default() };
+        local.record("insert", None, "2024-01-01T00:00:00.000Z");
+        local.record("update", Some(vec!["name".to_string()]), "2024-03-01T00:00:00.000Z");
+        local
+    }
+
+    #[test]
+    fn test_resolve_update_per_field() {
+        let remote = change("update", Some(&["name", "description"]), "2024-02-01T00:00:00.000Z");
+        assert_eq!(resolve_change(&remote, &local(true)), Resolution::Update(vec!["description".to_string()]));
+
+        let newer = change("update", Some(&["name"]), "2024-04-01T00:00:00.000Z");
+        assert_eq!(resolve_change(&newer, &local(true)), Resolution::Update(vec!["name".to_string()]));
+
+        let same_time = change("update", Some(&["name"]), "2024-03-01T00:00:00.000Z");
+        assert_eq!(resolve_change(&same_time, &local(true)), Resolution::Skip);
+    }
+
+    #[test]
+    fn test_resolve_missing_entity() {
+        let remote = change("update", Some(&["name"]), "2024-02-01T00:00:00.000Z");
+        assert_eq!(resolve_change(&remote, &LocalVersions::default()), Resolution::Insert);
+
+        let mut deleted = local(false);
+        deleted.record("delete", None, "2024-05-01T00:00:00.000Z");
+        assert_eq!(resolve_change(&remote, &deleted), Resolution::Skip);
+        let later = change("insert", None, "2024-06-01T00:00:00.000Z");
+        assert_eq!(resolve_change(&later, &deleted), Resolution::Insert);
+    }
+
+    #[test]
+    fn test_resolve_tombstone() {
+        assert_eq!(resolve_change(&change("delete", None, "2024-04-01T00:00:00.000Z"), &local(true)), Resolution::Delete);
+        assert_eq!(resolve_change(&change("delete", None, "2024-02-01T00:00:00.000Z"), &local(true)), Resolution::Skip);
+        assert_eq!(resolve_change(&change("delete", None, "2024-04-01T00:00:00.000Z"), &local(false)), Resolution::Skip);
+    }
+
+    #[test]
+    fn test_verify_sync_token() {
+        assert!(verify_sync_token("secret", "secret"));
+        assert!(!verify_sync_token("secret", "secreT"));
+        assert!(!verify_sync_token("secret", "secret-longer"));
+        assert!(!verify_sync_token("secret", ""));
+    }
+
+    #[test]
+    fn test_split_entity_id() {
+        assert_eq!(split_entity_id("m1:t1", &["media_id", "tag_id"]).unwrap(), vec!["m1", "t1"]);
+        assert_eq!(split_entity_id("a:b", &["id"]).unwrap(), vec!["a:b"]);
+        assert!(split_entity_id("m1", &["media_id", "tag_id"]).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+//! 后台任务进度登记表（磁力搜索、媒体刮削、演员刮削、自动刮削共用）
+//!
+//! 每个会话的进度保存在一个 watch 通道中：查询接口读取最新值，SSE/WebSocket 通过
+//! `subscribe` 订阅变化。创建新会话时顺带清理：已结束的会话保留 `ttl` 后移除，
+//! 一直未结束的会话（任务异常退出）保留 `stale_ttl` 后移除，避免长时间运行时无限增长。
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use futures_util::stream::{self, Stream};
+use tokio::sync::watch;
+
+/// 已结束会话的默认保留时间
+pub const FINISHED_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+/// 未结束会话的默认最长保留时间
+pub const STALE_SESSION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 可登记的进度：能判断任务是否已结束
+pub trait SessionProgress {
+    fn is_finished(&self) -> bool;
+}
+
+struct Session<T> {
+    sender: watch::Sender<T>,
+    created_at: Instant,
+    finished_at: Option<Instant>,
+}
+
+/// 按会话ID保存进度，支持订阅和自动清理过期会话
+pub struct ProgressRegistry<T> {
+    sessions: RwLock<HashMap<String, Session<T>>>,
+    ttl: Duration,
+    stale_ttl: Duration,
+}
+
+impl<T: SessionProgress + Clone> Default for ProgressRegistry<T> {
+    fn default() -> Self {
+        Self::with_ttl(FINISHED_SESSION_TTL, STALE_SESSION_TTL)
+    }
+}
+
+impl<T: SessionProgress + Clone> ProgressRegistry<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_ttl(ttl: Duration, stale_ttl: Duration) -> Self {
+        Self {
+            sessions: RwLock::new(HashMap::new()),
+            ttl,
+            stale_ttl,
+        }
+    }
+
+    /// 创建会话，返回会话ID
+    pub fn create(&self, initial: T) -> String {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let now = Instant::now();
+        let finished_at = initial.is_finished().then_some(now);
+        let (sender, _) = watch::channel(initial);
+        if let Ok(mut sessions) = self.sessions.write() {
+            self.evict_expired(&mut sessions, now);
+            sessions.insert(session_id.clone(), Session { sender, created_at: now, finished_at });
+        }
+        session_id
+    }
+
+    /// 更新进度并通知订阅者，会话不存在时返回 false
+    pub fn update(&self, session_id: &str, f: impl FnOnce(&mut T)) -> bool {
+        let Ok(mut sessions) = self.sessions.write() else {
+            return false;
+        };
+        let Some(session) = sessions.get_mut(session_id) else {
+            return false;
+        };
+        session.sender.send_modify(f);
+        if session.finished_at.is_none() && session.sender.borrow().is_finished() {
+            session.finished_at = Some(Instant::now());
+        }
+        true
+    }
+
+    /// 获取当前进度
+    pub fn get(&self, session_id: &str) -> Option<T> {
+        let sessions = self.sessions.read().ok()?;
+        sessions.get(session_id).map(|session| session.sender.borrow().clone())
+    }
+
+    /// 订阅进度变化
+    pub fn subscribe(&self, session_id: &str) -> Option<watch::Receiver<T>> {
+        let sessions = self.sessions.read().ok()?;
+        sessions.get(session_id).map(|session| session.sender.subscribe())
+    }
+
+    /// 移除会话，返回会话是否存在
+    pub fn remove(&self, session_id: &str) -> bool {
+        self.sessions.write().is_ok_and(|mut sessions| sessions.remove(session_id).is_some())
+    }
+
+    fn evict_expired(&self, sessions: &mut HashMap<String, Session<T>>, now: Instant) {
+        sessions.retain(|_, session| match session.finished_at {
+            Some(finished_at) => now.duration_since(finished_at) < self.ttl,
+            None => now.duration_since(session.created_at) < self.stale_ttl,
+        });
+    }
+}
+
+/// 把订阅转换为进度流：先发出当前进度，之后每次变化发出一次，任务结束或会话被移除后结束
+pub fn progress_stream<T>(receiver: watch::Receiver<T>) -> impl Stream<Item = T>
+where
+    T: SessionProgress + Clone + Send + Sync + 'static,
+{
+    stream::unfold((receiver, true, false), |(mut receiver, first, done)| async move {
+        if done {
+            return None;
+        }
+        if !first && receiver.changed().await.is_err() {
+            return None;
+        }
+        let progress = receiver.borrow_and_update().clone();
+        let finished = progress.is_finished();
+        Some((progress, (receiver, false, finished)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[derive(Clone)]
+    struct Job(bool);
+
+    impl SessionProgress for Job {
+        fn is_finished(&self) -> bool {
+            self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_finished_sessions_expire() {
+        let registry = ProgressRegistry::with_ttl(Duration::from_millis(20), Duration::from_secs(60));
+        let done = registry.create(Job(false));
+        let running = registry.create(Job(false));
+        assert!(registry.update(&done, |job| job.0 = true));
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        registry.create(Job(false));
+        assert!(registry.get(&done).is_none());
+        assert!(registry.get(&running).is_some());
+        assert!(!registry.update(&done, |job| job.0 = false));
+    }
+
+    #[tokio::test]
+    async fn test_stale_sessions_expire() {
+        let registry = ProgressRegistry::with_ttl(Duration::from_secs(60), Duration::from_millis(20));
+        let stuck = registry.create(Job(false));
+
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        registry.create(Job(false));
+        assert!(registry.get(&stuck).is_none());
+        assert!(!registry.remove(&stuck));
+    }
+
+    #[tokio::test]
+    async fn test_progress_stream_ends_when_finished() {
+        let registry = ProgressRegistry::new();
+        let session_id = registry.create(Job(false));
+        let stream = progress_stream(registry.subscribe(&session_id).unwrap());
+        registry.update(&session_id, |job| job.0 = true);
+
+        let updates: Vec<bool> = stream.map(|job| job.0).collect().await;
+        assert_eq!(updates.last(), Some(&true));
+    }
+}
@@ -0,0 +1,166 @@
+//! 质量识别与升级判断
+//!
+//! 从文件名/磁力标题中识别分辨率和编码，并根据质量配置判断是否可升级
+
+use regex::Regex;
+
+use crate::models::{QualityProfile, ReleaseQuality};
+
+lazy_static::lazy_static! {
+    static ref RESOLUTION_REGEX: Regex =
+        Regex::new(r"(?i)(?:^|[^a-z0-9])(2160|1440|1080|720|576|480)[pi](?:$|[^a-z0-9])").unwrap();
+    static ref UHD_REGEX: Regex =
+        Regex::new(r"(?i)(?:^|[^a-z0-9])(4k|uhd)(?:$|[^a-z0-9])").unwrap();
+    static ref CODEC_REGEX: Regex =
+        Regex::new(r"(?i)(?:^|[^a-z0-9])(x\.?265|h\.?265|hevc|x\.?264|h\.?264|avc|av1)(?:$|[^a-z0-9])").unwrap();
+}
+
+/// 识别文本中的质量信息
+pub fn detect_quality(text: &str) -> ReleaseQuality {
+    let resolution = RESOLUTION_REGEX
+        .captures_iter(text)
+        .filter_map(|c| c[1].parse::<u32>().ok())
+        .max()
+        .or_else(|| UHD_REGEX.is_match(text).then_some(2160));
+
+    let codec = CODEC_REGEX.captures(text).map(|c| normalize_codec(&c[1]));
+
+    ReleaseQuality { resolution, codec }
+}
+
+/// 统一编码名称（x265 / H.265 / HEVC 都记为 hevc，x264 / H.264 / AVC 都记为 h264）
+pub fn normalize_codec(codec: &str) -> String {
+    let raw = codec.trim().to_lowercase().replace('.', "");
+    match raw.as_str() {
+        "x265" | "h265" | "hevc" => "hevc".to_string(),
+        "x264" | "h264" | "avc" => "h264".to_string(),
+        _ => raw,
+    }
+}
+
+/// 多个文件中取最高质量（用于多分段媒体）
+pub fn best_quality_of<'a>(texts: impl IntoIterator<Item = &'a str>, profile: &QualityProfile) -> ReleaseQuality {
+    texts.into_iter()
+        .map(detect_quality)
+        .max_by_key(|q| quality_score(q, profile))
+        .unwrap_or_default()
+}
+
+/// 质量评分：分辨率为主，期望编码加分
+pub fn quality_score(quality: &ReleaseQuality, profile: &QualityProfile) -> u32 {
+    let resolution_score = quality.resolution.unwrap_or(0) * 10;
+    let codec_bonus = match quality.codec {
+        Some(ref codec) if is_preferred_codec(codec, profile) => 5,
+        _ => 0,
+    };
+    resolution_score + codec_bonus
+}
+
+/// 配置中的编码按同样的规则统一后再比较（配置写 x265 时也能匹配 hevc）
+fn is_preferred_codec(codec: &str, profile: &QualityProfile) -> bool {
+    let codec = normalize_codec(codec);
+    profile.preferred_codecs.iter().any(|c| normalize_codec(c) == codec)
+}
+
+/// 是否满足最低分辨率（无法识别分辨率时视为满足）
+pub fn meets_minimum(quality: &ReleaseQuality, profile: &QualityProfile) -> bool {
+    match (profile.min_resolution, quality.resolution) {
+        (Some(min), Some(resolution)) => resolution >= min,
+        _ => true,
+    }
+}
+
+/// 是否已达到期望质量（达到后不再需要升级）
+pub fn meets_cutoff(quality: &ReleaseQuality, profile: &QualityProfile) -> bool {
+    let Some(preferred) = profile.preferred_resolution else {
+        return true;
+    };
+    let resolution_ok = quality.resolution.is_some_and(|r| r >= preferred);
+    let codec_ok = profile.preferred_codecs.is_empty()
+        || quality.codec.as_deref().is_some_and(|c| is_preferred_codec(c, profile));
+    resolution_ok && codec_ok
+}
+
+/// 候选资源是否是当前文件的升级
+pub fn is_upgrade(current: &ReleaseQuality, candidate: &ReleaseQuality, profile: &QualityProfile) -> bool {
+    !meets_cutoff(current, profile)
+        && meets_minimum(candidate, profile)
+        && quality_score(candidate, profile) > quality_score(current, profile)
+}
+
+/// 选择默认质量配置（无配置时返回空配置，不做任何限制）
+pub fn default_profile(profiles: &[QualityProfile]) -> QualityProfile {
+    profiles.iter()
+        .find(|p| p.is_default)
+        .or_else(|| profiles.first())
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> QualityProfile {
+        QualityProfile {
+            name: "4K".to_string(),
+            is_default: true,
+            min_resolution: Some(1080),
+            preferred_resolution: Some(2160),
+            preferred_codecs: vec!["hevc".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_detect_quality() {
+        assert_eq!(
+            detect_quality("Movie.2019.2160p.WEB-DL.x265.mkv"),
+            ReleaseQuality { resolution: Some(2160), codec: Some("hevc".to_string()) }
+        );
+        assert_eq!(detect_quality("ABC-123_4K.mp4").resolution, Some(2160));
+        assert_eq!(detect_quality("ABC-123 [1080p] H.264").codec, Some("h264".to_string()));
+        assert_eq!(detect_quality("ABC-123.mp4"), ReleaseQuality::default());
+    }
+
+    #[test]
+    fn test_quality_label() {
+        assert_eq!(detect_quality("a.1080p.HEVC").label(), "1080p HEVC");
+        assert_eq!(ReleaseQuality::default().label(), "unknown");
+    }
+
+    #[test]
+    fn test_meets_minimum_and_cutoff() {
+        let p = profile();
+        assert!(!meets_minimum(&detect_quality("a.720p"), &p));
+        assert!(meets_minimum(&detect_quality("a.mp4"), &p));
+        assert!(meets_cutoff(&detect_quality("a.2160p.x265"), &p));
+        assert!(!meets_cutoff(&detect_quality("a.2160p.x264"), &p));
+        assert!(meets_cutoff(&detect_quality("a.720p"), &QualityProfile::default()));
+
+        // 配置中的编码写法与识别结果不同
+        let aliased = QualityProfile { preferred_codecs: vec!["x265".to_string(), "H.264".to_string()], ..profile() };
+        assert!(meets_cutoff(&detect_quality("a.2160p.HEVC"), &aliased));
+        assert!(meets_cutoff(&detect_quality("a.2160p.avc"), &aliased));
+        assert!(!meets_cutoff(&detect_quality("a.2160p.av1"), &aliased));
+    }
+
+    #[test]
+    fn test_is_upgrade() {
+        let p = profile();
+        let current = detect_quality("a.1080p.x264");
+        assert!(is_upgrade(&current, &detect_quality("a.2160p.x264"), &p));
+        assert!(is_upgrade(&current, &detect_quality("a.1080p.x265"), &p));
+        assert!(!is_upgrade(&current, &detect_quality("a.720p.x265"), &p));
+        assert!(!is_upgrade(&detect_quality("a.2160p.hevc"), &detect_quality("a.2160p.av1"), &p));
+    }
+
+    #[test]
+    fn test_default_profile() {
+        let mut second = profile();
+        second.name = "second".to_string();
+        let mut first = profile();
+        first.is_default = false;
+        assert_eq!(default_profile(&[first, second]).name, "second");
+        assert_eq!(default_profile(&[]), QualityProfile::default());
+    }
+}
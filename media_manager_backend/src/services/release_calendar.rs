@@ -0,0 +1,229 @@
+//! 发售日历服务
+//!
+//! 定时向刮削插件查询已关注系列/厂商/演员的即将发售作品，写入 release_calendar 表
+
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use chrono::{NaiveDate, Utc};
+use sqlx::{Pool, Sqlite};
+use tokio::sync::RwLock;
+
+use crate::database;
+use crate::models::ReleaseCalendarEntry;
+use crate::plugins::manager::PluginManager;
+use crate::plugins::protocol::CAPABILITY_UPCOMING;
+
+/// 刷新结果
+#[derive(Debug, Default, serde::Serialize)]
+pub struct CalendarRefreshResult {
+    pub follows_checked: usize,
+    pub entries_saved: usize,
+    pub errors: Vec<String>,
+}
+
+/// 刷新所有关注的发售日历
+pub async fn refresh_release_calendar(
+    pool: &Pool<Sqlite>,
+    plugin_manager: &Arc<RwLock<PluginManager>>,
+) -> Result<CalendarRefreshResult> {
+    let follows = database::list_follows(pool).await?;
+    let today = Utc::now().format("%Y-%m-%d").to_string();
+    let mut result = CalendarRefreshResult::default();
+    // 未指定插件的关注使用声明了 upcoming 能力的插件
+    let default_plugin = plugin_manager.read().await.plugin_with_capability(CAPABILITY_UPCOMING);
+
+    for follow in follows {
+        let Some(plugin_id) = follow.plugin_id.clone().or_else(|| default_plugin.clone()) else {
+            result.errors.push(format!("{} '{}': No plugin supports upcoming releases", follow.target_type, follow.target_name));
+            continue;
+        };
+
+        let releases = {
+            let manager = plugin_manager.read().await;
+            manager.fetch_upcoming_releases(&plugin_id, &follow.target_type, &follow.target_name).await
+        };
+
+        let releases = match releases {
+            Ok(r) => r,
+            Err(e) => {
+                tracing::warn!("获取即将发售作品失败 {} '{}': {}", follow.target_type, follow.target_name, e);
+                result.errors.push(format!("{} '{}': {}", follow.target_type, follow.target_name, e));
+                continue;
+            }
+        };
+
+        for release in releases {
+            // 只保留有效且未过期的发售日
+            let release_date = match release.release_date.as_deref().and_then(normalize_date) {
+                Some(d) if d >= today => d,
+                _ => continue,
+            };
+
+            let entry = ReleaseCalendarEntry {
+                id: uuid::Uuid::new_v4().to_string(),
+                follow_id: follow.id.clone(),
+                code: release.code.clone(),
+                title: release.title.clone(),
+                release_date,
+                poster_url: release.poster_url.clone(),
+                studio: release.studio.clone(),
+                series: release.series.clone(),
+                actors: if release.actors.is_empty() {
+                    None
+                } else {
+                    serde_json::to_string(&release.actors).ok()
+                },
+                plugin_id: plugin_id.clone(),
+                media_id: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            };
+
+            match database::upsert_release_entry(pool, &entry).await {
+                Ok(_) => result.entries_saved += 1,
+                Err(e) => result.errors.push(format!("{}: {}", entry.title, e)),
+            }
+        }
+
+        if let Err(e) = database::mark_follow_checked(pool, &follow.id).await {
+            tracing::warn!("更新关注检查时间失败 {} '{}': {}", follow.target_type, follow.target_name, e);
+            result.errors.push(format!("{} '{}': {}", follow.target_type, follow.target_name, e));
+        }
+        result.follows_checked += 1;
+    }
+
+    Ok(result)
+}
+
+/// 规范化日期为 YYYY-MM-DD（支持 YYYY-MM-DD 和 YYYY/MM/DD）
+pub fn normalize_date(date: &str) -> Option<String> {
+    let date = date.trim().replace('/', "-");
+    NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+        .ok()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+/// 将日历条目导出为 iCalendar (RFC 5545) 格式
+pub fn to_ical(entries: &[ReleaseCalendarEntry]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Media Manager//Release Calendar//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    for entry in entries {
+        let date = match NaiveDate::parse_from_str(&entry.release_date, "%Y-%m-%d") {
+            Ok(d) => d,
+            Err(_) => continue,
+        };
+        let summary = match &entry.code {
+            Some(code) if !code.is_empty() => format!("{} {}", code, entry.title),
+            _ => entry.title.clone(),
+        };
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@media-manager", entry.id));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")));
+        lines.push(format!("DTEND;VALUE=DATE:{}", date.succ_opt().unwrap_or(date).format("%Y%m%d")));
+        lines.push(format!("SUMMARY:{}", escape_ical_text(&summary)));
+        let mut description = Vec::new();
+        if let Some(studio) = &entry.studio {
+            description.push(format!("Studio: {}", studio));
+        }
+        if let Some(series) = &entry.series {
+            description.push(format!("Series: {}", series));
+        }
+        if !description.is_empty() {
+            lines.push(format!("DESCRIPTION:{}", escape_ical_text(&description.join("\n"))));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.join("\r\n") + "\r\n"
+}
+
+/// 转义 iCalendar 文本字段
+fn escape_ical_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// 发售日历定时刷新任务
+pub struct ReleaseCalendarTask {
+    pool: Pool<Sqlite>,
+    plugin_manager: Arc<RwLock<PluginManager>>,
+    interval: Duration,
+}
+
+impl ReleaseCalendarTask {
+    pub fn new(pool: Pool<Sqlite>, plugin_manager: Arc<RwLock<PluginManager>>, interval: Duration) -> Self {
+        Self { pool, plugin_manager, interval }
+    }
+
+    /// 启动定期刷新任务
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+            match refresh_release_calendar(&self.pool, &self.plugin_manager).await {
+                Ok(result) => tracing::debug!("Release calendar refreshed: {:?}", result),
+                Err(e) => tracing::warn!("Release calendar refresh failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(code: Option<&str>, title: &str, date: &str) -> ReleaseCalendarEntry {
+        ReleaseCalendarEntry {
+            id: "test-id".to_string(),
+            follow_id: "follow-id".to_string(),
+            code: code.map(|c| c.to_string()),
+            title: title.to_string(),
+            release_date: date.to_string(),
+            poster_url: None,
+            studio: Some("Studio, Inc".to_string()),
+            series: None,
+            actors: None,
+            plugin_id: "media_scraper".to_string(),
+            media_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_date() {
+        assert_eq!(normalize_date("2024-03-05"), Some("2024-03-05".to_string()));
+        assert_eq!(normalize_date("2024/3/5"), Some("2024-03-05".to_string()));
+        assert_eq!(normalize_date("soon"), None);
+    }
+
+    #[test]
+    fn test_to_ical() {
+        let ical = to_ical(&[entry(Some("ABC-123"), "Title; Part 1", "2024-12-31")]);
+        assert!(ical.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ical.ends_with("END:VCALENDAR\r\n"));
+        assert!(ical.contains("DTSTART;VALUE=DATE:20241231\r\n"));
+        assert!(ical.contains("DTEND;VALUE=DATE:20250101\r\n"));
+        assert!(ical.contains("SUMMARY:ABC-123 Title\\; Part 1\r\n"));
+        assert!(ical.contains("DESCRIPTION:Studio: Studio\\, Inc\r\n"));
+    }
+
+    #[test]
+    fn test_to_ical_skips_invalid_date() {
+        let ical = to_ical(&[entry(None, "Broken", "TBA")]);
+        assert!(!ical.contains("BEGIN:VEVENT"));
+    }
+}
@@ -0,0 +1,99 @@
+//! 刮削来源存档：压缩保存插件返回的原始数据
+//!
+//! 插件可以在结果（或其 data 字段）中提供 `raw_html` 字符串保存原始网页，否则保存插件返回的 JSON。
+
+use std::io::{Read, Write};
+
+use anyhow::Result;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde_json::Value;
+use sqlx::{Pool, Sqlite};
+
+use crate::database::{self, NewScrapeSource};
+
+pub const CONTENT_TYPE_JSON: &str = "application/json";
+pub const CONTENT_TYPE_HTML: &str = "text/html";
+
+pub fn compress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    GzDecoder::new(data).read_to_end(&mut out)?;
+    Ok(out)
+}
+
+fn lookup_str<'a>(payload: &'a Value, key: &str) -> Option<&'a str> {
+    payload.get(key)
+        .or_else(|| payload.get("data").and_then(|d| d.get(key)))
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+}
+
+/// 提取要存档的原始内容和类型
+fn raw_content(payload: &Value) -> Result<(Vec<u8>, &'static str)> {
+    match lookup_str(payload, "raw_html") {
+        Some(html) => Ok((html.as_bytes().to_vec(), CONTENT_TYPE_HTML)),
+        None => Ok((serde_json::to_vec(payload)?, CONTENT_TYPE_JSON)),
+    }
+}
+
+/// 按存档设置保存刮削原始数据，未启用或超过单条大小上限时返回 None
+pub async fn archive_scrape_payload(
+    pool: &Pool<Sqlite>,
+    media_id: &str,
+    query: Option<&str>,
+    payload: &Value,
+) -> Result<Option<i64>> {
+    let settings = database::get_scrape_archive_settings(pool).await?;
+    if !settings.enabled {
+        return Ok(None);
+    }
+
+    let (content, content_type) = raw_content(payload)?;
+    if content.len() as u64 > settings.max_payload_kb * 1024 {
+        tracing::warn!(
+            "刮削原始数据过大，跳过存档: media_id={}, size={} bytes, limit={} KB",
+            media_id, content.len(), settings.max_payload_kb
+        );
+        return Ok(None);
+    }
+
+    let compressed = compress(&content)?;
+    let id = database::insert_scrape_source(pool, &NewScrapeSource {
+        media_id,
+        source: lookup_str(payload, "source"),
+        query,
+        source_url: lookup_str(payload, "source_url").or_else(|| lookup_str(payload, "url")),
+        content_type,
+        payload: &compressed,
+        original_size: content.len(),
+    }).await?;
+
+    let pruned = database::prune_scrape_sources(pool, (settings.max_total_mb * 1024 * 1024) as i64).await?;
+    if pruned > 0 {
+        tracing::info!("刮削来源存档超过总大小上限，已删除 {} 条最早的存档", pruned);
+    }
+    Ok(Some(id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compress_roundtrip_and_raw_content() {
+        let data = "<html>".repeat(1000);
+        let compressed = compress(data.as_bytes()).unwrap();
+        assert!(compressed.len() < data.len());
+        assert_eq!(decompress(&compressed).unwrap(), data.as_bytes());
+
+        let json = serde_json::json!({"success": true, "data": {"title": "A", "raw_html": "<p>A</p>"}});
+        assert_eq!(raw_content(&json).unwrap(), (b"<p>A</p>".to_vec(), CONTENT_TYPE_HTML));
+        let json = serde_json::json!({"media_id": "m1", "data": {"title": "A"}});
+        assert_eq!(raw_content(&json).unwrap().1, CONTENT_TYPE_JSON);
+    }
+}
@@ -0,0 +1,156 @@
+//! 搜索关键词规范化：为一次搜索生成多个候选关键词，分别搜索后合并结果
+//!
+//! - 全角字母、数字和符号转半角，全角空格转空格，合并多余空白
+//! - 番号格式互相生成：abc123 / ABC 123 / ABC_123 → ABC-123、ABC123
+//! - 繁体转简体、简体转繁体（常用字对照表，不做词语级转换）
+
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::plugins::manager::PluginManager;
+use crate::plugins::protocol::{ScrapeResult, SearchResponse};
+
+/// 每次搜索最多使用的候选关键词数（含原关键词）
+pub const MAX_QUERY_VARIANTS: usize = 4;
+
+/// 常用繁体字，与 SIMPLIFIED 逐字对应
+const TRADITIONAL: &str = concat!(
+    "這個們來時說對會國後過發當經從點樣電話動開關體長實現問題種區頭媽愛傳戀戰鬥劇場線",
+    "網頁語讀寫書筆畫風雲飛機車東門馬鳥魚龍聲聽見視覺親殺誰為與萬無歲歷號隊陽陰壞華葉",
+    "黃綠紅藍夢學習聖戲紀錄綜藝節彈邊選擇醫藥師禮權歡樂園遊蘭蘇溫滿漢灣廣廳壓擊寶貝責",
+    "貴買賣錢銀鐵鋼鏡陣隨險雙難離雞韓顏願類顯飯館驗驚麗齊誘戶罰辦嬌癡纏濕熱獸員護襲絲",
+    "襪腳級緊縛調義務處鄰寢濃兒孫極樓記讓還給麼沒嗎裡傷氣燈認識計夠變舊將專業產報導單",
+    "獨總統際訊證據況則歸輕鬆興驅",
+);
+
+/// 常用简体字，与 TRADITIONAL 逐字对应
+const SIMPLIFIED: &str = concat!(
+    "这个们来时说对会国后过发当经从点样电话动开关体长实现问题种区头妈爱传恋战斗剧场线",
+    "网页语读写书笔画风云飞机车东门马鸟鱼龙声听见视觉亲杀谁为与万无岁历号队阳阴坏华叶",
+    "黄绿红蓝梦学习圣戏纪录综艺节弹边选择医药师礼权欢乐园游兰苏温满汉湾广厅压击宝贝责",
+    "贵买卖钱银铁钢镜阵随险双难离鸡韩颜愿类显饭馆验惊丽齐诱户罚办娇痴缠湿热兽员护袭丝",
+    "袜脚级紧缚调义务处邻寝浓儿孙极楼记让还给么没吗里伤气灯认识计够变旧将专业产报导单",
+    "独总统际讯证据况则归轻松兴驱",
+);
+
+lazy_static::lazy_static! {
+    /// 完整的番号（字母 + 可选分隔符 + 数字）
+    static ref CODE_PATTERN: regex::Regex = regex::Regex::new(r"^([A-Za-z]{2,8})[-_ ]?(\d{2,8})$").unwrap();
+}
+
+/// 全角字符转半角，全角空格转空格
+pub fn to_halfwidth(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+fn convert(text: &str, from: &str, to: &str) -> String {
+    text.chars()
+        .map(|c| from.chars().position(|f| f == c).and_then(|i| to.chars().nth(i)).unwrap_or(c))
+        .collect()
+}
+
+/// 繁体转简体（仅对照表中的常用字）
+pub fn to_simplified(text: &str) -> String {
+    convert(text, TRADITIONAL, SIMPLIFIED)
+}
+
+/// 简体转繁体（仅对照表中的常用字）
+pub fn to_traditional(text: &str) -> String {
+    convert(text, SIMPLIFIED, TRADITIONAL)
+}
+
+/// 整个关键词是番号时返回带连字符和不带连字符两种写法（大写）
+pub fn code_variants(query: &str) -> Vec<String> {
+    CODE_PATTERN.captures(query.trim())
+        .map(|caps| {
+            let (prefix, number) = (caps[1].to_uppercase(), &caps[2]);
+            vec![format!("{}-{}", prefix, number), format!("{}{}", prefix, number)]
+        })
+        .unwrap_or_default()
+}
+
+/// 生成候选关键词：第一个为规范化后的原关键词，其余按番号格式、简体、繁体的顺序，忽略大小写去重
+pub fn query_variants(query: &str) -> Vec<String> {
+    let normalized = to_halfwidth(query).split_whitespace().collect::<Vec<_>>().join(" ");
+    if normalized.is_empty() {
+        return vec![query.trim().to_string()];
+    }
+
+    let mut candidates = vec![normalized.clone()];
+    candidates.extend(code_variants(&normalized));
+    candidates.push(to_simplified(&normalized));
+    candidates.push(to_traditional(&normalized));
+
+    let mut seen = HashSet::new();
+    candidates.into_iter()
+        .filter(|candidate| seen.insert(candidate.to_lowercase()))
+        .take(MAX_QUERY_VARIANTS)
+        .collect()
+}
+
+/// 搜索结果去重键：优先番号，否则标题加年份
+fn result_key(result: &ScrapeResult) -> String {
+    match result.code.as_deref().filter(|code| !code.trim().is_empty()) {
+        Some(code) => code.to_uppercase().replace(['-', '_', ' '], ""),
+        None => format!("{}|{}", result.title.trim().to_lowercase(), result.year.unwrap_or_default()),
+    }
+}
+
+/// 使用插件搜索：第一页依次搜索所有候选关键词并合并去重（分页信息取第一个成功的关键词），
+/// 其他页只搜索规范化后的原关键词。所有关键词都失败时返回第一个错误
+pub async fn search_plugin(manager: &PluginManager, plugin_id: &str, query: &str, page: Option<u32>) -> Result<SearchResponse> {
+    let mut variants = query_variants(query);
+    if page.is_some_and(|p| p > 1) {
+        variants.truncate(1);
+    }
+
+    let mut merged: Option<SearchResponse> = None;
+    let mut seen = HashSet::new();
+    let mut first_error = None;
+    for variant in &variants {
+        match manager.search_with_plugin(plugin_id, variant, page).await {
+            Ok(response) => {
+                let results: Vec<ScrapeResult> = response.results.into_iter()
+                    .filter(|result| seen.insert(result_key(result)))
+                    .collect();
+                match merged.as_mut() {
+                    Some(merged) => merged.results.extend(results),
+                    None => merged = Some(SearchResponse { results, ..response }),
+                }
+            }
+            Err(e) => {
+                tracing::warn!("插件 {} 搜索 '{}' 失败: {}", plugin_id, variant, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    merged.ok_or_else(|| first_error.unwrap_or_else(|| anyhow::anyhow!("No search query")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_is_aligned() {
+        assert_eq!(TRADITIONAL.chars().count(), SIMPLIFIED.chars().count());
+    }
+
+    #[test]
+    fn test_query_variants() {
+        assert_eq!(to_halfwidth("ＡＢＣ－１２３　ｘ"), "ABC-123 x");
+        assert_eq!(query_variants("ｍｉｄｅ　０５０"), vec!["mide 050", "MIDE-050", "MIDE050"]);
+        assert_eq!(query_variants("ABC-123"), vec!["ABC-123", "ABC123"]);
+        assert_eq!(query_variants("戀愛  電影"), vec!["戀愛 電影", "恋爱 电影"]);
+        assert_eq!(query_variants("恋爱"), vec!["恋爱", "戀愛"]);
+        assert_eq!(query_variants("Spider Man 2019"), vec!["Spider Man 2019"]);
+        assert_eq!(query_variants("  "), vec![""]);
+    }
+}
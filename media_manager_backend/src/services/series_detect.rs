@@ -0,0 +1,256 @@
+//! 系列自动检测服务
+//!
+//! 为未设置系列的媒体推断系列：
+//! - 标题中包含已知系列名（系列表或其他媒体中出现过的系列，厂商需一致），取最长的匹配
+//! - 同番号前缀的已有媒体（由刮削器写入系列）全部属于同一个系列
+
+use std::collections::HashMap;
+use std::time::Duration;
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::RwLock;
+
+use crate::database;
+use crate::models::{
+    KnownSeries, SeriesApplyResult, SeriesAssignment, SeriesDetectCandidate, SeriesSuggestion,
+    SeriesSuggestionReport, SERIES_MATCH_CODE_PREFIX, SERIES_MATCH_TITLE,
+};
+
+/// 参与标题匹配的最短系列名（字符数），太短的名字容易误匹配
+const MIN_SERIES_NAME_CHARS: usize = 3;
+
+/// 推断番号前缀对应的系列至少需要的已有媒体数
+const MIN_PREFIX_SAMPLES: usize = 2;
+
+lazy_static::lazy_static! {
+    /// 最近一次生成的报告
+    static ref LATEST_REPORT: RwLock<Option<SeriesSuggestionReport>> = RwLock::new(None);
+
+    /// 番号前缀（字母部分）
+    static ref CODE_PREFIX: regex::Regex = regex::Regex::new(r"^([A-Za-z]{2,8})[-_ ]?\d").unwrap();
+}
+
+fn code_prefix(code: &str) -> Option<String> {
+    CODE_PREFIX.captures(code.trim()).map(|caps| caps[1].to_uppercase())
+}
+
+/// 标题中是否包含系列名：忽略大小写，英文数字的名字要求前后不是字母或数字
+fn title_contains(title: &str, name: &str) -> bool {
+    let title = title.to_lowercase();
+    let name = name.to_lowercase();
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+    title.match_indices(&name).any(|(start, matched)| {
+        let before = title[..start].chars().next_back();
+        let after = title[start + matched.len()..].chars().next();
+        let joined_before = is_word(before) && is_word(name.chars().next());
+        let joined_after = is_word(after) && is_word(name.chars().next_back());
+        !joined_before && !joined_after
+    })
+}
+
+/// 厂商是否一致（任一方未知时视为一致）
+fn same_studio(a: Option<&str>, b: Option<&str>) -> bool {
+    match (a.filter(|s| !s.is_empty()), b.filter(|s| !s.is_empty())) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => true,
+    }
+}
+
+/// 标题中包含的最长已知系列名，长度相同的不同系列视为有歧义
+fn match_title(candidate: &SeriesDetectCandidate, known: &[KnownSeries]) -> Option<String> {
+    let mut matches: Vec<&str> = known.iter()
+        .filter(|series| series.name.chars().count() >= MIN_SERIES_NAME_CHARS)
+        .filter(|series| same_studio(series.studio.as_deref(), candidate.studio.as_deref()))
+        .filter(|series| title_contains(&candidate.title, &series.name))
+        .map(|series| series.name.as_str())
+        .collect();
+    let longest = matches.iter().map(|name| name.chars().count()).max()?;
+    matches.retain(|name| name.chars().count() == longest);
+    matches.sort_by_key(|name| name.to_lowercase());
+    matches.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    match matches.as_slice() {
+        [name] => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// 番号前缀 → 系列：该前缀的已有媒体至少 MIN_PREFIX_SAMPLES 个且全部属于同一系列
+fn prefix_series(coded: &[(String, String)]) -> HashMap<String, String> {
+    let mut grouped: HashMap<String, Vec<&str>> = HashMap::new();
+    for (code, series) in coded {
+        if let Some(prefix) = code_prefix(code) {
+            grouped.entry(prefix).or_default().push(series);
+        }
+    }
+    grouped.into_iter()
+        .filter(|(_, series)| series.len() >= MIN_PREFIX_SAMPLES)
+        .filter(|(_, series)| series.iter().all(|s| s.eq_ignore_ascii_case(series[0])))
+        .map(|(prefix, series)| (prefix, series[0].to_string()))
+        .collect()
+}
+
+/// 为候选媒体生成系列建议（标题匹配优先）
+pub fn detect_series(
+    candidates: &[SeriesDetectCandidate],
+    known: &[KnownSeries],
+    coded: &[(String, String)],
+) -> Vec<SeriesSuggestion> {
+    let by_prefix = prefix_series(coded);
+    candidates.iter()
+        .filter_map(|candidate| {
+            let (series, reason) = match match_title(candidate, known) {
+                Some(series) => (series, SERIES_MATCH_TITLE),
+                None => {
+                    let prefix = candidate.code.as_deref().and_then(code_prefix)?;
+                    (by_prefix.get(&prefix)?.clone(), SERIES_MATCH_CODE_PREFIX)
+                }
+            };
+            Some(SeriesSuggestion {
+                media_id: candidate.id.clone(),
+                title: candidate.title.clone(),
+                code: candidate.code.clone(),
+                studio: candidate.studio.clone(),
+                series,
+                reason: reason.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// 扫描数据库生成报告并缓存
+pub async fn generate_report(pool: &Pool<Sqlite>) -> Result<SeriesSuggestionReport> {
+    let candidates = database::list_series_detect_candidates(pool).await?;
+    let known = database::list_known_series(pool).await?;
+    let coded = database::list_coded_series(pool).await?;
+    let report = SeriesSuggestionReport {
+        generated_at: Utc::now(),
+        media_scanned: candidates.len(),
+        suggestions: detect_series(&candidates, &known, &coded),
+    };
+    *LATEST_REPORT.write().await = Some(report.clone());
+    Ok(report)
+}
+
+/// 获取最近一次的报告（没有报告或 refresh 为 true 时重新生成）
+pub async fn latest_report(pool: &Pool<Sqlite>, refresh: bool) -> Result<SeriesSuggestionReport> {
+    if !refresh {
+        if let Some(report) = LATEST_REPORT.read().await.clone() {
+            return Ok(report);
+        }
+    }
+    generate_report(pool).await
+}
+
+/// 批量设置系列（assignments 为 None 时应用当前报告中的全部建议），完成后重新生成报告
+pub async fn apply_suggestions(pool: &Pool<Sqlite>, assignments: Option<Vec<SeriesAssignment>>) -> Result<SeriesApplyResult> {
+    let assignments = match assignments {
+        Some(assignments) => assignments,
+        None => latest_report(pool, false).await?
+            .suggestions
+            .into_iter()
+            .map(|s| SeriesAssignment { media_id: s.media_id, series: s.series })
+            .collect(),
+    };
+
+    let mut result = SeriesApplyResult::default();
+    for assignment in assignments {
+        if database::assign_media_series(pool, &assignment.media_id, assignment.series.trim()).await? {
+            result.applied += 1;
+        } else {
+            result.skipped += 1;
+        }
+    }
+
+    generate_report(pool).await?;
+    Ok(result)
+}
+
+/// 定期生成系列建议报告的后台任务
+pub struct SeriesDetectTask {
+    pool: Pool<Sqlite>,
+    interval: Duration,
+}
+
+impl SeriesDetectTask {
+    pub fn new(pool: Pool<Sqlite>, interval: Duration) -> Self {
+        Self { pool, interval }
+    }
+
+    /// 启动定期检测任务（启动后等待一个周期再执行首次检测）
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+            match generate_report(&self.pool).await {
+                Ok(report) => tracing::info!(
+                    "Series detection completed: {} media, {} suggestions",
+                    report.media_scanned, report.suggestions.len()
+                ),
+                Err(e) => tracing::warn!("Series detection failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: &str, title: &str, code: Option<&str>, studio: Option<&str>) -> SeriesDetectCandidate {
+        SeriesDetectCandidate {
+            id: id.to_string(),
+            title: title.to_string(),
+            code: code.map(str::to_string),
+            studio: studio.map(str::to_string),
+        }
+    }
+
+    fn known(name: &str, studio: Option<&str>) -> KnownSeries {
+        KnownSeries { name: name.to_string(), studio: studio.map(str::to_string) }
+    }
+
+    #[test]
+    fn test_title_contains() {
+        assert!(title_contains("Summer Days Vol.2", "summer days"));
+        assert!(!title_contains("Summertime", "summer"));
+        assert!(title_contains("新人デビュー作品集", "デビュー"));
+    }
+
+    #[test]
+    fn test_detect_series() {
+        let known = vec![
+            known("Summer", None),
+            known("Summer Days", Some("Sun Studio")),
+            known("Night", Some("Moon Studio")),
+            known("XY", None),
+        ];
+        let coded = vec![
+            ("ABC-001".to_string(), "Alpha Line".to_string()),
+            ("abc002".to_string(), "alpha line".to_string()),
+            ("MIX-001".to_string(), "One".to_string()),
+            ("MIX-002".to_string(), "Two".to_string()),
+        ];
+        let candidates = vec![
+            candidate("1", "Summer Days 3", None, Some("sun studio")),
+            candidate("2", "Summer Days 4", None, Some("Other Studio")),
+            candidate("3", "Night Walk", None, Some("Sun Studio")),
+            candidate("4", "Untitled", Some("ABC-003"), None),
+            candidate("5", "Untitled", Some("MIX-003"), None),
+            candidate("6", "XY Story", None, None),
+        ];
+
+        let suggestions = detect_series(&candidates, &known, &coded);
+        let pairs: Vec<(&str, &str, &str)> = suggestions.iter()
+            .map(|s| (s.media_id.as_str(), s.series.as_str(), s.reason.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![
+            ("1", "Summer Days", SERIES_MATCH_TITLE),
+            // 厂商不一致时退回到不限厂商的较短系列名
+            ("2", "Summer", SERIES_MATCH_TITLE),
+            ("4", "Alpha Line", SERIES_MATCH_CODE_PREFIX),
+        ]);
+    }
+}
@@ -0,0 +1,104 @@
+//! 系列缺失作品分析
+//!
+//! 将刮削插件返回的系列全部作品与本地媒体库对比，按番号（没有番号时按标题）找出缺失的作品
+
+use std::collections::HashSet;
+use serde::Serialize;
+
+use crate::plugins::protocol::ScrapeResult;
+use super::release_calendar::normalize_date;
+
+/// 缺失的作品
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SeriesGap {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub poster_url: Option<String>,
+}
+
+/// 番号规范化：只保留字母数字并转大写（ABC-123 与 abc_123 视为相同）
+fn normalize_code(code: &str) -> String {
+    code.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_uppercase()
+}
+
+/// 标题规范化：去掉首尾空白并转小写
+fn normalize_title(title: &str) -> String {
+    title.trim().to_lowercase()
+}
+
+/// 对比已知作品与本地媒体（code, title），返回缺失作品（按发售日期升序，无日期的排在最后）
+pub fn find_missing_releases(releases: Vec<ScrapeResult>, owned: &[(Option<String>, String)]) -> Vec<SeriesGap> {
+    let owned_codes: HashSet<String> = owned.iter()
+        .filter_map(|(code, _)| code.as_deref())
+        .map(normalize_code)
+        .filter(|c| !c.is_empty())
+        .collect();
+    let owned_titles: HashSet<String> = owned.iter().map(|(_, title)| normalize_title(title)).collect();
+
+    let mut seen = HashSet::new();
+    let mut missing: Vec<SeriesGap> = releases.into_iter()
+        .filter_map(|release| {
+            let code = release.code.as_deref().map(normalize_code).filter(|c| !c.is_empty());
+            let owned = match &code {
+                Some(code) => owned_codes.contains(code),
+                None => owned_titles.contains(&normalize_title(&release.title)),
+            };
+            let key = code.unwrap_or_else(|| normalize_title(&release.title));
+            if owned || !seen.insert(key) {
+                return None;
+            }
+            Some(SeriesGap {
+                release_date: release.release_date.as_deref().and_then(normalize_date),
+                code: release.code,
+                title: release.title,
+                poster_url: release.poster_url,
+            })
+        })
+        .collect();
+
+    missing.sort_by(|a, b| match (&a.release_date, &b.release_date) {
+        (Some(x), Some(y)) => x.cmp(y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.title.cmp(&b.title),
+    });
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(code: Option<&str>, title: &str, date: Option<&str>) -> ScrapeResult {
+        ScrapeResult {
+            code: code.map(String::from),
+            title: title.to_string(),
+            release_date: date.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_find_missing_releases() {
+        let releases = vec![
+            release(Some("ABC-003"), "Third", Some("2024/03/01")),
+            release(Some("ABC-001"), "First", Some("2024-01-01")),
+            release(Some("abc_002"), "Second", Some("2024-02-01")),
+            release(Some("ABC-003"), "Third (dup)", None),
+            release(None, "Special", None),
+            release(None, "Owned Special", Some("2023-12-01")),
+        ];
+        let owned = vec![
+            (Some("ABC-002".to_string()), "Second".to_string()),
+            (None, "owned special".to_string()),
+        ];
+        let missing = find_missing_releases(releases, &owned);
+        let titles: Vec<&str> = missing.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, vec!["First", "Third", "Special"]);
+        assert_eq!(missing[1].release_date.as_deref(), Some("2024-03-01"));
+    }
+}
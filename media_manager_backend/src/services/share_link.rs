@@ -0,0 +1,87 @@
+//! 分享链接令牌
+//!
+//! 令牌格式：`{share_id}.{过期时间戳}.{HMAC-SHA256 签名}`，签名密钥保存在 user_settings 中。
+//! 先校验签名和过期时间（无需查库即可拒绝伪造令牌），再由数据库记录判断是否已撤销
+
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::{Pool, Sqlite};
+
+use crate::database;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 保存签名密钥的设置键
+const SHARE_SECRET_KEY: &str = "share_link_secret";
+
+/// 令牌校验失败原因
+#[derive(Debug, PartialEq)]
+pub enum ShareTokenError {
+    Malformed,
+    BadSignature,
+    Expired,
+}
+
+/// 获取签名密钥（首次调用时生成并保存）
+pub async fn get_share_secret(pool: &Pool<Sqlite>) -> Result<String> {
+    if let Some(secret) = database::get_setting(pool, SHARE_SECRET_KEY).await? {
+        return Ok(secret);
+    }
+    let secret = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    database::set_setting(pool, SHARE_SECRET_KEY, &secret, Some("分享链接签名密钥")).await?;
+    Ok(secret)
+}
+
+fn mac_for(secret: &str, share_id: &str, expires_ts: i64) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(format!("{}.{}", share_id, expires_ts).as_bytes());
+    mac
+}
+
+/// 生成签名令牌
+pub fn sign_token(secret: &str, share_id: &str, expires_ts: i64) -> String {
+    let signature = hex::encode(mac_for(secret, share_id, expires_ts).finalize().into_bytes());
+    format!("{}.{}.{}", share_id, expires_ts, signature)
+}
+
+/// 校验令牌，返回分享ID
+pub fn verify_token(secret: &str, token: &str, now_ts: i64) -> Result<String, ShareTokenError> {
+    let mut parts = token.split('.');
+    let (Some(share_id), Some(expires), Some(signature), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err(ShareTokenError::Malformed);
+    };
+    let expires_ts: i64 = expires.parse().map_err(|_| ShareTokenError::Malformed)?;
+    let signature = hex::decode(signature).map_err(|_| ShareTokenError::Malformed)?;
+
+    mac_for(secret, share_id, expires_ts)
+        .verify_slice(&signature)
+        .map_err(|_| ShareTokenError::BadSignature)?;
+
+    if expires_ts <= now_ts {
+        return Err(ShareTokenError::Expired);
+    }
+    Ok(share_id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let token = sign_token("secret", "abc-123", 2_000);
+        assert_eq!(verify_token("secret", &token, 1_000), Ok("abc-123".to_string()));
+        assert_eq!(verify_token("secret", &token, 2_000), Err(ShareTokenError::Expired));
+        assert_eq!(verify_token("other", &token, 1_000), Err(ShareTokenError::BadSignature));
+    }
+
+    #[test]
+    fn test_tampered_token() {
+        let token = sign_token("secret", "abc-123", 2_000);
+        let extended = token.replacen("2000", "9000", 1);
+        assert_eq!(verify_token("secret", &extended, 1_000), Err(ShareTokenError::BadSignature));
+        assert_eq!(verify_token("secret", "abc-123.2000", 1_000), Err(ShareTokenError::Malformed));
+        assert_eq!(verify_token("secret", "abc.x.00", 1_000), Err(ShareTokenError::Malformed));
+    }
+}
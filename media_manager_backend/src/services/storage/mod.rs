@@ -0,0 +1,207 @@
+//! 文件存储后端
+//!
+//! 媒体文件和图片缓存除了本地目录，也可以放在 S3 兼容存储或 WebDAV 上。
+//! 每个媒体库根目录可以选择自己的存储后端，播放时由 stream_video 按 Range 代理读取。
+
+mod s3;
+mod webdav;
+
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt, TryStreamExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use crate::models::{LibraryRoot, StorageConfig};
+
+pub use s3::S3Storage;
+pub use webdav::WebDavStorage;
+
+/// 对象内容的字节流
+pub type ByteStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+
+lazy_static::lazy_static! {
+    /// 对象存储共用的 HTTP 客户端（不设整体超时，大文件的流式读取可能持续很久）
+    static ref HTTP_CLIENT: reqwest::Client = reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(10))
+        .build()
+        .expect("Failed to build storage HTTP client");
+}
+
+/// 存储后端（键为相对根目录的路径，使用 `/` 分隔）
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// 检查存储是否可访问
+    async fn check(&self) -> Result<()>;
+
+    /// 对象大小，不存在时返回 None
+    async fn size(&self, key: &str) -> Result<Option<u64>>;
+
+    /// 读取对象内容，range 为闭区间 (start, end)
+    async fn read(&self, key: &str, range: Option<(u64, u64)>) -> Result<ByteStream>;
+
+    /// 写入对象（已存在时覆盖）
+    async fn write(&self, key: &str, data: Vec<u8>) -> Result<()>;
+
+    /// 删除前缀（目录）下的所有对象
+    async fn delete_prefix(&self, prefix: &str) -> Result<()>;
+
+    /// 列出目录下的子目录名（不递归）
+    async fn list_dirs(&self, prefix: &str) -> Result<Vec<String>>;
+}
+
+/// 根据配置创建存储后端，本地存储以 `local_root` 为根目录
+pub fn open_storage(config: &StorageConfig, local_root: &Path) -> Arc<dyn StorageBackend> {
+    match config {
+        StorageConfig::Local => Arc::new(LocalStorage::new(local_root)),
+        StorageConfig::S3 { endpoint, bucket, region, access_key_id, secret_access_key, prefix } => {
+            Arc::new(S3Storage::new(endpoint, bucket, region, access_key_id, secret_access_key, prefix))
+        }
+        StorageConfig::Webdav { url, username, password } => {
+            Arc::new(WebDavStorage::new(url, username.clone(), password.clone()))
+        }
+    }
+}
+
+/// 媒体库根目录的存储后端
+pub fn root_storage(root: &LibraryRoot) -> Result<Arc<dyn StorageBackend>> {
+    let config = root.storage().map_err(anyhow::Error::msg)?;
+    Ok(open_storage(&config, Path::new(&root.path)))
+}
+
+/// 本地目录
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: &Path) -> Self {
+        Self { root: root.to_path_buf() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.join(key.trim_start_matches('/'))
+    }
+}
+
+#[async_trait]
+impl StorageBackend for LocalStorage {
+    async fn check(&self) -> Result<()> {
+        let meta = tokio::fs::metadata(&self.root).await
+            .with_context(|| format!("{} is not accessible", self.root.display()))?;
+        anyhow::ensure!(meta.is_dir(), "{} is not a directory", self.root.display());
+        Ok(())
+    }
+
+    async fn size(&self, key: &str) -> Result<Option<u64>> {
+        match tokio::fs::metadata(self.path(key)).await {
+            Ok(meta) => Ok(Some(meta.len())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn read(&self, key: &str, range: Option<(u64, u64)>) -> Result<ByteStream> {
+        let mut file = tokio::fs::File::open(self.path(key)).await?;
+        match range {
+            Some((start, end)) => {
+                file.seek(std::io::SeekFrom::Start(start)).await?;
+                Ok(ReaderStream::new(file.take(end - start + 1)).boxed())
+            }
+            None => Ok(ReaderStream::new(file).boxed()),
+        }
+    }
+
+    async fn write(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, data).await?;
+        Ok(())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        let path = self.path(prefix);
+        let result = if path.is_dir() {
+            tokio::fs::remove_dir_all(&path).await
+        } else {
+            tokio::fs::remove_file(&path).await
+        };
+        match result {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn list_dirs(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut entries = tokio::fs::read_dir(self.path(prefix)).await?;
+        let mut dirs = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            // 跟随符号链接判断是否为目录
+            if tokio::fs::metadata(entry.path()).await.map(|meta| meta.is_dir()).unwrap_or(false) {
+                dirs.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        Ok(dirs)
+    }
+}
+
+/// 把 HTTP 响应体转换为字节流
+fn response_stream(response: reqwest::Response) -> ByteStream {
+    response.bytes_stream()
+        .map_err(std::io::Error::other)
+        .boxed()
+}
+
+/// 读取 Content-Length 头（HEAD 响应的 `Response::content_length` 总是 0）
+fn content_length(response: &reqwest::Response) -> Option<u64> {
+    response.headers()
+        .get(reqwest::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
+
+/// 按 `/` 分段进行百分号编码（保留分隔符）
+fn encode_key(key: &str) -> String {
+    key.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_storage_range_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path());
+        storage.write("a/b.bin", b"0123456789".to_vec()).await.unwrap();
+        assert_eq!(storage.size("a/b.bin").await.unwrap(), Some(10));
+        assert_eq!(storage.size("missing").await.unwrap(), None);
+
+        let chunks: Vec<Bytes> = storage.read("a/b.bin", Some((2, 5))).await.unwrap()
+            .try_collect().await.unwrap();
+        assert_eq!(chunks.concat(), b"2345");
+
+        std::fs::create_dir(dir.path().join("a/c")).unwrap();
+        assert_eq!(storage.list_dirs("").await.unwrap(), vec!["a"]);
+        assert_eq!(storage.list_dirs("a").await.unwrap(), vec!["c"]);
+
+        storage.delete_prefix("a").await.unwrap();
+        assert!(!dir.path().join("a").exists());
+        storage.delete_prefix("a").await.unwrap();
+    }
+
+    #[test]
+    fn test_encode_key() {
+        assert_eq!(encode_key("movies/ABC 123/a+b.mp4"), "movies/ABC%20123/a%2Bb.mp4");
+    }
+}
@@ -0,0 +1,186 @@
+//! WebDAV 存储
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::{Method, RequestBuilder, StatusCode};
+
+use super::{content_length, encode_key, response_stream, ByteStream, StorageBackend, HTTP_CLIENT};
+
+pub struct WebDavStorage {
+    /// 不含末尾 `/` 的根地址
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+impl WebDavStorage {
+    pub fn new(url: &str, username: Option<String>, password: Option<String>) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            username,
+            password,
+        }
+    }
+
+    fn key_url(&self, key: &str) -> String {
+        format!("{}/{}", self.url, encode_key(key.trim_matches('/')))
+    }
+
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request = HTTP_CLIENT.request(method, url);
+        match &self.username {
+            Some(username) => request.basic_auth(username, self.password.as_ref()),
+            None => request,
+        }
+    }
+
+    /// 逐级创建父目录（已存在时服务器返回 405）
+    async fn ensure_parent_collections(&self, key: &str) -> Result<()> {
+        let segments: Vec<&str> = key.trim_matches('/').split('/').collect();
+        let mut path = String::new();
+        for segment in &segments[..segments.len().saturating_sub(1)] {
+            path = if path.is_empty() { segment.to_string() } else { format!("{}/{}", path, segment) };
+            let url = format!("{}/", self.key_url(&path));
+            let response = self.request(Method::from_bytes(b"MKCOL")?, &url).send().await?;
+            let status = response.status();
+            anyhow::ensure!(
+                status.is_success() || status == StatusCode::METHOD_NOT_ALLOWED,
+                "MKCOL {} returned {}", url, status
+            );
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StorageBackend for WebDavStorage {
+    async fn check(&self) -> Result<()> {
+        let url = format!("{}/", self.url);
+        let response = self.request(Method::from_bytes(b"PROPFIND")?, &url)
+            .header("Depth", "0")
+            .send()
+            .await
+            .with_context(|| format!("WebDAV request to {} failed", url))?;
+        anyhow::ensure!(response.status().is_success(), "{} returned {}", url, response.status());
+        Ok(())
+    }
+
+    async fn size(&self, key: &str) -> Result<Option<u64>> {
+        let response = self.request(Method::HEAD, &self.key_url(key)).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        anyhow::ensure!(response.status().is_success(), "HEAD {} returned {}", key, response.status());
+        Ok(content_length(&response))
+    }
+
+    async fn read(&self, key: &str, range: Option<(u64, u64)>) -> Result<ByteStream> {
+        let mut request = self.request(Method::GET, &self.key_url(key));
+        if let Some((start, end)) = range {
+            request = request.header("Range", format!("bytes={}-{}", start, end));
+        }
+        let response = request.send().await?;
+        anyhow::ensure!(response.status().is_success(), "GET {} returned {}", key, response.status());
+        Ok(response_stream(response))
+    }
+
+    async fn write(&self, key: &str, data: Vec<u8>) -> Result<()> {
+        self.ensure_parent_collections(key).await?;
+        let response = self.request(Method::PUT, &self.key_url(key)).body(data).send().await?;
+        anyhow::ensure!(response.status().is_success(), "PUT {} returned {}", key, response.status());
+        Ok(())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<()> {
+        // WebDAV 删除目录时会递归删除其中的内容
+        let response = self.request(Method::DELETE, &self.key_url(prefix)).send().await?;
+        let status = response.status();
+        anyhow::ensure!(
+            status.is_success() || status == StatusCode::NOT_FOUND,
+            "DELETE {} returned {}", prefix, status
+        );
+        Ok(())
+    }
+
+    async fn list_dirs(&self, prefix: &str) -> Result<Vec<String>> {
+        let prefix = prefix.trim_matches('/');
+        let url = if prefix.is_empty() { format!("{}/", self.url) } else { format!("{}/", self.key_url(prefix)) };
+        let response = self.request(Method::from_bytes(b"PROPFIND")?, &url)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml")
+            .body(r#"<?xml version="1.0"?><propfind xmlns="DAV:"><prop><resourcetype/></prop></propfind>"#)
+            .send()
+            .await
+            .with_context(|| format!("WebDAV request to {} failed", url))?;
+        anyhow::ensure!(response.status().is_success(), "PROPFIND {} returned {}", url, response.status());
+        let body = response.text().await?;
+        Ok(collection_names(&body, &url))
+    }
+}
+
+/// 取出 PROPFIND (Depth: 1) 结果中的子目录名（跳过目录本身）
+fn collection_names(xml: &str, request_url: &str) -> Vec<String> {
+    let request_path = url::Url::parse(request_url)
+        .map(|url| url.path().trim_end_matches('/').to_string())
+        .unwrap_or_default();
+    let mut reader = Reader::from_str(xml);
+    let mut names = Vec::new();
+    let mut href = String::new();
+    let mut in_href = false;
+    let mut is_collection = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                b"response" => {
+                    href.clear();
+                    is_collection = false;
+                }
+                b"href" => in_href = true,
+                b"collection" => is_collection = true,
+                _ => {}
+            },
+            Ok(Event::Empty(e)) if e.local_name().as_ref() == b"collection" => is_collection = true,
+            Ok(Event::Text(text)) if in_href => {
+                href.push_str(&text.unescape().unwrap_or_default());
+            }
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"href" => in_href = false,
+                b"response" if is_collection => {
+                    // href 可能是完整地址，也可能只有路径
+                    let path = url::Url::parse(&href).map(|url| url.path().to_string()).unwrap_or_else(|_| href.clone());
+                    let path = path.trim_end_matches('/');
+                    if path != request_path {
+                        if let Some(name) = path.rsplit('/').next().filter(|name| !name.is_empty()) {
+                            names.push(urlencoding::decode(name).map(|n| n.into_owned()).unwrap_or_else(|_| name.to_string()));
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_names() {
+        let xml = r#"<?xml version="1.0"?>
+            <d:multistatus xmlns:d="DAV:">
+              <d:response><d:href>/dav/movies/</d:href>
+                <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat></d:response>
+              <d:response><d:href>http://nas/dav/movies/Action%20Films/</d:href>
+                <d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop></d:propstat></d:response>
+              <d:response><d:href>/dav/movies/a.mp4</d:href>
+                <d:propstat><d:prop><d:resourcetype/></d:prop></d:propstat></d:response>
+            </d:multistatus>"#;
+        assert_eq!(collection_names(xml, "http://nas/dav/movies/"), vec!["Action Films"]);
+    }
+}
@@ -0,0 +1,219 @@
+//! 播放会话登记
+//!
+//! 视频流和转码请求在开始时登记会话，响应体结束（播放完、客户端断开或被终止）时注销。
+//! 同一用户对同一媒体分段的并发请求（如拖动进度时的 Range 请求）共用一个会话，
+//! 超过每个用户的会话数上限时拒绝新的播放。终止会话会立即结束所有连接的响应体，
+//! 转码时随响应体一起结束 FFmpeg 进程。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::body::Body;
+use axum::response::Response;
+use chrono::Utc;
+use futures_util::StreamExt;
+use sqlx::{Pool, Sqlite};
+use tokio_util::sync::CancellationToken;
+
+use crate::database;
+use crate::models::{StreamSession, StreamingSettings};
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: SessionRegistry = SessionRegistry::default();
+}
+
+struct SessionEntry {
+    session: StreamSession,
+    bytes_sent: Arc<AtomicU64>,
+    cancel: CancellationToken,
+}
+
+#[derive(Default)]
+struct SessionRegistry {
+    sessions: Mutex<HashMap<String, SessionEntry>>,
+    max_streams_per_user: AtomicUsize,
+}
+
+/// 超过了用户的同时播放数上限
+#[derive(Debug)]
+pub struct StreamLimitExceeded {
+    pub limit: usize,
+}
+
+/// 一个连接对会话的占用，drop 时释放（最后一个连接释放时注销会话）
+pub struct SessionGuard<'a> {
+    registry: &'a SessionRegistry,
+    id: String,
+    bytes_sent: Arc<AtomicU64>,
+    cancel: CancellationToken,
+}
+
+impl Drop for SessionGuard<'_> {
+    fn drop(&mut self) {
+        let mut sessions = self.registry.sessions.lock().unwrap();
+        if let Some(entry) = sessions.get_mut(&self.id) {
+            entry.session.connections -= 1;
+            if entry.session.connections == 0 {
+                sessions.remove(&self.id);
+            }
+        }
+    }
+}
+
+impl SessionRegistry {
+    fn open(
+        &self,
+        user: &str,
+        media_id: &str,
+        edition: Option<&str>,
+        part: usize,
+        kind: &str,
+        user_agent: Option<&str>,
+    ) -> Result<SessionGuard<'_>, StreamLimitExceeded> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let existing = sessions.values_mut().find(|entry| {
+            let session = &entry.session;
+            session.user == user && session.media_id == media_id && session.edition.as_deref() == edition
+                && session.part == part && session.kind == kind
+        });
+        if let Some(entry) = existing {
+            entry.session.connections += 1;
+            return Ok(SessionGuard {
+                registry: self,
+                id: entry.session.id.clone(),
+                bytes_sent: entry.bytes_sent.clone(),
+                cancel: entry.cancel.clone(),
+            });
+        }
+
+        let limit = self.max_streams_per_user.load(Ordering::Relaxed);
+        if limit > 0 && sessions.values().filter(|entry| entry.session.user == user).count() >= limit {
+            return Err(StreamLimitExceeded { limit });
+        }
+
+        let entry = SessionEntry {
+            session: StreamSession {
+                id: uuid::Uuid::new_v4().to_string(),
+                user: user.to_string(),
+                media_id: media_id.to_string(),
+                edition: edition.map(str::to_string),
+                part,
+                kind: kind.to_string(),
+                user_agent: user_agent.map(str::to_string),
+                started_at: Utc::now(),
+                connections: 1,
+                bytes_sent: 0,
+            },
+            bytes_sent: Arc::new(AtomicU64::new(0)),
+            cancel: CancellationToken::new(),
+        };
+        let guard = SessionGuard {
+            registry: self,
+            id: entry.session.id.clone(),
+            bytes_sent: entry.bytes_sent.clone(),
+            cancel: entry.cancel.clone(),
+        };
+        sessions.insert(entry.session.id.clone(), entry);
+        Ok(guard)
+    }
+
+    fn list(&self, user: Option<&str>) -> Vec<StreamSession> {
+        let sessions = self.sessions.lock().unwrap();
+        let mut list: Vec<StreamSession> = sessions.values()
+            .filter(|entry| user.is_none_or(|user| entry.session.user == user))
+            .map(|entry| StreamSession { bytes_sent: entry.bytes_sent.load(Ordering::Relaxed), ..entry.session.clone() })
+            .collect();
+        list.sort_by_key(|session| session.started_at);
+        list
+    }
+
+    fn terminate(&self, id: &str) -> bool {
+        match self.sessions.lock().unwrap().remove(id) {
+            Some(entry) => {
+                entry.cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// 登记播放会话（同一用户、媒体、版本和分段的请求共用会话）
+pub fn open(
+    user: &str,
+    media_id: &str,
+    edition: Option<&str>,
+    part: usize,
+    kind: &str,
+    user_agent: Option<&str>,
+) -> Result<SessionGuard<'static>, StreamLimitExceeded> {
+    REGISTRY.open(user, media_id, edition, part, kind, user_agent)
+}
+
+/// 正在进行的播放会话（user 为空时返回所有用户的）
+pub fn list(user: Option<&str>) -> Vec<StreamSession> {
+    REGISTRY.list(user)
+}
+
+/// 终止播放会话，返回会话是否存在
+pub fn terminate(id: &str) -> bool {
+    REGISTRY.terminate(id)
+}
+
+/// 应用设置（调用前需校验）
+pub fn apply_settings(settings: &StreamingSettings) {
+    REGISTRY.max_streams_per_user.store(settings.max_streams_per_user, Ordering::Relaxed);
+}
+
+/// 启动时读取保存的设置
+pub async fn load_settings(pool: &Pool<Sqlite>) -> Result<()> {
+    apply_settings(&database::get_streaming_settings(pool).await?);
+    Ok(())
+}
+
+/// 把会话绑定到响应体：统计发送的字节数，会话被终止时结束响应体，响应体结束时释放会话
+pub fn track_response(guard: SessionGuard<'static>, response: Response) -> Response {
+    let cancelled = guard.cancel.clone().cancelled_owned();
+    response.map(|body| {
+        let stream = body.into_data_stream()
+            .take_until(cancelled)
+            .map(move |chunk| {
+                if let Ok(bytes) = &chunk {
+                    guard.bytes_sent.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+                }
+                chunk
+            });
+        Body::from_stream(stream)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_share_and_limit() {
+        let registry = SessionRegistry::default();
+        registry.max_streams_per_user.store(1, Ordering::Relaxed);
+
+        let first = registry.open("alice", "m1", None, 0, "direct", None).unwrap();
+        // 同一分段的第二个请求共用会话，不受上限影响
+        let second = registry.open("alice", "m1", None, 0, "direct", None).unwrap();
+        assert_eq!(first.id, second.id);
+        assert_eq!(registry.list(Some("alice"))[0].connections, 2);
+        assert!(registry.open("alice", "m1", None, 1, "direct", None).is_err_and(|e| e.limit == 1));
+        let other = registry.open("bob", "m1", None, 1, "direct", None).unwrap();
+
+        drop(first);
+        assert_eq!(registry.list(None).len(), 2);
+        drop(second);
+        assert_eq!(registry.list(None).len(), 1);
+
+        assert!(registry.terminate(&other.id));
+        assert!(other.cancel.is_cancelled());
+        assert!(registry.list(None).is_empty());
+        assert!(!registry.terminate(&other.id));
+    }
+}
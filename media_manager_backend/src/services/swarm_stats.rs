@@ -0,0 +1,452 @@
+//! 已保存磁力链接的做种人数刷新
+//!
+//! 收集媒体 download_links 中的磁力链接，按 tracker 分组批量 scrape（HTTP 按 BEP 48，UDP 按 BEP 15），
+//! 同一磁力链接取各 tracker 中的最大值保存到 magnet_swarm_stats。限制同时查询的 tracker 数。
+//! 没有可用 tracker 的磁力链接需要 DHT 才能获取人数，目前不支持，记为未知。
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use reqwest::Client;
+use sqlx::{Pool, Sqlite};
+use tokio::net::UdpSocket;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::database::{self, DatabaseRepository, SqliteRepository};
+use crate::models::{same_tracker, MagnetSwarmStats, MediaItem, SwarmStatsRefreshResult, MAX_SWARM_STATS_CONCURRENCY};
+use super::magnet::MagnetUri;
+
+/// 单个 tracker 请求的超时
+const TRACKER_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 每次 scrape 请求的最大 hash 数（UDP 单个响应最多容纳约 74 个）
+const SCRAPE_BATCH_SIZE: usize = 50;
+
+/// UDP tracker 协议标识
+const UDP_PROTOCOL_ID: u64 = 0x0417_2710_1980;
+const UDP_ACTION_CONNECT: u32 = 0;
+const UDP_ACTION_SCRAPE: u32 = 2;
+const UDP_ACTION_ERROR: u32 = 3;
+
+/// tracker 返回的人数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SwarmCounts {
+    pub seeders: i64,
+    pub leechers: i64,
+    pub completed: i64,
+}
+
+/// 把 announce 地址转换为 scrape 地址（BEP 48：路径最后一段以 announce 开头时替换为 scrape）
+pub fn scrape_url(announce: &str) -> Option<String> {
+    let (base, last) = announce.rsplit_once('/')?;
+    let rest = last.strip_prefix("announce")?;
+    base.contains("://").then(|| format!("{}/scrape{}", base, rest))
+}
+
+fn hash_bytes(hash: &str) -> Option<[u8; 20]> {
+    let mut bytes = [0u8; 20];
+    if hash.len() != 40 {
+        return None;
+    }
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hash.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+fn hash_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// ============ Bencode ============
+
+/// 解析 HTTP scrape 响应需要的 bencode 子集（列表内容不保留）
+enum Bencode {
+    Int(i64),
+    Bytes(Vec<u8>),
+    List,
+    Dict(Vec<(Vec<u8>, Bencode)>),
+}
+
+impl Bencode {
+    fn get(&self, key: &str) -> Option<&Bencode> {
+        match self {
+            Bencode::Dict(entries) => entries.iter().find(|(k, _)| k == key.as_bytes()).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            Bencode::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// 嵌套层数上限，避免恶意响应耗尽栈
+const MAX_BENCODE_DEPTH: usize = 16;
+
+fn decode_bencode(data: &[u8], pos: &mut usize, depth: usize) -> Option<Bencode> {
+    match *data.get(*pos)? {
+        b'i' => {
+            let end = *pos + data[*pos..].iter().position(|&b| b == b'e')?;
+            let value = std::str::from_utf8(&data[*pos + 1..end]).ok()?.parse().ok()?;
+            *pos = end + 1;
+            Some(Bencode::Int(value))
+        }
+        b'0'..=b'9' => {
+            let colon = *pos + data[*pos..].iter().position(|&b| b == b':')?;
+            let len: usize = std::str::from_utf8(&data[*pos..colon]).ok()?.parse().ok()?;
+            let start = colon + 1;
+            let bytes = data.get(start..start.checked_add(len)?)?.to_vec();
+            *pos = start + len;
+            Some(Bencode::Bytes(bytes))
+        }
+        kind @ (b'l' | b'd') if depth < MAX_BENCODE_DEPTH => {
+            *pos += 1;
+            let mut entries = Vec::new();
+            while *data.get(*pos)? != b'e' {
+                if kind == b'd' {
+                    let Bencode::Bytes(key) = decode_bencode(data, pos, depth + 1)? else {
+                        return None;
+                    };
+                    entries.push((key, decode_bencode(data, pos, depth + 1)?));
+                } else {
+                    decode_bencode(data, pos, depth + 1)?;
+                }
+            }
+            *pos += 1;
+            Some(if kind == b'd' { Bencode::Dict(entries) } else { Bencode::List })
+        }
+        _ => None,
+    }
+}
+
+/// 解析 HTTP scrape 响应：d5:filesd<20 字节 hash>d8:completei..e10:incompletei..e10:downloadedi..eeee
+fn parse_http_scrape(body: &[u8]) -> Result<HashMap<String, SwarmCounts>> {
+    let root = decode_bencode(body, &mut 0, 0).ok_or_else(|| anyhow!("Invalid bencoded scrape response"))?;
+    if let Some(Bencode::Bytes(reason)) = root.get("failure reason") {
+        bail!("Tracker error: {}", String::from_utf8_lossy(reason));
+    }
+    let Some(Bencode::Dict(files)) = root.get("files") else {
+        bail!("Scrape response has no files");
+    };
+    Ok(files.iter()
+        .filter(|(hash, _)| hash.len() == 20)
+        .map(|(hash, stats)| {
+            let count = |key: &str| stats.get(key).and_then(Bencode::as_int).unwrap_or(0);
+            (hash_hex(hash), SwarmCounts {
+                seeders: count("complete"),
+                leechers: count("incomplete"),
+                completed: count("downloaded"),
+            })
+        })
+        .collect())
+}
+
+// ============ Tracker Scrape ============
+
+async fn scrape_http(client: &Client, announce: &str, hashes: &[[u8; 20]]) -> Result<HashMap<String, SwarmCounts>> {
+    let base = scrape_url(announce).ok_or_else(|| anyhow!("Tracker does not support scrape"))?;
+    let query: Vec<String> = hashes.iter()
+        .map(|hash| format!("info_hash={}", hash.iter().map(|b| format!("%{:02X}", b)).collect::<String>()))
+        .collect();
+    let separator = if base.contains('?') { '&' } else { '?' };
+    let body = client.get(format!("{}{}{}", base, separator, query.join("&")))
+        .send().await?
+        .error_for_status()?
+        .bytes().await?;
+    parse_http_scrape(&body)
+}
+
+/// 检查 UDP 响应头（action、transaction_id），返回其余内容
+fn udp_payload(packet: &[u8], action: u32, transaction_id: u32) -> Result<&[u8]> {
+    if packet.len() < 8 {
+        bail!("Truncated UDP tracker response");
+    }
+    let actual_action = u32::from_be_bytes(packet[0..4].try_into()?);
+    let actual_transaction = u32::from_be_bytes(packet[4..8].try_into()?);
+    if actual_transaction != transaction_id {
+        bail!("UDP tracker transaction id mismatch");
+    }
+    if actual_action == UDP_ACTION_ERROR {
+        bail!("Tracker error: {}", String::from_utf8_lossy(&packet[8..]));
+    }
+    if actual_action != action {
+        bail!("Unexpected UDP tracker action {}", actual_action);
+    }
+    Ok(&packet[8..])
+}
+
+/// 解析 UDP scrape 响应：每个 hash 依次为 seeders、completed、leechers（各 4 字节）
+fn parse_udp_scrape(payload: &[u8], hashes: &[[u8; 20]]) -> HashMap<String, SwarmCounts> {
+    hashes.iter()
+        .zip(payload.chunks_exact(12))
+        .map(|(hash, chunk)| {
+            let field = |i: usize| i64::from(u32::from_be_bytes([chunk[i], chunk[i + 1], chunk[i + 2], chunk[i + 3]]));
+            (hash_hex(hash), SwarmCounts { seeders: field(0), completed: field(4), leechers: field(8) })
+        })
+        .collect()
+}
+
+async fn scrape_udp(announce: &str, hashes: &[[u8; 20]]) -> Result<HashMap<String, SwarmCounts>> {
+    let url = url::Url::parse(announce)?;
+    let host = url.host_str().ok_or_else(|| anyhow!("Tracker has no host"))?;
+    let port = url.port().ok_or_else(|| anyhow!("Tracker has no port"))?;
+
+    tokio::time::timeout(TRACKER_TIMEOUT, async {
+        let addr = tokio::net::lookup_host((host, port)).await?
+            .next()
+            .ok_or_else(|| anyhow!("Cannot resolve tracker host"))?;
+        let socket = UdpSocket::bind(if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" }).await?;
+        socket.connect(addr).await?;
+        let mut buffer = [0u8; 2048];
+
+        let transaction_id = uuid::Uuid::new_v4().as_u128() as u32;
+        let mut request = Vec::with_capacity(16);
+        request.extend(UDP_PROTOCOL_ID.to_be_bytes());
+        request.extend(UDP_ACTION_CONNECT.to_be_bytes());
+        request.extend(transaction_id.to_be_bytes());
+        socket.send(&request).await?;
+        let len = socket.recv(&mut buffer).await?;
+        let payload = udp_payload(&buffer[..len], UDP_ACTION_CONNECT, transaction_id)?;
+        let connection_id: [u8; 8] = payload.get(..8).ok_or_else(|| anyhow!("Truncated UDP connect response"))?.try_into()?;
+
+        let transaction_id = uuid::Uuid::new_v4().as_u128() as u32;
+        let mut request = Vec::with_capacity(16 + hashes.len() * 20);
+        request.extend(connection_id);
+        request.extend(UDP_ACTION_SCRAPE.to_be_bytes());
+        request.extend(transaction_id.to_be_bytes());
+        hashes.iter().for_each(|hash| request.extend(hash));
+        socket.send(&request).await?;
+        let len = socket.recv(&mut buffer).await?;
+        let payload = udp_payload(&buffer[..len], UDP_ACTION_SCRAPE, transaction_id)?;
+        Ok(parse_udp_scrape(payload, hashes))
+    })
+    .await
+    .map_err(|_| anyhow!("Tracker timed out"))?
+}
+
+/// 查询一个 tracker 上一组 hash 的人数（分批请求）
+async fn scrape_tracker(client: &Client, tracker: &str, hashes: &[[u8; 20]]) -> Result<HashMap<String, SwarmCounts>> {
+    let scheme = tracker.split_once("://").map(|(scheme, _)| scheme.to_ascii_lowercase());
+    let mut counts = HashMap::new();
+    for batch in hashes.chunks(SCRAPE_BATCH_SIZE) {
+        counts.extend(match scheme.as_deref() {
+            Some("http" | "https") => scrape_http(client, tracker, batch).await?,
+            Some("udp") => scrape_udp(tracker, batch).await?,
+            _ => bail!("Unsupported tracker scheme"),
+        });
+    }
+    Ok(counts)
+}
+
+// ============ Refresh ============
+
+/// 媒体上的一个磁力链接及要查询的 tracker
+struct StoredMagnet {
+    media_id: String,
+    info_hash: String,
+    trackers: Vec<String>,
+}
+
+/// 收集媒体下载链接中的磁力链接（同一媒体同一 hash 只保留一次），tracker 按设置追加/移除
+fn collect_magnets(media: &[MediaItem], settings: &crate::models::MagnetTrackerSettings) -> Vec<StoredMagnet> {
+    let mut magnets: Vec<StoredMagnet> = Vec::new();
+    for item in media {
+        for link in item.get_download_links().unwrap_or_default() {
+            let Some(uri) = MagnetUri::parse(&link.url) else {
+                continue;
+            };
+            if magnets.iter().any(|m| m.media_id == item.id && m.info_hash == uri.info_hash) {
+                continue;
+            }
+            let mut trackers: Vec<String> = Vec::new();
+            for tracker in uri.trackers.iter().chain(&settings.trackers) {
+                if !settings.is_dead(tracker) && !trackers.iter().any(|t| same_tracker(t, tracker)) {
+                    trackers.push(tracker.clone());
+                }
+            }
+            magnets.push(StoredMagnet { media_id: item.id.clone(), info_hash: uri.info_hash, trackers });
+        }
+    }
+    magnets
+}
+
+/// 刷新指定媒体（为空时刷新全部媒体）的磁力链接做种人数
+pub async fn refresh_swarm_stats(
+    pool: &Pool<Sqlite>,
+    media_ids: Option<&[String]>,
+    concurrency: usize,
+) -> Result<SwarmStatsRefreshResult> {
+    let repository = SqliteRepository::new(pool.clone());
+    let media: Vec<MediaItem> = match media_ids {
+        Some(ids) => {
+            let mut media = Vec::with_capacity(ids.len());
+            for id in ids {
+                media.extend(repository.get_media_by_id(id).await?);
+            }
+            media
+        }
+        None => repository.get_all_media().await?,
+    };
+    let tracker_settings = database::get_magnet_tracker_settings(pool).await?;
+    let magnets = collect_magnets(&media, &tracker_settings);
+
+    // 按 tracker 分组，一个 tracker 的所有 hash 合并请求
+    let mut by_tracker: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for magnet in &magnets {
+        for tracker in &magnet.trackers {
+            by_tracker.entry(tracker.as_str()).or_default().insert(magnet.info_hash.as_str());
+        }
+    }
+
+    let mut result = SwarmStatsRefreshResult {
+        checked_media: media.len(),
+        checked_magnets: magnets.len(),
+        checked_trackers: by_tracker.len(),
+        ..Default::default()
+    };
+    let client = Client::builder()
+        .timeout(TRACKER_TIMEOUT)
+        .user_agent("Mozilla/5.0 (compatible; MediaManager tracker scrape)")
+        .build()?;
+    let semaphore = Arc::new(Semaphore::new(concurrency.clamp(1, MAX_SWARM_STATS_CONCURRENCY)));
+    let mut tasks = JoinSet::new();
+    for (tracker, hashes) in &by_tracker {
+        let hashes: Vec<[u8; 20]> = hashes.iter().filter_map(|hash| hash_bytes(hash)).collect();
+        let (client, semaphore, tracker) = (client.clone(), semaphore.clone(), tracker.to_string());
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let counts = scrape_tracker(&client, &tracker, &hashes).await;
+            (tracker, counts)
+        });
+    }
+
+    let mut responses: HashMap<String, HashMap<String, SwarmCounts>> = HashMap::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((tracker, Ok(counts))) => {
+                responses.insert(tracker, counts);
+            }
+            Ok((tracker, Err(e))) => result.errors.push(format!("{}: {}", tracker, e)),
+            Err(e) => result.errors.push(format!("Tracker scrape task failed: {}", e)),
+        }
+    }
+
+    let checked_at = Utc::now();
+    for item in &media {
+        let stats: Vec<MagnetSwarmStats> = magnets.iter()
+            .filter(|magnet| magnet.media_id == item.id)
+            .map(|magnet| {
+                let counts: Vec<SwarmCounts> = magnet.trackers.iter()
+                    .filter_map(|tracker| responses.get(tracker)?.get(&magnet.info_hash).copied())
+                    .collect();
+                let max = |field: fn(&SwarmCounts) -> i64| counts.iter().map(field).max();
+                let error = match (counts.is_empty(), magnet.trackers.is_empty()) {
+                    (false, _) => None,
+                    (true, true) => Some("No trackers (DHT lookup is not supported)".to_string()),
+                    (true, false) => Some("No tracker returned stats".to_string()),
+                };
+                let mut stats = MagnetSwarmStats {
+                    media_id: magnet.media_id.clone(),
+                    info_hash: magnet.info_hash.clone(),
+                    seeders: max(|c| c.seeders),
+                    leechers: max(|c| c.leechers),
+                    completed: max(|c| c.completed),
+                    trackers_ok: counts.len() as i64,
+                    error,
+                    checked_at,
+                    dead: false,
+                };
+                stats.dead = stats.is_dead();
+                stats
+            })
+            .collect();
+
+        for stats in &stats {
+            match (stats.dead, stats.trackers_ok) {
+                (true, _) => result.dead += 1,
+                (false, 0) => result.unknown += 1,
+                (false, _) => result.alive += 1,
+            }
+        }
+        database::replace_swarm_stats(pool, &item.id, &stats).await?;
+    }
+
+    Ok(result)
+}
+
+/// 定期刷新磁力链接做种人数的后台任务（按设置的间隔和并发数执行）
+pub struct SwarmStatsTask {
+    pool: Pool<Sqlite>,
+    interval: Duration,
+}
+
+impl SwarmStatsTask {
+    pub fn new(pool: Pool<Sqlite>, interval: Duration) -> Self {
+        Self { pool, interval }
+    }
+
+    /// 启动定时任务（每个周期检查一次设置，距上次刷新超过 interval_minutes 时刷新；启动后先等待一个刷新间隔）
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+        let mut last_refresh = Instant::now();
+
+        loop {
+            interval.tick().await;
+            let settings = match database::get_swarm_stats_settings(&self.pool).await {
+                Ok(settings) => settings,
+                Err(e) => {
+                    tracing::warn!("Failed to load swarm stats settings: {}", e);
+                    continue;
+                }
+            };
+            if !settings.enabled || last_refresh.elapsed() < Duration::from_secs(settings.interval_minutes * 60) {
+                continue;
+            }
+            last_refresh = Instant::now();
+            match refresh_swarm_stats(&self.pool, None, settings.concurrency).await {
+                Ok(result) => tracing::info!(
+                    "Swarm stats refreshed: {} magnets, {} trackers, {} alive, {} dead, {} unknown",
+                    result.checked_magnets, result.checked_trackers, result.alive, result.dead, result.unknown
+                ),
+                Err(e) => tracing::warn!("Swarm stats refresh failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrape_url() {
+        assert_eq!(scrape_url("http://t.example/announce").as_deref(), Some("http://t.example/scrape"));
+        assert_eq!(
+            scrape_url("https://t.example/x/announce.php?passkey=1").as_deref(),
+            Some("https://t.example/x/scrape.php?passkey=1")
+        );
+        assert_eq!(scrape_url("http://t.example/a"), None);
+    }
+
+    #[test]
+    fn test_parse_scrape_responses() {
+        let hash = [0xabu8; 20];
+        let mut body = b"d5:filesd20:".to_vec();
+        body.extend(hash);
+        body.extend(b"d8:completei5e10:downloadedi50e10:incompletei7e4:listli1eeeee");
+        let counts = parse_http_scrape(&body).unwrap();
+        assert_eq!(counts[&hash_hex(&hash)], SwarmCounts { seeders: 5, leechers: 7, completed: 50 });
+        assert!(parse_http_scrape(b"d14:failure reason6:bannede").is_err());
+
+        let payload: Vec<u8> = [3u32, 9, 1].iter().flat_map(|v| v.to_be_bytes()).collect();
+        let counts = parse_udp_scrape(&payload, &[hash]);
+        assert_eq!(counts[&hash_hex(&hash)], SwarmCounts { seeders: 3, leechers: 1, completed: 9 });
+    }
+}
@@ -0,0 +1,312 @@
+//! 视频缩略图生成队列
+//!
+//! 缩略图由固定数量的后台任务调用 FFmpeg 生成，请求时不再直接启动 FFmpeg：
+//! - 优先级队列：请求时生成（on_demand）优先于后台补全（backfill），同优先级先进先出
+//! - 同一个缩略图同时只生成一次，后来的请求等待同一个结果；补全中的任务被请求时提升为 on_demand
+//! - 并发数由 THUMBNAIL_WORKERS 配置，默认为 CPU 核数的一半（至少 1，最多 8）；
+//!   FFmpeg 进程同时受全局的 FFmpeg 任务调度限制（见 `services::ffmpeg`）
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, Once};
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Sqlite};
+use tokio::sync::{oneshot, Notify};
+
+use crate::database;
+use crate::models::{HwAccel, ThumbnailBackfillResult, ThumbnailPriority, ThumbnailQueueStats};
+use crate::services::{ffmpeg, hwaccel};
+
+/// 默认最多同时运行的 FFmpeg 进程数
+const MAX_DEFAULT_WORKERS: usize = 8;
+
+lazy_static::lazy_static! {
+    static ref QUEUE: ThumbnailQueue = ThumbnailQueue::new(workers_from_env());
+}
+
+/// 从环境变量 THUMBNAIL_WORKERS 读取并发数
+fn workers_from_env() -> usize {
+    std::env::var("THUMBNAIL_WORKERS")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or_else(|| {
+            let cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+            (cpus / 2).clamp(1, MAX_DEFAULT_WORKERS)
+        })
+}
+
+/// 获取缩略图缓存目录
+fn cache_dir() -> std::io::Result<PathBuf> {
+    // 优先使用环境变量 CACHE_DIR，如果没有设置则使用当前目录
+    let base_dir = std::env::var("CACHE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            // 在开发模式下，使用 Cargo.toml 所在目录（项目根目录）
+            // 在生产模式下，使用当前工作目录
+            if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
+                PathBuf::from(manifest_dir)
+            } else {
+                std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+            }
+        });
+
+    let cache_dir = base_dir.join("cache").join("thumbnails");
+    if !cache_dir.exists() {
+        std::fs::create_dir_all(&cache_dir)?;
+    }
+    Ok(cache_dir)
+}
+
+/// 缓存文件路径（基于视频路径、media_id 和文件索引的哈希）
+pub fn cache_path(video_path: &Path, media_id: &str, file_index: usize) -> std::io::Result<PathBuf> {
+    let mut hasher = Sha256::new();
+    hasher.update(video_path.to_string_lossy().as_bytes());
+    hasher.update(media_id.as_bytes());
+    hasher.update(file_index.to_string().as_bytes());
+    let hash: String = format!("{:x}", hasher.finalize()).chars().take(32).collect();
+    Ok(cache_dir()?.join(format!("{}.jpg", hash)))
+}
+
+/// 使用 FFmpeg 截取第 2 秒的画面（先写入临时文件，成功后再改名，避免读到不完整的缓存）
+async fn run_ffmpeg(video_path: &Path, cache_path: &Path) -> std::io::Result<()> {
+    let invalid = |what: &str| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid {} encoding", what));
+    let partial_path = cache_path.with_extension("part.jpg");
+    let video_path_str = video_path.to_str().ok_or_else(|| invalid("video path"))?;
+    let partial_path_str = partial_path.to_str().ok_or_else(|| invalid("cache path"))?;
+
+    let _permit = ffmpeg::acquire().await;
+    let extract = |accel: Option<HwAccel>| {
+        let decode_args = accel.map(hwaccel::decode_args).unwrap_or_default();
+        ffmpeg::ffmpeg_command(decode_args.iter().map(String::as_str).chain([
+            "-ss", "00:00:02",           // 从第2秒开始
+            "-i", video_path_str,
+            "-vframes", "1",              // 只提取一帧
+            "-update", "1",               // 允许输出单张图片
+            // 不缩放，保持原始分辨率
+            "-q:v", "2",                  // JPEG 质量（高质量）
+            "-y",                         // 覆盖输出文件
+            partial_path_str,
+        ]))
+        .kill_on_drop(true)
+        .output()
+    };
+
+    let accel = hwaccel::selected();
+    let mut output = extract(accel).await?;
+    if !output.status.success() && accel.is_some() {
+        // 硬件解码不支持该视频的编码时退回软件解码
+        tracing::debug!("Hardware decoding failed for {}, retrying in software", video_path.display());
+        output = extract(None).await?;
+    }
+
+    if !output.status.success() {
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        tracing::error!("FFmpeg 失败: {}", stderr);
+        return Err(std::io::Error::other(format!("FFmpeg failed to generate thumbnail: {}", stderr)));
+    }
+    tokio::fs::rename(&partial_path, cache_path).await
+}
+
+type Waiter = oneshot::Sender<Result<(), String>>;
+
+struct PendingJob {
+    video_path: PathBuf,
+    priority: ThumbnailPriority,
+    waiters: Vec<Waiter>,
+}
+
+/// 待生成的任务（以缓存路径为键）和正在生成的任务的等待者
+#[derive(Default)]
+struct PendingQueue {
+    /// (优先级, 入队顺序, 键)；提升优先级时插入新条目，旧条目出队时跳过
+    heap: BinaryHeap<(ThumbnailPriority, Reverse<u64>, PathBuf)>,
+    pending: HashMap<PathBuf, PendingJob>,
+    running: HashMap<PathBuf, Vec<Waiter>>,
+    next_seq: u64,
+}
+
+impl PendingQueue {
+    /// 加入队列，返回是否新增了任务（已在队列中或正在生成时只登记等待者）
+    fn push(&mut self, cache_path: PathBuf, video_path: PathBuf, priority: ThumbnailPriority, waiter: Option<Waiter>) -> bool {
+        if let Some(waiters) = self.running.get_mut(&cache_path) {
+            waiters.extend(waiter);
+            return false;
+        }
+
+        self.next_seq += 1;
+        let seq = Reverse(self.next_seq);
+        if let Some(job) = self.pending.get_mut(&cache_path) {
+            job.waiters.extend(waiter);
+            if priority > job.priority {
+                job.priority = priority;
+                self.heap.push((priority, seq, cache_path));
+            }
+            return false;
+        }
+
+        self.heap.push((priority, seq, cache_path.clone()));
+        self.pending.insert(cache_path, PendingJob { video_path, priority, waiters: waiter.into_iter().collect() });
+        true
+    }
+
+    /// 取出优先级最高的任务，返回 (缓存路径, 视频路径)
+    fn pop(&mut self) -> Option<(PathBuf, PathBuf)> {
+        while let Some((priority, _, cache_path)) = self.heap.pop() {
+            if self.pending.get(&cache_path).is_some_and(|job| job.priority == priority) {
+                let job = self.pending.remove(&cache_path)?;
+                self.running.insert(cache_path.clone(), job.waiters);
+                return Some((cache_path, job.video_path));
+            }
+        }
+        None
+    }
+
+    /// 任务结束，返回需要通知的等待者
+    fn finish(&mut self, cache_path: &Path) -> Vec<Waiter> {
+        self.running.remove(cache_path).unwrap_or_default()
+    }
+
+    fn pending_count(&self, priority: ThumbnailPriority) -> usize {
+        self.pending.values().filter(|job| job.priority == priority).count()
+    }
+}
+
+/// 缩略图生成队列（首次加入任务时启动工作任务）
+struct ThumbnailQueue {
+    workers: usize,
+    state: Mutex<PendingQueue>,
+    notify: Notify,
+    started: Once,
+    completed: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl ThumbnailQueue {
+    fn new(workers: usize) -> Self {
+        Self {
+            workers,
+            state: Mutex::new(PendingQueue::default()),
+            notify: Notify::new(),
+            started: Once::new(),
+            completed: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        }
+    }
+
+    fn push(&'static self, cache_path: PathBuf, video_path: PathBuf, priority: ThumbnailPriority, waiter: Option<Waiter>) {
+        self.started.call_once(|| {
+            tracing::info!("Starting {} thumbnail workers", self.workers);
+            for _ in 0..self.workers {
+                tokio::spawn(self.run_worker());
+            }
+        });
+        let added = self.state.lock().unwrap().push(cache_path, video_path, priority, waiter);
+        if added {
+            self.notify.notify_one();
+        }
+    }
+
+    async fn run_worker(&'static self) {
+        loop {
+            let next = self.state.lock().unwrap().pop();
+            let Some((cache_path, video_path)) = next else {
+                self.notify.notified().await;
+                continue;
+            };
+
+            let result = run_ffmpeg(&video_path, &cache_path).await.map_err(|e| e.to_string());
+            match &result {
+                Ok(()) => self.completed.fetch_add(1, Ordering::Relaxed),
+                Err(_) => self.failed.fetch_add(1, Ordering::Relaxed),
+            };
+            let waiters = self.state.lock().unwrap().finish(&cache_path);
+            for waiter in waiters {
+                let _ = waiter.send(result.clone());
+            }
+        }
+    }
+
+    fn stats(&self) -> ThumbnailQueueStats {
+        let state = self.state.lock().unwrap();
+        ThumbnailQueueStats {
+            workers: self.workers,
+            pending_on_demand: state.pending_count(ThumbnailPriority::OnDemand),
+            pending_backfill: state.pending_count(ThumbnailPriority::Backfill),
+            running: state.running.len(),
+            completed: self.completed.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 获取缩略图：有缓存时直接读取，否则以 on_demand 优先级加入队列并等待生成完成
+pub async fn get_thumbnail(video_path: &Path, media_id: &str, file_index: usize) -> std::io::Result<Vec<u8>> {
+    let cache_path = cache_path(video_path, media_id, file_index)?;
+    if !cache_path.exists() {
+        let (sender, receiver) = oneshot::channel();
+        QUEUE.push(cache_path.clone(), video_path.to_path_buf(), ThumbnailPriority::OnDemand, Some(sender));
+        receiver.await
+            .map_err(|_| std::io::Error::other("Thumbnail worker stopped"))?
+            .map_err(std::io::Error::other)?;
+    }
+    tokio::fs::read(&cache_path).await
+}
+
+/// 为每个媒体的第一个文件补全缺失的缩略图（以 backfill 优先级加入队列，不等待生成）
+pub async fn backfill(pool: &Pool<Sqlite>) -> Result<ThumbnailBackfillResult> {
+    let mut result = ThumbnailBackfillResult::default();
+    for (media_id, file_path) in database::list_primary_media_files(pool).await? {
+        let video_path = PathBuf::from(file_path);
+        if !video_path.exists() {
+            result.missing += 1;
+            continue;
+        }
+        let cache_path = cache_path(&video_path, &media_id, 0)?;
+        if cache_path.exists() {
+            result.cached += 1;
+            continue;
+        }
+        QUEUE.push(cache_path, video_path, ThumbnailPriority::Backfill, None);
+        result.queued += 1;
+    }
+    Ok(result)
+}
+
+/// 队列状态
+pub fn queue_stats() -> ThumbnailQueueStats {
+    QUEUE.stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_queue() {
+        let mut queue = PendingQueue::default();
+        let path = PathBuf::from;
+        assert!(queue.push(path("a"), path("a.mp4"), ThumbnailPriority::Backfill, None));
+        assert!(queue.push(path("b"), path("b.mp4"), ThumbnailPriority::Backfill, None));
+        assert!(queue.push(path("c"), path("c.mp4"), ThumbnailPriority::OnDemand, None));
+        // 请求补全中的任务：不重复加入，提升为 on_demand（排在已有的 on_demand 之后）
+        let (sender, _receiver) = oneshot::channel();
+        assert!(!queue.push(path("b"), path("b.mp4"), ThumbnailPriority::OnDemand, Some(sender)));
+        assert_eq!(queue.pending_count(ThumbnailPriority::OnDemand), 2);
+
+        assert_eq!(queue.pop().map(|(key, _)| key), Some(path("c")));
+        assert_eq!(queue.pop().map(|(key, _)| key), Some(path("b")));
+        // 正在生成的任务只登记等待者
+        let (sender, _receiver) = oneshot::channel();
+        assert!(!queue.push(path("b"), path("b.mp4"), ThumbnailPriority::OnDemand, Some(sender)));
+        assert_eq!(queue.finish(&path("b")).len(), 2);
+
+        assert_eq!(queue.pop().map(|(key, _)| key), Some(path("a")));
+        assert_eq!(queue.pop(), None);
+    }
+}
@@ -0,0 +1,137 @@
+//! TMDB 热门/趋势列表本地缓存
+//!
+//! 每天在设置的时间预取配置的列表页并保存到数据库。查询时缓存未超过 max_age_hours 直接返回，
+//! 否则请求 TMDB，请求失败（包括未配置 TMDB）时退回最近一次缓存的结果
+
+use std::time::Duration;
+use anyhow::Result;
+use chrono::{Timelike, Utc};
+use sqlx::{Pool, Sqlite};
+
+use crate::database;
+use crate::external::ExternalApiClient;
+use crate::models::{
+    TmdbListCacheSettings, TmdbListContent, TmdbListPage, TmdbListPrefetchResult, TMDB_LISTS, TMDB_LIST_MEDIA_TYPES,
+};
+
+/// 获取一页列表；既没有可用缓存又无法请求 TMDB 时返回 None
+pub async fn get_list_page(
+    pool: &Pool<Sqlite>,
+    client: &ExternalApiClient,
+    list: &str,
+    media_type: &str,
+    page: u32,
+) -> Result<Option<TmdbListContent>> {
+    let settings = database::get_tmdb_list_cache_settings(pool).await?;
+    let cached = database::get_tmdb_list_page(pool, list, media_type, page).await?;
+    let max_age = chrono::Duration::hours(settings.max_age_hours as i64);
+    let is_stale = |page: &TmdbListPage| Utc::now() - page.fetched_at > max_age;
+
+    if let Some(cached) = cached.as_ref().filter(|c| !is_stale(c)) {
+        return Ok(Some(content(cached.clone(), "cache", false)));
+    }
+
+    if client.is_tmdb_available() {
+        match client.get_list(list, media_type, page).await {
+            Ok(items) => {
+                if settings.enabled && settings.covers(list, media_type, page) {
+                    database::save_tmdb_list_page(pool, list, media_type, page, &items).await?;
+                }
+                let fetched = TmdbListPage {
+                    list: list.to_string(),
+                    media_type: media_type.to_string(),
+                    page,
+                    results: serde_json::to_value(&items)?,
+                    fetched_at: Utc::now(),
+                };
+                return Ok(Some(content(fetched, "tmdb", false)));
+            }
+            Err(e) => tracing::warn!("Failed to fetch TMDB {} {} page {}: {}", list, media_type, page, e),
+        }
+    }
+
+    Ok(cached.map(|cached| {
+        let stale = is_stale(&cached);
+        content(cached, "cache", stale)
+    }))
+}
+
+fn content(page: TmdbListPage, source: &str, stale: bool) -> TmdbListContent {
+    TmdbListContent {
+        total: page.results.as_array().map_or(0, Vec::len),
+        page,
+        source: source.to_string(),
+        stale,
+    }
+}
+
+/// 按设置预取全部列表页并保存（不受 enabled 影响，用于手动预取和定时任务）
+pub async fn prefetch(
+    pool: &Pool<Sqlite>,
+    client: &ExternalApiClient,
+    settings: &TmdbListCacheSettings,
+) -> Result<TmdbListPrefetchResult> {
+    let mut result = TmdbListPrefetchResult::default();
+    for list in TMDB_LISTS {
+        for media_type in TMDB_LIST_MEDIA_TYPES {
+            for page in 1..=settings.pages {
+                if !settings.covers(list, media_type, page) {
+                    continue;
+                }
+                match client.get_list(list, media_type, page).await {
+                    Ok(items) => {
+                        database::save_tmdb_list_page(pool, list, media_type, page, &items).await?;
+                        result.fetched += 1;
+                    }
+                    Err(e) => {
+                        result.failed += 1;
+                        result.errors.push(format!("{} {} page {}: {}", list, media_type, page, e));
+                    }
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// 每天在设置的时间预取 TMDB 列表的后台任务
+pub struct TmdbListPrefetchTask {
+    pool: Pool<Sqlite>,
+    client: ExternalApiClient,
+    interval: Duration,
+}
+
+impl TmdbListPrefetchTask {
+    pub fn new(pool: Pool<Sqlite>, client: ExternalApiClient, interval: Duration) -> Self {
+        Self { pool, client, interval }
+    }
+
+    /// 启动定时任务（每个周期检查一次，到达 refresh_hour 时预取）
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+            if !self.client.is_tmdb_available() {
+                continue;
+            }
+            let settings = match database::get_tmdb_list_cache_settings(&self.pool).await {
+                Ok(settings) => settings,
+                Err(e) => {
+                    tracing::warn!("Failed to load TMDB list cache settings: {}", e);
+                    continue;
+                }
+            };
+            if !settings.enabled || Utc::now().hour() != settings.refresh_hour {
+                continue;
+            }
+            match prefetch(&self.pool, &self.client, &settings).await {
+                Ok(result) => tracing::info!(
+                    "TMDB list prefetch completed: {} pages fetched, {} failed",
+                    result.fetched, result.failed
+                ),
+                Err(e) => tracing::warn!("TMDB list prefetch failed: {}", e),
+            }
+        }
+    }
+}
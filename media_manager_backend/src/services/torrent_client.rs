@@ -0,0 +1,157 @@
+//! 下载客户端集成
+//!
+//! 目前支持 qBittorrent Web API，通过环境变量配置：
+//! - TORRENT_CLIENT_URL: Web UI 地址，如 http://127.0.0.1:8080
+//! - TORRENT_CLIENT_USERNAME / TORRENT_CLIENT_PASSWORD: 登录凭据（可选）
+//! - TORRENT_CLIENT_SAVE_PATH: 下载目录（可选）
+
+use anyhow::{anyhow, Result};
+use reqwest::header;
+use serde::Deserialize;
+
+/// 做种/上传中的状态，表示下载已完成
+const COMPLETED_STATES: [&str; 6] = ["uploading", "stalledUP", "pausedUP", "queuedUP", "forcedUP", "checkingUP"];
+
+/// 下载客户端中的任务
+#[derive(Debug, Clone, Deserialize)]
+pub struct TorrentInfo {
+    pub hash: String,
+    pub name: String,
+    pub progress: f64,
+    pub state: String,
+    pub save_path: String,
+}
+
+impl TorrentInfo {
+    pub fn is_complete(&self) -> bool {
+        self.progress >= 1.0 || COMPLETED_STATES.contains(&self.state.as_str())
+    }
+}
+
+/// 任务中的文件（name 为相对 save_path 的路径）
+#[derive(Debug, Clone, Deserialize)]
+pub struct TorrentFile {
+    pub name: String,
+    pub size: i64,
+}
+
+/// qBittorrent 客户端
+#[derive(Debug, Clone)]
+pub struct TorrentClient {
+    client: reqwest::Client,
+    base_url: String,
+    username: Option<String>,
+    password: Option<String>,
+    save_path: Option<String>,
+}
+
+impl TorrentClient {
+    pub fn new(base_url: String, username: Option<String>, password: Option<String>, save_path: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            username,
+            password,
+            save_path,
+        }
+    }
+
+    /// 从环境变量创建，未配置 TORRENT_CLIENT_URL 时返回 None
+    pub fn from_env() -> Option<Self> {
+        let base_url = std::env::var("TORRENT_CLIENT_URL").ok().filter(|s| !s.is_empty())?;
+        Some(Self::new(
+            base_url,
+            std::env::var("TORRENT_CLIENT_USERNAME").ok(),
+            std::env::var("TORRENT_CLIENT_PASSWORD").ok(),
+            std::env::var("TORRENT_CLIENT_SAVE_PATH").ok(),
+        ))
+    }
+
+    /// 登录并返回会话 Cookie（未配置凭据时返回 None）
+    async fn login(&self) -> Result<Option<String>> {
+        let (Some(username), Some(password)) = (&self.username, &self.password) else {
+            return Ok(None);
+        };
+
+        let response = self.client
+            .post(format!("{}/api/v2/auth/login", self.base_url))
+            .header(header::REFERER, &self.base_url)
+            .form(&[("username", username.as_str()), ("password", password.as_str())])
+            .send()
+            .await?;
+
+        let cookie = response.headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find(|v| v.starts_with("SID="))
+            .and_then(|v| v.split(';').next())
+            .map(|s| s.to_string());
+
+        let body = response.text().await.unwrap_or_default();
+        if body.trim() != "Ok." || cookie.is_none() {
+            return Err(anyhow!("Torrent client login failed: {}", body.trim()));
+        }
+
+        Ok(cookie)
+    }
+
+    /// 带上 Referer 和会话 Cookie
+    fn with_session(&self, request: reqwest::RequestBuilder, cookie: Option<String>) -> reqwest::RequestBuilder {
+        let request = request.header(header::REFERER, &self.base_url);
+        match cookie {
+            Some(cookie) => request.header(header::COOKIE, cookie),
+            None => request,
+        }
+    }
+
+    /// 添加磁力链接到下载客户端
+    pub async fn add_magnet(&self, magnet: &str) -> Result<()> {
+        let cookie = self.login().await?;
+
+        let mut form = vec![("urls", magnet.to_string())];
+        if let Some(ref save_path) = self.save_path {
+            form.push(("savepath", save_path.clone()));
+        }
+
+        let request = self.client
+            .post(format!("{}/api/v2/torrents/add", self.base_url))
+            .form(&form);
+
+        let response = self.with_session(request, cookie).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Torrent client returned status {}", response.status()));
+        }
+
+        tracing::info!("已推送磁力链接到下载客户端");
+        Ok(())
+    }
+
+    /// 查询指定任务的状态（客户端中不存在的任务不会返回）
+    pub async fn list_torrents(&self, hashes: &[String]) -> Result<Vec<TorrentInfo>> {
+        let cookie = self.login().await?;
+        let request = self.client
+            .get(format!("{}/api/v2/torrents/info", self.base_url))
+            .query(&[("hashes", hashes.join("|"))]);
+
+        let response = self.with_session(request, cookie).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Torrent client returned status {}", response.status()));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// 获取任务的文件列表
+    pub async fn torrent_files(&self, hash: &str) -> Result<Vec<TorrentFile>> {
+        let cookie = self.login().await?;
+        let request = self.client
+            .get(format!("{}/api/v2/torrents/files", self.base_url))
+            .query(&[("hash", hash)]);
+
+        let response = self.with_session(request, cookie).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Torrent client returned status {}", response.status()));
+        }
+        Ok(response.json().await?)
+    }
+}
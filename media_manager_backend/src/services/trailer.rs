@@ -0,0 +1,91 @@
+//! 预告片播放地址解析
+//!
+//! 视频直链直接经 /api/proxy/video 代理播放；YouTube/Vimeo 页面地址提供嵌入地址，
+//! 启用 `yt-dlp` 特性时可调用本机 yt-dlp（`YTDLP_PATH`，默认 `yt-dlp`）解析为直链后代理播放
+
+use anyhow::{anyhow, Result};
+
+/// 可直接播放的视频扩展名
+const DIRECT_VIDEO_EXTENSIONS: [&str; 5] = ["mp4", "webm", "m4v", "mov", "m3u8"];
+
+/// 是否为视频直链
+pub fn is_direct_video(url: &str) -> bool {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.path().rsplit('.').next().map(str::to_lowercase))
+        .is_some_and(|ext| DIRECT_VIDEO_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// YouTube/Vimeo 页面地址对应的嵌入播放地址
+pub fn embed_url(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.trim_start_matches("www.").trim_start_matches("m.");
+    match host {
+        "youtube.com" => {
+            let id = parsed.query_pairs().find(|(k, _)| k == "v").map(|(_, v)| v.to_string())?;
+            Some(format!("https://www.youtube.com/embed/{}", id))
+        }
+        "youtu.be" => {
+            let id = parsed.path().trim_start_matches('/');
+            (!id.is_empty()).then(|| format!("https://www.youtube.com/embed/{}", id))
+        }
+        "vimeo.com" => {
+            let id = parsed.path().trim_start_matches('/');
+            (!id.is_empty()).then(|| format!("https://player.vimeo.com/video/{}", id))
+        }
+        _ => None,
+    }
+}
+
+/// 是否启用了 yt-dlp 解析
+pub fn ytdlp_enabled() -> bool {
+    cfg!(feature = "yt-dlp")
+}
+
+/// 解析可代理播放的视频直链
+pub async fn resolve_stream_url(url: &str) -> Result<String> {
+    if is_direct_video(url) {
+        return Ok(url.to_string());
+    }
+    resolve_with_ytdlp(url).await
+}
+
+#[cfg(feature = "yt-dlp")]
+async fn resolve_with_ytdlp(url: &str) -> Result<String> {
+    let program = std::env::var("YTDLP_PATH").unwrap_or_else(|_| "yt-dlp".to_string());
+    let output = tokio::process::Command::new(&program)
+        .args(["-g", "-f", "best[ext=mp4]/best", "--no-playlist", url])
+        .output()
+        .await
+        .map_err(|e| anyhow!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(anyhow!("yt-dlp failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with("http"))
+        .map(String::from)
+        .ok_or_else(|| anyhow!("yt-dlp returned no stream URL"))
+}
+
+#[cfg(not(feature = "yt-dlp"))]
+async fn resolve_with_ytdlp(url: &str) -> Result<String> {
+    Err(anyhow!("Not a direct video URL and yt-dlp support is not enabled: {}", url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embed_and_direct() {
+        assert_eq!(embed_url("https://www.youtube.com/watch?v=abc123").unwrap(), "https://www.youtube.com/embed/abc123");
+        assert_eq!(embed_url("https://youtu.be/abc123").unwrap(), "https://www.youtube.com/embed/abc123");
+        assert_eq!(embed_url("https://vimeo.com/42").unwrap(), "https://player.vimeo.com/video/42");
+        assert!(embed_url("https://example.com/trailer.mp4").is_none());
+
+        assert!(is_direct_video("https://cdn.example.com/t/trailer.MP4?token=1"));
+        assert!(!is_direct_video("https://www.youtube.com/watch?v=abc123"));
+    }
+}
@@ -0,0 +1,173 @@
+//! 标题转写：生成排序键和可搜索的罗马字形式
+//!
+//! - 假名按平文式罗马字转写（ちひろ → chihiro，シャ → sha）
+//! - GB2312 一级汉字转为拼音首字母（千与千寻 → qyqx），其余汉字保持原样
+//! - 其他字符转小写
+
+/// 平假名 → 罗马字（片假名先转换为平假名）
+const KANA_ROMAJI: &[(char, &str)] = &[
+    ('あ', "a"), ('い', "i"), ('う', "u"), ('え', "e"), ('お', "o"),
+    ('か', "ka"), ('き', "ki"), ('く', "ku"), ('け', "ke"), ('こ', "ko"),
+    ('さ', "sa"), ('し', "shi"), ('す', "su"), ('せ', "se"), ('そ', "so"),
+    ('た', "ta"), ('ち', "chi"), ('つ', "tsu"), ('て', "te"), ('と', "to"),
+    ('な', "na"), ('に', "ni"), ('ぬ', "nu"), ('ね', "ne"), ('の', "no"),
+    ('は', "ha"), ('ひ', "hi"), ('ふ', "fu"), ('へ', "he"), ('ほ', "ho"),
+    ('ま', "ma"), ('み', "mi"), ('む', "mu"), ('め', "me"), ('も', "mo"),
+    ('や', "ya"), ('ゆ', "yu"), ('よ', "yo"),
+    ('ら', "ra"), ('り', "ri"), ('る', "ru"), ('れ', "re"), ('ろ', "ro"),
+    ('わ', "wa"), ('ゐ', "i"), ('ゑ', "e"), ('を', "o"), ('ん', "n"),
+    ('が', "ga"), ('ぎ', "gi"), ('ぐ', "gu"), ('げ', "ge"), ('ご', "go"),
+    ('ざ', "za"), ('じ', "ji"), ('ず', "zu"), ('ぜ', "ze"), ('ぞ', "zo"),
+    ('だ', "da"), ('ぢ', "ji"), ('づ', "zu"), ('で', "de"), ('ど', "do"),
+    ('ば', "ba"), ('び', "bi"), ('ぶ', "bu"), ('べ', "be"), ('ぼ', "bo"),
+    ('ぱ', "pa"), ('ぴ', "pi"), ('ぷ', "pu"), ('ぺ', "pe"), ('ぽ', "po"),
+    ('ぁ', "a"), ('ぃ', "i"), ('ぅ', "u"), ('ぇ', "e"), ('ぉ', "o"),
+    ('ゃ', "ya"), ('ゅ', "yu"), ('ょ', "yo"), ('ゎ', "wa"), ('ゔ', "vu"),
+];
+
+/// GB2312 一级汉字（按拼音排序）各首字母的起始编码
+const GB2312_INITIALS: &[(u16, char)] = &[
+    (0xB0A1, 'a'), (0xB0C5, 'b'), (0xB2C1, 'c'), (0xB4EE, 'd'), (0xB6EA, 'e'),
+    (0xB7A2, 'f'), (0xB8C1, 'g'), (0xB9FE, 'h'), (0xBBF7, 'j'), (0xBFA6, 'k'),
+    (0xC0AC, 'l'), (0xC2E8, 'm'), (0xC4C3, 'n'), (0xC5B6, 'o'), (0xC5BE, 'p'),
+    (0xC6DA, 'q'), (0xC8BB, 'r'), (0xC8F6, 's'), (0xCBFA, 't'), (0xCDDA, 'w'),
+    (0xCEF4, 'x'), (0xD1B9, 'y'), (0xD4D1, 'z'),
+];
+
+/// GB2312 一级汉字的结束编码
+const GB2312_LEVEL1_END: u16 = 0xD7F9;
+
+/// 片假名转平假名（长音符等不在范围内的字符原样返回）
+fn to_hiragana(c: char) -> char {
+    match c {
+        'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+fn kana_romaji(c: char) -> Option<&'static str> {
+    let c = to_hiragana(c);
+    KANA_ROMAJI.iter().find(|(k, _)| *k == c).map(|(_, r)| *r)
+}
+
+/// 汉字的拼音首字母（仅 GB2312 一级汉字）
+pub fn pinyin_initial(c: char) -> Option<char> {
+    let mut buf = [0u8; 4];
+    let (bytes, _, had_errors) = encoding_rs::GBK.encode(c.encode_utf8(&mut buf));
+    if had_errors || bytes.len() != 2 {
+        return None;
+    }
+    let code = u16::from_be_bytes([bytes[0], bytes[1]]);
+    if !(GB2312_INITIALS[0].0..=GB2312_LEVEL1_END).contains(&code) {
+        return None;
+    }
+    GB2312_INITIALS.iter().rev().find(|(start, _)| code >= *start).map(|(_, initial)| *initial)
+}
+
+/// 转写为罗马字形式（小写）
+pub fn romanize(text: &str) -> String {
+    romanize_with(text, true)
+}
+
+/// 只转写假名，汉字保持原样（用于比较人名，拼音首字母会把不同的汉字名混为一谈）
+pub fn romanize_kana(text: &str) -> String {
+    romanize_with(text, false)
+}
+
+fn romanize_with(text: &str, hanzi_initials: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match to_hiragana(c) {
+            // 促音：重复下一个音节的首辅音
+            'っ' => {
+                if let Some(next) = chars.get(i + 1).and_then(|n| kana_romaji(*n)) {
+                    out.push_str(&next[..1]);
+                }
+            }
+            // 长音符：省略
+            'ー' => {}
+            h if kana_romaji(h).is_some() => {
+                let mut romaji = kana_romaji(h).unwrap_or_default().to_string();
+                // 拗音：きゃ → kya，しゃ → sha
+                if let Some(small @ ('ゃ' | 'ゅ' | 'ょ')) = chars.get(i + 1).map(|n| to_hiragana(*n)) {
+                    if romaji.len() > 1 && romaji.ends_with('i') {
+                        romaji.pop();
+                        let y = kana_romaji(small).unwrap_or_default();
+                        if matches!(romaji.as_str(), "sh" | "ch" | "j") {
+                            romaji.push_str(&y[1..]);
+                        } else {
+                            romaji.push_str(y);
+                        }
+                        i += 1;
+                    }
+                }
+                out.push_str(&romaji);
+            }
+            _ => match pinyin_initial(c).filter(|_| hanzi_initials) {
+                Some(initial) => out.push(initial),
+                None => out.extend(c.to_lowercase()),
+            },
+        }
+        i += 1;
+    }
+    out
+}
+
+/// 是否包含需要转写的字符（假名或汉字）
+pub fn needs_transliteration(text: &str) -> bool {
+    text.chars().any(|c| kana_romaji(c).is_some() || matches!(c, 'っ' | 'ッ' | '\u{4E00}'..='\u{9FFF}'))
+}
+
+/// 可搜索的罗马字形式：标题和原标题中含假名/汉字的部分转写后以空格连接，都不需要转写时返回 None
+pub fn searchable_form(title: &str, original_title: Option<&str>) -> Option<String> {
+    let forms: Vec<String> = std::iter::once(title)
+        .chain(original_title)
+        .filter(|t| needs_transliteration(t))
+        .map(romanize)
+        .collect();
+    (!forms.is_empty()).then(|| forms.join(" "))
+}
+
+/// 排序键：转写后的小写标题，去掉开头的标点和空白，并附上原标题保证同音时顺序稳定
+pub fn sort_key(title: &str) -> String {
+    let romanized = romanize(title);
+    let trimmed = romanized.trim_start_matches(|c: char| !c.is_alphanumeric());
+    format!("{}\u{1}{}", trimmed, title.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_romanize_kana() {
+        assert_eq!(romanize("ちひろ"), "chihiro");
+        assert_eq!(romanize("トトロ"), "totoro");
+        assert_eq!(romanize("きゃりーぱみゅぱみゅ"), "kyaripamyupamyu");
+        assert_eq!(romanize("しゃしん"), "shashin");
+        assert_eq!(romanize("がっこう"), "gakkou");
+    }
+
+    #[test]
+    fn test_romanize_hanzi() {
+        assert_eq!(pinyin_initial('千'), Some('q'));
+        assert_eq!(pinyin_initial('阿'), Some('a'));
+        assert_eq!(pinyin_initial('座'), Some('z'));
+        assert_eq!(pinyin_initial('A'), None);
+        assert_eq!(romanize("千与千寻 2001"), "qyqx 2001");
+        assert_eq!(romanize_kana("波多野ゆい"), "波多野yui");
+    }
+
+    #[test]
+    fn test_sort_key_and_search() {
+        let mut titles = vec!["Zebra", "阿凡达", "\"Batman\"", "千与千寻"];
+        titles.sort_by_key(|t| sort_key(t));
+        assert_eq!(titles, vec!["阿凡达", "\"Batman\"", "千与千寻", "Zebra"]);
+
+        assert_eq!(searchable_form("Spirited Away", Some("千と千尋の神隠し")).as_deref(), Some("qtoq尋nos隠shi"));
+        assert_eq!(searchable_form("Avatar", None), None);
+    }
+}
@@ -0,0 +1,367 @@
+//! 想要列表磁力监控服务
+//!
+//! 定时为想要列表中的媒体搜索磁力链接，按质量过滤条件和质量配置挑选资源，
+//! 根据条目设置记录通知（found）或自动推送到下载客户端（pushed）。
+//! 已有本地文件的条目只做升级检测：未达到期望质量时记录到可升级列表
+
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::RwLock;
+
+use crate::database;
+use crate::models::{MagnetQualityFilter, MediaUpgrade, QualityProfile, ReleaseQuality, WantedMediaWithInfo};
+use crate::plugins::manager::PluginManager;
+use crate::plugins::protocol::MagnetResult;
+use super::magnet;
+use super::torrent_client::TorrentClient;
+use super::quality::{best_quality_of, default_profile, detect_quality, is_upgrade, meets_cutoff, meets_minimum, quality_score};
+
+/// 未指定插件时使用的默认磁力插件
+pub const DEFAULT_MAGNET_PLUGIN: &str = "multi-site-magnet";
+
+/// 监控结果
+#[derive(Debug, Default, serde::Serialize)]
+pub struct WantedCheckResult {
+    pub checked: usize,
+    pub found: usize,
+    pub pushed: usize,
+    pub upgrades: usize,
+    pub errors: Vec<String>,
+}
+
+/// 解析体积字符串为 MB（支持 "1.5 GB"、"700MB"、"850.3 MiB" 等）
+pub fn parse_size_mb(size: &str) -> Option<f64> {
+    let size = size.trim().replace(',', "");
+    let split = size.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+    let (number, unit) = size.split_at(split);
+    let value: f64 = number.parse().ok()?;
+
+    let factor = match unit.trim().to_uppercase().as_str() {
+        "B" => 1.0 / (1024.0 * 1024.0),
+        "KB" | "KIB" | "K" => 1.0 / 1024.0,
+        "MB" | "MIB" | "M" => 1.0,
+        "GB" | "GIB" | "G" => 1024.0,
+        "TB" | "TIB" | "T" => 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(value * factor)
+}
+
+/// 判断磁力资源是否满足质量过滤条件
+pub fn matches_filter(result: &MagnetResult, filter: &MagnetQualityFilter) -> bool {
+    let title = result.title.to_lowercase();
+
+    if !filter.required_keywords.iter().all(|k| title.contains(&k.to_lowercase())) {
+        return false;
+    }
+    if filter.excluded_keywords.iter().any(|k| title.contains(&k.to_lowercase())) {
+        return false;
+    }
+
+    if filter.min_size_mb.is_some() || filter.max_size_mb.is_some() {
+        // 有体积限制时，无法解析体积的资源视为不满足
+        let Some(size) = result.size.as_deref().and_then(parse_size_mb) else {
+            return false;
+        };
+        if filter.min_size_mb.is_some_and(|min| size < min) {
+            return false;
+        }
+        if filter.max_size_mb.is_some_and(|max| size > max) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// 从搜索结果中挑选满足条件的最佳资源（质量评分优先，其次体积）
+pub fn select_best_magnet<'a>(
+    results: &'a [MagnetResult],
+    filter: &MagnetQualityFilter,
+    profile: &QualityProfile,
+) -> Option<&'a MagnetResult> {
+    let size_of = |r: &MagnetResult| r.size.as_deref().and_then(parse_size_mb).unwrap_or(0.0);
+
+    results.iter()
+        .filter(|r| matches_filter(r, filter) && meets_minimum(&detect_quality(&r.title), profile))
+        .max_by(|a, b| {
+            let score_a = quality_score(&detect_quality(&a.title), profile);
+            let score_b = quality_score(&detect_quality(&b.title), profile);
+            score_a.cmp(&score_b).then(size_of(a).total_cmp(&size_of(b)))
+        })
+}
+
+/// 生成搜索关键词：优先使用自定义关键词，其次番号，最后标题
+fn search_query_for(item: &WantedMediaWithInfo) -> String {
+    item.wanted.search_query.clone()
+        .filter(|q| !q.trim().is_empty())
+        .or_else(|| item.code.clone().filter(|c| !c.trim().is_empty()))
+        .unwrap_or_else(|| item.title.clone())
+}
+
+/// 记录推送的任务，下载完成后由 DownloadImportTask 自动导入
+pub async fn track_download(pool: &Pool<Sqlite>, media_id: &str, title: &str, magnet: &str) -> Result<()> {
+    match magnet::info_hash(magnet) {
+        Some(hash) => database::record_torrent_download(pool, &hash, media_id, Some(title), magnet).await,
+        None => {
+            tracing::warn!("无法从磁力链接解析 info hash，'{}' 不会自动导入", title);
+            Ok(())
+        }
+    }
+}
+
+/// 检查所有监控中的条目
+pub async fn check_wanted_media(
+    pool: &Pool<Sqlite>,
+    plugin_manager: &Arc<RwLock<PluginManager>>,
+    torrent_client: Option<&TorrentClient>,
+) -> Result<WantedCheckResult> {
+    let items = database::list_wanted_for_monitoring(pool).await?;
+    let profile = default_profile(&database::get_quality_profiles(pool).await?);
+    let trackers = database::get_magnet_tracker_settings(pool).await?;
+    let mut result = WantedCheckResult::default();
+
+    for item in items {
+        let media_id = item.wanted.media_id.clone();
+
+        // 已有本地文件：达到期望质量则移出想要列表，否则只做升级检测
+        // 单个条目的数据库错误只记录，不影响其余条目
+        let local_paths = match database::get_local_file_paths(pool, &media_id).await {
+            Ok(paths) => paths,
+            Err(e) => {
+                tracing::warn!("读取本地文件失败 '{}': {}", item.title, e);
+                result.errors.push(format!("{}: {}", item.title, e));
+                continue;
+            }
+        };
+        let current_quality: Option<ReleaseQuality> = if local_paths.is_empty() {
+            None
+        } else {
+            let quality = best_quality_of(local_paths.iter().map(|p| p.as_str()), &profile);
+            if meets_cutoff(&quality, &profile) {
+                if let Err(e) = database::remove_wanted(pool, &media_id).await {
+                    tracing::warn!("移出想要列表失败 '{}': {}", item.title, e);
+                    result.errors.push(format!("{}: {}", item.title, e));
+                }
+                continue;
+            }
+            Some(quality)
+        };
+
+        let query = search_query_for(&item);
+        let plugin_id = item.wanted.plugin_id.clone()
+            .unwrap_or_else(|| DEFAULT_MAGNET_PLUGIN.to_string());
+
+        let magnets = {
+            let manager = plugin_manager.read().await;
+            manager.search_magnets(&plugin_id, &query).await
+        };
+        if let Err(e) = database::mark_wanted_searched(pool, &media_id).await {
+            tracing::warn!("更新搜索时间失败 '{}': {}", item.title, e);
+        }
+        result.checked += 1;
+
+        let magnets = match magnets {
+            Ok(m) => {
+                let mut magnets = magnet::dedup(m);
+                magnet::apply_trackers(&mut magnets, &trackers);
+                magnets
+            }
+            Err(e) => {
+                tracing::warn!("磁力搜索失败 '{}': {}", query, e);
+                result.errors.push(format!("{}: {}", query, e));
+                continue;
+            }
+        };
+
+        let filter = item.wanted.get_quality_filter();
+        let Some(best) = select_best_magnet(&magnets, &filter, &profile) else {
+            continue;
+        };
+
+        if let Some(current) = current_quality {
+            let candidate = detect_quality(&best.title);
+            if is_upgrade(&current, &candidate, &profile) {
+                let upgrade = MediaUpgrade {
+                    media_id: media_id.clone(),
+                    current_quality: Some(current.label()),
+                    available_quality: candidate.label(),
+                    source: "magnet".to_string(),
+                    source_title: best.title.clone(),
+                    magnet_link: Some(best.magnet_link.clone()),
+                    file_path: None,
+                    detected_at: chrono::Utc::now(),
+                };
+                match database::record_upgrade(pool, &upgrade).await {
+                    Ok(_) => {
+                        tracing::info!("⬆️ 发现可升级资源: {} ({} -> {})", item.title, current.label(), candidate.label());
+                        result.upgrades += 1;
+                    }
+                    Err(e) => {
+                        tracing::warn!("记录可升级资源失败 '{}': {}", item.title, e);
+                        result.errors.push(format!("{}: {}", item.title, e));
+                    }
+                }
+            }
+            continue;
+        }
+
+        // 自动推送模式且配置了下载客户端时直接推送，否则仅记录通知
+        let mut status = "found";
+        if item.wanted.action == "auto_push" {
+            match torrent_client {
+                Some(client) => match client.add_magnet(&best.magnet_link).await {
+                    Ok(_) => {
+                        status = "pushed";
+                        if let Err(e) = track_download(pool, &media_id, &best.title, &best.magnet_link).await {
+                            tracing::warn!("记录下载任务失败 '{}': {}", item.title, e);
+                            result.errors.push(format!("{}: {}", item.title, e));
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("推送到下载客户端失败 '{}': {}", item.title, e);
+                        result.errors.push(format!("{}: {}", item.title, e));
+                    }
+                },
+                None => tracing::warn!("未配置下载客户端（TORRENT_CLIENT_URL），'{}' 仅记录通知", item.title),
+            }
+        }
+
+        if let Err(e) = database::mark_wanted_found(pool, &media_id, status, &best.title, &best.magnet_link, best.size.as_deref()).await {
+            tracing::warn!("记录找到的资源失败 '{}': {}", item.title, e);
+            result.errors.push(format!("{}: {}", item.title, e));
+        }
+        tracing::info!("🧲 想要列表找到资源: {} -> {}", item.title, best.title);
+        result.found += 1;
+        if status == "pushed" {
+            result.pushed += 1;
+        }
+    }
+
+    Ok(result)
+}
+
+/// 想要列表定时监控任务
+pub struct WantedMonitorTask {
+    pool: Pool<Sqlite>,
+    plugin_manager: Arc<RwLock<PluginManager>>,
+    torrent_client: Option<TorrentClient>,
+    interval: Duration,
+}
+
+impl WantedMonitorTask {
+    pub fn new(
+        pool: Pool<Sqlite>,
+        plugin_manager: Arc<RwLock<PluginManager>>,
+        torrent_client: Option<TorrentClient>,
+        interval: Duration,
+    ) -> Self {
+        Self { pool, plugin_manager, torrent_client, interval }
+    }
+
+    /// 启动定期监控任务
+    pub async fn start(self) {
+        let mut interval = tokio::time::interval(self.interval);
+
+        loop {
+            interval.tick().await;
+            match check_wanted_media(&self.pool, &self.plugin_manager, self.torrent_client.as_ref()).await {
+                Ok(result) => tracing::debug!("Wanted monitor completed: {:?}", result),
+                Err(e) => tracing::warn!("Wanted monitor failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn magnet(title: &str, size: Option<&str>) -> MagnetResult {
+        MagnetResult {
+            title: title.to_string(),
+            magnet_link: format!("magnet:?xt=urn:btih:{}", title.len()),
+            size: size.map(|s| s.to_string()),
+            file_count: None,
+            date: None,
+            files: Vec::new(),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_size_mb() {
+        assert_eq!(parse_size_mb("700MB"), Some(700.0));
+        assert_eq!(parse_size_mb("1.5 GB"), Some(1536.0));
+        assert_eq!(parse_size_mb("2 GiB"), Some(2048.0));
+        assert_eq!(parse_size_mb("512 KB"), Some(0.5));
+        assert_eq!(parse_size_mb("1,024 MB"), Some(1024.0));
+        assert_eq!(parse_size_mb("unknown"), None);
+        assert_eq!(parse_size_mb("12"), None);
+    }
+
+    #[test]
+    fn test_matches_filter_keywords() {
+        let filter = MagnetQualityFilter {
+            required_keywords: vec!["1080p".to_string()],
+            excluded_keywords: vec!["cam".to_string()],
+            ..Default::default()
+        };
+        assert!(matches_filter(&magnet("Movie.1080P.WEB", None), &filter));
+        assert!(!matches_filter(&magnet("Movie.720p.WEB", None), &filter));
+        assert!(!matches_filter(&magnet("Movie.1080p.CAM", None), &filter));
+    }
+
+    #[test]
+    fn test_matches_filter_size() {
+        let filter = MagnetQualityFilter {
+            min_size_mb: Some(1024.0),
+            max_size_mb: Some(8192.0),
+            ..Default::default()
+        };
+        assert!(matches_filter(&magnet("a", Some("4.2 GB")), &filter));
+        assert!(!matches_filter(&magnet("a", Some("500 MB")), &filter));
+        assert!(!matches_filter(&magnet("a", Some("20 GB")), &filter));
+        assert!(!matches_filter(&magnet("a", None), &filter));
+    }
+
+    #[test]
+    fn test_select_best_magnet() {
+        let results = vec![
+            magnet("small", Some("1 GB")),
+            magnet("large", Some("6 GB")),
+            magnet("huge", Some("30 GB")),
+        ];
+        let filter = MagnetQualityFilter {
+            max_size_mb: Some(10240.0),
+            ..Default::default()
+        };
+        let profile = QualityProfile::default();
+        assert_eq!(select_best_magnet(&results, &filter, &profile).map(|r| r.title.as_str()), Some("large"));
+        assert!(select_best_magnet(&[], &filter, &profile).is_none());
+    }
+
+    #[test]
+    fn test_select_best_magnet_prefers_quality() {
+        let results = vec![
+            magnet("Movie.720p.x264", Some("8 GB")),
+            magnet("Movie.1080p.x264", Some("4 GB")),
+            magnet("Movie.2160p.x265", Some("3 GB")),
+        ];
+        let profile = QualityProfile {
+            name: "default".to_string(),
+            min_resolution: Some(1080),
+            preferred_codecs: vec!["hevc".to_string()],
+            ..Default::default()
+        };
+        let filter = MagnetQualityFilter::default();
+        assert_eq!(
+            select_best_magnet(&results, &filter, &profile).map(|r| r.title.as_str()),
+            Some("Movie.2160p.x265")
+        );
+        let only_720p = vec![magnet("Movie.720p.x264", Some("8 GB"))];
+        assert!(select_best_magnet(&only_720p, &filter, &profile).is_none());
+    }
+}
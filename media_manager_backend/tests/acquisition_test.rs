@@ -0,0 +1,72 @@
+// TMDB 想要列表到入库的获取流程集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+fn tmdb_media(id: &str, title: &str) -> serde_json::Value {
+    json!({
+        "id": id,
+        "external_ids": { "tmdb_id": 603 },
+        "title": title,
+        "media_type": "Movie",
+    })
+}
+
+#[tokio::test]
+async fn test_saved_tmdb_media_acquisition() {
+    let server = TestServer::start().await;
+    let media_id = "5f0c6f3e-6a43-4a8e-9c55-2f6c2f0a6031";
+
+    let (status, body) = server.post("/api/tmdb/save", tmdb_media(media_id, "The Matrix")).await;
+    assert_eq!(status, 200, "{}", body);
+    let (status, body) = server.get(&format!("/api/media/{}", media_id)).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (status, body) = server.get("/api/acquisition?state=wanted").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["counts"]["wanted"], 1);
+    assert_eq!(body["data"]["items"][0]["media_id"], media_id);
+    assert_eq!(body["data"]["items"][0]["acquisition_status"], "wanted");
+
+    // 已有本地文件的媒体不进入获取流程
+    let local_id = server.create_media("Local Movie", None).await;
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    sqlx::query("UPDATE media_items SET local_file_path = '/movies/local.mkv' WHERE id = ?")
+        .bind(&local_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let mut local = tmdb_media(&local_id, "Local Movie");
+    local["external_ids"] = json!({ "tmdb_id": 604 });
+    let (status, _) = server.post("/api/tmdb/save", local).await;
+    assert_eq!(status, 200);
+    let (_, body) = server.get("/api/acquisition").await;
+    assert_eq!(body["data"]["items"].as_array().unwrap().len(), 1);
+
+    // 重试失败的下载时进入 downloading
+    sqlx::query("INSERT INTO torrent_downloads (info_hash, media_id, magnet_link, status) VALUES ('abc', ?, 'magnet:?xt=urn:btih:abc', 'failed')")
+        .bind(media_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+    let (status, _) = server.post("/api/downloads/abc/retry", json!({})).await;
+    assert_eq!(status, 200);
+    let (_, body) = server.get("/api/acquisition?state=downloading").await;
+    assert_eq!(body["data"]["items"][0]["media_id"], media_id);
+    assert_eq!(body["data"]["counts"]["wanted"], 0);
+
+    // 状态变化进入变更流（对端同步依赖它）
+    let (_, body) = server.get("/api/sync/changes?entity_type=media").await;
+    let change = body["data"]["changes"].as_array().unwrap().iter()
+        .rev()
+        .find(|c| c["entity_id"] == media_id && c["operation"] == "update")
+        .unwrap_or_else(|| panic!("{}", body));
+    assert!(change["changed_fields"].as_array().unwrap().contains(&json!("acquisition_status")), "{}", change);
+    assert_eq!(change["data"]["acquisition_status"], "downloading");
+
+    let (status, _) = server.get("/api/acquisition?state=bogus").await;
+    assert_eq!(status, 422);
+}
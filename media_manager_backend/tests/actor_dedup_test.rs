@@ -0,0 +1,83 @@
+// 重复演员合并建议集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::{json, Value};
+
+async fn create_media_with_cast(server: &TestServer, title: &str, names: &[&str]) -> String {
+    let cast: Vec<Value> = names.iter().map(|name| json!({ "name": name, "role": "Actor" })).collect();
+    let (status, body) = server.post("/api/media", json!({
+        "title": title,
+        "media_type": "Movie",
+        "cast": cast,
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    body["data"]["id"].as_str().unwrap().to_string()
+}
+
+async fn actor_id(server: &TestServer, media_id: &str, name: &str) -> String {
+    let (_, body) = server.get(&format!("/api/media/{}/actors", media_id)).await;
+    body["data"].as_array().unwrap().iter()
+        .find(|a| a["name"] == name)
+        .unwrap_or_else(|| panic!("{} not in {}", name, body))["id"]
+        .as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_duplicate_actor_suggestions_and_merge() {
+    let server = TestServer::start().await;
+
+    let first = create_media_with_cast(&server, "First", &["Yui Hatano"]).await;
+    let second = create_media_with_cast(&server, "Second", &["Yui Hatano", "Hatano Yui"]).await;
+    let third = create_media_with_cast(&server, "Third", &["はたのゆい", "Someone Else"]).await;
+    let target_id = actor_id(&server, &first, "Yui Hatano").await;
+    let reversed_id = actor_id(&server, &second, "Hatano Yui").await;
+    let kana_id = actor_id(&server, &third, "はたのゆい").await;
+
+    let (status, _) = server.put(&format!("/api/favorites/actor/{}", kana_id), json!({})).await;
+    assert_eq!(status, 200);
+
+    let (status, body) = server.get("/api/actors/duplicate-suggestions").await;
+    assert_eq!(status, 200, "{}", body);
+    let report = &body["data"];
+    assert_eq!(report["actors_scanned"], 4);
+    let groups = report["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 1, "{}", report);
+    assert_eq!(groups[0]["target_id"], target_id.as_str());
+    assert_eq!(groups[0]["actors"].as_array().unwrap().len(), 3);
+    assert_eq!(groups[0]["reasons"], json!(["kana_romaji", "name_order"]));
+
+    let (status, _) = server.post("/api/actors/duplicate-suggestions/merge", json!({
+        "groups": [{ "target_id": target_id, "source_ids": [] }],
+    })).await;
+    assert_eq!(status, 422);
+
+    // 不传 groups 时合并全部建议
+    let (status, body) = server.post("/api/actors/duplicate-suggestions/merge", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["merged_groups"], 1);
+    assert_eq!(body["data"]["merged_actors"], 2);
+
+    let (status, _) = server.get(&format!("/api/actors/{}", reversed_id)).await;
+    assert_eq!(status, 404);
+    let (_, body) = server.get(&format!("/api/actors/{}", target_id)).await;
+    assert_eq!(body["data"]["filmography"].as_array().unwrap().len(), 3);
+    let (_, body) = server.get("/api/favorites?entity_type=actor").await;
+    assert!(body.to_string().contains(&target_id), "{}", body);
+
+    let (_, body) = server.get("/api/actors/duplicate-suggestions").await;
+    assert_eq!(body["data"]["groups"].as_array().unwrap().len(), 0);
+
+    // 被合并的名字成为别名，再次出现时关联到保留的演员
+    let fourth = create_media_with_cast(&server, "Fourth", &["Hatano Yui"]).await;
+    let (_, body) = server.get(&format!("/api/media/{}/actors", fourth)).await;
+    assert_eq!(body["data"][0]["id"], target_id.as_str());
+
+    let (status, body) = server.post("/api/actors/duplicate-suggestions/merge", json!({
+        "groups": [{ "target_id": "missing", "source_ids": [target_id] }],
+    })).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["data"]["merged_groups"], 0);
+    assert_eq!(body["data"]["errors"].as_array().unwrap().len(), 1);
+}
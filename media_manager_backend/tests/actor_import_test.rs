@@ -0,0 +1,62 @@
+// 从插件导入厂商演员名单集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_import_studio_actors_from_plugin() {
+    let server = TestServer::start().await;
+
+    // 已有演员只补全空字段
+    let (_, body) = server.post("/api/actors", json!({ "name": "Alice Mock", "biography": "Local bio" })).await;
+    let alice_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    // 演职员表中有该名字、但还没有关联演员的媒体（如旧数据）
+    let media_id = server.create_media("Cast Only Movie", None).await;
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    sqlx::query("UPDATE media_items SET \"cast\" = ? WHERE id = ?")
+        .bind(json!([{ "name": "Bob Mock", "role": "Actor", "character": null }]).to_string())
+        .bind(&media_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+
+    let (status, body) = server.post("/api/actors/import-from-plugin", json!({ "studio": "Mock Studio" })).await;
+    assert_eq!(status, 200, "{}", body);
+    let data = &body["data"];
+    assert_eq!(data["plugin_id"], "media_scraper");
+    assert_eq!(data["total"], 2);
+    assert_eq!(data["created"], 1);
+    assert_eq!(data["updated"], 1);
+    assert_eq!(data["linked_media"], 1);
+
+    let (_, body) = server.get(&format!("/api/actors/{}", alice_id)).await;
+    assert_eq!(body["data"]["biography"], "Local bio");
+    assert_eq!(body["data"]["avatar_url"], "https://mock.invalid/actors/Alice_Mock.jpg");
+    let (_, body) = server.get(&format!("/api/media/{}/actors", media_id)).await;
+    assert_eq!(body["data"][0]["name"], "Bob Mock");
+
+    // 再次导入没有变化，替换模式覆盖已有字段
+    let (_, body) = server.post("/api/actors/import-from-plugin", json!({ "studio": "Mock Studio" })).await;
+    assert_eq!(body["data"]["unchanged"], 2);
+    assert_eq!(body["data"]["linked_media"], 0);
+    let (_, body) = server.post("/api/actors/import-from-plugin", json!({ "studio": "Mock Studio", "mode": "replace" })).await;
+    assert_eq!(body["data"]["updated"], 1);
+    let (_, body) = server.get(&format!("/api/actors/{}", alice_id)).await;
+    assert_eq!(body["data"]["biography"], "Alice Mock is a performer at Mock Studio");
+
+    let (status, _) = server.post("/api/actors/import-from-plugin", json!({ "studio": " " })).await;
+    assert_eq!(status, 422);
+    let (status, _) = server.post("/api/actors/import-from-plugin", json!({ "studio": "Mock Studio", "mode": "nope" })).await;
+    assert_eq!(status, 422);
+    let (status, _) = server.post("/api/actors/import-from-plugin", json!({ "studio": "Mock Studio", "plugin_id": "missing" })).await;
+    assert!(status >= 500, "{}", status);
+
+    server.set_plugin_capabilities(json!(["search_magnets"])).await;
+    let (status, body) = server.post("/api/actors/import-from-plugin", json!({ "studio": "Mock Studio" })).await;
+    assert_eq!(status, 400, "{}", body);
+    assert!(body.to_string().contains("No plugin supports actor lists"), "{}", body);
+}
@@ -0,0 +1,57 @@
+// 演员列表过滤集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::{json, Value};
+
+async fn create_actor(server: &TestServer, body: Value) -> String {
+    let (status, body) = server.post("/api/actors", body).await;
+    assert_eq!(status, 200, "{}", body);
+    body["data"]["id"].as_str().unwrap().to_string()
+}
+
+async fn create_media(server: &TestServer, title: &str, studio: &str, actor_ids: &[&str]) {
+    let (status, body) = server.post("/api/media", json!({ "title": title, "media_type": "Movie", "studio": studio })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["data"]["id"].as_str().unwrap().to_string();
+    for actor_id in actor_ids {
+        let (status, body) = server.post(&format!("/api/media/{}/actors", media_id), json!({ "actor_id": actor_id, "role": "cast" })).await;
+        assert_eq!(status, 200, "{}", body);
+    }
+}
+
+async fn list_names(server: &TestServer, query: &str) -> Vec<String> {
+    let (status, body) = server.get(&format!("/api/actors?sort_by=name&{}", query)).await;
+    assert_eq!(status, 200, "{}", body);
+    let names: Vec<String> = body["data"]["actors"].as_array().unwrap().iter()
+        .map(|a| a["name"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(body["data"]["total"], names.len(), "{}", body);
+    names
+}
+
+#[tokio::test]
+async fn test_list_actors_by_appearance() {
+    let server = TestServer::start().await;
+    let aoi = create_actor(&server, json!({ "name": "Aoi", "nationality": "Japan", "avatar_url": "http://example.com/aoi.jpg" })).await;
+    let ben = create_actor(&server, json!({ "name": "Ben", "nationality": "USA" })).await;
+    let cho = create_actor(&server, json!({ "name": "Cho", "nationality": "japan" })).await;
+    create_actor(&server, json!({ "name": "Dan" })).await;
+
+    create_media(&server, "First", "Alpha Studio", &[&aoi, &ben]).await;
+    create_media(&server, "Second", "Beta Studio", &[&aoi, &cho]).await;
+    create_media(&server, "Third", "Beta Studio", &[&aoi]).await;
+
+    assert_eq!(list_names(&server, "min_media_count=2").await, vec!["Aoi"]);
+    assert_eq!(list_names(&server, "min_media_count=1").await, vec!["Aoi", "Ben", "Cho"]);
+    assert_eq!(list_names(&server, "has_photo=true").await, vec!["Aoi"]);
+    assert_eq!(list_names(&server, "has_photo=false").await, vec!["Ben", "Cho", "Dan"]);
+    assert_eq!(list_names(&server, "nationality=JAPAN").await, vec!["Aoi", "Cho"]);
+    assert_eq!(list_names(&server, "studio=beta%20studio").await, vec!["Aoi", "Cho"]);
+    assert_eq!(list_names(&server, &format!("co_star_id={}", aoi)).await, vec!["Ben", "Cho"]);
+    assert_eq!(list_names(&server, &format!("co_star_id={}", ben)).await, vec!["Aoi"]);
+    // 条件可以组合
+    assert_eq!(list_names(&server, &format!("co_star_id={}&studio=Alpha%20Studio", aoi)).await, vec!["Ben"]);
+    assert_eq!(list_names(&server, "nationality=Japan&has_photo=false&min_media_count=1").await, vec!["Cho"]);
+}
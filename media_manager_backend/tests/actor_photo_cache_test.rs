@@ -0,0 +1,70 @@
+// 演员图片缓存集成测试
+
+mod common;
+
+use axum::routing::get;
+use common::{png_response, serve, PngResponse, TestServer};
+use serde_json::json;
+
+/// 提供一张 PNG 图片，其他路径返回 404
+async fn start_image_server() -> String {
+    async fn photo() -> PngResponse {
+        png_response(image::RgbImage::new(4, 4))
+    }
+    serve(axum::Router::new().route("/photo.png", get(photo))).await
+}
+
+#[tokio::test]
+async fn test_actor_photos_are_cached_locally() {
+    let server = TestServer::start().await;
+    let image_server = start_image_server().await;
+    let photo = format!("{}/photo.png", image_server);
+    let missing = format!("{}/missing.png", image_server);
+
+    // 默认关闭：保存演员时保留远程 URL
+    let (status, body) = server.get("/api/settings/actor-photo-cache").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["enabled"], false);
+    let (status, body) = server.post("/api/actors", json!({
+        "name": "Remote Actor",
+        "avatar_url": photo,
+        "photo_url": format!("{},{}", photo, missing),
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let actor_id = body["data"]["id"].as_str().unwrap().to_string();
+    assert_eq!(body["data"]["avatar_url"], photo.as_str());
+
+    // 补缓存：成功的替换为本地路径，失败的保留原始 URL
+    let (status, body) = server.post("/api/cache/actors/backfill", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["actors"], 1);
+    assert_eq!(body["data"]["cached"], 2);
+    assert_eq!(body["data"]["failed"], 1);
+    let (_, body) = server.get(&format!("/api/actors/{}", actor_id)).await;
+    let avatar = format!("/cache/images/actors/{}/avatar.webp", actor_id);
+    assert_eq!(body["data"]["avatar_url"], avatar.as_str());
+    assert_eq!(
+        body["data"]["photo_url"],
+        format!("/cache/images/actors/{}/photo_0.webp,{}", actor_id, missing).as_str()
+    );
+    let response = reqwest::get(server.url(&avatar)).await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "image/webp");
+
+    let (status, _) = server.put("/api/settings/actor-photo-cache", json!({ "enabled": true, "fields": ["nope"] })).await;
+    assert_eq!(status, 422);
+    let (status, body) = server.put("/api/settings/actor-photo-cache", json!({ "enabled": true, "fields": ["avatar"] })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    // 开启后保存演员时自动缓存，只处理设置中的字段
+    let (_, body) = server.post("/api/actors", json!({
+        "name": "Auto Actor",
+        "avatar_url": photo,
+        "poster_url": photo,
+    })).await;
+    let actor_id = body["data"]["id"].as_str().unwrap().to_string();
+    let body = server.wait_for(&format!("/api/actors/{}", actor_id), |body| {
+        body["data"]["avatar_url"].as_str().is_some_and(|url| url.starts_with("/cache/"))
+    }).await;
+    assert_eq!(body["data"]["poster_url"], photo.as_str());
+}
@@ -0,0 +1,69 @@
+// 批量演员刮削进度与结果汇总集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_batch_actor_scrape_progress_and_summary() {
+    let server = TestServer::start().await;
+
+    let (_, body) = server.post("/api/actors", json!({ "name": "Alice Mock", "biography": "Local bio" })).await;
+    let alice_id = body["data"]["id"].as_str().unwrap().to_string();
+    let (_, body) = server.post("/api/actors", json!({ "name": "Nobody Mock" })).await;
+    let nobody_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, _) = server.post("/api/scrape/actor/batch", json!({
+        "actor_ids": [alice_id],
+        "mode": "overwrite",
+    })).await;
+    assert_eq!(status, 422);
+
+    let (status, body) = server.post("/api/scrape/actor/batch", json!({
+        "actor_ids": [alice_id, nobody_id, "missing"],
+        "mode": "supplement",
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let session_id = body["session_id"].as_str().unwrap().to_string();
+
+    // 与媒体刮削共用进度端点
+    let progress = server.wait_for(&format!("/api/scrape/progress/{}", session_id), |body| {
+        body["data"]["completed"] == true
+    }).await;
+    let data = &progress["data"];
+    assert_eq!(data["status"], "completed", "{}", progress);
+    assert_eq!(data["mode"], "supplement");
+    assert_eq!(data["total"], 2);
+    assert_eq!(data["success_count"], 1);
+    assert_eq!(data["failed_count"], 1);
+
+    let results = data["results"].as_array().unwrap();
+    assert_eq!(results[0]["actor_id"], alice_id.as_str());
+    assert_eq!(results[0]["fields_updated"], json!(["avatar_url", "photo_url"]));
+    assert_eq!(results[0]["photo_downloaded"], false);
+    assert_eq!(results[1]["name"], "Nobody Mock");
+    assert_eq!(results[1]["status"], "failed");
+
+    let events = server.client.get(server.url(&format!("/api/scrape/progress/{}/events", session_id)))
+        .send().await.unwrap()
+        .text().await.unwrap();
+    assert!(events.contains("\"current_actor\""), "{}", events);
+
+    let (_, body) = server.get(&format!("/api/actors/{}", alice_id)).await;
+    assert_eq!(body["data"]["biography"], "Local bio");
+    assert_eq!(body["data"]["avatar_url"], "https://mock.invalid/actors/Alice_Mock.jpg");
+
+    // 结果汇总在进度会话删除后仍可查看
+    let (status, _) = server.delete(&format!("/api/scrape/progress/{}", session_id)).await;
+    assert_eq!(status, 200);
+    let (status, body) = server.get(&format!("/api/scrape/actor/runs/{}", session_id)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["success_count"], 1);
+    assert_eq!(body["data"]["results"].as_array().unwrap().len(), 2);
+
+    let (_, body) = server.get("/api/scrape/actor/runs").await;
+    assert_eq!(body["data"][0]["id"], session_id.as_str());
+    let (status, _) = server.get("/api/scrape/actor/runs/missing").await;
+    assert_eq!(status, 404);
+}
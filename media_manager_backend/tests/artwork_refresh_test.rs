@@ -0,0 +1,66 @@
+// 批量刷新图片集成测试
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::routing::get;
+use common::{png_response, serve, PngResponse, TestServer};
+use serde_json::{json, Value};
+
+/// 提供一张 PNG 封面并统计请求次数
+async fn start_image_server(hits: Arc<AtomicUsize>) -> String {
+    async fn poster(State(hits): State<Arc<AtomicUsize>>) -> PngResponse {
+        hits.fetch_add(1, Ordering::SeqCst);
+        png_response(image::RgbImage::from_pixel(20, 30, image::Rgb([200, 40, 40])))
+    }
+    serve(axum::Router::new().route("/poster.png", get(poster)).with_state(hits)).await
+}
+
+/// 启动刷新并等待完成，返回进度
+async fn refresh(server: &TestServer, media_id: &str) -> Value {
+    let (status, body) = server.post("/api/batch/refresh-artwork", json!({ "ids": [media_id] })).await;
+    assert_eq!(status, 200, "{}", body);
+    let session_id = body["session_id"].as_str().unwrap();
+    server.wait_for(&format!("/api/scrape/progress/{}", session_id), |body| body["data"]["completed"] == true).await
+}
+
+#[tokio::test]
+async fn test_refresh_cached_artwork_uses_source_url() {
+    let server = TestServer::start().await;
+    let hits = Arc::new(AtomicUsize::new(0));
+    let image_server = start_image_server(hits.clone()).await;
+
+    let (status, body) = server.post("/api/media", json!({
+        "title": "Refresh Movie",
+        "media_type": "Movie",
+        "poster_url": format!("{}/poster.png", image_server),
+        "backdrop_url": ["/cache/images/unknown.webp"],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let progress = refresh(&server, &media_id).await;
+    assert_eq!(progress["data"]["success_count"], 1, "{}", progress);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // 模拟缓存后数据库中的封面地址已替换为缓存路径
+    let local_path = format!("/cache/images/media/{}/poster.webp", media_id);
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    sqlx::query("UPDATE media_items SET poster_url = ? WHERE id = ?")
+        .bind(&local_path)
+        .bind(&media_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+
+    // 封面从记录的原始 URL 重新下载，没有来源的本地背景图跳过
+    let progress = refresh(&server, &media_id).await;
+    assert_eq!(progress["data"]["success_count"], 1, "{}", progress);
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+    assert!(progress["data"]["message"].as_str().unwrap().contains("跳过 1 张"), "{}", progress);
+    assert!(server.dir().join("cache").join(local_path.trim_start_matches('/')).exists());
+}
@@ -0,0 +1,34 @@
+// 发售日历接口集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_refresh_requires_upcoming_capability() {
+    let server = TestServer::start().await;
+    let (status, body) = server.post("/api/follows", json!({ "target_type": "series", "target_name": "Mock Series" })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (status, body) = server.post("/api/calendar/refresh", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["follows_checked"], 1, "{}", body);
+    assert_eq!(body["data"]["errors"], json!([]), "{}", body);
+
+    // 指定了插件的关注直接使用该插件，其他关注按能力选择插件
+    let (status, body) = server.post("/api/follows", json!({
+        "target_type": "studio",
+        "target_name": "Mock Studio",
+        "plugin_id": "media_scraper",
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    server.set_plugin_capabilities(json!(["search_magnets"])).await;
+    let (status, body) = server.post("/api/calendar/refresh", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    let mut errors: Vec<&str> = body["data"]["errors"].as_array().unwrap().iter().map(|e| e.as_str().unwrap()).collect();
+    errors.sort();
+    assert_eq!(errors.len(), 2, "{}", body);
+    assert!(errors[0].starts_with("series 'Mock Series': No plugin supports upcoming releases"), "{}", body);
+    assert!(errors[1].contains("Plugin 'media_scraper' does not support upcoming releases"), "{}", body);
+}
@@ -0,0 +1,93 @@
+// 收藏统计接口集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_collection_stats() {
+    let server = TestServer::start().await;
+    let (_, body) = server.get("/api/collections/stats").await;
+    assert_eq!(body["data"]["total"], 0, "{}", body);
+    assert_eq!(body["data"]["average_rating"], json!(null));
+
+    let mut media_ids = Vec::new();
+    for (title, runtime, studio) in [("Stats One", 100, "Stats Studio"), ("Stats Two", 60, "Stats Studio"), ("Stats Three", 90, "Other")] {
+        let (status, body) = server.post("/api/media", json!({
+            "title": title,
+            "media_type": "Movie",
+            "runtime": runtime,
+            "studio": studio,
+        })).await;
+        assert_eq!(status, 200, "{}", body);
+        media_ids.push(body["data"]["id"].as_str().unwrap().to_string());
+    }
+    let (status, body) = server.post("/api/batch/tag-by-filter", json!({
+        "filter": { "studio": "Stats Studio" },
+        "add_tags": ["Drama"],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    for (media_id, watch_status) in media_ids.iter().zip(["Completed", "Watching", "WantToWatch"]) {
+        let (status, body) = server.post("/api/collections", json!({ "media_id": media_id, "watch_status": watch_status })).await;
+        assert_eq!(status, 200, "{}", body);
+    }
+    let (status, _) = server.put(&format!("/api/collections/{}/status", media_ids[1]), json!({
+        "watch_status": "Watching",
+        "progress": 0.5,
+    })).await;
+    assert_eq!(status, 200);
+
+    let (status, body) = server.get("/api/collections/stats").await;
+    assert_eq!(status, 200, "{}", body);
+    let data = &body["data"];
+    assert_eq!(data["total"], 3, "{}", data);
+    assert_eq!(data["by_status"].as_array().unwrap().len(), 3, "{}", data);
+    // 看完 100 分钟 + 看了一半的 60 分钟
+    assert_eq!(data["runtime_watched_minutes"], 130, "{}", data);
+    assert_eq!(data["tags"], json!([{ "tag": "Drama", "total": 2, "completed": 1, "completion_percent": 50.0 }]));
+}
+
+#[tokio::test]
+async fn test_collection_status_history() {
+    let server = TestServer::start().await;
+    let media_id = server.create_media("Timeline Movie", None).await;
+    let (status, _) = server.get(&format!("/api/collections/{}/history", media_id)).await;
+    assert_eq!(status, 404);
+
+    server.post("/api/collections", json!({ "media_id": media_id })).await;
+    for watch_status in ["Watching", "Watching", "Completed"] {
+        let (status, _) = server.put(&format!("/api/collections/{}/status", media_id), json!({ "watch_status": watch_status })).await;
+        assert_eq!(status, 200);
+    }
+
+    // 状态不变的更新不产生记录，最新的在前
+    let (status, body) = server.get(&format!("/api/collections/{}/history", media_id)).await;
+    assert_eq!(status, 200, "{}", body);
+    let transitions: Vec<(serde_json::Value, serde_json::Value)> = body["data"].as_array().unwrap().iter()
+        .map(|c| (c["from_status"].clone(), c["to_status"].clone()))
+        .collect();
+    assert_eq!(transitions, vec![
+        (json!("Watching"), json!("Completed")),
+        (json!("WantToWatch"), json!("Watching")),
+        (json!(null), json!("WantToWatch")),
+    ]);
+
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let (_, body) = server.get(&format!("/api/collections/history?status=Completed&from={}&to={}", today, today)).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1, "{}", body);
+    assert_eq!(body["data"][0]["media_title"], "Timeline Movie");
+    let (_, body) = server.get("/api/collections/history?status=Completed&to=2000-01-31").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 0, "{}", body);
+    let (status, _) = server.get("/api/collections/history?from=yesterday").await;
+    assert_eq!(status, 422);
+
+    let (_, body) = server.get("/api/collections/stats").await;
+    assert_eq!(body["data"]["completed_this_month"], 1, "{}", body);
+
+    // 移出收藏后记录一并删除
+    server.delete(&format!("/api/collections/{}", media_id)).await;
+    let (_, body) = server.get("/api/collections/history").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 0, "{}", body);
+}
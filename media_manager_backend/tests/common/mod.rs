@@ -0,0 +1,199 @@
+// 集成测试辅助工具
+//
+// 在临时目录中启动后端程序，并把模拟刮削插件安装为 media_scraper，
+// 通过 HTTP 调用接口，不访问任何外部网站。
+
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+/// 把模拟插件安装到插件目录（plugin_id 为 media_scraper 时可以替代真实的刮削插件）
+pub fn install_mock_plugin(plugins_dir: &Path, plugin_id: &str) {
+    let plugin_dir = plugins_dir.join(plugin_id);
+    std::fs::create_dir_all(&plugin_dir).unwrap();
+    let executable = if cfg!(windows) { "mock_scraper.exe" } else { "mock_scraper" };
+    std::fs::copy(env!("CARGO_BIN_EXE_mock_scraper"), plugin_dir.join(executable)).unwrap();
+    let config = json!({
+        "id": plugin_id,
+        "name": "Mock Scraper",
+        "version": "1.0.0",
+        "executable": executable,
+        "id_patterns": ["^MOCK-"],
+        "supports_search": true,
+        "url_domains": ["mock.invalid"],
+        "resolve_domains": ["pan.mock.invalid"],
+        "capabilities": ["search_magnets", "upcoming", "releases", "list_actors"],
+    });
+    std::fs::write(plugin_dir.join("plugin.json"), config.to_string()).unwrap();
+}
+
+/// 在随机端口上启动进程内 HTTP 服务器（模拟图片站、tracker、下载器等），返回 http://地址
+pub async fn serve(app: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+/// PNG 图片响应
+pub type PngResponse = ([(axum::http::header::HeaderName, &'static str); 1], Vec<u8>);
+
+/// 把图片编码为 PNG 响应
+pub fn png_response(image: image::RgbImage) -> PngResponse {
+    let mut png = Vec::new();
+    image.write_to(&mut std::io::Cursor::new(&mut png), image::ImageOutputFormat::Png).unwrap();
+    ([(axum::http::header::CONTENT_TYPE, "image/png")], png)
+}
+
+/// 运行中的测试服务器，drop 时结束进程并删除临时目录
+pub struct TestServer {
+    child: Child,
+    pub base_url: String,
+    pub client: reqwest::Client,
+    dir: tempfile::TempDir,
+}
+
+impl TestServer {
+    /// 启动使用空数据库和模拟插件的服务器
+    pub async fn start() -> Self {
+        Self::start_with_env(&[]).await
+    }
+
+    /// 启动服务器并设置额外的环境变量
+    pub async fn start_with_env(envs: &[(&str, &str)]) -> Self {
+        let dir = tempfile::TempDir::new().unwrap();
+        let plugins_dir = dir.path().join("plugins");
+        install_mock_plugin(&plugins_dir, "media_scraper");
+
+        // 先占用一个空闲端口再释放，交给服务器使用
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let child = Command::new(env!("CARGO_BIN_EXE_media_manager_backend"))
+            .current_dir(dir.path())
+            .env("HOST", "127.0.0.1")
+            .env("PORT", port.to_string())
+            .env("DATABASE_URL", format!("sqlite:{}?mode=rwc", dir.path().join("test.db").display()))
+            .env("PLUGINS_DIR", &plugins_dir)
+            .env("CACHE_DIR", dir.path().join("cache"))
+            .env("CACHE_CONFIG_PATH", dir.path().join("cache_config.json"))
+            .env_remove("TMDB_API_KEY")
+            .env_remove("SYNC_PEER_TOKEN")
+            .envs(envs.iter().copied())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let server = Self {
+            child,
+            base_url: format!("http://127.0.0.1:{}", port),
+            client: reqwest::Client::new(),
+            dir,
+        };
+        server.wait_until_ready().await;
+        server
+    }
+
+    async fn wait_until_ready(&self) {
+        for _ in 0..300 {
+            if let Ok(response) = self.client.get(self.url("/api/health")).send().await {
+                if response.status().is_success() {
+                    return;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("test server did not start");
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// GET 并返回状态码和 JSON
+    pub async fn get(&self, path: &str) -> (u16, Value) {
+        let response = self.client.get(self.url(path)).send().await.unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap_or(Value::Null))
+    }
+
+    /// POST JSON 并返回状态码和 JSON
+    pub async fn post(&self, path: &str, body: Value) -> (u16, Value) {
+        let response = self.client.post(self.url(path)).json(&body).send().await.unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap_or(Value::Null))
+    }
+
+    /// PUT JSON 并返回状态码和 JSON
+    pub async fn put(&self, path: &str, body: Value) -> (u16, Value) {
+        let response = self.client.put(self.url(path)).json(&body).send().await.unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap_or(Value::Null))
+    }
+
+    /// DELETE 并返回状态码和 JSON
+    pub async fn delete(&self, path: &str) -> (u16, Value) {
+        let response = self.client.delete(self.url(path)).send().await.unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap_or(Value::Null))
+    }
+
+    /// 带 Accept-Language 请求头 POST JSON
+    pub async fn post_with_language(&self, path: &str, body: Value, language: &str) -> (u16, Value) {
+        let response = self.client.post(self.url(path))
+            .header("Accept-Language", language)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().await.unwrap_or(Value::Null))
+    }
+
+    /// 创建媒体并返回ID
+    pub async fn create_media(&self, title: &str, code: Option<&str>) -> String {
+        let (status, body) = self.post("/api/media", json!({
+            "title": title,
+            "media_type": "Movie",
+            "code": code,
+        })).await;
+        assert_eq!(status, 200, "create media failed: {}", body);
+        body["data"]["id"].as_str().unwrap().to_string()
+    }
+
+    /// 修改模拟插件声明的能力并重新加载插件
+    pub async fn set_plugin_capabilities(&self, capabilities: Value) {
+        let path = self.dir().join("plugins").join("media_scraper").join("plugin.json");
+        let mut config: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        config["capabilities"] = capabilities;
+        std::fs::write(&path, config.to_string()).unwrap();
+        let (status, _) = self.post("/api/scrape/plugins/reload", json!({})).await;
+        assert_eq!(status, 200);
+    }
+
+    /// 轮询进度接口直到 done 返回 true
+    pub async fn wait_for(&self, path: &str, done: impl Fn(&Value) -> bool) -> Value {
+        for _ in 0..300 {
+            let (_, body) = self.get(path).await;
+            if done(&body) {
+                return body;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("timed out waiting for {}", path);
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
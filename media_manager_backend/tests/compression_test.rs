@@ -0,0 +1,57 @@
+// 响应压缩集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_compress_large_responses_but_not_ranges() {
+    let server = TestServer::start().await;
+    for index in 0..20 {
+        server.create_media(&format!("Compressed Movie {}", index), None).await;
+    }
+    let get = |path: &str, encoding: &str| {
+        server.client.get(server.url(path)).header("Accept-Encoding", encoding).send()
+    };
+    let encoding = |response: &reqwest::Response| {
+        response.headers().get("content-encoding").map(|v| v.to_str().unwrap().to_string())
+    };
+
+    let response = get("/api/media?page=1&limit=20", "gzip").await.unwrap();
+    assert_eq!(encoding(&response).as_deref(), Some("gzip"));
+    let response = get("/api/media?page=1&limit=20", "zstd").await.unwrap();
+    assert_eq!(encoding(&response).as_deref(), Some("zstd"));
+    // 小于最小字节数的响应不压缩
+    let response = get("/api/health", "gzip").await.unwrap();
+    assert_eq!(encoding(&response), None);
+
+    // 视频流的范围请求不压缩
+    let file_path = server.dir().join("ZIP-001.mp4");
+    std::fs::write(&file_path, vec![b'x'; 8192]).unwrap();
+    let (status, body) = server.post("/api/scan/identify", json!({
+        "files": [{ "file_path": file_path.to_str().unwrap(), "file_size": 8192 }],
+        "scrape_data": { "title": "Range Movie" },
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["media_id"].as_str().unwrap();
+    let response = server.client.get(server.url(&format!("/api/media/{}/video", media_id)))
+        .header("Accept-Encoding", "gzip")
+        .header("Range", "bytes=0-4095")
+        .send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 206);
+    assert_eq!(encoding(&response), None);
+    assert_eq!(response.bytes().await.unwrap().len(), 4096);
+}
+
+#[tokio::test]
+async fn test_compression_disabled() {
+    let server = TestServer::start_with_env(&[("COMPRESSION_ALGORITHMS", "none")]).await;
+    for index in 0..20 {
+        server.create_media(&format!("Plain Movie {}", index), None).await;
+    }
+    let response = server.client.get(server.url("/api/media?page=1&limit=20"))
+        .header("Accept-Encoding", "gzip, br, zstd")
+        .send().await.unwrap();
+    assert_eq!(response.headers().get("content-encoding"), None);
+}
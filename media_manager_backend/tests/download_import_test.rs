@@ -0,0 +1,249 @@
+// 下载自动导入集成测试（使用进程内的简易 qBittorrent Web API 和 aria2 JSON-RPC）
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Query, State};
+use axum::routing::{get, post};
+use axum::Json;
+use common::{serve, TestServer};
+use serde_json::{json, Value};
+
+const HASH: &str = "c12fe1c06bba254a9dc9f519b335aa7c1367a88a";
+
+#[derive(Clone)]
+struct MockClient {
+    save_path: String,
+    progress: Arc<Mutex<f64>>,
+    added: Arc<Mutex<Vec<String>>>,
+}
+
+async fn add(State(mock): State<MockClient>, body: String) -> &'static str {
+    mock.added.lock().unwrap().push(body);
+    "Ok."
+}
+
+async fn info(State(mock): State<MockClient>, Query(query): Query<HashMap<String, String>>) -> Json<Value> {
+    if !query.get("hashes").is_some_and(|h| h.split('|').any(|h| h == HASH)) {
+        return Json(json!([]));
+    }
+    let progress = *mock.progress.lock().unwrap();
+    let state = if progress >= 1.0 { "stalledUP" } else { "downloading" };
+    Json(json!([{ "hash": HASH, "name": "ABC-123", "progress": progress, "state": state, "save_path": mock.save_path }]))
+}
+
+async fn files() -> Json<Value> {
+    Json(json!([
+        { "name": "ABC-123/abc-123.mp4", "size": 5 },
+        { "name": "ABC-123/abc-123-sample.mp4", "size": 1 },
+        { "name": "ABC-123/readme.txt", "size": 1 },
+    ]))
+}
+
+async fn start_mock(mock: MockClient) -> String {
+    serve(axum::Router::new()
+        .route("/api/v2/torrents/add", post(add))
+        .route("/api/v2/torrents/info", get(info))
+        .route("/api/v2/torrents/files", get(files))
+        .with_state(mock)).await
+}
+
+#[tokio::test]
+async fn test_completed_download_is_imported() {
+    let downloads = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(downloads.path().join("ABC-123")).unwrap();
+    std::fs::write(downloads.path().join("ABC-123/abc-123.mp4"), b"video").unwrap();
+    std::fs::write(downloads.path().join("ABC-123/abc-123-sample.mp4"), b"s").unwrap();
+
+    let mock = MockClient {
+        save_path: downloads.path().display().to_string(),
+        progress: Arc::new(Mutex::new(0.5)),
+        added: Arc::default(),
+    };
+    let client_url = start_mock(mock.clone()).await;
+    let server = TestServer::start_with_env(&[("TORRENT_CLIENT_URL", &client_url)]).await;
+
+    let library = server.dir().join("library");
+    std::fs::create_dir_all(&library).unwrap();
+    std::fs::write(library.join(".keep"), b"").unwrap();
+    let (status, root) = server.post("/api/library/roots", json!({
+        "path": library.display().to_string(),
+        "content_type": "Movie",
+        "rename_template": "{studio}/{code} {title}",
+    })).await;
+    assert_eq!(status, 200, "{}", root);
+
+    let media_id = server.create_media("Some Title", Some("ABC-123")).await;
+    let (status, body) = server.post("/api/wanted", json!({ "media_id": media_id })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    sqlx::query("UPDATE wanted_media SET status = 'found', found_title = 'ABC-123 1080p', found_magnet = ? WHERE media_id = ?")
+        .bind(format!("magnet:?xt=urn:btih:{}&dn=ABC-123", HASH.to_uppercase()))
+        .bind(&media_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE media_items SET acquisition_status = 'wanted' WHERE id = ?")
+        .bind(&media_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, body) = server.post(&format!("/api/wanted/{}/push", media_id), json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(mock.added.lock().unwrap().len(), 1);
+    let (_, body) = server.get("/api/acquisition?state=downloading").await;
+    assert_eq!(body["data"]["items"][0]["media_id"], media_id.as_str());
+
+    // 下载中：只更新进度
+    let (status, body) = server.post("/api/downloads/import", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["checked"], 1);
+    assert_eq!(body["data"]["imported"], 0);
+    let (_, body) = server.get("/api/downloads?status=downloading").await;
+    assert_eq!(body["data"][0]["info_hash"], HASH);
+    assert_eq!(body["data"][0]["progress"], 0.5);
+
+    *mock.progress.lock().unwrap() = 1.0;
+    let (status, body) = server.post("/api/downloads/import", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["imported"], 1, "{}", body);
+
+    // 按模板整理到库中，空的 {studio} 层级被去掉，sample 不导入
+    let target = library.join("ABC-123 Some Title.mp4");
+    assert_eq!(std::fs::read(&target).unwrap(), b"video");
+    let (_, body) = server.get("/api/downloads").await;
+    assert_eq!(body["data"][0]["status"], "imported");
+    let imported: Vec<String> = serde_json::from_str(body["data"][0]["imported_files"].as_str().unwrap()).unwrap();
+    assert_eq!(imported, vec![target.display().to_string()]);
+
+    let file_paths: Vec<String> = sqlx::query_scalar("SELECT file_path FROM media_files WHERE media_id = ?")
+        .bind(&media_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(file_paths, imported);
+    let (_, body) = server.get("/api/acquisition").await;
+    assert_eq!(body["data"]["counts"], json!({ "downloading": 0, "imported": 1, "wanted": 0 }));
+    let watch_status: String = sqlx::query_scalar("SELECT watch_status FROM collections WHERE media_id = ?")
+        .bind(&media_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(watch_status, "WantToWatch");
+    // 文件名中识别不出分辨率，未达到默认期望质量，恢复监控以检测升级
+    let wanted_status: String = sqlx::query_scalar("SELECT status FROM wanted_media WHERE media_id = ?")
+        .bind(&media_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(wanted_status, "monitoring");
+    pool.close().await;
+
+    let (status, _) = server.post(&format!("/api/downloads/{}/retry", HASH), json!({})).await;
+    assert_eq!(status, 409);
+    let (status, _) = server.post("/api/downloads/0000/retry", json!({})).await;
+    assert_eq!(status, 404);
+    let (status, _) = server.get("/api/downloads?status=bogus").await;
+    assert_eq!(status, 422);
+}
+
+#[derive(Clone)]
+struct MockAria2 {
+    file: String,
+    status: Arc<Mutex<&'static str>>,
+    added: Arc<Mutex<Vec<Value>>>,
+}
+
+async fn aria2_rpc(State(mock): State<MockAria2>, Json(request): Json<Value>) -> Json<Value> {
+    let params = request["params"].as_array().unwrap();
+    if params[0] != "token:secret" {
+        return Json(json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": 1, "message": "Unauthorized" } }));
+    }
+    let result = match request["method"].as_str().unwrap() {
+        "aria2.addUri" => {
+            mock.added.lock().unwrap().push(params[1].clone());
+            json!(format!("gid{}", mock.added.lock().unwrap().len()))
+        }
+        "aria2.tellStatus" => {
+            let status = *mock.status.lock().unwrap();
+            let completed = if status == "complete" { "5" } else { "1" };
+            json!({
+                "gid": params[1], "status": status, "totalLength": "5", "completedLength": completed,
+                "files": [{ "index": "1", "path": mock.file, "length": "5" }],
+                "errorMessage": if status == "error" { Some("404 Not Found") } else { None },
+            })
+        }
+        _ => unreachable!(),
+    };
+    Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }))
+}
+
+#[tokio::test]
+async fn test_http_link_is_downloaded_with_aria2() {
+    let downloads = tempfile::TempDir::new().unwrap();
+    let file = downloads.path().join("XYZ-001.mp4");
+    std::fs::write(&file, b"video").unwrap();
+
+    let mock = MockAria2 {
+        file: file.display().to_string(),
+        status: Arc::new(Mutex::new("error")),
+        added: Arc::default(),
+    };
+    let app = axum::Router::new().route("/jsonrpc", post(aria2_rpc)).with_state(mock.clone());
+    let rpc_url = format!("{}/jsonrpc", serve(app).await);
+
+    let server = TestServer::start_with_env(&[("ARIA2_RPC_URL", &rpc_url), ("ARIA2_RPC_SECRET", "secret")]).await;
+    let media_id = server.create_media("Direct Download", Some("XYZ-001")).await;
+
+    let (status, _) = server.post("/api/download/link", json!({ "media_id": media_id, "url": "ed2k://|file|a.mp4|1|ABC|/" })).await;
+    assert_eq!(status, 422);
+    let (status, _) = server.post("/api/download/link", json!({ "media_id": media_id, "url": "magnet:?xt=urn:btih:abc" })).await;
+    assert_eq!(status, 422);
+    let (status, _) = server.post("/api/download/link", json!({ "media_id": "missing", "url": "https://example.com/a.mp4" })).await;
+    assert_eq!(status, 404);
+
+    let (status, body) = server.post("/api/download/link", json!({
+        "media_id": media_id,
+        "url": "https://example.com/XYZ-001.mp4",
+        "name": "XYZ-001 1080p",
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"], json!({ "client": "aria2", "id": "gid1" }));
+
+    // aria2 下载失败：记录错误，重试时重新添加下载
+    let (status, body) = server.post("/api/downloads/import", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["failed"], 1);
+    let (_, body) = server.get("/api/download/links?status=failed").await;
+    assert_eq!(body["data"][0]["error"], "404 Not Found");
+
+    *mock.status.lock().unwrap() = "active";
+    let (status, body) = server.post("/api/download/links/gid1/retry", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["id"], "gid2");
+    let (_, body) = server.post("/api/downloads/import", json!({})).await;
+    assert_eq!(body["data"]["imported"], 0);
+    let (_, body) = server.get("/api/download/links").await;
+    assert_eq!(body["data"][0]["gid"], "gid2");
+    assert_eq!(body["data"][0]["progress"], 0.2);
+
+    // 完成后导入（没有配置根目录时保留在下载目录中）
+    *mock.status.lock().unwrap() = "complete";
+    let (_, body) = server.post("/api/downloads/import", json!({})).await;
+    assert_eq!(body["data"]["imported"], 1, "{}", body);
+    let (_, body) = server.get("/api/download/links").await;
+    assert_eq!(body["data"][0]["status"], "imported");
+
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    let file_paths: Vec<String> = sqlx::query_scalar("SELECT file_path FROM media_files WHERE media_id = ?")
+        .bind(&media_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(file_paths, vec![file.display().to_string()]);
+    pool.close().await;
+}
@@ -0,0 +1,66 @@
+// 重复封面检测集成测试
+
+mod common;
+
+use axum::routing::get;
+use common::{png_response, serve, PngResponse, TestServer};
+use serde_json::json;
+
+fn png(width: u32, height: u32, flip: bool) -> PngResponse {
+    png_response(image::RgbImage::from_fn(width, height, |x, y| {
+        let (fx, fy) = (x as f32 / width as f32, y as f32 / height as f32);
+        let fy = if flip { 1.0 - fy } else { fy };
+        let v = ((fx * 7.0).sin() * (fy * 5.0).cos() * 100.0 + 128.0) as u8;
+        image::Rgb([v, v / 2, 255 - v])
+    }))
+}
+
+/// 提供同一张封面的两个尺寸和一张不同的封面
+async fn start_image_server() -> String {
+    async fn large() -> PngResponse { png(200, 300, false) }
+    async fn small() -> PngResponse { png(100, 150, false) }
+    async fn other() -> PngResponse { png(200, 300, true) }
+    serve(axum::Router::new()
+        .route("/large.png", get(large))
+        .route("/small.png", get(small))
+        .route("/other.png", get(other))).await
+}
+
+#[tokio::test]
+async fn test_duplicate_artwork() {
+    let server = TestServer::start().await;
+    let image_server = start_image_server().await;
+
+    let mut ids = Vec::new();
+    for (title, poster) in [("Source A", "large.png"), ("Source B", "small.png"), ("Other", "other.png")] {
+        let (status, body) = server.post("/api/media", json!({
+            "title": title,
+            "media_type": "Movie",
+            "poster_url": format!("{}/{}", image_server, poster),
+        })).await;
+        assert_eq!(status, 200, "{}", body);
+        ids.push(body["data"]["id"].as_str().unwrap().to_string());
+    }
+
+    let (_, body) = server.get("/api/media/duplicate-artwork").await;
+    assert_eq!(body["data"]["media_scanned"], 0);
+
+    let (status, body) = server.post("/api/batch/refresh-artwork", json!({ "ids": ids })).await;
+    assert_eq!(status, 200, "{}", body);
+    for id in &ids {
+        server.wait_for(&format!("/api/media/{}", id), |body| !body["data"]["poster_blurhash"].is_null()).await;
+    }
+
+    let (status, body) = server.get("/api/media/duplicate-artwork").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["media_scanned"], 3);
+    assert_eq!(body["data"]["max_distance"], 6);
+    let groups = body["data"]["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 1, "{}", body);
+    let titles: Vec<&str> = groups[0]["media"].as_array().unwrap().iter().map(|m| m["title"].as_str().unwrap()).collect();
+    assert_eq!(titles, ["Source A", "Source B"]);
+    assert!(groups[0]["media"][0].get("poster_phash").is_none());
+
+    let (status, _) = server.get("/api/media/duplicate-artwork?max_distance=17").await;
+    assert_eq!(status, 422);
+}
@@ -0,0 +1,59 @@
+// 新建媒体后的后台补全集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_new_media_is_enriched_in_background() {
+    let server = TestServer::start().await;
+
+    // 默认关闭：新建媒体不会加入队列
+    let (status, body) = server.get("/api/settings/enrichment").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["enabled"], false);
+    let manual_id = server.create_media("Manual Only", Some("MOCK-002")).await;
+
+    let (status, _) = server.put("/api/settings/enrichment", json!({ "enabled": true, "steps": ["nope"] })).await;
+    assert_eq!(status, 422);
+    let (status, body) = server.put("/api/settings/enrichment", json!({
+        "enabled": true,
+        "steps": ["scrape", "probe"],
+        "job_delay_secs": 0,
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let media_id = server.create_media("MOCK-001", Some("MOCK-001")).await;
+    let body = server.wait_for(&format!("/api/media/{}/enrichment", media_id), |body| {
+        body["data"]["job"]["status"] == "completed"
+    }).await;
+    let job = &body["data"]["job"];
+    assert_eq!(job["reason"], "create");
+    assert_eq!(job["steps"][0]["step"], "scrape");
+    assert_eq!(job["steps"][0]["status"], "completed");
+    assert_eq!(job["steps"][1]["status"], "skipped");
+
+    // 补充刮削只填充空字段，标题保持不变
+    let (_, body) = server.get(&format!("/api/media/{}", media_id)).await;
+    assert_eq!(body["data"]["title"], "MOCK-001");
+    assert_eq!(body["data"]["studio"], "Mock Studio");
+
+    let (_, body) = server.get("/api/enrichment/jobs").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    let (status, _) = server.get("/api/enrichment/jobs?status=nope").await;
+    assert_eq!(status, 422);
+
+    // 手动加入队列不受开关影响
+    let (_, body) = server.get(&format!("/api/media/{}/enrichment", manual_id)).await;
+    assert!(body["data"]["job"].is_null());
+    let (status, body) = server.post(&format!("/api/media/{}/enrich", manual_id), json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    let body = server.wait_for(&format!("/api/media/{}/enrichment", manual_id), |body| {
+        body["data"]["job"]["status"] == "completed"
+    }).await;
+    assert_eq!(body["data"]["job"]["reason"], "manual");
+
+    let (status, _) = server.post("/api/media/missing/enrich", json!({})).await;
+    assert_eq!(status, 404);
+}
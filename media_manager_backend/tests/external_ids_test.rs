@@ -0,0 +1,84 @@
+// 媒体外部ID管理集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_update_external_ids() {
+    let server = TestServer::start().await;
+    let first = server.create_media("First", None).await;
+    let second = server.create_media("Second", None).await;
+
+    let (status, body) = server.put(&format!("/api/media/{}/external-ids", first), json!({
+        "tmdb_id": 603,
+        "imdb_id": "tt0133093",
+        "tvdb_id": 169,
+        "custom": { "douban": "1291843" },
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["tmdb_relinked"], true);
+
+    let (_, body) = server.get(&format!("/api/media/{}", first)).await;
+    let ids = &body["data"]["external_ids"];
+    assert_eq!(ids["tmdb_id"], 603);
+    assert_eq!(ids["tvdb_id"], 169);
+    assert_eq!(ids["custom"]["douban"], "1291843");
+
+    // 格式错误
+    for invalid in [
+        json!({ "imdb_id": "0133093" }),
+        json!({ "tmdb_id": 0 }),
+        json!({ "custom": { "Bad Key": "1" } }),
+    ] {
+        let (status, body) = server.put(&format!("/api/media/{}/external-ids", second), invalid).await;
+        assert_eq!(status, 422, "{}", body);
+    }
+
+    // 其他媒体已使用的ID
+    for taken in [
+        json!({ "tmdb_id": 603 }),
+        json!({ "imdb_id": "tt0133093" }),
+        json!({ "tvdb_id": 169 }),
+        json!({ "custom": { "douban": "1291843" } }),
+    ] {
+        let (status, body) = server.put(&format!("/api/media/{}/external-ids", second), taken).await;
+        assert_eq!(status, 409, "{}", body);
+    }
+    let (status, _) = server.put(&format!("/api/media/{}/external-ids", second), json!({
+        "tmdb_id": 604,
+        "custom": { "douban": "1304141" },
+    })).await;
+    assert_eq!(status, 200);
+
+    // 改绑 TMDB ID 时清除旧条目的翻译，手动翻译保留
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    for (language, source) in [("en", "tmdb"), ("fr", "manual")] {
+        sqlx::query("INSERT INTO media_translations (media_id, language, title, source, updated_at) VALUES (?, ?, 'Translated', ?, CURRENT_TIMESTAMP)")
+            .bind(&first)
+            .bind(language)
+            .bind(source)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    pool.close().await;
+
+    let (status, body) = server.put(&format!("/api/media/{}/external-ids", first), json!({
+        "tmdb_id": 603,
+        "imdb_id": "tt0133093",
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["tmdb_relinked"], false);
+    assert_eq!(body["data"]["cleared_translations"], 0);
+
+    let (_, body) = server.put(&format!("/api/media/{}/external-ids", first), json!({ "tmdb_id": 605 })).await;
+    assert_eq!(body["data"]["tmdb_relinked"], true);
+    assert_eq!(body["data"]["cleared_translations"], 1);
+    let (_, body) = server.get(&format!("/api/media/{}/translations", first)).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+    let (status, _) = server.put("/api/media/missing/external-ids", json!({})).await;
+    assert_eq!(status, 404);
+}
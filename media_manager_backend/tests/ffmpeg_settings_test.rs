@@ -0,0 +1,57 @@
+// FFmpeg 任务调度设置集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_ffmpeg_settings() {
+    let server = TestServer::start().await;
+
+    let (status, body) = server.get("/api/settings/ffmpeg").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["nice"], 10);
+    assert_eq!(body["data"]["threads"], 0);
+    assert_eq!(body["data"]["running"], 0);
+    assert!(body["data"]["max_concurrent"].as_u64().unwrap() >= 1);
+
+    for invalid in [
+        json!({ "max_concurrent": 0 }),
+        json!({ "max_concurrent": 2, "nice": 20 }),
+        json!({ "max_concurrent": 2, "threads": 100 }),
+    ] {
+        let (status, body) = server.put("/api/settings/ffmpeg", invalid).await;
+        assert_eq!(status, 422, "{}", body);
+    }
+
+    let (status, body) = server.put("/api/settings/ffmpeg", json!({ "max_concurrent": 2, "nice": 5, "threads": 1 })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["max_concurrent"], 2);
+
+    let (_, body) = server.get("/api/settings/ffmpeg").await;
+    assert_eq!(body["data"]["max_concurrent"], 2);
+    assert_eq!(body["data"]["nice"], 5);
+    assert_eq!(body["data"]["threads"], 1);
+}
+
+#[tokio::test]
+async fn test_health_reports_hardware_acceleration() {
+    let server = TestServer::start_with_env(&[("HW_ACCEL", "none")]).await;
+
+    // 检测在启动后进行，完成前为 null
+    let mut capabilities = serde_json::Value::Null;
+    for _ in 0..100 {
+        let (status, body) = server.get("/api/health").await;
+        assert_eq!(status, 200, "{}", body);
+        capabilities = body["data"]["hardware_acceleration"].clone();
+        if !capabilities.is_null() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(capabilities["requested"], "none", "{}", capabilities);
+    assert!(capabilities["selected"].is_null());
+    assert!(capabilities["available"].is_array());
+    assert!(capabilities["ffmpeg_available"].is_boolean());
+}
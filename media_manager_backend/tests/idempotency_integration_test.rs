@@ -0,0 +1,49 @@
+// Idempotency-Key 端到端测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::{json, Value};
+
+async fn post_with_key(server: &TestServer, path: &str, key: &str, body: Value) -> (u16, Option<String>, Value) {
+    let response = reqwest::Client::new()
+        .post(server.url(path))
+        .header("Idempotency-Key", key)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    let status = response.status().as_u16();
+    let replayed = response.headers().get("idempotent-replayed")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    (status, replayed, response.json().await.unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_create_media_replays_on_retry() {
+    let server = TestServer::start().await;
+    let body = json!({ "title": "Retry Movie", "media_type": "Movie" });
+
+    let (status, replayed, first) = post_with_key(&server, "/api/media", "key-1", body.clone()).await;
+    assert_eq!(status, 200, "{}", first);
+    assert!(replayed.is_none());
+
+    let (status, replayed, second) = post_with_key(&server, "/api/media", "key-1", body).await;
+    assert_eq!(status, 200, "{}", second);
+    assert_eq!(replayed.as_deref(), Some("true"));
+    assert_eq!(first["data"]["id"], second["data"]["id"]);
+
+    let (_, list) = server.get("/api/media?page=1&limit=10").await;
+    assert_eq!(list["data"]["items"].as_array().unwrap().len(), 1, "{}", list);
+
+    // 相同的键用于不同的请求
+    let (status, _, body) = post_with_key(&server, "/api/media", "key-1", json!({ "title": "Other", "media_type": "Movie" })).await;
+    assert_eq!(status, 422, "{}", body);
+    assert_eq!(body["error"]["code"], "IDEMPOTENCY_KEY_REUSED");
+
+    // 不带键的请求不受影响
+    let (status, body) = server.post("/api/media", json!({ "title": "Retry Movie", "media_type": "Movie" })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_ne!(body["data"]["id"], first["data"]["id"]);
+}
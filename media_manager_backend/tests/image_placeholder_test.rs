@@ -0,0 +1,47 @@
+// 图片占位图集成测试
+
+mod common;
+
+use axum::routing::get;
+use common::{png_response, serve, PngResponse, TestServer};
+use serde_json::json;
+
+/// 提供一张渐变 PNG 图片，其他路径返回 404
+async fn start_image_server() -> String {
+    async fn poster() -> PngResponse {
+        png_response(image::RgbImage::from_fn(40, 60, |x, y| image::Rgb([(x * 6) as u8, (y * 4) as u8, 128])))
+    }
+    serve(axum::Router::new().route("/poster.png", get(poster))).await
+}
+
+#[tokio::test]
+async fn test_cached_artwork_has_blurhash() {
+    let server = TestServer::start().await;
+    let image_server = start_image_server().await;
+    let poster = format!("{}/poster.png", image_server);
+    let missing = format!("{}/missing.png", image_server);
+
+    let (status, body) = server.post("/api/media", json!({
+        "title": "Placeholder Movie",
+        "media_type": "Movie",
+        "poster_url": poster,
+        "backdrop_url": [missing, poster],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["data"]["id"].as_str().unwrap().to_string();
+    assert!(body["data"]["poster_blurhash"].is_null());
+    assert_eq!(body["data"]["backdrop_blurhash"], json!([null, null]));
+
+    let (status, body) = server.post("/api/batch/refresh-artwork", json!({ "ids": [media_id] })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    // 竖版封面使用 3x4 分量，长度为 4 + 2 * 12 = 28
+    let body = server.wait_for(&format!("/api/media/{}", media_id), |body| {
+        !body["data"]["poster_blurhash"].is_null()
+    }).await;
+    let blurhash = body["data"]["poster_blurhash"].as_str().unwrap();
+    assert_eq!(blurhash.len(), 28);
+    assert!(blurhash.starts_with('T'));
+    // 下载失败的背景图没有占位图
+    assert_eq!(body["data"]["backdrop_blurhash"], json!([null, blurhash]));
+}
@@ -0,0 +1,117 @@
+// 导入后处理钩子集成测试
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::routing::post;
+use axum::Json;
+use common::{serve, TestServer};
+use serde_json::{json, Value};
+
+type Received = Arc<Mutex<Vec<(String, Value)>>>;
+
+async fn start_webhook(received: Received) -> String {
+    async fn handle(State(received): State<Received>, uri: axum::http::Uri, Json(body): Json<Value>) -> &'static str {
+        received.lock().unwrap().push((uri.to_string(), body));
+        "refreshed"
+    }
+    serve(axum::Router::new().route("/refresh", post(handle)).with_state(received)).await
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_hooks_fire_after_import() {
+    let server = TestServer::start_with_env(&[("IMPORT_HOOK_COMMANDS_ENABLED", "true")]).await;
+    let received: Received = Arc::default();
+    let webhook_url = start_webhook(received.clone()).await;
+    let out = server.dir().join("hook.out");
+
+    let (status, body) = server.post("/api/hooks", json!({
+        "name": "Plex refresh",
+        "hook_type": "webhook",
+        "target": format!("{}/refresh?dir={{dir}}", webhook_url),
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let webhook_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, body) = server.post("/api/hooks", json!({
+        "name": "Script",
+        "hook_type": "command",
+        "target": "sh",
+        "args": ["-c", "printf '%s|%s' \"$1\" \"$2\" > \"$3\"; echo done", "hook", "{code}", "{title}", out.display().to_string()],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["args"][4], "{title}");
+
+    let (status, body) = server.post("/api/hooks", json!({ "name": "Failing", "hook_type": "command", "target": "false" })).await;
+    assert_eq!(status, 200, "{}", body);
+    let failing_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, _) = server.post("/api/hooks", json!({ "name": "Bad", "hook_type": "command", "target": "{nope}" })).await;
+    assert_eq!(status, 422);
+    // 命令路径不能由刮削数据决定
+    let (status, _) = server.post("/api/hooks", json!({ "name": "Bad", "hook_type": "command", "target": "{title}" })).await;
+    assert_eq!(status, 422);
+
+    let media_id = server.create_media("Title; rm -rf /", Some("ABC-123")).await;
+    let file_path = server.dir().join("library/ABC-123/ABC-123.mp4").display().to_string();
+    let (status, body) = server.post("/api/scan/confirm", json!({
+        "matches": [{ "media_id": media_id, "files": [{ "file_path": file_path, "file_size": 1 }] }],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let events = server.wait_for("/api/hooks/events", |body| body["data"].as_array().is_some_and(|e| e.len() == 3)).await;
+    let events = events["data"].as_array().unwrap();
+    let event = |hook_id: &str| events.iter().find(|e| e["hook_id"] == hook_id).unwrap();
+
+    // 参数不经过 shell，标题中的特殊字符原样传递
+    assert_eq!(std::fs::read_to_string(&out).unwrap(), "ABC-123|Title; rm -rf /");
+
+    assert_eq!(event(&webhook_id)["success"], true);
+    assert_eq!(event(&webhook_id)["exit_code"], 200);
+    assert_eq!(event(&webhook_id)["output"], "refreshed");
+    assert_eq!(event(&webhook_id)["media_id"], media_id.as_str());
+    assert_eq!(event(&webhook_id)["file_path"], file_path.as_str());
+    {
+        let received = received.lock().unwrap();
+        let (uri, payload) = &received[0];
+        assert!(uri.ends_with(&format!("dir={}", urlencoding::encode(&format!("{}/library/ABC-123", server.dir().display())))), "{}", uri);
+        assert_eq!(payload["event"], "import");
+        assert_eq!(payload["code"], "ABC-123");
+        assert_eq!(payload["files"], json!([file_path]));
+    }
+
+    assert_eq!(event(&failing_id)["success"], false);
+    assert_eq!(event(&failing_id)["exit_code"], 1);
+
+    // 禁用后不再执行，手动测试仍可执行
+    let (status, _) = server.put(&format!("/api/hooks/{}", failing_id), json!({ "enabled": false })).await;
+    assert_eq!(status, 200);
+    let (status, body) = server.post(&format!("/api/hooks/{}/test", failing_id), json!({ "media_id": media_id })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["success"], false);
+    let (_, body) = server.get(&format!("/api/hooks/events?hook_id={}", failing_id)).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 2);
+
+    let (status, _) = server.delete(&format!("/api/hooks/{}", failing_id)).await;
+    assert_eq!(status, 200);
+    let (_, body) = server.get(&format!("/api/hooks/events?hook_id={}", failing_id)).await;
+    assert!(body["data"].as_array().unwrap().is_empty(), "删除钩子时一并删除执行记录");
+}
+
+#[tokio::test]
+async fn test_command_hooks_require_opt_in() {
+    let server = TestServer::start().await;
+    let (status, body) = server.post("/api/hooks", json!({ "name": "Script", "hook_type": "command", "target": "true" })).await;
+    assert_eq!(status, 403, "{}", body);
+
+    // Webhook 不受影响
+    let (status, body) = server.post("/api/hooks", json!({
+        "name": "Plex refresh",
+        "hook_type": "webhook",
+        "target": "http://127.0.0.1:9/refresh",
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+}
@@ -0,0 +1,90 @@
+// 数据导入接口集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_import_batches_media_and_actors() {
+    let server = TestServer::start().await;
+
+    let (status, body) = server.post("/api/data/import", json!({
+        "version": "1.2",
+        "on_conflict": "skip",
+        "media": [
+            { "code": "IMP-001", "title": "Import One", "media_type": "Movie", "year": 2021 },
+            { "code": "IMP-002", "title": "Import Two", "media_type": "Scene" },
+            // 与同一批中未写入的媒体番号相同，应当跳过
+            { "code": "imp-001", "title": "Import One Again", "media_type": "Movie" },
+        ],
+        "actors": [
+            { "name": "Import Actor" },
+            { "name": "Import Actor", "biography": "duplicated entry" },
+            { "name": "" },
+        ],
+        "actor_media_relations": [
+            { "actor_name": "Import Actor", "media_title": "Import One", "role": "cast" },
+            { "actor_name": "Import Actor", "media_title": "Import Two", "role": "cast" },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let data = &body["data"];
+    assert_eq!(data["media_imported"], 2, "{}", data);
+    assert_eq!(data["media_skipped"], 1, "{}", data);
+    assert_eq!(data["actors_imported"], 2, "{}", data);
+    assert_eq!(data["actors_failed"], 1, "{}", data);
+    assert_eq!(data["relations_imported"], 2, "{}", data);
+    assert_eq!(data["decisions"][2]["matched_on"], "code");
+    assert_eq!(data["decisions"][2]["media_id"], data["decisions"][0]["media_id"]);
+
+    let media_id = data["decisions"][0]["media_id"].as_str().unwrap();
+    let (_, media) = server.get(&format!("/api/media/{}", media_id)).await;
+    assert_eq!(media["data"]["code"], "IMP-001");
+    assert_eq!(media["data"]["year"], 2021);
+    let (_, actors) = server.get(&format!("/api/media/{}/actors", media_id)).await;
+    assert_eq!(actors["data"].as_array().unwrap().len(), 1, "{}", actors);
+
+    let (_, list) = server.get("/api/media?page=1&limit=10").await;
+    assert_eq!(list["data"]["items"].as_array().unwrap().len(), 2, "{}", list);
+}
+
+#[tokio::test]
+async fn test_chunked_import_session() {
+    let server = TestServer::start().await;
+
+    // 关系和演员写在媒体前面，媒体逐条导入后仍能关联
+    let payload = json!({
+        "version": "1.2",
+        "actors": [{ "name": "Chunk Actor" }],
+        "actor_media_relations": [{ "actor_name": "Chunk Actor", "media_title": "Chunk Two", "role": "cast" }],
+        "media": [
+            { "code": "CHK-001", "title": "Chunk One", "media_type": "Movie" },
+            { "code": "CHK-002", "title": "Chunk Two", "media_type": "Scene" },
+        ],
+    }).to_string();
+
+    let (status, body) = server.post("/api/data/import/session", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    let session_id = body["data"]["session_id"].as_str().unwrap().to_string();
+    let (first, second) = payload.as_bytes().split_at(payload.len() / 2);
+    for (index, chunk) in [first, second].into_iter().enumerate() {
+        let response = server.client
+            .put(server.url(&format!("/api/data/import/session/{}/chunks/{}", session_id, index)))
+            .body(chunk.to_vec())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+    }
+
+    let (status, body) = server.post(&format!("/api/data/import/session/{}/commit", session_id), json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    let body = server.wait_for(&format!("/api/data/import/session/{}", session_id), |body| body["data"]["completed"] == true).await;
+    let data = &body["data"];
+    assert_eq!(data["status"], "completed", "{}", data);
+    assert_eq!(data["total"], 2);
+    assert_eq!(data["result"]["media_imported"], 2, "{}", data);
+    assert_eq!(data["result"]["relations_imported"], 1, "{}", data);
+    assert_eq!(data["result"]["decisions"][1]["title"], "Chunk Two");
+}
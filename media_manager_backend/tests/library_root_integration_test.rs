@@ -0,0 +1,138 @@
+// 媒体库根目录接口集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_library_roots_drive_scans() {
+    let server = TestServer::start().await;
+    let library = server.dir().join("library");
+    std::fs::create_dir_all(library.join("jav")).unwrap();
+    std::fs::write(library.join("jav/ABC-123.mp4"), b"video").unwrap();
+
+    let (status, body) = server.post("/api/scan/start", json!({})).await;
+    assert_eq!(status, 400, "{}", body);
+
+    let root_path = format!("{}/", library.display());
+    let (status, root) = server.post("/api/library/roots", json!({
+        "path": root_path,
+        "label": "Main",
+        "content_type": "Movie",
+        "rename_template": "{studio}/{code} {title}",
+    })).await;
+    assert_eq!(status, 200, "{}", root);
+    assert_eq!(root["data"]["path"], library.display().to_string(), "末尾的分隔符会被去掉");
+    assert_eq!(root["data"]["auto_scan"], false);
+    let root_id = root["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, body) = server.post("/api/library/roots", json!({ "path": library.display().to_string() })).await;
+    assert_eq!(status, 409, "{}", body);
+    let (status, body) = server.post("/api/library/roots", json!({ "path": "/other", "rename_template": "{nope}" })).await;
+    assert_eq!(status, 422, "{}", body);
+
+    let response = server.client.put(server.url(&format!("/api/library/roots/{}", root_id)))
+        .json(&json!({ "auto_scan": true, "label": "" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    let (_, roots) = server.get("/api/library/roots").await;
+    assert_eq!(roots["data"][0]["auto_scan"], true, "{}", roots);
+    assert!(roots["data"][0]["label"].is_null());
+    assert_eq!(roots["data"][0]["content_type"], "Movie");
+
+    // 不传路径时扫描全部根目录
+    let (status, scan) = server.post("/api/scan/start", json!({})).await;
+    assert_eq!(status, 200, "{}", scan);
+    assert_eq!(scan["total_files"], 1, "{}", scan);
+    let (status, scan) = server.post("/api/scan/start", json!({ "root_ids": [root_id] })).await;
+    assert_eq!(status, 200, "{}", scan);
+    assert_eq!(scan["scanned_files"][0]["parsed_code"], "ABC-123");
+    let (status, _) = server.post("/api/scan/start", json!({ "root_ids": ["missing"] })).await;
+    assert_eq!(status, 404);
+
+    let response = server.client.delete(server.url(&format!("/api/library/roots/{}", root_id))).send().await.unwrap();
+    assert!(response.status().is_success());
+    let (_, roots) = server.get("/api/library/roots").await;
+    assert_eq!(roots["data"], json!([]));
+}
+
+#[tokio::test]
+async fn test_unreachable_roots_are_skipped() {
+    let server = TestServer::start().await;
+    let online = server.dir().join("online");
+    let offline = server.dir().join("offline");
+    std::fs::create_dir_all(&online).unwrap();
+    std::fs::write(online.join("ABC-123.mp4"), b"video").unwrap();
+
+    let (_, root) = server.post("/api/library/roots", json!({ "path": online.display().to_string() })).await;
+    assert_eq!(root["data"]["reachable"], true, "{}", root);
+    assert!(root["data"]["last_seen_at"].is_string());
+    let (_, root) = server.post("/api/library/roots", json!({ "path": offline.display().to_string() })).await;
+    assert_eq!(root["data"]["path_exists"], false, "{}", root);
+    assert_eq!(root["data"]["reachable"], false);
+    assert!(root["data"]["last_error"].is_string());
+    let offline_id = root["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, scan) = server.post("/api/scan/start", json!({})).await;
+    assert_eq!(status, 200, "{}", scan);
+    assert_eq!(scan["total_files"], 1, "{}", scan);
+    assert_eq!(scan["skipped_roots"], json!([offline.display().to_string()]));
+
+    let (status, _) = server.post("/api/scan/start", json!({ "root_ids": [offline_id] })).await;
+    assert_eq!(status, 503);
+
+    // 挂载恢复后（目录非空）重新变为可访问
+    std::fs::create_dir_all(&offline).unwrap();
+    std::fs::write(offline.join("XYZ-001.mp4"), b"video").unwrap();
+    let (status, scan) = server.post("/api/scan/start", json!({ "root_ids": [offline_id] })).await;
+    assert_eq!(status, 200, "{}", scan);
+    let (_, roots) = server.get("/api/library/roots").await;
+    assert!(roots["data"].as_array().unwrap().iter().all(|root| root["reachable"] == true), "{}", roots);
+}
+
+#[tokio::test]
+async fn test_browse_is_restricted_to_roots() {
+    let server = TestServer::start().await;
+    let library = server.dir().join("media");
+    std::fs::create_dir_all(library.join("Movies/2024")).unwrap();
+    std::fs::create_dir_all(library.join("anime")).unwrap();
+    std::fs::create_dir_all(library.join(".trash")).unwrap();
+    std::fs::write(library.join("readme.txt"), b"x").unwrap();
+    std::fs::create_dir_all(server.dir().join("outside")).unwrap();
+    let root_path = library.display().to_string();
+    let (_, root) = server.post("/api/library/roots", json!({ "path": root_path, "label": "Media" })).await;
+
+    let (status, listing) = server.get("/api/fs/browse").await;
+    assert_eq!(status, 200, "{}", listing);
+    assert_eq!(listing["data"]["entries"], json!([{ "name": "Media", "path": root_path }]));
+
+    let (_, listing) = server.get(&format!("/api/fs/browse?path={}", urlencoding::encode(&root_path))).await;
+    let names: Vec<&str> = listing["data"]["entries"].as_array().unwrap().iter().map(|e| e["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["anime", "Movies"], "{}", listing);
+    assert_eq!(listing["data"]["root_id"], root["data"]["id"]);
+    assert!(listing["data"]["parent"].is_null());
+
+    let movies = library.join("Movies").display().to_string();
+    let (_, listing) = server.get(&format!("/api/fs/browse?path={}", urlencoding::encode(&movies))).await;
+    assert_eq!(listing["data"]["parent"], root_path);
+    assert_eq!(listing["data"]["entries"][0]["path"], library.join("Movies/2024").display().to_string());
+
+    let outside = server.dir().join("outside").display().to_string();
+    let (status, _) = server.get(&format!("/api/fs/browse?path={}", urlencoding::encode(&outside))).await;
+    assert_eq!(status, 403);
+    let escape = format!("{}/../outside", root_path);
+    let (status, _) = server.get(&format!("/api/fs/browse?path={}", urlencoding::encode(&escape))).await;
+    assert_eq!(status, 422);
+
+    // 指向根目录外的符号链接
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(server.dir().join("outside"), library.join("link")).unwrap();
+        let link = library.join("link").display().to_string();
+        let (status, _) = server.get(&format!("/api/fs/browse?path={}", urlencoding::encode(&link))).await;
+        assert_eq!(status, 403);
+    }
+}
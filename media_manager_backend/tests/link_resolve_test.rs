@@ -0,0 +1,135 @@
+// 网盘链接解析集成测试
+
+mod common;
+
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use common::{serve, TestServer};
+use serde_json::json;
+
+/// 只接受带正确提取码 Cookie 的请求的直链服务器（支持 `bytes=N-` 范围请求）
+async fn start_file_server() -> String {
+    async fn handle(headers: HeaderMap) -> Response {
+        const BODY: &str = "video-bytes";
+        if headers.get("cookie").and_then(|v| v.to_str().ok()) != Some("pwd=abcd") {
+            return (StatusCode::FORBIDDEN, [("content-type", "text/plain")], "forbidden").into_response();
+        }
+        let start = headers.get("range")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("bytes=")?.strip_suffix('-')?.parse::<usize>().ok());
+        match start {
+            Some(start) => (
+                StatusCode::PARTIAL_CONTENT,
+                [("content-type", "video/mp4".to_string()), ("content-range", format!("bytes {}-{}/{}", start, BODY.len() - 1, BODY.len()))],
+                &BODY[start..],
+            ).into_response(),
+            None => (StatusCode::OK, [("content-type", "video/mp4")], BODY).into_response(),
+        }
+    }
+    async fn playlist(headers: HeaderMap) -> Response {
+        if headers.get("cookie").and_then(|v| v.to_str().ok()) != Some("pwd=abcd") {
+            return (StatusCode::FORBIDDEN, "forbidden").into_response();
+        }
+        ([("content-type", "application/vnd.apple.mpegurl")], "#EXTM3U\n#EXTINF:10,\nseg0.ts\n#EXT-X-ENDLIST\n").into_response()
+    }
+    async fn segment(headers: HeaderMap) -> Response {
+        if headers.get("cookie").and_then(|v| v.to_str().ok()) != Some("pwd=abcd") {
+            return (StatusCode::FORBIDDEN, "forbidden").into_response();
+        }
+        ([("content-type", "video/mp2t")], "segment-bytes").into_response()
+    }
+    serve(axum::Router::new()
+        .route("/files/ABC-123.mp4", get(handle))
+        .route("/files/ABC-123.m3u8", get(playlist))
+        .route("/files/seg0.ts", get(segment))).await
+}
+
+#[tokio::test]
+async fn test_pan_link_is_resolved_and_played() {
+    let server = TestServer::start().await;
+    let file_server = start_file_server().await;
+    let direct = format!("{}/files/ABC-123.mp4", file_server);
+
+    let (status, body) = server.post("/api/media", json!({
+        "title": "Pan Movie",
+        "media_type": "Movie",
+        "download_links": [
+            { "name": "magnet", "url": "magnet:?xt=urn:btih:0123456789abcdef0123456789abcdef01234567", "link_type": "magnet" },
+            {
+                "name": "网盘",
+                "url": format!("https://pan.mock.invalid/s/abc?direct={}", urlencoding::encode(&direct)),
+                "link_type": "pan",
+                "password": "abcd",
+            },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, body) = server.get(&format!("/api/media/{}/links/1/resolve", media_id)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["plugin_id"], "media_scraper");
+    assert_eq!(body["data"]["url"], direct.as_str());
+    assert_eq!(body["data"]["file_name"], "ABC-123.mp4");
+    let play_url = body["data"]["play_url"].as_str().unwrap().to_string();
+
+    // 代理播放时附带插件返回的请求头
+    let response = server.client.get(server.url(&play_url)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "video-bytes");
+
+    // 范围请求转发给直链并返回 206
+    let response = server.client.get(server.url(&play_url)).header("Range", "bytes=6-").send().await.unwrap();
+    assert_eq!(response.status(), 206);
+    assert_eq!(response.headers()["content-range"], "bytes 6-10/11");
+    assert_eq!(response.text().await.unwrap(), "bytes");
+
+    // 没有插件声明能解析的链接
+    let (status, _) = server.get(&format!("/api/media/{}/links/0/resolve", media_id)).await;
+    assert_eq!(status, 404);
+    let (status, _) = server.get(&format!("/api/media/{}/links/5/resolve", media_id)).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_pan_hls_link_forwards_resolved_headers() {
+    let server = TestServer::start().await;
+    let file_server = start_file_server().await;
+    let direct = format!("{}/files/ABC-123.m3u8", file_server);
+
+    let (status, body) = server.post("/api/media", json!({
+        "title": "Pan HLS Movie",
+        "media_type": "Movie",
+        "download_links": [{
+            "name": "网盘",
+            "url": format!("https://pan.mock.invalid/s/abc?direct={}", urlencoding::encode(&direct)),
+            "link_type": "pan",
+            "password": "abcd",
+        }],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["data"]["id"].as_str().unwrap().to_string();
+    let (status, body) = server.get(&format!("/api/media/{}/links/0/resolve", media_id)).await;
+    assert_eq!(status, 200, "{}", body);
+    let play_url = body["data"]["play_url"].as_str().unwrap().to_string();
+
+    // 播放列表请求附带插件返回的请求头，改写后的分片地址也带上这些请求头
+    let response = server.client.get(server.url(&play_url)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let playlist = response.text().await.unwrap();
+    let segment_url = playlist.lines()
+        .find(|line| line.contains("/api/proxy/hls/segment?"))
+        .unwrap_or_else(|| panic!("{}", playlist));
+    let segment_path = segment_url.trim_start_matches("http://localhost:3000");
+    assert!(segment_path.contains("&headers="), "{}", segment_path);
+
+    let response = server.client.get(server.url(segment_path)).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "segment-bytes");
+
+    // 不带请求头时分片服务器拒绝访问
+    let bare = segment_path.split("&headers=").next().unwrap();
+    let response = server.client.get(server.url(bare)).send().await.unwrap();
+    assert_eq!(response.status(), 502);
+}
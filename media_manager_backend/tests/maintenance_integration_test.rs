@@ -0,0 +1,141 @@
+// 数据库维护接口集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_optimize_database() {
+    let server = TestServer::start().await;
+    let media_id = server.create_media("Optimize Me", Some("OPT-001")).await;
+
+    let (status, body) = server.post("/api/maintenance/optimize", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    let data = &body["data"];
+    assert_eq!(data["search_index_rows"], 1, "{}", data);
+    assert_eq!(data["orphan_relations_removed"], 0);
+    assert!(data["size_after_bytes"].as_i64().unwrap() > 0);
+    assert!(data["reclaimed_bytes"].as_i64().unwrap() >= 0);
+
+    // 重建后的全文索引仍可搜索
+    let (status, search) = server.get("/api/search?q=Optimize").await;
+    assert_eq!(status, 200, "{}", search);
+    assert!(search.to_string().contains(&media_id), "{}", search);
+}
+
+#[tokio::test]
+async fn test_cleanup_orphans() {
+    let server = TestServer::start().await;
+    let media_id = server.create_media("Keep Me", Some("ORP-001")).await;
+
+    // 旧版本遗留的数据：关闭外键写入指向不存在媒体的关联行
+    let options = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(server.dir().join("test.db"))
+        .foreign_keys(false);
+    let pool = sqlx::SqlitePool::connect_with(options).await.unwrap();
+    sqlx::query("INSERT INTO browsing_history (user_key, media_id, viewed_at) VALUES ('default', 'gone-media', '2024-01-01T00:00:00Z')")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+
+    let cache_dir = |id: &str| server.dir().join("cache/images/media").join(id);
+    for id in ["gone-media", media_id.as_str()] {
+        std::fs::create_dir_all(cache_dir(id)).unwrap();
+        std::fs::write(cache_dir(id).join("poster.webp"), b"webp").unwrap();
+    }
+
+    let (status, body) = server.post("/api/maintenance/cleanup-orphans", json!({ "dry_run": true })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["relation_rows"], 1, "{}", body);
+    assert_eq!(body["data"]["relations"][0]["table"], "browsing_history");
+    assert_eq!(body["data"]["cache_files"], 1);
+    assert_eq!(body["data"]["cache"][0]["media_id"], "gone-media");
+    assert!(cache_dir("gone-media").exists());
+
+    let (status, body) = server.post("/api/maintenance/cleanup-orphans", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["relation_rows"], 1, "{}", body);
+    assert!(!cache_dir("gone-media").exists());
+    assert!(cache_dir(&media_id).exists());
+
+    let (_, body) = server.post("/api/maintenance/cleanup-orphans", json!({ "dry_run": true })).await;
+    assert_eq!(body["data"]["relation_rows"], 0, "{}", body);
+
+    // 删除媒体时同时删除缓存
+    let response = server.client.delete(server.url(&format!("/api/media/{}", media_id))).send().await.unwrap();
+    assert!(response.status().is_success());
+    assert!(!cache_dir(&media_id).exists());
+}
+
+#[tokio::test]
+async fn test_prune_expired_changes() {
+    let server = TestServer::start_with_env(&[("CHANGES_RETENTION_DAYS", "30")]).await;
+    for i in 0..3 {
+        server.create_media(&format!("Prune {}", i), None).await;
+    }
+
+    // 前两条变更超过保留期
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    let seqs: Vec<i64> = sqlx::query_scalar("SELECT seq FROM changes ORDER BY seq").fetch_all(&pool).await.unwrap();
+    assert_eq!(seqs.len(), 3);
+    sqlx::query("UPDATE changes SET changed_at = '2020-01-01T00:00:00.000Z' WHERE seq <= ?")
+        .bind(seqs[1])
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+
+    let (_, body) = server.post("/api/maintenance/cleanup-orphans", json!({ "dry_run": true })).await;
+    assert_eq!(body["data"]["changes_removed"], 2, "{}", body);
+    let (_, body) = server.get("/api/sync/changes").await;
+    assert_eq!(body["data"]["reset_required"], false, "{}", body);
+
+    let (status, body) = server.post("/api/maintenance/cleanup-orphans", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["changes_removed"], 2, "{}", body);
+
+    // 从头拉取的客户端需要全量重新加载，已拉取到被清理位置的客户端不受影响
+    let (_, body) = server.get("/api/sync/changes").await;
+    assert_eq!(body["data"]["reset_required"], true, "{}", body);
+    assert_eq!(body["data"]["changes"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"]["latest_seq"], seqs[2]);
+    let (_, body) = server.get(&format!("/api/sync/changes?since_seq={}", seqs[1])).await;
+    assert_eq!(body["data"]["reset_required"], false, "{}", body);
+}
+
+#[tokio::test]
+async fn test_migration_status() {
+    let server = TestServer::start().await;
+
+    let (status, body) = server.get("/api/maintenance/migrations").await;
+    assert_eq!(status, 200, "{}", body);
+    let data = &body["data"];
+    assert_eq!(data["schema_version"], data["latest_version"], "{}", data);
+    assert_eq!(data["pending"], json!([]));
+    assert_eq!(data["unknown"], json!([]));
+    assert_eq!(data["applied"][0]["version"], 1);
+    assert!(data["applied"][0]["installed_on"].is_string());
+}
+
+#[tokio::test]
+async fn test_refuse_newer_schema() {
+    use media_manager_backend::database::schema::{check_schema_version, MIGRATOR};
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let options = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(dir.path().join("newer.db"))
+        .create_if_missing(true);
+    let pool = sqlx::SqlitePool::connect_with(options).await.unwrap();
+    MIGRATOR.run(&pool).await.unwrap();
+    check_schema_version(&pool).await.unwrap();
+
+    // 模拟更新版本的程序执行过的迁移
+    sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (9999, 'from the future', 1, x'00', 0)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let error = check_schema_version(&pool).await.unwrap_err().to_string();
+    assert!(error.contains("9999"), "{}", error);
+}
@@ -0,0 +1,39 @@
+// 媒体详情聚合接口集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_media_full_with_include() {
+    let server = TestServer::start().await;
+    let media_id = server.create_media("Full Detail", Some("FUL-001")).await;
+    let (_, actor) = server.post("/api/actors", json!({ "name": "Full Actor" })).await;
+    let actor_id = actor["data"]["id"].as_str().unwrap();
+    server.post(&format!("/api/media/{}/actors", media_id), json!({ "actor_id": actor_id, "role": "cast" })).await;
+
+    let (status, body) = server.get(&format!("/api/media/{}/full", media_id)).await;
+    assert_eq!(status, 200, "{}", body);
+    let data = &body["data"];
+    assert_eq!(data["media"]["code"], "FUL-001");
+    assert_eq!(data["actors"].as_array().unwrap().len(), 1, "{}", data);
+    assert_eq!(data["collection"], json!(null));
+    for section in ["files", "editions", "magnets", "extras", "translations"] {
+        assert!(data[section].is_array(), "{}: {}", section, data);
+    }
+
+    server.post("/api/collections", json!({ "media_id": media_id, "watch_status": "Watching" })).await;
+    let (status, body) = server.get(&format!("/api/media/{}/full?include=collection,%20actors", media_id)).await;
+    assert_eq!(status, 200, "{}", body);
+    let data = body["data"].as_object().unwrap();
+    let mut keys: Vec<&str> = data.keys().map(String::as_str).collect();
+    keys.sort();
+    assert_eq!(keys, vec!["actors", "collection", "media"]);
+    assert_eq!(data["collection"]["watch_status"], "Watching");
+
+    let (status, _) = server.get(&format!("/api/media/{}/full?include=posters", media_id)).await;
+    assert_eq!(status, 422);
+    let (status, _) = server.get("/api/media/missing/full").await;
+    assert_eq!(status, 404);
+}
@@ -0,0 +1,38 @@
+// 媒体列表排序集成测试
+
+mod common;
+
+use common::TestServer;
+
+/// 按页取完整的媒体ID列表
+async fn list_ids(server: &TestServer, query: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    for page in 1..=3 {
+        let (status, body) = server.get(&format!("/api/media?{}&limit=2&page={}", query, page)).await;
+        assert_eq!(status, 200, "{}", body);
+        ids.extend(body["data"]["items"].as_array().unwrap().iter().map(|m| m["id"].as_str().unwrap().to_string()));
+    }
+    ids
+}
+
+#[tokio::test]
+async fn test_random_sort_is_stable_across_pages() {
+    let server = TestServer::start().await;
+    let mut created = Vec::new();
+    for i in 1..=6 {
+        created.push(server.create_media(&format!("Random {}", i), Some(&format!("RND-{:03}", i))).await);
+    }
+    created.sort();
+
+    let first = list_ids(&server, "sort_by=random&seed=42").await;
+    let mut sorted = first.clone();
+    sorted.sort();
+    assert_eq!(sorted, created, "每页之间不应重复或遗漏");
+    assert_eq!(list_ids(&server, "sort_by=random&seed=42").await, first);
+
+    let mut orders = Vec::new();
+    for seed in 1..=5 {
+        orders.push(list_ids(&server, &format!("sort_by=random&seed={}", seed)).await);
+    }
+    assert!(orders.iter().any(|order| *order != first), "不同的种子应得到不同的顺序");
+}
@@ -0,0 +1,96 @@
+// 批量写入媒体接口集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_upsert_media_by_code() {
+    let server = TestServer::start().await;
+
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [
+            { "code": "UP-001", "title": "Upsert One", "media_type": "Movie", "year": 2020 },
+            { "code": "UP-002", "title": "Upsert Two" },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["created"], 2);
+    let first_id = body["data"]["results"][0]["media_id"].clone();
+
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "items": [
+            { "code": "up-001", "title": "Upsert One Renamed" },
+            { "code": "UP-002", "title": "Upsert Two" },
+            { "match_on": "title_year", "title": "Upsert One Renamed", "year": 2020, "rating": 8.0 },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let actions: Vec<&str> = body["data"]["results"].as_array().unwrap().iter()
+        .map(|r| r["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, vec!["updated", "unchanged", "updated"]);
+    assert_eq!(body["data"]["results"][0]["media_id"], first_id);
+    assert_eq!(body["data"]["results"][2]["media_id"], first_id);
+
+    let (_, media) = server.get(&format!("/api/media/{}", first_id.as_str().unwrap())).await;
+    assert_eq!(media["data"]["title"], "Upsert One Renamed");
+    assert_eq!(media["data"]["rating"], 8.0);
+}
+
+#[tokio::test]
+async fn test_upsert_media_rolls_back_on_invalid_item() {
+    let server = TestServer::start().await;
+
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [
+            { "code": "UP-100", "title": "Valid" },
+            { "code": "UP-101", "title": "Invalid Year", "year": 3000 },
+            { "title": "Missing Code" },
+        ],
+    })).await;
+    assert_eq!(status, 422, "{}", body);
+    assert_eq!(body["error"]["code"], "VALIDATION_FIELD");
+    let fields: Vec<&str> = body["error"]["details"].as_array().unwrap().iter()
+        .map(|d| d["field"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, vec!["items[1].year", "items[2].code"]);
+
+    let (_, list) = server.get("/api/media?page=1&limit=10").await;
+    assert_eq!(list["data"]["items"].as_array().unwrap().len(), 0, "{}", list);
+}
+
+#[tokio::test]
+async fn test_filter_options_follow_media_writes() {
+    let server = TestServer::start().await;
+
+    let (status, body) = server.get("/api/media/filters").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["studios"], json!([]));
+
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [
+            { "code": "FO-001", "title": "Filter One", "media_type": "Movie", "year": 2019, "studio": "Studio A", "genres": ["Drama"] },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let (_, body) = server.get("/api/media/filters").await;
+    assert_eq!(body["data"]["studios"], json!(["Studio A"]), "{}", body);
+    assert_eq!(body["data"]["years"], json!([2019]));
+    assert_eq!(body["data"]["genres"], json!(["Drama"]));
+
+    // 修改筛选相关字段后缓存失效
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [
+            { "code": "FO-001", "title": "Filter One", "studio": "Studio B" },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let (_, body) = server.get("/api/media/filters").await;
+    assert_eq!(body["data"]["studios"], json!(["Studio B"]), "{}", body);
+}
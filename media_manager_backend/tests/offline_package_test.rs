@@ -0,0 +1,92 @@
+// 离线下载包集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+async fn create_media_with_file(server: &TestServer) -> String {
+    let file_path = server.dir().join("library").join("MOCK-040.mkv");
+    let (status, body) = server.post("/api/scan/identify", json!({
+        "files": [{ "file_path": file_path.to_str().unwrap(), "file_size": 1024, "part_number": 1 }],
+        "scrape_data": { "title": "Flight Movie" },
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    body["media_id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_create_offline_package() {
+    let server = TestServer::start().await;
+    let media_id = create_media_with_file(&server).await;
+    let path = format!("/api/media/{}/offline-package", media_id);
+
+    let (status, _) = server.post(&path, json!({ "max_size_mb": 10 })).await;
+    assert_eq!(status, 422);
+    let (status, _) = server.post(&path, json!({ "max_height": 2160 })).await;
+    assert_eq!(status, 422);
+    let (status, _) = server.post(&path, json!({ "part": 3 })).await;
+    assert_eq!(status, 404);
+    let (status, _) = server.post("/api/media/missing/offline-package", json!({})).await;
+    assert_eq!(status, 404);
+
+    let (status, body) = server.post(&path, json!({ "max_size_mb": 700 })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["max_size_mb"], 700);
+    assert_eq!(body["data"]["max_height"], 720);
+    assert!(body["data"]["download_url"].is_null());
+    let id = body["data"]["id"].as_str().unwrap().to_string();
+
+    // 文件不在本地，后台任务转码失败
+    let body = server.wait_for(&format!("/api/offline-packages/{}", id), |body| {
+        body["data"]["status"] == "failed"
+    }).await;
+    assert!(body["data"]["error"].as_str().unwrap().contains("local"), "{}", body);
+
+    let (_, body) = server.get(&format!("/api/offline-packages?media_id={}", media_id)).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+    let (status, _) = server.delete(&format!("/api/offline-packages/{}", id)).await;
+    assert_eq!(status, 200);
+    let (status, _) = server.get(&format!("/api/offline-packages/{}", id)).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_offline_download_is_one_time() {
+    let server = TestServer::start().await;
+    let media_id = create_media_with_file(&server).await;
+
+    // 模拟转码完成的离线包
+    let file_path = server.dir().join("package.mp4");
+    let content = vec![7u8; 256 * 1024];
+    std::fs::write(&file_path, &content).unwrap();
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    sqlx::query(
+        r#"INSERT INTO offline_packages
+           (id, media_id, part, max_size_mb, max_height, status, file_path, file_size, download_token, expires_at, created_at)
+           VALUES ('p1', ?, 0, 1500, 720, 'ready', ?, ?, 'token-1', '2099-01-01T00:00:00Z', '2024-01-01T00:00:00Z')"#
+    )
+    .bind(&media_id)
+    .bind(file_path.to_str().unwrap())
+    .bind(content.len() as i64)
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool.close().await;
+
+    let (_, body) = server.get("/api/offline-packages/p1").await;
+    assert_eq!(body["data"]["download_url"], "/api/public/offline/token-1");
+
+    let response = server.client.get(server.url("/api/public/offline/token-1")).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["content-disposition"].to_str().unwrap().contains("attachment"));
+    assert_eq!(response.bytes().await.unwrap().len(), content.len());
+
+    // 下载完成后链接失效、文件删除
+    let body = server.wait_for("/api/offline-packages/p1", |body| body["data"]["status"] == "downloaded").await;
+    assert!(body["data"]["download_url"].is_null());
+    assert!(!file_path.exists());
+    let response = server.client.get(server.url("/api/public/offline/token-1")).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 404);
+}
@@ -0,0 +1,71 @@
+// 分段观看进度集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_mark_parts_watched_updates_collection() {
+    let server = TestServer::start().await;
+    let library = server.dir().join("library");
+    let files: Vec<serde_json::Value> = (1..=2)
+        .map(|part| json!({
+            "file_path": library.join(format!("MOCK-010-cd{}.mp4", part)).to_str().unwrap(),
+            "file_size": 1024,
+            "part_number": part,
+        }))
+        .collect();
+    let (status, body) = server.post("/api/scan/identify", json!({
+        "files": files,
+        "scrape_data": { "title": "Two Part Movie" },
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["media_id"].as_str().unwrap().to_string();
+
+    let (_, body) = server.get(&format!("/api/media/{}/files", media_id)).await;
+    let file_ids: Vec<String> = body["files"].as_array().unwrap().iter()
+        .map(|f| f["id"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(file_ids.len(), 2, "{}", body);
+    let watch = |index: usize, request: serde_json::Value| {
+        let path = format!("/api/media/{}/files/{}/watch", media_id, file_ids[index]);
+        let server = &server;
+        async move { server.put(&path, request).await }
+    };
+
+    let (status, _) = watch(0, json!({ "progress": 1.5 })).await;
+    assert_eq!(status, 400);
+    let (status, _) = server.put(&format!("/api/media/{}/files/missing/watch", media_id), json!({ "watched": true })).await;
+    assert_eq!(status, 404);
+
+    // 看完第一段：自动加入收藏并设为正在观看
+    let (status, body) = watch(0, json!({ "watched": true })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["watch_status"], "Watching");
+    assert_eq!(body["watch_progress"], 0.5);
+    assert_eq!(body["editions"][0]["watched_parts"], 1);
+    let (_, body) = server.get("/api/collections").await;
+    assert_eq!(body["data"][0]["watch_status"], "Watching", "{}", body);
+
+    let (_, body) = watch(1, json!({ "watched": true })).await;
+    assert_eq!(body["watch_status"], "Completed", "{}", body);
+
+    // 分段观看状态进入变更流（对端同步依赖它）
+    let (_, body) = server.get("/api/sync/changes?entity_type=media_file").await;
+    let change = body["data"]["changes"].as_array().unwrap().iter()
+        .rev()
+        .find(|c| c["entity_id"] == file_ids[1].as_str() && c["operation"] == "update")
+        .unwrap_or_else(|| panic!("{}", body));
+    assert!(change["changed_fields"].as_array().unwrap().contains(&json!("watched_at")), "{}", change);
+    assert_eq!(change["data"]["watch_progress"], 1.0);
+    let (_, body) = server.get(&format!("/api/media/{}/files", media_id)).await;
+    assert!(body["files"].as_array().unwrap().iter().all(|f| !f["watched_at"].is_null()), "{}", body);
+
+    // 清除进度后退回想看
+    watch(0, json!({ "watched": false })).await;
+    let (_, body) = watch(1, json!({ "progress": 0 })).await;
+    assert_eq!(body["watch_status"], "WantToWatch", "{}", body);
+    let (_, body) = server.get("/api/collections").await;
+    assert_eq!(body["data"][0]["watch_status"], "WantToWatch", "{}", body);
+}
@@ -0,0 +1,99 @@
+// 播放决策集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_play_decision() {
+    let server = TestServer::start().await;
+    let library = server.dir().join("library");
+    let files: Vec<serde_json::Value> = (1..=2)
+        .map(|part| json!({
+            "file_path": library.join(format!("MOCK-030-cd{}.mkv", part)).to_str().unwrap(),
+            "file_size": 1024,
+            "part_number": part,
+        }))
+        .collect();
+    let (status, body) = server.post("/api/scan/identify", json!({
+        "files": files,
+        "scrape_data": { "title": "Probed Movie" },
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["media_id"].as_str().unwrap().to_string();
+    let decide = |request: serde_json::Value| {
+        let path = format!("/api/media/{}/play-decision", media_id);
+        let server = &server;
+        async move { server.post(&path, request).await }
+    };
+
+    // 没有技术信息时直接播放
+    let (status, body) = decide(json!({ "containers": ["mp4"] })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["method"], "direct_play");
+    assert_eq!(body["data"]["probed"], false);
+    assert_eq!(body["data"]["stream_url"], format!("/api/media/{}/video?part=0", media_id));
+
+    // 写入第二段的探测结果
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    sqlx::query(
+        r#"INSERT INTO media_probes (file_path, media_id, container, bit_rate, height, video_codec, audio_codec, probed_at)
+           VALUES (?, ?, 'matroska,webm', 8000000, 2160, 'hevc', 'dts', '2024-01-01T00:00:00Z')"#
+    )
+    .bind(library.join("MOCK-030-cd2.mkv").to_str().unwrap())
+    .bind(&media_id)
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool.close().await;
+
+    let (_, body) = decide(json!({
+        "part": 1,
+        "containers": ["mkv", "mp4"],
+        "video_codecs": ["h265", "h264"],
+        "audio_codecs": ["dts", "aac"],
+    })).await;
+    assert_eq!(body["data"]["method"], "direct_play", "{}", body);
+    assert_eq!(body["data"]["probed"], true);
+    assert_eq!(body["data"]["stream_url"], format!("/api/media/{}/video?part=1", media_id));
+
+    let (_, body) = decide(json!({
+        "part": 1,
+        "containers": ["mp4"],
+        "video_codecs": ["hevc"],
+        "audio_codecs": ["dts"],
+    })).await;
+    assert_eq!(body["data"]["method"], "remux", "{}", body);
+    assert_eq!(body["data"]["reasons"], json!(["container_not_supported"]));
+    assert_eq!(
+        body["data"]["stream_url"],
+        format!("/api/media/{}/video/transcode?part=1&container=mp4&video=copy&audio=copy", media_id)
+    );
+
+    let (_, body) = decide(json!({
+        "part": 1,
+        "containers": ["mp4"],
+        "video_codecs": ["h264"],
+        "audio_codecs": ["aac"],
+        "max_height": 1080,
+    })).await;
+    assert_eq!(body["data"]["method"], "transcode", "{}", body);
+    assert_eq!(body["data"]["video_codec"], "h264");
+    assert_eq!(body["data"]["audio_codec"], "aac");
+    assert_eq!(
+        body["data"]["stream_url"],
+        format!("/api/media/{}/video/transcode?part=1&container=mp4&video=h264&audio=aac&max_height=1080", media_id)
+    );
+
+    let (status, _) = decide(json!({ "part": 5 })).await;
+    assert_eq!(status, 404);
+    let (status, _) = server.post("/api/media/missing/play-decision", json!({})).await;
+    assert_eq!(status, 404);
+
+    // 不支持的转码参数
+    let response = server.client
+        .get(server.url(&format!("/api/media/{}/video/transcode?container=avi", media_id)))
+        .send().await.unwrap();
+    assert_eq!(response.status(), 400);
+}
@@ -0,0 +1,51 @@
+// 预览图演员标注集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_tag_actors_in_preview_images() {
+    let server = TestServer::start().await;
+    let media_id = server.create_media("Scene Movie", Some("ABC-001")).await;
+    let (status, body) = server.put(&format!("/api/media/{}", media_id), json!({
+        "preview_urls": ["http://img.test/0.jpg", "http://img.test/1.jpg"],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let (_, body) = server.post("/api/actors", json!({ "name": "Scene Actor" })).await;
+    let actor_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let path = format!("/api/media/{}/previews/1/actors", media_id);
+    let (status, body) = server.post(&path, json!({ "actor_ids": [actor_id] })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"][0]["actor_name"], "Scene Actor");
+    assert_eq!(body["data"][0]["preview_index"], 1);
+    // 重复标注不会新增记录
+    let (_, body) = server.post(&path, json!({ "actor_ids": [actor_id] })).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+    // 无效的下标、演员和媒体
+    let (status, _) = server.post(&format!("/api/media/{}/previews/2/actors", media_id), json!({ "actor_ids": [actor_id] })).await;
+    assert_eq!(status, 422);
+    let (status, _) = server.post(&path, json!({ "actor_ids": ["missing"] })).await;
+    assert_eq!(status, 404);
+    let (status, _) = server.post("/api/media/missing/previews/0/actors", json!({ "actor_ids": [actor_id] })).await;
+    assert_eq!(status, 404);
+
+    let (_, body) = server.get(&format!("/api/media/{}/previews/actors", media_id)).await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+    // 演员详情页画廊
+    let (status, body) = server.get(&format!("/api/actors/{}/scenes", actor_id)).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"][0]["media_title"], "Scene Movie");
+    assert_eq!(body["data"][0]["preview_url"], "http://img.test/1.jpg");
+
+    let (status, _) = server.delete(&format!("{}/{}", path, actor_id)).await;
+    assert_eq!(status, 200);
+    let (status, _) = server.delete(&format!("{}/{}", path, actor_id)).await;
+    assert_eq!(status, 404);
+    let (_, body) = server.get(&format!("/api/actors/{}/scenes", actor_id)).await;
+    assert!(body["data"].as_array().unwrap().is_empty());
+}
@@ -0,0 +1,65 @@
+// 预览视频健康检查集成测试
+
+mod common;
+
+use axum::http::{Method, StatusCode};
+use axum::routing::any;
+use common::{serve, TestServer};
+
+/// 4K 地址失效，1080P 地址不支持 HEAD 但可以 GET
+async fn start_video_server() -> String {
+    async fn four_k() -> StatusCode {
+        StatusCode::NOT_FOUND
+    }
+    async fn full_hd(method: Method) -> (StatusCode, &'static str) {
+        if method == Method::HEAD {
+            (StatusCode::METHOD_NOT_ALLOWED, "")
+        } else {
+            (StatusCode::PARTIAL_CONTENT, "v")
+        }
+    }
+    async fn hd() -> &'static str {
+        "video"
+    }
+    serve(axum::Router::new()
+        .route("/4k.mp4", any(four_k))
+        .route("/1080p.mp4", any(full_hd))
+        .route("/720p.mp4", any(hd))).await
+}
+
+#[tokio::test]
+async fn test_preview_resolve_picks_best_working_quality() {
+    let server = TestServer::start().await;
+    let video_server = start_video_server().await;
+    let media_id = server.create_media("Preview Movie", Some("ABC-123")).await;
+    let no_preview_id = server.create_media("No Preview", None).await;
+
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    let previews = serde_json::json!([
+        { "quality": "720P", "url": format!("{}/720p.mp4", video_server) },
+        { "quality": "4K", "url": format!("{}/4k.mp4", video_server) },
+        { "quality": "1080P", "url": format!("{}/1080p.mp4", video_server) },
+    ]);
+    sqlx::query("UPDATE media_items SET preview_video_urls = ? WHERE id = ?")
+        .bind(previews.to_string())
+        .bind(&media_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+
+    let client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap();
+    let response = client.get(server.url(&format!("/api/media/{}/preview/resolve", media_id))).send().await.unwrap();
+    assert_eq!(response.status(), 302);
+    assert_eq!(response.headers()["location"], format!("{}/1080p.mp4", video_server).as_str());
+    assert_eq!(response.headers()["x-preview-quality"], "1080P");
+
+    let response = client.get(server.url(&format!("/api/media/{}/preview/resolve?mode=proxy", media_id))).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "v");
+
+    let response = client.get(server.url(&format!("/api/media/{}/preview/resolve", no_preview_id))).send().await.unwrap();
+    assert_eq!(response.status(), 404);
+    let response = client.get(server.url(&format!("/api/media/{}/preview/resolve?mode=embed", media_id))).send().await.unwrap();
+    assert_eq!(response.status(), 400);
+}
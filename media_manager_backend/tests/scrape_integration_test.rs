@@ -0,0 +1,409 @@
+// 刮削接口集成测试
+//
+// 使用模拟刮削插件（src/bin/mock_scraper.rs）和 tests/fixtures/mock_scraper.json 中的固定数据
+
+mod common;
+
+use common::{install_mock_plugin, serve, TestServer};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_scrape_media_single_result() {
+    let server = TestServer::start().await;
+    let media_id = server.create_media("placeholder", Some("MOCK-001")).await;
+
+    let (status, body) = server.post(&format!("/api/scrape/media/{}", media_id), json!({ "mode": "replace" })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media = &body["data"];
+    assert_eq!(media["title"], "Mock Movie One");
+    assert_eq!(media["studio"], "Mock Studio");
+    assert_eq!(media["runtime"], 120);
+    assert_eq!(media["genres"], json!(["Drama", "Comedy"]));
+
+    let (_, actors) = server.get(&format!("/api/media/{}/actors", media_id)).await;
+    let mut names: Vec<&str> = actors["data"].as_array().unwrap().iter()
+        .filter_map(|a| a["name"].as_str().or_else(|| a["actor"]["name"].as_str()))
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["Alice Mock", "Bob Mock"]);
+}
+
+#[tokio::test]
+async fn test_scrape_media_multiple_and_not_found() {
+    let server = TestServer::start().await;
+
+    let media_id = server.create_media("placeholder", Some("MOCK-MULTI")).await;
+    let (status, body) = server.post(&format!("/api/scrape/media/{}", media_id), json!({ "mode": "replace" })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["mode"], "multiple");
+    assert_eq!(body["data"]["results"].as_array().unwrap().len(), 2);
+    let second_title = body["data"]["results"][1]["title"].clone();
+
+    // 用 result_token + 序号选择结果，不必回传完整数据
+    let token = body["data"]["result_token"].as_str().unwrap().to_string();
+    let (status, body) = server.post(&format!("/api/scrape/media/{}", media_id), json!({
+        "mode": "replace", "result_token": token, "result_index": 5,
+    })).await;
+    assert_eq!(status, 422, "{}", body);
+    let (status, body) = server.post(&format!("/api/scrape/media/{}", media_id), json!({
+        "mode": "replace", "result_token": token, "result_index": 1,
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["title"], second_title);
+    let (status, _) = server.post(&format!("/api/scrape/media/{}", media_id), json!({
+        "mode": "replace", "result_token": "expired", "result_index": 0,
+    })).await;
+    assert_eq!(status, 404);
+
+    let media_id = server.create_media("placeholder", Some("MOCK-404")).await;
+    let (status, body) = server.post(&format!("/api/scrape/media/{}", media_id), json!({ "mode": "replace" })).await;
+    assert_eq!(status, 502, "{}", body);
+    assert_eq!(body["error"]["code"], "PLUGIN_FAILED");
+    assert!(body["error"]["message"].as_str().unwrap().contains("MOCK-404"));
+
+    let (status, body) = server.post("/api/scrape/media/missing", json!({ "mode": "replace" })).await;
+    assert_eq!(status, 404, "{}", body);
+    assert_eq!(body["error"]["code"], "MEDIA_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_localized_errors() {
+    let server = TestServer::start().await;
+    let media_id = server.create_media("placeholder", Some("MOCK-404")).await;
+    let path = format!("/api/scrape/media/{}", media_id);
+
+    // 插件返回的多语言消息按 Accept-Language 透传
+    let (status, body) = server.post_with_language(&path, json!({ "mode": "replace" }), "en-US,en;q=0.9").await;
+    assert_eq!(status, 502, "{}", body);
+    assert_eq!(body["error"]["message"], "Scrape failed: Not found: MOCK-404");
+    assert_eq!(body["error"]["plugin_code"], "not_found");
+    let (_, body) = server.post_with_language(&path, json!({ "mode": "replace" }), "zh-CN").await;
+    assert_eq!(body["error"]["message"], "刮削失败: 未找到: MOCK-404");
+
+    // 后端错误使用错误码目录中的消息
+    let (status, body) = server.post_with_language("/api/scrape/media/missing", json!({ "mode": "replace" }), "zh").await;
+    assert_eq!(status, 404, "{}", body);
+    assert_eq!(body["error"]["message"], "媒体不存在");
+    assert_eq!(body["error"]["detail"], "Media not found");
+}
+
+#[tokio::test]
+async fn test_batch_scrape_media() {
+    let server = TestServer::start().await;
+    let found = server.create_media("placeholder one", Some("MOCK-001")).await;
+    let also_found = server.create_media("placeholder two", Some("MOCK-002")).await;
+    let missing = server.create_media("placeholder three", Some("MOCK-404")).await;
+
+    let (status, body) = server.post("/api/scrape/media/batch", json!({
+        "media_ids": [found, also_found, missing],
+        "mode": "replace",
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let session_id = body["session_id"].as_str().unwrap();
+
+    let progress = server.wait_for(&format!("/api/scrape/progress/{}", session_id), |body| {
+        body["data"]["completed"] == true
+    }).await;
+    assert_eq!(progress["data"]["status"], "completed", "{}", progress);
+    assert_eq!(progress["data"]["success_count"], 2);
+    assert_eq!(progress["data"]["failed_count"], 1);
+
+    // 已结束的会话订阅时推送最终进度后关闭连接
+    let events = server.client.get(server.url(&format!("/api/scrape/progress/{}/events", session_id)))
+        .send().await.unwrap()
+        .text().await.unwrap();
+    assert!(events.starts_with("data: "), "{}", events);
+    assert!(events.contains("\"completed\":true"), "{}", events);
+
+    let (status, _) = server.delete(&format!("/api/scrape/progress/{}", session_id)).await;
+    assert_eq!(status, 200);
+    let (status, _) = server.get(&format!("/api/scrape/progress/{}", session_id)).await;
+    assert_eq!(status, 404);
+    let (status, _) = server.delete(&format!("/api/scrape/progress/{}", session_id)).await;
+    assert_eq!(status, 404);
+
+    let (_, media) = server.get(&format!("/api/media/{}", also_found)).await;
+    assert_eq!(media["data"]["title"], "Mock Movie Two");
+}
+
+#[tokio::test]
+async fn test_magnet_search_merges_streamed_sources() {
+    let server = TestServer::start().await;
+
+    let (status, body) = server.get("/api/scrape/magnets/media_scraper?q=MOCK").await;
+    assert_eq!(status, 200, "{}", body);
+    let session_id = body["session_id"].as_str().unwrap();
+
+    let progress = server.wait_for(&format!("/api/scrape/magnets/progress/{}", session_id), |body| {
+        body["data"]["completed"] == true
+    }).await;
+    let data = &progress["data"];
+    assert_eq!(data["status"], "completed", "{}", progress);
+    // 两个网站的结果 hash 相同，只保留先完成网站的结果
+    let links: Vec<&str> = data["results"].as_array().unwrap().iter()
+        .filter_map(|m| m["magnet_link"].as_str())
+        .collect();
+    assert_eq!(links.len(), 2, "{}", progress);
+    assert!(links.iter().all(|link| !link.contains("mirror")), "{:?}", links);
+    for site in ["MockSite", "MockMirror"] {
+        let status = data["sites_status"].as_array().unwrap().iter()
+            .find(|s| s["site_name"] == site)
+            .unwrap_or_else(|| panic!("{} missing in {}", site, progress));
+        assert_eq!(status["status"], "completed");
+        assert_eq!(status["result_count"], 2);
+    }
+}
+
+#[tokio::test]
+async fn test_search_normalizes_query_variants() {
+    let server = TestServer::start().await;
+
+    // 全角、无连字符的番号也能搜到 MOCK-001
+    let (status, body) = server.get("/api/scrape/media_scraper/search?q=%EF%BD%8D%EF%BD%8F%EF%BD%83%EF%BD%8B001").await;
+    assert_eq!(status, 200, "{}", body);
+    let codes: Vec<&str> = body["data"]["results"].as_array().unwrap().iter()
+        .filter_map(|r| r["code"].as_str())
+        .collect();
+    assert_eq!(codes, vec!["MOCK-001"], "{}", body);
+
+    let (_, body) = server.get("/api/scrape/magnets/media_scraper?q=mock002").await;
+    let session_id = body["session_id"].as_str().unwrap();
+    let progress = server.wait_for(&format!("/api/scrape/magnets/progress/{}", session_id), |body| {
+        body["data"]["completed"] == true
+    }).await;
+    assert_eq!(progress["data"]["status"], "completed", "{}", progress);
+    let titles: Vec<&str> = progress["data"]["results"].as_array().unwrap().iter()
+        .filter_map(|m| m["title"].as_str())
+        .collect();
+    assert_eq!(titles, vec!["MOCK-002 720p"]);
+}
+
+#[tokio::test]
+async fn test_magnet_search_applies_tracker_settings() {
+    let server = TestServer::start().await;
+
+    let (status, _) = server.put("/api/settings/magnet-trackers", json!({ "trackers": ["ftp://tracker.example"] })).await;
+    assert_eq!(status, 422);
+    let (status, body) = server.put("/api/settings/magnet-trackers", json!({
+        "trackers": ["udp://tracker.example:1337/announce"],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let (_, body) = server.get("/api/settings/magnet-trackers").await;
+    assert_eq!(body["data"]["trackers"][0], "udp://tracker.example:1337/announce");
+
+    let (_, body) = server.get("/api/scrape/magnets/media_scraper?q=MOCK-001").await;
+    let session_id = body["session_id"].as_str().unwrap();
+    let progress = server.wait_for(&format!("/api/scrape/magnets/progress/{}", session_id), |body| {
+        body["data"]["completed"] == true
+    }).await;
+    let results = progress["data"]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1, "{}", progress);
+    assert_eq!(
+        results[0]["magnet_link"],
+        "magnet:?xt=urn:btih:0000000000000000000000000000000000000001&tr=udp%3A%2F%2Ftracker.example%3A1337%2Fannounce"
+    );
+}
+
+#[tokio::test]
+async fn test_magnet_search_all_plugins() {
+    let server = TestServer::start().await;
+    install_mock_plugin(&server.dir().join("plugins"), "mock_magnets");
+    let (status, _) = server.post("/api/scrape/plugins/reload", json!({})).await;
+    assert_eq!(status, 200);
+
+    let (status, body) = server.get("/api/scrape/magnets/all?q=MOCK-001&timeout=30").await;
+    assert_eq!(status, 200, "{}", body);
+    let session_id = body["session_id"].as_str().unwrap();
+
+    let progress = server.wait_for(&format!("/api/scrape/magnets/progress/{}", session_id), |body| {
+        body["data"]["completed"] == true
+    }).await;
+    let data = &progress["data"];
+    assert_eq!(data["status"], "completed", "{}", progress);
+    let plugins: Vec<&str> = data["plugins_status"].as_array().unwrap().iter()
+        .map(|p| { assert_eq!(p["status"], "completed", "{}", progress); p["plugin_id"].as_str().unwrap() })
+        .collect();
+    assert_eq!(plugins, vec!["media_scraper", "mock_magnets"]);
+    // 两个插件返回同一个 hash，只保留一条并标记来源插件
+    let results = data["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1, "{}", progress);
+    assert!(plugins.contains(&results[0]["source"].as_str().unwrap()), "{}", progress);
+    assert_eq!(data["sites_status"].as_array().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_file_scan_auto_scrape() {
+    let server = TestServer::start().await;
+    let file_path = server.dir().join("library").join("MOCK-003.mp4");
+
+    let (status, body) = server.post("/api/scan/auto-scrape", json!({
+        "unmatched_files": [{
+            "file_path": file_path,
+            "file_name": "MOCK-003.mp4",
+            "file_size": 1024,
+            "parsed_code": "MOCK-003",
+        }],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let session_id = body["session_id"].as_str().unwrap();
+
+    let progress = server.wait_for(&format!("/api/scan/auto-scrape/progress/{}", session_id), |body| {
+        body["status"] == "completed"
+    }).await;
+    assert_eq!(progress["scraped_count"], 1, "{}", progress);
+
+    let (_, list) = server.get("/api/media?page=1&limit=10").await;
+    let titles: Vec<&str> = list["data"]["items"].as_array().unwrap().iter()
+        .filter_map(|m| m["title"].as_str())
+        .collect();
+    assert_eq!(titles, vec!["Mock Scene Three"]);
+}
+
+#[tokio::test]
+async fn test_identify_writes_media_actors_and_files() {
+    let server = TestServer::start().await;
+    let file_path = server.dir().join("library").join("MOCK-001.mp4");
+    let file_path = file_path.to_str().unwrap();
+
+    let (status, body) = server.post("/api/scan/identify", json!({
+        "files": [{ "file_path": file_path, "file_size": 2048 }],
+        "scrape_data": {
+            "title": "Identified Movie",
+            "actors": ["Alice Mock"],
+            "translations": [{ "language": "en", "title": "Identified Movie (EN)" }],
+        },
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["created"], true);
+    let media_id = body["media_id"].as_str().unwrap();
+
+    let (_, actors) = server.get(&format!("/api/media/{}/actors", media_id)).await;
+    assert_eq!(actors["data"].as_array().unwrap().len(), 1, "{}", actors);
+    let (_, translations) = server.get(&format!("/api/media/{}/translations", media_id)).await;
+    assert_eq!(translations["data"][0]["title"], "Identified Movie (EN)");
+    let (_, files) = server.get(&format!("/api/media/{}/files", media_id)).await;
+    assert_eq!(files["total_size"], 2048, "{}", files);
+
+    // 已关联的文件不能再次识别，也不会创建新媒体
+    let (status, _) = server.post("/api/scan/identify", json!({
+        "files": [{ "file_path": file_path, "file_size": 2048 }],
+        "scrape_data": { "title": "Duplicate" },
+    })).await;
+    assert_eq!(status, 409);
+    let (_, list) = server.get("/api/media?page=1&limit=10").await;
+    assert_eq!(list["data"]["items"].as_array().unwrap().len(), 1, "{}", list);
+}
+
+#[tokio::test]
+async fn test_mock_plugin_conformance() {
+    let server = TestServer::start().await;
+
+    let (status, body) = server.post("/api/scrape/plugins/media_scraper/selftest", json!({
+        "sample_id": "MOCK-001",
+        "timeout_secs": 10,
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["passed"], true, "{}", body);
+    assert_eq!(body["data"]["warning_count"], 0, "{}", body);
+}
+
+#[tokio::test]
+async fn test_refresh_media_from_provider() {
+    let server = TestServer::start().await;
+    let media_id = server.create_media("Refresh Me", None).await;
+
+    let (status, _) = server.post(&format!("/api/media/{}/refresh", media_id), json!({ "mode": "merge" })).await;
+    assert_eq!(status, 422);
+    let (status, _) = server.post(&format!("/api/media/{}/refresh", media_id), json!({ "include_fields": ["nope"] })).await;
+    assert_eq!(status, 422);
+
+    // 没有外部ID时无法确定提供方
+    let (status, body) = server.post(&format!("/api/media/{}/refresh", media_id), json!({})).await;
+    assert_eq!(status, 422, "{}", body);
+
+    // 测试环境未配置 TMDB
+    let (status, _) = server.put(&format!("/api/media/{}/external-ids", media_id), json!({ "tmdb_id": 603 })).await;
+    assert_eq!(status, 200);
+    let (status, body) = server.post(&format!("/api/media/{}/refresh", media_id), json!({ "mode": "replace" })).await;
+    assert_eq!(status, 502, "{}", body);
+
+    let (status, _) = server.post("/api/media/missing/refresh", json!({})).await;
+    assert_eq!(status, 404);
+}
+
+/// 只提供 /movie/603 详情的模拟 TMDB
+async fn start_mock_tmdb() -> String {
+    async fn movie() -> axum::Json<serde_json::Value> {
+        axum::Json(json!({
+            "id": 603,
+            "title": "The Matrix",
+            "original_title": "The Matrix",
+            "overview": "A hacker learns the truth.",
+            "release_date": "1999-03-30",
+            "poster_path": null,
+            "backdrop_path": null,
+            "vote_average": 8.2,
+            "vote_count": 100,
+            "runtime": 136,
+            "budget": 0,
+            "revenue": 0,
+            "status": "Released",
+            "tagline": null,
+            "genres": [{ "id": 28, "name": "Action" }],
+            "production_countries": [],
+            "spoken_languages": [],
+            "credits": null,
+            "imdb_id": "tt0133093",
+            "adult": false,
+            "original_language": "en",
+            "popularity": 1.0,
+        }))
+    }
+    serve(axum::Router::new().route("/movie/603", axum::routing::get(movie))).await
+}
+
+#[tokio::test]
+async fn test_refresh_media_supplement_mode() {
+    let tmdb = start_mock_tmdb().await;
+    let server = TestServer::start_with_env(&[("TMDB_API_KEY", "test"), ("TMDB_BASE_URL", &tmdb)]).await;
+    let media_id = server.create_media("Refresh Me", None).await;
+    let (status, _) = server.put(&format!("/api/media/{}/external-ids", media_id), json!({ "tmdb_id": 603 })).await;
+    assert_eq!(status, 200);
+
+    // 不带请求体时按默认的 supplement 模式刷新：保留已有标题，只补全空字段
+    let response = server.client.post(server.url(&format!("/api/media/{}/refresh", media_id))).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["title"], "Refresh Me", "{}", body);
+    assert_eq!(body["data"]["overview"], "A hacker learns the truth.", "{}", body);
+    assert_eq!(body["data"]["runtime"], 136, "{}", body);
+
+    let (status, body) = server.post(&format!("/api/media/{}/refresh", media_id), json!({ "mode": "supplement" })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["title"], "Refresh Me", "{}", body);
+
+    let (status, body) = server.post(&format!("/api/media/{}/refresh", media_id), json!({ "mode": "replace" })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["title"], "The Matrix", "{}", body);
+}
+
+#[tokio::test]
+async fn test_scrape_url_requires_url_domains() {
+    let server = TestServer::start().await;
+    let (status, body) = server.post("/api/scrape/url", json!({ "url": "https://mock.invalid/MOCK-001", "dry_run": true })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["plugin_id"], "media_scraper");
+    assert_eq!(body["data"]["result"]["code"], "MOCK-001", "{}", body);
+
+    let (status, body) = server.post("/api/scrape/url", json!({ "url": "https://other.invalid/MOCK-001", "dry_run": true })).await;
+    assert_eq!(status, 404, "{}", body);
+    assert!(body.to_string().contains("No plugin supports URL"), "{}", body);
+
+    let (status, body) = server.post("/api/scrape/url", json!({
+        "url": "https://other.invalid/MOCK-001",
+        "plugin_id": "media_scraper",
+        "dry_run": true,
+    })).await;
+    assert_eq!(status, 400, "{}", body);
+    assert!(body.to_string().contains("does not support URL"), "{}", body);
+}
@@ -0,0 +1,102 @@
+// 搜索建议集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::{json, Value};
+
+async fn search_as(server: &TestServer, user: &str, query: &str) {
+    let response = server.client.get(server.url("/api/search"))
+        .query(&[("q", query), ("source", "local")])
+        .header("X-User-Id", user)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+async fn suggestions_as(server: &TestServer, user: &str, query: &str) -> Vec<(String, String)> {
+    let body: Value = server.client.get(server.url("/api/search/suggestions"))
+        .query(&[("q", query)])
+        .header("X-User-Id", user)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    body["data"]["suggestions"].as_array().unwrap().iter()
+        .map(|s| (s["text"].as_str().unwrap().to_string(), s["type_"].as_str().unwrap().to_string()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_search_suggestions_from_history_and_library() {
+    let server = TestServer::start().await;
+    server.create_media("Moonlight Garden", Some("MOON-001")).await;
+    server.create_media("Moon River", Some("MOON-002")).await;
+    let (status, body) = server.post("/api/actors", json!({ "name": "Moana Aoi" })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    // 媒体库匹配：番号、标题、演员
+    let suggestions = suggestions_as(&server, "alice", "mo").await;
+    let types: Vec<&str> = suggestions.iter().map(|(_, t)| t.as_str()).collect();
+    assert!(types.contains(&"code") && types.contains(&"title") && types.contains(&"person"), "{:?}", suggestions);
+    assert!(suggestions.contains(&("Moon River".to_string(), "title".to_string())), "{:?}", suggestions);
+    assert!(suggestions.contains(&("Moana Aoi".to_string(), "person".to_string())), "{:?}", suggestions);
+
+    // 搜索历史按用户区分，搜索次数多的排在前面
+    search_as(&server, "alice", "moon river").await;
+    search_as(&server, "alice", "moonlight").await;
+    search_as(&server, "alice", "Moonlight").await;
+    let suggestions = suggestions_as(&server, "alice", "moo").await;
+    assert_eq!(suggestions[0].0.to_lowercase(), "moonlight", "{:?}", suggestions);
+    assert_eq!(suggestions[0].1, "history");
+    assert_eq!(suggestions[1], ("moon river".to_string(), "history".to_string()), "{:?}", suggestions);
+    // 与历史重复的标题不再出现
+    assert!(!suggestions.iter().any(|(text, _)| text == "Moon River"), "{:?}", suggestions);
+    assert!(!suggestions_as(&server, "bob", "moo").await.iter().any(|(_, t)| t == "history"));
+
+    // 关键词为空时只返回历史
+    let suggestions = suggestions_as(&server, "alice", "").await;
+    assert_eq!(suggestions.len(), 2, "{:?}", suggestions);
+
+    // 删除单个关键词，再清空全部
+    let response = server.client.delete(server.url("/api/search/history?q=MOONLIGHT"))
+        .header("X-User-Id", "alice")
+        .send()
+        .await
+        .unwrap();
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["removed"], 2, "{}", body);
+    let response = server.client.delete(server.url("/api/search/history"))
+        .header("X-User-Id", "alice")
+        .send()
+        .await
+        .unwrap();
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["removed"], 1, "{}", body);
+    assert!(suggestions_as(&server, "alice", "").await.is_empty());
+}
+
+#[tokio::test]
+async fn test_trending_searches_from_history() {
+    let server = TestServer::start().await;
+    let (status, body) = server.get("/api/search/trending").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"], json!([]));
+
+    // 汇总所有用户的搜索
+    search_as(&server, "alice", "matrix").await;
+    search_as(&server, "bob", "Matrix").await;
+    search_as(&server, "bob", "inception").await;
+    let (_, body) = server.get("/api/search/trending?days=7").await;
+    let trending: Vec<String> = serde_json::from_value(body["data"].clone()).unwrap();
+    assert_eq!(trending.len(), 2, "{:?}", trending);
+    assert_eq!(trending[0].to_lowercase(), "matrix");
+
+    let (_, body) = server.get("/api/search/trending?limit=1").await;
+    assert_eq!(body["data"].as_array().unwrap().len(), 1);
+    let (status, _) = server.get("/api/search/trending?days=0").await;
+    assert_eq!(status, 422);
+}
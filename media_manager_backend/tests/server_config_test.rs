@@ -0,0 +1,33 @@
+// 服务器连接参数集成测试
+
+mod common;
+
+use common::TestServer;
+
+fn h2_client() -> reqwest::Client {
+    reqwest::Client::builder().http2_prior_knowledge().build().unwrap()
+}
+
+#[tokio::test]
+async fn test_http2_and_keep_alive() {
+    let server = TestServer::start().await;
+
+    let response = h2_client().get(server.url("/api/health")).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.version(), reqwest::Version::HTTP_2);
+
+    // HTTP/1.1 客户端不受影响，连接可以复用
+    for _ in 0..3 {
+        let response = server.client.get(server.url("/api/health")).send().await.unwrap();
+        assert_eq!(response.version(), reqwest::Version::HTTP_11);
+        assert_ne!(response.headers().get("connection").map(|v| v.to_str().unwrap()), Some("close"));
+    }
+}
+
+#[tokio::test]
+async fn test_http2_disabled() {
+    let server = TestServer::start_with_env(&[("HTTP2_ENABLED", "false")]).await;
+    assert!(h2_client().get(server.url("/api/health")).send().await.is_err());
+    let (status, _) = server.get("/api/health").await;
+    assert_eq!(status, 200);
+}
@@ -0,0 +1,110 @@
+// 对象存储根目录集成测试（使用进程内的简易 WebDAV 服务）
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::body::Bytes;
+use axum::http::{header, HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use common::{serve, TestServer};
+use serde_json::json;
+
+type Files = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+async fn webdav(
+    axum::extract::State(files): axum::extract::State<Files>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if headers.get(header::AUTHORIZATION).is_none() {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let path = uri.path().to_string();
+    let mut files = files.lock().unwrap();
+    match method.as_str() {
+        "PROPFIND" => StatusCode::MULTI_STATUS.into_response(),
+        "MKCOL" => StatusCode::CREATED.into_response(),
+        "PUT" => {
+            files.insert(path, body.to_vec());
+            StatusCode::CREATED.into_response()
+        }
+        "HEAD" | "GET" => {
+            let Some(data) = files.get(&path) else {
+                return StatusCode::NOT_FOUND.into_response();
+            };
+            let range = headers.get(header::RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("bytes="))
+                .and_then(|v| v.split_once('-'))
+                .map(|(start, end)| (start.parse::<usize>().unwrap(), end.parse::<usize>().unwrap()));
+            let body = match range {
+                Some((start, end)) => data[start..=end].to_vec(),
+                None => data.clone(),
+            };
+            if method == Method::HEAD {
+                return (StatusCode::OK, [(header::CONTENT_LENGTH, data.len().to_string())]).into_response();
+            }
+            let status = if range.is_some() { StatusCode::PARTIAL_CONTENT } else { StatusCode::OK };
+            (status, body).into_response()
+        }
+        _ => StatusCode::METHOD_NOT_ALLOWED.into_response(),
+    }
+}
+
+async fn start_webdav(files: Files) -> String {
+    format!("{}/dav", serve(axum::Router::new().fallback(webdav).with_state(files)).await)
+}
+
+#[tokio::test]
+async fn test_webdav_root_streams_with_range() {
+    let server = TestServer::start().await;
+    let files: Files = Arc::default();
+    files.lock().unwrap().insert("/dav/jav/ABC%20123.mp4".to_string(), b"0123456789".to_vec());
+    let dav_url = start_webdav(files).await;
+
+    let (status, root) = server.post("/api/library/roots", json!({
+        "path": "webdav://nas/media",
+        "storage": { "type": "webdav", "url": dav_url, "username": "user", "password": "secret" },
+    })).await;
+    assert_eq!(status, 200, "{}", root);
+    assert_eq!(root["data"]["storage_type"], "webdav");
+    assert_eq!(root["data"]["reachable"], true, "{}", root);
+    assert!(!root.to_string().contains("secret"), "密钥不应返回给客户端: {}", root);
+    let root_id = root["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, body) = server.post("/api/library/roots", json!({
+        "path": "s3://bucket",
+        "storage": { "type": "s3", "endpoint": "not a url", "bucket": "b", "access_key_id": "a", "secret_access_key": "s" },
+    })).await;
+    assert_eq!(status, 422, "{}", body);
+
+    let media_id = server.create_media("Remote Movie", Some("ABC-123")).await;
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    sqlx::query("INSERT INTO media_files (id, media_id, file_path, file_size) VALUES ('f1', ?, 'webdav://nas/media/jav/ABC 123.mp4', 10)")
+        .bind(&media_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+
+    let response = server.client.get(server.url(&format!("/api/media/{}/video", media_id)))
+        .header("Range", "bytes=2-5")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 206);
+    assert_eq!(response.headers()["content-range"], "bytes 2-5/10");
+    assert_eq!(response.bytes().await.unwrap().as_ref(), b"2345");
+
+    let response = server.client.get(server.url(&format!("/api/media/{}/video", media_id))).send().await.unwrap();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.bytes().await.unwrap().as_ref(), b"0123456789");
+
+    // 对象存储上的根目录不能用本地扫描器扫描
+    let (status, _) = server.post("/api/scan/start", json!({ "root_ids": [root_id] })).await;
+    assert_eq!(status, 400);
+}
@@ -0,0 +1,91 @@
+// 播放会话集成测试
+
+mod common;
+
+use std::time::Duration;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_stream_sessions_limit_and_terminate() {
+    let server = TestServer::start().await;
+    let library = server.dir().join("library");
+    std::fs::create_dir_all(&library).unwrap();
+    // 足够大的文件，客户端不读取时响应体不会发送完
+    let mut files = Vec::new();
+    for part in 1..=2 {
+        let path = library.join(format!("MOCK-040-cd{}.mp4", part));
+        std::fs::File::create(&path).unwrap().set_len(64 * 1024 * 1024).unwrap();
+        files.push(json!({ "file_path": path.to_str().unwrap(), "file_size": 64 * 1024 * 1024, "part_number": part }));
+    }
+    let (status, body) = server.post("/api/scan/identify", json!({
+        "files": files,
+        "scrape_data": { "title": "Session Movie" },
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["media_id"].as_str().unwrap().to_string();
+
+    let (status, body) = server.put("/api/settings/streaming", json!({ "max_streams_per_user": 1000 })).await;
+    assert_eq!(status, 422, "{}", body);
+    let (status, body) = server.put("/api/settings/streaming", json!({ "max_streams_per_user": 1 })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let play = |user: &str, part: usize| {
+        server.client
+            .get(server.url(&format!("/api/media/{}/video?part={}", media_id, part)))
+            .header("X-User-Id", user)
+            .send()
+    };
+
+    let mut first = play("alice", 0).await.unwrap();
+    assert_eq!(first.status(), 200);
+    // 同一分段的第二个请求共用会话
+    let second = play("alice", 0).await.unwrap();
+    assert_eq!(second.status(), 200);
+    assert_eq!(play("alice", 1).await.unwrap().status(), 429);
+    let other = play("bob", 1).await.unwrap();
+    assert_eq!(other.status(), 200);
+
+    let (status, body) = server.get("/api/sessions?user=alice").await;
+    assert_eq!(status, 200, "{}", body);
+    let sessions = body["data"].as_array().unwrap();
+    assert_eq!(sessions.len(), 1, "{}", body);
+    assert_eq!(sessions[0]["media_id"], media_id.as_str());
+    assert_eq!(sessions[0]["kind"], "direct");
+    assert_eq!(sessions[0]["connections"], 2);
+    let session_id = sessions[0]["id"].as_str().unwrap().to_string();
+
+    // 断开连接后释放会话
+    drop(second);
+    drop(other);
+    let mut remaining = json!(null);
+    for _ in 0..50 {
+        let (_, body) = server.get("/api/sessions").await;
+        remaining = body["data"].clone();
+        if remaining.as_array().unwrap().len() == 1 && remaining[0]["connections"] == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(remaining.as_array().unwrap().len(), 1, "{}", remaining);
+    assert_eq!(remaining[0]["connections"], 1, "{}", remaining);
+
+    // 终止会话后响应体提前结束
+    let (status, body) = server.delete(&format!("/api/sessions/{}", session_id)).await;
+    assert_eq!(status, 200, "{}", body);
+    let (status, _) = server.delete(&format!("/api/sessions/{}", session_id)).await;
+    assert_eq!(status, 404);
+    let mut received = 0;
+    let read_all = async {
+        while let Ok(Some(chunk)) = first.chunk().await {
+            received += chunk.len();
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(10), read_all).await.unwrap();
+    assert!(received < 64 * 1024 * 1024);
+
+    let (_, body) = server.get("/api/sessions").await;
+    assert_eq!(body["data"], json!([]));
+    assert_eq!(play("alice", 1).await.unwrap().status(), 200);
+}
@@ -0,0 +1,311 @@
+// 厂商/系列接口集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_studio_series_counts_follow_media() {
+    let server = TestServer::start().await;
+
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [
+            { "code": "CNT-001", "title": "Count One", "studio": "Count Studio", "series": "Count Series" },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    // 新建厂商/系列时统计已有媒体
+    let (status, studio) = server.post("/api/studios", json!({ "name": "count studio" })).await;
+    assert_eq!(status, 200, "{}", studio);
+    assert_eq!(studio["data"]["media_count"], 1, "{}", studio);
+    let studio_id = studio["data"]["id"].as_str().unwrap().to_string();
+    let (status, series) = server.post("/api/series", json!({ "name": "Count Series" })).await;
+    assert_eq!(status, 200, "{}", series);
+    assert_eq!(series["data"]["media_count"], 1, "{}", series);
+    let series_id = series["data"]["id"].as_str().unwrap().to_string();
+    let other_id = server.post("/api/studios", json!({ "name": "Other Studio" })).await.1["data"]["id"]
+        .as_str().unwrap().to_string();
+
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [
+            { "code": "CNT-002", "title": "Count Two", "studio": "Count Studio", "series": "Count Series" },
+            // 改到其他厂商
+            { "code": "CNT-001", "title": "Count One", "studio": "Other Studio", "series": "Count Series" },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["data"]["results"][0]["media_id"].as_str().unwrap().to_string();
+
+    let count = |path: String| {
+        let server = &server;
+        async move { server.get(&path).await.1["data"]["media_count"].clone() }
+    };
+    assert_eq!(count(format!("/api/studios/{}", studio_id)).await, 1);
+    assert_eq!(count(format!("/api/studios/{}", other_id)).await, 1);
+    assert_eq!(count(format!("/api/series/{}", series_id)).await, 2);
+
+    let response = server.client.delete(server.url(&format!("/api/media/{}", media_id))).send().await.unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(count(format!("/api/studios/{}", studio_id)).await, 0);
+    assert_eq!(count(format!("/api/series/{}", series_id)).await, 1);
+}
+
+#[tokio::test]
+async fn test_list_pagination_sorting_and_filter() {
+    let server = TestServer::start().await;
+    for name in ["Beta Works", "alpha Films", "Gamma Films"] {
+        let (status, body) = server.post("/api/studios", json!({ "name": name })).await;
+        assert_eq!(status, 200, "{}", body);
+        let (status, body) = server.post("/api/series", json!({ "name": format!("{} Series", name) })).await;
+        assert_eq!(status, 200, "{}", body);
+        let (status, body) = server.post("/api/actors", json!({ "name": name })).await;
+        assert_eq!(status, 200, "{}", body);
+    }
+    let names = |items: &serde_json::Value| -> Vec<String> {
+        items.as_array().unwrap().iter().map(|i| i["name"].as_str().unwrap().to_string()).collect()
+    };
+
+    let (status, body) = server.get("/api/studios?sort_by=name&limit=2").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["total"], 3);
+    assert_eq!(body["data"]["limit"], 2);
+    assert_eq!(names(&body["data"]["studios"]), vec!["alpha Films", "Beta Works"]);
+    let (_, body) = server.get("/api/studios?sort_by=name&sort_order=desc&limit=2&offset=2").await;
+    assert_eq!(names(&body["data"]["studios"]), vec!["alpha Films"]);
+
+    let (_, body) = server.get("/api/series?q=films&sort_by=name&sort_order=desc").await;
+    assert_eq!(body["data"]["total"], 2);
+    assert_eq!(names(&body["data"]["series"]), vec!["Gamma Films Series", "alpha Films Series"]);
+
+    let (_, body) = server.get("/api/actors?q=films&sort_by=name").await;
+    assert_eq!(body["data"]["total"], 2);
+    assert_eq!(names(&body["data"]["actors"]), vec!["alpha Films", "Gamma Films"]);
+    // 最近添加的在前
+    let (_, body) = server.get("/api/actors?sort_by=created_at").await;
+    assert_eq!(names(&body["data"]["actors"]), vec!["Gamma Films", "alpha Films", "Beta Works"]);
+
+    let (status, _) = server.get("/api/actors?sort_by=name;drop").await;
+    assert_eq!(status, 400);
+    let (status, _) = server.get("/api/studios?sort_order=sideways").await;
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn test_studio_overview() {
+    let server = TestServer::start().await;
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [
+            { "code": "OVR-001", "title": "Overview One", "studio": "Overview Studio", "series": "Main Line", "year": 2020 },
+            { "code": "OVR-002", "title": "Overview Two", "studio": "overview studio", "series": "Main Line", "year": 2021 },
+            { "code": "OVR-003", "title": "Overview Three", "studio": "Overview Studio", "series": "Side Line", "year": 2021 },
+            { "code": "OVR-004", "title": "Overview Four", "studio": "Overview Studio" },
+            { "code": "OTH-001", "title": "Other One", "studio": "Other Studio", "series": "Main Line", "year": 2021 },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_ids: Vec<String> = body["data"]["results"].as_array().unwrap().iter()
+        .map(|r| r["media_id"].as_str().unwrap().to_string())
+        .collect();
+
+    let (_, studio) = server.post("/api/studios", json!({ "name": "Overview Studio" })).await;
+    let studio_id = studio["data"]["id"].as_str().unwrap().to_string();
+    let (_, series) = server.post("/api/series", json!({ "name": "Main Line", "studio_id": studio_id })).await;
+    let (_, actor) = server.post("/api/actors", json!({ "name": "Regular" })).await;
+    let actor_id = actor["data"]["id"].as_str().unwrap();
+    for media_id in &media_ids[..2] {
+        let (status, body) = server.post(&format!("/api/media/{}/actors", media_id), json!({ "actor_id": actor_id, "role": "cast" })).await;
+        assert_eq!(status, 200, "{}", body);
+    }
+    let (status, body) = server.post("/api/collections", json!({ "media_id": media_ids[0] })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (status, body) = server.get(&format!("/api/studios/{}/overview?limit=1", studio_id)).await;
+    assert_eq!(status, 200, "{}", body);
+    let overview = &body["data"];
+    assert_eq!(overview["name"], "Overview Studio");
+    assert_eq!(overview["total_media"], 4);
+    assert_eq!(overview["collected_media"], 1);
+    assert_eq!(overview["collected_percent"], 25.0);
+    assert_eq!(overview["media_by_year"], json!([
+        { "year": 2021, "media_count": 2 },
+        { "year": 2020, "media_count": 1 },
+        { "year": null, "media_count": 1 },
+    ]));
+    assert_eq!(overview["top_series"], json!([{ "id": series["data"]["id"], "name": "Main Line", "media_count": 2 }]));
+    assert_eq!(overview["top_actors"][0]["name"], "Regular");
+    assert_eq!(overview["top_actors"][0]["media_count"], 2);
+    assert_eq!(overview["recent_media"].as_array().unwrap().len(), 1);
+
+    let (status, _) = server.get("/api/studios/missing/overview").await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_series_suggestions_detect_and_apply() {
+    let server = TestServer::start().await;
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [
+            { "code": "SUN-001", "title": "Sunrise Collection 1", "studio": "Dawn", "series": "Sunrise Collection" },
+            { "code": "SUN-002", "title": "Sunrise Collection 2", "studio": "Dawn", "series": "Sunrise Collection" },
+            { "code": "SUN-003", "title": "Untitled Morning" },
+            { "code": "ZZZ-001", "title": "Sunrise Collection 3", "studio": "Dawn" },
+            { "code": "ZZZ-002", "title": "Sunrise Collection 4", "studio": "Dusk" },
+            { "code": "ZZZ-003", "title": "Nothing Here" },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (status, body) = server.get("/api/series/suggestions?refresh=true").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["media_scanned"], 4, "{}", body);
+    let suggestions = body["data"]["suggestions"].as_array().unwrap();
+    let found: Vec<(&str, &str, &str)> = suggestions.iter()
+        .map(|s| (s["code"].as_str().unwrap(), s["series"].as_str().unwrap(), s["reason"].as_str().unwrap()))
+        .collect();
+    assert_eq!(found.len(), 2, "{:?}", found);
+    assert!(found.contains(&("SUN-003", "Sunrise Collection", "code_prefix")), "{:?}", found);
+    assert!(found.contains(&("ZZZ-001", "Sunrise Collection", "title")), "{:?}", found);
+    let title_match = suggestions.iter().find(|s| s["code"] == "ZZZ-001").unwrap()["media_id"].clone();
+
+    // 只应用选中的建议
+    let (status, body) = server.post("/api/series/suggestions/apply", json!({
+        "assignments": [{ "media_id": title_match, "series": "Sunrise Collection" }],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["applied"], 1);
+    let (_, body) = server.get("/api/series/suggestions").await;
+    assert_eq!(body["data"]["suggestions"].as_array().unwrap().len(), 1, "{}", body);
+
+    // 不传 assignments 时应用全部
+    let (_, body) = server.post("/api/series/suggestions/apply", json!({})).await;
+    assert_eq!(body["data"]["applied"], 1, "{}", body);
+    let (_, body) = server.get("/api/series/suggestions").await;
+    assert_eq!(body["data"]["media_scanned"], 2, "{}", body);
+}
+
+#[tokio::test]
+async fn test_rename_propagates_and_reassign_studio() {
+    let server = TestServer::start().await;
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [
+            { "code": "REN-001", "title": "Rename One", "studio": "Old Name", "series": "Old Line" },
+            { "code": "REN-002", "title": "Rename Two", "studio": "old name", "series": "Old Line" },
+            { "code": "REN-003", "title": "Rename Three", "studio": "Target", "series": "Shared" },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_ids: Vec<String> = body["data"]["results"].as_array().unwrap().iter()
+        .map(|r| r["media_id"].as_str().unwrap().to_string())
+        .collect();
+    let media_field = |index: usize, field: &'static str| {
+        let server = &server;
+        let id = media_ids[index].clone();
+        async move { server.get(&format!("/api/media/{}", id)).await.1["data"][field].clone() }
+    };
+
+    let (_, studio) = server.post("/api/studios", json!({ "name": "Old Name" })).await;
+    let studio_id = studio["data"]["id"].as_str().unwrap().to_string();
+    let (_, series) = server.post("/api/series", json!({ "name": "Old Line", "studio_id": studio_id })).await;
+    let series_id = series["data"]["id"].as_str().unwrap().to_string();
+
+    // 改名同步到媒体，计数不变
+    let (status, body) = server.put(&format!("/api/studios/{}", studio_id), json!({ "name": "New Name" })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["media_count"], 2, "{}", body);
+    assert_eq!(media_field(1, "studio").await, "New Name");
+    let (status, body) = server.put(&format!("/api/series/{}", series_id), json!({ "name": "New Line" })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["media_count"], 2, "{}", body);
+    assert_eq!(media_field(0, "series").await, "New Line");
+
+    // 移到另一个厂商并删除原厂商
+    let (_, target) = server.post("/api/studios", json!({ "name": "Target" })).await;
+    let target_id = target["data"]["id"].as_str().unwrap().to_string();
+    let (status, _) = server.post(&format!("/api/studios/{}/reassign", studio_id), json!({ "target_studio_id": studio_id })).await;
+    assert_eq!(status, 422);
+    let (status, body) = server.post(&format!("/api/studios/{}/reassign", studio_id), json!({
+        "target_studio_id": target_id,
+        "delete_source": true,
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["moved_media"], 2);
+    assert_eq!(body["data"]["moved_series"], 1);
+    assert_eq!(media_field(0, "studio").await, "Target");
+
+    let (_, body) = server.get(&format!("/api/studios/{}", target_id)).await;
+    assert_eq!(body["data"]["media_count"], 3, "{}", body);
+    assert_eq!(body["data"]["series_list"][0]["id"], series_id.as_str());
+    let (status, _) = server.get(&format!("/api/studios/{}", studio_id)).await;
+    assert_eq!(status, 404);
+}
+
+#[tokio::test]
+async fn test_link_media_to_studio_and_series() {
+    let server = TestServer::start().await;
+    let (status, body) = server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [
+            { "code": "LNK-001", "title": "Link One", "studio": "Link Studio", "series": "Link Line" },
+            { "code": "LNK-002", "title": "Link Two", "studio": "link studio" },
+            { "code": "LNK-003", "title": "Link Three", "studio": "Lnk Studio", "series": "Link Line" },
+        ],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    let (_, studio) = server.post("/api/studios", json!({ "name": "Link Studio" })).await;
+    let studio_id = studio["data"]["id"].as_str().unwrap().to_string();
+    server.post("/api/series", json!({ "name": "Link Line", "studio_id": studio_id })).await;
+
+    let (status, body) = server.post("/api/studios-series/link-media", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["linked_studios"], 2, "{}", body);
+    assert_eq!(body["data"]["linked_series"], 2, "{}", body);
+    assert_eq!(body["data"]["unmatched_studios"], json!([{ "name": "Lnk Studio", "media_count": 1 }]));
+    assert_eq!(body["data"]["unmatched_series"], json!([]));
+
+    // 已关联的媒体按外键过滤（忽略大小写），未关联的按文本字段过滤
+    let (_, body) = server.get("/api/media?studio=LINK%20STUDIO").await;
+    assert_eq!(body["data"]["total"], 2, "{}", body);
+    let (_, body) = server.get("/api/media?studio=Lnk%20Studio").await;
+    assert_eq!(body["data"]["total"], 1, "{}", body);
+    let (_, body) = server.get("/api/media?series=Link%20Line").await;
+    assert_eq!(body["data"]["total"], 2, "{}", body);
+
+    // 新增的媒体自动关联
+    server.post("/api/media/upsert", json!({
+        "match_on": "code",
+        "items": [{ "code": "LNK-004", "title": "Link Four", "studio": "LINK STUDIO" }],
+    })).await;
+    let (_, body) = server.post("/api/studios-series/link-media", json!({})).await;
+    assert_eq!(body["data"]["linked_studios"], 0, "{}", body);
+    let (_, body) = server.get("/api/media?studio=Link%20Studio").await;
+    assert_eq!(body["data"]["total"], 3, "{}", body);
+}
+
+#[tokio::test]
+async fn test_series_gaps_require_releases_capability() {
+    let server = TestServer::start().await;
+    server.create_media("Mock Movie One", Some("MOCK-001")).await;
+    let (status, series) = server.post("/api/series", json!({ "name": "Mock Series" })).await;
+    assert_eq!(status, 200, "{}", series);
+    let path = format!("/api/series/{}/gaps", series["data"]["id"].as_str().unwrap());
+
+    let (status, body) = server.get(&path).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["known_count"], 2, "{}", body);
+
+    server.set_plugin_capabilities(json!(["search_magnets"])).await;
+    let (status, body) = server.get(&path).await;
+    assert_eq!(status, 400, "{}", body);
+    assert!(body.to_string().contains("No plugin supports release lists"), "{}", body);
+    let (status, body) = server.get(&format!("{}?plugin_id=media_scraper", path)).await;
+    assert_eq!(status, 400, "{}", body);
+    assert!(body.to_string().contains("does not support release lists"), "{}", body);
+}
@@ -0,0 +1,86 @@
+// 磁力链接做种人数刷新集成测试
+
+mod common;
+
+use axum::extract::RawQuery;
+use axum::routing::get;
+use common::{serve, TestServer};
+use serde_json::json;
+
+const ALIVE_HASH: &str = "0000000000000000000000000000000000000001";
+const DEAD_HASH: &str = "0000000000000000000000000000000000000002";
+
+/// 模拟 HTTP tracker：hash 1 有 12 个做种者，hash 2 没有做种者
+async fn start_tracker() -> String {
+    async fn scrape(RawQuery(query): RawQuery) -> Vec<u8> {
+        let query = query.unwrap_or_default();
+        let mut body = b"d5:filesd".to_vec();
+        for (last_byte, seeders) in [(1u8, 12), (2u8, 0)] {
+            if !query.contains(&format!("info_hash={}%{:02X}", "%00".repeat(19), last_byte)) {
+                continue;
+            }
+            let mut hash = [0u8; 20];
+            hash[19] = last_byte;
+            body.extend(b"20:");
+            body.extend(hash);
+            body.extend(format!("d8:completei{}e10:downloadedi30e10:incompletei4ee", seeders).as_bytes());
+        }
+        body.extend(b"ee");
+        body
+    }
+    format!("{}/announce", serve(axum::Router::new().route("/scrape", get(scrape))).await)
+}
+
+#[tokio::test]
+async fn test_refresh_swarm_stats_from_tracker() {
+    let server = TestServer::start().await;
+    let tracker = start_tracker().await;
+    let magnet = |hash: &str, tracker: Option<&str>| json!({
+        "name": hash,
+        "url": match tracker {
+            Some(tracker) => format!("magnet:?xt=urn:btih:{}&tr={}", hash, tracker),
+            None => format!("magnet:?xt=urn:btih:{}", hash),
+        },
+        "link_type": "magnet",
+        "size": null,
+        "password": null,
+    });
+    let (status, body) = server.post("/api/media", json!({
+        "title": "Seeded Movie",
+        "media_type": "Movie",
+        "download_links": [magnet(ALIVE_HASH, Some(&tracker)), magnet(DEAD_HASH, Some(&tracker)), magnet(&"3".repeat(40), None)],
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+    let media_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let (status, body) = server.get("/api/settings/swarm-stats").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["enabled"], false);
+    let (status, _) = server.put("/api/settings/swarm-stats", json!({ "enabled": true, "concurrency": 0 })).await;
+    assert_eq!(status, 422);
+
+    let (status, body) = server.post("/api/magnets/stats/refresh", json!({ "ids": [media_id] })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["checked_magnets"], 3, "{}", body);
+    assert_eq!(body["data"]["checked_trackers"], 1);
+    assert_eq!(body["data"]["alive"], 1);
+    assert_eq!(body["data"]["dead"], 1);
+    assert_eq!(body["data"]["unknown"], 1);
+
+    let (_, body) = server.get(&format!("/api/magnets/stats?media_id={}", media_id)).await;
+    let stats = body["data"].as_array().unwrap();
+    assert_eq!(stats.len(), 3, "{}", body);
+    let alive = stats.iter().find(|s| s["info_hash"] == ALIVE_HASH).unwrap();
+    assert_eq!(alive["seeders"], 12);
+    assert_eq!(alive["leechers"], 4);
+    assert_eq!(alive["completed"], 30);
+    assert_eq!(alive["dead"], false);
+    let dead = stats.iter().find(|s| s["info_hash"] == DEAD_HASH).unwrap();
+    assert_eq!(dead["dead"], true);
+
+    // 隐藏没有做种者的磁力链接
+    let (_, body) = server.get(&format!("/api/magnets/stats?media_id={}&alive_only=true", media_id)).await;
+    let hashes: Vec<&str> = body["data"].as_array().unwrap().iter().filter_map(|s| s["info_hash"].as_str()).collect();
+    assert!(!hashes.contains(&DEAD_HASH), "{:?}", hashes);
+    assert_eq!(hashes.len(), 2);
+}
@@ -0,0 +1,34 @@
+// 对端变更推送鉴权集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+fn push_body() -> serde_json::Value {
+    json!({ "origin": "peer-1", "changes": [] })
+}
+
+#[tokio::test]
+async fn test_push_disabled_without_token() {
+    let server = TestServer::start().await;
+    let (status, _) = server.post("/api/sync/push", push_body()).await;
+    assert_eq!(status, 403);
+}
+
+#[tokio::test]
+async fn test_push_requires_matching_token() {
+    let server = TestServer::start_with_env(&[("SYNC_PEER_TOKEN", "secret")]).await;
+    let (status, _) = server.post("/api/sync/push", push_body()).await;
+    assert_eq!(status, 401);
+
+    let push = |token: &'static str| server.client.post(server.url("/api/sync/push"))
+        .header("X-Sync-Token", token)
+        .json(&push_body())
+        .send();
+    assert_eq!(push("wrong").await.unwrap().status().as_u16(), 401);
+    let response = push("secret").await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["data"]["applied"], 0);
+}
@@ -0,0 +1,62 @@
+// 缩略图生成队列集成测试
+
+mod common;
+
+use std::time::Duration;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_backfill_and_on_demand_thumbnails() {
+    let server = TestServer::start_with_env(&[("THUMBNAIL_WORKERS", "2")]).await;
+    let library = server.dir().join("library");
+    std::fs::create_dir_all(&library).unwrap();
+    // 不是有效的视频文件，FFmpeg 会生成失败
+    let existing = library.join("MOCK-020.mp4");
+    std::fs::write(&existing, b"not a video").unwrap();
+
+    let mut media_ids = Vec::new();
+    for (title, path) in [("Existing", existing.clone()), ("Missing", library.join("MOCK-021.mp4"))] {
+        let (status, body) = server.post("/api/scan/identify", json!({
+            "files": [{ "file_path": path.to_str().unwrap(), "file_size": 11, "part_number": 1 }],
+            "scrape_data": { "title": title },
+        })).await;
+        assert_eq!(status, 200, "{}", body);
+        media_ids.push(body["media_id"].as_str().unwrap().to_string());
+    }
+
+    let (status, body) = server.get("/api/maintenance/thumbnails").await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["workers"], 2);
+
+    let (status, body) = server.post("/api/maintenance/thumbnails/backfill", json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["queued"], 1, "{}", body);
+    assert_eq!(body["data"]["missing"], 1);
+    assert_eq!(body["data"]["cached"], 0);
+
+    // 等待队列处理完
+    let mut stats = json!(null);
+    for _ in 0..100 {
+        let (_, body) = server.get("/api/maintenance/thumbnails").await;
+        stats = body["data"].clone();
+        if stats["pending_backfill"] == 0 && stats["running"] == 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(stats["failed"], 1, "{}", stats);
+    assert_eq!(stats["pending_on_demand"], 0);
+
+    // 请求时生成失败返回 500，视频文件不存在返回 404
+    let status = server.client.get(server.url(&format!("/api/media/{}/thumbnail", media_ids[0])))
+        .send().await.unwrap().status();
+    assert_eq!(status, 500);
+    let status = server.client.get(server.url(&format!("/api/media/{}/thumbnail", media_ids[1])))
+        .send().await.unwrap().status();
+    assert_eq!(status, 404);
+
+    let (_, body) = server.get("/api/maintenance/thumbnails").await;
+    assert_eq!(body["data"]["failed"], 2, "{}", body);
+}
@@ -0,0 +1,24 @@
+// TMDB 发现接口集成测试（测试环境未配置 TMDB，只检查参数校验）
+
+mod common;
+
+use common::TestServer;
+
+#[tokio::test]
+async fn test_tmdb_discover_validation() {
+    let server = TestServer::start().await;
+
+    for (query, status) in [
+        ("media_type=anime", 400),
+        ("genres=28,action", 422),
+        ("year_from=2010&year_to=2000", 422),
+        ("vote_average_gte=11", 422),
+        ("sort_by=title.desc", 422),
+        ("sort_by=popularity", 422),
+        // 条件有效，但测试环境未配置 TMDB
+        ("media_type=tv&genres=16&year_from=2000&sort_by=release_date.desc", 502),
+    ] {
+        let (actual, body) = server.get(&format!("/api/tmdb/discover?{}", query)).await;
+        assert_eq!(actual, status, "{}: {}", query, body);
+    }
+}
@@ -0,0 +1,71 @@
+// TMDB 热门/趋势列表本地缓存集成测试（测试环境未配置 TMDB，只能使用本地缓存）
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_tmdb_lists_served_from_local_cache() {
+    let server = TestServer::start().await;
+
+    let (_, body) = server.get("/api/settings/tmdb-list-cache").await;
+    assert_eq!(body["data"]["enabled"], false);
+    assert_eq!(body["data"]["pages"], 3);
+    for invalid in [
+        json!({ "lists": ["top_rated"] }),
+        json!({ "media_types": ["anime"] }),
+        json!({ "pages": 0 }),
+        json!({ "refresh_hour": 24 }),
+    ] {
+        let (status, body) = server.put("/api/settings/tmdb-list-cache", invalid).await;
+        assert_eq!(status, 422, "{}", body);
+    }
+    let (status, body) = server.put("/api/settings/tmdb-list-cache", json!({
+        "enabled": true,
+        "media_types": ["movie"],
+        "max_age_hours": 12,
+    })).await;
+    assert_eq!(status, 200, "{}", body);
+
+    // 没有缓存且 TMDB 不可用
+    let (status, _) = server.get("/api/tmdb/popular?media_type=movie").await;
+    assert_eq!(status, 502);
+    let (status, _) = server.post("/api/tmdb/lists/prefetch", json!({})).await;
+    assert_eq!(status, 502);
+    let (status, _) = server.get("/api/tmdb/trending?media_type=anime").await;
+    assert_eq!(status, 400);
+
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}", server.dir().join("test.db").display())).await.unwrap();
+    for (list, page, fetched_at) in [
+        ("popular", 1, "2020-01-01T00:00:00Z".to_string()),
+        ("trending", 1, chrono::Utc::now().to_rfc3339()),
+    ] {
+        sqlx::query("INSERT INTO tmdb_list_cache (list, media_type, page, results, fetched_at) VALUES (?, 'movie', ?, ?, ?)")
+            .bind(list)
+            .bind(page)
+            .bind(json!([{ "title": format!("Cached {}", list) }]).to_string())
+            .bind(&fetched_at)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+    pool.close().await;
+
+    // 过期的缓存在 TMDB 无法访问时仍然返回
+    let (status, body) = server.get("/api/tmdb/popular").await;
+    assert_eq!(status, 200, "{}", body);
+    let data = &body["data"];
+    assert_eq!(data["source"], "cache");
+    assert_eq!(data["stale"], true);
+    assert_eq!(data["total"], 1);
+    assert_eq!(data["results"][0]["title"], "Cached popular");
+    assert!(data["fetched_at"].as_str().unwrap().starts_with("2020-01-01"));
+
+    let (_, body) = server.get("/api/tmdb/trending?media_type=movie&page=1").await;
+    assert_eq!(body["data"]["list"], "trending");
+    assert_eq!(body["data"]["stale"], false);
+
+    let (status, _) = server.get("/api/tmdb/trending?media_type=movie&page=2").await;
+    assert_eq!(status, 502);
+}
@@ -0,0 +1,24 @@
+// 想要列表接口集成测试
+
+mod common;
+
+use common::TestServer;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_update_wanted() {
+    let server = TestServer::start().await;
+    let media_id = server.create_media("Wanted Movie", Some("WNT-001")).await;
+
+    let (status, _) = server.put(&format!("/api/wanted/{}", media_id), json!({ "status": "paused" })).await;
+    assert_eq!(status, 404);
+
+    let (status, body) = server.post("/api/wanted", json!({ "media_id": media_id })).await;
+    assert_eq!(status, 200, "{}", body);
+    let (status, body) = server.put(&format!("/api/wanted/{}", media_id), json!({ "status": "paused" })).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["status"], "paused");
+    let (status, body) = server.put(&format!("/api/wanted/{}", media_id), json!({})).await;
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["data"]["status"], "paused");
+}